@@ -1,11 +1,12 @@
 use anchor_lang::{
     error::ErrorCode as AnchorErrorCode, AccountDeserialize as AnchorAccountDeserialize,
-    Discriminator as AnchorDiscriminator, Owner as AnchorOwner,
+    AccountSerialize as AnchorAccountSerialize, Discriminator as AnchorDiscriminator,
+    Owner as AnchorOwner,
 };
-use derive_more::Deref;
+use derive_more::{Deref, DerefMut};
 use micro_anchor::{AccountDeserialize, AccountDeserializeError, Discriminator, Owner};
 
-#[derive(Deref)]
+#[derive(Clone, Deref, DerefMut)]
 pub struct Wrapper<T>(T);
 
 impl<T: Discriminator> AnchorDiscriminator for Wrapper<T> {
@@ -42,3 +43,6 @@ impl<T: AccountDeserialize> AnchorAccountDeserialize for Wrapper<T> {
             .map_err(|e| AnchorErrorCode::from(Wrapper(e)).into())
     }
 }
+
+/// Read only: the wrapped accounts are owned by other programs, so nothing is written back on exit
+impl<T> AnchorAccountSerialize for Wrapper<T> {}
@@ -0,0 +1,401 @@
+//! C ABI over the quotes and instruction builders of marinade-sdk, for wallets linking it as a
+//! static or dynamic library from Swift, Kotlin (JNI) or C. Kept out of marinade-sdk so the
+//! Rust users of the SDK do not build the cdylib.
+//!
+//! - The state is passed as the raw state account data (discriminator included) with its
+//!   length, pubkeys as pointers to 32 bytes.
+//! - Every function returns a MarinadeStatus and writes its results through out pointers,
+//!   which are left untouched unless the status is Ok (out_len excepted, see below).
+//! - Instructions are serialized into a caller provided buffer as
+//!   program id (32) | account count (u32 LE) | per account: pubkey (32), is_signer (u8),
+//!   is_writable (u8) | data length (u32 LE) | data, i.e. the Borsh encoding of
+//!   (Pubkey, Vec<(Pubkey, bool, bool)>, Vec<u8>). The required length is always written to
+//!   out_len, so a BufferTooSmall call can be repeated with a large enough buffer.
+//! - A panic inside the SDK is caught and returned as MarinadeStatus::Panic, so the library
+//!   must be built with the default panic = "unwind".
+
+use std::{
+    panic::{self, AssertUnwindSafe},
+    slice,
+};
+
+use borsh::BorshSerialize;
+use micro_anchor::AccountDeserialize;
+use solana_program::{instruction::Instruction, pubkey::Pubkey};
+
+use marinade_sdk::{
+    error::CommonError,
+    instructions::{
+        deposit::DepositData, liquid_unstake::LiquidUnstakeData, order_unstake::OrderUnstakeData,
+        SerializedInstruction,
+    },
+    located::KeyedAccount,
+    quote::{liquid_unstake_quote, order_unstake_quote, quote_deposit, LiqPoolLegs},
+    state::marinade::{Marinade, MarinadeExtension, MarinadeHelpers},
+};
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MarinadeStatus {
+    Ok = 0,
+    NullPointer = 1,
+    /// The state data does not decode as a Marinade state account
+    InvalidState = 2,
+    /// Overflow, or more than the state allows (e.g. over the staking cap)
+    CalculationFailure = 3,
+    /// Stored bump seeds of the state do not derive its PDAs
+    InvalidBumpSeed = 4,
+    BufferTooSmall = 5,
+    /// The liq pool SOL leg can not pay the liquid unstake
+    InsufficientLiquidity = 6,
+    /// The SDK panicked, a bug to report. The outs may be partly written
+    Panic = 7,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct MarinadeDepositQuote {
+    pub msol_from_liq_pool: u64,
+    pub lamports_to_liq_pool: u64,
+    pub msol_minted: u64,
+    pub lamports_to_reserve: u64,
+}
+
+macro_rules! ffi_try {
+    ($result:expr) => {
+        match $result {
+            Ok(value) => value,
+            Err(status) => return status,
+        }
+    };
+}
+
+/// Runs an extern function body, a panic is reported as MarinadeStatus::Panic instead of
+/// unwinding across the C ABI (undefined behaviour). Needs the default panic = "unwind"
+fn guarded<F: FnOnce() -> MarinadeStatus>(body: F) -> MarinadeStatus {
+    // nothing touched by a panicking body is used afterwards
+    panic::catch_unwind(AssertUnwindSafe(body)).unwrap_or(MarinadeStatus::Panic)
+}
+
+unsafe fn account_data<'a>(data: *const u8, len: usize) -> Result<&'a [u8], MarinadeStatus> {
+    if data.is_null() {
+        return Err(MarinadeStatus::NullPointer);
+    }
+    Ok(slice::from_raw_parts(data, len))
+}
+
+unsafe fn marinade(data: *const u8, len: usize) -> Result<Marinade, MarinadeStatus> {
+    Marinade::try_deserialize(&mut account_data(data, len)?)
+        .map_err(|_| MarinadeStatus::InvalidState)
+}
+
+unsafe fn pubkey(pubkey: *const u8) -> Result<Pubkey, MarinadeStatus> {
+    if pubkey.is_null() {
+        return Err(MarinadeStatus::NullPointer);
+    }
+    let mut bytes = [0u8; 32];
+    bytes.copy_from_slice(slice::from_raw_parts(pubkey, 32));
+    Ok(Pubkey::new_from_array(bytes))
+}
+
+unsafe fn write<T>(out: *mut T, value: T) -> Result<(), MarinadeStatus> {
+    if out.is_null() {
+        return Err(MarinadeStatus::NullPointer);
+    }
+    *out = value;
+    Ok(())
+}
+
+unsafe fn write_instruction(
+    instruction: Instruction,
+    out: *mut u8,
+    out_capacity: usize,
+    out_len: *mut usize,
+) -> MarinadeStatus {
+    let bytes = SerializedInstruction::from(&instruction)
+        .try_to_vec()
+        .unwrap();
+    ffi_try!(write(out_len, bytes.len()));
+    if bytes.len() > out_capacity {
+        return MarinadeStatus::BufferTooSmall;
+    }
+    if out.is_null() {
+        return MarinadeStatus::NullPointer;
+    }
+    slice::from_raw_parts_mut(out, bytes.len()).copy_from_slice(&bytes);
+    MarinadeStatus::Ok
+}
+
+/// mSOL minted for lamports at the state price
+///
+/// # Safety
+/// state_data points to state_len readable bytes, msol_out to a writable u64
+#[no_mangle]
+pub unsafe extern "C" fn marinade_calc_msol_from_lamports(
+    state_data: *const u8,
+    state_len: usize,
+    lamports: u64,
+    msol_out: *mut u64,
+) -> MarinadeStatus {
+    guarded(|| {
+        let marinade = ffi_try!(marinade(state_data, state_len));
+        let msol = ffi_try!(marinade
+            .calc_msol_from_lamports(lamports)
+            .map_err(|_| MarinadeStatus::CalculationFailure));
+        ffi_try!(write(msol_out, msol));
+        MarinadeStatus::Ok
+    })
+}
+
+/// Lamports worth msol_amount at the state price
+///
+/// # Safety
+/// state_data points to state_len readable bytes, lamports_out to a writable u64
+#[no_mangle]
+pub unsafe extern "C" fn marinade_calc_lamports_from_msol(
+    state_data: *const u8,
+    state_len: usize,
+    msol_amount: u64,
+    lamports_out: *mut u64,
+) -> MarinadeStatus {
+    guarded(|| {
+        let marinade = ffi_try!(marinade(state_data, state_len));
+        let lamports = ffi_try!(marinade
+            .calc_lamports_from_msol_amount(msol_amount)
+            .map_err(|_| MarinadeStatus::CalculationFailure));
+        ffi_try!(write(lamports_out, lamports));
+        MarinadeStatus::Ok
+    })
+}
+
+/// See quote::quote_deposit, msol_leg_balance is the liq pool mSOL leg token amount
+///
+/// # Safety
+/// state_data points to state_len readable bytes, quote_out to a writable MarinadeDepositQuote
+#[no_mangle]
+pub unsafe extern "C" fn marinade_quote_deposit(
+    state_data: *const u8,
+    state_len: usize,
+    msol_leg_balance: u64,
+    lamports: u64,
+    quote_out: *mut MarinadeDepositQuote,
+) -> MarinadeStatus {
+    guarded(|| {
+        let marinade = ffi_try!(marinade(state_data, state_len));
+        let quote = ffi_try!(quote_deposit(
+            &marinade,
+            &LiqPoolLegs {
+                msol_leg_balance,
+                ..Default::default()
+            },
+            lamports
+        )
+        .map_err(|_| MarinadeStatus::CalculationFailure));
+        ffi_try!(write(
+            quote_out,
+            MarinadeDepositQuote {
+                msol_from_liq_pool: quote.msol_from_liq_pool,
+                lamports_to_liq_pool: quote.lamports_to_liq_pool,
+                msol_minted: quote.msol_minted,
+                lamports_to_reserve: quote.lamports_to_reserve,
+            }
+        ));
+        MarinadeStatus::Ok
+    })
+}
+
+/// Ticket lamports and mSOL fee of order_unstake, with the delayed unstake fee of the state
+///
+/// # Safety
+/// state_data points to state_len readable bytes, the outs to writable u64s
+#[no_mangle]
+pub unsafe extern "C" fn marinade_quote_order_unstake(
+    state_data: *const u8,
+    state_len: usize,
+    msol_amount: u64,
+    lamports_out: *mut u64,
+    msol_fee_out: *mut u64,
+) -> MarinadeStatus {
+    guarded(|| {
+        let marinade = ffi_try!(marinade(state_data, state_len));
+        let extension =
+            MarinadeExtension::from_account_data(ffi_try!(account_data(state_data, state_len)));
+        let quote =
+            ffi_try!(
+                order_unstake_quote(&marinade, extension.delayed_unstake_fee, msol_amount)
+                    .map_err(|_| MarinadeStatus::CalculationFailure)
+            );
+        if lamports_out.is_null() || msol_fee_out.is_null() {
+            return MarinadeStatus::NullPointer;
+        }
+        *lamports_out = quote.lamports;
+        *msol_fee_out = quote.msol_fee;
+        MarinadeStatus::Ok
+    })
+}
+
+/// Lamports received and mSOL fee of liquid_unstake. max_lamports is the liq pool SOL leg
+/// balance minus its rent exempt reserve
+///
+/// # Safety
+/// state_data points to state_len readable bytes, the outs to writable u64s
+#[no_mangle]
+pub unsafe extern "C" fn marinade_quote_liquid_unstake(
+    state_data: *const u8,
+    state_len: usize,
+    msol_amount: u64,
+    max_lamports: u64,
+    lamports_out: *mut u64,
+    msol_fee_out: *mut u64,
+) -> MarinadeStatus {
+    guarded(|| {
+        let marinade = ffi_try!(marinade(state_data, state_len));
+        let quote = ffi_try!(
+            liquid_unstake_quote(&marinade, max_lamports, msol_amount).map_err(
+                |error| match error {
+                    CommonError::InsufficientLiquidity => MarinadeStatus::InsufficientLiquidity,
+                    _ => MarinadeStatus::CalculationFailure,
+                }
+            )
+        );
+        if lamports_out.is_null() || msol_fee_out.is_null() {
+            return MarinadeStatus::NullPointer;
+        }
+        *lamports_out = quote.lamports;
+        *msol_fee_out = quote.msol_fee;
+        MarinadeStatus::Ok
+    })
+}
+
+unsafe fn keyed_state(
+    state_address: *const u8,
+    state_data: *const u8,
+    state_len: usize,
+) -> Result<KeyedAccount<Marinade>, MarinadeStatus> {
+    Ok(KeyedAccount::new(
+        pubkey(state_address)?,
+        marinade(state_data, state_len)?,
+    ))
+}
+
+/// Serialized deposit instruction, see the module docs for the format
+///
+/// # Safety
+/// Pubkeys point to 32 readable bytes, state_data to state_len readable bytes, out to
+/// out_capacity writable bytes and out_len to a writable usize
+#[no_mangle]
+#[allow(clippy::too_many_arguments)]
+pub unsafe extern "C" fn marinade_deposit_instruction(
+    state_address: *const u8,
+    state_data: *const u8,
+    state_len: usize,
+    lamports: u64,
+    transfer_from: *const u8,
+    mint_to: *const u8,
+    out: *mut u8,
+    out_capacity: usize,
+    out_len: *mut usize,
+) -> MarinadeStatus {
+    guarded(|| {
+        let state = ffi_try!(keyed_state(state_address, state_data, state_len));
+        let instruction = ffi_try!(state
+            .deposit(
+                DepositData::new(lamports),
+                ffi_try!(pubkey(transfer_from)),
+                ffi_try!(pubkey(mint_to)),
+            )
+            .map_err(|_| MarinadeStatus::InvalidBumpSeed));
+        write_instruction(instruction, out, out_capacity, out_len)
+    })
+}
+
+/// Serialized liquid_unstake instruction, see the module docs for the format
+///
+/// # Safety
+/// As marinade_deposit_instruction
+#[no_mangle]
+#[allow(clippy::too_many_arguments)]
+pub unsafe extern "C" fn marinade_liquid_unstake_instruction(
+    state_address: *const u8,
+    state_data: *const u8,
+    state_len: usize,
+    msol_amount: u64,
+    get_msol_from: *const u8,
+    get_msol_from_authority: *const u8,
+    transfer_sol_to: *const u8,
+    out: *mut u8,
+    out_capacity: usize,
+    out_len: *mut usize,
+) -> MarinadeStatus {
+    guarded(|| {
+        let state = ffi_try!(keyed_state(state_address, state_data, state_len));
+        let instruction = ffi_try!(state
+            .liquid_unstake(
+                LiquidUnstakeData::new(msol_amount),
+                ffi_try!(pubkey(get_msol_from)),
+                ffi_try!(pubkey(get_msol_from_authority)),
+                ffi_try!(pubkey(transfer_sol_to)),
+            )
+            .map_err(|_| MarinadeStatus::InvalidBumpSeed));
+        write_instruction(instruction, out, out_capacity, out_len)
+    })
+}
+
+/// Serialized order_unstake instruction, see the module docs for the format. The new ticket
+/// account has to be created beforehand (or in the same transaction)
+///
+/// # Safety
+/// As marinade_deposit_instruction
+#[no_mangle]
+#[allow(clippy::too_many_arguments)]
+pub unsafe extern "C" fn marinade_order_unstake_instruction(
+    state_address: *const u8,
+    state_data: *const u8,
+    state_len: usize,
+    msol_amount: u64,
+    burn_msol_from: *const u8,
+    burn_msol_authority: *const u8,
+    new_ticket_account: *const u8,
+    out: *mut u8,
+    out_capacity: usize,
+    out_len: *mut usize,
+) -> MarinadeStatus {
+    guarded(|| {
+        let state = ffi_try!(keyed_state(state_address, state_data, state_len));
+        let instruction = state.order_unstake(
+            OrderUnstakeData::new(msol_amount),
+            ffi_try!(pubkey(burn_msol_from)),
+            ffi_try!(pubkey(burn_msol_authority)),
+            ffi_try!(pubkey(new_ticket_account)),
+        );
+        write_instruction(instruction, out, out_capacity, out_len)
+    })
+}
+
+/// Serialized claim instruction, see the module docs for the format
+///
+/// # Safety
+/// As marinade_deposit_instruction
+#[no_mangle]
+#[allow(clippy::too_many_arguments)]
+pub unsafe extern "C" fn marinade_claim_instruction(
+    state_address: *const u8,
+    state_data: *const u8,
+    state_len: usize,
+    ticket_account: *const u8,
+    transfer_sol_to: *const u8,
+    out: *mut u8,
+    out_capacity: usize,
+    out_len: *mut usize,
+) -> MarinadeStatus {
+    guarded(|| {
+        let state = ffi_try!(keyed_state(state_address, state_data, state_len));
+        let instruction = ffi_try!(state
+            .claim(
+                ffi_try!(pubkey(ticket_account)),
+                ffi_try!(pubkey(transfer_sol_to)),
+            )
+            .map_err(|_| MarinadeStatus::InvalidBumpSeed));
+        write_instruction(instruction, out, out_capacity, out_len)
+    })
+}
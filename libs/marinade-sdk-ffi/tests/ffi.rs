@@ -0,0 +1,97 @@
+#[cfg(test)]
+mod tests {
+    use borsh::{BorshDeserialize, BorshSerialize};
+    use marinade_sdk::{
+        instructions::deposit::DepositData,
+        state::marinade::{Marinade, MarinadeHelpers},
+        testing::test_state,
+    };
+    use marinade_sdk_ffi::{
+        marinade_calc_msol_from_lamports, marinade_deposit_instruction,
+        marinade_quote_liquid_unstake, marinade_quote_order_unstake, MarinadeStatus,
+    };
+    use micro_anchor::Discriminator;
+    use solana_program::pubkey::Pubkey;
+
+    fn state_data(state: &Marinade) -> Vec<u8> {
+        let mut data = Marinade::DISCRIMINATOR.to_vec();
+        data.extend(state.try_to_vec().unwrap());
+        data
+    }
+
+    #[test]
+    fn test_quotes_read_the_state_data() {
+        let data = state_data(&test_state().state);
+        let mut msol = 0;
+        let status = unsafe {
+            marinade_calc_msol_from_lamports(data.as_ptr(), data.len(), 1_000, &mut msol)
+        };
+        assert_eq!(status, MarinadeStatus::Ok);
+        assert_eq!(msol, 1_000);
+
+        let (mut lamports, mut msol_fee) = (0, 0);
+        let status = unsafe {
+            marinade_quote_order_unstake(
+                data.as_ptr(),
+                data.len(),
+                1_000,
+                &mut lamports,
+                &mut msol_fee,
+            )
+        };
+        assert_eq!(status, MarinadeStatus::Ok);
+        assert_eq!((lamports, msol_fee), (1_000, 0));
+
+        // the SOL leg can not pay out 1_000 mSOL less the fee
+        let status = unsafe {
+            marinade_quote_liquid_unstake(
+                data.as_ptr(),
+                data.len(),
+                1_000,
+                10,
+                &mut lamports,
+                &mut msol_fee,
+            )
+        };
+        assert_eq!(status, MarinadeStatus::InsufficientLiquidity);
+
+        let status =
+            unsafe { marinade_calc_msol_from_lamports(data.as_ptr(), 8, 1_000, &mut msol) };
+        assert_eq!(status, MarinadeStatus::InvalidState);
+    }
+
+    #[test]
+    fn test_instructions_are_serialized_into_the_buffer() {
+        let test = test_state();
+        let data = state_data(&test.state);
+        let (transfer_from, mint_to) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let expected = test
+            .deposit(DepositData::new(42), transfer_from, mint_to)
+            .unwrap();
+        let build = |out: &mut [u8], out_len: &mut usize| unsafe {
+            marinade_deposit_instruction(
+                test.key.as_ref().as_ptr(),
+                data.as_ptr(),
+                data.len(),
+                42,
+                transfer_from.as_ref().as_ptr(),
+                mint_to.as_ref().as_ptr(),
+                out.as_mut_ptr(),
+                out.len(),
+                out_len,
+            )
+        };
+
+        let mut out_len = 0;
+        assert_eq!(build(&mut [], &mut out_len), MarinadeStatus::BufferTooSmall);
+        let mut out = vec![0; out_len];
+        assert_eq!(build(&mut out, &mut out_len), MarinadeStatus::Ok);
+
+        let (program_id, accounts, ix_data) =
+            <(Pubkey, Vec<(Pubkey, bool, bool)>, Vec<u8>)>::try_from_slice(&out).unwrap();
+        assert_eq!(program_id, expected.program_id);
+        assert_eq!(ix_data, expected.data);
+        assert_eq!(accounts.len(), expected.accounts.len());
+        assert_eq!(accounts[0].0, expected.accounts[0].pubkey);
+    }
+}
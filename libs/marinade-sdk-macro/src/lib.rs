@@ -148,44 +148,23 @@ fn get_related_struct_idents(
     (data_struct_name, infos_struct_name)
 }
 
-fn emit_struct_fields_non_pubkey(
-    struct_fields: &Vec<(Ident, AccountsFieldData)>,
-    base_pattern: &str,
-    f: fn(&AccountsFieldData) -> String,
+/// Emits one statement per field in declaration order, so nested account structs
+/// keep their position in the account list (as anchor does).
+fn emit_struct_fields_in_order(
+    struct_fields: &[(Ident, AccountsFieldData)],
+    pubkey_statement: fn(&AccountsFieldData) -> String,
+    nested_pattern: &str,
 ) -> Vec<TokenStream2> {
     struct_fields
         .iter()
-        .filter_map(|(_, props)| {
-            let adjusted_pattern = base_pattern.replace("{}", f(props).as_str());
-            let parsed_pattern: proc_macro2::TokenStream = adjusted_pattern.parse().unwrap();
-            if !props.type_is_pubkey {
-                Some(quote!(#parsed_pattern))
+        .map(|(_, props)| {
+            let statement = if props.type_is_pubkey {
+                pubkey_statement(props)
             } else {
-                None
-            }
-        })
-        .collect::<Vec<_>>()
-}
-
-fn emit_struct_fields_at_pattern(
-    struct_fields: &Vec<(Ident, AccountsFieldData)>,
-    base_pattern: &str,
-    f: fn(&AccountsFieldData) -> &str,
-) -> Vec<TokenStream2> {
-    struct_fields
-        .iter()
-        .filter_map(|(_, props)| {
-            let self_name = base_pattern.replace("{}", f(props));
-            let account_meta_def: proc_macro2::TokenStream = props
-                .account_meta_formatter
-                .replace("{}", self_name.as_str())
-                .parse()
-                .unwrap();
-            if props.type_is_pubkey {
-                Some(quote!(#account_meta_def))
-            } else {
-                None
-            }
+                nested_pattern.replace("{}", props.name.as_str())
+            };
+            let parsed_statement: proc_macro2::TokenStream = statement.parse().unwrap();
+            quote!(#parsed_statement)
         })
         .collect::<Vec<_>>()
 }
@@ -270,6 +249,26 @@ fn emit_struct_fields_at_pattern(
 ///         solana_program::bpf_loader::ID
 ///     }
 /// }
+/// impl micro_anchor::FromAccountKeys for TestAccounts {
+///     fn from_account_keys(
+///         keys: &mut std::slice::Iter<'_, solana_program::pubkey::Pubkey>,
+///     ) -> Option<Self> {
+///         Some(Self {
+///             marinade: *keys.next()?,
+///         })
+///     }
+/// }
+/// ```
+///
+/// With the `debug-print` feature of this crate, which needs `micro-anchor/debug-print`,
+/// it also generates
+///
+/// ```ignore
+/// impl micro_anchor::AccountNames for TestAccounts {
+///     fn account_names() -> Vec<String> {
+///         vec!["marinade".to_string()]
+///     }
+/// }
 /// ```
 #[proc_macro_derive(InstructionAccounts, attributes(account, accounts))]
 pub fn derive_instruction_accounts(input: TokenStream) -> TokenStream {
@@ -395,37 +394,84 @@ pub fn derive_instruction_accounts(input: TokenStream) -> TokenStream {
             }
         })
         .collect::<Vec<_>>();
-    let to_infos_fields_cloning_inner = struct_fields
-        .iter()
-        .filter_map(|(field, props)| {
-            if props.type_is_pubkey {
-                Some(quote!(self.#field.clone()))
-            } else {
-                None
-            }
-        })
-        .collect::<Vec<_>>();
-    let to_account_metas_inner_fields = emit_struct_fields_at_pattern(
+    let to_account_metas_fields = emit_struct_fields_in_order(
         &struct_fields,
-        "self.{}",
-        |props: &AccountsFieldData| -> &str { props.name.as_str() },
+        |props: &AccountsFieldData| -> String {
+            let self_name = format!("self.{}", props.name);
+            format!(
+                "output.push({});",
+                props
+                    .account_meta_formatter
+                    .replace("{}", self_name.as_str())
+            )
+        },
+        "output.extend(self.{}.to_account_metas());",
     );
-    let to_account_metas_inner_cloning = emit_struct_fields_at_pattern(
+    let to_account_metas_fields_cloning = emit_struct_fields_in_order(
         &struct_fields,
-        "self.{}.key.clone()",
-        |props: &AccountsFieldData| -> &str { props.name.as_str() },
+        |props: &AccountsFieldData| -> String {
+            let self_name = format!("self.{}.key.clone()", props.name);
+            format!(
+                "output.push({});",
+                props
+                    .account_meta_formatter
+                    .replace("{}", self_name.as_str())
+            )
+        },
+        "output.extend(self.{}.to_account_metas());",
     );
-    let to_account_metas_nested_iter_fields = emit_struct_fields_non_pubkey(
+    let to_account_infos_fields_cloning = emit_struct_fields_in_order(
         &struct_fields,
-        "self.{}.to_account_metas().into_iter().for_each(|i| output.push(i));",
-        |props: &AccountsFieldData| -> String { props.name.clone() },
-    );
-    let to_account_infos_nested_iter_fields = emit_struct_fields_non_pubkey(
-        &struct_fields,
-        "self.{}.to_account_infos().into_iter().for_each(|i| output.push(i));",
-        |props: &AccountsFieldData| -> String { props.name.clone() },
+        |props: &AccountsFieldData| -> String {
+            format!("output.push(self.{}.clone());", props.name)
+        },
+        "output.extend(self.{}.to_account_infos());",
     );
 
+    let from_account_keys_fields = struct_fields
+        .iter()
+        .map(|(field, props)| {
+            if props.type_is_pubkey {
+                quote!(#field: *keys.next()?)
+            } else {
+                let type_name = &props.type_name;
+                quote!(#field: <#type_name as micro_anchor::FromAccountKeys>::from_account_keys(keys)?)
+            }
+        })
+        .collect::<Vec<_>>();
+
+    let account_names_fields = struct_fields
+        .iter()
+        .map(|(_, props)| {
+            let name = props.name.as_str();
+            if props.type_is_pubkey {
+                quote!(output.push(#name.to_string());)
+            } else {
+                let type_name = &props.type_name;
+                quote!(output.extend(
+                    <#type_name as micro_anchor::AccountNames>::account_names()
+                        .into_iter()
+                        .map(|nested| format!("{}.{}", #name, nested))
+                );)
+            }
+        })
+        .collect::<Vec<_>>();
+
+    // decided here rather than by a cfg in the output, which the using crate would have to declare
+    let account_names_impl = if cfg!(feature = "debug-print") {
+        quote! {
+            impl micro_anchor::AccountNames for #struct_name {
+                fn account_names() -> Vec<String> {
+                    let mut output: Vec<String> = Vec::new();
+                    #(#account_names_fields)*
+                    output
+                }
+            }
+        }
+    } else {
+        quote! {}
+    };
+
     let token_stream_output = quote! {
         pub struct #infos_struct_name<'info> {
             #(#fields_declaration),*
@@ -441,47 +487,25 @@ pub fn derive_instruction_accounts(input: TokenStream) -> TokenStream {
                 }
             }
         }
-        impl #struct_name {
-            fn to_account_metas_inner(&self) -> Vec<solana_program::instruction::AccountMeta> {
-                vec![
-                    #(#to_account_metas_inner_fields),*
-                ]
-            }
-        }
         impl micro_anchor::ToAccountMetas for #struct_name {
             fn to_account_metas(&self) -> Vec<solana_program::instruction::AccountMeta> {
                 let mut output: Vec<solana_program::instruction::AccountMeta> = Vec::new();
-                self.to_account_metas_inner().into_iter().for_each(|i| output.push(i));
-                #(#to_account_metas_nested_iter_fields);*
+                #(#to_account_metas_fields)*
                 output
             }
             type Data = #data_struct_name;
         }
-        impl<'info> #infos_struct_name<'info> {
-             fn to_account_infos_inner(&self) -> Vec<solana_program::account_info::AccountInfo<'info>> {
-                vec![
-                    #(#to_infos_fields_cloning_inner),*
-                ]
-            }
-            fn to_account_metas_inner(&self) -> Vec<solana_program::instruction::AccountMeta> {
-                vec![
-                    #(#to_account_metas_inner_cloning),*
-                ]
-            }
-        }
         impl<'info> micro_anchor::ToAccountInfos<'info> for #infos_struct_name<'info> {
             fn to_account_infos(&self) -> Vec<solana_program::account_info::AccountInfo<'info>> {
                 let mut output: Vec<solana_program::account_info::AccountInfo<'info>> = Vec::new();
-                self.to_account_infos_inner().into_iter().for_each(|i| output.push(i));
-                #(#to_account_infos_nested_iter_fields);*
+                #(#to_account_infos_fields_cloning)*
                 output
             }
         }
         impl<'info> micro_anchor::ToAccountMetas for #infos_struct_name<'info> {
             fn to_account_metas(&self) -> Vec<solana_program::instruction::AccountMeta> {
                 let mut output: Vec<solana_program::instruction::AccountMeta> = Vec::new();
-                self.to_account_metas_inner().into_iter().for_each(|i| output.push(i));
-                #(#to_account_metas_nested_iter_fields);*
+                #(#to_account_metas_fields_cloning)*
                 output
             }
             type Data = #data_struct_name;
@@ -496,6 +520,17 @@ pub fn derive_instruction_accounts(input: TokenStream) -> TokenStream {
                 #owner_id
             }
         }
+        impl micro_anchor::FromAccountKeys for #struct_name {
+            fn from_account_keys(
+                keys: &mut std::slice::Iter<'_, solana_program::pubkey::Pubkey>,
+            ) -> Option<Self> {
+                // struct fields are evaluated in declaration order, the order of to_account_metas
+                Some(Self {
+                    #(#from_account_keys_fields),*
+                })
+            }
+        }
+        #account_names_impl
     };
 
     TokenStream::from(token_stream_output)
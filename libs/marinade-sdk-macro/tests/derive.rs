@@ -128,5 +128,73 @@ mod tests {
         let account_metas: Vec<solana_program::instruction::AccountMeta> =
             test_accounts.to_account_metas();
         assert_eq!(account_metas.len(), 2);
+
+        use micro_anchor::FromAccountKeys;
+        let keys: Vec<Pubkey> = account_metas.iter().map(|meta| meta.pubkey).collect();
+        let decoded = OuterTestAccounts::from_account_keys(&mut keys.iter()).unwrap();
+        assert_eq!(decoded.outer_pk, outer_pk);
+        assert_eq!(decoded.nested_struct.nested_pk, nested_pk);
+        assert!(OuterTestAccounts::from_account_keys(&mut keys[..1].iter()).is_none());
+    }
+
+    #[test]
+    fn test_nested_accounts_keep_their_position() {
+        #[derive(InstructionData, BorshSerialize, BorshDeserialize)]
+        #[discriminator([1,2,3,4,5,6,7,8])]
+        pub struct InnerTestData {}
+        #[derive(InstructionData, BorshSerialize, BorshDeserialize)]
+        #[discriminator([1,2,3,4,5,6,7,8])]
+        pub struct MiddleTestData {}
+
+        use micro_anchor::{FromAccountKeys, ToAccountMetas};
+
+        #[derive(InstructionAccounts)]
+        #[accounts(ownerid=solana_program::bpf_loader::ID,data=InnerTestData)]
+        pub struct InnerTestAccounts {
+            #[account(mut)]
+            pub inner_first: Pubkey,
+            pub inner_second: Pubkey,
+        }
+
+        // the nested struct is declared between pubkeys, like the liq pool accounts of
+        // marinade's initialize
+        #[derive(InstructionAccounts)]
+        #[accounts(ownerid=solana_program::bpf_loader::ID,data=MiddleTestData)]
+        pub struct MiddleTestAccounts {
+            #[account(signer)]
+            pub before: Pubkey,
+            pub inner: InnerTestAccounts,
+            #[account(mut)]
+            pub after: Pubkey,
+        }
+
+        let accounts = MiddleTestAccounts {
+            before: Pubkey::new_unique(),
+            inner: InnerTestAccounts {
+                inner_first: Pubkey::new_unique(),
+                inner_second: Pubkey::new_unique(),
+            },
+            after: Pubkey::new_unique(),
+        };
+        let account_metas = accounts.to_account_metas();
+        assert_eq!(
+            account_metas
+                .iter()
+                .map(|meta| (meta.pubkey, meta.is_signer, meta.is_writable))
+                .collect::<Vec<_>>(),
+            vec![
+                (accounts.before, true, false),
+                (accounts.inner.inner_first, false, true),
+                (accounts.inner.inner_second, false, false),
+                (accounts.after, false, true),
+            ]
+        );
+
+        let keys: Vec<Pubkey> = account_metas.iter().map(|meta| meta.pubkey).collect();
+        let decoded = MiddleTestAccounts::from_account_keys(&mut keys.iter()).unwrap();
+        assert_eq!(decoded.before, accounts.before);
+        assert_eq!(decoded.inner.inner_first, accounts.inner.inner_first);
+        assert_eq!(decoded.inner.inner_second, accounts.inner.inner_second);
+        assert_eq!(decoded.after, accounts.after);
     }
 }
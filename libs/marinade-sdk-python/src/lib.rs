@@ -0,0 +1,283 @@
+//! Python bindings of marinade-sdk for account decoding, quotes and instruction building,
+//! imported as `marinade_sdk`. Build with `maturin build -m libs/marinade-sdk-python/Cargo.toml`.
+//! Instructions are returned as `(program_id, data, accounts)` tuples, the argument order of
+//! solders `Instruction`, with accounts as `(pubkey, is_signer, is_writable)`:
+//!
+//! ```python
+//! program_id, data, accounts = state.deposit(lamports, transfer_from, mint_to)
+//! ix = Instruction(Pubkey.from_string(program_id), data,
+//!                  [AccountMeta(Pubkey.from_string(k), s, w) for k, s, w in accounts])
+//! ```
+
+use std::{fmt::Display, str::FromStr};
+
+use micro_anchor::AccountDeserialize;
+use pyo3::{exceptions::PyValueError, prelude::*};
+use solana_program::{instruction::Instruction, pubkey::Pubkey};
+
+use marinade_sdk::{
+    instructions::{
+        deposit::DepositData, liquid_unstake::LiquidUnstakeData, order_unstake::OrderUnstakeData,
+    },
+    located::{KeyedAccount, Located},
+    quote::{liquid_unstake_quote, order_unstake_quote, quote_deposit, LiqPoolLegs},
+    state::{
+        delayed_unstake_ticket::DelayedUnstakeTicket,
+        marinade::{Marinade, MarinadeExtension, MarinadeHelpers},
+    },
+};
+
+type PyInstruction = (String, Vec<u8>, Vec<(String, bool, bool)>);
+
+fn to_py_err<E: Display>(error: E) -> PyErr {
+    PyValueError::new_err(error.to_string())
+}
+
+fn parse_pubkey(pubkey: &str) -> PyResult<Pubkey> {
+    Pubkey::from_str(pubkey).map_err(to_py_err)
+}
+
+fn py_instruction(instruction: Instruction) -> PyInstruction {
+    (
+        instruction.program_id.to_string(),
+        instruction.data,
+        instruction
+            .accounts
+            .iter()
+            .map(|meta| (meta.pubkey.to_string(), meta.is_signer, meta.is_writable))
+            .collect(),
+    )
+}
+
+#[pyclass(name = "MarinadeState")]
+pub struct PyMarinade {
+    inner: KeyedAccount<Marinade>,
+    extension: MarinadeExtension,
+}
+
+#[pymethods]
+impl PyMarinade {
+    #[staticmethod]
+    fn decode(address: &str, data: &[u8]) -> PyResult<Self> {
+        let state = Marinade::try_deserialize(&mut &data[..]).map_err(to_py_err)?;
+        Ok(Self {
+            inner: KeyedAccount::new(parse_pubkey(address)?, state),
+            extension: MarinadeExtension::from_account_data(data),
+        })
+    }
+
+    #[getter]
+    fn address(&self) -> String {
+        self.inner.key().to_string()
+    }
+
+    #[getter]
+    fn msol_mint(&self) -> String {
+        self.inner.as_ref().msol_mint.to_string()
+    }
+
+    /// Scaled by Marinade::PRICE_DENOMINATOR (0x1_0000_0000)
+    #[getter]
+    fn msol_price(&self) -> u64 {
+        self.inner.as_ref().msol_price
+    }
+
+    /// Hundredths of a basis point, 0 for deployments without the fee
+    #[getter]
+    fn delayed_unstake_fee_bp_cents(&self) -> u32 {
+        self.extension.delayed_unstake_fee.bp_cents
+    }
+
+    #[getter]
+    fn msol_supply(&self) -> u64 {
+        self.inner.as_ref().msol_supply
+    }
+
+    #[getter]
+    fn available_reserve_balance(&self) -> u64 {
+        self.inner.as_ref().available_reserve_balance
+    }
+
+    #[getter]
+    fn circulating_ticket_balance(&self) -> u64 {
+        self.inner.as_ref().circulating_ticket_balance
+    }
+
+    #[getter]
+    fn circulating_ticket_count(&self) -> u64 {
+        self.inner.as_ref().circulating_ticket_count
+    }
+
+    #[getter]
+    fn emergency_cooling_down(&self) -> u64 {
+        self.inner.as_ref().emergency_cooling_down
+    }
+
+    #[getter]
+    fn total_virtual_staked_lamports(&self) -> PyResult<u64> {
+        self.inner
+            .as_ref()
+            .try_total_virtual_staked_lamports()
+            .map_err(to_py_err)
+    }
+
+    #[getter]
+    fn validators_len(&self) -> u32 {
+        self.inner.as_ref().validators_len()
+    }
+
+    #[getter]
+    fn stakes_len(&self) -> u32 {
+        self.inner.as_ref().stakes_len()
+    }
+
+    /// (mSOL bought from the liq pool, lamports paid to it, mSOL minted, lamports to the
+    /// reserve) of deposit, which buys from the liq pool mSOL leg first and mints the rest
+    /// within the staking cap. `msol_leg_balance` is the mSOL leg token amount.
+    fn quote_deposit(
+        &self,
+        lamports: u64,
+        msol_leg_balance: u64,
+    ) -> PyResult<(u64, u64, u64, u64)> {
+        quote_deposit(
+            self.inner.as_ref(),
+            &LiqPoolLegs {
+                msol_leg_balance,
+                ..Default::default()
+            },
+            lamports,
+        )
+        .map(|quote| {
+            (
+                quote.msol_from_liq_pool,
+                quote.lamports_to_liq_pool,
+                quote.msol_minted,
+                quote.lamports_to_reserve,
+            )
+        })
+        .map_err(to_py_err)
+    }
+
+    /// Lamports of the ticket created by order_unstake, after the delayed unstake fee
+    fn quote_order_unstake(&self, msol_amount: u64) -> PyResult<u64> {
+        order_unstake_quote(
+            self.inner.as_ref(),
+            self.extension.delayed_unstake_fee,
+            msol_amount,
+        )
+        .map(|quote| quote.lamports)
+        .map_err(to_py_err)
+    }
+
+    /// (lamports received, fee in mSOL) of liquid_unstake.
+    /// `max_lamports` is the liq pool SOL leg balance minus its rent exempt reserve.
+    fn quote_liquid_unstake(&self, msol_amount: u64, max_lamports: u64) -> PyResult<(u64, u64)> {
+        liquid_unstake_quote(self.inner.as_ref(), max_lamports, msol_amount)
+            .map(|quote| (quote.lamports, quote.msol_fee))
+            .map_err(to_py_err)
+    }
+
+    fn deposit(
+        &self,
+        lamports: u64,
+        transfer_from: &str,
+        mint_to: &str,
+    ) -> PyResult<PyInstruction> {
+        Ok(py_instruction(
+            self.inner
+                .deposit(
+                    DepositData::new(lamports),
+                    parse_pubkey(transfer_from)?,
+                    parse_pubkey(mint_to)?,
+                )
+                .map_err(to_py_err)?,
+        ))
+    }
+
+    fn liquid_unstake(
+        &self,
+        msol_amount: u64,
+        get_msol_from: &str,
+        get_msol_from_authority: &str,
+        transfer_sol_to: &str,
+    ) -> PyResult<PyInstruction> {
+        Ok(py_instruction(
+            self.inner
+                .liquid_unstake(
+                    LiquidUnstakeData::new(msol_amount),
+                    parse_pubkey(get_msol_from)?,
+                    parse_pubkey(get_msol_from_authority)?,
+                    parse_pubkey(transfer_sol_to)?,
+                )
+                .map_err(to_py_err)?,
+        ))
+    }
+
+    fn order_unstake(
+        &self,
+        msol_amount: u64,
+        burn_msol_from: &str,
+        burn_msol_authority: &str,
+        new_ticket_account: &str,
+    ) -> PyResult<PyInstruction> {
+        Ok(py_instruction(self.inner.order_unstake(
+            OrderUnstakeData::new(msol_amount),
+            parse_pubkey(burn_msol_from)?,
+            parse_pubkey(burn_msol_authority)?,
+            parse_pubkey(new_ticket_account)?,
+        )))
+    }
+
+    fn claim(&self, ticket_account: &str, transfer_sol_to: &str) -> PyResult<PyInstruction> {
+        Ok(py_instruction(
+            self.inner
+                .claim(
+                    parse_pubkey(ticket_account)?,
+                    parse_pubkey(transfer_sol_to)?,
+                )
+                .map_err(to_py_err)?,
+        ))
+    }
+}
+
+#[pyclass(name = "DelayedUnstakeTicket")]
+pub struct PyDelayedUnstakeTicket {
+    inner: DelayedUnstakeTicket,
+}
+
+#[pymethods]
+impl PyDelayedUnstakeTicket {
+    #[staticmethod]
+    fn decode(data: &[u8]) -> PyResult<Self> {
+        let inner = DelayedUnstakeTicket::try_deserialize(&mut &data[..]).map_err(to_py_err)?;
+        Ok(Self { inner })
+    }
+
+    #[getter]
+    fn state_address(&self) -> String {
+        self.inner.state_address.to_string()
+    }
+
+    #[getter]
+    fn beneficiary(&self) -> String {
+        self.inner.beneficiary.to_string()
+    }
+
+    #[getter]
+    fn lamports_amount(&self) -> u64 {
+        self.inner.lamports_amount
+    }
+
+    #[getter]
+    fn created_epoch(&self) -> u64 {
+        self.inner.created_epoch
+    }
+}
+
+#[pymodule]
+#[pyo3(name = "marinade_sdk")]
+fn marinade_sdk_python(_py: Python, m: &PyModule) -> PyResult<()> {
+    m.add_class::<PyMarinade>()?;
+    m.add_class::<PyDelayedUnstakeTicket>()?;
+    Ok(())
+}
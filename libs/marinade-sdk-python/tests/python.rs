@@ -0,0 +1,91 @@
+//! Runs the bindings in an embedded interpreter, which needs libpython linked:
+//! `cargo test -p marinade-sdk-python --no-default-features`
+#![cfg(not(feature = "extension-module"))]
+
+#[cfg(test)]
+mod tests {
+    use borsh::BorshSerialize;
+    use marinade_sdk::{
+        instructions::deposit::DepositData,
+        state::marinade::{Marinade, MarinadeHelpers},
+        testing::{test_state, TestState},
+    };
+    use marinade_sdk_python::PyMarinade;
+    use micro_anchor::Discriminator;
+    use pyo3::{exceptions::PyValueError, prelude::*, types::PyDict};
+    use solana_program::pubkey::Pubkey;
+
+    type PyInstruction = (String, Vec<u8>, Vec<(String, bool, bool)>);
+
+    /// Runs f with MarinadeState and the address and account data of state as locals
+    fn with_state<R>(state: &TestState, f: impl FnOnce(Python, &PyDict) -> R) -> R {
+        pyo3::prepare_freethreaded_python();
+        let mut data = Marinade::DISCRIMINATOR.to_vec();
+        data.extend(state.state.try_to_vec().unwrap());
+        Python::with_gil(|py| {
+            let locals = PyDict::new(py);
+            locals
+                .set_item("MarinadeState", py.get_type::<PyMarinade>())
+                .unwrap();
+            locals.set_item("address", state.key.to_string()).unwrap();
+            locals.set_item("data", data).unwrap();
+            f(py, locals)
+        })
+    }
+
+    #[test]
+    fn test_deposit_matches_the_sdk() {
+        let state = test_state();
+        let transfer_from = Pubkey::new_unique();
+        let mint_to = Pubkey::new_unique();
+        let expected = state
+            .deposit(DepositData::new(5), transfer_from, mint_to)
+            .unwrap();
+
+        let instruction: PyInstruction = with_state(&state, |py, locals| {
+            locals.set_item("transfer_from", transfer_from.to_string())?;
+            locals.set_item("mint_to", mint_to.to_string())?;
+            py.run(
+                "state = MarinadeState.decode(address, bytes(data))\n\
+                 assert state.address == address\n\
+                 result = state.deposit(5, transfer_from, mint_to)",
+                None,
+                Some(locals),
+            )?;
+            locals.get_item("result").unwrap().extract()
+        })
+        .unwrap();
+
+        assert_eq!(
+            instruction,
+            (
+                expected.program_id.to_string(),
+                expected.data,
+                expected
+                    .accounts
+                    .iter()
+                    .map(|meta| (meta.pubkey.to_string(), meta.is_signer, meta.is_writable))
+                    .collect(),
+            )
+        );
+    }
+
+    #[test]
+    fn test_invalid_input_raises_value_error() {
+        with_state(&test_state(), |py, locals| {
+            let err = py
+                .run("MarinadeState.decode(address, b'bad')", None, Some(locals))
+                .unwrap_err();
+            assert!(err.is_instance_of::<PyValueError>(py));
+
+            let err = py
+                .run(
+                    "MarinadeState.decode(address, bytes(data)).deposit(5, 'bad', 'bad')",
+                    None,
+                    Some(locals),
+                )
+                .unwrap_err();
+            assert!(err.is_instance_of::<PyValueError>(py));
+        });
+    }
+}
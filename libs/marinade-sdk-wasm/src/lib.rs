@@ -0,0 +1,297 @@
+//! wasm-bindgen bindings of marinade-sdk for account decoding, quotes and instruction
+//! building, the same surface as marinade-sdk-python. Build with
+//! `wasm-pack build libs/marinade-sdk-wasm`; the default modules of marinade-sdk compile to
+//! wasm32-unknown-unknown, the RPC client and the other std-only parts are gated.
+//! The bindings are tested with `wasm-pack test --node libs/marinade-sdk-wasm`.
+//! Amounts are u64, i.e. BigInt on the JS side. Instructions are returned as JSON in the
+//! shape of the @solana/web3.js TransactionInstruction constructor:
+//!
+//! ```js
+//! const { programId, keys, data } = JSON.parse(state.deposit(lamports, transferFrom, mintTo));
+//! const ix = new TransactionInstruction({
+//!   programId: new PublicKey(programId),
+//!   keys: keys.map(({ pubkey, isSigner, isWritable }) =>
+//!     ({ pubkey: new PublicKey(pubkey), isSigner, isWritable })),
+//!   data: Buffer.from(data),
+//! });
+//! ```
+
+use std::{fmt::Display, str::FromStr};
+
+use micro_anchor::AccountDeserialize;
+use serde_json::json;
+use solana_program::{instruction::Instruction, pubkey::Pubkey};
+use wasm_bindgen::prelude::*;
+
+use marinade_sdk::{
+    instructions::{
+        deposit::DepositData, liquid_unstake::LiquidUnstakeData, order_unstake::OrderUnstakeData,
+    },
+    located::{KeyedAccount, Located},
+    quote::{liquid_unstake_quote, order_unstake_quote, quote_deposit, LiqPoolLegs},
+    state::{
+        delayed_unstake_ticket::DelayedUnstakeTicket,
+        marinade::{Marinade, MarinadeExtension, MarinadeHelpers},
+    },
+};
+
+fn to_js_err<E: Display>(error: E) -> JsValue {
+    JsValue::from_str(&error.to_string())
+}
+
+fn parse_pubkey(pubkey: &str) -> Result<Pubkey, JsValue> {
+    Pubkey::from_str(pubkey).map_err(to_js_err)
+}
+
+fn js_instruction(instruction: Instruction) -> String {
+    json!({
+        "programId": instruction.program_id.to_string(),
+        "keys": instruction
+            .accounts
+            .iter()
+            .map(|meta| json!({
+                "pubkey": meta.pubkey.to_string(),
+                "isSigner": meta.is_signer,
+                "isWritable": meta.is_writable,
+            }))
+            .collect::<Vec<_>>(),
+        "data": instruction.data,
+    })
+    .to_string()
+}
+
+/// How deposit splits the lamports between the liq pool mSOL leg swap and the minted mSOL
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug)]
+pub struct DepositQuote {
+    #[wasm_bindgen(js_name = msolFromLiqPool)]
+    pub msol_from_liq_pool: u64,
+    #[wasm_bindgen(js_name = lamportsToLiqPool)]
+    pub lamports_to_liq_pool: u64,
+    #[wasm_bindgen(js_name = msolMinted)]
+    pub msol_minted: u64,
+    #[wasm_bindgen(js_name = lamportsToReserve)]
+    pub lamports_to_reserve: u64,
+}
+
+#[wasm_bindgen]
+impl DepositQuote {
+    /// mSOL the user receives
+    #[wasm_bindgen(getter, js_name = msolAmount)]
+    pub fn msol_amount(&self) -> u64 {
+        self.msol_from_liq_pool + self.msol_minted
+    }
+}
+
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug)]
+pub struct LiquidUnstakeQuote {
+    pub lamports: u64,
+    pub msol_fee: u64,
+}
+
+#[wasm_bindgen(js_name = MarinadeState)]
+pub struct WasmMarinade {
+    inner: KeyedAccount<Marinade>,
+    extension: MarinadeExtension,
+}
+
+#[wasm_bindgen(js_class = MarinadeState)]
+impl WasmMarinade {
+    pub fn decode(address: &str, data: &[u8]) -> Result<WasmMarinade, JsValue> {
+        let state = Marinade::try_deserialize(&mut &data[..]).map_err(to_js_err)?;
+        Ok(Self {
+            inner: KeyedAccount::new(parse_pubkey(address)?, state),
+            extension: MarinadeExtension::from_account_data(data),
+        })
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn address(&self) -> String {
+        self.inner.key().to_string()
+    }
+
+    #[wasm_bindgen(getter, js_name = msolMint)]
+    pub fn msol_mint(&self) -> String {
+        self.inner.as_ref().msol_mint.to_string()
+    }
+
+    /// Scaled by Marinade::PRICE_DENOMINATOR (0x1_0000_0000)
+    #[wasm_bindgen(getter, js_name = msolPrice)]
+    pub fn msol_price(&self) -> u64 {
+        self.inner.as_ref().msol_price
+    }
+
+    /// Hundredths of a basis point, 0 for deployments without the fee
+    #[wasm_bindgen(getter, js_name = delayedUnstakeFeeBpCents)]
+    pub fn delayed_unstake_fee_bp_cents(&self) -> u32 {
+        self.extension.delayed_unstake_fee.bp_cents
+    }
+
+    #[wasm_bindgen(getter, js_name = msolSupply)]
+    pub fn msol_supply(&self) -> u64 {
+        self.inner.as_ref().msol_supply
+    }
+
+    #[wasm_bindgen(getter, js_name = availableReserveBalance)]
+    pub fn available_reserve_balance(&self) -> u64 {
+        self.inner.as_ref().available_reserve_balance
+    }
+
+    #[wasm_bindgen(getter, js_name = totalVirtualStakedLamports)]
+    pub fn total_virtual_staked_lamports(&self) -> Result<u64, JsValue> {
+        self.inner
+            .as_ref()
+            .try_total_virtual_staked_lamports()
+            .map_err(to_js_err)
+    }
+
+    /// Replicates deposit: mSOL is bought from the liq pool mSOL leg first, the rest is minted
+    /// within the staking cap. `msol_leg_balance` is the mSOL leg token amount.
+    #[wasm_bindgen(js_name = quoteDeposit)]
+    pub fn quote_deposit(
+        &self,
+        lamports: u64,
+        msol_leg_balance: u64,
+    ) -> Result<DepositQuote, JsValue> {
+        let quote = quote_deposit(
+            self.inner.as_ref(),
+            &LiqPoolLegs {
+                msol_leg_balance,
+                ..Default::default()
+            },
+            lamports,
+        )
+        .map_err(to_js_err)?;
+        Ok(DepositQuote {
+            msol_from_liq_pool: quote.msol_from_liq_pool,
+            lamports_to_liq_pool: quote.lamports_to_liq_pool,
+            msol_minted: quote.msol_minted,
+            lamports_to_reserve: quote.lamports_to_reserve,
+        })
+    }
+
+    /// Lamports of the ticket created by order_unstake, after the delayed unstake fee
+    #[wasm_bindgen(js_name = quoteOrderUnstake)]
+    pub fn quote_order_unstake(&self, msol_amount: u64) -> Result<u64, JsValue> {
+        order_unstake_quote(
+            self.inner.as_ref(),
+            self.extension.delayed_unstake_fee,
+            msol_amount,
+        )
+        .map(|quote| quote.lamports)
+        .map_err(to_js_err)
+    }
+
+    /// `max_lamports` is the liq pool SOL leg balance minus its rent exempt reserve.
+    #[wasm_bindgen(js_name = quoteLiquidUnstake)]
+    pub fn quote_liquid_unstake(
+        &self,
+        msol_amount: u64,
+        max_lamports: u64,
+    ) -> Result<LiquidUnstakeQuote, JsValue> {
+        let quote = liquid_unstake_quote(self.inner.as_ref(), max_lamports, msol_amount)
+            .map_err(to_js_err)?;
+        Ok(LiquidUnstakeQuote {
+            lamports: quote.lamports,
+            msol_fee: quote.msol_fee,
+        })
+    }
+
+    pub fn deposit(
+        &self,
+        lamports: u64,
+        transfer_from: &str,
+        mint_to: &str,
+    ) -> Result<String, JsValue> {
+        Ok(js_instruction(
+            self.inner
+                .deposit(
+                    DepositData::new(lamports),
+                    parse_pubkey(transfer_from)?,
+                    parse_pubkey(mint_to)?,
+                )
+                .map_err(to_js_err)?,
+        ))
+    }
+
+    #[wasm_bindgen(js_name = liquidUnstake)]
+    pub fn liquid_unstake(
+        &self,
+        msol_amount: u64,
+        get_msol_from: &str,
+        get_msol_from_authority: &str,
+        transfer_sol_to: &str,
+    ) -> Result<String, JsValue> {
+        Ok(js_instruction(
+            self.inner
+                .liquid_unstake(
+                    LiquidUnstakeData::new(msol_amount),
+                    parse_pubkey(get_msol_from)?,
+                    parse_pubkey(get_msol_from_authority)?,
+                    parse_pubkey(transfer_sol_to)?,
+                )
+                .map_err(to_js_err)?,
+        ))
+    }
+
+    #[wasm_bindgen(js_name = orderUnstake)]
+    pub fn order_unstake(
+        &self,
+        msol_amount: u64,
+        burn_msol_from: &str,
+        burn_msol_authority: &str,
+        new_ticket_account: &str,
+    ) -> Result<String, JsValue> {
+        Ok(js_instruction(self.inner.order_unstake(
+            OrderUnstakeData::new(msol_amount),
+            parse_pubkey(burn_msol_from)?,
+            parse_pubkey(burn_msol_authority)?,
+            parse_pubkey(new_ticket_account)?,
+        )))
+    }
+
+    pub fn claim(&self, ticket_account: &str, transfer_sol_to: &str) -> Result<String, JsValue> {
+        Ok(js_instruction(
+            self.inner
+                .claim(
+                    parse_pubkey(ticket_account)?,
+                    parse_pubkey(transfer_sol_to)?,
+                )
+                .map_err(to_js_err)?,
+        ))
+    }
+}
+
+#[wasm_bindgen(js_name = DelayedUnstakeTicket)]
+pub struct WasmDelayedUnstakeTicket {
+    inner: DelayedUnstakeTicket,
+}
+
+#[wasm_bindgen(js_class = DelayedUnstakeTicket)]
+impl WasmDelayedUnstakeTicket {
+    pub fn decode(data: &[u8]) -> Result<WasmDelayedUnstakeTicket, JsValue> {
+        let inner = DelayedUnstakeTicket::try_deserialize(&mut &data[..]).map_err(to_js_err)?;
+        Ok(Self { inner })
+    }
+
+    #[wasm_bindgen(getter, js_name = stateAddress)]
+    pub fn state_address(&self) -> String {
+        self.inner.state_address.to_string()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn beneficiary(&self) -> String {
+        self.inner.beneficiary.to_string()
+    }
+
+    #[wasm_bindgen(getter, js_name = lamportsAmount)]
+    pub fn lamports_amount(&self) -> u64 {
+        self.inner.lamports_amount
+    }
+
+    #[wasm_bindgen(getter, js_name = createdEpoch)]
+    pub fn created_epoch(&self) -> u64 {
+        self.inner.created_epoch
+    }
+}
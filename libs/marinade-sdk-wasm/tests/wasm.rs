@@ -0,0 +1,60 @@
+#![cfg(target_arch = "wasm32")]
+
+#[cfg(test)]
+mod tests {
+    use borsh::{BorshDeserialize, BorshSerialize};
+    use marinade_sdk::state::marinade::Marinade;
+    use marinade_sdk_wasm::WasmMarinade;
+    use micro_anchor::Discriminator;
+    use wasm_bindgen_test::wasm_bindgen_test;
+
+    /// Zeroed state priced 1:1, marinade_sdk::testing needs the rpc client which does not
+    /// build for wasm
+    fn zeroed_state() -> Marinade {
+        Marinade::try_from_slice(&vec![0u8; Marinade::serialized_len() - 8]).unwrap()
+    }
+
+    fn decode(state: &Marinade) -> WasmMarinade {
+        let mut data = Marinade::DISCRIMINATOR.to_vec();
+        data.extend(state.try_to_vec().unwrap());
+        WasmMarinade::decode(
+            &solana_program::pubkey::Pubkey::new_unique().to_string(),
+            &data,
+        )
+        .unwrap()
+    }
+
+    #[wasm_bindgen_test]
+    fn test_quote_deposit_swaps_from_the_msol_leg() {
+        let mut marinade = zeroed_state();
+        marinade.staking_sol_cap = u64::MAX;
+        let state = decode(&marinade);
+
+        // price 1, the mSOL leg covers 400 of the 1_000 lamports
+        let quote = state.quote_deposit(1_000, 400).unwrap();
+        assert_eq!(quote.msol_from_liq_pool, 400);
+        assert_eq!(quote.msol_minted, 600);
+        assert_eq!(quote.msol_amount(), 1_000);
+        assert_eq!(quote.lamports_to_reserve, 600);
+    }
+
+    #[wasm_bindgen_test]
+    fn test_quote_deposit_respects_the_staking_cap() {
+        let mut marinade = zeroed_state();
+        marinade.staking_sol_cap = 500;
+        let state = decode(&marinade);
+
+        assert!(state.quote_deposit(1_000, 0).is_err());
+        // bought from the mSOL leg only, nothing is minted
+        assert_eq!(state.quote_deposit(1_000, 1_000).unwrap().msol_minted, 0);
+    }
+
+    #[wasm_bindgen_test]
+    fn test_quote_liquid_unstake_needs_liquidity() {
+        let state = decode(&zeroed_state());
+
+        assert!(state.quote_liquid_unstake(1_000, 10).is_err());
+        let quote = state.quote_liquid_unstake(1_000, 1_000_000).unwrap();
+        assert!(quote.lamports <= 1_000);
+    }
+}
@@ -0,0 +1,102 @@
+//! Admin config changes shipped with their rollback. Before a change is applied the fields
+//! it sets are snapshotted from the current state, and the inverse config instruction setting
+//! them back is packaged the same way as the change (see propose::Proposal), so it can be
+//! stored with the proposal and executed as is when the change has to be undone.
+
+use solana_program::instruction::Instruction;
+
+use crate::{
+    instructions::{config_lp::ConfigLpData, config_marinade::ConfigMarinadeData},
+    located::Located,
+    propose::{config_changes, lp_config_changes, ConfigChange, Proposal, ProposalTarget},
+    state::marinade::{Marinade, MarinadeHelpers},
+};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ConfigUpdate {
+    Marinade(ConfigMarinadeData),
+    Lp(ConfigLpData),
+}
+
+impl ConfigUpdate {
+    /// Fields of the update that differ from the current state
+    pub fn changes(&self, state: &Marinade) -> Vec<ConfigChange> {
+        match self {
+            ConfigUpdate::Marinade(config) => config_changes(state, config),
+            ConfigUpdate::Lp(config) => lp_config_changes(state, config),
+        }
+    }
+
+    /// Update setting every field this update sets back to its current value
+    pub fn snapshot(&self, state: &Marinade) -> Self {
+        fn current<T, U>(field: Option<T>, value: U) -> Option<U> {
+            field.map(|_| value)
+        }
+
+        match self {
+            ConfigUpdate::Marinade(config) => ConfigUpdate::Marinade(ConfigMarinadeData {
+                rewards_fee: current(config.rewards_fee, state.reward_fee),
+                slots_for_stake_delta: current(
+                    config.slots_for_stake_delta,
+                    state.stake_system.slots_for_stake_delta,
+                ),
+                min_stake: current(config.min_stake, state.stake_system.min_stake),
+                min_deposit: current(config.min_deposit, state.min_deposit),
+                min_withdraw: current(config.min_withdraw, state.min_withdraw),
+                staking_sol_cap: current(config.staking_sol_cap, state.staking_sol_cap),
+                liquidity_sol_cap: current(
+                    config.liquidity_sol_cap,
+                    state.liq_pool.liquidity_sol_cap,
+                ),
+                auto_add_validator_enabled: current(
+                    config.auto_add_validator_enabled,
+                    state.validator_system.is_auto_add_validator_enabled(),
+                ),
+            }),
+            ConfigUpdate::Lp(config) => {
+                let liq_pool = &state.liq_pool;
+                ConfigUpdate::Lp(ConfigLpData {
+                    min_fee: current(config.min_fee, liq_pool.lp_min_fee),
+                    max_fee: current(config.max_fee, liq_pool.lp_max_fee),
+                    liquidity_target: current(
+                        config.liquidity_target,
+                        liq_pool.lp_liquidity_target,
+                    ),
+                    treasury_cut: current(config.treasury_cut, liq_pool.treasury_cut),
+                })
+            }
+        }
+    }
+
+    pub fn instruction<T: Located<Marinade>>(&self, state: &T) -> Instruction {
+        match *self {
+            ConfigUpdate::Marinade(config) => state.config_marinade_instruction(config),
+            ConfigUpdate::Lp(config) => state.config_lp_instruction(config),
+        }
+    }
+}
+
+/// (apply, rollback) proposals of update, both packaged for target. The rollback sets the
+/// snapshotted values back, its changes are the ones of apply reversed. It only restores
+/// the state update was computed against: admin changes landing in between are overwritten
+pub fn with_rollback<T: Located<Marinade>>(
+    state: &T,
+    update: ConfigUpdate,
+    target: ProposalTarget,
+) -> (Proposal, Proposal) {
+    let marinade = state.as_ref();
+    let changes = update.changes(marinade);
+    let reverted = changes
+        .iter()
+        .map(|change| ConfigChange {
+            field: change.field,
+            old: change.new.clone(),
+            new: change.old.clone(),
+        })
+        .collect();
+    let rollback = update.snapshot(marinade);
+    (
+        Proposal::new(changes, update.instruction(state), target),
+        Proposal::new(reverted, rollback.instruction(state), target),
+    )
+}
@@ -0,0 +1,168 @@
+//! Threshold rules evaluated against fetched state, producing typed alert events
+
+use std::fmt::Display;
+
+use crate::state::marinade::Marinade;
+
+/// Display gives a stable form, e.g. `reserve_below(1000)` or
+/// `price_deviation(reference_price=4294967296, max_bps=50)`, sent by notify to webhooks
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AlertRule {
+    /// available_reserve_balance lower than lamports
+    ReserveBelow(u64),
+    /// circulating_ticket_balance (lamports waiting to be claimed) higher than lamports
+    TicketBacklogAbove(u64),
+    /// msol_price deviates from reference_price (same PRICE_DENOMINATOR scale) by more than max_bps
+    PriceDeviation { reference_price: u64, max_bps: u64 },
+    /// no crank run observed during the last slots
+    CrankNotRunForSlots(u64),
+    /// emergency_cooling_down is not zero
+    EmergencyUnstakeInProgress,
+}
+
+impl Display for AlertRule {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AlertRule::ReserveBelow(lamports) => write!(f, "reserve_below({})", lamports),
+            AlertRule::TicketBacklogAbove(lamports) => {
+                write!(f, "ticket_backlog_above({})", lamports)
+            }
+            AlertRule::PriceDeviation {
+                reference_price,
+                max_bps,
+            } => write!(
+                f,
+                "price_deviation(reference_price={}, max_bps={})",
+                reference_price, max_bps
+            ),
+            AlertRule::CrankNotRunForSlots(slots) => {
+                write!(f, "crank_not_run_for_slots({})", slots)
+            }
+            AlertRule::EmergencyUnstakeInProgress => write!(f, "emergency_unstake_in_progress"),
+        }
+    }
+}
+
+/// Data the rules are evaluated on
+#[derive(Clone, Copy, Debug)]
+pub struct AlertContext<'a> {
+    pub state: &'a Marinade,
+    pub current_slot: u64,
+    /// Slot of the last crank (stake-delta/update) transaction seen by the caller
+    pub last_crank_slot: Option<u64>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AlertEvent {
+    /// Name the rule was registered with
+    pub name: String,
+    pub rule: AlertRule,
+    /// Observed value of the metric the rule checks
+    pub observed: u64,
+    pub slot: u64,
+    pub message: String,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct AlertEngine {
+    rules: Vec<(String, AlertRule)>,
+}
+
+impl AlertRule {
+    /// Some(observed value, message) when the rule fires
+    pub fn check(&self, context: &AlertContext) -> Option<(u64, String)> {
+        let state = context.state;
+        match *self {
+            AlertRule::ReserveBelow(min) => {
+                let observed = state.available_reserve_balance;
+                (observed < min).then(|| {
+                    (
+                        observed,
+                        format!("Reserve balance {} below {}", observed, min),
+                    )
+                })
+            }
+            AlertRule::TicketBacklogAbove(max) => {
+                let observed = state.circulating_ticket_balance;
+                (observed > max).then(|| {
+                    (
+                        observed,
+                        format!(
+                            "Ticket backlog {} lamports in {} tickets above {}",
+                            observed, state.circulating_ticket_count, max
+                        ),
+                    )
+                })
+            }
+            AlertRule::PriceDeviation {
+                reference_price,
+                max_bps,
+            } => {
+                if reference_price == 0 {
+                    return None;
+                }
+                let observed = (state.msol_price.abs_diff(reference_price) as u128 * 10_000
+                    / reference_price as u128) as u64;
+                (observed > max_bps).then(|| {
+                    (
+                        observed,
+                        format!(
+                            "mSOL price {} deviates {} bps from {}",
+                            state.msol_price, observed, reference_price
+                        ),
+                    )
+                })
+            }
+            AlertRule::CrankNotRunForSlots(max_slots) => {
+                let observed = context
+                    .current_slot
+                    .saturating_sub(context.last_crank_slot.unwrap_or(0));
+                (observed > max_slots).then(|| {
+                    (
+                        observed,
+                        format!("Crank has not run for {} slots", observed),
+                    )
+                })
+            }
+            AlertRule::EmergencyUnstakeInProgress => {
+                let observed = state.emergency_cooling_down;
+                state.is_emergency_unstake_in_progress().then(|| {
+                    (
+                        observed,
+                        format!("Emergency unstake of {} lamports in progress", observed),
+                    )
+                })
+            }
+        }
+    }
+}
+
+impl AlertEngine {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn register(&mut self, name: &str, rule: AlertRule) -> &mut Self {
+        self.rules.push((name.to_string(), rule));
+        self
+    }
+
+    pub fn rules(&self) -> &[(String, AlertRule)] {
+        &self.rules
+    }
+
+    pub fn evaluate(&self, context: &AlertContext) -> Vec<AlertEvent> {
+        self.rules
+            .iter()
+            .filter_map(|(name, rule)| {
+                rule.check(context).map(|(observed, message)| AlertEvent {
+                    name: name.clone(),
+                    rule: *rule,
+                    observed,
+                    slot: context.current_slot,
+                    message,
+                })
+            })
+            .collect()
+    }
+}
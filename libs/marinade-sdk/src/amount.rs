@@ -0,0 +1,95 @@
+//! Exact conversion between base units (lamports, mSOL token units) and decimal strings.
+//! Integer arithmetic only: an amount never goes through f64, and parsing refuses
+//! digits below the smallest unit instead of rounding them away.
+//! decimals is that of a token mint, at most 38 (MAX_DECIMALS).
+
+use derive_more::Display;
+
+/// Lamports per SOL as a power of ten
+pub const SOL_DECIMALS: u8 = 9;
+/// Decimals of the mSOL mint
+pub const MSOL_DECIMALS: u8 = 9;
+/// Largest decimals whose unit fits the u128 arithmetic
+pub const MAX_DECIMALS: u8 = 38;
+
+#[derive(Clone, Debug, Display, PartialEq, Eq)]
+pub enum ParseAmountError {
+    #[display(fmt = "Empty amount")]
+    Empty,
+    #[display(fmt = "Invalid character {:?} in amount", _0)]
+    InvalidCharacter(char),
+    #[display(fmt = "More than {} decimal places", _0)]
+    TooManyDecimals(u8),
+    #[display(fmt = "Amount too large")]
+    Overflow,
+    #[display(fmt = "{} decimals is more than the supported {}", _0, MAX_DECIMALS)]
+    UnsupportedDecimals(u8),
+}
+
+impl std::error::Error for ParseAmountError {}
+
+fn unit(decimals: u8) -> Result<u128, ParseAmountError> {
+    10u128
+        .checked_pow(decimals as u32)
+        .ok_or(ParseAmountError::UnsupportedDecimals(decimals))
+}
+
+/// Shortest exact decimal form, e.g. 1_500_000_000 with 9 decimals is "1.5".
+/// Fails for decimals above MAX_DECIMALS
+pub fn format_amount(amount: u64, decimals: u8) -> Result<String, ParseAmountError> {
+    let unit = unit(decimals)?;
+    let whole = amount as u128 / unit;
+    let fraction = amount as u128 % unit;
+    if fraction == 0 {
+        return Ok(whole.to_string());
+    }
+    let fraction = format!("{:0width$}", fraction, width = decimals as usize);
+    Ok(format!("{}.{}", whole, fraction.trim_end_matches('0')))
+}
+
+/// Base units of a decimal string like "1.5", ".5" or "2"
+pub fn parse_amount(amount: &str, decimals: u8) -> Result<u64, ParseAmountError> {
+    let amount = amount.trim();
+    let (whole, fraction) = amount.split_once('.').unwrap_or((amount, ""));
+    if whole.is_empty() && fraction.is_empty() {
+        return Err(ParseAmountError::Empty);
+    }
+    if let Some(invalid) = whole
+        .chars()
+        .chain(fraction.chars())
+        .find(|c| !c.is_ascii_digit())
+    {
+        return Err(ParseAmountError::InvalidCharacter(invalid));
+    }
+    if fraction.len() > decimals as usize {
+        return Err(ParseAmountError::TooManyDecimals(decimals));
+    }
+    let scale = unit(decimals)? / unit(fraction.len() as u8)?;
+    let mut value: u128 = 0;
+    for digit in whole.bytes().chain(fraction.bytes()) {
+        value = value
+            .checked_mul(10)
+            .and_then(|value| value.checked_add((digit - b'0') as u128))
+            .ok_or(ParseAmountError::Overflow)?;
+    }
+    value
+        .checked_mul(scale)
+        .and_then(|value| u64::try_from(value).ok())
+        .ok_or(ParseAmountError::Overflow)
+}
+
+pub fn format_sol(lamports: u64) -> String {
+    format_amount(lamports, SOL_DECIMALS).expect("SOL_DECIMALS is supported")
+}
+
+pub fn parse_sol(sol: &str) -> Result<u64, ParseAmountError> {
+    parse_amount(sol, SOL_DECIMALS)
+}
+
+pub fn format_msol(msol_amount: u64) -> String {
+    format_amount(msol_amount, MSOL_DECIMALS).expect("MSOL_DECIMALS is supported")
+}
+
+pub fn parse_msol(msol: &str) -> Result<u64, ParseAmountError> {
+    parse_amount(msol, MSOL_DECIMALS)
+}
@@ -0,0 +1,29 @@
+//! Located for the state held by anchor programs as `Account<'info, Wrapper<Marinade>>`
+//! (or boxed), so their handlers can use the helper traits directly.
+//! AccountLoader is for zero copy accounts and does not apply to the borsh encoded state.
+
+use anchor_adapter::Wrapper;
+use anchor_lang::{prelude::Account, AccountDeserialize, AccountSerialize, Key, Owner};
+use solana_program::pubkey::Pubkey;
+
+use crate::located::Located;
+
+impl<'info, T> Located<T> for Account<'info, Wrapper<T>>
+where
+    Wrapper<T>: AccountSerialize + AccountDeserialize + Owner + Clone,
+{
+    fn as_ref(&self) -> &T {
+        self
+    }
+
+    /// Changes only the copy deserialized by Account. Anchor writes an account back on exit
+    /// only when the running program owns it, and the state is owned by the Marinade program,
+    /// so the changes a calling program makes here are dropped.
+    fn as_mut(&mut self) -> &mut T {
+        self
+    }
+
+    fn key(&self) -> Pubkey {
+        Key::key(self)
+    }
+}
@@ -0,0 +1,143 @@
+//! Associated token accounts of a wallet for the mSOL and LP mints, and user flows that
+//! create them on the way: with create_ata the flow starts with an idempotent creation of
+//! the token account it needs, paid by the wallet, so it works on a fresh wallet and is a
+//! no-op otherwise.
+//! The instructions are built here so the module does not need spl-associated-token-account.
+
+use solana_program::{
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+    system_program,
+};
+
+use crate::{
+    builders::{AddLiquidityBuilder, BuildError, DepositBuilder, LiquidUnstakeBuilder},
+    located::Located,
+    state::marinade::Marinade,
+};
+
+pub const ASSOCIATED_TOKEN_PROGRAM_ID: Pubkey =
+    solana_program::pubkey!("ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL");
+
+/// AssociatedTokenAccountInstruction::CreateIdempotent
+const CREATE_IDEMPOTENT: u8 = 1;
+
+pub fn associated_token_address(wallet: &Pubkey, mint: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[wallet.as_ref(), spl_token::ID.as_ref(), mint.as_ref()],
+        &ASSOCIATED_TOKEN_PROGRAM_ID,
+    )
+    .0
+}
+
+/// Creates the associated token account unless it exists, payer signs and funds the rent
+pub fn create_associated_token_account_idempotent(
+    payer: &Pubkey,
+    wallet: &Pubkey,
+    mint: &Pubkey,
+) -> Instruction {
+    Instruction {
+        program_id: ASSOCIATED_TOKEN_PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new(*payer, true),
+            AccountMeta::new(associated_token_address(wallet, mint), false),
+            AccountMeta::new_readonly(*wallet, false),
+            AccountMeta::new_readonly(*mint, false),
+            AccountMeta::new_readonly(system_program::ID, false),
+            AccountMeta::new_readonly(spl_token::ID, false),
+        ],
+        data: vec![CREATE_IDEMPOTENT],
+    }
+}
+
+/// Token accounts of a wallet for the state's mints
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct UserTokenAccounts {
+    pub wallet: Pubkey,
+    pub msol_account: Pubkey,
+    pub lp_account: Pubkey,
+}
+
+impl UserTokenAccounts {
+    pub fn new<T: Located<Marinade>>(state: &T, wallet: Pubkey) -> Self {
+        let marinade = state.as_ref();
+        Self {
+            wallet,
+            msol_account: associated_token_address(&wallet, &marinade.msol_mint),
+            lp_account: associated_token_address(&wallet, &marinade.liq_pool.lp_mint),
+        }
+    }
+}
+
+fn with_ata(
+    create_ata: bool,
+    wallet: &Pubkey,
+    mint: &Pubkey,
+    instruction: Instruction,
+) -> Vec<Instruction> {
+    let mut instructions = Vec::with_capacity(2);
+    if create_ata {
+        instructions.push(create_associated_token_account_idempotent(
+            wallet, wallet, mint,
+        ));
+    }
+    instructions.push(instruction);
+    instructions
+}
+
+/// deposit from wallet into its mSOL ATA
+pub fn deposit_to_ata<T: Located<Marinade>>(
+    state: &T,
+    wallet: Pubkey,
+    lamports: u64,
+    create_ata: bool,
+) -> Result<Vec<Instruction>, BuildError> {
+    let deposit = DepositBuilder::new(state)
+        .lamports(lamports)
+        .transfer_from(wallet)
+        .build()?;
+    Ok(with_ata(
+        create_ata,
+        &wallet,
+        &state.as_ref().msol_mint,
+        deposit,
+    ))
+}
+
+/// liquid_unstake from the mSOL ATA of wallet, the SOL goes to wallet
+pub fn liquid_unstake_from_ata<T: Located<Marinade>>(
+    state: &T,
+    wallet: Pubkey,
+    msol_amount: u64,
+    create_ata: bool,
+) -> Result<Vec<Instruction>, BuildError> {
+    let unstake = LiquidUnstakeBuilder::new(state)
+        .msol_amount(msol_amount)
+        .owner(wallet)
+        .build()?;
+    Ok(with_ata(
+        create_ata,
+        &wallet,
+        &state.as_ref().msol_mint,
+        unstake,
+    ))
+}
+
+/// add_liquidity from wallet into its LP ATA
+pub fn add_liquidity_to_ata<T: Located<Marinade>>(
+    state: &T,
+    wallet: Pubkey,
+    lamports: u64,
+    create_ata: bool,
+) -> Result<Vec<Instruction>, BuildError> {
+    let add_liquidity = AddLiquidityBuilder::new(state)
+        .lamports(lamports)
+        .transfer_from(wallet)
+        .build()?;
+    Ok(with_ata(
+        create_ata,
+        &wallet,
+        &state.as_ref().liq_pool.lp_mint,
+        add_liquidity,
+    ))
+}
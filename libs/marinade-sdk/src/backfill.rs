@@ -0,0 +1,163 @@
+//! Rebuilds per-wallet mSOL balances and open tickets from the classified program history
+//! and reconciles them with balances fetched from chain.
+//! Only transactions of the ingested address are seen: plain mSOL transfers between wallets
+//! or through other programs never reach the rebuilt positions and show up as discrepancies.
+
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    ops::RangeBounds,
+};
+
+use solana_program::pubkey::Pubkey;
+
+use crate::{
+    history::{ClassifiedTransaction, InstructionKind},
+    program::MarinadeProgram,
+};
+
+/// burn_msol_authority (the ticket beneficiary) and new_ticket_account of order_unstake
+const ORDER_UNSTAKE_BENEFICIARY: usize = 3;
+const ORDER_UNSTAKE_TICKET: usize = 4;
+/// ticket_account of claim
+const CLAIM_TICKET: usize = 2;
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct RebuiltPosition {
+    /// Sum of the mSOL balance changes of the wallet's token accounts.
+    /// May be negative when the history does not cover how the mSOL was received
+    pub msol_amount: i128,
+    pub tickets: BTreeSet<Pubkey>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct RebuiltPositions {
+    pub wallets: BTreeMap<Pubkey, RebuiltPosition>,
+    /// Slot of the last applied transaction
+    pub last_slot: Option<u64>,
+    pub applied_transactions: usize,
+}
+
+/// Balances of a wallet as currently on chain
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct OnChainPosition {
+    pub wallet: Pubkey,
+    pub msol_amount: u64,
+    pub tickets: Vec<Pubkey>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Discrepancy {
+    MsolBalance {
+        wallet: Pubkey,
+        rebuilt: i128,
+        on_chain: u64,
+    },
+    /// Ticket on chain that the history never created
+    UnknownTicket { wallet: Pubkey, ticket: Pubkey },
+    /// Ticket created by the history that is not on chain anymore
+    MissingTicket { wallet: Pubkey, ticket: Pubkey },
+}
+
+/// Applies the successful transactions of the slot range in the given (oldest first) order.
+/// Use `..` to rebuild from genesis.
+pub fn rebuild_positions<R: RangeBounds<u64>>(
+    program: &MarinadeProgram,
+    history: &[ClassifiedTransaction],
+    msol_mint: &Pubkey,
+    range: R,
+) -> RebuiltPositions {
+    let mut positions = RebuiltPositions::default();
+    for classified in history
+        .iter()
+        .filter(|classified| !classified.failed && range.contains(&classified.slot))
+    {
+        let transaction = &classified.transaction;
+        for change in transaction
+            .token_balances
+            .iter()
+            .filter(|change| change.mint == *msol_mint)
+        {
+            if let Some(owner) = change.owner {
+                positions.wallets.entry(owner).or_default().msol_amount +=
+                    change.post as i128 - change.pre as i128;
+            }
+        }
+        for (kind, instruction) in transaction.marinade_instructions(program) {
+            match kind {
+                InstructionKind::OrderUnstake => {
+                    if let (Some(beneficiary), Some(ticket)) = (
+                        transaction.account(instruction, ORDER_UNSTAKE_BENEFICIARY),
+                        transaction.account(instruction, ORDER_UNSTAKE_TICKET),
+                    ) {
+                        positions
+                            .wallets
+                            .entry(beneficiary)
+                            .or_default()
+                            .tickets
+                            .insert(ticket);
+                    }
+                }
+                InstructionKind::Claim => {
+                    if let Some(ticket) = transaction.account(instruction, CLAIM_TICKET) {
+                        for position in positions.wallets.values_mut() {
+                            position.tickets.remove(&ticket);
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+        positions.last_slot = Some(classified.slot);
+        positions.applied_transactions += 1;
+    }
+    positions
+}
+
+impl RebuiltPositions {
+    /// Wallets missing from on_chain are compared with an empty position
+    pub fn reconcile(&self, on_chain: &[OnChainPosition]) -> Vec<Discrepancy> {
+        let empty = RebuiltPosition::default();
+        let mut discrepancies = Vec::new();
+        for actual in on_chain {
+            let rebuilt = self.wallets.get(&actual.wallet).unwrap_or(&empty);
+            compare(actual, rebuilt, &mut discrepancies);
+        }
+        for (wallet, rebuilt) in &self.wallets {
+            if !on_chain.iter().any(|actual| actual.wallet == *wallet) {
+                let actual = OnChainPosition {
+                    wallet: *wallet,
+                    ..OnChainPosition::default()
+                };
+                compare(&actual, rebuilt, &mut discrepancies);
+            }
+        }
+        discrepancies
+    }
+}
+
+fn compare(actual: &OnChainPosition, rebuilt: &RebuiltPosition, out: &mut Vec<Discrepancy>) {
+    let wallet = actual.wallet;
+    if rebuilt.msol_amount != actual.msol_amount as i128 {
+        out.push(Discrepancy::MsolBalance {
+            wallet,
+            rebuilt: rebuilt.msol_amount,
+            on_chain: actual.msol_amount,
+        });
+    }
+    for ticket in &actual.tickets {
+        if !rebuilt.tickets.contains(ticket) {
+            out.push(Discrepancy::UnknownTicket {
+                wallet,
+                ticket: *ticket,
+            });
+        }
+    }
+    for ticket in &rebuilt.tickets {
+        if !actual.tickets.contains(ticket) {
+            out.push(Discrepancy::MissingTicket {
+                wallet,
+                ticket: *ticket,
+            });
+        }
+    }
+}
@@ -0,0 +1,229 @@
+//! Deposits for many users paid by one custodian wallet.
+//! Each user gets one deposit minting to their mSOL associated token account, created on the way
+//! when missing, with the rent paid by the custodian. Deposits are packed into as few legacy transactions as the size and compute
+//! limits allow; a failed transaction only fails the users it contains.
+
+use std::collections::HashMap;
+
+use derive_more::{Display, From};
+use micro_anchor::{legacy_transaction_size, PACKET_DATA_SIZE};
+use solana_client::rpc_client::RpcClient;
+use solana_program::{
+    instruction::Instruction,
+    pubkey::{Pubkey, PubkeyError},
+};
+use solana_sdk::{
+    compute_budget::ComputeBudgetInstruction,
+    signature::{Keypair, Signature},
+    signer::Signer,
+    transaction::Transaction,
+};
+use spl_associated_token_account::{
+    get_associated_token_address, instruction::create_associated_token_account_idempotent,
+};
+
+use crate::{
+    error::CommonError,
+    instructions::deposit::DepositData,
+    located::Located,
+    state::marinade::{Marinade, MarinadeHelpers},
+};
+
+#[derive(Debug, Display, From)]
+pub enum BatchError {
+    Pubkey(PubkeyError),
+    #[display(fmt = "Deposit of {} does not fit in a transaction alone", _0)]
+    #[from(ignore)]
+    DepositTooLarge(Pubkey),
+    /// the amounts of a wallet listed several times do not fit in u64
+    Calculation(CommonError),
+}
+
+impl std::error::Error for BatchError {}
+
+/// Compute units budgeted per transaction. The per user costs are estimated upper bounds
+/// with some margin, raise them if transactions start failing on compute
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BatchLimits {
+    pub max_compute_units: u32,
+    pub deposit_compute_units: u32,
+    pub create_ata_compute_units: u32,
+}
+
+impl Default for BatchLimits {
+    fn default() -> Self {
+        Self {
+            max_compute_units: 1_400_000,
+            deposit_compute_units: 80_000,
+            create_ata_compute_units: 40_000,
+        }
+    }
+}
+
+impl BatchLimits {
+    pub fn user_compute_units(&self) -> u32 {
+        self.deposit_compute_units + self.create_ata_compute_units
+    }
+}
+
+/// One transaction of the batch
+#[derive(Clone, Debug)]
+pub struct DepositTransaction {
+    /// (wallet, lamports) deposited by this transaction
+    pub deposits: Vec<(Pubkey, u64)>,
+    /// Compute unit limit followed by an ATA creation and a deposit per user
+    pub instructions: Vec<Instruction>,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct DepositPlan {
+    pub transactions: Vec<DepositTransaction>,
+    /// (wallet, lamports) under the state min_deposit, never sent
+    pub below_min_deposit: Vec<(Pubkey, u64)>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DepositOutcome {
+    Confirmed {
+        lamports: u64,
+        signature: Signature,
+    },
+    /// The transaction failed or was not confirmed in time,
+    /// check the user mSOL balance before sending the deposit again
+    Failed {
+        lamports: u64,
+        error: String,
+    },
+}
+
+/// Sums the amounts of a wallet listed several times, keeping the first listing order
+fn merge_deposits(deposits: Vec<(Pubkey, u64)>) -> Result<Vec<(Pubkey, u64)>, CommonError> {
+    let mut merged: Vec<(Pubkey, u64)> = Vec::with_capacity(deposits.len());
+    let mut index: HashMap<Pubkey, usize> = HashMap::with_capacity(deposits.len());
+    for (wallet, lamports) in deposits {
+        match index.get(&wallet) {
+            Some(&i) => {
+                merged[i].1 = merged[i]
+                    .1
+                    .checked_add(lamports)
+                    .ok_or(CommonError::CalculationFailure)?
+            }
+            None => {
+                index.insert(wallet, merged.len());
+                merged.push((wallet, lamports));
+            }
+        }
+    }
+    Ok(merged)
+}
+
+fn user_instructions<S: Located<Marinade>>(
+    state: &S,
+    custodian: &Pubkey,
+    wallet: &Pubkey,
+    lamports: u64,
+) -> Result<[Instruction; 2], PubkeyError> {
+    let msol_mint = &state.as_ref().msol_mint;
+    Ok([
+        create_associated_token_account_idempotent(custodian, wallet, msol_mint, &spl_token::ID),
+        state.deposit(
+            DepositData::new(lamports),
+            *custodian,
+            get_associated_token_address(wallet, msol_mint),
+        )?,
+    ])
+}
+
+/// Groups the deposits into transactions paid by custodian, one deposit per user
+pub fn plan_deposits<S: Located<Marinade>>(
+    state: &S,
+    custodian: &Pubkey,
+    deposits: Vec<(Pubkey, u64)>,
+    limits: &BatchLimits,
+) -> Result<DepositPlan, BatchError> {
+    let users_per_compute = (limits.max_compute_units / limits.user_compute_units()) as usize;
+    let mut plan = DepositPlan::default();
+    let mut current: Option<DepositTransaction> = None;
+    for (wallet, lamports) in merge_deposits(deposits)? {
+        if lamports < state.as_ref().min_deposit {
+            plan.below_min_deposit.push((wallet, lamports));
+            continue;
+        }
+        let user = user_instructions(state, custodian, &wallet, lamports)?;
+        if let Some(transaction) = &mut current {
+            let mut instructions = transaction.instructions.clone();
+            instructions.extend_from_slice(&user);
+            if transaction.deposits.len() < users_per_compute
+                && legacy_transaction_size(&instructions) <= PACKET_DATA_SIZE
+            {
+                transaction.instructions = instructions;
+                transaction.deposits.push((wallet, lamports));
+                continue;
+            }
+            plan.transactions.extend(current.take());
+        }
+        let mut instructions = vec![ComputeBudgetInstruction::set_compute_unit_limit(0)];
+        instructions.extend_from_slice(&user);
+        if users_per_compute == 0 || legacy_transaction_size(&instructions) > PACKET_DATA_SIZE {
+            return Err(BatchError::DepositTooLarge(wallet));
+        }
+        current = Some(DepositTransaction {
+            deposits: vec![(wallet, lamports)],
+            instructions,
+        });
+    }
+    plan.transactions.extend(current);
+    for transaction in &mut plan.transactions {
+        transaction.instructions[0] = ComputeBudgetInstruction::set_compute_unit_limit(
+            transaction.deposits.len() as u32 * limits.user_compute_units(),
+        );
+    }
+    Ok(plan)
+}
+
+/// Sends the planned transactions one by one and waits for their confirmation.
+/// Every wallet of deposits gets an outcome; below min_deposit ones fail without being sent.
+/// Amounts of a wallet listed several times are deposited together
+pub fn deposits<S: Located<Marinade>>(
+    rpc: &RpcClient,
+    state: &S,
+    custodian: &Keypair,
+    deposits: Vec<(Pubkey, u64)>,
+    limits: &BatchLimits,
+) -> Result<HashMap<Pubkey, DepositOutcome>, BatchError> {
+    let plan = plan_deposits(state, &custodian.pubkey(), deposits, limits)?;
+    let mut outcomes = HashMap::new();
+    for (wallet, lamports) in plan.below_min_deposit {
+        outcomes.insert(
+            wallet,
+            DepositOutcome::Failed {
+                lamports,
+                error: format!("below min deposit {}", state.as_ref().min_deposit),
+            },
+        );
+    }
+    for transaction in plan.transactions {
+        let result = rpc.get_latest_blockhash().and_then(|blockhash| {
+            rpc.send_and_confirm_transaction(&Transaction::new_signed_with_payer(
+                &transaction.instructions,
+                Some(&custodian.pubkey()),
+                &[custodian],
+                blockhash,
+            ))
+        });
+        for (wallet, lamports) in transaction.deposits {
+            let outcome = match &result {
+                Ok(signature) => DepositOutcome::Confirmed {
+                    lamports,
+                    signature: *signature,
+                },
+                Err(error) => DepositOutcome::Failed {
+                    lamports,
+                    error: error.to_string(),
+                },
+            };
+            outcomes.insert(wallet, outcome);
+        }
+    }
+    Ok(outcomes)
+}
@@ -0,0 +1,518 @@
+//! Reference consumer of the SDK and support tool.
+//! Build with `cargo run -p marinade-sdk --features cli --bin marinade-cli -- <command>`
+
+use std::error::Error;
+
+use clap::{Parser, Subcommand};
+use marinade_sdk::{
+    client::{fetch_account_data, AccountEncoding},
+    compat::stake_account_len,
+    filters,
+    instructions::{
+        config_marinade::ConfigMarinadeData, deposit::DepositData, order_unstake::OrderUnstakeData,
+        stake_reserve::StakeReserveData,
+    },
+    located::{KeyedAccount, Located},
+    planner::{next_stake_reserve, EpochPosition, StakeDeltaAction},
+    program::{MarinadeProgram, ProgramLocated},
+    propose::ProposalTarget,
+    seed_sequence::SeedSequence,
+    state::{
+        delayed_unstake_ticket::DelayedUnstakeTicket,
+        fee::Fee,
+        marinade::{Marinade, MarinadeHelpers, StakeDelta},
+        validator_system::ValidatorRecord,
+    },
+    storage::FileStorage,
+    ticket::{order_unstake_with_seed, ExistingTicketAccount},
+    ID,
+};
+use micro_anchor::AccountDeserialize;
+use solana_client::{
+    rpc_client::RpcClient,
+    rpc_config::{RpcAccountInfoConfig, RpcProgramAccountsConfig},
+};
+use solana_program::{instruction::Instruction, pubkey::Pubkey, system_instruction};
+use solana_sdk::{
+    commitment_config::CommitmentConfig,
+    signature::{read_keypair_file, Keypair, Signature},
+    signer::Signer,
+    transaction::Transaction,
+};
+use spl_associated_token_account::{
+    get_associated_token_address, instruction::create_associated_token_account_idempotent,
+};
+
+type CliResult<T> = Result<T, Box<dyn Error>>;
+
+/// State of the deployment at --program
+type State = ProgramLocated<KeyedAccount<Marinade>>;
+
+const MAINNET_STATE: &str = "8szGkuLTAux9XMgZ2vtY39jVSowEcpBfFfD8hXSEqdGC";
+const MAINNET_URL: &str = "https://api.mainnet-beta.solana.com";
+
+#[derive(Parser)]
+#[clap(name = "marinade-cli", version)]
+struct Cli {
+    /// RPC url or moniker of the cluster: mainnet-beta, devnet, testnet, localhost
+    /// (or their first letter). No default, so a command never hits mainnet by accident
+    #[clap(long, short)]
+    url: String,
+    /// Signer and fee payer, defaults to ~/.config/solana/id.json
+    #[clap(long, short)]
+    keypair: Option<String>,
+    /// State account, defaults to the Marinade state on mainnet-beta only
+    #[clap(long)]
+    state: Option<Pubkey>,
+    /// Program id of the deployment, e.g. a devnet fork
+    #[clap(long, default_value_t = ID)]
+    program: Pubkey,
+    #[clap(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Print main state values
+    State,
+    /// Deposit lamports and receive mSOL into the associated token account
+    Deposit { lamports: u64 },
+    /// Burn mSOL and create a delayed unstake ticket
+    OrderUnstake {
+        msol_amount: u64,
+        /// Create the ticket at keypair+seed instead of a new keypair, safe to retry
+        #[clap(long)]
+        ticket_seed: Option<String>,
+    },
+    /// Claim a due ticket
+    Claim { ticket: Pubkey },
+    /// List tickets of a beneficiary, defaults to the keypair
+    Tickets { beneficiary: Option<Pubkey> },
+    /// List validators with their score and active balance
+    Validators,
+    /// Run the stake-delta crank when there is reserve to stake
+    Crank {
+        /// Where the stake account seed counter is kept, defaults to ~/.config/marinade
+        #[clap(long)]
+        seed_dir: Option<String>,
+    },
+    /// Print the summary and hex payload of an admin config change
+    Propose(ProposeArgs),
+}
+
+#[derive(clap::ValueEnum, Clone, Copy)]
+enum ProposeFormat {
+    Transaction,
+    Governance,
+    Squads,
+}
+
+#[derive(clap::Args)]
+struct ProposeArgs {
+    #[clap(long, value_enum, default_value = "transaction")]
+    format: ProposeFormat,
+    /// Fee payer of the transaction format, defaults to the admin authority
+    #[clap(long)]
+    fee_payer: Option<Pubkey>,
+    #[clap(long)]
+    rewards_fee_bps: Option<u32>,
+    #[clap(long)]
+    slots_for_stake_delta: Option<u64>,
+    #[clap(long)]
+    min_stake: Option<u64>,
+    #[clap(long)]
+    min_deposit: Option<u64>,
+    #[clap(long)]
+    min_withdraw: Option<u64>,
+    #[clap(long)]
+    staking_sol_cap: Option<u64>,
+    #[clap(long)]
+    liquidity_sol_cap: Option<u64>,
+    #[clap(long)]
+    auto_add_validator_enabled: Option<bool>,
+}
+
+fn cluster_url(url: &str) -> &str {
+    match url {
+        "m" | "mainnet-beta" => MAINNET_URL,
+        "d" | "devnet" => "https://api.devnet.solana.com",
+        "t" | "testnet" => "https://api.testnet.solana.com",
+        "l" | "localhost" => "http://localhost:8899",
+        url => url,
+    }
+}
+
+fn load_keypair(path: &Option<String>) -> CliResult<Keypair> {
+    let path = match path {
+        Some(path) => path.clone(),
+        None => format!("{}/.config/solana/id.json", std::env::var("HOME")?),
+    };
+    read_keypair_file(&path).map_err(|e| format!("reading keypair {}: {}", path, e).into())
+}
+
+fn fetch_state(rpc: &RpcClient, program: MarinadeProgram, address: &Pubkey) -> CliResult<State> {
+    let data = rpc.get_account_data(address)?;
+    Ok(program.locate(KeyedAccount::new(
+        *address,
+        Marinade::try_deserialize(&mut data.as_slice())?,
+    )))
+}
+
+fn fetch_validators(rpc: &RpcClient, state: &Marinade) -> CliResult<Vec<ValidatorRecord>> {
+    let list = &state.validator_system.validator_list;
+    let data = fetch_account_data(rpc, &list.account, AccountEncoding::Base64Zstd)?
+        .ok_or_else(|| format!("validator list {} not found", list.account))?;
+    (0..list.len())
+        .map(|index| Ok(list.get(&data, index, "validator_list")?))
+        .collect()
+}
+
+fn send(
+    rpc: &RpcClient,
+    instructions: &[Instruction],
+    payer: &Keypair,
+    extra_signers: &[&Keypair],
+) -> CliResult<Signature> {
+    let mut signers = vec![payer];
+    signers.extend_from_slice(extra_signers);
+    let transaction = Transaction::new_signed_with_payer(
+        instructions,
+        Some(&payer.pubkey()),
+        &signers,
+        rpc.get_latest_blockhash()?,
+    );
+    Ok(rpc.send_and_confirm_transaction(&transaction)?)
+}
+
+fn print_state(state: &State) -> CliResult<()> {
+    let marinade = state.as_ref();
+    println!("program:                    {}", state.program_id());
+    println!("state:                      {}", state.key());
+    println!("msol mint:                  {}", marinade.msol_mint);
+    println!(
+        "msol price:                 {}",
+        marinade.msol_price as f64 / Marinade::PRICE_DENOMINATOR as f64
+    );
+    println!("msol supply:                {}", marinade.msol_supply);
+    println!(
+        "total virtual staked:       {}",
+        marinade.try_total_virtual_staked_lamports()?
+    );
+    println!(
+        "available reserve balance:  {}",
+        marinade.available_reserve_balance
+    );
+    println!(
+        "circulating tickets:        {} ({} lamports)",
+        marinade.circulating_ticket_count, marinade.circulating_ticket_balance
+    );
+    println!(
+        "emergency cooling down:     {}",
+        marinade.emergency_cooling_down
+    );
+    println!("staking cap:                {}", marinade.staking_cap());
+    println!("validators:                 {}", marinade.validators_len());
+    println!("stake accounts:             {}", marinade.stakes_len());
+    Ok(())
+}
+
+fn deposit(rpc: &RpcClient, state: &State, user: &Keypair, lamports: u64) -> CliResult<()> {
+    let msol_mint = state.as_ref().msol_mint;
+    let mint_to = get_associated_token_address(&user.pubkey(), &msol_mint);
+    let signature = send(
+        rpc,
+        &[
+            create_associated_token_account_idempotent(
+                &user.pubkey(),
+                &user.pubkey(),
+                &msol_mint,
+                &spl_token::ID,
+            ),
+            state.deposit(DepositData::new(lamports), user.pubkey(), mint_to)?,
+        ],
+        user,
+        &[],
+    )?;
+    println!("{}", signature);
+    Ok(())
+}
+
+fn order_unstake(
+    rpc: &RpcClient,
+    state: &State,
+    user: &Keypair,
+    msol_amount: u64,
+    ticket_seed: Option<String>,
+) -> CliResult<()> {
+    let burn_msol_from = get_associated_token_address(&user.pubkey(), &state.as_ref().msol_mint);
+    if let Some(seed) = ticket_seed {
+        let ticket = state
+            .program
+            .ticket_address_with_seed(&user.pubkey(), &seed)?;
+        let existing = rpc
+            .get_account_with_commitment(&ticket, CommitmentConfig::confirmed())?
+            .value;
+        let (ticket, instructions) = order_unstake_with_seed(
+            state,
+            OrderUnstakeData::new(msol_amount),
+            burn_msol_from,
+            user.pubkey(),
+            user.pubkey(),
+            user.pubkey(),
+            &seed,
+            rpc.get_minimum_balance_for_rent_exemption(DelayedUnstakeTicket::ACCOUNT_LEN)?,
+            existing.as_ref().map(|account| ExistingTicketAccount {
+                owner: &account.owner,
+                data: &account.data,
+            }),
+        )?;
+        let signature = send(rpc, &instructions, user, &[])?;
+        println!("ticket {} created by {}", ticket, signature);
+        return Ok(());
+    }
+    let ticket = Keypair::new();
+    let signature = send(
+        rpc,
+        &[
+            system_instruction::create_account(
+                &user.pubkey(),
+                &ticket.pubkey(),
+                rpc.get_minimum_balance_for_rent_exemption(DelayedUnstakeTicket::ACCOUNT_LEN)?,
+                DelayedUnstakeTicket::ACCOUNT_LEN as u64,
+                &state.program_id(),
+            ),
+            state.order_unstake(
+                OrderUnstakeData::new(msol_amount),
+                burn_msol_from,
+                user.pubkey(),
+                ticket.pubkey(),
+            ),
+        ],
+        user,
+        &[&ticket],
+    )?;
+    println!("ticket {} created by {}", ticket.pubkey(), signature);
+    Ok(())
+}
+
+fn tickets(rpc: &RpcClient, state: &State, beneficiary: &Pubkey) -> CliResult<()> {
+    let accounts = rpc.get_program_accounts_with_config(
+        &state.program_id(),
+        RpcProgramAccountsConfig {
+            filters: Some(filters::tickets_of(&state.key(), beneficiary)),
+            account_config: RpcAccountInfoConfig {
+                commitment: Some(CommitmentConfig::confirmed()),
+                ..RpcAccountInfoConfig::default()
+            },
+            ..RpcProgramAccountsConfig::default()
+        },
+    )?;
+    let epoch = rpc.get_epoch_info()?.epoch;
+    for (address, account) in accounts {
+        let ticket = DelayedUnstakeTicket::try_deserialize(&mut account.data.as_slice())?;
+        println!(
+            "{} {} lamports created in epoch {}{}",
+            address,
+            ticket.lamports_amount,
+            ticket.created_epoch,
+            if ticket.created_epoch < epoch {
+                ", due"
+            } else {
+                ""
+            }
+        );
+    }
+    Ok(())
+}
+
+fn validators(rpc: &RpcClient, state: &State) -> CliResult<()> {
+    for (index, validator) in fetch_validators(rpc, state.as_ref())?.iter().enumerate() {
+        println!(
+            "{:4} {} score {:6} active balance {}",
+            index, validator.validator_account, validator.score, validator.active_balance
+        );
+    }
+    Ok(())
+}
+
+/// Stakes the reserve into the validator most under its score based target, only within
+/// the stake-delta window and for validators the program still accepts this epoch
+fn crank(
+    rpc: &RpcClient,
+    state: &State,
+    payer: &Keypair,
+    seed_dir: Option<String>,
+) -> CliResult<()> {
+    let marinade = state.as_ref();
+    let epoch_info = rpc.get_epoch_info()?;
+    let position = EpochPosition {
+        epoch: epoch_info.epoch,
+        slot_index: epoch_info.slot_index,
+        slots_in_epoch: epoch_info.slots_in_epoch,
+    };
+    let limits = marinade.stake_system.stake_delta_limits();
+    if !limits.is_window_open(position.slot_index, position.slots_in_epoch) {
+        println!(
+            "The stake-delta window opens in {} slots",
+            position
+                .slots_in_epoch
+                .saturating_sub(limits.slots_for_stake_delta)
+                .saturating_sub(position.slot_index)
+        );
+        return Ok(());
+    }
+    let reserve_balance = rpc.get_balance(&state.try_reserve_address()?)?;
+    let stake_delta = marinade.stake_delta_direction(reserve_balance)?;
+    let amount = match stake_delta {
+        StakeDelta::Stake(amount) => amount,
+        StakeDelta::Unstake(amount) => {
+            println!(
+                "{} lamports have to be unstaked, deactivation is not supported by this command",
+                amount
+            );
+            return Ok(());
+        }
+        StakeDelta::Neutral => {
+            println!("Nothing to stake");
+            return Ok(());
+        }
+    };
+
+    let validators = fetch_validators(rpc, marinade)?;
+    let validator_index = match next_stake_reserve(&validators, &limits, stake_delta, &position) {
+        Some(StakeDeltaAction::Stake {
+            validator_index, ..
+        }) => validator_index,
+        _ => {
+            println!(
+                "No validator can take the stake in this run (unscored, already processed or below min stake)"
+            );
+            return Ok(());
+        }
+    };
+    let validator = &validators[validator_index as usize];
+
+    let seed_dir = match seed_dir {
+        Some(seed_dir) => seed_dir,
+        None => format!("{}/.config/marinade", std::env::var("HOME")?),
+    };
+    let mut seeds = SeedSequence::new(payer.pubkey(), state.key(), FileStorage::new(seed_dir)?);
+    let stake_account = seeds.next(position.epoch, |address| {
+        rpc.get_account_with_commitment(address, rpc.commitment())
+            .map(|response| response.value.is_some())
+    })?;
+    let stake_reserve = state.stake_reserve(
+        StakeReserveData::new(validator_index),
+        validator.validator_account,
+        stake_account.address,
+    )?;
+    let signature = send(
+        rpc,
+        &[
+            seeds.create_stake_account(
+                &stake_account,
+                &payer.pubkey(),
+                rpc.get_minimum_balance_for_rent_exemption(stake_account_len())?,
+            ),
+            stake_reserve,
+        ],
+        payer,
+        &[],
+    )?;
+    println!(
+        "staked {} lamports to {} by {}",
+        amount, validator.validator_account, signature
+    );
+    Ok(())
+}
+
+fn propose(state: &State, args: ProposeArgs) -> CliResult<()> {
+    let mut config = ConfigMarinadeData::default();
+    if let Some(v) = args.rewards_fee_bps {
+        config = config.with_rewards_fee(Fee::from_basis_points(v));
+    }
+    if let Some(v) = args.slots_for_stake_delta {
+        config = config.with_slots_for_stake_delta(v);
+    }
+    if let Some(v) = args.min_stake {
+        config = config.with_min_stake(v);
+    }
+    if let Some(v) = args.min_deposit {
+        config = config.with_min_deposit(v);
+    }
+    if let Some(v) = args.min_withdraw {
+        config = config.with_min_withdraw(v);
+    }
+    if let Some(v) = args.staking_sol_cap {
+        config = config.with_staking_sol_cap(v);
+    }
+    if let Some(v) = args.liquidity_sol_cap {
+        config = config.with_liquidity_sol_cap(v);
+    }
+    if let Some(v) = args.auto_add_validator_enabled {
+        config = config.with_auto_add_validator_enabled(v);
+    }
+    let target = match args.format {
+        ProposeFormat::Transaction => ProposalTarget::Transaction {
+            fee_payer: args.fee_payer.unwrap_or(state.as_ref().admin_authority),
+        },
+        ProposeFormat::Governance => ProposalTarget::Governance,
+        ProposeFormat::Squads => ProposalTarget::Squads,
+    };
+    let proposal = marinade_sdk::propose::propose(state, config, target);
+    println!("{}", proposal.summary());
+    println!(
+        "{}",
+        proposal
+            .payload
+            .iter()
+            .map(|b| format!("{:02x}", b))
+            .collect::<String>()
+    );
+    Ok(())
+}
+
+fn main() -> CliResult<()> {
+    let cli = Cli::parse();
+    let url = cluster_url(&cli.url);
+    let state_address = match cli.state {
+        Some(state) => state,
+        None if url == MAINNET_URL => MAINNET_STATE.parse()?,
+        None => return Err("--state is required outside of mainnet-beta".into()),
+    };
+    let rpc = RpcClient::new_with_commitment(url.to_string(), CommitmentConfig::confirmed());
+    let state = fetch_state(&rpc, MarinadeProgram::new(cli.program), &state_address)?;
+
+    match cli.command {
+        Command::State => print_state(&state)?,
+        Command::Deposit { lamports } => {
+            deposit(&rpc, &state, &load_keypair(&cli.keypair)?, lamports)?
+        }
+        Command::OrderUnstake {
+            msol_amount,
+            ticket_seed,
+        } => order_unstake(
+            &rpc,
+            &state,
+            &load_keypair(&cli.keypair)?,
+            msol_amount,
+            ticket_seed,
+        )?,
+        Command::Claim { ticket } => {
+            let user = load_keypair(&cli.keypair)?;
+            let signature = send(&rpc, &[state.claim(ticket, user.pubkey())?], &user, &[])?;
+            println!("{}", signature);
+        }
+        Command::Tickets { beneficiary } => {
+            let beneficiary = match beneficiary {
+                Some(beneficiary) => beneficiary,
+                None => load_keypair(&cli.keypair)?.pubkey(),
+            };
+            tickets(&rpc, &state, &beneficiary)?
+        }
+        Command::Validators => validators(&rpc, &state)?,
+        Command::Crank { seed_dir } => crank(&rpc, &state, &load_keypair(&cli.keypair)?, seed_dir)?,
+        Command::Propose(args) => propose(&state, args)?,
+    }
+    Ok(())
+}
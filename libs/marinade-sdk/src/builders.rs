@@ -0,0 +1,354 @@
+//! Fluent builders for the user instructions. Accounts left unset get the usual defaults:
+//! the token accounts are the associated token accounts of the owner wallet and SOL goes
+//! back to the owner. build() checks the required fields and the amounts before deriving
+//! the PDAs, see MarinadeHelpers for the underlying builders. checks() runs the same checks
+//! as checks::Checks, so CheckMode::AllErrors reports every missing field and bad amount.
+
+use derive_more::{Display, From};
+use solana_program::{
+    instruction::Instruction,
+    program_error::ProgramError,
+    pubkey::{Pubkey, PubkeyError},
+};
+
+use crate::{
+    ata::associated_token_address,
+    checks::{CheckFailure, CheckMode, Checks},
+    error::{CommonError, Mismatch},
+    instructions::{
+        add_liquidity::AddLiquidityData, deposit::DepositData, liquid_unstake::LiquidUnstakeData,
+        order_unstake::OrderUnstakeData, remove_liquidity::RemoveLiquidityData,
+    },
+    located::Located,
+    state::marinade::{Marinade, MarinadeHelpers},
+};
+
+#[derive(Debug, Display, From, PartialEq, Eq)]
+pub enum BuildError {
+    #[display(fmt = "{} is required", _0)]
+    #[from(ignore)]
+    Missing(&'static str),
+    #[display(fmt = "{} must not be zero", _0)]
+    #[from(ignore)]
+    ZeroAmount(&'static str),
+    /// The program rejects amounts below the state minimum
+    #[display(fmt = "{} {} is below the minimum {}", name, amount, min)]
+    #[from(ignore)]
+    BelowMinimum {
+        name: &'static str,
+        amount: u64,
+        min: u64,
+    },
+    Pubkey(PubkeyError),
+}
+
+impl std::error::Error for BuildError {}
+
+impl From<BuildError> for CheckFailure {
+    fn from(error: BuildError) -> Self {
+        let program_error = match &error {
+            BuildError::Missing(_) => ProgramError::NotEnoughAccountKeys,
+            BuildError::ZeroAmount(_) => {
+                CommonError::NumberTooLow(Some(Mismatch::new(1, 0))).into()
+            }
+            BuildError::BelowMinimum { amount, min, .. } => {
+                CommonError::NumberTooLow(Some(Mismatch::new(*min, *amount))).into()
+            }
+            BuildError::Pubkey(err) => err.clone().into(),
+        };
+        CheckFailure {
+            message: error.to_string(),
+            error: program_error,
+        }
+    }
+}
+
+fn required<V>(value: Option<V>, name: &'static str) -> Result<V, BuildError> {
+    value.ok_or(BuildError::Missing(name))
+}
+
+/// Check of a builder field for Checks::check
+fn field<V>(result: Result<V, BuildError>) -> Result<(), CheckFailure> {
+    result.map(drop).map_err(CheckFailure::from)
+}
+
+fn amount(value: Option<u64>, name: &'static str, min: u64) -> Result<u64, BuildError> {
+    let amount = required(value, name)?;
+    if amount == 0 {
+        return Err(BuildError::ZeroAmount(name));
+    }
+    if amount < min {
+        return Err(BuildError::BelowMinimum { name, amount, min });
+    }
+    Ok(amount)
+}
+
+macro_rules! setters {
+    ($($(#[$doc:meta])* $field:ident: $type:ty,)*) => {
+        $(
+            $(#[$doc])*
+            pub fn $field(mut self, $field: $type) -> Self {
+                self.$field = Some($field);
+                self
+            }
+        )*
+    };
+}
+
+/// deposit, transfer_from signs
+pub struct DepositBuilder<'a, T> {
+    state: &'a T,
+    lamports: Option<u64>,
+    transfer_from: Option<Pubkey>,
+    mint_to: Option<Pubkey>,
+}
+
+impl<'a, T: Located<Marinade>> DepositBuilder<'a, T> {
+    pub fn new(state: &'a T) -> Self {
+        Self {
+            state,
+            lamports: None,
+            transfer_from: None,
+            mint_to: None,
+        }
+    }
+
+    setters! {
+        lamports: u64,
+        transfer_from: Pubkey,
+        /// mSOL account, the mSOL ATA of transfer_from by default
+        mint_to: Pubkey,
+    }
+
+    /// The checks of build, see checks::CheckMode
+    pub fn checks(&self, mode: CheckMode) -> Checks {
+        let min_deposit = self.state.as_ref().min_deposit;
+        Checks::new(mode)
+            .check(|| field(amount(self.lamports, "lamports", min_deposit)))
+            .check(|| field(required(self.transfer_from, "transfer_from")))
+    }
+
+    pub fn build(&self) -> Result<Instruction, BuildError> {
+        let marinade = self.state.as_ref();
+        let lamports = amount(self.lamports, "lamports", marinade.min_deposit)?;
+        let transfer_from = required(self.transfer_from, "transfer_from")?;
+        let mint_to = self
+            .mint_to
+            .unwrap_or_else(|| associated_token_address(&transfer_from, &marinade.msol_mint));
+        Ok(self
+            .state
+            .deposit(DepositData::new(lamports), transfer_from, mint_to)?)
+    }
+}
+
+/// liquid_unstake, owner signs
+pub struct LiquidUnstakeBuilder<'a, T> {
+    state: &'a T,
+    msol_amount: Option<u64>,
+    owner: Option<Pubkey>,
+    get_msol_from: Option<Pubkey>,
+    transfer_sol_to: Option<Pubkey>,
+}
+
+impl<'a, T: Located<Marinade>> LiquidUnstakeBuilder<'a, T> {
+    pub fn new(state: &'a T) -> Self {
+        Self {
+            state,
+            msol_amount: None,
+            owner: None,
+            get_msol_from: None,
+            transfer_sol_to: None,
+        }
+    }
+
+    setters! {
+        msol_amount: u64,
+        /// Owner or delegate of get_msol_from
+        owner: Pubkey,
+        /// The mSOL ATA of owner by default
+        get_msol_from: Pubkey,
+        /// owner by default
+        transfer_sol_to: Pubkey,
+    }
+
+    /// The checks of build, see checks::CheckMode
+    pub fn checks(&self, mode: CheckMode) -> Checks {
+        Checks::new(mode)
+            .check(|| field(amount(self.msol_amount, "msol_amount", 0)))
+            .check(|| field(required(self.owner, "owner")))
+    }
+
+    pub fn build(&self) -> Result<Instruction, BuildError> {
+        let msol_amount = amount(self.msol_amount, "msol_amount", 0)?;
+        let owner = required(self.owner, "owner")?;
+        let get_msol_from = self
+            .get_msol_from
+            .unwrap_or_else(|| associated_token_address(&owner, &self.state.as_ref().msol_mint));
+        Ok(self.state.liquid_unstake(
+            LiquidUnstakeData::new(msol_amount),
+            get_msol_from,
+            owner,
+            self.transfer_sol_to.unwrap_or(owner),
+        )?)
+    }
+}
+
+/// order_unstake into a ticket account created beforehand, owner signs
+pub struct OrderUnstakeBuilder<'a, T> {
+    state: &'a T,
+    msol_amount: Option<u64>,
+    owner: Option<Pubkey>,
+    burn_msol_from: Option<Pubkey>,
+    new_ticket_account: Option<Pubkey>,
+}
+
+impl<'a, T: Located<Marinade>> OrderUnstakeBuilder<'a, T> {
+    pub fn new(state: &'a T) -> Self {
+        Self {
+            state,
+            msol_amount: None,
+            owner: None,
+            burn_msol_from: None,
+            new_ticket_account: None,
+        }
+    }
+
+    setters! {
+        msol_amount: u64,
+        /// Owner or delegate of burn_msol_from, the beneficiary of the ticket
+        owner: Pubkey,
+        /// The mSOL ATA of owner by default
+        burn_msol_from: Pubkey,
+        new_ticket_account: Pubkey,
+    }
+
+    /// The checks of build, see checks::CheckMode
+    pub fn checks(&self, mode: CheckMode) -> Checks {
+        Checks::new(mode)
+            .check(|| field(amount(self.msol_amount, "msol_amount", 0)))
+            .check(|| field(required(self.owner, "owner")))
+            .check(|| field(required(self.new_ticket_account, "new_ticket_account")))
+    }
+
+    pub fn build(&self) -> Result<Instruction, BuildError> {
+        let msol_amount = amount(self.msol_amount, "msol_amount", 0)?;
+        let owner = required(self.owner, "owner")?;
+        let new_ticket_account = required(self.new_ticket_account, "new_ticket_account")?;
+        let burn_msol_from = self
+            .burn_msol_from
+            .unwrap_or_else(|| associated_token_address(&owner, &self.state.as_ref().msol_mint));
+        Ok(self.state.order_unstake(
+            OrderUnstakeData::new(msol_amount),
+            burn_msol_from,
+            owner,
+            new_ticket_account,
+        ))
+    }
+}
+
+/// add_liquidity, transfer_from signs
+pub struct AddLiquidityBuilder<'a, T> {
+    state: &'a T,
+    lamports: Option<u64>,
+    transfer_from: Option<Pubkey>,
+    mint_to: Option<Pubkey>,
+}
+
+impl<'a, T: Located<Marinade>> AddLiquidityBuilder<'a, T> {
+    pub fn new(state: &'a T) -> Self {
+        Self {
+            state,
+            lamports: None,
+            transfer_from: None,
+            mint_to: None,
+        }
+    }
+
+    setters! {
+        lamports: u64,
+        transfer_from: Pubkey,
+        /// LP token account, the LP ATA of transfer_from by default
+        mint_to: Pubkey,
+    }
+
+    /// The checks of build, see checks::CheckMode
+    pub fn checks(&self, mode: CheckMode) -> Checks {
+        let min_deposit = self.state.as_ref().min_deposit;
+        Checks::new(mode)
+            .check(|| field(amount(self.lamports, "lamports", min_deposit)))
+            .check(|| field(required(self.transfer_from, "transfer_from")))
+    }
+
+    pub fn build(&self) -> Result<Instruction, BuildError> {
+        let marinade = self.state.as_ref();
+        let lamports = amount(self.lamports, "lamports", marinade.min_deposit)?;
+        let transfer_from = required(self.transfer_from, "transfer_from")?;
+        let mint_to = self.mint_to.unwrap_or_else(|| {
+            associated_token_address(&transfer_from, &marinade.liq_pool.lp_mint)
+        });
+        Ok(self
+            .state
+            .add_liquidity(AddLiquidityData::new(lamports), transfer_from, mint_to)?)
+    }
+}
+
+/// remove_liquidity, owner signs
+pub struct RemoveLiquidityBuilder<'a, T> {
+    state: &'a T,
+    tokens: Option<u64>,
+    owner: Option<Pubkey>,
+    burn_from: Option<Pubkey>,
+    transfer_sol_to: Option<Pubkey>,
+    transfer_msol_to: Option<Pubkey>,
+}
+
+impl<'a, T: Located<Marinade>> RemoveLiquidityBuilder<'a, T> {
+    pub fn new(state: &'a T) -> Self {
+        Self {
+            state,
+            tokens: None,
+            owner: None,
+            burn_from: None,
+            transfer_sol_to: None,
+            transfer_msol_to: None,
+        }
+    }
+
+    setters! {
+        tokens: u64,
+        /// Owner or delegate of burn_from
+        owner: Pubkey,
+        /// The LP ATA of owner by default
+        burn_from: Pubkey,
+        /// owner by default
+        transfer_sol_to: Pubkey,
+        /// The mSOL ATA of owner by default
+        transfer_msol_to: Pubkey,
+    }
+
+    /// The checks of build, see checks::CheckMode
+    pub fn checks(&self, mode: CheckMode) -> Checks {
+        Checks::new(mode)
+            .check(|| field(amount(self.tokens, "tokens", 0)))
+            .check(|| field(required(self.owner, "owner")))
+    }
+
+    pub fn build(&self) -> Result<Instruction, BuildError> {
+        let marinade = self.state.as_ref();
+        let tokens = amount(self.tokens, "tokens", 0)?;
+        let owner = required(self.owner, "owner")?;
+        let burn_from = self
+            .burn_from
+            .unwrap_or_else(|| associated_token_address(&owner, &marinade.liq_pool.lp_mint));
+        let transfer_msol_to = self
+            .transfer_msol_to
+            .unwrap_or_else(|| associated_token_address(&owner, &marinade.msol_mint));
+        Ok(self.state.remove_liquidity(
+            RemoveLiquidityData::new(tokens),
+            burn_from,
+            owner,
+            self.transfer_sol_to.unwrap_or(owner),
+            transfer_msol_to,
+        )?)
+    }
+}
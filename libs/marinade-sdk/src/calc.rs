@@ -1,18 +1,92 @@
 //! Common calculations
 
-use crate::error::CommonError;
-use std::convert::TryFrom;
+use crate::{
+    error::{CommonError, Mismatch},
+    state::liq_pool::LpUnderlying,
+};
+use std::{convert::TryFrom, fmt::Display};
+
+/// Which side benefits from integer rounding of an amount paid out to the user.
+/// The program rounds every payout down (ProtocolFavor), so all SDK quotes use the same
+/// policy and never show the user more than the on-chain execution gives.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RoundingPolicy {
+    /// round payouts down, as the on-chain program does
+    ProtocolFavor,
+    /// round payouts up, an upper bound of what the user may receive
+    UserFavor,
+}
+
+/// Rounding used by the on-chain program and by SDK quotes
+pub const ON_CHAIN_ROUNDING: RoundingPolicy = RoundingPolicy::ProtocolFavor;
+
+/// Part of a whole, e.g. a share of the liquidity pool or of the TVL. Unlike Fee nothing is
+/// charged with it, and the exact part and whole are kept instead of rounded basis points.
+/// Equality compares part and whole, not the quotient
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Ratio {
+    pub part: u64,
+    pub whole: u64,
+}
+
+impl Display for Ratio {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}%", self.basis_points() as f32 / 100.0)
+    }
+}
+
+impl Ratio {
+    /// Fails with NumberTooHigh when part exceeds whole
+    pub fn new(part: u64, whole: u64) -> Result<Self, CommonError> {
+        if part > whole {
+            return Err(CommonError::NumberTooHigh(Some(Mismatch::new(whole, part))));
+        }
+        Ok(Self { part, whole })
+    }
+
+    /// part scaled to denominator, rounded down. 0 of an empty whole
+    pub fn scaled(&self, denominator: u64) -> u64 {
+        if self.whole == 0 {
+            return 0;
+        }
+        // LMT no overflow, part <= whole
+        (denominator as u128 * self.part as u128 / self.whole as u128) as u64
+    }
+
+    pub fn basis_points(&self) -> u64 {
+        self.scaled(10_000)
+    }
+
+    /// Part of amount, rounded down
+    pub fn apply(&self, amount: u64) -> u64 {
+        self.scaled(amount)
+    }
+}
 
 /// calculate amount*numerator/denominator
 /// as value  = shares * share_price where share_price=total_value/total_shares
 /// or shares = amount_value / share_price where share_price=total_value/total_shares
 ///     => shares = amount_value * 1/share_price where 1/share_price=total_shares/total_value
 pub fn proportional(amount: u64, numerator: u64, denominator: u64) -> Result<u64, CommonError> {
+    proportional_with_rounding(amount, numerator, denominator, ON_CHAIN_ROUNDING)
+}
+
+/// proportional with explicit rounding of the result
+pub fn proportional_with_rounding(
+    amount: u64,
+    numerator: u64,
+    denominator: u64,
+    rounding: RoundingPolicy,
+) -> Result<u64, CommonError> {
     if denominator == 0 {
         return Ok(amount);
     }
-    u64::try_from((amount as u128) * (numerator as u128) / (denominator as u128))
-        .map_err(|_| CommonError::CalculationFailure)
+    let product = (amount as u128) * (numerator as u128);
+    let result = match rounding {
+        RoundingPolicy::ProtocolFavor => product / (denominator as u128),
+        RoundingPolicy::UserFavor => (product + denominator as u128 - 1) / (denominator as u128),
+    };
+    u64::try_from(result).map_err(|_| CommonError::CalculationFailure)
 }
 
 #[inline] //alias for proportional
@@ -36,3 +110,37 @@ pub fn shares_from_value(
         proportional(value, total_shares, total_value)
     }
 }
+
+/// LP tokens minted by add_liquidity for lamports.
+/// sol_leg is the liq pool SOL leg lamports minus its rent exempt reserve,
+/// msol_leg_value the mSOL leg valued in lamports at the current mSOL price
+pub fn quote_add_liquidity(
+    lamports: u64,
+    sol_leg: u64,
+    msol_leg_value: u64,
+    lp_supply: u64,
+) -> Result<u64, CommonError> {
+    let total_value = sol_leg
+        .checked_add(msol_leg_value)
+        .ok_or(CommonError::CalculationFailure)?;
+    shares_from_value(lamports, total_value, lp_supply)
+}
+
+/// Lamports and mSOL paid out by remove_liquidity for tokens,
+/// sol_leg as in quote_add_liquidity and msol_leg the mSOL leg token amount
+pub fn quote_remove_liquidity(
+    tokens: u64,
+    sol_leg: u64,
+    msol_leg: u64,
+    lp_supply: u64,
+) -> Result<LpUnderlying, CommonError> {
+    if tokens > lp_supply {
+        return Err(CommonError::NumberTooHigh(Some(Mismatch::new(
+            lp_supply, tokens,
+        ))));
+    }
+    Ok(LpUnderlying {
+        sol_amount: value_from_shares(tokens, sol_leg, lp_supply)?,
+        msol_amount: value_from_shares(tokens, msol_leg, lp_supply)?,
+    })
+}
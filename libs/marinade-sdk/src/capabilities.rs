@@ -0,0 +1,125 @@
+//! Instructions that only some deployed program versions have.
+//!
+//! The program account carries no version marker, so support is probed: an instruction with
+//! the capability's discriminator and no accounts is simulated (see client::detect_capabilities).
+//! Anchor rejects unknown discriminators with InstructionFallbackNotFound, while a known
+//! instruction fails later on its missing accounts. Builders of these instructions take the
+//! detected Capabilities and call Capabilities::require, so an old deployment surfaces as
+//! UnsupportedByProgram instead of an opaque on-chain failure. Only pause and resume have
+//! builders (MarinadeHelpers::pause_instruction and resume_instruction) so far.
+
+use derive_more::Display;
+use solana_program::{
+    hash::hash,
+    instruction::{Instruction, InstructionError},
+    pubkey::Pubkey,
+};
+
+/// Anchor's ErrorCode::InstructionFallbackNotFound
+pub const INSTRUCTION_FALLBACK_NOT_FOUND: u32 = 101;
+
+#[derive(Clone, Copy, Debug, Display, PartialEq, Eq, Hash)]
+pub enum Capability {
+    /// pause and resume
+    #[display(fmt = "pause")]
+    Pause,
+    #[display(fmt = "withdraw_stake_account")]
+    WithdrawStakeAccount,
+    /// realloc_validator_list and realloc_stake_list
+    #[display(fmt = "realloc")]
+    Realloc,
+}
+
+impl Capability {
+    pub const ALL: [Capability; 3] = [
+        Capability::Pause,
+        Capability::WithdrawStakeAccount,
+        Capability::Realloc,
+    ];
+
+    fn bit(self) -> u32 {
+        1 << self as u32
+    }
+
+    /// Instruction whose presence in the program implies the capability
+    pub fn probe_instruction_name(self) -> &'static str {
+        match self {
+            Capability::Pause => "pause",
+            Capability::WithdrawStakeAccount => "withdraw_stake_account",
+            Capability::Realloc => "realloc_validator_list",
+        }
+    }
+
+    /// Anchor discriminator of the probed instruction: sha256("global:<name>")[..8]
+    pub fn discriminator(self) -> [u8; 8] {
+        let preimage = format!("global:{}", self.probe_instruction_name());
+        hash(preimage.as_bytes()).to_bytes()[..8]
+            .try_into()
+            .unwrap()
+    }
+
+    /// Instruction to simulate, it never succeeds: it has no accounts
+    pub fn probe(self, program_id: Pubkey) -> Instruction {
+        Instruction {
+            program_id,
+            accounts: vec![],
+            data: self.discriminator().to_vec(),
+        }
+    }
+
+    /// Whether the program knows the probed instruction, from the error the probe failed with
+    pub fn is_supported_by(error: &InstructionError) -> bool {
+        *error != InstructionError::Custom(INSTRUCTION_FALLBACK_NOT_FOUND)
+    }
+}
+
+#[derive(Clone, Copy, Debug, Display, PartialEq, Eq)]
+#[display(fmt = "Instruction {} is not supported by the deployed program", _0)]
+pub struct UnsupportedByProgram(pub Capability);
+
+impl std::error::Error for UnsupportedByProgram {}
+
+/// Set of capabilities of a deployed program
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Capabilities {
+    bits: u32,
+}
+
+impl Capabilities {
+    pub fn all() -> Self {
+        Capability::ALL.into_iter().collect()
+    }
+
+    pub fn contains(&self, capability: Capability) -> bool {
+        self.bits & capability.bit() != 0
+    }
+
+    pub fn insert(&mut self, capability: Capability) {
+        self.bits |= capability.bit();
+    }
+
+    pub fn require(&self, capability: Capability) -> Result<(), UnsupportedByProgram> {
+        if self.contains(capability) {
+            Ok(())
+        } else {
+            Err(UnsupportedByProgram(capability))
+        }
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = Capability> {
+        let capabilities = *self;
+        Capability::ALL
+            .into_iter()
+            .filter(move |capability| capabilities.contains(*capability))
+    }
+}
+
+impl FromIterator<Capability> for Capabilities {
+    fn from_iter<I: IntoIterator<Item = Capability>>(iter: I) -> Self {
+        let mut capabilities = Self::default();
+        for capability in iter {
+            capabilities.insert(capability);
+        }
+        capabilities
+    }
+}
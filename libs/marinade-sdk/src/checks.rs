@@ -1,4 +1,8 @@
-use solana_program::stake::state::StakeState;
+//! Account and amount checks. The check_* functions stop at the first failure and log it like
+//! the program does; Checks composes them and, in CheckMode::AllErrors, collects every failure
+//! so a UI can list all the problems of a transaction at once.
+
+use derive_more::Display;
 use solana_program::{
     account_info::AccountInfo, entrypoint::ProgramResult, msg, program_error::ProgramError,
     pubkey::Pubkey,
@@ -6,150 +10,359 @@ use solana_program::{
 use spl_token::state::Account as TokenAccount;
 use spl_token::state::Mint;
 
-use crate::error::CommonError;
+use crate::{
+    compat::StakeState,
+    error::{CommonError, Mismatch},
+};
 
-pub fn check_min_amount(amount: u64, min_amount: u64, action_name: &str) -> ProgramResult {
+#[derive(Clone, Debug, Display, PartialEq, Eq)]
+#[display(fmt = "{}", message)]
+pub struct CheckFailure {
+    pub message: String,
+    pub error: ProgramError,
+}
+
+impl std::error::Error for CheckFailure {}
+
+impl CheckFailure {
+    fn new<E: Into<ProgramError>>(error: E, message: String) -> Self {
+        Self {
+            message,
+            error: error.into(),
+        }
+    }
+}
+
+impl From<CheckFailure> for ProgramError {
+    fn from(failure: CheckFailure) -> Self {
+        failure.error
+    }
+}
+
+fn logged(result: Result<(), CheckFailure>) -> ProgramResult {
+    result.map_err(|failure| {
+        msg!("{}", failure.message);
+        failure.error
+    })
+}
+
+fn min_amount(amount: u64, min_amount: u64, action_name: &str) -> Result<(), CheckFailure> {
     if amount >= min_amount {
         Ok(())
     } else {
-        msg!(
-            "{}: Number too low {} (min is {})",
-            action_name,
-            amount,
-            min_amount,
-        );
-        Err(CommonError::NumberTooLow.into())
+        Err(CheckFailure::new(
+            CommonError::NumberTooLow(Some(Mismatch::new(min_amount, amount))),
+            format!(
+                "{}: Number too low {} (min is {})",
+                action_name, amount, min_amount,
+            ),
+        ))
     }
 }
 
-pub fn check_address(
+fn address(
     actual_address: &Pubkey,
     reference_address: &Pubkey,
     field_name: &str,
-) -> ProgramResult {
+) -> Result<(), CheckFailure> {
     if actual_address == reference_address {
         Ok(())
     } else {
-        msg!(
-            "Invalid {} address: expected {} got {}",
-            field_name,
-            reference_address,
-            actual_address
-        );
-        Err(ProgramError::InvalidArgument)
+        Err(CheckFailure::new(
+            ProgramError::InvalidArgument,
+            format!(
+                "Invalid {} address: expected {} got {}",
+                field_name, reference_address, actual_address
+            ),
+        ))
     }
 }
 
-pub fn check_owner_program<'info>(
-    account: &AccountInfo<'info>,
+fn owner_program(
+    actual_owner: &Pubkey,
     owner: &Pubkey,
     field_name: &str,
-) -> ProgramResult {
-    let actual_owner = account.owner;
+) -> Result<(), CheckFailure> {
     if actual_owner == owner {
         Ok(())
     } else {
-        msg!(
-            "Invalid {} owner_program: expected {} got {}",
-            field_name,
-            owner,
-            actual_owner
-        );
-        Err(ProgramError::InvalidArgument)
+        Err(CheckFailure::new(
+            ProgramError::InvalidArgument,
+            format!(
+                "Invalid {} owner_program: expected {} got {}",
+                field_name, owner, actual_owner
+            ),
+        ))
     }
 }
 
-pub fn check_mint_authority(
+fn mint_authority(
     mint: &Mint,
     mint_authority: Pubkey,
     field_name: &str,
-) -> ProgramResult {
+) -> Result<(), CheckFailure> {
     if mint.mint_authority.contains(&mint_authority) {
         Ok(())
     } else {
-        msg!(
-            "Invalid {} mint authority {}. Expected {}",
-            field_name,
-            mint.mint_authority.unwrap_or_default(),
-            mint_authority
-        );
-        Err(ProgramError::InvalidAccountData)
+        Err(CheckFailure::new(
+            ProgramError::InvalidAccountData,
+            format!(
+                "Invalid {} mint authority {}. Expected {}",
+                field_name,
+                mint.mint_authority.unwrap_or_default(),
+                mint_authority
+            ),
+        ))
     }
 }
 
-pub fn check_freeze_authority(mint: &Mint, field_name: &str) -> ProgramResult {
+fn freeze_authority(mint: &Mint, field_name: &str) -> Result<(), CheckFailure> {
     if mint.freeze_authority.is_none() {
         Ok(())
     } else {
-        msg!("Mint {} must have freeze authority not set", field_name);
-        Err(ProgramError::InvalidAccountData)
+        Err(CheckFailure::new(
+            ProgramError::InvalidAccountData,
+            format!("Mint {} must have freeze authority not set", field_name),
+        ))
     }
 }
 
-pub fn check_mint_empty(mint: &Mint, field_name: &str) -> ProgramResult {
+fn mint_empty(mint: &Mint, field_name: &str) -> Result<(), CheckFailure> {
     if mint.supply == 0 {
         Ok(())
     } else {
-        msg!("Non empty mint {} supply: {}", field_name, mint.supply);
-        Err(ProgramError::InvalidArgument)
+        Err(CheckFailure::new(
+            ProgramError::InvalidArgument,
+            format!("Non empty mint {} supply: {}", field_name, mint.supply),
+        ))
     }
 }
 
-pub fn check_token_mint(token: &TokenAccount, mint: Pubkey, field_name: &str) -> ProgramResult {
+fn token_mint(token: &TokenAccount, mint: Pubkey, field_name: &str) -> Result<(), CheckFailure> {
     if token.mint == mint {
         Ok(())
     } else {
-        msg!(
-            "Invalid token {} mint {}. Expected {}",
-            field_name,
-            token.mint,
-            mint
-        );
-        Err(ProgramError::InvalidAccountData)
+        Err(CheckFailure::new(
+            ProgramError::InvalidAccountData,
+            format!(
+                "Invalid token {} mint {}. Expected {}",
+                field_name, token.mint, mint
+            ),
+        ))
     }
 }
 
-pub fn check_token_owner(token: &TokenAccount, owner: &Pubkey, field_name: &str) -> ProgramResult {
+fn token_owner(token: &TokenAccount, owner: &Pubkey, field_name: &str) -> Result<(), CheckFailure> {
     if token.owner == *owner {
         Ok(())
     } else {
-        msg!(
-            "Invalid token account {} owner {}. Expected {}",
-            field_name,
-            token.owner,
-            owner
-        );
-        Err(ProgramError::InvalidAccountData)
+        Err(CheckFailure::new(
+            ProgramError::InvalidAccountData,
+            format!(
+                "Invalid token account {} owner {}. Expected {}",
+                field_name, token.owner, owner
+            ),
+        ))
     }
 }
 
-// check that the account is delegated and to the right validator
-// also that the stake amount is updated
-pub fn check_stake_amount_and_validator(
+fn stake_amount_and_validator(
     stake_state: &StakeState,
     expected_stake_amount: u64,
     validator_vote_pubkey: &Pubkey,
-) -> ProgramResult {
+) -> Result<(), CheckFailure> {
     let currently_staked = if let Some(delegation) = stake_state.delegation() {
         if delegation.voter_pubkey != *validator_vote_pubkey {
-            msg!(
-                "Invalid stake validator index. Need to point into validator {}",
-                validator_vote_pubkey
-            );
-            return Err(ProgramError::InvalidInstructionData);
+            return Err(CheckFailure::new(
+                ProgramError::InvalidInstructionData,
+                format!(
+                    "Invalid stake validator index. Need to point into validator {}",
+                    validator_vote_pubkey
+                ),
+            ));
         }
         delegation.stake
     } else {
-        return Err(CommonError::StakeNotDelegated.into());
+        return Err(CheckFailure::new(
+            CommonError::StakeNotDelegated,
+            "Stake account is not delegated".to_string(),
+        ));
     };
     // do not allow to operate on an account where last_update_delegated_lamports != currently_staked
     if currently_staked != expected_stake_amount {
-        msg!(
-            "Operation on a stake account not yet updated. expected stake:{}, current:{}",
-            expected_stake_amount,
-            currently_staked
-        );
-        return Err(CommonError::StakeAccountNotUpdatedYet.into());
+        return Err(CheckFailure::new(
+            CommonError::StakeAccountNotUpdatedYet,
+            format!(
+                "Operation on a stake account not yet updated. expected stake:{}, current:{}",
+                expected_stake_amount, currently_staked
+            ),
+        ));
     }
     Ok(())
 }
+
+pub fn check_min_amount(amount: u64, min_amount: u64, action_name: &str) -> ProgramResult {
+    logged(self::min_amount(amount, min_amount, action_name))
+}
+
+pub fn check_address(
+    actual_address: &Pubkey,
+    reference_address: &Pubkey,
+    field_name: &str,
+) -> ProgramResult {
+    logged(address(actual_address, reference_address, field_name))
+}
+
+pub fn check_owner_program<'info>(
+    account: &AccountInfo<'info>,
+    owner: &Pubkey,
+    field_name: &str,
+) -> ProgramResult {
+    logged(owner_program(account.owner, owner, field_name))
+}
+
+pub fn check_mint_authority(
+    mint: &Mint,
+    mint_authority: Pubkey,
+    field_name: &str,
+) -> ProgramResult {
+    logged(self::mint_authority(mint, mint_authority, field_name))
+}
+
+pub fn check_freeze_authority(mint: &Mint, field_name: &str) -> ProgramResult {
+    logged(freeze_authority(mint, field_name))
+}
+
+pub fn check_mint_empty(mint: &Mint, field_name: &str) -> ProgramResult {
+    logged(mint_empty(mint, field_name))
+}
+
+pub fn check_token_mint(token: &TokenAccount, mint: Pubkey, field_name: &str) -> ProgramResult {
+    logged(token_mint(token, mint, field_name))
+}
+
+pub fn check_token_owner(token: &TokenAccount, owner: &Pubkey, field_name: &str) -> ProgramResult {
+    logged(token_owner(token, owner, field_name))
+}
+
+// check that the account is delegated and to the right validator
+// also that the stake amount is updated
+pub fn check_stake_amount_and_validator(
+    stake_state: &StakeState,
+    expected_stake_amount: u64,
+    validator_vote_pubkey: &Pubkey,
+) -> ProgramResult {
+    match stake_amount_and_validator(stake_state, expected_stake_amount, validator_vote_pubkey) {
+        // the program returns StakeNotDelegated without logging
+        Err(failure) if failure.error == CommonError::StakeNotDelegated.into() => {
+            Err(failure.error)
+        }
+        result => logged(result),
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CheckMode {
+    /// Skip the checks after the first failure, as the check_* functions and the program do
+    FirstError,
+    AllErrors,
+}
+
+/// Chain of checks, e.g.
+/// `Checks::new(CheckMode::AllErrors).address(..).min_amount(..).finish()`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Checks {
+    pub mode: CheckMode,
+    failures: Vec<CheckFailure>,
+}
+
+impl Checks {
+    pub fn new(mode: CheckMode) -> Self {
+        Self {
+            mode,
+            failures: Vec::new(),
+        }
+    }
+
+    /// Adds the result of a check, e.g. a custom one
+    pub fn check(mut self, check: impl FnOnce() -> Result<(), CheckFailure>) -> Self {
+        if self.mode == CheckMode::AllErrors || self.failures.is_empty() {
+            if let Err(failure) = check() {
+                self.failures.push(failure);
+            }
+        }
+        self
+    }
+
+    pub fn min_amount(self, amount: u64, min_amount: u64, action_name: &str) -> Self {
+        self.check(|| self::min_amount(amount, min_amount, action_name))
+    }
+
+    pub fn address(
+        self,
+        actual_address: &Pubkey,
+        reference_address: &Pubkey,
+        field_name: &str,
+    ) -> Self {
+        self.check(|| address(actual_address, reference_address, field_name))
+    }
+
+    pub fn owner_program(self, account: &AccountInfo, owner: &Pubkey, field_name: &str) -> Self {
+        self.check(|| owner_program(account.owner, owner, field_name))
+    }
+
+    pub fn mint_authority(self, mint: &Mint, mint_authority: Pubkey, field_name: &str) -> Self {
+        self.check(|| self::mint_authority(mint, mint_authority, field_name))
+    }
+
+    pub fn freeze_authority(self, mint: &Mint, field_name: &str) -> Self {
+        self.check(|| freeze_authority(mint, field_name))
+    }
+
+    pub fn mint_empty(self, mint: &Mint, field_name: &str) -> Self {
+        self.check(|| mint_empty(mint, field_name))
+    }
+
+    pub fn token_mint(self, token: &TokenAccount, mint: Pubkey, field_name: &str) -> Self {
+        self.check(|| token_mint(token, mint, field_name))
+    }
+
+    pub fn token_owner(self, token: &TokenAccount, owner: &Pubkey, field_name: &str) -> Self {
+        self.check(|| token_owner(token, owner, field_name))
+    }
+
+    pub fn stake_amount_and_validator(
+        self,
+        stake_state: &StakeState,
+        expected_stake_amount: u64,
+        validator_vote_pubkey: &Pubkey,
+    ) -> Self {
+        self.check(|| {
+            stake_amount_and_validator(stake_state, expected_stake_amount, validator_vote_pubkey)
+        })
+    }
+
+    /// Failures so far, in check order
+    pub fn failures(&self) -> &[CheckFailure] {
+        &self.failures
+    }
+
+    pub fn finish(self) -> Result<(), Vec<CheckFailure>> {
+        if self.failures.is_empty() {
+            Ok(())
+        } else {
+            Err(self.failures)
+        }
+    }
+
+    /// Logs every failure and returns the error of the first one
+    pub fn into_program_result(self) -> ProgramResult {
+        for failure in &self.failures {
+            msg!("{}", failure.message);
+        }
+        match self.failures.into_iter().next() {
+            Some(failure) => Err(failure.error),
+            None => Ok(()),
+        }
+    }
+}
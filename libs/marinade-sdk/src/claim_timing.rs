@@ -0,0 +1,104 @@
+//! When a ticket becomes claimable, as an interval rather than a fixed-duration ETA.
+//! Epochs are a fixed number of slots but slot times vary, so their wall-clock length does too.
+//! The estimate draws on the lengths of recent epochs: given as a series, or measured with
+//! client::fetch_epoch_durations. The quantiles of the series bound the remaining time, and
+//! every future epoch is assumed as slow (or fast) as the same quantile. That makes the
+//! interval wider than treating the epochs as independent, which suits a countdown.
+
+use crate::{state::ticket::TicketAccountData, sysvars::SysvarCache};
+
+/// Wall-clock length of a past epoch
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct EpochDuration {
+    pub epoch: u64,
+    pub seconds: i64,
+}
+
+/// Unix timestamps of the claim, earliest <= expected <= latest
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ClaimTimeEstimate {
+    pub earliest: i64,
+    /// At the median epoch length
+    pub expected: i64,
+    pub latest: i64,
+}
+
+impl ClaimTimeEstimate {
+    fn at(timestamp: i64) -> Self {
+        Self {
+            earliest: timestamp,
+            expected: timestamp,
+            latest: timestamp,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EpochDurationModel {
+    /// Epoch lengths in seconds, sorted
+    seconds: Vec<i64>,
+}
+
+impl EpochDurationModel {
+    /// None without any duration
+    pub fn new<I: IntoIterator<Item = i64>>(seconds: I) -> Option<Self> {
+        let mut seconds: Vec<i64> = seconds.into_iter().collect();
+        if seconds.is_empty() {
+            return None;
+        }
+        seconds.sort_unstable();
+        Some(Self { seconds })
+    }
+
+    pub fn from_durations(durations: &[EpochDuration]) -> Option<Self> {
+        Self::new(durations.iter().map(|duration| duration.seconds))
+    }
+
+    /// Every epoch takes seconds, the naive ETA
+    pub fn fixed(seconds: i64) -> Self {
+        Self {
+            seconds: vec![seconds],
+        }
+    }
+
+    /// Nearest-rank percentile of the epoch lengths, percent in 0..=100
+    pub fn percentile(&self, percent: u8) -> i64 {
+        let rank = (self.seconds.len() * percent.min(100) as usize + 99) / 100;
+        self.seconds[rank.saturating_sub(1)]
+    }
+
+    /// Claim time of a ticket due in due_epoch within the central interval_percent of the
+    /// epoch lengths, e.g. 80 for the 10th to 90th percentile. Includes the wait after the
+    /// start of the due epoch (TicketAccountData::EXTRA_WAIT_SECONDS)
+    pub fn estimate_claim(
+        &self,
+        due_epoch: u64,
+        sysvars: &SysvarCache,
+        interval_percent: u8,
+    ) -> ClaimTimeEstimate {
+        let clock = &sysvars.clock;
+        if TicketAccountData::is_due(due_epoch, clock) {
+            return ClaimTimeEstimate::at(clock.unix_timestamp);
+        }
+        if clock.epoch >= due_epoch {
+            return ClaimTimeEstimate::at(
+                clock.epoch_start_timestamp + TicketAccountData::EXTRA_WAIT_SECONDS,
+            );
+        }
+        // later epochs are assumed to have as many slots as the current one
+        let position = sysvars.epoch_position();
+        let slots_in_epoch = position.slots_in_epoch.max(1) as i128;
+        let slots_left = (position.slots_in_epoch.saturating_sub(position.slot_index) as i128)
+            + (due_epoch - clock.epoch - 1) as i128 * slots_in_epoch;
+        let claim_at = |epoch_seconds: i64| {
+            let seconds = epoch_seconds as i128 * slots_left / slots_in_epoch;
+            clock.unix_timestamp + seconds as i64 + TicketAccountData::EXTRA_WAIT_SECONDS
+        };
+        let tail = (100 - interval_percent.min(100)) / 2;
+        ClaimTimeEstimate {
+            earliest: claim_at(self.percentile(tail)),
+            expected: claim_at(self.percentile(50)),
+            latest: claim_at(self.percentile(100 - tail)),
+        }
+    }
+}
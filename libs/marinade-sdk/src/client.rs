@@ -0,0 +1,1422 @@
+//! RPC access to Marinade accounts.
+//! The validator and stake lists are 100KB+ accounts; they are requested zstd compressed
+//! from nodes supporting it, which RpcClient decompresses transparently.
+//! Providers truncating large responses are detected from the record counts of the state,
+//! the lists are then fetched again in dataSlice chunks (fetch_account_data_chunked) pinned to
+//! the slot the other accounts were read at.
+//! The nonblocking module (feature async) has the same loader for tokio based services.
+//! MarinadeClient never holds keys and ReadClient does not even expose its RpcClient; sending
+//! goes through SignerClient, which wraps a client together with the signer.
+
+#[cfg(feature = "rate-limit")]
+use std::sync::{Arc, Mutex};
+use std::{
+    ops::{Deref, DerefMut},
+    time::{Duration, Instant},
+};
+
+use derive_more::{Display, From};
+use micro_anchor::{AccountDeserialize, AccountDeserializeError};
+use solana_account_decoder::{UiAccountEncoding, UiDataSliceConfig};
+use solana_client::{
+    client_error::{ClientError, ClientErrorKind},
+    rpc_client::RpcClient,
+    rpc_config::{RpcAccountInfoConfig, RpcProgramAccountsConfig, RpcSimulateTransactionConfig},
+    rpc_request::RpcError,
+};
+use solana_program::{
+    clock::Clock,
+    hash::{hash, Hash},
+    instruction::Instruction,
+    program_error::ProgramError,
+    program_pack::Pack,
+    pubkey::Pubkey,
+    sysvar::Sysvar,
+};
+use solana_sdk::{
+    account::{from_account, Account},
+    commitment_config::CommitmentConfig,
+    epoch_info::EpochInfo,
+    message::Message,
+    signature::Signature,
+    signer::Signer,
+    transaction::{Transaction, TransactionError},
+};
+
+use crate::{
+    capabilities::{Capabilities, Capability},
+    claim_timing::EpochDuration,
+    error::CommonError,
+    filters,
+    health::{Health, StateHealth},
+    located::{KeyedAccount, Located},
+    program::MarinadeProgram,
+    quote::{
+        quote_deposit_with_liq_pool, quote_max_liquid_unstake, quote_order_unstake, DepositQuote,
+        LiqPoolLegs, MaxLiquidUnstake, OrderUnstakeQuote, Quote, StaleQuote,
+    },
+    required_accounts::{
+        addresses, program_state_accounts, AccountRole, Operation, RequiredAccount,
+    },
+    state::{
+        delayed_unstake_ticket::DelayedUnstakeTicket,
+        fee::Fee,
+        list::List,
+        marinade::{Marinade, MarinadeExtension},
+        stake_system::StakeRecord,
+        validator_system::ValidatorRecord,
+    },
+    sysvars::SysvarCache,
+    ticket::plan_claims,
+};
+
+#[cfg(feature = "rate-limit")]
+use crate::rate_limit::{EndpointCounters, EndpointPool, Limiter};
+
+#[cfg(feature = "async")]
+pub mod nonblocking;
+
+#[derive(Debug, Display, From)]
+pub enum FetchError {
+    Client(ClientError),
+    Deserialize(AccountDeserializeError),
+    /// list, token or sysvar account data not matching the expected layout
+    Program(ProgramError),
+    #[display(fmt = "Account {} not found", _0)]
+    #[from(ignore)]
+    AccountNotFound(Pubkey),
+    /// Reassembled or fetched data not matching the expected length or hash
+    #[display(fmt = "Account {} failed the {} check", _0, _1)]
+    #[from(ignore)]
+    Integrity(Pubkey, &'static str),
+    /// Capability probe failing before the program ran, e.g. a payer without lamports
+    #[display(fmt = "Probe simulation failed: {}", _0)]
+    #[from(ignore)]
+    Simulation(TransactionError),
+    /// No endpoint of the client's rate limit has budget for the request, the wait until one
+    /// has (Duration::MAX when none ever will)
+    #[display(fmt = "Rate limited, retry in {:?}", _0)]
+    #[from(ignore)]
+    RateLimited(Duration),
+    /// send_quoted refusing a quote older than its max_age
+    StaleQuote(StaleQuote),
+    /// send_quoted refusing a quote of another state than the client's
+    #[display(fmt = "Quote is for state {} not {}", _0, _1)]
+    #[from(ignore)]
+    QuoteStateMismatch(Pubkey, Pubkey),
+    /// execute_recovery failing to send or confirm a transaction, with the signatures of the
+    /// transactions confirmed before it
+    #[display(fmt = "Recovery transaction {} failed: {}", "confirmed.len()", error)]
+    #[from(ignore)]
+    Recovery {
+        confirmed: Vec<Signature>,
+        error: ClientError,
+    },
+}
+
+impl std::error::Error for FetchError {}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AccountEncoding {
+    Base64,
+    /// Falls back to Base64 when the node rejects it
+    Base64Zstd,
+}
+
+impl AccountEncoding {
+    /// Small accounts do not gain from compression
+    pub fn for_len(expected_len: usize) -> Self {
+        if expected_len >= 10 * 1024 {
+            AccountEncoding::Base64Zstd
+        } else {
+            AccountEncoding::Base64
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FetchedAccount {
+    pub lamports: u64,
+    pub owner: Pubkey,
+    pub data: Vec<u8>,
+}
+
+/// JSON-RPC error code of requests with parameters the node does not accept
+const INVALID_PARAMS: i64 = -32602;
+
+/// Whether err is a node rejecting the base64+zstd encoding (nodes built without zstd answer
+/// "unknown variant `base64+zstd`"), the one error the fetches retry in plain base64
+pub fn is_unsupported_encoding(err: &ClientError) -> bool {
+    matches!(
+        err.kind(),
+        ClientErrorKind::RpcError(RpcError::RpcResponseError { code, message, .. })
+            if *code == INVALID_PARAMS && message.contains("zstd")
+    )
+}
+
+pub(crate) fn account_config(
+    commitment: CommitmentConfig,
+    encoding: UiAccountEncoding,
+) -> RpcAccountInfoConfig {
+    RpcAccountInfoConfig {
+        encoding: Some(encoding),
+        commitment: Some(commitment),
+        ..RpcAccountInfoConfig::default()
+    }
+}
+
+/// Accounts in the order of addresses with the slot they were read at
+fn get_accounts(
+    rpc: &RpcClient,
+    addresses: &[Pubkey],
+    encoding: UiAccountEncoding,
+) -> Result<(u64, Vec<Option<FetchedAccount>>), ClientError> {
+    let response = rpc
+        .get_multiple_accounts_with_config(addresses, account_config(rpc.commitment(), encoding))?;
+    let accounts = response
+        .value
+        .into_iter()
+        .map(|account| {
+            account.map(|account| FetchedAccount {
+                lamports: account.lamports,
+                owner: account.owner,
+                data: account.data,
+            })
+        })
+        .collect();
+    Ok((response.context.slot, accounts))
+}
+
+/// Up to 100 accounts (the RPC limit) read at the same slot, None for missing ones
+pub fn fetch_accounts(
+    rpc: &RpcClient,
+    addresses: &[Pubkey],
+    encoding: AccountEncoding,
+) -> Result<(u64, Vec<Option<FetchedAccount>>), ClientError> {
+    match encoding {
+        AccountEncoding::Base64 => get_accounts(rpc, addresses, UiAccountEncoding::Base64),
+        AccountEncoding::Base64Zstd => {
+            match get_accounts(rpc, addresses, UiAccountEncoding::Base64Zstd) {
+                Err(err) if is_unsupported_encoding(&err) => {
+                    get_accounts(rpc, addresses, UiAccountEncoding::Base64)
+                }
+                result => result,
+            }
+        }
+    }
+}
+
+/// Account data, None if the account does not exist
+pub fn fetch_account_data(
+    rpc: &RpcClient,
+    address: &Pubkey,
+    encoding: AccountEncoding,
+) -> Result<Option<Vec<u8>>, ClientError> {
+    let (_, mut accounts) = fetch_accounts(rpc, &[*address], encoding)?;
+    Ok(accounts.pop().flatten().map(|account| account.data))
+}
+
+/// dataSlice length of fetch_account_data_chunked when fetch_program_state falls back to it
+pub const DEFAULT_CHUNK_LEN: usize = 128 * 1024;
+
+/// Checks reassembled account data against its expected length and, when known, its sha256
+pub fn check_integrity(
+    address: &Pubkey,
+    data: &[u8],
+    expected_len: usize,
+    expected_hash: Option<&Hash>,
+) -> Result<(), FetchError> {
+    if data.len() != expected_len {
+        return Err(FetchError::Integrity(*address, "length"));
+    }
+    match expected_hash {
+        Some(expected_hash) if hash(data) != *expected_hash => {
+            Err(FetchError::Integrity(*address, "hash"))
+        }
+        _ => Ok(()),
+    }
+}
+
+pub(crate) fn chunk_config(
+    commitment: CommitmentConfig,
+    offset: usize,
+    length: usize,
+    min_context_slot: Option<u64>,
+) -> RpcAccountInfoConfig {
+    RpcAccountInfoConfig {
+        data_slice: Some(UiDataSliceConfig { offset, length }),
+        min_context_slot,
+        ..account_config(commitment, UiAccountEncoding::Base64)
+    }
+}
+
+/// Chunk loop of fetch_account_data_chunked, shared with the nonblocking client:
+/// next_config gives the dataSlice request to send, push takes its response
+pub(crate) struct ChunkedRead<'a> {
+    address: &'a Pubkey,
+    expected_len: usize,
+    chunk_len: usize,
+    min_context_slot: Option<u64>,
+    /// Every chunk must be read at min_context_slot
+    pinned: bool,
+    data: Vec<u8>,
+}
+
+impl<'a> ChunkedRead<'a> {
+    pub(crate) fn new(
+        address: &'a Pubkey,
+        expected_len: usize,
+        chunk_len: usize,
+        slot: Option<u64>,
+    ) -> Self {
+        Self {
+            address,
+            expected_len,
+            chunk_len: chunk_len.max(1),
+            min_context_slot: slot,
+            pinned: slot.is_some(),
+            data: Vec::with_capacity(expected_len),
+        }
+    }
+
+    fn next_len(&self) -> usize {
+        self.chunk_len.min(self.expected_len - self.data.len())
+    }
+
+    /// None once every chunk is read
+    pub(crate) fn next_config(&self, commitment: CommitmentConfig) -> Option<RpcAccountInfoConfig> {
+        (self.data.len() < self.expected_len).then(|| {
+            chunk_config(
+                commitment,
+                self.data.len(),
+                self.next_len(),
+                self.min_context_slot,
+            )
+        })
+    }
+
+    pub(crate) fn push(
+        &mut self,
+        context_slot: u64,
+        account: Option<Account>,
+    ) -> Result<(), FetchError> {
+        if self.pinned && self.min_context_slot != Some(context_slot) {
+            return Err(FetchError::Integrity(*self.address, "chunk slot"));
+        }
+        let chunk = account
+            .ok_or(FetchError::AccountNotFound(*self.address))?
+            .data;
+        if chunk.len() != self.next_len() {
+            return Err(FetchError::Integrity(*self.address, "chunk length"));
+        }
+        self.min_context_slot = Some(context_slot);
+        self.data.extend(chunk);
+        Ok(())
+    }
+
+    pub(crate) fn finish(self, expected_hash: Option<&Hash>) -> Result<Vec<u8>, FetchError> {
+        check_integrity(self.address, &self.data, self.expected_len, expected_hash)?;
+        Ok(self.data)
+    }
+}
+
+/// The first expected_len bytes of the account data in chunk_len dataSlice requests.
+/// With slot, every chunk is requested at and must be read at that slot, e.g. the slot of the
+/// other accounts read with it; nodes past the slot fail the chunk slot check and the read has
+/// to start over. Without it every chunk is read at a slot no older than the previous one, the
+/// chunks are then not consistent with each other, pass expected_hash when they must be
+pub fn fetch_account_data_chunked(
+    rpc: &RpcClient,
+    address: &Pubkey,
+    expected_len: usize,
+    chunk_len: usize,
+    slot: Option<u64>,
+    expected_hash: Option<&Hash>,
+) -> Result<Vec<u8>, FetchError> {
+    let mut read = ChunkedRead::new(address, expected_len, chunk_len, slot);
+    while let Some(config) = read.next_config(rpc.commitment()) {
+        let response = rpc.get_account_with_config(address, config)?;
+        read.push(response.context.slot, response.value)?;
+    }
+    read.finish(expected_hash)
+}
+
+/// Bytes of a list account holding its records, the account itself may be larger
+pub fn list_data_len(list: &List) -> usize {
+    List::bytes_for(list.item_size(), list.len()) as usize
+}
+
+/// (position in program_state_addresses, needed length) of the lists fetched shorter
+/// than their records, e.g. truncated by the provider
+pub(crate) fn truncated_lists(
+    state: &KeyedAccount<Marinade>,
+    accounts: &[Option<FetchedAccount>],
+) -> Vec<(usize, usize)> {
+    let marinade = &state.account;
+    [
+        list_data_len(&marinade.validator_system.validator_list),
+        list_data_len(&marinade.stake_system.stake_list),
+    ]
+    .into_iter()
+    .enumerate()
+    .filter(|(index, len)| {
+        matches!(accounts.get(*index), Some(Some(account)) if account.data.len() < *len)
+    })
+    .collect()
+}
+
+fn check_list_len(address: &Pubkey, data: &[u8], list: &List) -> Result<(), FetchError> {
+    if data.len() < list_data_len(list) {
+        return Err(FetchError::Integrity(*address, "list length"));
+    }
+    Ok(())
+}
+
+/// The state with everything its quotes and builders read, fetched at the same slot
+#[derive(Clone, Debug)]
+pub struct MarinadeProgramState {
+    pub key: Pubkey,
+    /// Deployment owning the state, the PDAs and instructions of the state target its id
+    pub program: MarinadeProgram,
+    pub state: Marinade,
+    /// Fields of newer program versions, default for older deployments
+    pub extension: MarinadeExtension,
+    pub validators: Vec<ValidatorRecord>,
+    pub stakes: Vec<StakeRecord>,
+    pub liq_pool_sol_leg_lamports: u64,
+    pub liq_pool_msol_leg_amount: u64,
+    /// Slot the accounts besides the state were read at
+    pub slot: u64,
+}
+
+impl MarinadeProgramState {
+    /// Deposit split between the fetched liq pool mSOL leg and minting
+    pub fn quote_deposit(&self, lamports: u64) -> Result<Quote<DepositQuote>, ProgramError> {
+        quote_deposit_with_liq_pool(self, self.slot, &self.liq_pool_legs(), lamports)
+    }
+
+    /// Ticket of order_unstake after the delayed unstake fee of the deployment
+    pub fn quote_order_unstake(
+        &self,
+        msol_amount: u64,
+    ) -> Result<Quote<OrderUnstakeQuote>, CommonError> {
+        quote_order_unstake(
+            self,
+            self.slot,
+            msol_amount,
+            self.extension.delayed_unstake_fee,
+        )
+    }
+
+    /// Largest liquid_unstake at a fee of at most max_fee with the fetched SOL leg,
+    /// with its fee schedule in tranches (see max_liquid_unstake)
+    pub fn max_liquid_unstake_now(
+        &self,
+        max_fee: Fee,
+        tranches: u32,
+    ) -> Result<Quote<MaxLiquidUnstake>, CommonError> {
+        let max_lamports = self.liq_pool_legs().sol_leg_balance;
+        quote_max_liquid_unstake(self, self.slot, max_lamports, max_fee, tranches)
+    }
+
+    /// The fetched liq pool balances, the SOL leg without its rent exempt reserve
+    pub fn liq_pool_legs(&self) -> LiqPoolLegs {
+        LiqPoolLegs {
+            sol_leg_balance: self
+                .liq_pool_sol_leg_lamports
+                .saturating_sub(self.state.rent_exempt_for_token_acc),
+            msol_leg_balance: self.liq_pool_msol_leg_amount,
+        }
+    }
+}
+
+impl Located<Marinade> for MarinadeProgramState {
+    fn as_ref(&self) -> &Marinade {
+        &self.state
+    }
+
+    fn as_mut(&mut self) -> &mut Marinade {
+        &mut self.state
+    }
+
+    fn key(&self) -> Pubkey {
+        self.key
+    }
+
+    fn program_id(&self) -> Pubkey {
+        self.program.program_id
+    }
+}
+
+fn required(
+    address: &Pubkey,
+    account: Option<FetchedAccount>,
+) -> Result<FetchedAccount, FetchError> {
+    account.ok_or(FetchError::AccountNotFound(*address))
+}
+
+pub(crate) fn decode_state(
+    state_address: &Pubkey,
+    data: Option<Vec<u8>>,
+) -> Result<(KeyedAccount<Marinade>, MarinadeExtension), FetchError> {
+    let data = data.ok_or(FetchError::AccountNotFound(*state_address))?;
+    let state = Marinade::try_deserialize(&mut data.as_slice())?;
+    Ok((
+        KeyedAccount::new(*state_address, state),
+        MarinadeExtension::from_account_data(&data),
+    ))
+}
+
+/// validator list, stake list, liq-pool SOL and mSOL legs
+pub(crate) fn program_state_addresses(
+    program: &MarinadeProgram,
+    state: &KeyedAccount<Marinade>,
+) -> Result<Vec<Pubkey>, FetchError> {
+    Ok(program_state_accounts(&program.locate(state.clone()))
+        .map_err(ProgramError::from)?
+        .iter()
+        .map(|account| account.address)
+        .collect())
+}
+
+/// accounts fetched at program_state_addresses
+pub(crate) fn program_state_from_accounts(
+    program: &MarinadeProgram,
+    state: KeyedAccount<Marinade>,
+    extension: MarinadeExtension,
+    addresses: &[Pubkey],
+    slot: u64,
+    accounts: Vec<Option<FetchedAccount>>,
+) -> Result<MarinadeProgramState, FetchError> {
+    let mut accounts = addresses
+        .iter()
+        .zip(accounts)
+        .map(|(address, account)| required(address, account));
+    let mut next = || {
+        accounts
+            .next()
+            .unwrap_or(Err(FetchError::AccountNotFound(Pubkey::default())))
+    };
+    let validator_list = next()?;
+    let stake_list = next()?;
+    let sol_leg = next()?;
+    let msol_leg = next()?;
+
+    let marinade = &state.account;
+    check_list_len(
+        &addresses[0],
+        &validator_list.data,
+        &marinade.validator_system.validator_list,
+    )?;
+    check_list_len(
+        &addresses[1],
+        &stake_list.data,
+        &marinade.stake_system.stake_list,
+    )?;
+    let validators = (0..marinade.validator_system.validator_list.len())
+        .map(|index| marinade.validator_system.get(&validator_list.data, index))
+        .collect::<Result<_, _>>()?;
+    let stakes = (0..marinade.stake_system.stake_list.len())
+        .map(|index| marinade.stake_system.get(&stake_list.data, index))
+        .collect::<Result<_, _>>()?;
+    let msol_leg = spl_token::state::Account::unpack(&msol_leg.data)?;
+    Ok(MarinadeProgramState {
+        key: state.key,
+        program: *program,
+        state: state.account,
+        extension,
+        validators,
+        stakes,
+        liq_pool_sol_leg_lamports: sol_leg.lamports,
+        liq_pool_msol_leg_amount: msol_leg.amount,
+        slot,
+    })
+}
+
+/// State account first, then the lists and liq-pool legs in one getMultipleAccounts call.
+/// program is the deployment owning the state, MarinadeProgram::default() for crate::ID
+pub fn fetch_program_state(
+    rpc: &RpcClient,
+    program: &MarinadeProgram,
+    state_address: &Pubkey,
+) -> Result<MarinadeProgramState, FetchError> {
+    let (state, extension) = decode_state(
+        state_address,
+        fetch_account_data(rpc, state_address, AccountEncoding::Base64)?,
+    )?;
+    let addresses = program_state_addresses(program, &state)?;
+    let (slot, mut accounts) = fetch_accounts(rpc, &addresses, AccountEncoding::Base64Zstd)?;
+    for (index, len) in truncated_lists(&state, &accounts) {
+        let data = fetch_account_data_chunked(
+            rpc,
+            &addresses[index],
+            len,
+            DEFAULT_CHUNK_LEN,
+            Some(slot),
+            None,
+        )?;
+        if let Some(account) = &mut accounts[index] {
+            account.data = data;
+        }
+    }
+    program_state_from_accounts(program, state, extension, &addresses, slot, accounts)
+}
+
+/// The accounts one operation reads (see required_accounts::Operation), the ones besides the
+/// state read at one slot
+#[derive(Clone, Debug)]
+pub struct OperationAccounts {
+    pub state: KeyedAccount<Marinade>,
+    /// Deployment the required accounts were derived for, locate state with it to build the
+    /// instruction
+    pub program: MarinadeProgram,
+    pub extension: MarinadeExtension,
+    /// Slot the accounts besides the state were read at
+    pub slot: u64,
+    /// The required accounts but the state, None when the account does not exist, e.g. a token
+    /// account not created yet
+    pub accounts: Vec<(RequiredAccount, Option<FetchedAccount>)>,
+}
+
+impl OperationAccounts {
+    /// The account of role, the first one if the operation reads several
+    pub fn get(&self, role: AccountRole) -> Option<&FetchedAccount> {
+        self.accounts
+            .iter()
+            .find(|(required, _)| required.role == role)
+            .and_then(|(_, account)| account.as_ref())
+    }
+}
+
+/// Accounts of operation besides the state, to fetch at once
+pub(crate) fn operation_required_accounts(
+    program: &MarinadeProgram,
+    state: &KeyedAccount<Marinade>,
+    operation: &Operation,
+) -> Result<Vec<RequiredAccount>, FetchError> {
+    let mut required = operation
+        .required_accounts(&program.locate(state.clone()))
+        .map_err(ProgramError::from)?;
+    required.retain(|account| account.role != AccountRole::State);
+    Ok(required)
+}
+
+/// accounts fetched at addresses(&required)
+pub(crate) fn operation_accounts(
+    program: &MarinadeProgram,
+    state: KeyedAccount<Marinade>,
+    extension: MarinadeExtension,
+    required: Vec<RequiredAccount>,
+    slot: u64,
+    accounts: Vec<Option<FetchedAccount>>,
+) -> OperationAccounts {
+    let fetched: Vec<(Pubkey, Option<FetchedAccount>)> =
+        addresses(&required).into_iter().zip(accounts).collect();
+    let accounts = required
+        .into_iter()
+        .map(|required| {
+            let account = fetched
+                .iter()
+                .find(|(address, _)| *address == required.address)
+                .and_then(|(_, account)| account.clone());
+            (required, account)
+        })
+        .collect();
+    OperationAccounts {
+        state,
+        program: *program,
+        extension,
+        slot,
+        accounts,
+    }
+}
+
+/// Only what operation reads: the state, then its other required accounts in one request.
+/// Cheaper than fetch_program_state for a single user operation
+pub fn fetch_operation_accounts(
+    rpc: &RpcClient,
+    program: &MarinadeProgram,
+    state_address: &Pubkey,
+    operation: &Operation,
+) -> Result<OperationAccounts, FetchError> {
+    let (state, extension) = decode_state(
+        state_address,
+        fetch_account_data(rpc, state_address, AccountEncoding::Base64)?,
+    )?;
+    let required = operation_required_accounts(program, &state, operation)?;
+    let (slot, accounts) = fetch_accounts(rpc, &addresses(&required), AccountEncoding::Base64Zstd)?;
+    Ok(operation_accounts(
+        program, state, extension, required, slot, accounts,
+    ))
+}
+
+pub(crate) fn decode_sysvar<S: Sysvar>(account: Option<FetchedAccount>) -> Result<S, FetchError> {
+    let account = required(&S::id(), account)?;
+    from_account(&Account {
+        lamports: account.lamports,
+        data: account.data,
+        owner: account.owner,
+        executable: false,
+        rent_epoch: 0,
+    })
+    .ok_or(FetchError::Program(ProgramError::InvalidAccountData))
+}
+
+/// accounts fetched at SysvarCache::addresses
+pub(crate) fn sysvars_from_accounts(
+    accounts: Vec<Option<FetchedAccount>>,
+) -> Result<SysvarCache, FetchError> {
+    let mut accounts = accounts.into_iter();
+    Ok(SysvarCache {
+        clock: decode_sysvar(accounts.next().flatten())?,
+        epoch_schedule: decode_sysvar(accounts.next().flatten())?,
+        rent: decode_sysvar(accounts.next().flatten())?,
+        stake_history: decode_sysvar(accounts.next().flatten())?,
+    })
+}
+
+/// One sysvar, e.g. `fetch_sysvar::<Clock>(&rpc)`
+pub fn fetch_sysvar<S: Sysvar>(rpc: &RpcClient) -> Result<S, FetchError> {
+    let (_, mut accounts) = fetch_accounts(rpc, &[S::id()], AccountEncoding::Base64)?;
+    decode_sysvar(accounts.pop().flatten())
+}
+
+/// Clock, epoch schedule, rent and stake history read at the same slot
+pub fn fetch_sysvars(rpc: &RpcClient) -> Result<SysvarCache, FetchError> {
+    let (_, accounts) = fetch_accounts(rpc, &SysvarCache::addresses(), AccountEncoding::Base64)?;
+    sysvars_from_accounts(accounts)
+}
+
+pub(crate) fn probe_transaction(
+    program: &MarinadeProgram,
+    capability: Capability,
+    payer: &Pubkey,
+) -> Transaction {
+    Transaction::new_unsigned(Message::new(
+        &[capability.probe(program.program_id)],
+        Some(payer),
+    ))
+}
+
+pub(crate) fn probe_config(commitment: CommitmentConfig) -> RpcSimulateTransactionConfig {
+    RpcSimulateTransactionConfig {
+        sig_verify: false,
+        replace_recent_blockhash: true,
+        commitment: Some(commitment),
+        ..RpcSimulateTransactionConfig::default()
+    }
+}
+
+/// Whether the probed instruction is known to the program, from the simulation error
+pub fn probe_supported(err: Option<TransactionError>) -> Result<bool, FetchError> {
+    match err {
+        None => Ok(true),
+        Some(TransactionError::InstructionError(0, error)) => {
+            Ok(Capability::is_supported_by(&error))
+        }
+        Some(err) => Err(FetchError::Simulation(err)),
+    }
+}
+
+/// Capabilities of the program deployed at program.program_id, one simulated probe per
+/// capability. Nothing is sent; payer only needs lamports for the simulated fee
+pub fn detect_capabilities(
+    rpc: &RpcClient,
+    program: &MarinadeProgram,
+    payer: &Pubkey,
+) -> Result<Capabilities, FetchError> {
+    let mut capabilities = Capabilities::default();
+    for capability in Capability::ALL {
+        let response = rpc.simulate_transaction_with_config(
+            &probe_transaction(program, capability, payer),
+            probe_config(rpc.commitment()),
+        )?;
+        if probe_supported(response.value.err)? {
+            capabilities.insert(capability);
+        }
+    }
+    Ok(capabilities)
+}
+
+pub(crate) fn tickets_config(
+    commitment: CommitmentConfig,
+    state: &Pubkey,
+    beneficiary: &Pubkey,
+) -> RpcProgramAccountsConfig {
+    RpcProgramAccountsConfig {
+        filters: Some(filters::tickets_of(state, beneficiary)),
+        account_config: account_config(commitment, UiAccountEncoding::Base64),
+        ..RpcProgramAccountsConfig::default()
+    }
+}
+
+pub(crate) fn decode_tickets(
+    accounts: Vec<(Pubkey, Account)>,
+) -> Result<Vec<(Pubkey, DelayedUnstakeTicket)>, FetchError> {
+    accounts
+        .into_iter()
+        .map(|(address, account)| {
+            Ok((
+                address,
+                DelayedUnstakeTicket::try_deserialize(&mut account.data.as_slice())?,
+            ))
+        })
+        .collect()
+}
+
+/// Every ticket of beneficiary at the state, claimed or not yet due included
+pub fn fetch_tickets<T: Located<Marinade>>(
+    rpc: &RpcClient,
+    state: &T,
+    beneficiary: &Pubkey,
+) -> Result<Vec<(Pubkey, DelayedUnstakeTicket)>, FetchError> {
+    decode_tickets(rpc.get_program_accounts_with_config(
+        &state.program_id(),
+        tickets_config(rpc.commitment(), &state.key(), beneficiary),
+    )?)
+}
+
+/// Claims of every matured ticket of beneficiary, one instruction list per transaction,
+/// see ticket::plan_claims
+pub fn claim_all<T: Located<Marinade>>(
+    rpc: &RpcClient,
+    state: &T,
+    beneficiary: &Pubkey,
+) -> Result<Vec<Vec<Instruction>>, FetchError> {
+    let tickets = fetch_tickets(rpc, state, beneficiary)?;
+    let clock = fetch_sysvar::<Clock>(rpc)?;
+    Ok(plan_claims(state, beneficiary, &tickets, &clock).map_err(ProgramError::from)?)
+}
+
+fn send_recovery_transaction(
+    rpc: &RpcClient,
+    instructions: &[Instruction],
+    payer: &dyn Signer,
+) -> Result<Signature, ClientError> {
+    let transaction = Transaction::new_signed_with_payer(
+        instructions,
+        Some(&payer.pubkey()),
+        &[payer],
+        rpc.get_latest_blockhash()?,
+    );
+    rpc.send_and_confirm_transaction(&transaction)
+}
+
+/// Sends the recovery transactions of the watchdog (watchdog::recovery_instructions) one
+/// after the other, payer paying the fees. Stops at the first failure with
+/// FetchError::Recovery; updates already confirmed stay applied and the next check plans
+/// only what is left
+pub fn execute_recovery(
+    rpc: &RpcClient,
+    transactions: &[Vec<Instruction>],
+    payer: &dyn Signer,
+) -> Result<Vec<Signature>, FetchError> {
+    let mut signatures = Vec::new();
+    for instructions in transactions {
+        match send_recovery_transaction(rpc, instructions, payer) {
+            Ok(signature) => signatures.push(signature),
+            Err(error) => {
+                return Err(FetchError::Recovery {
+                    confirmed: signatures,
+                    error,
+                })
+            }
+        }
+    }
+    Ok(signatures)
+}
+
+/// Block time of the first block at or after slot
+fn first_block_time(rpc: &RpcClient, slot: u64) -> Result<i64, FetchError> {
+    let block = rpc
+        .get_blocks_with_limit(slot, 1)?
+        .first()
+        .copied()
+        .unwrap_or(slot);
+    Ok(rpc.get_block_time(block)?)
+}
+
+/// Lengths of the count epochs before the current one, oldest first, measured between the
+/// block times of their first blocks. Nodes without the older blocks fail with a client error
+pub fn fetch_epoch_durations(
+    rpc: &RpcClient,
+    count: u64,
+) -> Result<Vec<EpochDuration>, FetchError> {
+    let current = rpc.get_epoch_info()?.epoch;
+    let schedule = rpc.get_epoch_schedule()?;
+    let first = current.saturating_sub(count);
+    let starts = (first..=current)
+        .map(|epoch| first_block_time(rpc, schedule.get_first_slot_in_epoch(epoch)))
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok((first..current)
+        .zip(starts.windows(2))
+        .map(|(epoch, window)| EpochDuration {
+            epoch,
+            seconds: window[1] - window[0],
+        })
+        .collect())
+}
+
+/// Health from the epoch info of the node (requested in rpc_latency) and the state in use
+pub(crate) fn health(
+    epoch_info: Result<EpochInfo, ClientError>,
+    rpc_latency: Duration,
+    state: Result<&MarinadeProgramState, FetchError>,
+) -> Health {
+    let unhealthy = |error: String| Health {
+        rpc_error: Some(error),
+        rpc_latency,
+        state: None,
+    };
+    match (epoch_info, state) {
+        (Err(err), _) => unhealthy(err.to_string()),
+        (_, Err(err)) => unhealthy(err.to_string()),
+        (Ok(epoch_info), Ok(state)) => Health {
+            rpc_error: None,
+            rpc_latency,
+            state: Some(StateHealth::new(
+                &state.state,
+                &state.extension,
+                &state.stakes,
+                state.slot,
+                epoch_info.absolute_slot,
+                epoch_info.epoch,
+            )),
+        },
+    }
+}
+
+/// Client a request goes through: the client's own one or, with a rate limit, the one of the
+/// endpoint the request spilled over to
+pub(crate) enum Route<'a, C> {
+    Primary(&'a C),
+    #[cfg(feature = "rate-limit")]
+    Secondary(Arc<C>),
+}
+
+impl<'a, C> Deref for Route<'a, C> {
+    type Target = C;
+
+    fn deref(&self) -> &C {
+        match self {
+            Route::Primary(client) => client,
+            #[cfg(feature = "rate-limit")]
+            Route::Secondary(client) => client,
+        }
+    }
+}
+
+/// Requests counted against the rate limit: the state and list accounts of a refresh (chunked
+/// list reads go to the same endpoint uncounted), the sysvars, the epoch info of health and
+/// the ticket search of claim_all
+pub(crate) const STATE_REQUESTS: u32 = 2;
+pub(crate) const SYSVARS_REQUESTS: u32 = 1;
+pub(crate) const HEALTH_REQUESTS: u32 = 1;
+pub(crate) const TICKETS_REQUESTS: u32 = 1;
+
+pub(crate) fn check_quote_state<T>(
+    quote: &Quote<T>,
+    state_address: &Pubkey,
+) -> Result<(), FetchError> {
+    if quote.state_address != *state_address {
+        return Err(FetchError::QuoteStateMismatch(
+            quote.state_address,
+            *state_address,
+        ));
+    }
+    Ok(())
+}
+
+/// Keeps the last fetched program state and sysvars for max_age
+pub struct MarinadeClient {
+    rpc: RpcClient,
+    pub state_address: Pubkey,
+    /// Deployment owning the state, crate::ID unless set for a fork. The fetched state, its
+    /// PDAs, ticket search and instructions and detect_capabilities all use it
+    pub program: MarinadeProgram,
+    pub max_age: Duration,
+    cached: Option<(Instant, MarinadeProgramState)>,
+    cached_sysvars: Option<(Instant, SysvarCache)>,
+    #[cfg(feature = "rate-limit")]
+    limiter: Option<Mutex<Limiter<RpcClient>>>,
+}
+
+impl MarinadeClient {
+    pub const DEFAULT_MAX_AGE: Duration = Duration::from_secs(10);
+
+    pub fn new(rpc: RpcClient, state_address: Pubkey) -> Self {
+        Self {
+            rpc,
+            state_address,
+            program: MarinadeProgram::default(),
+            max_age: Self::DEFAULT_MAX_AGE,
+            cached: None,
+            cached_sysvars: None,
+            #[cfg(feature = "rate-limit")]
+            limiter: None,
+        }
+    }
+
+    /// The node the client reads from and sends to
+    pub fn rpc(&self) -> &RpcClient {
+        &self.rpc
+    }
+
+    /// Sends the reads through pool, waiting for budget and spilling over to its secondary
+    /// endpoints. List the url of rpc in the pool to give it a budget, requests to it otherwise
+    /// never go out. Sending transactions is not limited
+    #[cfg(feature = "rate-limit")]
+    pub fn with_rate_limit(mut self, pool: EndpointPool) -> Self {
+        self.limiter = Some(Mutex::new(Limiter::new(pool)));
+        self
+    }
+
+    /// Counters of the endpoints of the rate limit, empty without one
+    #[cfg(feature = "rate-limit")]
+    pub fn rate_limit_counters(&self) -> Vec<(String, EndpointCounters)> {
+        let limiter = match &self.limiter {
+            Some(limiter) => limiter.lock().unwrap(),
+            None => return vec![],
+        };
+        limiter
+            .pool
+            .counters()
+            .into_iter()
+            .map(|(url, counters)| (url.to_string(), counters))
+            .collect()
+    }
+
+    /// Client of the endpoint with budget for requests
+    pub(crate) fn route(&self, requests: u32) -> Result<Route<'_, RpcClient>, FetchError> {
+        #[cfg(feature = "rate-limit")]
+        if let Some(limiter) = &self.limiter {
+            let mut limiter = limiter.lock().unwrap();
+            let url = limiter
+                .pool
+                .acquire_blocking(requests)
+                .map(str::to_string)
+                .ok_or(FetchError::RateLimited(Duration::MAX))?;
+            let commitment = self.rpc.commitment();
+            let connect = |url| RpcClient::new_with_commitment(url, commitment);
+            if let Some(rpc) = limiter.secondary(&url, &self.rpc.url(), connect) {
+                return Ok(Route::Secondary(rpc));
+            }
+        }
+        #[cfg(not(feature = "rate-limit"))]
+        let _ = requests;
+        Ok(Route::Primary(&self.rpc))
+    }
+
+    /// Fetches a fresh state and caches it
+    pub fn refresh(&mut self) -> Result<&MarinadeProgramState, FetchError> {
+        let state = fetch_program_state(
+            &self.route(STATE_REQUESTS)?,
+            &self.program,
+            &self.state_address,
+        )?;
+        Ok(&self.cached.insert((Instant::now(), state)).1)
+    }
+
+    /// Cached state, fetched again when older than max_age or fetched for another program
+    pub fn state(&mut self) -> Result<&MarinadeProgramState, FetchError> {
+        let fresh = matches!(
+            &self.cached,
+            Some((fetched_at, state))
+                if fetched_at.elapsed() <= self.max_age && state.program == self.program
+        );
+        if !fresh {
+            return self.refresh();
+        }
+        Ok(&self.cached.as_ref().unwrap().1)
+    }
+
+    /// Cached sysvars, fetched again when older than max_age
+    pub fn sysvars(&mut self) -> Result<&SysvarCache, FetchError> {
+        let fresh = matches!(
+            &self.cached_sysvars,
+            Some((fetched_at, _)) if fetched_at.elapsed() <= self.max_age
+        );
+        if !fresh {
+            let sysvars = fetch_sysvars(&self.route(SYSVARS_REQUESTS)?)?;
+            return Ok(&self.cached_sysvars.insert((Instant::now(), sysvars)).1);
+        }
+        Ok(&self.cached_sysvars.as_ref().unwrap().1)
+    }
+
+    /// See detect_capabilities
+    pub fn detect_capabilities(&self, payer: &Pubkey) -> Result<Capabilities, FetchError> {
+        let rpc = self.route(Capability::ALL.len() as u32)?;
+        detect_capabilities(&rpc, &self.program, payer)
+    }
+
+    /// Health of the node and of the cached state, see HealthPolicy
+    pub fn health(&mut self) -> Health {
+        let started = Instant::now();
+        let epoch_info = match self.route(HEALTH_REQUESTS) {
+            Ok(rpc) => rpc.get_epoch_info(),
+            Err(err) => Err(ClientErrorKind::Custom(err.to_string()).into()),
+        };
+        let rpc_latency = started.elapsed();
+        health(epoch_info, rpc_latency, self.state())
+    }
+
+    /// Drops the cached state and sysvars, e.g. after sending a transaction changing the state
+    pub fn invalidate(&mut self) {
+        self.cached = None;
+        self.cached_sysvars = None;
+    }
+}
+
+/// MarinadeClient without access to its RpcClient: quotes, state, sysvars, health and
+/// capability probes, and no way to send. Read-only deployments use it alone, sending needs a
+/// SignerClient built from a MarinadeClient; a ReadClient never turns into one
+///
+/// ```compile_fail
+/// use marinade_sdk::client::ReadClient;
+/// use solana_client::rpc_client::RpcClient;
+/// use solana_program::pubkey::Pubkey;
+///
+/// let rpc = RpcClient::new("http://localhost:8899".to_string());
+/// let client = ReadClient::new(rpc, Pubkey::new_unique());
+/// client.rpc().get_latest_blockhash().unwrap();
+/// ```
+///
+/// ```compile_fail
+/// use marinade_sdk::client::{ReadClient, SignerClient};
+/// use solana_client::rpc_client::RpcClient;
+/// use solana_program::pubkey::Pubkey;
+/// use solana_sdk::signature::Keypair;
+///
+/// let rpc = RpcClient::new("http://localhost:8899".to_string());
+/// let read = ReadClient::new(rpc, Pubkey::new_unique());
+/// let client = SignerClient::new(read, Keypair::new());
+/// ```
+pub struct ReadClient(MarinadeClient);
+
+impl ReadClient {
+    pub fn new(rpc: RpcClient, state_address: Pubkey) -> Self {
+        Self(MarinadeClient::new(rpc, state_address))
+    }
+
+    pub fn state_address(&self) -> Pubkey {
+        self.0.state_address
+    }
+
+    pub fn max_age(&self) -> Duration {
+        self.0.max_age
+    }
+
+    pub fn set_max_age(&mut self, max_age: Duration) {
+        self.0.max_age = max_age;
+    }
+
+    pub fn program(&self) -> MarinadeProgram {
+        self.0.program
+    }
+
+    pub fn set_program(&mut self, program: MarinadeProgram) {
+        self.0.program = program;
+    }
+
+    /// See MarinadeClient::with_rate_limit
+    #[cfg(feature = "rate-limit")]
+    pub fn with_rate_limit(self, pool: EndpointPool) -> Self {
+        Self(self.0.with_rate_limit(pool))
+    }
+
+    /// See MarinadeClient::rate_limit_counters
+    #[cfg(feature = "rate-limit")]
+    pub fn rate_limit_counters(&self) -> Vec<(String, EndpointCounters)> {
+        self.0.rate_limit_counters()
+    }
+
+    /// See MarinadeClient::refresh
+    pub fn refresh(&mut self) -> Result<&MarinadeProgramState, FetchError> {
+        self.0.refresh()
+    }
+
+    /// See MarinadeClient::state
+    pub fn state(&mut self) -> Result<&MarinadeProgramState, FetchError> {
+        self.0.state()
+    }
+
+    /// See MarinadeClient::sysvars
+    pub fn sysvars(&mut self) -> Result<&SysvarCache, FetchError> {
+        self.0.sysvars()
+    }
+
+    /// See detect_capabilities
+    pub fn detect_capabilities(&self, payer: &Pubkey) -> Result<Capabilities, FetchError> {
+        self.0.detect_capabilities(payer)
+    }
+
+    /// See MarinadeClient::health
+    pub fn health(&mut self) -> Health {
+        self.0.health()
+    }
+
+    pub fn invalidate(&mut self) {
+        self.0.invalidate()
+    }
+}
+
+impl From<MarinadeClient> for ReadClient {
+    fn from(client: MarinadeClient) -> Self {
+        Self(client)
+    }
+}
+
+/// MarinadeClient with the signer paying for and signing what it sends; the read side is
+/// reached through Deref
+///
+/// # Examples
+///
+/// The examples run against testing::MockRpc, which records the sent transactions instead of
+/// executing them, and are only tested with feature test-utils; with a node only the RpcClient
+/// differs. A deposit into the mSOL associated
+/// token account of the signer:
+///
+#[cfg_attr(feature = "test-utils", doc = "```")]
+#[cfg_attr(not(feature = "test-utils"), doc = "```ignore")]
+/// use marinade_sdk::{
+///     ata::deposit_to_ata,
+///     client::{MarinadeClient, SignerClient},
+/// };
+/// use solana_sdk::signature::{Keypair, Signer};
+/// # use marinade_sdk::testing::{program_accounts, test_state, MockRpc};
+/// # let mut state = test_state();
+/// # state.state.staking_sol_cap = u64::MAX;
+/// # let mock = MockRpc::new(program_accounts(&state));
+/// # let sent = mock.sent_transactions();
+/// # let rpc = mock.into_client();
+///
+/// let mut client = SignerClient::new(MarinadeClient::new(rpc, state.key), Keypair::new());
+/// let program = client.state().unwrap().clone();
+/// let quote = program.quote_deposit(1_000_000_000).unwrap();
+/// assert_eq!(quote.value.msol_amount(), 1_000_000_000);
+///
+/// let instructions = deposit_to_ata(&program, client.pubkey(), 1_000_000_000, true).unwrap();
+/// let signature = client.send(&instructions, &[]).unwrap();
+/// # assert_eq!(sent.lock().unwrap()[0].signatures[0], signature);
+/// ```
+///
+/// A delayed unstake, the ticket account is derived from the signer with a seed:
+///
+#[cfg_attr(feature = "test-utils", doc = "```")]
+#[cfg_attr(not(feature = "test-utils"), doc = "```ignore")]
+/// use marinade_sdk::{
+///     ata::associated_token_address,
+///     client::{MarinadeClient, SignerClient},
+///     instructions::order_unstake::OrderUnstakeData,
+///     state::delayed_unstake_ticket::DelayedUnstakeTicket,
+///     ticket::order_unstake_with_seed,
+/// };
+/// use solana_sdk::{
+///     rent::Rent,
+///     signature::{Keypair, Signer},
+/// };
+/// # use marinade_sdk::testing::{program_accounts, test_state, MockRpc};
+/// # let state = test_state();
+/// # let mock = MockRpc::new(program_accounts(&state));
+/// # let sent = mock.sent_transactions();
+/// # let rpc = mock.into_client();
+///
+/// let mut client = SignerClient::new(MarinadeClient::new(rpc, state.key), Keypair::new());
+/// let program = client.state().unwrap().clone();
+/// let quote = program.quote_order_unstake(1_000_000_000).unwrap();
+/// assert_eq!(quote.value.lamports, 1_000_000_000);
+///
+/// let wallet = client.pubkey();
+/// let (ticket, instructions) = order_unstake_with_seed(
+///     &program,
+///     OrderUnstakeData::new(1_000_000_000),
+///     associated_token_address(&wallet, &program.state.msol_mint),
+///     wallet,
+///     wallet,
+///     wallet,
+///     "ticket-0",
+///     Rent::default().minimum_balance(DelayedUnstakeTicket::ACCOUNT_LEN),
+///     None,
+/// )
+/// .unwrap();
+/// client.send(&instructions, &[]).unwrap();
+/// # assert_eq!(sent.lock().unwrap()[0].message.instructions.len(), 2);
+/// # assert!(sent.lock().unwrap()[0].message.account_keys.contains(&ticket));
+/// ```
+pub struct SignerClient<S: Signer> {
+    client: MarinadeClient,
+    signer: S,
+}
+
+impl<S: Signer> SignerClient<S> {
+    pub fn new(client: MarinadeClient, signer: S) -> Self {
+        Self { client, signer }
+    }
+
+    /// SignerClient of a new MarinadeClient, see MarinadeClient::new
+    pub fn connect(rpc: RpcClient, state_address: Pubkey, signer: S) -> Self {
+        Self::new(MarinadeClient::new(rpc, state_address), signer)
+    }
+
+    pub fn pubkey(&self) -> Pubkey {
+        self.signer.pubkey()
+    }
+
+    pub fn signer(&self) -> &S {
+        &self.signer
+    }
+
+    /// Drops the signer
+    pub fn into_read_client(self) -> ReadClient {
+        ReadClient(self.client)
+    }
+
+    /// Sends instructions in one transaction paid by the signer and waits for its confirmation.
+    /// signers are the other keys the instructions need; the cache is dropped afterwards
+    pub fn send(
+        &mut self,
+        instructions: &[Instruction],
+        signers: &[&dyn Signer],
+    ) -> Result<Signature, FetchError> {
+        let mut all_signers: Vec<&dyn Signer> = vec![&self.signer];
+        all_signers.extend_from_slice(signers);
+        let transaction = Transaction::new_signed_with_payer(
+            instructions,
+            Some(&self.signer.pubkey()),
+            &all_signers,
+            self.client.rpc.get_latest_blockhash()?,
+        );
+        let signature = self.client.rpc.send_and_confirm_transaction(&transaction);
+        self.client.invalidate();
+        Ok(signature?)
+    }
+
+    /// send, refused unless quote was computed from the client's state at most max_age slots
+    /// before the current slot of the node, so a UI cannot execute a minutes-old quote
+    pub fn send_quoted<T>(
+        &mut self,
+        quote: &Quote<T>,
+        max_age: u64,
+        instructions: &[Instruction],
+        signers: &[&dyn Signer],
+    ) -> Result<Signature, FetchError> {
+        check_quote_state(quote, &self.client.state_address)?;
+        quote.fresh_value(self.client.rpc.get_slot()?, max_age)?;
+        self.send(instructions, signers)
+    }
+
+    /// Claims every due ticket of the signer, see client::claim_all. The error is the one of
+    /// the ticket search; each claim transaction then has its own result, a failed one does
+    /// not stop the next ones and its tickets stay claimable
+    ///
+    #[cfg_attr(feature = "test-utils", doc = "```")]
+    #[cfg_attr(not(feature = "test-utils"), doc = "```ignore")]
+    /// use marinade_sdk::client::{MarinadeClient, SignerClient};
+    /// use solana_program::{clock::Clock, pubkey::Pubkey, sysvar::SysvarId};
+    /// use solana_sdk::signature::{Keypair, Signer};
+    /// # use marinade_sdk::testing::{
+    /// #     program_accounts, sysvar_account, test_state, ticket_account, MockRpc,
+    /// # };
+    /// # let state = test_state();
+    /// # let signer = Keypair::new();
+    /// # let mut mock = MockRpc::new(program_accounts(&state));
+    /// # let clock = Clock {
+    /// #     epoch: 10,
+    /// #     unix_timestamp: 1_000_000,
+    /// #     ..Clock::default()
+    /// # };
+    /// # mock.set_account(Clock::id(), sysvar_account(&clock));
+    /// # // due since epoch 6 and 10, and not due yet
+    /// # for created_epoch in [5, 9, 10] {
+    /// #     let ticket = ticket_account(state.key, signer.pubkey(), 1_000, created_epoch);
+    /// #     mock.set_account(Pubkey::new_unique(), ticket);
+    /// # }
+    /// # let sent = mock.sent_transactions();
+    /// # let rpc = mock.into_client();
+    ///
+    /// let mut client = SignerClient::new(MarinadeClient::new(rpc, state.key), signer);
+    /// let results = client.claim_all().unwrap();
+    /// assert_eq!(results.len(), 1);
+    /// assert!(results[0].is_ok());
+    /// # assert_eq!(sent.lock().unwrap()[0].message.instructions.len(), 2);
+    /// ```
+    pub fn claim_all(&mut self) -> Result<Vec<Result<Signature, FetchError>>, FetchError> {
+        let state = self.client.state()?.clone();
+        let pubkey = self.pubkey();
+        let transactions = claim_all(&self.client.route(TICKETS_REQUESTS)?, &state, &pubkey)?;
+        Ok(transactions
+            .iter()
+            .map(|instructions| self.send(instructions, &[]))
+            .collect())
+    }
+
+    /// See execute_recovery. A crank step updating a stake account left behind in the epoch:
+    ///
+    #[cfg_attr(feature = "test-utils", doc = "```")]
+    #[cfg_attr(not(feature = "test-utils"), doc = "```ignore")]
+    /// use marinade_sdk::{
+    ///     client::{MarinadeClient, SignerClient},
+    ///     planner::{plan_updates, DelegatedStake},
+    ///     state::{stake_system::StakeRecord, validator_system::ValidatorRecord},
+    ///     watchdog::recovery_instructions,
+    /// };
+    /// use solana_program::pubkey::Pubkey;
+    /// use solana_sdk::signature::Keypair;
+    /// # use marinade_sdk::testing::{program_accounts, test_state, MockRpc};
+    /// # let state = test_state();
+    /// # let mock = MockRpc::new(program_accounts(&state));
+    /// # let sent = mock.sent_transactions();
+    /// # let rpc = mock.into_client();
+    ///
+    /// let mut client = SignerClient::new(MarinadeClient::new(rpc, state.key), Keypair::new());
+    /// let program = client.state().unwrap().clone();
+    /// // records of the validator and stake lists, the stake delegated to the validator
+    /// let vote = Pubkey::new_unique();
+    /// let validators = [ValidatorRecord {
+    ///     validator_account: vote,
+    ///     ..ValidatorRecord::default()
+    /// }];
+    /// let stakes = [DelegatedStake {
+    ///     stake_index: 0,
+    ///     record: StakeRecord {
+    ///         stake_account: Pubkey::new_unique(),
+    ///         last_update_epoch: 9,
+    ///         ..StakeRecord::default()
+    ///     },
+    ///     validator_vote: vote,
+    /// }];
+    ///
+    /// let updates = plan_updates(&validators, &stakes, 10, |_| false);
+    /// let transactions = recovery_instructions(&program, &stakes, &updates, 5).unwrap();
+    /// let signatures = client.execute_recovery(&transactions).unwrap();
+    /// assert_eq!(signatures.len(), 1);
+    /// # assert_eq!(sent.lock().unwrap().len(), 1);
+    /// ```
+    pub fn execute_recovery(
+        &mut self,
+        transactions: &[Vec<Instruction>],
+    ) -> Result<Vec<Signature>, FetchError> {
+        let signatures = execute_recovery(&self.client.rpc, transactions, &self.signer);
+        self.client.invalidate();
+        signatures
+    }
+
+    /// See detect_capabilities, the signer pays the simulated fees
+    pub fn detect_capabilities(&self) -> Result<Capabilities, FetchError> {
+        self.client.detect_capabilities(&self.pubkey())
+    }
+}
+
+impl<S: Signer> Deref for SignerClient<S> {
+    type Target = MarinadeClient;
+
+    fn deref(&self) -> &MarinadeClient {
+        &self.client
+    }
+}
+
+impl<S: Signer> DerefMut for SignerClient<S> {
+    fn deref_mut(&mut self) -> &mut MarinadeClient {
+        &mut self.client
+    }
+}
@@ -0,0 +1,575 @@
+//! Async counterpart of the state loader on the nonblocking RpcClient.
+//! MarinadeProgramState implements Located<Marinade>, so every MarinadeHelpers builder
+//! works on the loaded state without blocking calls.
+
+#[cfg(feature = "rate-limit")]
+use std::sync::Mutex;
+use std::{
+    ops::{Deref, DerefMut},
+    time::{Duration, Instant},
+};
+
+use solana_account_decoder::UiAccountEncoding;
+use solana_client::{
+    client_error::{ClientError, ClientErrorKind},
+    nonblocking::rpc_client::RpcClient,
+};
+use solana_program::{
+    clock::Clock, hash::Hash, instruction::Instruction, program_error::ProgramError,
+    pubkey::Pubkey, sysvar::Sysvar,
+};
+use solana_sdk::{signature::Signature, signer::Signer, transaction::Transaction};
+
+use super::{
+    account_config, check_quote_state, decode_state, decode_sysvar, decode_tickets, health,
+    is_unsupported_encoding, operation_accounts, operation_required_accounts, probe_config,
+    probe_supported, probe_transaction, program_state_addresses, program_state_from_accounts,
+    sysvars_from_accounts, tickets_config, truncated_lists, AccountEncoding, ChunkedRead,
+    FetchError, FetchedAccount, MarinadeProgramState, OperationAccounts, Route, DEFAULT_CHUNK_LEN,
+    HEALTH_REQUESTS, STATE_REQUESTS, SYSVARS_REQUESTS, TICKETS_REQUESTS,
+};
+#[cfg(feature = "rate-limit")]
+use crate::rate_limit::{EndpointCounters, EndpointPool, Limiter};
+use crate::{
+    capabilities::{Capabilities, Capability},
+    health::Health,
+    located::Located,
+    program::MarinadeProgram,
+    quote::Quote,
+    required_accounts::{addresses, Operation},
+    state::{delayed_unstake_ticket::DelayedUnstakeTicket, marinade::Marinade},
+    sysvars::SysvarCache,
+    ticket::plan_claims,
+};
+
+async fn get_accounts(
+    rpc: &RpcClient,
+    addresses: &[Pubkey],
+    encoding: UiAccountEncoding,
+) -> Result<(u64, Vec<Option<FetchedAccount>>), ClientError> {
+    let response = rpc
+        .get_multiple_accounts_with_config(addresses, account_config(rpc.commitment(), encoding))
+        .await?;
+    let accounts = response
+        .value
+        .into_iter()
+        .map(|account| {
+            account.map(|account| FetchedAccount {
+                lamports: account.lamports,
+                owner: account.owner,
+                data: account.data,
+            })
+        })
+        .collect();
+    Ok((response.context.slot, accounts))
+}
+
+/// See client::fetch_accounts
+pub async fn fetch_accounts(
+    rpc: &RpcClient,
+    addresses: &[Pubkey],
+    encoding: AccountEncoding,
+) -> Result<(u64, Vec<Option<FetchedAccount>>), ClientError> {
+    match encoding {
+        AccountEncoding::Base64 => get_accounts(rpc, addresses, UiAccountEncoding::Base64).await,
+        AccountEncoding::Base64Zstd => {
+            match get_accounts(rpc, addresses, UiAccountEncoding::Base64Zstd).await {
+                Err(err) if is_unsupported_encoding(&err) => {
+                    get_accounts(rpc, addresses, UiAccountEncoding::Base64).await
+                }
+                result => result,
+            }
+        }
+    }
+}
+
+/// Account data, None if the account does not exist
+pub async fn fetch_account_data(
+    rpc: &RpcClient,
+    address: &Pubkey,
+    encoding: AccountEncoding,
+) -> Result<Option<Vec<u8>>, ClientError> {
+    let (_, mut accounts) = fetch_accounts(rpc, &[*address], encoding).await?;
+    Ok(accounts.pop().flatten().map(|account| account.data))
+}
+
+/// See client::fetch_account_data_chunked
+pub async fn fetch_account_data_chunked(
+    rpc: &RpcClient,
+    address: &Pubkey,
+    expected_len: usize,
+    chunk_len: usize,
+    slot: Option<u64>,
+    expected_hash: Option<&Hash>,
+) -> Result<Vec<u8>, FetchError> {
+    let mut read = ChunkedRead::new(address, expected_len, chunk_len, slot);
+    while let Some(config) = read.next_config(rpc.commitment()) {
+        let response = rpc.get_account_with_config(address, config).await?;
+        read.push(response.context.slot, response.value)?;
+    }
+    read.finish(expected_hash)
+}
+
+/// See client::fetch_program_state
+pub async fn fetch_program_state(
+    rpc: &RpcClient,
+    program: &MarinadeProgram,
+    state_address: &Pubkey,
+) -> Result<MarinadeProgramState, FetchError> {
+    let (state, extension) = decode_state(
+        state_address,
+        fetch_account_data(rpc, state_address, AccountEncoding::Base64).await?,
+    )?;
+    let addresses = program_state_addresses(program, &state)?;
+    let (slot, mut accounts) = fetch_accounts(rpc, &addresses, AccountEncoding::Base64Zstd).await?;
+    for (index, len) in truncated_lists(&state, &accounts) {
+        let data = fetch_account_data_chunked(
+            rpc,
+            &addresses[index],
+            len,
+            DEFAULT_CHUNK_LEN,
+            Some(slot),
+            None,
+        )
+        .await?;
+        if let Some(account) = &mut accounts[index] {
+            account.data = data;
+        }
+    }
+    program_state_from_accounts(program, state, extension, &addresses, slot, accounts)
+}
+
+/// See client::fetch_operation_accounts
+pub async fn fetch_operation_accounts(
+    rpc: &RpcClient,
+    program: &MarinadeProgram,
+    state_address: &Pubkey,
+    operation: &Operation,
+) -> Result<OperationAccounts, FetchError> {
+    let (state, extension) = decode_state(
+        state_address,
+        fetch_account_data(rpc, state_address, AccountEncoding::Base64).await?,
+    )?;
+    let required = operation_required_accounts(program, &state, operation)?;
+    let (slot, accounts) =
+        fetch_accounts(rpc, &addresses(&required), AccountEncoding::Base64Zstd).await?;
+    Ok(operation_accounts(
+        program, state, extension, required, slot, accounts,
+    ))
+}
+
+/// See client::fetch_sysvar
+pub async fn fetch_sysvar<S: Sysvar>(rpc: &RpcClient) -> Result<S, FetchError> {
+    let (_, mut accounts) = fetch_accounts(rpc, &[S::id()], AccountEncoding::Base64).await?;
+    decode_sysvar(accounts.pop().flatten())
+}
+
+/// See client::fetch_sysvars
+pub async fn fetch_sysvars(rpc: &RpcClient) -> Result<SysvarCache, FetchError> {
+    let (_, accounts) =
+        fetch_accounts(rpc, &SysvarCache::addresses(), AccountEncoding::Base64).await?;
+    sysvars_from_accounts(accounts)
+}
+
+/// See client::detect_capabilities
+pub async fn detect_capabilities(
+    rpc: &RpcClient,
+    program: &MarinadeProgram,
+    payer: &Pubkey,
+) -> Result<Capabilities, FetchError> {
+    let mut capabilities = Capabilities::default();
+    for capability in Capability::ALL {
+        let response = rpc
+            .simulate_transaction_with_config(
+                &probe_transaction(program, capability, payer),
+                probe_config(rpc.commitment()),
+            )
+            .await?;
+        if probe_supported(response.value.err)? {
+            capabilities.insert(capability);
+        }
+    }
+    Ok(capabilities)
+}
+
+/// See client::fetch_tickets
+pub async fn fetch_tickets<T: Located<Marinade>>(
+    rpc: &RpcClient,
+    state: &T,
+    beneficiary: &Pubkey,
+) -> Result<Vec<(Pubkey, DelayedUnstakeTicket)>, FetchError> {
+    decode_tickets(
+        rpc.get_program_accounts_with_config(
+            &state.program_id(),
+            tickets_config(rpc.commitment(), &state.key(), beneficiary),
+        )
+        .await?,
+    )
+}
+
+/// See client::claim_all
+pub async fn claim_all<T: Located<Marinade>>(
+    rpc: &RpcClient,
+    state: &T,
+    beneficiary: &Pubkey,
+) -> Result<Vec<Vec<Instruction>>, FetchError> {
+    let tickets = fetch_tickets(rpc, state, beneficiary).await?;
+    let clock = fetch_sysvar::<Clock>(rpc).await?;
+    Ok(plan_claims(state, beneficiary, &tickets, &clock).map_err(ProgramError::from)?)
+}
+
+async fn send_recovery_transaction(
+    rpc: &RpcClient,
+    instructions: &[Instruction],
+    payer: &dyn Signer,
+) -> Result<Signature, ClientError> {
+    let transaction = Transaction::new_signed_with_payer(
+        instructions,
+        Some(&payer.pubkey()),
+        &[payer],
+        rpc.get_latest_blockhash().await?,
+    );
+    rpc.send_and_confirm_transaction(&transaction).await
+}
+
+/// See client::execute_recovery
+pub async fn execute_recovery(
+    rpc: &RpcClient,
+    transactions: &[Vec<Instruction>],
+    payer: &dyn Signer,
+) -> Result<Vec<Signature>, FetchError> {
+    let mut signatures = Vec::new();
+    for instructions in transactions {
+        match send_recovery_transaction(rpc, instructions, payer).await {
+            Ok(signature) => signatures.push(signature),
+            Err(error) => {
+                return Err(FetchError::Recovery {
+                    confirmed: signatures,
+                    error,
+                })
+            }
+        }
+    }
+    Ok(signatures)
+}
+
+/// Keeps the last fetched program state and sysvars for max_age
+pub struct MarinadeClient {
+    rpc: RpcClient,
+    pub state_address: Pubkey,
+    /// See client::MarinadeClient::program
+    pub program: MarinadeProgram,
+    pub max_age: Duration,
+    cached: Option<(Instant, MarinadeProgramState)>,
+    cached_sysvars: Option<(Instant, SysvarCache)>,
+    #[cfg(feature = "rate-limit")]
+    limiter: Option<Mutex<Limiter<RpcClient>>>,
+}
+
+impl MarinadeClient {
+    pub fn new(rpc: RpcClient, state_address: Pubkey) -> Self {
+        Self {
+            rpc,
+            state_address,
+            program: MarinadeProgram::default(),
+            max_age: super::MarinadeClient::DEFAULT_MAX_AGE,
+            cached: None,
+            cached_sysvars: None,
+            #[cfg(feature = "rate-limit")]
+            limiter: None,
+        }
+    }
+
+    /// See client::MarinadeClient::rpc
+    pub fn rpc(&self) -> &RpcClient {
+        &self.rpc
+    }
+
+    /// See client::MarinadeClient::with_rate_limit. Requests are not delayed: without budget
+    /// they fail with FetchError::RateLimited telling how long to wait
+    #[cfg(feature = "rate-limit")]
+    pub fn with_rate_limit(mut self, pool: EndpointPool) -> Self {
+        self.limiter = Some(Mutex::new(Limiter::new(pool)));
+        self
+    }
+
+    /// See client::MarinadeClient::rate_limit_counters
+    #[cfg(feature = "rate-limit")]
+    pub fn rate_limit_counters(&self) -> Vec<(String, EndpointCounters)> {
+        let limiter = match &self.limiter {
+            Some(limiter) => limiter.lock().unwrap(),
+            None => return vec![],
+        };
+        limiter
+            .pool
+            .counters()
+            .into_iter()
+            .map(|(url, counters)| (url.to_string(), counters))
+            .collect()
+    }
+
+    /// Client of the endpoint with budget for requests
+    fn route(&self, requests: u32) -> Result<Route<'_, RpcClient>, FetchError> {
+        #[cfg(feature = "rate-limit")]
+        if let Some(limiter) = &self.limiter {
+            let mut limiter = limiter.lock().unwrap();
+            let url = match limiter.pool.acquire(requests) {
+                Some(url) => url.to_string(),
+                None => {
+                    let wait = limiter.pool.time_until_available(requests);
+                    return Err(FetchError::RateLimited(wait));
+                }
+            };
+            let commitment = self.rpc.commitment();
+            let connect = |url| RpcClient::new_with_commitment(url, commitment);
+            if let Some(rpc) = limiter.secondary(&url, &self.rpc.url(), connect) {
+                return Ok(Route::Secondary(rpc));
+            }
+        }
+        #[cfg(not(feature = "rate-limit"))]
+        let _ = requests;
+        Ok(Route::Primary(&self.rpc))
+    }
+
+    /// Fetches a fresh state and caches it
+    pub async fn refresh(&mut self) -> Result<&MarinadeProgramState, FetchError> {
+        let rpc = self.route(STATE_REQUESTS)?;
+        let state = fetch_program_state(&rpc, &self.program, &self.state_address).await?;
+        drop(rpc);
+        Ok(&self.cached.insert((Instant::now(), state)).1)
+    }
+
+    /// Cached state, fetched again when older than max_age or fetched for another program
+    pub async fn state(&mut self) -> Result<&MarinadeProgramState, FetchError> {
+        let fresh = matches!(
+            &self.cached,
+            Some((fetched_at, state))
+                if fetched_at.elapsed() <= self.max_age && state.program == self.program
+        );
+        if !fresh {
+            return self.refresh().await;
+        }
+        Ok(&self.cached.as_ref().unwrap().1)
+    }
+
+    /// See client::detect_capabilities
+    pub async fn detect_capabilities(&self, payer: &Pubkey) -> Result<Capabilities, FetchError> {
+        let rpc = self.route(Capability::ALL.len() as u32)?;
+        detect_capabilities(&rpc, &self.program, payer).await
+    }
+
+    /// Cached sysvars, fetched again when older than max_age
+    pub async fn sysvars(&mut self) -> Result<&SysvarCache, FetchError> {
+        let fresh = matches!(
+            &self.cached_sysvars,
+            Some((fetched_at, _)) if fetched_at.elapsed() <= self.max_age
+        );
+        if !fresh {
+            let sysvars = fetch_sysvars(&*self.route(SYSVARS_REQUESTS)?).await?;
+            return Ok(&self.cached_sysvars.insert((Instant::now(), sysvars)).1);
+        }
+        Ok(&self.cached_sysvars.as_ref().unwrap().1)
+    }
+
+    /// See client::MarinadeClient::health
+    pub async fn health(&mut self) -> Health {
+        let started = Instant::now();
+        let epoch_info = match self.route(HEALTH_REQUESTS) {
+            Ok(rpc) => rpc.get_epoch_info().await,
+            Err(err) => Err(ClientErrorKind::Custom(err.to_string()).into()),
+        };
+        let rpc_latency = started.elapsed();
+        health(epoch_info, rpc_latency, self.state().await)
+    }
+
+    /// Drops the cached state and sysvars, e.g. after sending a transaction changing the state
+    pub fn invalidate(&mut self) {
+        self.cached = None;
+        self.cached_sysvars = None;
+    }
+}
+
+/// See client::ReadClient
+pub struct ReadClient(MarinadeClient);
+
+impl ReadClient {
+    pub fn new(rpc: RpcClient, state_address: Pubkey) -> Self {
+        Self(MarinadeClient::new(rpc, state_address))
+    }
+
+    pub fn state_address(&self) -> Pubkey {
+        self.0.state_address
+    }
+
+    pub fn max_age(&self) -> Duration {
+        self.0.max_age
+    }
+
+    pub fn set_max_age(&mut self, max_age: Duration) {
+        self.0.max_age = max_age;
+    }
+
+    pub fn program(&self) -> MarinadeProgram {
+        self.0.program
+    }
+
+    pub fn set_program(&mut self, program: MarinadeProgram) {
+        self.0.program = program;
+    }
+
+    /// See MarinadeClient::with_rate_limit
+    #[cfg(feature = "rate-limit")]
+    pub fn with_rate_limit(self, pool: EndpointPool) -> Self {
+        Self(self.0.with_rate_limit(pool))
+    }
+
+    /// See MarinadeClient::rate_limit_counters
+    #[cfg(feature = "rate-limit")]
+    pub fn rate_limit_counters(&self) -> Vec<(String, EndpointCounters)> {
+        self.0.rate_limit_counters()
+    }
+
+    /// See MarinadeClient::refresh
+    pub async fn refresh(&mut self) -> Result<&MarinadeProgramState, FetchError> {
+        self.0.refresh().await
+    }
+
+    /// See MarinadeClient::state
+    pub async fn state(&mut self) -> Result<&MarinadeProgramState, FetchError> {
+        self.0.state().await
+    }
+
+    /// See MarinadeClient::sysvars
+    pub async fn sysvars(&mut self) -> Result<&SysvarCache, FetchError> {
+        self.0.sysvars().await
+    }
+
+    /// See client::detect_capabilities
+    pub async fn detect_capabilities(&self, payer: &Pubkey) -> Result<Capabilities, FetchError> {
+        self.0.detect_capabilities(payer).await
+    }
+
+    /// See client::MarinadeClient::health
+    pub async fn health(&mut self) -> Health {
+        self.0.health().await
+    }
+
+    pub fn invalidate(&mut self) {
+        self.0.invalidate()
+    }
+}
+
+impl From<MarinadeClient> for ReadClient {
+    fn from(client: MarinadeClient) -> Self {
+        Self(client)
+    }
+}
+
+/// See client::SignerClient
+pub struct SignerClient<S: Signer> {
+    client: MarinadeClient,
+    signer: S,
+}
+
+impl<S: Signer> SignerClient<S> {
+    pub fn new(client: MarinadeClient, signer: S) -> Self {
+        Self { client, signer }
+    }
+
+    /// See client::SignerClient::connect
+    pub fn connect(rpc: RpcClient, state_address: Pubkey, signer: S) -> Self {
+        Self::new(MarinadeClient::new(rpc, state_address), signer)
+    }
+
+    pub fn pubkey(&self) -> Pubkey {
+        self.signer.pubkey()
+    }
+
+    pub fn signer(&self) -> &S {
+        &self.signer
+    }
+
+    /// Drops the signer
+    pub fn into_read_client(self) -> ReadClient {
+        ReadClient(self.client)
+    }
+
+    /// See client::SignerClient::send
+    pub async fn send(
+        &mut self,
+        instructions: &[Instruction],
+        signers: &[&dyn Signer],
+    ) -> Result<Signature, FetchError> {
+        let blockhash = self.client.rpc.get_latest_blockhash().await?;
+        let mut all_signers: Vec<&dyn Signer> = vec![&self.signer];
+        all_signers.extend_from_slice(signers);
+        let transaction = Transaction::new_signed_with_payer(
+            instructions,
+            Some(&self.signer.pubkey()),
+            &all_signers,
+            blockhash,
+        );
+        let signature = self
+            .client
+            .rpc
+            .send_and_confirm_transaction(&transaction)
+            .await;
+        self.client.invalidate();
+        Ok(signature?)
+    }
+
+    /// See client::SignerClient::send_quoted
+    pub async fn send_quoted<T>(
+        &mut self,
+        quote: &Quote<T>,
+        max_age: u64,
+        instructions: &[Instruction],
+        signers: &[&dyn Signer],
+    ) -> Result<Signature, FetchError> {
+        check_quote_state(quote, &self.client.state_address)?;
+        quote.fresh_value(self.client.rpc.get_slot().await?, max_age)?;
+        self.send(instructions, signers).await
+    }
+
+    /// Claims every due ticket of the signer, see client::SignerClient::claim_all
+    pub async fn claim_all(&mut self) -> Result<Vec<Result<Signature, FetchError>>, FetchError> {
+        let state = self.client.state().await?.clone();
+        let rpc = self.client.route(TICKETS_REQUESTS)?;
+        let transactions = claim_all(&rpc, &state, &self.pubkey()).await?;
+        drop(rpc);
+        let mut results = Vec::new();
+        for instructions in &transactions {
+            results.push(self.send(instructions, &[]).await);
+        }
+        Ok(results)
+    }
+
+    /// See client::SignerClient::execute_recovery
+    pub async fn execute_recovery(
+        &mut self,
+        transactions: &[Vec<Instruction>],
+    ) -> Result<Vec<Signature>, FetchError> {
+        let signatures = execute_recovery(&self.client.rpc, transactions, &self.signer).await;
+        self.client.invalidate();
+        signatures
+    }
+
+    /// See detect_capabilities, the signer pays the simulated fees
+    pub async fn detect_capabilities(&self) -> Result<Capabilities, FetchError> {
+        self.client.detect_capabilities(&self.pubkey()).await
+    }
+}
+
+impl<S: Signer> Deref for SignerClient<S> {
+    type Target = MarinadeClient;
+
+    fn deref(&self) -> &MarinadeClient {
+        &self.client
+    }
+}
+
+impl<S: Signer> DerefMut for SignerClient<S> {
+    fn deref_mut(&mut self) -> &mut MarinadeClient {
+        &mut self.client
+    }
+}
@@ -0,0 +1,18 @@
+//! solana-program items that differ between the supported SDK minor versions (1.14, 1.16, 1.17).
+//! The crate imports them from here.
+//! `Pack`, the sysvar ids and `stake::program` are the same in all of them and are used directly.
+//! The marinade_finance submodule (feature marinade-finance-compat) converts the types of
+//! the program crate that older integrations CPI through.
+
+#[cfg(feature = "marinade-finance-compat")]
+pub mod marinade_finance;
+
+/// The stake account state of every supported version. 1.17 deprecates it for StakeStateV2,
+/// which only adds the stake flags to the same layout.
+#[allow(deprecated)]
+pub type StakeState = solana_program::stake::state::StakeState;
+
+/// Size of a stake account, the same in every supported version
+pub fn stake_account_len() -> usize {
+    StakeState::size_of()
+}
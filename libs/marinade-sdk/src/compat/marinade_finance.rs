@@ -0,0 +1,172 @@
+//! Conversions from the types of the `marinade-finance` program crate, which older
+//! integrations use through its `cpi` feature, so they can move to this SDK one call site
+//! at a time. The state converts through its borsh bytes, which both crates lay out the same.
+//!
+//! Migration, in the order tests/marinade_finance_compat.rs walks through it:
+//! 1. Keep deserializing `marinade_finance::State` and convert it with
+//!    `Marinade::try_from(&state)`; the helper traits (MarinadeHelpers, LiqPoolHelpers, ...)
+//!    then work on `KeyedAccount::new(state_address, marinade)`.
+//! 2. Replace `marinade_finance::instruction::X { .. }` with `XData::from(..)` and
+//!    `marinade_finance::accounts::X { .. }` with `XAccounts::from(..)`; the only renamed
+//!    account is `state`, called `marinade` here.
+//! 3. Build the Instruction with `InstructionBuilder { accounts, data }` instead of
+//!    `anchor_lang::InstructionData`/`ToAccountMetas`, the account metas are identical.
+//! 4. Drop the `marinade-finance` dependency and this feature once no conversion is left.
+
+use std::io;
+
+use ::marinade_finance::{accounts, instruction, State};
+use borsh::{BorshDeserialize, BorshSerialize};
+
+use crate::{
+    instructions::{
+        add_liquidity::{AddLiquidityAccounts, AddLiquidityData},
+        claim::{ClaimAccounts, ClaimData},
+        deposit::{DepositAccounts, DepositData},
+        liquid_unstake::{LiquidUnstakeAccounts, LiquidUnstakeData},
+        order_unstake::{OrderUnstakeAccounts, OrderUnstakeData},
+        remove_liquidity::{RemoveLiquidityAccounts, RemoveLiquidityData},
+    },
+    state::marinade::Marinade,
+};
+
+impl TryFrom<&State> for Marinade {
+    type Error = io::Error;
+
+    fn try_from(state: &State) -> Result<Self, io::Error> {
+        Marinade::try_from_slice(&state.try_to_vec()?)
+    }
+}
+
+impl From<instruction::Deposit> for DepositData {
+    fn from(data: instruction::Deposit) -> Self {
+        DepositData::new(data.lamports)
+    }
+}
+
+impl From<instruction::LiquidUnstake> for LiquidUnstakeData {
+    fn from(data: instruction::LiquidUnstake) -> Self {
+        LiquidUnstakeData::new(data.msol_amount)
+    }
+}
+
+impl From<instruction::OrderUnstake> for OrderUnstakeData {
+    fn from(data: instruction::OrderUnstake) -> Self {
+        OrderUnstakeData::new(data.msol_amount)
+    }
+}
+
+impl From<instruction::Claim> for ClaimData {
+    fn from(_: instruction::Claim) -> Self {
+        ClaimData::new()
+    }
+}
+
+impl From<instruction::AddLiquidity> for AddLiquidityData {
+    fn from(data: instruction::AddLiquidity) -> Self {
+        AddLiquidityData::new(data.lamports)
+    }
+}
+
+impl From<instruction::RemoveLiquidity> for RemoveLiquidityData {
+    fn from(data: instruction::RemoveLiquidity) -> Self {
+        RemoveLiquidityData::new(data.tokens)
+    }
+}
+
+impl From<accounts::Deposit> for DepositAccounts {
+    fn from(accounts: accounts::Deposit) -> Self {
+        DepositAccounts {
+            marinade: accounts.state,
+            msol_mint: accounts.msol_mint,
+            liq_pool_sol_leg_pda: accounts.liq_pool_sol_leg_pda,
+            liq_pool_msol_leg: accounts.liq_pool_msol_leg,
+            liq_pool_msol_leg_authority: accounts.liq_pool_msol_leg_authority,
+            reserve_pda: accounts.reserve_pda,
+            transfer_from: accounts.transfer_from,
+            mint_to: accounts.mint_to,
+            msol_mint_authority: accounts.msol_mint_authority,
+            system_program: accounts.system_program,
+            token_program: accounts.token_program,
+        }
+    }
+}
+
+impl From<accounts::LiquidUnstake> for LiquidUnstakeAccounts {
+    fn from(accounts: accounts::LiquidUnstake) -> Self {
+        LiquidUnstakeAccounts {
+            marinade: accounts.state,
+            msol_mint: accounts.msol_mint,
+            liq_pool_sol_leg_pda: accounts.liq_pool_sol_leg_pda,
+            liq_pool_msol_leg: accounts.liq_pool_msol_leg,
+            treasury_msol_account: accounts.treasury_msol_account,
+            get_msol_from: accounts.get_msol_from,
+            get_msol_from_authority: accounts.get_msol_from_authority,
+            transfer_sol_to: accounts.transfer_sol_to,
+            system_program: accounts.system_program,
+            token_program: accounts.token_program,
+        }
+    }
+}
+
+impl From<accounts::OrderUnstake> for OrderUnstakeAccounts {
+    fn from(accounts: accounts::OrderUnstake) -> Self {
+        OrderUnstakeAccounts {
+            marinade: accounts.state,
+            msol_mint: accounts.msol_mint,
+            burn_msol_from: accounts.burn_msol_from,
+            burn_msol_authority: accounts.burn_msol_authority,
+            new_ticket_account: accounts.new_ticket_account,
+            clock: accounts.clock,
+            rent: accounts.rent,
+            token_program: accounts.token_program,
+        }
+    }
+}
+
+impl From<accounts::Claim> for ClaimAccounts {
+    fn from(accounts: accounts::Claim) -> Self {
+        ClaimAccounts {
+            marinade: accounts.state,
+            reserve_pda: accounts.reserve_pda,
+            ticket_account: accounts.ticket_account,
+            transfer_sol_to: accounts.transfer_sol_to,
+            clock: accounts.clock,
+            system_program: accounts.system_program,
+        }
+    }
+}
+
+impl From<accounts::AddLiquidity> for AddLiquidityAccounts {
+    fn from(accounts: accounts::AddLiquidity) -> Self {
+        AddLiquidityAccounts {
+            marinade: accounts.state,
+            lp_mint: accounts.lp_mint,
+            lp_mint_authority: accounts.lp_mint_authority,
+            liq_pool_msol_leg: accounts.liq_pool_msol_leg,
+            liq_pool_sol_leg_pda: accounts.liq_pool_sol_leg_pda,
+            transfer_from: accounts.transfer_from,
+            mint_to: accounts.mint_to,
+            system_program: accounts.system_program,
+            token_program: accounts.token_program,
+        }
+    }
+}
+
+impl From<accounts::RemoveLiquidity> for RemoveLiquidityAccounts {
+    fn from(accounts: accounts::RemoveLiquidity) -> Self {
+        RemoveLiquidityAccounts {
+            marinade: accounts.state,
+            lp_mint: accounts.lp_mint,
+            burn_from: accounts.burn_from,
+            burn_from_authority: accounts.burn_from_authority,
+            transfer_sol_to: accounts.transfer_sol_to,
+            transfer_msol_to: accounts.transfer_msol_to,
+            liq_pool_sol_leg_pda: accounts.liq_pool_sol_leg_pda,
+            liq_pool_msol_leg: accounts.liq_pool_msol_leg,
+            liq_pool_msol_leg_authority: accounts.liq_pool_msol_leg_authority,
+            system_program: accounts.system_program,
+            token_program: accounts.token_program,
+        }
+    }
+}
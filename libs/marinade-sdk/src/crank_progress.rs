@@ -0,0 +1,72 @@
+//! Progress of the per-epoch maintenance: updating every stake account, then the stake-delta
+//! run moving the reserve into (or out of) stake during the last slots of the epoch.
+
+use crate::{
+    calc::proportional,
+    error::CommonError,
+    planner::EpochPosition,
+    state::{
+        marinade::{Marinade, StakeDelta},
+        stake_system::StakeRecord,
+    },
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CrankProgress {
+    /// Stake records already updated in the current epoch
+    pub stakes_updated: u32,
+    pub stakes_total: u32,
+    /// Lamports staked or unstaked by stake-delta so far
+    pub delta_applied: u64,
+    /// delta_applied plus what is left to stake or unstake
+    pub delta_target: u64,
+    /// Slots left in the stake-delta window, the full window length before it opens
+    pub window_slots_remaining: u64,
+}
+
+impl CrankProgress {
+    /// The state only tells what is left of the stake delta. window_open_delta is the
+    /// stake delta amount the caller observed when the window opened; without it the
+    /// remaining delta is taken as the target and nothing counts as applied yet
+    pub fn new(
+        marinade: &Marinade,
+        stakes: &[StakeRecord],
+        reserve_balance: u64,
+        position: &EpochPosition,
+        window_open_delta: Option<u64>,
+    ) -> Result<Self, CommonError> {
+        let delta_remaining = match marinade.stake_delta_direction(reserve_balance)? {
+            StakeDelta::Stake(lamports) | StakeDelta::Unstake(lamports) => lamports,
+            StakeDelta::Neutral => 0,
+        };
+        let delta_target = window_open_delta
+            .unwrap_or(delta_remaining)
+            .max(delta_remaining);
+        let slots_for_stake_delta = marinade.stake_system.slots_for_stake_delta;
+        Ok(Self {
+            stakes_updated: stakes
+                .iter()
+                .filter(|stake| stake.last_update_epoch >= position.epoch)
+                .count() as u32,
+            stakes_total: stakes.len() as u32,
+            delta_applied: delta_target - delta_remaining,
+            delta_target,
+            window_slots_remaining: position
+                .slots_in_epoch
+                .saturating_sub(position.slot_index)
+                .min(slots_for_stake_delta),
+        })
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.stakes_updated >= self.stakes_total && self.delta_applied >= self.delta_target
+    }
+
+    /// 0-100 with both phases weighted equally, an empty phase counts as done
+    pub fn percent(&self) -> Result<u64, CommonError> {
+        Ok(
+            proportional(50, self.stakes_updated as u64, self.stakes_total as u64)?
+                + proportional(50, self.delta_applied, self.delta_target)?,
+        )
+    }
+}
@@ -0,0 +1,183 @@
+//! Typed decoding of Marinade instructions as they appear on chain, without the anchor IDL.
+//! The data is matched by its discriminator and the accounts are named by their position,
+//! the order the instruction builders emit them in. Keys past the last named account
+//! (remaining accounts) are ignored. Instructions are only decoded for the program id of the
+//! MarinadeProgram passed in, MarinadeProgram::default() for the mainnet deployment.
+
+use borsh::BorshDeserialize;
+use derive_more::Display;
+use micro_anchor::{Discriminator, FromAccountKeys};
+use solana_program::{
+    instruction::{CompiledInstruction, Instruction},
+    pubkey::Pubkey,
+};
+
+use crate::{
+    instructions::{
+        add_liquidity::{AddLiquidityAccounts, AddLiquidityData},
+        add_validator::{AddValidatorAccounts, AddValidatorData},
+        change_authority::{ChangeAuthorityAccounts, ChangeAuthorityData},
+        claim::{ClaimAccounts, ClaimData},
+        config_lp::{ConfigLpAccounts, ConfigLpData},
+        config_marinade::{ConfigMarinadeAccounts, ConfigMarinadeData},
+        config_validator_system::{ConfigValidatorSystemAccounts, ConfigValidatorSystemData},
+        deactivate_stake::{DeactivateStakeAccounts, DeactivateStakeData},
+        deposit::{DepositAccounts, DepositData},
+        deposit_stake_account::{DepositStakeAccountAccounts, DepositStakeAccountData},
+        emergency_unstake::{EmergencyUnstakeAccounts, EmergencyUnstakeData},
+        initialize::{InitializeAccounts, InitializeData},
+        liquid_unstake::{LiquidUnstakeAccounts, LiquidUnstakeData},
+        merge_stakes::{MergeStakesAccounts, MergeStakesData},
+        order_unstake::{OrderUnstakeAccounts, OrderUnstakeData},
+        partial_unstake::{PartialUnstakeAccounts, PartialUnstakeData},
+        remove_liquidity::{RemoveLiquidityAccounts, RemoveLiquidityData},
+        remove_validator::{RemoveValidatorAccounts, RemoveValidatorData},
+        set_validator_score::{SetValidatorScoreAccounts, SetValidatorScoreData},
+        stake_reserve::{StakeReserveAccounts, StakeReserveData},
+        update_active::{UpdateActiveAccounts, UpdateActiveData},
+        update_deactivated::{UpdateDeactivatedAccounts, UpdateDeactivatedData},
+    },
+    program::MarinadeProgram,
+};
+
+#[derive(Clone, Copy, Debug, Display, PartialEq, Eq)]
+pub enum DecodeError {
+    #[display(fmt = "Instruction of program {}", _0)]
+    NotMarinade(Pubkey),
+    #[display(fmt = "Instruction data too short for a discriminator")]
+    MissingDiscriminator,
+    /// Program instruction the SDK has no type for, e.g. withdraw_stake_account
+    #[display(fmt = "Unknown instruction discriminator {:?}", _0)]
+    UnknownDiscriminator([u8; 8]),
+    #[display(fmt = "Can not deserialize {} data", _0)]
+    InvalidData(&'static str),
+    #[display(fmt = "Not enough accounts for {}", _0)]
+    NotEnoughAccounts(&'static str),
+    #[display(fmt = "Account index {} out of the message keys", _0)]
+    AccountIndexOutOfRange(u8),
+}
+
+impl std::error::Error for DecodeError {}
+
+macro_rules! marinade_instructions {
+    ($($variant:ident($name:literal, $accounts:ident, $data:ident),)*) => {
+        #[derive(Clone, Copy, Debug, PartialEq)]
+        pub enum MarinadeInstruction {
+            $($variant { accounts: $accounts, data: $data },)*
+        }
+
+        impl MarinadeInstruction {
+            /// Instruction name as in the program IDL
+            pub fn name(&self) -> &'static str {
+                match self {
+                    $(Self::$variant { .. } => $name,)*
+                }
+            }
+
+            fn decode_known(
+                discriminator: [u8; 8],
+                mut args: &[u8],
+                keys: &[Pubkey],
+            ) -> Result<Self, DecodeError> {
+                $(
+                    if discriminator == $data::DISCRIMINATOR {
+                        let data = $data::deserialize(&mut args)
+                            .map_err(|_| DecodeError::InvalidData($name))?;
+                        let accounts = $accounts::from_account_keys(&mut keys.iter())
+                            .ok_or(DecodeError::NotEnoughAccounts($name))?;
+                        return Ok(Self::$variant { accounts, data });
+                    }
+                )*
+                Err(DecodeError::UnknownDiscriminator(discriminator))
+            }
+        }
+    };
+}
+
+marinade_instructions! {
+    Initialize("initialize", InitializeAccounts, InitializeData),
+    ChangeAuthority("change_authority", ChangeAuthorityAccounts, ChangeAuthorityData),
+    AddValidator("add_validator", AddValidatorAccounts, AddValidatorData),
+    RemoveValidator("remove_validator", RemoveValidatorAccounts, RemoveValidatorData),
+    SetValidatorScore("set_validator_score", SetValidatorScoreAccounts, SetValidatorScoreData),
+    ConfigValidatorSystem(
+        "config_validator_system",
+        ConfigValidatorSystemAccounts,
+        ConfigValidatorSystemData
+    ),
+    Deposit("deposit", DepositAccounts, DepositData),
+    DepositStakeAccount(
+        "deposit_stake_account",
+        DepositStakeAccountAccounts,
+        DepositStakeAccountData
+    ),
+    LiquidUnstake("liquid_unstake", LiquidUnstakeAccounts, LiquidUnstakeData),
+    AddLiquidity("add_liquidity", AddLiquidityAccounts, AddLiquidityData),
+    RemoveLiquidity("remove_liquidity", RemoveLiquidityAccounts, RemoveLiquidityData),
+    ConfigLp("config_lp", ConfigLpAccounts, ConfigLpData),
+    ConfigMarinade("config_marinade", ConfigMarinadeAccounts, ConfigMarinadeData),
+    OrderUnstake("order_unstake", OrderUnstakeAccounts, OrderUnstakeData),
+    Claim("claim", ClaimAccounts, ClaimData),
+    StakeReserve("stake_reserve", StakeReserveAccounts, StakeReserveData),
+    DeactivateStake("deactivate_stake", DeactivateStakeAccounts, DeactivateStakeData),
+    EmergencyUnstake("emergency_unstake", EmergencyUnstakeAccounts, EmergencyUnstakeData),
+    PartialUnstake("partial_unstake", PartialUnstakeAccounts, PartialUnstakeData),
+    MergeStakes("merge_stakes", MergeStakesAccounts, MergeStakesData),
+    UpdateActive("update_active", UpdateActiveAccounts, UpdateActiveData),
+    UpdateDeactivated(
+        "update_deactivated",
+        UpdateDeactivatedAccounts,
+        UpdateDeactivatedData
+    ),
+}
+
+/// Decodes an instruction given its program id, account keys in instruction order and data
+pub fn decode(
+    program: &MarinadeProgram,
+    program_id: &Pubkey,
+    keys: &[Pubkey],
+    data: &[u8],
+) -> Result<MarinadeInstruction, DecodeError> {
+    if *program_id != program.program_id {
+        return Err(DecodeError::NotMarinade(*program_id));
+    }
+    let (discriminator, args) = match data.get(..8) {
+        Some(discriminator) => (discriminator.try_into().unwrap(), &data[8..]),
+        None => return Err(DecodeError::MissingDiscriminator),
+    };
+    MarinadeInstruction::decode_known(discriminator, args, keys)
+}
+
+pub fn decode_instruction(
+    program: &MarinadeProgram,
+    instruction: &Instruction,
+) -> Result<MarinadeInstruction, DecodeError> {
+    let keys: Vec<Pubkey> = instruction
+        .accounts
+        .iter()
+        .map(|meta| meta.pubkey)
+        .collect();
+    decode(program, &instruction.program_id, &keys, &instruction.data)
+}
+
+/// Decodes an instruction of a transaction message; account_keys are the message keys,
+/// including the addresses loaded from lookup tables for versioned transactions
+pub fn decode_compiled(
+    program: &MarinadeProgram,
+    instruction: &CompiledInstruction,
+    account_keys: &[Pubkey],
+) -> Result<MarinadeInstruction, DecodeError> {
+    let key = |index: u8| {
+        account_keys
+            .get(index as usize)
+            .copied()
+            .ok_or(DecodeError::AccountIndexOutOfRange(index))
+    };
+    let program_id = key(instruction.program_id_index)?;
+    let keys = instruction
+        .accounts
+        .iter()
+        .map(|index| key(*index))
+        .collect::<Result<Vec<_>, _>>()?;
+    decode(program, &program_id, &keys, &instruction.data)
+}
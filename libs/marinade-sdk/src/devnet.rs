@@ -0,0 +1,184 @@
+//! Smoke test helpers for checking an RPC provider against a devnet Marinade deployment
+//! before going live. Never use them with mainnet keys.
+
+use derive_more::{Display, From};
+use micro_anchor::{AccountDeserialize, AccountDeserializeError};
+use solana_client::{client_error::ClientError, rpc_client::RpcClient};
+use solana_program::{
+    instruction::Instruction,
+    pubkey::{Pubkey, PubkeyError},
+};
+use solana_sdk::{
+    signature::{Keypair, Signature},
+    signer::Signer,
+    transaction::Transaction,
+};
+use spl_associated_token_account::{
+    get_associated_token_address, instruction::create_associated_token_account_idempotent,
+};
+
+use crate::{
+    error::CommonError,
+    instructions::{deposit::DepositData, liquid_unstake::LiquidUnstakeData},
+    located::KeyedAccount,
+    state::marinade::{Marinade, MarinadeHelpers},
+};
+
+#[derive(Debug, Display, From)]
+pub enum DevnetError {
+    Client(ClientError),
+    Deserialize(AccountDeserializeError),
+    Pubkey(PubkeyError),
+    #[display(fmt = "Invariant violated: {}", _0)]
+    #[from(ignore)]
+    InvariantViolated(String),
+    Calculation(CommonError),
+}
+
+impl std::error::Error for DevnetError {}
+
+pub fn fetch_state(
+    rpc: &RpcClient,
+    state_address: &Pubkey,
+) -> Result<KeyedAccount<Marinade>, DevnetError> {
+    let data = rpc.get_account_data(state_address)?;
+    let state = Marinade::try_deserialize(&mut data.as_slice())?;
+    Ok(KeyedAccount::new(*state_address, state))
+}
+
+/// Requests an airdrop and waits for its confirmation
+pub fn airdrop(rpc: &RpcClient, to: &Pubkey, lamports: u64) -> Result<Signature, DevnetError> {
+    let signature = rpc.request_airdrop(to, lamports)?;
+    rpc.poll_for_signature(&signature)?;
+    Ok(signature)
+}
+
+fn send(
+    rpc: &RpcClient,
+    instructions: &[Instruction],
+    payer: &Keypair,
+) -> Result<Signature, DevnetError> {
+    let transaction = Transaction::new_signed_with_payer(
+        instructions,
+        Some(&payer.pubkey()),
+        &[payer],
+        rpc.get_latest_blockhash()?,
+    );
+    Ok(rpc.send_and_confirm_transaction(&transaction)?)
+}
+
+fn token_balance(rpc: &RpcClient, token_account: &Pubkey) -> Result<u64, DevnetError> {
+    let amount = rpc.get_token_account_balance(token_account)?.amount;
+    amount
+        .parse()
+        .map_err(|_| DevnetError::InvariantViolated(format!("bad token amount {}", amount)))
+}
+
+#[derive(Clone, Debug)]
+pub struct RoundTripReport {
+    pub deposited_lamports: u64,
+    pub msol_received: u64,
+    pub sol_returned: u64,
+    pub msol_price_before: u64,
+    pub msol_price_after: u64,
+    pub deposit_signature: Signature,
+    pub unstake_signature: Signature,
+}
+
+impl RoundTripReport {
+    /// Some mSOL was received, no more SOL came back than was deposited and the mSOL price
+    /// did not drop
+    pub fn check(&self) -> Result<(), DevnetError> {
+        if self.msol_received == 0 {
+            return Err(DevnetError::InvariantViolated(
+                "deposit did not mint or swap any mSOL".to_string(),
+            ));
+        }
+        if self.sol_returned > self.deposited_lamports {
+            return Err(DevnetError::InvariantViolated(format!(
+                "liquid unstake returned {} lamports for a {} lamports deposit",
+                self.sol_returned, self.deposited_lamports
+            )));
+        }
+        if self.msol_price_after < self.msol_price_before {
+            return Err(DevnetError::InvariantViolated(format!(
+                "msol price dropped from {} to {}",
+                self.msol_price_before, self.msol_price_after
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// Deposits `lamports`, liquid-unstakes the received mSOL and checks the invariants of
+/// RoundTripReport::check. Airdrops to `user` first when its balance is not enough.
+pub fn deposit_unstake_round_trip(
+    rpc: &RpcClient,
+    state_address: &Pubkey,
+    user: &Keypair,
+    lamports: u64,
+) -> Result<RoundTripReport, DevnetError> {
+    // deposit + fees + ATA rent
+    let required = lamports
+        .checked_add(10_000_000)
+        .ok_or(CommonError::CalculationFailure)?;
+    if rpc.get_balance(&user.pubkey())? < required {
+        airdrop(rpc, &user.pubkey(), required)?;
+    }
+
+    let state = fetch_state(rpc, state_address)?;
+    let msol_price_before = state.account.msol_price;
+    let msol_account = get_associated_token_address(&user.pubkey(), &state.account.msol_mint);
+
+    let msol_before = if rpc.get_account(&msol_account).is_ok() {
+        token_balance(rpc, &msol_account)?
+    } else {
+        0
+    };
+    let deposit_signature = send(
+        rpc,
+        &[
+            create_associated_token_account_idempotent(
+                &user.pubkey(),
+                &user.pubkey(),
+                &state.account.msol_mint,
+                &spl_token::ID,
+            ),
+            state.deposit(DepositData::new(lamports), user.pubkey(), msol_account)?,
+        ],
+        user,
+    )?;
+    let msol_received = token_balance(rpc, &msol_account)?.saturating_sub(msol_before);
+    if msol_received == 0 {
+        return Err(DevnetError::InvariantViolated(
+            "deposit did not mint or swap any mSOL".to_string(),
+        ));
+    }
+
+    let sol_before = rpc.get_balance(&user.pubkey())?;
+    let unstake_signature = send(
+        rpc,
+        &[state.liquid_unstake(
+            LiquidUnstakeData::new(msol_received),
+            msol_account,
+            user.pubkey(),
+            user.pubkey(),
+        )?],
+        user,
+    )?;
+    // transaction fee is paid by the user as well
+    let sol_returned = rpc.get_balance(&user.pubkey())?.saturating_sub(sol_before);
+    let msol_price_after = fetch_state(rpc, state_address)?.account.msol_price;
+
+    let report = RoundTripReport {
+        deposited_lamports: lamports,
+        msol_received,
+        sol_returned,
+        msol_price_before,
+        msol_price_after,
+        deposit_signature,
+        unstake_signature,
+    };
+    report.check()?;
+    Ok(report)
+}
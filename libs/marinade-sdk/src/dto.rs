@@ -0,0 +1,256 @@
+//! Transport-friendly mirrors of the state types.
+//!
+//! Pubkeys are base58 strings and every u64 is a decimal string, so the values survive
+//! JSON clients that parse numbers as f64. Small integers (u8/u32) are kept numeric.
+//! With feature serde the DTOs derive Serialize and Deserialize, with the field names as keys.
+
+use crate::state::{
+    delayed_unstake_ticket::DelayedUnstakeTicket,
+    fee::Fee,
+    liq_pool::LiqPool,
+    list::List,
+    marinade::Marinade,
+    stake_system::{StakeRecord, StakeSystem},
+    validator_system::{ValidatorRecord, ValidatorSystem},
+};
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FeeDto {
+    pub basis_points: u32,
+    /// Human readable percentage, e.g. "0.3%"
+    pub display: String,
+}
+
+impl From<&Fee> for FeeDto {
+    fn from(fee: &Fee) -> Self {
+        Self {
+            basis_points: fee.basis_points,
+            display: fee.to_string(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ListDto {
+    pub account: String,
+    pub item_size: u32,
+    pub count: u32,
+    pub new_account: String,
+    pub copied_count: u32,
+}
+
+impl From<&List> for ListDto {
+    fn from(list: &List) -> Self {
+        Self {
+            account: list.account.to_string(),
+            item_size: list.item_size,
+            count: list.count,
+            new_account: list.new_account.to_string(),
+            copied_count: list.copied_count,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LiqPoolDto {
+    pub lp_mint: String,
+    pub lp_mint_authority_bump_seed: u8,
+    pub sol_leg_bump_seed: u8,
+    pub msol_leg_authority_bump_seed: u8,
+    pub msol_leg: String,
+    pub lp_liquidity_target: String,
+    pub lp_max_fee: FeeDto,
+    pub lp_min_fee: FeeDto,
+    pub treasury_cut: FeeDto,
+    pub lp_supply: String,
+    pub lent_from_sol_leg: String,
+    pub liquidity_sol_cap: String,
+}
+
+impl From<&LiqPool> for LiqPoolDto {
+    fn from(liq_pool: &LiqPool) -> Self {
+        Self {
+            lp_mint: liq_pool.lp_mint.to_string(),
+            lp_mint_authority_bump_seed: liq_pool.lp_mint_authority_bump_seed,
+            sol_leg_bump_seed: liq_pool.sol_leg_bump_seed,
+            msol_leg_authority_bump_seed: liq_pool.msol_leg_authority_bump_seed,
+            msol_leg: liq_pool.msol_leg.to_string(),
+            lp_liquidity_target: liq_pool.lp_liquidity_target.to_string(),
+            lp_max_fee: (&liq_pool.lp_max_fee).into(),
+            lp_min_fee: (&liq_pool.lp_min_fee).into(),
+            treasury_cut: (&liq_pool.treasury_cut).into(),
+            lp_supply: liq_pool.lp_supply.to_string(),
+            lent_from_sol_leg: liq_pool.lent_from_sol_leg.to_string(),
+            liquidity_sol_cap: liq_pool.liquidity_sol_cap.to_string(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct StakeSystemDto {
+    pub stake_list: ListDto,
+    pub delayed_unstake_cooling_down: String,
+    pub stake_deposit_bump_seed: u8,
+    pub stake_withdraw_bump_seed: u8,
+    pub slots_for_stake_delta: String,
+    pub last_stake_delta_epoch: String,
+    pub min_stake: String,
+    pub extra_stake_delta_runs: u32,
+}
+
+impl From<&StakeSystem> for StakeSystemDto {
+    fn from(stake_system: &StakeSystem) -> Self {
+        Self {
+            stake_list: (&stake_system.stake_list).into(),
+            delayed_unstake_cooling_down: stake_system.delayed_unstake_cooling_down.to_string(),
+            stake_deposit_bump_seed: stake_system.stake_deposit_bump_seed,
+            stake_withdraw_bump_seed: stake_system.stake_withdraw_bump_seed,
+            slots_for_stake_delta: stake_system.slots_for_stake_delta.to_string(),
+            last_stake_delta_epoch: stake_system.last_stake_delta_epoch.to_string(),
+            min_stake: stake_system.min_stake.to_string(),
+            extra_stake_delta_runs: stake_system.extra_stake_delta_runs,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ValidatorSystemDto {
+    pub validator_list: ListDto,
+    pub manager_authority: String,
+    pub total_validator_score: u32,
+    pub total_active_balance: String,
+    pub auto_add_validator_enabled: bool,
+}
+
+impl From<&ValidatorSystem> for ValidatorSystemDto {
+    fn from(validator_system: &ValidatorSystem) -> Self {
+        Self {
+            validator_list: (&validator_system.validator_list).into(),
+            manager_authority: validator_system.manager_authority.to_string(),
+            total_validator_score: validator_system.total_validator_score,
+            total_active_balance: validator_system.total_active_balance.to_string(),
+            auto_add_validator_enabled: validator_system.auto_add_validator_enabled != 0,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MarinadeDto {
+    pub msol_mint: String,
+    pub admin_authority: String,
+    pub operational_sol_account: String,
+    pub treasury_msol_account: String,
+    pub reserve_bump_seed: u8,
+    pub msol_mint_authority_bump_seed: u8,
+    pub rent_exempt_for_token_acc: String,
+    pub reward_fee: FeeDto,
+    pub stake_system: StakeSystemDto,
+    pub validator_system: ValidatorSystemDto,
+    pub liq_pool: LiqPoolDto,
+    pub available_reserve_balance: String,
+    pub msol_supply: String,
+    pub msol_price: String,
+    pub circulating_ticket_count: String,
+    pub circulating_ticket_balance: String,
+    pub lent_from_reserve: String,
+    pub min_deposit: String,
+    pub min_withdraw: String,
+    pub staking_sol_cap: String,
+    pub emergency_cooling_down: String,
+}
+
+impl From<&Marinade> for MarinadeDto {
+    fn from(marinade: &Marinade) -> Self {
+        Self {
+            msol_mint: marinade.msol_mint.to_string(),
+            admin_authority: marinade.admin_authority.to_string(),
+            operational_sol_account: marinade.operational_sol_account.to_string(),
+            treasury_msol_account: marinade.treasury_msol_account.to_string(),
+            reserve_bump_seed: marinade.reserve_bump_seed,
+            msol_mint_authority_bump_seed: marinade.msol_mint_authority_bump_seed,
+            rent_exempt_for_token_acc: marinade.rent_exempt_for_token_acc.to_string(),
+            reward_fee: (&marinade.reward_fee).into(),
+            stake_system: (&marinade.stake_system).into(),
+            validator_system: (&marinade.validator_system).into(),
+            liq_pool: (&marinade.liq_pool).into(),
+            available_reserve_balance: marinade.available_reserve_balance.to_string(),
+            msol_supply: marinade.msol_supply.to_string(),
+            msol_price: marinade.msol_price.to_string(),
+            circulating_ticket_count: marinade.circulating_ticket_count.to_string(),
+            circulating_ticket_balance: marinade.circulating_ticket_balance.to_string(),
+            lent_from_reserve: marinade.lent_from_reserve.to_string(),
+            min_deposit: marinade.min_deposit.to_string(),
+            min_withdraw: marinade.min_withdraw.to_string(),
+            staking_sol_cap: marinade.staking_sol_cap.to_string(),
+            emergency_cooling_down: marinade.emergency_cooling_down.to_string(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ValidatorRecordDto {
+    pub validator_account: String,
+    pub active_balance: String,
+    pub score: u32,
+    pub last_stake_delta_epoch: String,
+    pub duplication_flag_bump_seed: u8,
+}
+
+impl From<&ValidatorRecord> for ValidatorRecordDto {
+    fn from(record: &ValidatorRecord) -> Self {
+        Self {
+            validator_account: record.validator_account.to_string(),
+            active_balance: record.active_balance.to_string(),
+            score: record.score,
+            last_stake_delta_epoch: record.last_stake_delta_epoch.to_string(),
+            duplication_flag_bump_seed: record.duplication_flag_bump_seed,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct StakeRecordDto {
+    pub stake_account: String,
+    pub last_update_delegated_lamports: String,
+    pub last_update_epoch: String,
+    pub is_emergency_unstaking: bool,
+}
+
+impl From<&StakeRecord> for StakeRecordDto {
+    fn from(record: &StakeRecord) -> Self {
+        Self {
+            stake_account: record.stake_account.to_string(),
+            last_update_delegated_lamports: record.last_update_delegated_lamports.to_string(),
+            last_update_epoch: record.last_update_epoch.to_string(),
+            is_emergency_unstaking: record.is_emergency_unstaking != 0,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DelayedUnstakeTicketDto {
+    pub state_address: String,
+    pub beneficiary: String,
+    pub lamports_amount: String,
+    pub created_epoch: String,
+}
+
+impl From<&DelayedUnstakeTicket> for DelayedUnstakeTicketDto {
+    fn from(ticket: &DelayedUnstakeTicket) -> Self {
+        Self {
+            state_address: ticket.state_address.to_string(),
+            beneficiary: ticket.beneficiary.to_string(),
+            lamports_amount: ticket.lamports_amount.to_string(),
+            created_epoch: ticket.created_epoch.to_string(),
+        }
+    }
+}
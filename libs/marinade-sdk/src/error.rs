@@ -1,8 +1,37 @@
-use derive_more::Display;
-use solana_program::program_error::ProgramError;
+use std::fmt::Display;
 
-#[repr(u32)]
-#[derive(Debug, Clone, Copy, Display)]
+#[cfg(feature = "rpc")]
+use solana_program::{instruction::InstructionError, message::Message};
+use solana_program::{program_error::ProgramError, pubkey::Pubkey};
+#[cfg(feature = "rpc")]
+use solana_sdk::transaction::TransactionError;
+use thiserror::Error;
+
+/// Context of a failed comparison, e.g. the minimum and the amount for NumberTooLow
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Mismatch<T> {
+    pub expected: T,
+    pub actual: T,
+}
+
+impl<T> Mismatch<T> {
+    pub fn new(expected: T, actual: T) -> Self {
+        Self { expected, actual }
+    }
+}
+
+fn context<T: Display>(mismatch: &Option<Mismatch<T>>) -> String {
+    match mismatch {
+        Some(Mismatch { expected, actual }) => format!(": expected {}, got {}", expected, actual),
+        None => String::new(),
+    }
+}
+
+/// Errors of the program and of the SDK calculations mirroring it. Context is set where the
+/// SDK raises the error, errors decoded from a program error code have none.
+/// Displayed as the variant name followed by the context, if any.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Error)]
+#[non_exhaustive]
 pub enum CommonError {
     WrongReserveOwner,
     NonEmptyReserveData,
@@ -15,29 +44,294 @@ pub enum CommonError {
     InvalidMintAuthority,
     MintHasInitialSupply,
     InvalidOwnerFeeState,
-    InvalidProgramId = 6116,
-    UnexpectedAccount = 65140,
-    CalculationFailure = 51619,
-    AccountWithLockup = 45694,
-    NumberTooLow = 7892,
-    NumberTooHigh = 7893,
-    FeeTooHigh = 4052,
-    FeesWrongWayRound = 4053,
-    LiquidityTargetTooLow = 4054,
-    TicketNotDue = 4055,
-    TicketNotReady = 4056,
-    WrongBeneficiary = 4057,
-    StakeAccountNotUpdatedYet = 4058,
-    StakeNotDelegated = 4059,
-    StakeAccountIsEmergencyUnstaking = 4060,
-    InsufficientLiquidity = 4205,
-    InvalidValidator = 47525,
+    InvalidProgramId,
+    UnexpectedAccount(Option<Mismatch<Pubkey>>),
+    CalculationFailure,
+    AccountWithLockup,
+    /// expected is the minimum
+    NumberTooLow(Option<Mismatch<u64>>),
+    /// expected is the maximum
+    NumberTooHigh(Option<Mismatch<u64>>),
+    /// expected is the maximum, in the unit of the fee (basis points or their hundredths)
+    FeeTooHigh(Option<Mismatch<u32>>),
+    FeesWrongWayRound,
+    LiquidityTargetTooLow,
+    TicketNotDue,
+    TicketNotReady,
+    WrongBeneficiary,
+    StakeAccountNotUpdatedYet,
+    StakeNotDelegated,
+    StakeAccountIsEmergencyUnstaking,
+    InsufficientLiquidity,
+    InvalidValidator,
+    /// Staking or liquidity cap, the program raises the same code for both.
+    /// expected is the cap
+    CapReached(Option<Mismatch<u64>>),
+}
+
+impl Display for CommonError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let context = match self {
+            CommonError::UnexpectedAccount(mismatch) => context(mismatch),
+            CommonError::NumberTooLow(mismatch)
+            | CommonError::NumberTooHigh(mismatch)
+            | CommonError::CapReached(mismatch) => context(mismatch),
+            CommonError::FeeTooHigh(mismatch) => context(mismatch),
+            _ => String::new(),
+        };
+        write!(f, "{}{}", self.name(), context)
+    }
 }
 
 const ERROR_CODE_OFFSET: u32 = 300;
+/// Raised as is, without the offset
+const CAP_REACHED_CODE: u32 = 3782;
+
+impl CommonError {
+    /// Variant name, without the context
+    pub fn name(&self) -> &'static str {
+        match self {
+            CommonError::WrongReserveOwner => "WrongReserveOwner",
+            CommonError::NonEmptyReserveData => "NonEmptyReserveData",
+            CommonError::InvalidInitialReserveLamports => "InvalidInitialReserveLamports",
+            CommonError::ZeroValidatorChunkSize => "ZeroValidatorChunkSize",
+            CommonError::TooBigValidatorChunkSize => "TooBigValidatorChunkSize",
+            CommonError::ZeroCreditChunkSize => "ZeroCreditChunkSize",
+            CommonError::TooBigCreditChunkSize => "TooBigCreditChunkSize",
+            CommonError::TooLowCreditFee => "TooLowCreditFee",
+            CommonError::InvalidMintAuthority => "InvalidMintAuthority",
+            CommonError::MintHasInitialSupply => "MintHasInitialSupply",
+            CommonError::InvalidOwnerFeeState => "InvalidOwnerFeeState",
+            CommonError::InvalidProgramId => "InvalidProgramId",
+            CommonError::UnexpectedAccount(_) => "UnexpectedAccount",
+            CommonError::CalculationFailure => "CalculationFailure",
+            CommonError::AccountWithLockup => "AccountWithLockup",
+            CommonError::NumberTooLow(_) => "NumberTooLow",
+            CommonError::NumberTooHigh(_) => "NumberTooHigh",
+            CommonError::FeeTooHigh(_) => "FeeTooHigh",
+            CommonError::FeesWrongWayRound => "FeesWrongWayRound",
+            CommonError::LiquidityTargetTooLow => "LiquidityTargetTooLow",
+            CommonError::TicketNotDue => "TicketNotDue",
+            CommonError::TicketNotReady => "TicketNotReady",
+            CommonError::WrongBeneficiary => "WrongBeneficiary",
+            CommonError::StakeAccountNotUpdatedYet => "StakeAccountNotUpdatedYet",
+            CommonError::StakeNotDelegated => "StakeNotDelegated",
+            CommonError::StakeAccountIsEmergencyUnstaking => "StakeAccountIsEmergencyUnstaking",
+            CommonError::InsufficientLiquidity => "InsufficientLiquidity",
+            CommonError::InvalidValidator => "InvalidValidator",
+            CommonError::CapReached(_) => "CapReached",
+        }
+    }
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            CommonError::WrongReserveOwner => {
+                "The reserve account is not owned by the system program"
+            }
+            CommonError::NonEmptyReserveData => "The reserve account holds data",
+            CommonError::InvalidInitialReserveLamports => {
+                "The reserve account does not hold exactly its rent exemption"
+            }
+            CommonError::ZeroValidatorChunkSize => "Validator chunk size is zero",
+            CommonError::TooBigValidatorChunkSize => "Validator chunk size is too big",
+            CommonError::ZeroCreditChunkSize => "Credit chunk size is zero",
+            CommonError::TooBigCreditChunkSize => "Credit chunk size is too big",
+            CommonError::TooLowCreditFee => "Credit fee is too low",
+            CommonError::InvalidMintAuthority => {
+                "The mint authority is not the expected program address"
+            }
+            CommonError::MintHasInitialSupply => "The mint must have no supply",
+            CommonError::InvalidOwnerFeeState => "Invalid owner fee state",
+            CommonError::InvalidProgramId => "An account is owned by an unexpected program",
+            CommonError::UnexpectedAccount(_) => "An account is not the one the state expects",
+            CommonError::CalculationFailure => "Arithmetic overflow or underflow",
+            CommonError::AccountWithLockup => "The stake account has a lockup",
+            CommonError::NumberTooLow(_) => {
+                "An amount is below the minimum, e.g. min_deposit or min_withdraw"
+            }
+            CommonError::NumberTooHigh(_) => "An amount is above the maximum",
+            CommonError::FeeTooHigh(_) => "A fee is above its maximum",
+            CommonError::FeesWrongWayRound => "The liq pool min fee is above the max fee",
+            CommonError::LiquidityTargetTooLow => {
+                "The liq pool liquidity target is below its minimum"
+            }
+            CommonError::TicketNotDue => "The ticket's unstake epoch has not ended yet",
+            CommonError::TicketNotReady => {
+                "The ticket is due but the waiting time after the epoch start has not passed"
+            }
+            CommonError::WrongBeneficiary => "The SOL destination is not the ticket beneficiary",
+            CommonError::StakeAccountNotUpdatedYet => {
+                "The stake account has to be updated in this epoch first"
+            }
+            CommonError::StakeNotDelegated => "The stake account is not delegated",
+            CommonError::StakeAccountIsEmergencyUnstaking => {
+                "The stake account is being emergency unstaked"
+            }
+            CommonError::InsufficientLiquidity => {
+                "The liq pool has not enough SOL for the liquid unstake"
+            }
+            CommonError::InvalidValidator => {
+                "The validator does not match its validator list record"
+            }
+            CommonError::CapReached(_) => "The staking or liquidity cap would be exceeded",
+        }
+    }
+
+    /// ProgramError::Custom code the program fails with
+    pub fn custom_code(&self) -> u32 {
+        let code = match self {
+            CommonError::WrongReserveOwner => 0,
+            CommonError::NonEmptyReserveData => 1,
+            CommonError::InvalidInitialReserveLamports => 2,
+            CommonError::ZeroValidatorChunkSize => 3,
+            CommonError::TooBigValidatorChunkSize => 4,
+            CommonError::ZeroCreditChunkSize => 5,
+            CommonError::TooBigCreditChunkSize => 6,
+            CommonError::TooLowCreditFee => 7,
+            CommonError::InvalidMintAuthority => 8,
+            CommonError::MintHasInitialSupply => 9,
+            CommonError::InvalidOwnerFeeState => 10,
+            CommonError::InvalidProgramId => 6116,
+            CommonError::UnexpectedAccount(_) => 65140,
+            CommonError::CalculationFailure => 51619,
+            CommonError::AccountWithLockup => 45694,
+            CommonError::NumberTooLow(_) => 7892,
+            CommonError::NumberTooHigh(_) => 7893,
+            CommonError::FeeTooHigh(_) => 4052,
+            CommonError::FeesWrongWayRound => 4053,
+            CommonError::LiquidityTargetTooLow => 4054,
+            CommonError::TicketNotDue => 4055,
+            CommonError::TicketNotReady => 4056,
+            CommonError::WrongBeneficiary => 4057,
+            CommonError::StakeAccountNotUpdatedYet => 4058,
+            CommonError::StakeNotDelegated => 4059,
+            CommonError::StakeAccountIsEmergencyUnstaking => 4060,
+            CommonError::InsufficientLiquidity => 4205,
+            CommonError::InvalidValidator => 47525,
+            CommonError::CapReached(_) => return CAP_REACHED_CODE,
+        };
+        code + ERROR_CODE_OFFSET
+    }
+
+    /// Error of a ProgramError::Custom code, e.g. from a failed simulation.
+    /// None for codes of other programs or of program errors the SDK does not know
+    pub fn from_custom_code(code: u32) -> Option<Self> {
+        if code == CAP_REACHED_CODE {
+            return Some(CommonError::CapReached(None));
+        }
+        let error = match code.checked_sub(ERROR_CODE_OFFSET)? {
+            0 => CommonError::WrongReserveOwner,
+            1 => CommonError::NonEmptyReserveData,
+            2 => CommonError::InvalidInitialReserveLamports,
+            3 => CommonError::ZeroValidatorChunkSize,
+            4 => CommonError::TooBigValidatorChunkSize,
+            5 => CommonError::ZeroCreditChunkSize,
+            6 => CommonError::TooBigCreditChunkSize,
+            7 => CommonError::TooLowCreditFee,
+            8 => CommonError::InvalidMintAuthority,
+            9 => CommonError::MintHasInitialSupply,
+            10 => CommonError::InvalidOwnerFeeState,
+            6116 => CommonError::InvalidProgramId,
+            65140 => CommonError::UnexpectedAccount(None),
+            51619 => CommonError::CalculationFailure,
+            45694 => CommonError::AccountWithLockup,
+            7892 => CommonError::NumberTooLow(None),
+            7893 => CommonError::NumberTooHigh(None),
+            4052 => CommonError::FeeTooHigh(None),
+            4053 => CommonError::FeesWrongWayRound,
+            4054 => CommonError::LiquidityTargetTooLow,
+            4055 => CommonError::TicketNotDue,
+            4056 => CommonError::TicketNotReady,
+            4057 => CommonError::WrongBeneficiary,
+            4058 => CommonError::StakeAccountNotUpdatedYet,
+            4059 => CommonError::StakeNotDelegated,
+            4060 => CommonError::StakeAccountIsEmergencyUnstaking,
+            4205 => CommonError::InsufficientLiquidity,
+            47525 => CommonError::InvalidValidator,
+            _ => return None,
+        };
+        Some(error)
+    }
+}
 
 impl From<CommonError> for ProgramError {
     fn from(e: CommonError) -> Self {
-        ProgramError::Custom(e as u32 + ERROR_CODE_OFFSET)
+        ProgramError::Custom(e.custom_code())
     }
 }
+
+/// Marinade error a transaction failed with
+#[cfg(feature = "rpc")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Error)]
+#[error(
+    "Instruction {} failed with {} ({}): {}",
+    instruction_index,
+    error.name(),
+    code,
+    error.description()
+)]
+pub struct MarinadeErrorInfo {
+    /// Position of the failed instruction in the transaction
+    pub instruction_index: u8,
+    pub code: u32,
+    pub error: CommonError,
+}
+
+/// Marinade error of a failed transaction or simulation; None for other failures and for
+/// custom codes the SDK does not know.
+///
+/// **The program raising the code is not checked**: a custom code of any other program in
+/// the same range decodes as a Marinade error too. Prefer decode_message_error, which checks
+/// the program of the failed instruction, or decode_logged_error, which also finds Marinade
+/// failing inside a CPI.
+#[cfg(feature = "rpc")]
+pub fn decode_program_error(error: &TransactionError) -> Option<MarinadeErrorInfo> {
+    match error {
+        TransactionError::InstructionError(instruction_index, InstructionError::Custom(code)) => {
+            Some(MarinadeErrorInfo {
+                instruction_index: *instruction_index,
+                code: *code,
+                error: CommonError::from_custom_code(*code)?,
+            })
+        }
+        _ => None,
+    }
+}
+
+/// decode_program_error, only when the failed instruction of message calls program_id.
+/// A Marinade failure inside a CPI is reported against the calling top level instruction,
+/// so it is not decoded; see decode_logged_error
+#[cfg(feature = "rpc")]
+pub fn decode_message_error(
+    error: &TransactionError,
+    message: &Message,
+    program_id: &Pubkey,
+) -> Option<MarinadeErrorInfo> {
+    let info = decode_program_error(error)?;
+    let instruction = message.instructions.get(info.instruction_index as usize)?;
+    if message
+        .account_keys
+        .get(instruction.program_id_index as usize)?
+        == program_id
+    {
+        Some(info)
+    } else {
+        None
+    }
+}
+
+/// decode_program_error, only when the transaction logs show program_id failing with the
+/// code, at any invocation depth (e.g. Marinade called by another program through CPI)
+#[cfg(feature = "rpc")]
+pub fn decode_logged_error<S: AsRef<str>>(
+    error: &TransactionError,
+    logs: &[S],
+    program_id: &Pubkey,
+) -> Option<MarinadeErrorInfo> {
+    let info = decode_program_error(error)?;
+    let failure = format!(
+        "Program {} failed: custom program error: {:#x}",
+        program_id, info.code
+    );
+    logs.iter().any(|log| log.as_ref() == failure).then_some(info)
+}
@@ -0,0 +1,171 @@
+//! Events of the Marinade program read back from transaction logs (log_messages of the
+//! transaction meta).
+//!
+//! Anchor emits an event as a "Program data: <base64>" line holding the event discriminator
+//! (sha256("event:<Name>")[..8]) and the borsh encoded event. Only lines written while the
+//! Marinade program is executing are taken, so a CPI caller can not forge them; the program is
+//! recognised by the program id of the MarinadeProgram passed in.
+//! Deployments before the events were added write msg! lines only, see program_messages.
+//! The event layouts follow the program's events.rs.
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use derive_more::Display;
+use micro_anchor::Discriminator;
+use solana_program::pubkey::Pubkey;
+
+use crate::program::MarinadeProgram;
+
+#[derive(Clone, Debug, Display, PartialEq, Eq)]
+pub enum EventError {
+    #[display(fmt = "Can not decode log line {}", _0)]
+    InvalidLogLine(String),
+    #[display(fmt = "Can not deserialize {} data", _0)]
+    InvalidData(&'static str),
+}
+
+impl std::error::Error for EventError {}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, BorshSerialize, BorshDeserialize)]
+pub struct DepositEvent {
+    pub state: Pubkey,
+    pub sol_owner: Pubkey,
+    pub user_sol_balance: u64,
+    pub user_msol_balance: u64,
+    pub sol_leg_balance: u64,
+    pub msol_leg_balance: u64,
+    pub reserve_balance: u64,
+    pub sol_swapped: u64,
+    pub msol_swapped: u64,
+    pub sol_deposited: u64,
+    pub msol_minted: u64,
+    pub total_virtual_staked_lamports: u64,
+    pub msol_supply: u64,
+}
+
+impl Discriminator for DepositEvent {
+    const DISCRIMINATOR: [u8; 8] = [120, 248, 61, 83, 31, 142, 107, 144];
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, BorshSerialize, BorshDeserialize)]
+pub struct LiquidUnstakeEvent {
+    pub state: Pubkey,
+    pub msol_owner: Pubkey,
+    pub liq_pool_sol_balance: u64,
+    pub liq_pool_msol_balance: u64,
+    pub treasury_msol_balance: Option<u64>,
+    pub user_msol_balance: u64,
+    pub user_sol_balance: u64,
+    pub msol_amount: u64,
+    pub msol_fee: u64,
+    pub treasury_msol_cut: u64,
+    pub sol_amount: u64,
+    pub lp_liquidity_target: u64,
+    /// Fees in hundredths of a basis point
+    pub lp_max_fee_bp_cents: u32,
+    pub lp_min_fee_bp_cents: u32,
+    pub treasury_cut_bp_cents: u32,
+}
+
+impl Discriminator for LiquidUnstakeEvent {
+    const DISCRIMINATOR: [u8; 8] = [173, 5, 147, 15, 5, 14, 194, 116];
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, BorshSerialize, BorshDeserialize)]
+pub struct ClaimEvent {
+    pub state: Pubkey,
+    pub epoch: u64,
+    pub ticket: Pubkey,
+    pub beneficiary: Pubkey,
+    pub circulating_ticket_balance: u64,
+    pub circulating_ticket_count: u64,
+    pub reserve_balance: u64,
+    pub user_balance: u64,
+    pub amount: u64,
+}
+
+impl Discriminator for ClaimEvent {
+    const DISCRIMINATOR: [u8; 8] = [93, 15, 70, 170, 48, 140, 212, 219];
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum MarinadeEvent {
+    Deposit(DepositEvent),
+    LiquidUnstake(LiquidUnstakeEvent),
+    Claim(ClaimEvent),
+    /// Event the SDK has no type for, with its borsh data
+    Other([u8; 8], Vec<u8>),
+}
+
+impl MarinadeEvent {
+    /// Event of a "Program data:" payload, None when too short to hold a discriminator
+    pub fn from_data(data: &[u8]) -> Result<Option<Self>, EventError> {
+        fn event<E: BorshDeserialize>(
+            mut data: &[u8],
+            name: &'static str,
+        ) -> Result<E, EventError> {
+            E::deserialize(&mut data).map_err(|_| EventError::InvalidData(name))
+        }
+
+        let discriminator: [u8; 8] = match data.get(..8) {
+            Some(discriminator) => discriminator.try_into().unwrap(),
+            None => return Ok(None),
+        };
+        let data = &data[8..];
+        Ok(Some(match discriminator {
+            DepositEvent::DISCRIMINATOR => Self::Deposit(event(data, "DepositEvent")?),
+            LiquidUnstakeEvent::DISCRIMINATOR => {
+                Self::LiquidUnstake(event(data, "LiquidUnstakeEvent")?)
+            }
+            ClaimEvent::DISCRIMINATOR => Self::Claim(event(data, "ClaimEvent")?),
+            _ => Self::Other(discriminator, data.to_vec()),
+        }))
+    }
+}
+
+/// Log lines written while the Marinade program was executing, CPI callers and callees excluded
+fn marinade_lines<'a>(
+    program: &MarinadeProgram,
+    logs: &'a [String],
+) -> impl Iterator<Item = &'a str> {
+    let marinade_invoke = format!("Program {} invoke", program.program_id);
+    let mut stack: Vec<bool> = Vec::new();
+    logs.iter().filter_map(move |line| {
+        let is_output = line.starts_with("Program log: ") || line.starts_with("Program data: ");
+        if !is_output && line.starts_with("Program ") {
+            if line.contains(" invoke [") {
+                stack.push(line.starts_with(&marinade_invoke));
+            } else if line.ends_with(" success") || line.contains(" failed: ") {
+                stack.pop();
+            }
+            return None;
+        }
+        match stack.last() {
+            Some(true) => Some(line.as_str()),
+            _ => None,
+        }
+    })
+}
+
+/// Events emitted by the Marinade program, top level and CPI, in log order
+pub fn parse_events(
+    program: &MarinadeProgram,
+    logs: &[String],
+) -> Result<Vec<MarinadeEvent>, EventError> {
+    let mut events = Vec::new();
+    for line in marinade_lines(program, logs) {
+        if let Some(encoded) = line.strip_prefix("Program data: ") {
+            let data = base64::decode(encoded)
+                .map_err(|_| EventError::InvalidLogLine(line.to_string()))?;
+            events.extend(MarinadeEvent::from_data(&data)?);
+        }
+    }
+    Ok(events)
+}
+
+/// msg! lines of the Marinade program without the "Program log: " prefix,
+/// e.g. "Instruction: Deposit" written by anchor before every instruction
+pub fn program_messages<'a>(program: &MarinadeProgram, logs: &'a [String]) -> Vec<&'a str> {
+    marinade_lines(program, logs)
+        .filter_map(|line| line.strip_prefix("Program log: "))
+        .collect()
+}
@@ -0,0 +1,254 @@
+//! Flat-file (CSV) export of decoded list records, tickets, validator reports and epoch reports.
+//! Parquet is written by the [`parquet`] module behind the `parquet` feature.
+//!
+//! Column names are part of the public interface: they are only ever appended to,
+//! never renamed or reordered, so dashboards can rely on them across releases.
+
+#[cfg(feature = "parquet")]
+pub mod parquet;
+
+use std::{
+    borrow::Cow,
+    io::{self, Write},
+};
+
+use solana_program::pubkey::Pubkey;
+
+use crate::{
+    calc::proportional,
+    error::CommonError,
+    state::{
+        delayed_unstake_ticket::DelayedUnstakeTicket, marinade::Marinade,
+        stake_system::StakeRecord, validator_system::ValidatorRecord,
+    },
+    validator_report::ValidatorReport,
+};
+
+/// A record that can be written as a single CSV row
+pub trait CsvRecord {
+    const COLUMNS: &'static [&'static str];
+    fn csv_fields(&self) -> Vec<String>;
+}
+
+impl CsvRecord for ValidatorRecord {
+    const COLUMNS: &'static [&'static str] = &[
+        "validator_account",
+        "active_balance",
+        "score",
+        "last_stake_delta_epoch",
+        "duplication_flag_bump_seed",
+    ];
+
+    fn csv_fields(&self) -> Vec<String> {
+        vec![
+            self.validator_account.to_string(),
+            self.active_balance.to_string(),
+            self.score.to_string(),
+            self.last_stake_delta_epoch.to_string(),
+            self.duplication_flag_bump_seed.to_string(),
+        ]
+    }
+}
+
+impl CsvRecord for StakeRecord {
+    const COLUMNS: &'static [&'static str] = &[
+        "stake_account",
+        "last_update_delegated_lamports",
+        "last_update_epoch",
+        "is_emergency_unstaking",
+    ];
+
+    fn csv_fields(&self) -> Vec<String> {
+        vec![
+            self.stake_account.to_string(),
+            self.last_update_delegated_lamports.to_string(),
+            self.last_update_epoch.to_string(),
+            self.is_emergency_unstaking.to_string(),
+        ]
+    }
+}
+
+impl CsvRecord for DelayedUnstakeTicket {
+    const COLUMNS: &'static [&'static str] = &[
+        "state_address",
+        "beneficiary",
+        "lamports_amount",
+        "created_epoch",
+    ];
+
+    fn csv_fields(&self) -> Vec<String> {
+        vec![
+            self.state_address.to_string(),
+            self.beneficiary.to_string(),
+            self.lamports_amount.to_string(),
+            self.created_epoch.to_string(),
+        ]
+    }
+}
+
+/// A validator of a [`ValidatorReport`] with its share of the Marinade stake and its place in
+/// the cluster
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ValidatorReportRow {
+    pub validator_account: Pubkey,
+    pub active_balance: u64,
+    pub score: u32,
+    /// Share of the Marinade stake in basis points
+    pub stake_share_bps: u64,
+    /// activated_stake of the vote account, 0 if the cluster does not know it
+    pub network_stake: u64,
+    pub superminority: bool,
+}
+
+impl ValidatorReport {
+    /// One row per validator of the list, in list order
+    pub fn rows(&self) -> Result<Vec<ValidatorReportRow>, CommonError> {
+        let total_stake = self.total_stake();
+        let superminority = self.superminority();
+        self.validators
+            .iter()
+            .map(|validator| {
+                Ok(ValidatorReportRow {
+                    validator_account: validator.validator_account,
+                    active_balance: validator.active_balance,
+                    score: validator.score,
+                    stake_share_bps: if total_stake == 0 {
+                        0
+                    } else {
+                        proportional(10_000, validator.active_balance, total_stake)?
+                    },
+                    network_stake: self
+                        .network_stakes
+                        .iter()
+                        .find(|(vote, _)| *vote == validator.validator_account)
+                        .map_or(0, |(_, stake)| *stake),
+                    superminority: superminority.contains(&validator.validator_account),
+                })
+            })
+            .collect()
+    }
+}
+
+impl CsvRecord for ValidatorReportRow {
+    const COLUMNS: &'static [&'static str] = &[
+        "validator_account",
+        "active_balance",
+        "score",
+        "stake_share_bps",
+        "network_stake",
+        "superminority",
+    ];
+
+    fn csv_fields(&self) -> Vec<String> {
+        vec![
+            self.validator_account.to_string(),
+            self.active_balance.to_string(),
+            self.score.to_string(),
+            self.stake_share_bps.to_string(),
+            self.network_stake.to_string(),
+            self.superminority.to_string(),
+        ]
+    }
+}
+
+/// Pool totals of the state at an epoch, one row of a per-epoch time series
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct EpochReport {
+    pub epoch: u64,
+    /// Lamports of one mSOL scaled by Marinade::PRICE_DENOMINATOR, as stored by the program
+    pub msol_price: u64,
+    pub msol_supply: u64,
+    pub total_active_balance: u64,
+    pub total_cooling_down: u64,
+    pub available_reserve_balance: u64,
+    pub circulating_ticket_balance: u64,
+    pub total_lamports_under_control: u64,
+}
+
+impl EpochReport {
+    pub fn new(epoch: u64, state: &Marinade) -> Result<Self, CommonError> {
+        Ok(Self {
+            epoch,
+            msol_price: state.msol_price,
+            msol_supply: state.msol_supply,
+            total_active_balance: state.validator_system.total_active_balance,
+            total_cooling_down: state.try_total_cooling_down()?,
+            available_reserve_balance: state.available_reserve_balance,
+            circulating_ticket_balance: state.circulating_ticket_balance,
+            total_lamports_under_control: state.try_total_lamports_under_control()?,
+        })
+    }
+}
+
+impl CsvRecord for EpochReport {
+    const COLUMNS: &'static [&'static str] = &[
+        "epoch",
+        "msol_price",
+        "msol_supply",
+        "total_active_balance",
+        "total_cooling_down",
+        "available_reserve_balance",
+        "circulating_ticket_balance",
+        "total_lamports_under_control",
+    ];
+
+    fn csv_fields(&self) -> Vec<String> {
+        vec![
+            self.epoch.to_string(),
+            self.msol_price.to_string(),
+            self.msol_supply.to_string(),
+            self.total_active_balance.to_string(),
+            self.total_cooling_down.to_string(),
+            self.available_reserve_balance.to_string(),
+            self.circulating_ticket_balance.to_string(),
+            self.total_lamports_under_control.to_string(),
+        ]
+    }
+}
+
+/// Quotes a field containing a separator, a quote or a line break (RFC 4180).
+/// The SDK records only export pubkeys, integers and booleans, which are never quoted.
+fn quote_field(field: &str) -> Cow<str> {
+    if field.contains(|c: char| matches!(c, ',' | '"' | '\n' | '\r')) {
+        Cow::Owned(format!("\"{}\"", field.replace('"', "\"\"")))
+    } else {
+        Cow::Borrowed(field)
+    }
+}
+
+fn csv_row<R: CsvRecord>(record: &R) -> String {
+    record
+        .csv_fields()
+        .iter()
+        .map(|field| quote_field(field))
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+/// Writes the header row followed by one row per record
+pub fn write_csv<'a, W, R, I>(writer: &mut W, records: I) -> io::Result<()>
+where
+    W: Write,
+    R: CsvRecord + 'a,
+    I: IntoIterator<Item = &'a R>,
+{
+    writeln!(writer, "{}", R::COLUMNS.join(","))?;
+    for record in records {
+        writeln!(writer, "{}", csv_row(record))?;
+    }
+    Ok(())
+}
+
+/// Same as [`write_csv`] but for records keyed by (ticket or stake) account address
+pub fn write_csv_with_address<'a, W, R, I>(writer: &mut W, records: I) -> io::Result<()>
+where
+    W: Write,
+    R: CsvRecord + 'a,
+    I: IntoIterator<Item = (&'a Pubkey, &'a R)>,
+{
+    writeln!(writer, "address,{}", R::COLUMNS.join(","))?;
+    for (address, record) in records {
+        writeln!(writer, "{},{}", address, csv_row(record))?;
+    }
+    Ok(())
+}
@@ -0,0 +1,225 @@
+//! Parquet export (arrow2) of the same records and columns as the CSV writers.
+//! Pubkeys are written as base58 strings, integers as unsigned 64-bit and flags as booleans.
+
+use std::io::Write;
+
+use arrow2::{
+    array::{Array, BooleanArray, UInt64Array, Utf8Array},
+    chunk::Chunk,
+    datatypes::{DataType, Field, Schema},
+    error::Error,
+    io::parquet::write::{
+        transverse, CompressionOptions, Encoding, FileWriter, RowGroupIterator, Version,
+        WriteOptions,
+    },
+};
+use solana_program::pubkey::Pubkey;
+
+use crate::{
+    export::{CsvRecord, EpochReport, ValidatorReportRow},
+    state::{
+        delayed_unstake_ticket::DelayedUnstakeTicket, stake_system::StakeRecord,
+        validator_system::ValidatorRecord,
+    },
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ColumnType {
+    Utf8,
+    UInt64,
+    Boolean,
+}
+
+impl ColumnType {
+    fn data_type(self) -> DataType {
+        match self {
+            ColumnType::Utf8 => DataType::Utf8,
+            ColumnType::UInt64 => DataType::UInt64,
+            ColumnType::Boolean => DataType::Boolean,
+        }
+    }
+
+    fn array(self, column: &str, fields: Vec<String>) -> Result<Box<dyn Array>, Error> {
+        fn parse<T: std::str::FromStr>(column: &str, field: &str) -> Result<T, Error> {
+            field.parse().map_err(|_| {
+                Error::InvalidArgumentError(format!("{}: invalid value {}", column, field))
+            })
+        }
+        Ok(match self {
+            ColumnType::Utf8 => Box::new(Utf8Array::<i32>::from_slice(fields)),
+            ColumnType::UInt64 => Box::new(UInt64Array::from_vec(
+                fields
+                    .iter()
+                    .map(|field| parse(column, field))
+                    .collect::<Result<_, _>>()?,
+            )),
+            ColumnType::Boolean => Box::new(BooleanArray::from_slice(
+                fields
+                    .iter()
+                    .map(|field| parse(column, field))
+                    .collect::<Result<Vec<bool>, _>>()?,
+            )),
+        })
+    }
+}
+
+/// A [`CsvRecord`] with the type of each of its columns
+pub trait ParquetRecord: CsvRecord {
+    const COLUMN_TYPES: &'static [ColumnType];
+}
+
+impl ParquetRecord for ValidatorRecord {
+    const COLUMN_TYPES: &'static [ColumnType] = &[
+        ColumnType::Utf8,
+        ColumnType::UInt64,
+        ColumnType::UInt64,
+        ColumnType::UInt64,
+        ColumnType::UInt64,
+    ];
+}
+
+impl ParquetRecord for StakeRecord {
+    const COLUMN_TYPES: &'static [ColumnType] = &[
+        ColumnType::Utf8,
+        ColumnType::UInt64,
+        ColumnType::UInt64,
+        ColumnType::UInt64,
+    ];
+}
+
+impl ParquetRecord for DelayedUnstakeTicket {
+    const COLUMN_TYPES: &'static [ColumnType] = &[
+        ColumnType::Utf8,
+        ColumnType::Utf8,
+        ColumnType::UInt64,
+        ColumnType::UInt64,
+    ];
+}
+
+impl ParquetRecord for ValidatorReportRow {
+    const COLUMN_TYPES: &'static [ColumnType] = &[
+        ColumnType::Utf8,
+        ColumnType::UInt64,
+        ColumnType::UInt64,
+        ColumnType::UInt64,
+        ColumnType::UInt64,
+        ColumnType::Boolean,
+    ];
+}
+
+impl ParquetRecord for EpochReport {
+    const COLUMN_TYPES: &'static [ColumnType] = &[ColumnType::UInt64; 8];
+}
+
+/// Writes the columns as a single row group
+fn write_columns<W: Write>(
+    writer: W,
+    columns: Vec<(&str, ColumnType, Vec<String>)>,
+) -> Result<(), Error> {
+    let schema = Schema::from(
+        columns
+            .iter()
+            .map(|(name, column_type, _)| Field::new(*name, column_type.data_type(), false))
+            .collect::<Vec<_>>(),
+    );
+    let arrays = columns
+        .into_iter()
+        .map(|(name, column_type, fields)| column_type.array(name, fields))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let options = WriteOptions {
+        write_statistics: true,
+        compression: CompressionOptions::Uncompressed,
+        version: Version::V2,
+        data_pagesize_limit: None,
+    };
+    let encodings = schema
+        .fields
+        .iter()
+        .map(|field| transverse(&field.data_type, |_| Encoding::Plain))
+        .collect();
+    let row_groups = RowGroupIterator::try_new(
+        std::iter::once(Chunk::try_new(arrays)),
+        &schema,
+        options,
+        encodings,
+    )?;
+    let mut writer = FileWriter::try_new(writer, schema, options)?;
+    for group in row_groups {
+        writer.write(group?)?;
+    }
+    writer.end(None)?;
+    Ok(())
+}
+
+/// Splits the CSV fields of the records into columns
+fn record_columns<'a, R, I>(records: I) -> Result<Vec<Vec<String>>, Error>
+where
+    R: ParquetRecord + 'a,
+    I: IntoIterator<Item = &'a R>,
+{
+    let mut columns = vec![Vec::new(); R::COLUMNS.len()];
+    for record in records {
+        let fields = record.csv_fields();
+        if fields.len() != columns.len() {
+            return Err(Error::InvalidArgumentError(format!(
+                "{} fields for {} columns",
+                fields.len(),
+                columns.len()
+            )));
+        }
+        for (column, field) in columns.iter_mut().zip(fields) {
+            column.push(field);
+        }
+    }
+    Ok(columns)
+}
+
+/// Writes the records as a parquet file with the columns of [`CsvRecord::COLUMNS`]
+pub fn write_parquet<'a, W, R, I>(writer: W, records: I) -> Result<(), Error>
+where
+    W: Write,
+    R: ParquetRecord + 'a,
+    I: IntoIterator<Item = &'a R>,
+{
+    let columns = record_columns(records)?;
+    write_columns(
+        writer,
+        R::COLUMNS
+            .iter()
+            .zip(R::COLUMN_TYPES)
+            .zip(columns)
+            .map(|((name, column_type), fields)| (*name, *column_type, fields))
+            .collect(),
+    )
+}
+
+/// Same as [`write_parquet`] but for records keyed by (ticket or stake) account address
+pub fn write_parquet_with_address<'a, W, R, I>(writer: W, records: I) -> Result<(), Error>
+where
+    W: Write,
+    R: ParquetRecord + 'a,
+    I: IntoIterator<Item = (&'a Pubkey, &'a R)>,
+{
+    let (addresses, records): (Vec<&Pubkey>, Vec<&R>) = records.into_iter().unzip();
+    let columns = record_columns(records)?;
+    write_columns(
+        writer,
+        std::iter::once((
+            "address",
+            ColumnType::Utf8,
+            addresses
+                .iter()
+                .map(|address| address.to_string())
+                .collect(),
+        ))
+        .chain(
+            R::COLUMNS
+                .iter()
+                .zip(R::COLUMN_TYPES)
+                .zip(columns)
+                .map(|((name, column_type), fields)| (*name, *column_type, fields)),
+        )
+        .collect(),
+    )
+}
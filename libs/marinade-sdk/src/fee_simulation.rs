@@ -0,0 +1,256 @@
+//! Projected effects of a proposed fee change (config_lp, config_marinade and/or the
+//! delayed unstake fee) on liquid unstake fees, treasury revenue and depositor APY,
+//! rendered for governance forum posts.
+//! Projections are yearly and linear: rewards and unstake volume are caller assumptions.
+
+use std::fmt::Write;
+
+use crate::{
+    calc::proportional,
+    error::CommonError,
+    instructions::{config_lp::ConfigLpData, config_marinade::ConfigMarinadeData},
+    state::{
+        fee::{Fee, FeeCents},
+        liq_pool::LiqPool,
+        marinade::{Marinade, MarinadeExtension},
+    },
+};
+
+/// Fee related parts of a proposal. Fields not set in the configs keep their current value
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct FeeProposal {
+    pub lp: ConfigLpData,
+    pub marinade: ConfigMarinadeData,
+    /// Delayed unstake fee of program versions that have it, see MarinadeExtension
+    pub delayed_unstake_fee: Option<FeeCents>,
+}
+
+/// Market assumptions the projection is based on
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FeeAssumptions {
+    /// Staking APY before the reward fee
+    pub gross_staking_apy: Fee,
+    /// Lamports liquid unstaked per year
+    pub liquid_unstake_volume: u64,
+    /// Lamports delayed unstaked (order_unstake) per year
+    pub delayed_unstake_volume: u64,
+    /// SOL leg balance the yearly volume is assumed to be unstaked at
+    pub sol_leg: u64,
+    /// SOL leg balances to show the fee curve at. Empty for fractions of the liquidity target
+    pub liquidity_levels: Vec<u64>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct UnstakeFeePoint {
+    pub sol_leg: u64,
+    pub before: Fee,
+    pub after: Fee,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Projection {
+    /// Fee of the liquid unstake at FeeAssumptions::sol_leg
+    pub unstake_fee: Fee,
+    /// Yearly lamports from the reward fee
+    pub reward_fee_revenue: u64,
+    /// Yearly lamports from the treasury cut of liquid unstake fees
+    pub unstake_fee_revenue: u64,
+    pub delayed_unstake_fee: FeeCents,
+    /// Yearly lamports from the delayed unstake fee, all of it goes to the treasury
+    pub delayed_unstake_fee_revenue: u64,
+    /// Staking APY net of the reward fee
+    pub depositor_apy: Fee,
+}
+
+impl Projection {
+    pub fn treasury_revenue(&self) -> u64 {
+        self.reward_fee_revenue
+            .saturating_add(self.unstake_fee_revenue)
+            .saturating_add(self.delayed_unstake_fee_revenue)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FeeImpact {
+    pub unstake_fees: Vec<UnstakeFeePoint>,
+    pub before: Projection,
+    pub after: Projection,
+}
+
+fn apply_lp(liq_pool: &LiqPool, config: &ConfigLpData) -> LiqPool {
+    let mut liq_pool = liq_pool.clone();
+    if let Some(min_fee) = config.min_fee {
+        liq_pool.lp_min_fee = min_fee;
+    }
+    if let Some(max_fee) = config.max_fee {
+        liq_pool.lp_max_fee = max_fee;
+    }
+    if let Some(liquidity_target) = config.liquidity_target {
+        liq_pool.lp_liquidity_target = liquidity_target;
+    }
+    if let Some(treasury_cut) = config.treasury_cut {
+        liq_pool.treasury_cut = treasury_cut;
+    }
+    liq_pool
+}
+
+fn project(
+    state: &Marinade,
+    liq_pool: &LiqPool,
+    reward_fee: Fee,
+    delayed_unstake_fee: FeeCents,
+    assumptions: &FeeAssumptions,
+) -> Result<Projection, CommonError> {
+    let gross_rewards = assumptions
+        .gross_staking_apy
+        .apply(state.try_total_virtual_staked_lamports()?)?;
+    let unstake_fee = liq_pool.linear_fee(assumptions.sol_leg);
+    let unstake_fees = unstake_fee.apply(assumptions.liquid_unstake_volume)?;
+    let apy = assumptions.gross_staking_apy.basis_points;
+    Ok(Projection {
+        unstake_fee,
+        reward_fee_revenue: reward_fee.apply(gross_rewards)?,
+        unstake_fee_revenue: liq_pool.treasury_cut.apply(unstake_fees)?,
+        delayed_unstake_fee,
+        delayed_unstake_fee_revenue: delayed_unstake_fee
+            .apply(assumptions.delayed_unstake_volume)?,
+        depositor_apy: Fee::from_basis_points(apy - reward_fee.apply(apy as u64)? as u32),
+    })
+}
+
+/// Compares the current fee parameters with the proposed ones under the same assumptions,
+/// for deployments without the delayed unstake fee
+pub fn simulate_fee_change(
+    state: &Marinade,
+    proposal: &FeeProposal,
+    assumptions: &FeeAssumptions,
+) -> Result<FeeImpact, CommonError> {
+    simulate_fee_change_with_extension(state, &MarinadeExtension::default(), proposal, assumptions)
+}
+
+/// simulate_fee_change with the current delayed unstake fee read from extension
+pub fn simulate_fee_change_with_extension(
+    state: &Marinade,
+    extension: &MarinadeExtension,
+    proposal: &FeeProposal,
+    assumptions: &FeeAssumptions,
+) -> Result<FeeImpact, CommonError> {
+    let current_lp = &state.liq_pool;
+    let proposed_lp = apply_lp(current_lp, &proposal.lp);
+    proposed_lp.lp_min_fee.check()?;
+    proposed_lp.lp_max_fee.check()?;
+    proposed_lp.treasury_cut.check()?;
+    if proposed_lp.lp_min_fee > proposed_lp.lp_max_fee {
+        return Err(CommonError::FeesWrongWayRound);
+    }
+    let proposed_reward_fee = proposal.marinade.rewards_fee.unwrap_or(state.reward_fee);
+    proposed_reward_fee.check()?;
+    let proposed_delayed_unstake_fee = proposal
+        .delayed_unstake_fee
+        .unwrap_or(extension.delayed_unstake_fee);
+    proposed_delayed_unstake_fee.check()?;
+
+    let levels = if assumptions.liquidity_levels.is_empty() {
+        let target = current_lp
+            .lp_liquidity_target
+            .max(proposed_lp.lp_liquidity_target);
+        [0, 25, 50, 75, 100, 150]
+            .iter()
+            .map(|percent| proportional(target, *percent, 100))
+            .collect::<Result<Vec<_>, _>>()?
+    } else {
+        assumptions.liquidity_levels.clone()
+    };
+    let unstake_fees = levels
+        .into_iter()
+        .map(|sol_leg| UnstakeFeePoint {
+            sol_leg,
+            before: current_lp.linear_fee(sol_leg),
+            after: proposed_lp.linear_fee(sol_leg),
+        })
+        .collect();
+
+    Ok(FeeImpact {
+        unstake_fees,
+        before: project(
+            state,
+            current_lp,
+            state.reward_fee,
+            extension.delayed_unstake_fee,
+            assumptions,
+        )?,
+        after: project(
+            state,
+            &proposed_lp,
+            proposed_reward_fee,
+            proposed_delayed_unstake_fee,
+            assumptions,
+        )?,
+    })
+}
+
+/// Rounded half up to 0.01 SOL in integer arithmetic
+fn sol(lamports: u64) -> String {
+    let hundredths = (lamports as u128 + 5_000_000) / 10_000_000;
+    format!("{}.{:02} SOL", hundredths / 100, hundredths % 100)
+}
+
+impl FeeImpact {
+    /// Markdown tables ready to paste into a forum post
+    pub fn to_markdown(&self) -> String {
+        let mut out = String::new();
+        out.push_str("| SOL leg | Unstake fee now | Proposed |\n|---|---|---|\n");
+        for point in &self.unstake_fees {
+            writeln!(
+                out,
+                "| {} | {} | {} |",
+                sol(point.sol_leg),
+                point.before,
+                point.after
+            )
+            .unwrap();
+        }
+        out.push_str("\n| Yearly projection | Now | Proposed |\n|---|---|---|\n");
+        let rows = [
+            (
+                "Unstake fee",
+                self.before.unstake_fee.to_string(),
+                self.after.unstake_fee.to_string(),
+            ),
+            (
+                "Reward fee revenue",
+                sol(self.before.reward_fee_revenue),
+                sol(self.after.reward_fee_revenue),
+            ),
+            (
+                "Unstake fee revenue",
+                sol(self.before.unstake_fee_revenue),
+                sol(self.after.unstake_fee_revenue),
+            ),
+            (
+                "Delayed unstake fee",
+                self.before.delayed_unstake_fee.to_string(),
+                self.after.delayed_unstake_fee.to_string(),
+            ),
+            (
+                "Delayed unstake fee revenue",
+                sol(self.before.delayed_unstake_fee_revenue),
+                sol(self.after.delayed_unstake_fee_revenue),
+            ),
+            (
+                "Treasury revenue",
+                sol(self.before.treasury_revenue()),
+                sol(self.after.treasury_revenue()),
+            ),
+            (
+                "Depositor APY",
+                self.before.depositor_apy.to_string(),
+                self.after.depositor_apy.to_string(),
+            ),
+        ];
+        for (name, before, after) in rows {
+            writeln!(out, "| {} | {} | {} |", name, before, after).unwrap();
+        }
+        out
+    }
+}
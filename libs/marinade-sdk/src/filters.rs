@@ -0,0 +1,110 @@
+//! getProgramAccounts filters of the Marinade accounts, so that scanners don't hard-code
+//! byte offsets. Every filter set starts with the discriminator of the account type; the
+//! offsets follow the Borsh layouts of the state types, after the 8 byte discriminator.
+//! The state account has no size filter: the extension grows it past Marinade::serialized_len.
+
+use micro_anchor::Discriminator;
+use solana_client::rpc_filter::{Memcmp, RpcFilterType};
+use solana_program::{program_pack::Pack, pubkey::Pubkey};
+
+use crate::state::{
+    delayed_unstake_ticket::DelayedUnstakeTicket, marinade::Marinade, stake_system::StakeRecord,
+    validator_system::ValidatorRecord,
+};
+
+const DISCRIMINATOR_LEN: usize = 8;
+const PUBKEY_LEN: usize = std::mem::size_of::<Pubkey>();
+
+/// Marinade::msol_mint
+pub const STATE_MSOL_MINT_OFFSET: usize = DISCRIMINATOR_LEN;
+/// Marinade::admin_authority
+pub const STATE_ADMIN_AUTHORITY_OFFSET: usize = STATE_MSOL_MINT_OFFSET + PUBKEY_LEN;
+/// DelayedUnstakeTicket::state_address
+pub const TICKET_STATE_OFFSET: usize = DISCRIMINATOR_LEN;
+/// DelayedUnstakeTicket::beneficiary
+pub const TICKET_BENEFICIARY_OFFSET: usize = TICKET_STATE_OFFSET + PUBKEY_LEN;
+/// spl_token::state::Account::mint
+pub const TOKEN_ACCOUNT_MINT_OFFSET: usize = 0;
+/// spl_token::state::Account::owner
+pub const TOKEN_ACCOUNT_OWNER_OFFSET: usize = TOKEN_ACCOUNT_MINT_OFFSET + PUBKEY_LEN;
+
+fn memcmp(offset: usize, bytes: &[u8]) -> RpcFilterType {
+    RpcFilterType::Memcmp(Memcmp::new_base58_encoded(offset, bytes))
+}
+
+fn discriminator(discriminator: &[u8; 8]) -> RpcFilterType {
+    memcmp(0, discriminator)
+}
+
+/// Every state account of the program
+pub fn marinade_state() -> Vec<RpcFilterType> {
+    vec![discriminator(&Marinade::DISCRIMINATOR)]
+}
+
+pub fn marinade_state_by_msol_mint(msol_mint: &Pubkey) -> Vec<RpcFilterType> {
+    let mut filters = marinade_state();
+    filters.push(memcmp(STATE_MSOL_MINT_OFFSET, msol_mint.as_ref()));
+    filters
+}
+
+pub fn marinade_state_by_admin(admin_authority: &Pubkey) -> Vec<RpcFilterType> {
+    let mut filters = marinade_state();
+    filters.push(memcmp(
+        STATE_ADMIN_AUTHORITY_OFFSET,
+        admin_authority.as_ref(),
+    ));
+    filters
+}
+
+/// Every ticket of the program, of all states
+pub fn tickets() -> Vec<RpcFilterType> {
+    vec![
+        RpcFilterType::DataSize(DelayedUnstakeTicket::ACCOUNT_LEN as u64),
+        discriminator(&DelayedUnstakeTicket::DISCRIMINATOR),
+    ]
+}
+
+pub fn tickets_by_state(state: &Pubkey) -> Vec<RpcFilterType> {
+    let mut filters = tickets();
+    filters.push(memcmp(TICKET_STATE_OFFSET, state.as_ref()));
+    filters
+}
+
+/// Tickets of beneficiary, of all states
+pub fn tickets_by_beneficiary(beneficiary: &Pubkey) -> Vec<RpcFilterType> {
+    let mut filters = tickets();
+    filters.push(memcmp(TICKET_BENEFICIARY_OFFSET, beneficiary.as_ref()));
+    filters
+}
+
+/// Tickets of beneficiary at state
+pub fn tickets_of(state: &Pubkey, beneficiary: &Pubkey) -> Vec<RpcFilterType> {
+    let mut filters = tickets_by_state(state);
+    filters.push(memcmp(TICKET_BENEFICIARY_OFFSET, beneficiary.as_ref()));
+    filters
+}
+
+/// Validator lists of the program; their size depends on the capacity
+pub fn validator_list() -> Vec<RpcFilterType> {
+    vec![discriminator(ValidatorRecord::DISCRIMINATOR)]
+}
+
+/// Stake lists of the program; their size depends on the capacity
+pub fn stake_list() -> Vec<RpcFilterType> {
+    vec![discriminator(StakeRecord::DISCRIMINATOR)]
+}
+
+/// Token accounts of mint (mSOL or LP), to query on the token program
+pub fn token_accounts(mint: &Pubkey) -> Vec<RpcFilterType> {
+    vec![
+        RpcFilterType::DataSize(spl_token::state::Account::LEN as u64),
+        memcmp(TOKEN_ACCOUNT_MINT_OFFSET, mint.as_ref()),
+    ]
+}
+
+/// Token accounts of mint owned by owner, to query on the token program
+pub fn token_accounts_by_owner(mint: &Pubkey, owner: &Pubkey) -> Vec<RpcFilterType> {
+    let mut filters = token_accounts(mint);
+    filters.push(memcmp(TOKEN_ACCOUNT_OWNER_OFFSET, owner.as_ref()));
+    filters
+}
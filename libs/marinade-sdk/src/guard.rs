@@ -0,0 +1,170 @@
+//! Opt-in guard for long-queued transactions, e.g. multisig proposals waiting for signatures.
+//! Instructions are kept together with the pool conditions they were built for and released
+//! only while the current state still matches them. The program has no assertion instruction,
+//! so the guard is enforced by the client right before signing or sending.
+
+use derive_more::{Display, From};
+use solana_program::{instruction::Instruction, pubkey::Pubkey};
+
+use crate::{
+    calc::proportional,
+    checks::{CheckFailure, CheckMode, Checks},
+    error::{CommonError, Mismatch},
+    located::Located,
+    state::{fee::Fee, marinade::Marinade},
+};
+
+#[derive(Clone, Copy, Debug, Display, From)]
+pub enum GuardViolation {
+    #[display(fmt = "Guard is for state {} not {}", expected, actual)]
+    #[from(ignore)]
+    WrongState {
+        expected: Pubkey,
+        actual: Pubkey,
+    },
+    #[display(fmt = "mSOL price {} out of [{}, {}]", price, min, max)]
+    #[from(ignore)]
+    PriceOutOfBounds {
+        price: u64,
+        min: u64,
+        max: u64,
+    },
+    #[display(fmt = "Guard expired at epoch {} (current {})", max_epoch, epoch)]
+    #[from(ignore)]
+    Expired {
+        epoch: u64,
+        max_epoch: u64,
+    },
+    Calculation(CommonError),
+}
+
+impl std::error::Error for GuardViolation {}
+
+impl From<GuardViolation> for CheckFailure {
+    fn from(violation: GuardViolation) -> Self {
+        let error = match violation {
+            GuardViolation::WrongState { expected, actual } => {
+                CommonError::UnexpectedAccount(Some(Mismatch::new(expected, actual))).into()
+            }
+            GuardViolation::PriceOutOfBounds { price, min, .. } if price < min => {
+                CommonError::NumberTooLow(Some(Mismatch::new(min, price))).into()
+            }
+            GuardViolation::PriceOutOfBounds { price, max, .. } => {
+                CommonError::NumberTooHigh(Some(Mismatch::new(max, price))).into()
+            }
+            GuardViolation::Expired { epoch, max_epoch } => {
+                CommonError::NumberTooHigh(Some(Mismatch::new(max_epoch, epoch))).into()
+            }
+            GuardViolation::Calculation(err) => err.into(),
+        };
+        CheckFailure {
+            message: violation.to_string(),
+            error,
+        }
+    }
+}
+
+/// mSOL price bounds (scaled by Marinade::PRICE_DENOMINATOR) and last epoch
+/// the guarded instructions may be sent in
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct StateGuard {
+    pub state_address: Pubkey,
+    pub min_msol_price: u64,
+    pub max_msol_price: u64,
+    pub max_epoch: Option<u64>,
+}
+
+/// Price computed from the pool totals, the msol_price field is only updated by the crank
+fn msol_price(state: &Marinade) -> Result<u64, CommonError> {
+    state.calc_lamports_from_msol_amount(Marinade::PRICE_DENOMINATOR)
+}
+
+impl StateGuard {
+    /// Accepts prices within tolerance of the current price in both directions, no expiration
+    pub fn new<T: Located<Marinade>>(state: &T, tolerance: Fee) -> Result<Self, CommonError> {
+        let price = msol_price(state.as_ref())?;
+        let delta = proportional(price, tolerance.basis_points as u64, 10_000)?;
+        Ok(Self {
+            state_address: state.key(),
+            min_msol_price: price.saturating_sub(delta),
+            max_msol_price: price.saturating_add(delta),
+            max_epoch: None,
+        })
+    }
+
+    pub fn with_max_epoch(mut self, max_epoch: u64) -> Self {
+        self.max_epoch = Some(max_epoch);
+        self
+    }
+
+    fn check_state<T: Located<Marinade>>(&self, state: &T) -> Result<(), GuardViolation> {
+        if state.key() != self.state_address {
+            return Err(GuardViolation::WrongState {
+                expected: self.state_address,
+                actual: state.key(),
+            });
+        }
+        Ok(())
+    }
+
+    fn check_epoch(&self, epoch: u64) -> Result<(), GuardViolation> {
+        match self.max_epoch {
+            Some(max_epoch) if epoch > max_epoch => {
+                Err(GuardViolation::Expired { epoch, max_epoch })
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn check_price(&self, state: &Marinade) -> Result<(), GuardViolation> {
+        let price = msol_price(state)?;
+        if price < self.min_msol_price || price > self.max_msol_price {
+            return Err(GuardViolation::PriceOutOfBounds {
+                price,
+                min: self.min_msol_price,
+                max: self.max_msol_price,
+            });
+        }
+        Ok(())
+    }
+
+    pub fn check<T: Located<Marinade>>(&self, state: &T, epoch: u64) -> Result<(), GuardViolation> {
+        self.check_state(state)?;
+        self.check_epoch(epoch)?;
+        self.check_price(state.as_ref())
+    }
+
+    /// The checks of check, see checks::CheckMode
+    pub fn checks<T: Located<Marinade>>(&self, state: &T, epoch: u64, mode: CheckMode) -> Checks {
+        Checks::new(mode)
+            .check(|| self.check_state(state).map_err(CheckFailure::from))
+            .check(|| self.check_epoch(epoch).map_err(CheckFailure::from))
+            .check(|| self.check_price(state.as_ref()).map_err(CheckFailure::from))
+    }
+}
+
+/// Instructions released only while the guard holds
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GuardedInstructions {
+    pub guard: StateGuard,
+    instructions: Vec<Instruction>,
+}
+
+impl GuardedInstructions {
+    pub fn new(guard: StateGuard, instructions: Vec<Instruction>) -> Self {
+        Self {
+            guard,
+            instructions,
+        }
+    }
+
+    /// Instructions to sign, given freshly fetched state and the current epoch
+    pub fn release<T: Located<Marinade>>(
+        &self,
+        state: &T,
+        epoch: u64,
+    ) -> Result<&[Instruction], GuardViolation> {
+        self.guard.check(state, epoch)?;
+        Ok(&self.instructions)
+    }
+}
@@ -0,0 +1,146 @@
+//! Health summary backing the /healthz endpoint of a service built on the SDK: whether the
+//! RPC node answers, how far behind the node the state is, whether the maintenance crank
+//! ran this epoch and whether the program is paused. client::MarinadeClient::health fills it,
+//! HealthPolicy decides what is still healthy.
+
+use std::time::Duration;
+
+use derive_more::Display;
+
+use crate::state::{
+    marinade::{Marinade, MarinadeExtension},
+    stake_system::StakeRecord,
+};
+
+/// State dependent part of Health
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct StateHealth {
+    /// Slots between the state the service uses and the RPC node's current slot
+    pub slot_lag: u64,
+    pub epoch: u64,
+    /// Epochs since the least recently updated stake account, 0 once the update crank
+    /// went through every stake account this epoch
+    pub stake_update_epochs_behind: u64,
+    /// Epochs since the last stake-delta run
+    pub stake_delta_epochs_behind: u64,
+    pub paused: bool,
+}
+
+impl StateHealth {
+    pub fn new(
+        marinade: &Marinade,
+        extension: &MarinadeExtension,
+        stakes: &[StakeRecord],
+        state_slot: u64,
+        current_slot: u64,
+        epoch: u64,
+    ) -> Self {
+        let oldest_update = stakes
+            .iter()
+            .map(|stake| stake.last_update_epoch)
+            .min()
+            .unwrap_or(epoch);
+        Self {
+            slot_lag: current_slot.saturating_sub(state_slot),
+            epoch,
+            stake_update_epochs_behind: epoch.saturating_sub(oldest_update),
+            stake_delta_epochs_behind: epoch
+                .saturating_sub(marinade.stake_system.last_stake_delta_epoch),
+            paused: extension.paused,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Health {
+    /// Error of the first failed RPC request, None when every request succeeded
+    pub rpc_error: Option<String>,
+    /// Round trip of the epoch info request
+    pub rpc_latency: Duration,
+    /// None when the state could not be fetched
+    pub state: Option<StateHealth>,
+}
+
+#[derive(Clone, Debug, Display, PartialEq, Eq)]
+pub enum HealthIssue {
+    #[display(fmt = "RPC unreachable: {}", _0)]
+    RpcUnreachable(String),
+    #[display(fmt = "RPC answered in {:?}", _0)]
+    SlowRpc(Duration),
+    #[display(fmt = "State unavailable")]
+    StateUnavailable,
+    #[display(fmt = "State is {} slots behind", _0)]
+    SlotLag(u64),
+    #[display(fmt = "Stake accounts not updated for {} epochs", _0)]
+    StakeUpdateBehind(u64),
+    #[display(fmt = "Stake delta not run for {} epochs", _0)]
+    StakeDeltaBehind(u64),
+    #[display(fmt = "Program paused")]
+    Paused,
+}
+
+/// Limits of a healthy service. The crank limits count whole epochs: the update and
+/// stake-delta runs of the current epoch may still be pending, so 1 is the strictest useful value
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct HealthPolicy {
+    pub max_rpc_latency: Duration,
+    pub max_slot_lag: u64,
+    pub max_stake_update_epochs_behind: u64,
+    pub max_stake_delta_epochs_behind: u64,
+    /// Whether a paused program still counts as healthy, e.g. for read only services
+    pub allow_paused: bool,
+}
+
+impl Default for HealthPolicy {
+    fn default() -> Self {
+        Self {
+            max_rpc_latency: Duration::from_secs(5),
+            // about a minute
+            max_slot_lag: 150,
+            max_stake_update_epochs_behind: 1,
+            max_stake_delta_epochs_behind: 1,
+            allow_paused: false,
+        }
+    }
+}
+
+impl Health {
+    /// Every limit of policy the service is over, empty when healthy
+    pub fn issues(&self, policy: &HealthPolicy) -> Vec<HealthIssue> {
+        let mut issues = Vec::new();
+        if let Some(error) = &self.rpc_error {
+            issues.push(HealthIssue::RpcUnreachable(error.clone()));
+        }
+        if self.rpc_latency > policy.max_rpc_latency {
+            issues.push(HealthIssue::SlowRpc(self.rpc_latency));
+        }
+        let state = match &self.state {
+            Some(state) => state,
+            None => {
+                issues.push(HealthIssue::StateUnavailable);
+                return issues;
+            }
+        };
+        if state.slot_lag > policy.max_slot_lag {
+            issues.push(HealthIssue::SlotLag(state.slot_lag));
+        }
+        if state.stake_update_epochs_behind > policy.max_stake_update_epochs_behind {
+            issues.push(HealthIssue::StakeUpdateBehind(
+                state.stake_update_epochs_behind,
+            ));
+        }
+        if state.stake_delta_epochs_behind > policy.max_stake_delta_epochs_behind {
+            issues.push(HealthIssue::StakeDeltaBehind(
+                state.stake_delta_epochs_behind,
+            ));
+        }
+        if state.paused && !policy.allow_paused {
+            issues.push(HealthIssue::Paused);
+        }
+        issues
+    }
+
+    pub fn is_healthy(&self, policy: &HealthPolicy) -> bool {
+        self.issues(policy).is_empty()
+    }
+}
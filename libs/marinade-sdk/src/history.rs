@@ -0,0 +1,578 @@
+//! Resumable ingestion of the transaction history of the program or the state account.
+//! getSignaturesForAddress is paged back to the last handled signature, then the new
+//! transactions are classified and handed over oldest first. The resume point is stored after
+//! every handled transaction, so a restart continues right after it.
+
+use std::io;
+
+use derive_more::{Display, From};
+use micro_anchor::Discriminator;
+use solana_client::{
+    client_error::ClientError,
+    rpc_client::{GetConfirmedSignaturesForAddress2Config, RpcClient},
+    rpc_config::RpcTransactionConfig,
+};
+use solana_program::{instruction::CompiledInstruction, pubkey::Pubkey};
+use solana_sdk::signature::Signature;
+use solana_transaction_status::{
+    UiInnerInstructions, UiInstruction, UiLoadedAddresses, UiTransactionEncoding,
+    UiTransactionTokenBalance,
+};
+
+use crate::{
+    instructions::{
+        add_liquidity::AddLiquidityData, add_validator::AddValidatorData,
+        change_authority::ChangeAuthorityData, claim::ClaimData, config_lp::ConfigLpData,
+        config_marinade::ConfigMarinadeData, config_validator_system::ConfigValidatorSystemData,
+        deactivate_stake::DeactivateStakeData, deposit::DepositData,
+        deposit_stake_account::DepositStakeAccountData, emergency_unstake::EmergencyUnstakeData,
+        initialize::InitializeData, liquid_unstake::LiquidUnstakeData,
+        merge_stakes::MergeStakesData, order_unstake::OrderUnstakeData,
+        partial_unstake::PartialUnstakeData, remove_liquidity::RemoveLiquidityData,
+        remove_validator::RemoveValidatorData, set_validator_score::SetValidatorScoreData,
+        stake_reserve::StakeReserveData, update_active::UpdateActiveData,
+        update_deactivated::UpdateDeactivatedData,
+    },
+    program::MarinadeProgram,
+    storage::Storage,
+};
+
+#[derive(Debug, Display, From)]
+pub enum HistoryError {
+    Client(ClientError),
+    Storage(io::Error),
+    #[display(fmt = "Can not decode {}", _0)]
+    #[from(ignore)]
+    Decode(String),
+}
+
+impl std::error::Error for HistoryError {}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum InstructionKind {
+    Initialize,
+    ChangeAuthority,
+    AddValidator,
+    RemoveValidator,
+    SetValidatorScore,
+    ConfigValidatorSystem,
+    Deposit,
+    DepositStakeAccount,
+    LiquidUnstake,
+    AddLiquidity,
+    RemoveLiquidity,
+    ConfigLp,
+    ConfigMarinade,
+    OrderUnstake,
+    Claim,
+    StakeReserve,
+    DeactivateStake,
+    EmergencyUnstake,
+    PartialUnstake,
+    MergeStakes,
+    UpdateActive,
+    UpdateDeactivated,
+    /// Program instruction the SDK has no builder for, e.g. withdraw_stake_account
+    Other([u8; 8]),
+}
+
+impl InstructionKind {
+    /// None when the data is too short to hold a discriminator
+    pub fn from_data(data: &[u8]) -> Option<Self> {
+        let discriminator: [u8; 8] = data.get(..8)?.try_into().ok()?;
+        let kinds = [
+            (InitializeData::DISCRIMINATOR, InstructionKind::Initialize),
+            (
+                ChangeAuthorityData::DISCRIMINATOR,
+                InstructionKind::ChangeAuthority,
+            ),
+            (
+                AddValidatorData::DISCRIMINATOR,
+                InstructionKind::AddValidator,
+            ),
+            (
+                RemoveValidatorData::DISCRIMINATOR,
+                InstructionKind::RemoveValidator,
+            ),
+            (
+                SetValidatorScoreData::DISCRIMINATOR,
+                InstructionKind::SetValidatorScore,
+            ),
+            (
+                ConfigValidatorSystemData::DISCRIMINATOR,
+                InstructionKind::ConfigValidatorSystem,
+            ),
+            (DepositData::DISCRIMINATOR, InstructionKind::Deposit),
+            (
+                DepositStakeAccountData::DISCRIMINATOR,
+                InstructionKind::DepositStakeAccount,
+            ),
+            (
+                LiquidUnstakeData::DISCRIMINATOR,
+                InstructionKind::LiquidUnstake,
+            ),
+            (
+                AddLiquidityData::DISCRIMINATOR,
+                InstructionKind::AddLiquidity,
+            ),
+            (
+                RemoveLiquidityData::DISCRIMINATOR,
+                InstructionKind::RemoveLiquidity,
+            ),
+            (ConfigLpData::DISCRIMINATOR, InstructionKind::ConfigLp),
+            (
+                ConfigMarinadeData::DISCRIMINATOR,
+                InstructionKind::ConfigMarinade,
+            ),
+            (
+                OrderUnstakeData::DISCRIMINATOR,
+                InstructionKind::OrderUnstake,
+            ),
+            (ClaimData::DISCRIMINATOR, InstructionKind::Claim),
+            (
+                StakeReserveData::DISCRIMINATOR,
+                InstructionKind::StakeReserve,
+            ),
+            (
+                DeactivateStakeData::DISCRIMINATOR,
+                InstructionKind::DeactivateStake,
+            ),
+            (
+                EmergencyUnstakeData::DISCRIMINATOR,
+                InstructionKind::EmergencyUnstake,
+            ),
+            (
+                PartialUnstakeData::DISCRIMINATOR,
+                InstructionKind::PartialUnstake,
+            ),
+            (MergeStakesData::DISCRIMINATOR, InstructionKind::MergeStakes),
+            (
+                UpdateActiveData::DISCRIMINATOR,
+                InstructionKind::UpdateActive,
+            ),
+            (
+                UpdateDeactivatedData::DISCRIMINATOR,
+                InstructionKind::UpdateDeactivated,
+            ),
+        ];
+        Some(
+            kinds
+                .iter()
+                .find(|(known, _)| *known == discriminator)
+                .map_or(InstructionKind::Other(discriminator), |(_, kind)| *kind),
+        )
+    }
+}
+
+/// Top level instructions of the Marinade program of a transaction
+pub fn classify(
+    program: &MarinadeProgram,
+    account_keys: &[Pubkey],
+    instructions: &[CompiledInstruction],
+) -> Vec<InstructionKind> {
+    marinade_instructions(program.program_id, account_keys, instructions)
+        .map(|(kind, _)| kind)
+        .collect()
+}
+
+fn marinade_instructions<'a>(
+    program_id: Pubkey,
+    account_keys: &'a [Pubkey],
+    instructions: &'a [CompiledInstruction],
+) -> impl Iterator<Item = (InstructionKind, &'a CompiledInstruction)> {
+    instructions
+        .iter()
+        .filter(move |instruction| {
+            account_keys.get(instruction.program_id_index as usize) == Some(&program_id)
+        })
+        .filter_map(|instruction| {
+            InstructionKind::from_data(&instruction.data).map(|kind| (kind, instruction))
+        })
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SignatureInfo {
+    pub signature: Signature,
+    pub slot: u64,
+    pub block_time: Option<i64>,
+    pub failed: bool,
+}
+
+/// Token account balance before and after a transaction
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TokenBalanceChange {
+    pub account: Pubkey,
+    pub mint: Pubkey,
+    pub owner: Option<Pubkey>,
+    pub pre: u64,
+    pub post: u64,
+}
+
+/// Message of a fetched transaction. account_keys includes the addresses loaded from lookup tables
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HistoryTransaction {
+    pub account_keys: Vec<Pubkey>,
+    pub instructions: Vec<CompiledInstruction>,
+    /// (index of the top level instruction, the instructions it invoked), from the status meta.
+    /// Admin changes made through SPL Governance or Squads are only found here
+    pub inner_instructions: Vec<(u8, Vec<CompiledInstruction>)>,
+    pub token_balances: Vec<TokenBalanceChange>,
+}
+
+impl HistoryTransaction {
+    /// Every instruction in execution order, each top level one followed by the ones it invoked
+    pub fn all_instructions(&self) -> impl Iterator<Item = &CompiledInstruction> {
+        self.instructions
+            .iter()
+            .enumerate()
+            .flat_map(move |(index, instruction)| {
+                std::iter::once(instruction).chain(
+                    self.inner_instructions
+                        .iter()
+                        .filter(move |(inner_index, _)| *inner_index as usize == index)
+                        .flat_map(|(_, inner)| inner.iter()),
+                )
+            })
+    }
+
+    /// Top level instructions of the Marinade program with their kind
+    pub fn marinade_instructions(
+        &self,
+        program: &MarinadeProgram,
+    ) -> impl Iterator<Item = (InstructionKind, &CompiledInstruction)> {
+        marinade_instructions(program.program_id, &self.account_keys, &self.instructions)
+    }
+
+    /// Address of the account at position of the instruction account list
+    pub fn account(&self, instruction: &CompiledInstruction, position: usize) -> Option<Pubkey> {
+        let index = *instruction.accounts.get(position)?;
+        self.account_keys.get(index as usize).copied()
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ClassifiedTransaction {
+    pub signature: Signature,
+    pub slot: u64,
+    pub block_time: Option<i64>,
+    /// Failed transactions are still handed over, they have no effect on the accounts
+    pub failed: bool,
+    pub instructions: Vec<InstructionKind>,
+    pub transaction: HistoryTransaction,
+}
+
+/// What the cursor needs from an RPC node, implemented for RpcClient
+pub trait HistorySource {
+    /// Newest first, at most limit signatures before `before` (exclusive) and after `until`
+    fn signatures(
+        &self,
+        address: &Pubkey,
+        before: Option<Signature>,
+        until: Option<Signature>,
+        limit: usize,
+    ) -> Result<Vec<SignatureInfo>, HistoryError>;
+
+    fn transaction(&self, signature: &Signature) -> Result<HistoryTransaction, HistoryError>;
+}
+
+fn parse_pubkey(pubkey: &str) -> Result<Pubkey, HistoryError> {
+    pubkey
+        .parse()
+        .map_err(|_| HistoryError::Decode(format!("pubkey {}", pubkey)))
+}
+
+fn parse_amount(balance: &UiTransactionTokenBalance) -> Result<u64, HistoryError> {
+    let amount = &balance.ui_token_amount.amount;
+    amount
+        .parse()
+        .map_err(|_| HistoryError::Decode(format!("token amount {}", amount)))
+}
+
+fn inner_instructions(
+    inner: Vec<UiInnerInstructions>,
+) -> Result<Vec<(u8, Vec<CompiledInstruction>)>, HistoryError> {
+    inner
+        .into_iter()
+        .map(|inner| {
+            let instructions = inner
+                .instructions
+                .into_iter()
+                .map(|instruction| match instruction {
+                    UiInstruction::Compiled(compiled) => Ok(CompiledInstruction {
+                        program_id_index: compiled.program_id_index,
+                        accounts: compiled.accounts,
+                        data: bs58::decode(&compiled.data).into_vec().map_err(|_| {
+                            HistoryError::Decode(format!("instruction data {}", compiled.data))
+                        })?,
+                    }),
+                    UiInstruction::Parsed(_) => {
+                        Err(HistoryError::Decode("parsed inner instruction".to_string()))
+                    }
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            Ok((inner.index, instructions))
+        })
+        .collect()
+}
+
+/// Accounts missing from pre were created, the ones missing from post were closed
+fn token_balance_changes(
+    account_keys: &[Pubkey],
+    pre: &[UiTransactionTokenBalance],
+    post: &[UiTransactionTokenBalance],
+) -> Result<Vec<TokenBalanceChange>, HistoryError> {
+    let mut changes: Vec<TokenBalanceChange> = Vec::new();
+    for (balance, is_post) in pre
+        .iter()
+        .map(|balance| (balance, false))
+        .chain(post.iter().map(|balance| (balance, true)))
+    {
+        let account = *account_keys
+            .get(balance.account_index as usize)
+            .ok_or_else(|| HistoryError::Decode(format!("account {}", balance.account_index)))?;
+        let amount = parse_amount(balance)?;
+        let index = match changes.iter().position(|change| change.account == account) {
+            Some(index) => index,
+            None => {
+                changes.push(TokenBalanceChange {
+                    account,
+                    mint: parse_pubkey(&balance.mint)?,
+                    owner: Option::<String>::from(balance.owner.clone())
+                        .map(|owner| parse_pubkey(&owner))
+                        .transpose()?,
+                    pre: 0,
+                    post: 0,
+                });
+                changes.len() - 1
+            }
+        };
+        if is_post {
+            changes[index].post = amount;
+        } else {
+            changes[index].pre = amount;
+        }
+    }
+    Ok(changes)
+}
+
+impl HistorySource for RpcClient {
+    fn signatures(
+        &self,
+        address: &Pubkey,
+        before: Option<Signature>,
+        until: Option<Signature>,
+        limit: usize,
+    ) -> Result<Vec<SignatureInfo>, HistoryError> {
+        self.get_signatures_for_address_with_config(
+            address,
+            GetConfirmedSignaturesForAddress2Config {
+                before,
+                until,
+                limit: Some(limit),
+                commitment: None,
+            },
+        )?
+        .into_iter()
+        .map(|status| {
+            Ok(SignatureInfo {
+                signature: status
+                    .signature
+                    .parse()
+                    .map_err(|_| HistoryError::Decode(status.signature.clone()))?,
+                slot: status.slot,
+                block_time: status.block_time,
+                failed: status.err.is_some(),
+            })
+        })
+        .collect()
+    }
+
+    fn transaction(&self, signature: &Signature) -> Result<HistoryTransaction, HistoryError> {
+        let confirmed = self.get_transaction_with_config(
+            signature,
+            RpcTransactionConfig {
+                encoding: Some(UiTransactionEncoding::Base64),
+                commitment: None,
+                max_supported_transaction_version: Some(0),
+            },
+        )?;
+        let transaction = confirmed
+            .transaction
+            .transaction
+            .decode()
+            .ok_or_else(|| HistoryError::Decode(signature.to_string()))?;
+        let mut account_keys = transaction.message.static_account_keys().to_vec();
+        let mut token_balances = Vec::new();
+        let mut inner = Vec::new();
+        if let Some(meta) = confirmed.transaction.meta {
+            if let Some(loaded) = Option::<UiLoadedAddresses>::from(meta.loaded_addresses) {
+                for address in loaded.writable.iter().chain(loaded.readonly.iter()) {
+                    account_keys.push(parse_pubkey(address)?);
+                }
+            }
+            let pre = Option::<Vec<UiTransactionTokenBalance>>::from(meta.pre_token_balances)
+                .unwrap_or_default();
+            let post = Option::<Vec<UiTransactionTokenBalance>>::from(meta.post_token_balances)
+                .unwrap_or_default();
+            token_balances = token_balance_changes(&account_keys, &pre, &post)?;
+            inner = inner_instructions(
+                Option::<Vec<UiInnerInstructions>>::from(meta.inner_instructions)
+                    .unwrap_or_default(),
+            )?;
+        }
+        Ok(HistoryTransaction {
+            account_keys,
+            instructions: transaction.message.instructions().to_vec(),
+            inner_instructions: inner,
+            token_balances,
+        })
+    }
+}
+
+/// Incremental ingestion of the history of one address with the resume point kept in storage.
+/// A crash between handling a transaction and storing the resume point hands it over again,
+/// so handlers writing by signature (upsert) see every transaction exactly once.
+pub struct HistoryCursor<S: Storage> {
+    pub address: Pubkey,
+    pub storage: S,
+    pub page_size: usize,
+    /// Deployment the instructions are classified for, the mainnet one by default
+    pub program: MarinadeProgram,
+}
+
+impl<S: Storage> HistoryCursor<S> {
+    /// getSignaturesForAddress maximal page size
+    pub const MAX_PAGE_SIZE: usize = 1_000;
+
+    pub fn new(address: Pubkey, storage: S) -> Self {
+        Self {
+            address,
+            storage,
+            page_size: Self::MAX_PAGE_SIZE,
+            program: MarinadeProgram::default(),
+        }
+    }
+
+    fn key(&self) -> String {
+        format!("history-cursor-{}", self.address)
+    }
+
+    /// Last handled signature, None before the first run
+    pub fn resume_point(&self) -> Result<Option<Signature>, HistoryError> {
+        match self.storage.load(&self.key())? {
+            Some(data) if data.len() == 64 => Ok(Some(Signature::new(&data))),
+            Some(_) => Err(HistoryError::Decode(self.key())),
+            None => Ok(None),
+        }
+    }
+
+    /// Signatures after the resume point, oldest first. The history is walked back once to
+    /// find where the pages start, only the newest and the oldest page are kept, the ones in
+    /// between are fetched again when the iteration reaches them.
+    pub fn pending<'a, H: HistorySource>(
+        &self,
+        source: &'a H,
+    ) -> Result<PendingSignatures<'a, H>, HistoryError> {
+        let until = self.resume_point()?;
+        let mut pages = Vec::new();
+        let mut before = None;
+        loop {
+            let page = source.signatures(&self.address, before, until, self.page_size)?;
+            let is_last = page.len() < self.page_size || page.is_empty();
+            let next = page.last().map(|info| info.signature);
+            pages.push(match before {
+                // the newest page can not be fetched again, new signatures would shift it
+                Some(before) if !is_last => PendingPage::Before(before),
+                _ => PendingPage::Fetched(page),
+            });
+            if is_last {
+                break;
+            }
+            before = next;
+        }
+        Ok(PendingSignatures {
+            source,
+            address: self.address,
+            until,
+            page_size: self.page_size,
+            pages,
+            current: Vec::new().into_iter().rev(),
+        })
+    }
+
+    /// Hands every new transaction to handler oldest first and moves the resume point after it.
+    /// Stops at the first handler error, leaving the resume point before the failed transaction.
+    /// Returns the number of handled transactions.
+    pub fn ingest<H, F>(&mut self, source: &H, mut handler: F) -> Result<usize, HistoryError>
+    where
+        H: HistorySource,
+        F: FnMut(&ClassifiedTransaction) -> Result<(), HistoryError>,
+    {
+        let mut handled = 0;
+        for info in self.pending(source)? {
+            let info = info?;
+            let transaction = source.transaction(&info.signature)?;
+            handler(&ClassifiedTransaction {
+                signature: info.signature,
+                slot: info.slot,
+                block_time: info.block_time,
+                failed: info.failed,
+                instructions: classify(
+                    &self.program,
+                    &transaction.account_keys,
+                    &transaction.instructions,
+                ),
+                transaction,
+            })?;
+            let key = self.key();
+            self.storage.store(&key, info.signature.as_ref())?;
+            handled += 1;
+        }
+        Ok(handled)
+    }
+}
+
+enum PendingPage {
+    Fetched(Vec<SignatureInfo>),
+    /// Not kept, fetched again with this `before` signature
+    Before(Signature),
+}
+
+/// Iterator over the signatures after the resume point, oldest first, see HistoryCursor::pending
+pub struct PendingSignatures<'a, H: HistorySource> {
+    source: &'a H,
+    address: Pubkey,
+    until: Option<Signature>,
+    page_size: usize,
+    /// Newest page first
+    pages: Vec<PendingPage>,
+    current: std::iter::Rev<std::vec::IntoIter<SignatureInfo>>,
+}
+
+impl<'a, H: HistorySource> Iterator for PendingSignatures<'a, H> {
+    type Item = Result<SignatureInfo, HistoryError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(info) = self.current.next() {
+                return Some(Ok(info));
+            }
+            let page = match self.pages.pop()? {
+                PendingPage::Fetched(page) => page,
+                PendingPage::Before(before) => match self.source.signatures(
+                    &self.address,
+                    Some(before),
+                    self.until,
+                    self.page_size,
+                ) {
+                    Ok(page) => page,
+                    Err(err) => {
+                        self.pages.clear();
+                        return Some(Err(err));
+                    }
+                },
+            };
+            self.current = page.into_iter().rev();
+        }
+    }
+}
@@ -0,0 +1,149 @@
+//! Memoized instruction builders for quoting services rebuilding the same instructions
+//! at high rates. Instructions are keyed by their data and user accounts and live as long
+//! as the state snapshot they were built from: replacing it with a different one drops them.
+
+use std::{
+    collections::{hash_map::DefaultHasher, HashMap},
+    hash::{Hash, Hasher},
+};
+
+use borsh::BorshSerialize;
+use micro_anchor::InstructionData;
+use solana_program::{
+    instruction::Instruction,
+    pubkey::{Pubkey, PubkeyError},
+};
+
+use crate::{
+    instructions::{deposit::DepositData, liquid_unstake::LiquidUnstakeData},
+    located::{KeyedAccount, Located},
+    state::marinade::{Marinade, MarinadeHelpers},
+};
+
+/// Hash of the state address and its serialized content
+pub fn state_version(state: &impl Located<Marinade>) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    state.key().hash(&mut hasher);
+    state
+        .as_ref()
+        .try_to_vec()
+        .expect("Marinade must serialize")
+        .hash(&mut hasher);
+    hasher.finish()
+}
+
+#[derive(Clone, Debug)]
+pub struct InstructionCache {
+    state: KeyedAccount<Marinade>,
+    version: u64,
+    /// Limit of cached instructions, the cache is cleared when reached
+    pub capacity: usize,
+    instructions: HashMap<(Vec<u8>, Vec<Pubkey>), Instruction>,
+    hits: u64,
+    misses: u64,
+}
+
+impl InstructionCache {
+    pub const DEFAULT_CAPACITY: usize = 10_000;
+
+    pub fn new(state: KeyedAccount<Marinade>) -> Self {
+        Self {
+            version: state_version(&state),
+            state,
+            capacity: Self::DEFAULT_CAPACITY,
+            instructions: HashMap::new(),
+            hits: 0,
+            misses: 0,
+        }
+    }
+
+    pub fn state(&self) -> &KeyedAccount<Marinade> {
+        &self.state
+    }
+
+    pub fn version(&self) -> u64 {
+        self.version
+    }
+
+    /// Replaces the state snapshot. Returns true if it changed and the cache was invalidated
+    pub fn update(&mut self, state: KeyedAccount<Marinade>) -> bool {
+        let version = state_version(&state);
+        self.state = state;
+        if version == self.version {
+            return false;
+        }
+        self.version = version;
+        self.instructions.clear();
+        true
+    }
+
+    pub fn len(&self) -> usize {
+        self.instructions.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.instructions.is_empty()
+    }
+
+    /// (hits, misses) since creation
+    pub fn stats(&self) -> (u64, u64) {
+        (self.hits, self.misses)
+    }
+
+    /// Cached instruction for the data and user supplied accounts or the result of build.
+    /// Everything else build uses must be derived from the state
+    pub fn get_or_build<D, E>(
+        &mut self,
+        data: &D,
+        accounts: &[Pubkey],
+        build: impl FnOnce(&KeyedAccount<Marinade>) -> Result<Instruction, E>,
+    ) -> Result<Instruction, E>
+    where
+        D: InstructionData,
+    {
+        let key = (data.data(), accounts.to_vec());
+        if let Some(instruction) = self.instructions.get(&key) {
+            self.hits += 1;
+            return Ok(instruction.clone());
+        }
+        self.misses += 1;
+        let instruction = build(&self.state)?;
+        if self.instructions.len() >= self.capacity {
+            self.instructions.clear();
+        }
+        self.instructions.insert(key, instruction.clone());
+        Ok(instruction)
+    }
+
+    pub fn deposit(
+        &mut self,
+        data: DepositData,
+        transfer_from: Pubkey,
+        mint_to: Pubkey,
+    ) -> Result<Instruction, PubkeyError> {
+        self.get_or_build(&data, &[transfer_from, mint_to], |state| {
+            state.deposit(data, transfer_from, mint_to)
+        })
+    }
+
+    pub fn liquid_unstake(
+        &mut self,
+        data: LiquidUnstakeData,
+        get_msol_from: Pubkey,
+        get_msol_from_authority: Pubkey,
+        transfer_sol_to: Pubkey,
+    ) -> Result<Instruction, PubkeyError> {
+        self.get_or_build(
+            &data,
+            &[get_msol_from, get_msol_from_authority, transfer_sol_to],
+            |state| {
+                state.liquid_unstake(
+                    data,
+                    get_msol_from,
+                    get_msol_from_authority,
+                    transfer_sol_to,
+                )
+            },
+        )
+    }
+}
@@ -5,11 +5,12 @@ use solana_program::pubkey::Pubkey;
     InstructionData, Clone, Copy, Debug, Default, PartialEq, BorshSerialize, BorshDeserialize,
 )]
 #[discriminator([181, 157, 89, 67, 143, 182, 52, 72])]
+#[non_exhaustive]
 pub struct AddLiquidityData {
     pub lamports: u64,
 }
 
-#[derive(InstructionAccounts)]
+#[derive(InstructionAccounts, Clone, Copy, Debug, PartialEq, Eq)]
 #[accounts(ownerid=crate::ID, data=AddLiquidityData)]
 pub struct AddLiquidityAccounts {
     #[account(mut)]
@@ -27,3 +28,9 @@ pub struct AddLiquidityAccounts {
     pub system_program: Pubkey,
     pub token_program: Pubkey,
 }
+
+impl AddLiquidityData {
+    pub fn new(lamports: u64) -> Self {
+        Self { lamports }
+    }
+}
@@ -5,11 +5,12 @@ use solana_program::pubkey::Pubkey;
     InstructionData, Clone, Copy, Debug, Default, PartialEq, BorshSerialize, BorshDeserialize,
 )]
 #[discriminator([250, 113, 53, 54, 141, 117, 215, 185])]
+#[non_exhaustive]
 pub struct AddValidatorData {
     pub score: u32,
 }
 
-#[derive(InstructionAccounts)]
+#[derive(InstructionAccounts, Clone, Copy, Debug, PartialEq, Eq)]
 #[accounts(ownerid=crate::ID,data=AddValidatorData)]
 pub struct AddValidatorAccounts {
     #[account(mut)]
@@ -27,3 +28,9 @@ pub struct AddValidatorAccounts {
     pub rent: Pubkey,
     pub system_program: Pubkey,
 }
+
+impl AddValidatorData {
+    pub fn new(score: u32) -> Self {
+        Self { score }
+    }
+}
@@ -5,6 +5,7 @@ use solana_program::pubkey::Pubkey;
     InstructionData, Clone, Copy, Debug, Default, PartialEq, BorshSerialize, BorshDeserialize,
 )]
 #[discriminator([50, 106, 66, 104, 99, 118, 145, 88])]
+#[non_exhaustive]
 pub struct ChangeAuthorityData {
     pub admin: Option<Pubkey>,
     pub validator_manager: Option<Pubkey>,
@@ -12,7 +13,7 @@ pub struct ChangeAuthorityData {
     pub treasury_msol_account: Option<Pubkey>,
 }
 
-#[derive(InstructionAccounts)]
+#[derive(InstructionAccounts, Clone, Copy, Debug, PartialEq, Eq)]
 #[accounts(ownerid=crate::ID, data=ChangeAuthorityData)]
 pub struct ChangeAuthorityAccounts {
     #[account(mut)]
@@ -5,9 +5,10 @@ use solana_program::pubkey::Pubkey;
     InstructionData, Clone, Copy, Debug, Default, PartialEq, BorshSerialize, BorshDeserialize,
 )]
 #[discriminator([62, 198, 214, 193, 213, 159, 108, 210])]
+#[non_exhaustive]
 pub struct ClaimData {}
 
-#[derive(InstructionAccounts)]
+#[derive(InstructionAccounts, Clone, Copy, Debug, PartialEq, Eq)]
 #[accounts(ownerid=crate::ID,data=ClaimData)]
 pub struct ClaimAccounts {
     #[account(mut)]
@@ -21,3 +22,9 @@ pub struct ClaimAccounts {
     pub clock: Pubkey,
     pub system_program: Pubkey,
 }
+
+impl ClaimData {
+    pub fn new() -> Self {
+        Self {}
+    }
+}
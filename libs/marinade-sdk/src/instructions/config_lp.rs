@@ -6,6 +6,7 @@ use solana_program::pubkey::Pubkey;
     InstructionData, Clone, Copy, Debug, Default, PartialEq, BorshSerialize, BorshDeserialize,
 )]
 #[discriminator([10, 24, 168, 119, 86, 48, 225, 17])]
+#[non_exhaustive]
 pub struct ConfigLpData {
     pub min_fee: Option<Fee>,
     pub max_fee: Option<Fee>,
@@ -13,7 +14,7 @@ pub struct ConfigLpData {
     pub treasury_cut: Option<Fee>,
 }
 
-#[derive(InstructionAccounts)]
+#[derive(InstructionAccounts, Clone, Copy, Debug, PartialEq, Eq)]
 #[accounts(ownerid=crate::ID, data=ConfigLpData)]
 pub struct ConfigLpAccounts {
     #[account(mut)]
@@ -6,6 +6,7 @@ use solana_program::pubkey::Pubkey;
     InstructionData, Clone, Copy, Debug, Default, PartialEq, BorshSerialize, BorshDeserialize,
 )]
 #[discriminator([67, 3, 34, 114, 190, 185, 17, 62])]
+#[non_exhaustive]
 pub struct ConfigMarinadeData {
     pub rewards_fee: Option<Fee>,
     pub slots_for_stake_delta: Option<u64>,
@@ -17,7 +18,7 @@ pub struct ConfigMarinadeData {
     pub auto_add_validator_enabled: Option<bool>,
 }
 
-#[derive(InstructionAccounts)]
+#[derive(InstructionAccounts, Clone, Copy, Debug, PartialEq, Eq)]
 #[accounts(ownerid=crate::ID,data=ConfigMarinadeData)]
 pub struct ConfigMarinadeAccounts {
     #[account(mut)]
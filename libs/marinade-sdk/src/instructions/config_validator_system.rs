@@ -5,11 +5,12 @@ use solana_program::pubkey::Pubkey;
     InstructionData, Clone, Copy, Debug, Default, PartialEq, BorshSerialize, BorshDeserialize,
 )]
 #[discriminator([27, 90, 97, 209, 17, 115, 7, 40])]
+#[non_exhaustive]
 pub struct ConfigValidatorSystemData {
     pub extra_runs: u32,
 }
 
-#[derive(InstructionAccounts)]
+#[derive(InstructionAccounts, Clone, Copy, Debug, PartialEq, Eq)]
 #[accounts(ownerid=crate::ID,data=ConfigValidatorSystemData)]
 pub struct ConfigValidatorSystemAccounts {
     #[account(mut)]
@@ -17,3 +18,9 @@ pub struct ConfigValidatorSystemAccounts {
     #[account(signer)]
     pub manager_authority: Pubkey,
 }
+
+impl ConfigValidatorSystemData {
+    pub fn new(extra_runs: u32) -> Self {
+        Self { extra_runs }
+    }
+}
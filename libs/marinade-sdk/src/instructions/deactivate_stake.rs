@@ -5,12 +5,13 @@ use solana_program::pubkey::Pubkey;
     InstructionData, Clone, Copy, Debug, Default, PartialEq, BorshSerialize, BorshDeserialize,
 )]
 #[discriminator([165, 158, 229, 97, 168, 220, 187, 225])]
+#[non_exhaustive]
 pub struct DeactivateStakeData {
     pub stake_index: u32,
     pub validator_index: u32,
 }
 
-#[derive(InstructionAccounts)]
+#[derive(InstructionAccounts, Clone, Copy, Debug, PartialEq, Eq)]
 #[accounts(ownerid=crate::ID,data=DeactivateStakeData)]
 pub struct DeactivateStakeAccounts {
     #[account(mut)]
@@ -34,3 +35,12 @@ pub struct DeactivateStakeAccounts {
     pub system_program: Pubkey,
     pub stake_program: Pubkey,
 }
+
+impl DeactivateStakeData {
+    pub fn new(stake_index: u32, validator_index: u32) -> Self {
+        Self {
+            stake_index,
+            validator_index,
+        }
+    }
+}
@@ -5,11 +5,12 @@ use solana_program::pubkey::Pubkey;
     InstructionData, Clone, Copy, Debug, Default, PartialEq, BorshSerialize, BorshDeserialize,
 )]
 #[discriminator([242, 35, 198, 137, 82, 225, 242, 182])]
+#[non_exhaustive]
 pub struct DepositData {
     pub lamports: u64,
 }
 
-#[derive(InstructionAccounts)]
+#[derive(InstructionAccounts, Clone, Copy, Debug, PartialEq, Eq)]
 #[accounts(ownerid=crate::ID,data=DepositData)]
 pub struct DepositAccounts {
     #[account(mut)]
@@ -31,3 +32,9 @@ pub struct DepositAccounts {
     pub system_program: Pubkey,
     pub token_program: Pubkey,
 }
+
+impl DepositData {
+    pub fn new(lamports: u64) -> Self {
+        Self { lamports }
+    }
+}
@@ -5,11 +5,12 @@ use solana_program::pubkey::Pubkey;
     InstructionData, Clone, Copy, Debug, Default, PartialEq, BorshSerialize, BorshDeserialize,
 )]
 #[discriminator([110, 130, 115, 41, 164, 102, 2, 59])]
+#[non_exhaustive]
 pub struct DepositStakeAccountData {
     pub validator_index: u32,
 }
 
-#[derive(InstructionAccounts)]
+#[derive(InstructionAccounts, Clone, Copy, Debug, PartialEq, Eq)]
 #[accounts(ownerid=crate::ID, data=DepositStakeAccountData)]
 pub struct DepositStakeAccountAccounts {
     #[account(mut)]
@@ -37,3 +38,9 @@ pub struct DepositStakeAccountAccounts {
     pub token_program: Pubkey,
     pub stake_program: Pubkey,
 }
+
+impl DepositStakeAccountData {
+    pub fn new(validator_index: u32) -> Self {
+        Self { validator_index }
+    }
+}
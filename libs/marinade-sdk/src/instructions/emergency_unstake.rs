@@ -5,12 +5,13 @@ use solana_program::pubkey::Pubkey;
     InstructionData, Clone, Copy, Debug, Default, PartialEq, BorshSerialize, BorshDeserialize,
 )]
 #[discriminator([123, 69, 168, 195, 183, 213, 199, 214])]
+#[non_exhaustive]
 pub struct EmergencyUnstakeData {
     pub stake_index: u32,
     pub validator_index: u32,
 }
 
-#[derive(InstructionAccounts)]
+#[derive(InstructionAccounts, Clone, Copy, Debug, PartialEq, Eq)]
 #[accounts(ownerid=crate::ID,data=EmergencyUnstakeData)]
 pub struct EmergencyUnstakeAccounts {
     #[account(mut)]
@@ -27,3 +28,12 @@ pub struct EmergencyUnstakeAccounts {
     pub clock: Pubkey,
     pub stake_program: Pubkey,
 }
+
+impl EmergencyUnstakeData {
+    pub fn new(stake_index: u32, validator_index: u32) -> Self {
+        Self {
+            stake_index,
+            validator_index,
+        }
+    }
+}
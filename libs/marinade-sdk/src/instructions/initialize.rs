@@ -6,6 +6,7 @@ use solana_program::pubkey::Pubkey;
     InstructionData, Clone, Copy, Debug, Default, PartialEq, BorshSerialize, BorshDeserialize,
 )]
 #[discriminator([175, 175, 109, 31, 13, 152, 155, 237])]
+#[non_exhaustive]
 pub struct InitializeData {
     pub admin_authority: Pubkey,
     pub validator_manager_authority: Pubkey,
@@ -21,6 +22,7 @@ pub struct InitializeData {
     InstructionData, Clone, Copy, Debug, Default, PartialEq, BorshSerialize, BorshDeserialize,
 )]
 #[discriminator([1,2,3,4,5,6,7,8])] // fake discriminator
+#[non_exhaustive]
 pub struct LiqPoolInitializeData {
     pub lp_liquidity_target: u64,
     pub lp_max_fee: Fee,
@@ -28,7 +30,7 @@ pub struct LiqPoolInitializeData {
     pub lp_treasury_cut: Fee,
 }
 
-#[derive(InstructionAccounts)]
+#[derive(InstructionAccounts, Clone, Copy, Debug, PartialEq, Eq)]
 #[accounts(ownerid=crate::ID,data=InitializeData)]
 pub struct InitializeAccounts {
     #[account(signer)]
@@ -47,10 +49,60 @@ pub struct InitializeAccounts {
     pub rent: Pubkey,
 }
 
-#[derive(InstructionAccounts)]
+#[derive(InstructionAccounts, Clone, Copy, Debug, PartialEq, Eq)]
 #[accounts(ownerid=crate::ID,data=LiqPoolInitializeData)]
 pub struct LiqPoolInitializeAccounts {
     pub lp_mint: Pubkey,
     pub sol_leg_pda: Pubkey,
     pub msol_leg: Pubkey,
 }
+
+impl InitializeData {
+    pub fn new(admin_authority: Pubkey, validator_manager_authority: Pubkey) -> Self {
+        Self {
+            admin_authority,
+            validator_manager_authority,
+            ..Default::default()
+        }
+    }
+    pub fn with_min_stake(mut self, v: u64) -> Self {
+        self.min_stake = v;
+        self
+    }
+    pub fn with_reward_fee(mut self, v: Fee) -> Self {
+        self.reward_fee = v;
+        self
+    }
+    pub fn with_liq_pool(mut self, v: LiqPoolInitializeData) -> Self {
+        self.liq_pool = v;
+        self
+    }
+    pub fn with_additional_stake_record_space(mut self, v: u32) -> Self {
+        self.additional_stake_record_space = v;
+        self
+    }
+    pub fn with_additional_validator_record_space(mut self, v: u32) -> Self {
+        self.additional_validator_record_space = v;
+        self
+    }
+    pub fn with_slots_for_stake_delta(mut self, v: u64) -> Self {
+        self.slots_for_stake_delta = v;
+        self
+    }
+}
+
+impl LiqPoolInitializeData {
+    pub fn new(
+        lp_liquidity_target: u64,
+        lp_max_fee: Fee,
+        lp_min_fee: Fee,
+        lp_treasury_cut: Fee,
+    ) -> Self {
+        Self {
+            lp_liquidity_target,
+            lp_max_fee,
+            lp_min_fee,
+            lp_treasury_cut,
+        }
+    }
+}
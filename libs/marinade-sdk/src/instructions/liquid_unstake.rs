@@ -5,11 +5,12 @@ use solana_program::pubkey::Pubkey;
     InstructionData, Clone, Copy, Debug, Default, PartialEq, BorshSerialize, BorshDeserialize,
 )]
 #[discriminator([30, 30, 119, 240, 191, 227, 12, 16])]
+#[non_exhaustive]
 pub struct LiquidUnstakeData {
     pub msol_amount: u64,
 }
 
-#[derive(InstructionAccounts)]
+#[derive(InstructionAccounts, Clone, Copy, Debug, PartialEq, Eq)]
 #[accounts(ownerid=crate::ID,data=LiquidUnstakeData)]
 pub struct LiquidUnstakeAccounts {
     #[account(mut)]
@@ -31,3 +32,9 @@ pub struct LiquidUnstakeAccounts {
     pub system_program: Pubkey,
     pub token_program: Pubkey,
 }
+
+impl LiquidUnstakeData {
+    pub fn new(msol_amount: u64) -> Self {
+        Self { msol_amount }
+    }
+}
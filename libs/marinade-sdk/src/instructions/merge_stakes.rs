@@ -5,13 +5,14 @@ use solana_program::pubkey::Pubkey;
     InstructionData, Clone, Copy, Debug, Default, PartialEq, BorshSerialize, BorshDeserialize,
 )]
 #[discriminator([216, 36, 141, 225, 243, 78, 125, 237])]
+#[non_exhaustive]
 pub struct MergeStakesData {
     pub destination_stake_index: u32,
     pub source_stake_index: u32,
     pub validator_index: u32,
 }
 
-#[derive(InstructionAccounts)]
+#[derive(InstructionAccounts, Clone, Copy, Debug, PartialEq, Eq)]
 #[accounts(ownerid=crate::ID,data=MergeStakesData)]
 pub struct MergeStakesAccounts {
     #[account(mut)]
@@ -32,3 +33,17 @@ pub struct MergeStakesAccounts {
     pub stake_history: Pubkey,
     pub stake_program: Pubkey,
 }
+
+impl MergeStakesData {
+    pub fn new(
+        destination_stake_index: u32,
+        source_stake_index: u32,
+        validator_index: u32,
+    ) -> Self {
+        Self {
+            destination_stake_index,
+            source_stake_index,
+            validator_index,
+        }
+    }
+}
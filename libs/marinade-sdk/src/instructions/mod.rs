@@ -14,7 +14,47 @@ pub mod liquid_unstake;
 pub mod merge_stakes;
 pub mod order_unstake;
 pub mod partial_unstake;
+pub mod pause;
 pub mod remove_liquidity;
 pub mod remove_validator;
 pub mod set_validator_score;
 pub mod stake_reserve;
+pub mod update_active;
+pub mod update_deactivated;
+
+use borsh::BorshSerialize;
+use solana_program::{instruction::Instruction, pubkey::Pubkey};
+
+#[derive(BorshSerialize)]
+pub(crate) struct SerializedAccountMeta {
+    pubkey: Pubkey,
+    is_signer: bool,
+    is_writable: bool,
+}
+
+/// Borsh layout shared by spl-governance InstructionData, squads-mpl IncomingInstruction
+/// and the instruction buffers of the C API (marinade-sdk-ffi)
+#[derive(BorshSerialize)]
+pub struct SerializedInstruction {
+    program_id: Pubkey,
+    accounts: Vec<SerializedAccountMeta>,
+    data: Vec<u8>,
+}
+
+impl From<&Instruction> for SerializedInstruction {
+    fn from(instruction: &Instruction) -> Self {
+        Self {
+            program_id: instruction.program_id,
+            accounts: instruction
+                .accounts
+                .iter()
+                .map(|meta| SerializedAccountMeta {
+                    pubkey: meta.pubkey,
+                    is_signer: meta.is_signer,
+                    is_writable: meta.is_writable,
+                })
+                .collect(),
+            data: instruction.data.clone(),
+        }
+    }
+}
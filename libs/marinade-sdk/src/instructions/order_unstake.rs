@@ -5,11 +5,12 @@ use solana_program::pubkey::Pubkey;
     InstructionData, Clone, Copy, Debug, Default, PartialEq, BorshSerialize, BorshDeserialize,
 )]
 #[discriminator([97, 167, 144, 107, 117, 190, 128, 36])]
+#[non_exhaustive]
 pub struct OrderUnstakeData {
     pub msol_amount: u64,
 }
 
-#[derive(InstructionAccounts)]
+#[derive(InstructionAccounts, Clone, Copy, Debug, PartialEq, Eq)]
 #[accounts(ownerid=crate::ID,data=OrderUnstakeData)]
 pub struct OrderUnstakeAccounts {
     #[account(mut)]
@@ -25,3 +26,9 @@ pub struct OrderUnstakeAccounts {
     pub rent: Pubkey,
     pub token_program: Pubkey,
 }
+
+impl OrderUnstakeData {
+    pub fn new(msol_amount: u64) -> Self {
+        Self { msol_amount }
+    }
+}
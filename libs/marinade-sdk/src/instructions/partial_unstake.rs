@@ -5,13 +5,14 @@ use solana_program::pubkey::Pubkey;
     InstructionData, Clone, Copy, Debug, Default, PartialEq, BorshSerialize, BorshDeserialize,
 )]
 #[discriminator([55, 241, 205, 221, 45, 114, 205, 163])]
+#[non_exhaustive]
 pub struct PartialUnstakeData {
     pub stake_index: u32,
     pub validator_index: u32,
     pub desired_unstake_amount: u64,
 }
 
-#[derive(InstructionAccounts)]
+#[derive(InstructionAccounts, Clone, Copy, Debug, PartialEq, Eq)]
 #[accounts(ownerid=crate::ID,data=PartialUnstakeData)]
 pub struct PartialUnstakeAccounts {
     #[account(mut)]
@@ -36,3 +37,13 @@ pub struct PartialUnstakeAccounts {
     pub system_program: Pubkey,
     pub stake_program: Pubkey,
 }
+
+impl PartialUnstakeData {
+    pub fn new(stake_index: u32, validator_index: u32, desired_unstake_amount: u64) -> Self {
+        Self {
+            stake_index,
+            validator_index,
+            desired_unstake_amount,
+        }
+    }
+}
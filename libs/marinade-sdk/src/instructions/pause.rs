@@ -0,0 +1,46 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::pubkey::Pubkey;
+
+#[derive(
+    InstructionData, Clone, Copy, Debug, Default, PartialEq, BorshSerialize, BorshDeserialize,
+)]
+#[discriminator([211, 22, 221, 251, 74, 121, 193, 47])]
+#[non_exhaustive]
+pub struct PauseData {}
+
+#[derive(
+    InstructionData, Clone, Copy, Debug, Default, PartialEq, BorshSerialize, BorshDeserialize,
+)]
+#[discriminator([1, 166, 51, 170, 127, 32, 141, 206])]
+#[non_exhaustive]
+pub struct ResumeData {}
+
+#[derive(InstructionAccounts, Clone, Copy, Debug, PartialEq, Eq)]
+#[accounts(ownerid=crate::ID, data=PauseData)]
+pub struct PauseAccounts {
+    #[account(mut)]
+    pub marinade: Pubkey, // state
+    #[account(signer)]
+    pub pause_authority: Pubkey,
+}
+
+#[derive(InstructionAccounts, Clone, Copy, Debug, PartialEq, Eq)]
+#[accounts(ownerid=crate::ID, data=ResumeData)]
+pub struct ResumeAccounts {
+    #[account(mut)]
+    pub marinade: Pubkey, // state
+    #[account(signer)]
+    pub pause_authority: Pubkey,
+}
+
+impl PauseData {
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+impl ResumeData {
+    pub fn new() -> Self {
+        Self {}
+    }
+}
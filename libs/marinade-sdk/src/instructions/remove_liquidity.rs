@@ -5,11 +5,12 @@ use solana_program::pubkey::Pubkey;
     InstructionData, Clone, Copy, Debug, Default, PartialEq, BorshSerialize, BorshDeserialize,
 )]
 #[discriminator([80, 85, 209, 72, 24, 206, 177, 108])]
+#[non_exhaustive]
 pub struct RemoveLiquidityData {
     pub tokens: u64,
 }
 
-#[derive(InstructionAccounts)]
+#[derive(InstructionAccounts, Clone, Copy, Debug, PartialEq, Eq)]
 #[accounts(ownerid=crate::ID,data=RemoveLiquidityData)]
 pub struct RemoveLiquidityAccounts {
     #[account(mut)]
@@ -32,3 +33,9 @@ pub struct RemoveLiquidityAccounts {
     pub system_program: Pubkey,
     pub token_program: Pubkey,
 }
+
+impl RemoveLiquidityData {
+    pub fn new(tokens: u64) -> Self {
+        Self { tokens }
+    }
+}
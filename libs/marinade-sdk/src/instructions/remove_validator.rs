@@ -5,12 +5,13 @@ use solana_program::pubkey::Pubkey;
     InstructionData, Clone, Copy, Debug, Default, PartialEq, BorshSerialize, BorshDeserialize,
 )]
 #[discriminator([25, 96, 211, 155, 161, 14, 168, 188])]
+#[non_exhaustive]
 pub struct RemoveValidatorData {
     pub index: u32,
     pub validator_vote: Pubkey,
 }
 
-#[derive(InstructionAccounts)]
+#[derive(InstructionAccounts, Clone, Copy, Debug, PartialEq, Eq)]
 #[accounts(ownerid=crate::ID,data=RemoveValidatorData)]
 pub struct RemoveValidatorAccounts {
     #[account(mut)]
@@ -24,3 +25,12 @@ pub struct RemoveValidatorAccounts {
     #[account(mut)]
     pub operational_sol_account: Pubkey,
 }
+
+impl RemoveValidatorData {
+    pub fn new(index: u32, validator_vote: Pubkey) -> Self {
+        Self {
+            index,
+            validator_vote,
+        }
+    }
+}
@@ -5,13 +5,14 @@ use solana_program::pubkey::Pubkey;
     InstructionData, Clone, Copy, Debug, Default, PartialEq, BorshSerialize, BorshDeserialize,
 )]
 #[discriminator([101, 41, 206, 33, 216, 111, 25, 78])]
+#[non_exhaustive]
 pub struct SetValidatorScoreData {
     pub index: u32,
     pub validator_vote: Pubkey,
     pub score: u32,
 }
 
-#[derive(InstructionAccounts)]
+#[derive(InstructionAccounts, Clone, Copy, Debug, PartialEq, Eq)]
 #[accounts(ownerid=crate::ID,data=SetValidatorScoreData)]
 pub struct SetValidatorScoreAccounts {
     #[account(mut)]
@@ -21,3 +22,13 @@ pub struct SetValidatorScoreAccounts {
     #[account(mut)]
     pub validator_list: Pubkey,
 }
+
+impl SetValidatorScoreData {
+    pub fn new(index: u32, validator_vote: Pubkey, score: u32) -> Self {
+        Self {
+            index,
+            validator_vote,
+            score,
+        }
+    }
+}
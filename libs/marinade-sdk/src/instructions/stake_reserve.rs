@@ -5,11 +5,12 @@ use solana_program::pubkey::Pubkey;
     InstructionData, Clone, Copy, Debug, Default, PartialEq, BorshSerialize, BorshDeserialize,
 )]
 #[discriminator([87, 217, 23, 179, 205, 25, 113, 129])]
+#[non_exhaustive]
 pub struct StakeReserveData {
     pub validator_index: u32,
 }
 
-#[derive(InstructionAccounts)]
+#[derive(InstructionAccounts, Clone, Copy, Debug, PartialEq, Eq)]
 #[accounts(ownerid=crate::ID,data=StakeReserveData)]
 pub struct StakeReserveAccounts {
     #[account(mut)]
@@ -33,3 +34,9 @@ pub struct StakeReserveAccounts {
     pub system_program: Pubkey,
     pub stake_program: Pubkey,
 }
+
+impl StakeReserveData {
+    pub fn new(validator_index: u32) -> Self {
+        Self { validator_index }
+    }
+}
@@ -0,0 +1,46 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::pubkey::Pubkey;
+
+#[derive(
+    InstructionData, Clone, Copy, Debug, Default, PartialEq, BorshSerialize, BorshDeserialize,
+)]
+#[discriminator([4, 67, 81, 64, 136, 245, 93, 152])]
+#[non_exhaustive]
+pub struct UpdateActiveData {
+    pub stake_index: u32,
+    pub validator_index: u32,
+}
+
+#[derive(InstructionAccounts, Clone, Copy, Debug, PartialEq, Eq)]
+#[accounts(ownerid=crate::ID,data=UpdateActiveData)]
+pub struct UpdateActiveAccounts {
+    #[account(mut)]
+    pub marinade: Pubkey, // state
+    #[account(mut)]
+    pub stake_list: Pubkey,
+    #[account(mut)]
+    pub stake_account: Pubkey,
+    pub stake_withdraw_authority: Pubkey,
+    #[account(mut)]
+    pub reserve_pda: Pubkey,
+    #[account(mut)]
+    pub msol_mint: Pubkey,
+    pub msol_mint_authority: Pubkey,
+    #[account(mut)]
+    pub treasury_msol_account: Pubkey,
+    pub clock: Pubkey,
+    pub stake_history: Pubkey,
+    pub stake_program: Pubkey,
+    pub token_program: Pubkey,
+    #[account(mut)]
+    pub validator_list: Pubkey,
+}
+
+impl UpdateActiveData {
+    pub fn new(stake_index: u32, validator_index: u32) -> Self {
+        Self {
+            stake_index,
+            validator_index,
+        }
+    }
+}
@@ -0,0 +1,43 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::pubkey::Pubkey;
+
+#[derive(
+    InstructionData, Clone, Copy, Debug, Default, PartialEq, BorshSerialize, BorshDeserialize,
+)]
+#[discriminator([16, 232, 131, 115, 156, 100, 239, 50])]
+#[non_exhaustive]
+pub struct UpdateDeactivatedData {
+    pub stake_index: u32,
+}
+
+#[derive(InstructionAccounts, Clone, Copy, Debug, PartialEq, Eq)]
+#[accounts(ownerid=crate::ID,data=UpdateDeactivatedData)]
+pub struct UpdateDeactivatedAccounts {
+    #[account(mut)]
+    pub marinade: Pubkey, // state
+    #[account(mut)]
+    pub stake_list: Pubkey,
+    #[account(mut)]
+    pub stake_account: Pubkey,
+    pub stake_withdraw_authority: Pubkey,
+    #[account(mut)]
+    pub reserve_pda: Pubkey,
+    #[account(mut)]
+    pub msol_mint: Pubkey,
+    pub msol_mint_authority: Pubkey,
+    #[account(mut)]
+    pub treasury_msol_account: Pubkey,
+    pub clock: Pubkey,
+    pub stake_history: Pubkey,
+    pub stake_program: Pubkey,
+    pub token_program: Pubkey,
+    #[account(mut)]
+    pub operational_sol_account: Pubkey,
+    pub system_program: Pubkey,
+}
+
+impl UpdateDeactivatedData {
+    pub fn new(stake_index: u32) -> Self {
+        Self { stake_index }
+    }
+}
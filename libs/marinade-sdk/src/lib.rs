@@ -1,9 +1,82 @@
+pub mod admin;
+pub mod alerts;
+pub mod amount;
+#[cfg(feature = "anchor")]
+pub mod anchor;
+pub mod ata;
+#[cfg(feature = "history")]
+pub mod backfill;
+#[cfg(feature = "batch")]
+pub mod batch;
+pub mod builders;
 pub mod calc;
+pub mod capabilities;
 pub mod checks;
+pub mod claim_timing;
+#[cfg(feature = "rpc")]
+pub mod client;
+pub mod compat;
+pub mod crank_progress;
+pub mod decode;
+#[cfg(feature = "devnet-tools")]
+pub mod devnet;
+pub mod dto;
 pub mod error;
+#[cfg(feature = "events")]
+pub mod events;
+#[cfg(feature = "export")]
+pub mod export;
+pub mod fee_simulation;
+#[cfg(feature = "rpc")]
+pub mod filters;
+pub mod guard;
+pub mod health;
+#[cfg(feature = "history")]
+pub mod history;
+pub mod instruction_cache;
 pub mod instructions;
+pub mod lint;
 pub mod located;
+pub mod lp_pnl;
+#[cfg(feature = "notify")]
+pub mod notify;
+#[cfg(feature = "history")]
+pub mod param_history;
+#[cfg(feature = "parse")]
+pub mod parse;
+pub mod pda_cache;
+pub mod planner;
+pub mod portfolio;
+pub mod prelude;
+pub mod program;
+pub mod propose;
+pub mod quote;
+#[cfg(feature = "rate-limit")]
+pub mod rate_limit;
+pub mod reconciliation;
+#[cfg(feature = "replay")]
+pub mod replay;
+pub mod required_accounts;
+#[cfg(feature = "reserves")]
+pub mod reserves;
+pub mod seed_sequence;
+#[cfg(feature = "serde")]
+pub mod serde_pubkey;
+pub mod split_stake;
 pub mod state;
+pub mod storage;
+pub mod sysvars;
+pub mod templates;
+#[cfg(feature = "test-utils")]
+pub mod test_vectors;
+#[cfg(feature = "test-utils")]
+pub mod testing;
+pub mod ticket;
+#[cfg(feature = "tx")]
+pub mod tx;
+pub mod validator_report;
+pub mod watchdog;
+pub mod withdrawal_queue;
 
 use solana_program::pubkey::Pubkey;
 
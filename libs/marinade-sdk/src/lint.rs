@@ -0,0 +1,125 @@
+//! Checks instructions modified after building (accounts added, flags changed) against the
+//! account template of their discriminator, so integrators get an explanation instead of a
+//! failed simulation. The templates are those of templates::instruction_templates.
+
+use derive_more::Display;
+use solana_program::{instruction::Instruction, pubkey::Pubkey};
+
+use crate::{program::MarinadeProgram, templates::find_template};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Display)]
+pub enum LintIssue {
+    #[display(fmt = "Program id {} is not Marinade {}", actual, expected)]
+    WrongProgram { expected: Pubkey, actual: Pubkey },
+    #[display(fmt = "Unknown instruction discriminator {:?}", _0)]
+    UnknownInstruction([u8; 8]),
+    #[display(fmt = "Expected {} accounts, got {}", expected, actual)]
+    MissingAccounts { expected: usize, actual: usize },
+    #[display(
+        fmt = "Expected {} accounts, got {}: the extra ones are ignored",
+        expected,
+        actual
+    )]
+    ExtraAccounts { expected: usize, actual: usize },
+    #[display(fmt = "Account #{} {} must sign", index, pubkey)]
+    MissingSigner { index: usize, pubkey: Pubkey },
+    #[display(fmt = "Account #{} {} signs but does not need to", index, pubkey)]
+    UnneededSigner { index: usize, pubkey: Pubkey },
+    #[display(fmt = "Account #{} {} is readonly but must be writable", index, pubkey)]
+    ReadonlyShouldBeWritable { index: usize, pubkey: Pubkey },
+    #[display(
+        fmt = "Account #{} {} is writable but only read, it locks the account needlessly",
+        index,
+        pubkey
+    )]
+    WritableShouldBeReadonly { index: usize, pubkey: Pubkey },
+}
+
+impl LintIssue {
+    /// False for divergences the program accepts
+    pub fn is_error(&self) -> bool {
+        !matches!(
+            self,
+            LintIssue::ExtraAccounts { .. }
+                | LintIssue::UnneededSigner { .. }
+                | LintIssue::WritableShouldBeReadonly { .. }
+        )
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct LintReport {
+    /// snake_case name of the recognized instruction
+    pub instruction: Option<&'static str>,
+    pub issues: Vec<LintIssue>,
+}
+
+impl LintReport {
+    /// No issue would make the program reject the instruction
+    pub fn is_ok(&self) -> bool {
+        !self.issues.iter().any(LintIssue::is_error)
+    }
+}
+
+/// Lints an instruction for the deployment of program, MarinadeProgram::default() for crate::ID
+pub fn lint_instruction(instruction: &Instruction, program: &MarinadeProgram) -> LintReport {
+    let mut report = LintReport::default();
+    if instruction.program_id != program.program_id {
+        report.issues.push(LintIssue::WrongProgram {
+            expected: program.program_id,
+            actual: instruction.program_id,
+        });
+    }
+    let template = match find_template(&instruction.data) {
+        Some(template) => template,
+        None => {
+            let mut discriminator = [0u8; 8];
+            let len = instruction.data.len().min(8);
+            discriminator[..len].copy_from_slice(&instruction.data[..len]);
+            report
+                .issues
+                .push(LintIssue::UnknownInstruction(discriminator));
+            return report;
+        }
+    };
+    report.instruction = Some(template.name);
+
+    let expected = template.instruction.accounts.len();
+    let actual = instruction.accounts.len();
+    if actual < expected {
+        report
+            .issues
+            .push(LintIssue::MissingAccounts { expected, actual });
+    } else if actual > expected {
+        report
+            .issues
+            .push(LintIssue::ExtraAccounts { expected, actual });
+    }
+    for (index, (meta, expected_meta)) in instruction
+        .accounts
+        .iter()
+        .zip(template.instruction.accounts.iter())
+        .enumerate()
+    {
+        let pubkey = meta.pubkey;
+        match (expected_meta.is_signer, meta.is_signer) {
+            (true, false) => report
+                .issues
+                .push(LintIssue::MissingSigner { index, pubkey }),
+            (false, true) => report
+                .issues
+                .push(LintIssue::UnneededSigner { index, pubkey }),
+            _ => {}
+        }
+        match (expected_meta.is_writable, meta.is_writable) {
+            (true, false) => report
+                .issues
+                .push(LintIssue::ReadonlyShouldBeWritable { index, pubkey }),
+            (false, true) => report
+                .issues
+                .push(LintIssue::WritableShouldBeReadonly { index, pubkey }),
+            _ => {}
+        }
+    }
+    report
+}
@@ -7,4 +7,54 @@ pub trait Located<T> {
     fn as_ref(&self) -> &T;
     fn as_mut(&mut self) -> &mut T;
     fn key(&self) -> Pubkey;
+
+    /// Program owning the account, see program::MarinadeProgram for forks
+    fn program_id(&self) -> Pubkey {
+        crate::ID
+    }
+}
+
+/// Plain parsed account with its address, e.g. fetched by an off-chain client
+#[derive(Clone, Debug)]
+pub struct KeyedAccount<T> {
+    pub key: Pubkey,
+    pub account: T,
+}
+
+impl<T> KeyedAccount<T> {
+    pub fn new(key: Pubkey, account: T) -> Self {
+        Self { key, account }
+    }
+}
+
+impl<T, L: Located<T>> Located<T> for Box<L> {
+    fn as_ref(&self) -> &T {
+        (**self).as_ref()
+    }
+
+    fn as_mut(&mut self) -> &mut T {
+        (**self).as_mut()
+    }
+
+    fn key(&self) -> Pubkey {
+        (**self).key()
+    }
+
+    fn program_id(&self) -> Pubkey {
+        (**self).program_id()
+    }
+}
+
+impl<T> Located<T> for KeyedAccount<T> {
+    fn as_ref(&self) -> &T {
+        &self.account
+    }
+
+    fn as_mut(&mut self) -> &mut T {
+        &mut self.account
+    }
+
+    fn key(&self) -> Pubkey {
+        self.key
+    }
 }
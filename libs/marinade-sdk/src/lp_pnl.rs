@@ -0,0 +1,155 @@
+//! Liquidity provider profit and loss across add/remove liquidity events
+
+use solana_program::pubkey::Pubkey;
+
+use crate::{
+    calc::proportional,
+    error::{CommonError, Mismatch},
+    state::{liq_pool::LiqPool, marinade::Marinade},
+};
+
+/// Liquidity pool action of some wallet.
+/// msol_price is the state msol_price (scaled by Marinade::PRICE_DENOMINATOR) at the time of the event
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LiquidityEvent {
+    Add {
+        wallet: Pubkey,
+        lamports: u64,
+        lp_minted: u64,
+        msol_price: u64,
+    },
+    Remove {
+        wallet: Pubkey,
+        lp_burned: u64,
+        sol_out: u64,
+        msol_out: u64,
+        msol_price: u64,
+    },
+}
+
+impl LiquidityEvent {
+    pub fn wallet(&self) -> &Pubkey {
+        match self {
+            LiquidityEvent::Add { wallet, .. } => wallet,
+            LiquidityEvent::Remove { wallet, .. } => wallet,
+        }
+    }
+}
+
+/// All values are in lamports
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct LpPnl {
+    pub lp_balance: u64,
+    /// SOL added and not yet withdrawn (average cost basis)
+    pub cost_basis: u64,
+    /// Withdrawn value minus its cost basis
+    pub realized: i128,
+    /// Current value of the remaining lp_balance minus its cost basis
+    pub unrealized: i128,
+    /// Current value of the remaining lp_balance
+    pub current_value: u64,
+    /// Result of the LP position compared to keeping the SOL
+    pub vs_holding_sol: i128,
+    /// Result of the LP position compared to depositing the same SOL for mSOL instead
+    pub vs_holding_msol: i128,
+}
+
+fn msol_value(msol_amount: u64, msol_price: u64) -> Result<u64, CommonError> {
+    proportional(msol_amount, msol_price, Marinade::PRICE_DENOMINATOR)
+}
+
+/// Replays the wallet's liquidity events in order and values the remaining position.
+/// `sol_leg` is the SOL leg balance without the rent exempt reserve.
+pub fn lp_pnl(
+    wallet: &Pubkey,
+    history: &[LiquidityEvent],
+    marinade: &Marinade,
+    sol_leg: u64,
+    msol_leg: u64,
+) -> Result<LpPnl, CommonError> {
+    let mut lp_balance: u64 = 0;
+    let mut cost_basis: u64 = 0;
+    let mut deposited: u64 = 0;
+    let mut realized: i128 = 0;
+    let mut withdrawn_value: u64 = 0;
+    // the alternative of buying mSOL with the same lamports
+    let mut hodl_msol: u64 = 0;
+    let mut hodl_withdrawn_value: u64 = 0;
+
+    for event in history.iter().filter(|e| e.wallet() == wallet) {
+        match *event {
+            LiquidityEvent::Add {
+                lamports,
+                lp_minted,
+                msol_price,
+                ..
+            } => {
+                lp_balance = lp_balance
+                    .checked_add(lp_minted)
+                    .ok_or(CommonError::CalculationFailure)?;
+                cost_basis = cost_basis
+                    .checked_add(lamports)
+                    .ok_or(CommonError::CalculationFailure)?;
+                deposited = deposited
+                    .checked_add(lamports)
+                    .ok_or(CommonError::CalculationFailure)?;
+                hodl_msol = hodl_msol
+                    .checked_add(proportional(
+                        lamports,
+                        Marinade::PRICE_DENOMINATOR,
+                        msol_price,
+                    )?)
+                    .ok_or(CommonError::CalculationFailure)?;
+            }
+            LiquidityEvent::Remove {
+                lp_burned,
+                sol_out,
+                msol_out,
+                msol_price,
+                ..
+            } => {
+                if lp_burned > lp_balance {
+                    // history is incomplete
+                    return Err(CommonError::NumberTooHigh(Some(Mismatch::new(
+                        lp_balance, lp_burned,
+                    ))));
+                }
+                let basis_removed = proportional(cost_basis, lp_burned, lp_balance)?;
+                let hodl_sold = proportional(hodl_msol, lp_burned, lp_balance)?;
+                let proceeds = sol_out
+                    .checked_add(msol_value(msol_out, msol_price)?)
+                    .ok_or(CommonError::CalculationFailure)?;
+
+                realized += proceeds as i128 - basis_removed as i128;
+                withdrawn_value = withdrawn_value
+                    .checked_add(proceeds)
+                    .ok_or(CommonError::CalculationFailure)?;
+                hodl_withdrawn_value = hodl_withdrawn_value
+                    .checked_add(msol_value(hodl_sold, msol_price)?)
+                    .ok_or(CommonError::CalculationFailure)?;
+
+                lp_balance -= lp_burned;
+                cost_basis -= basis_removed;
+                hodl_msol -= hodl_sold;
+            }
+        }
+    }
+
+    let current_value =
+        LiqPool::underlying_for_lp(lp_balance, sol_leg, msol_leg, marinade.liq_pool.lp_supply)?
+            .exposure(marinade)?
+            .total_value;
+    let hodl_value =
+        hodl_withdrawn_value as i128 + marinade.calc_lamports_from_msol_amount(hodl_msol)? as i128;
+    let lp_value = withdrawn_value as i128 + current_value as i128;
+
+    Ok(LpPnl {
+        lp_balance,
+        cost_basis,
+        realized,
+        unrealized: current_value as i128 - cost_basis as i128,
+        current_value,
+        vs_holding_sol: lp_value - deposited as i128,
+        vs_holding_msol: lp_value - hodl_value,
+    })
+}
@@ -0,0 +1,149 @@
+//! Senders delivering alert events to Slack, Discord or a generic JSON webhook
+
+use derive_more::{Display, From};
+use serde_json::{json, Value};
+
+use crate::alerts::AlertEvent;
+
+#[derive(Debug, Display, From)]
+pub enum NotifyError {
+    Http(reqwest::Error),
+}
+
+impl std::error::Error for NotifyError {}
+
+pub trait AlertSender {
+    fn payload(&self, events: &[AlertEvent]) -> Value;
+    fn url(&self) -> &str;
+    fn client(&self) -> &reqwest::blocking::Client;
+
+    /// Posts all events in one message. Nothing is sent for an empty slice.
+    fn send(&self, events: &[AlertEvent]) -> Result<(), NotifyError> {
+        if events.is_empty() {
+            return Ok(());
+        }
+        self.client()
+            .post(self.url())
+            .json(&self.payload(events))
+            .send()?
+            .error_for_status()?;
+        Ok(())
+    }
+}
+
+fn text_lines(events: &[AlertEvent]) -> String {
+    events
+        .iter()
+        .map(|event| format!("[{}] slot {}: {}", event.name, event.slot, event.message))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+pub struct SlackNotifier {
+    pub webhook_url: String,
+    client: reqwest::blocking::Client,
+}
+
+impl SlackNotifier {
+    pub fn new(webhook_url: String) -> Self {
+        Self {
+            webhook_url,
+            client: reqwest::blocking::Client::new(),
+        }
+    }
+}
+
+impl AlertSender for SlackNotifier {
+    fn payload(&self, events: &[AlertEvent]) -> Value {
+        json!({ "text": format!(":rotating_light: Marinade alerts\n{}", text_lines(events)) })
+    }
+
+    fn url(&self) -> &str {
+        &self.webhook_url
+    }
+
+    fn client(&self) -> &reqwest::blocking::Client {
+        &self.client
+    }
+}
+
+pub struct DiscordNotifier {
+    pub webhook_url: String,
+    client: reqwest::blocking::Client,
+}
+
+impl DiscordNotifier {
+    pub fn new(webhook_url: String) -> Self {
+        Self {
+            webhook_url,
+            client: reqwest::blocking::Client::new(),
+        }
+    }
+}
+
+impl AlertSender for DiscordNotifier {
+    fn payload(&self, events: &[AlertEvent]) -> Value {
+        // discord limits content to 2000 characters
+        let mut content = format!("**Marinade alerts**\n{}", text_lines(events));
+        if content.len() > 2000 {
+            let mut end = 1997;
+            while !content.is_char_boundary(end) {
+                end -= 1;
+            }
+            content.truncate(end);
+            content.push_str("...");
+        }
+        json!({ "content": content })
+    }
+
+    fn url(&self) -> &str {
+        &self.webhook_url
+    }
+
+    fn client(&self) -> &reqwest::blocking::Client {
+        &self.client
+    }
+}
+
+/// Posts `{"alerts": [{name, rule, observed, slot, message}]}` to any endpoint,
+/// rule in the stable AlertRule Display form
+pub struct WebhookNotifier {
+    pub url: String,
+    client: reqwest::blocking::Client,
+}
+
+impl WebhookNotifier {
+    pub fn new(url: String) -> Self {
+        Self {
+            url,
+            client: reqwest::blocking::Client::new(),
+        }
+    }
+}
+
+impl AlertSender for WebhookNotifier {
+    fn payload(&self, events: &[AlertEvent]) -> Value {
+        let alerts: Vec<Value> = events
+            .iter()
+            .map(|event| {
+                json!({
+                    "name": event.name,
+                    "rule": event.rule.to_string(),
+                    // u64 as string, JS clients lose precision on big numbers
+                    "observed": event.observed.to_string(),
+                    "slot": event.slot.to_string(),
+                    "message": event.message,
+                })
+            })
+            .collect();
+        json!({ "alerts": alerts })
+    }
+
+    fn url(&self) -> &str {
+        &self.url
+    }
+
+    fn client(&self) -> &reqwest::blocking::Client {
+        &self.client
+    }
+}
@@ -0,0 +1,210 @@
+//! Changelog of the on-chain parameters of a state, extracted from the classified program
+//! history: every successful config_marinade, config_lp and change_authority with the values
+//! it set, top level or invoked by SPL Governance or Squads. The previous values come from state snapshots (e.g. fetched at known slots or kept
+//! by an indexer): the last snapshot before the transaction, updated by the changes landing
+//! after it. Fields are unknown before the first snapshot until a change sets them.
+
+use std::{collections::BTreeMap, fmt::Display, ops::RangeBounds};
+
+use solana_program::pubkey::Pubkey;
+use solana_sdk::signature::Signature;
+
+use crate::{
+    decode::{decode_compiled, MarinadeInstruction},
+    history::ClassifiedTransaction,
+    program::MarinadeProgram,
+    propose::{config_changes, lp_config_changes, ConfigChange},
+    state::marinade::{Marinade, StakingCap},
+};
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParamChange {
+    pub field: &'static str,
+    /// None when no snapshot or earlier change covers the field
+    pub old: Option<String>,
+    pub new: String,
+}
+
+impl ParamChange {
+    /// The field was set to the value it already had
+    pub fn is_noop(&self) -> bool {
+        self.old.as_ref() == Some(&self.new)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParamChangeEntry {
+    pub signature: Signature,
+    pub slot: u64,
+    pub block_time: Option<i64>,
+    /// Instruction name as in the program IDL
+    pub instruction: &'static str,
+    /// Every field the instruction sets, in instruction data order
+    pub changes: Vec<ParamChange>,
+}
+
+fn push_set<T: Display>(
+    fields: &mut Vec<(&'static str, String)>,
+    field: &'static str,
+    new: Option<T>,
+) {
+    if let Some(new) = new {
+        fields.push((field, new.to_string()));
+    }
+}
+
+/// Fields set by an admin parameter instruction, None for the other instructions
+fn set_fields(instruction: &MarinadeInstruction) -> Option<Vec<(&'static str, String)>> {
+    let mut fields = Vec::new();
+    match instruction {
+        MarinadeInstruction::ConfigMarinade { data, .. } => {
+            push_set(&mut fields, "rewards_fee", data.rewards_fee);
+            push_set(
+                &mut fields,
+                "slots_for_stake_delta",
+                data.slots_for_stake_delta,
+            );
+            push_set(&mut fields, "min_stake", data.min_stake);
+            push_set(&mut fields, "min_deposit", data.min_deposit);
+            push_set(&mut fields, "min_withdraw", data.min_withdraw);
+            push_set(
+                &mut fields,
+                "staking_sol_cap",
+                data.staking_sol_cap.map(StakingCap::from_raw),
+            );
+            push_set(&mut fields, "liquidity_sol_cap", data.liquidity_sol_cap);
+            push_set(
+                &mut fields,
+                "auto_add_validator_enabled",
+                data.auto_add_validator_enabled,
+            );
+        }
+        MarinadeInstruction::ConfigLp { data, .. } => {
+            push_set(&mut fields, "min_fee", data.min_fee);
+            push_set(&mut fields, "max_fee", data.max_fee);
+            push_set(&mut fields, "liquidity_target", data.liquidity_target);
+            push_set(&mut fields, "treasury_cut", data.treasury_cut);
+        }
+        MarinadeInstruction::ChangeAuthority { data, .. } => {
+            push_set(&mut fields, "admin", data.admin);
+            push_set(&mut fields, "validator_manager", data.validator_manager);
+            push_set(
+                &mut fields,
+                "operational_sol_account",
+                data.operational_sol_account,
+            );
+            push_set(
+                &mut fields,
+                "treasury_msol_account",
+                data.treasury_msol_account,
+            );
+        }
+        _ => return None,
+    }
+    Some(fields)
+}
+
+/// Fields of the instruction that differ from snapshot, the others it sets are unchanged
+fn snapshot_changes(snapshot: &Marinade, instruction: &MarinadeInstruction) -> Vec<ConfigChange> {
+    match instruction {
+        MarinadeInstruction::ConfigMarinade { data, .. } => config_changes(snapshot, data),
+        MarinadeInstruction::ConfigLp { data, .. } => lp_config_changes(snapshot, data),
+        MarinadeInstruction::ChangeAuthority { data, .. } => [
+            ("admin", snapshot.admin_authority, data.admin),
+            (
+                "validator_manager",
+                snapshot.validator_system.manager_authority,
+                data.validator_manager,
+            ),
+            (
+                "operational_sol_account",
+                snapshot.operational_sol_account,
+                data.operational_sol_account,
+            ),
+            (
+                "treasury_msol_account",
+                snapshot.treasury_msol_account,
+                data.treasury_msol_account,
+            ),
+        ]
+        .into_iter()
+        .filter_map(|(field, old, new)| {
+            new.filter(|new| *new != old).map(|new| ConfigChange {
+                field,
+                old: old.to_string(),
+                new: new.to_string(),
+            })
+        })
+        .collect(),
+        _ => Vec::new(),
+    }
+}
+
+fn targets(instruction: &MarinadeInstruction, state: &Pubkey) -> bool {
+    match instruction {
+        MarinadeInstruction::ConfigMarinade { accounts, .. } => accounts.marinade == *state,
+        MarinadeInstruction::ConfigLp { accounts, .. } => accounts.marinade == *state,
+        MarinadeInstruction::ChangeAuthority { accounts, .. } => accounts.marinade == *state,
+        _ => false,
+    }
+}
+
+/// Parameter changes of state in the slot range, from the successful transactions of history
+/// in the given (oldest first) order. snapshots are (slot, state at the end of the slot), in
+/// slot order; the ones before the range are used too. Use `..` for the whole history.
+pub fn param_history<R: RangeBounds<u64>>(
+    program: &MarinadeProgram,
+    history: &[ClassifiedTransaction],
+    state: &Pubkey,
+    snapshots: &[(u64, Marinade)],
+    range: R,
+) -> Vec<ParamChangeEntry> {
+    let mut snapshots = snapshots.iter().peekable();
+    let mut snapshot: Option<&Marinade> = None;
+    // fields changed since the snapshot
+    let mut changed: BTreeMap<&'static str, String> = BTreeMap::new();
+    let mut entries = Vec::new();
+    for transaction in history.iter().filter(|transaction| !transaction.failed) {
+        while let Some((_, newer)) = snapshots.next_if(|(slot, _)| *slot < transaction.slot) {
+            snapshot = Some(newer);
+            changed.clear();
+        }
+        let message = &transaction.transaction;
+        for compiled in message.all_instructions() {
+            let instruction = match decode_compiled(program, compiled, &message.account_keys) {
+                Ok(instruction) if targets(&instruction, state) => instruction,
+                _ => continue,
+            };
+            let fields = match set_fields(&instruction) {
+                Some(fields) => fields,
+                None => continue,
+            };
+            let differing = snapshot.map(|snapshot| snapshot_changes(snapshot, &instruction));
+            let changes = fields
+                .into_iter()
+                .map(|(field, new)| {
+                    let old = changed.get(field).cloned().or_else(|| {
+                        differing.as_ref().map(|differing| {
+                            differing
+                                .iter()
+                                .find(|change| change.field == field)
+                                .map_or_else(|| new.clone(), |change| change.old.clone())
+                        })
+                    });
+                    changed.insert(field, new.clone());
+                    ParamChange { field, old, new }
+                })
+                .collect();
+            if range.contains(&transaction.slot) {
+                entries.push(ParamChangeEntry {
+                    signature: transaction.signature,
+                    slot: transaction.slot,
+                    block_time: transaction.block_time,
+                    instruction: instruction.name(),
+                    changes,
+                });
+            }
+        }
+    }
+    entries
+}
@@ -0,0 +1,144 @@
+//! Every Marinade instruction of a transaction, top level and inner (CPI) ones,
+//! decoded with decode::decode_compiled. Inner instructions are only known from the
+//! transaction status meta, without it only the top level ones are found. Instructions are
+//! matched by the program id of the MarinadeProgram passed in.
+
+use derive_more::Display;
+use solana_program::{instruction::CompiledInstruction, pubkey::Pubkey};
+use solana_sdk::transaction::VersionedTransaction;
+use solana_transaction_status::{
+    EncodedTransactionWithStatusMeta, UiInnerInstructions, UiInstruction, UiLoadedAddresses,
+    UiTransactionStatusMeta,
+};
+
+use crate::{
+    decode::{decode_compiled, DecodeError, MarinadeInstruction},
+    program::MarinadeProgram,
+};
+
+#[derive(Clone, Debug, Display, PartialEq, Eq)]
+pub enum ParseError {
+    /// JSON encoded transactions carry no binary message, fetch them as base64
+    #[display(fmt = "Can not decode the transaction encoding")]
+    Encoding,
+    #[display(fmt = "Can not decode {}", _0)]
+    Decode(String),
+}
+
+impl std::error::Error for ParseError {}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ParsedMarinadeIx {
+    /// Position of the top level instruction, or of the one that invoked it
+    pub instruction_index: usize,
+    /// Position among the inner instructions of instruction_index, None at the top level
+    pub inner_index: Option<usize>,
+    /// Err for Marinade instructions the SDK has no type for, e.g. withdraw_stake_account
+    pub instruction: Result<MarinadeInstruction, DecodeError>,
+}
+
+fn parse_pubkey(pubkey: &str) -> Result<Pubkey, ParseError> {
+    pubkey
+        .parse()
+        .map_err(|_| ParseError::Decode(format!("pubkey {}", pubkey)))
+}
+
+/// Message keys followed by the addresses loaded from lookup tables, writable first
+fn account_keys(
+    transaction: &VersionedTransaction,
+    meta: Option<&UiTransactionStatusMeta>,
+) -> Result<Vec<Pubkey>, ParseError> {
+    let mut account_keys = transaction.message.static_account_keys().to_vec();
+    if let Some(loaded) =
+        meta.and_then(|meta| Option::<UiLoadedAddresses>::from(meta.loaded_addresses.clone()))
+    {
+        for address in loaded.writable.iter().chain(loaded.readonly.iter()) {
+            account_keys.push(parse_pubkey(address)?);
+        }
+    }
+    Ok(account_keys)
+}
+
+fn inner_instructions(
+    meta: Option<&UiTransactionStatusMeta>,
+) -> Result<Vec<(usize, Vec<CompiledInstruction>)>, ParseError> {
+    let inner = meta
+        .and_then(|meta| Option::<Vec<UiInnerInstructions>>::from(meta.inner_instructions.clone()))
+        .unwrap_or_default();
+    inner
+        .into_iter()
+        .map(|inner| {
+            let instructions = inner
+                .instructions
+                .into_iter()
+                .map(|instruction| match instruction {
+                    UiInstruction::Compiled(compiled) => Ok(CompiledInstruction {
+                        program_id_index: compiled.program_id_index,
+                        accounts: compiled.accounts,
+                        data: bs58::decode(&compiled.data).into_vec().map_err(|_| {
+                            ParseError::Decode(format!("instruction data {}", compiled.data))
+                        })?,
+                    }),
+                    UiInstruction::Parsed(_) => Err(ParseError::Encoding),
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            Ok((inner.index as usize, instructions))
+        })
+        .collect()
+}
+
+fn is_marinade(
+    program: &MarinadeProgram,
+    account_keys: &[Pubkey],
+    instruction: &CompiledInstruction,
+) -> bool {
+    account_keys.get(instruction.program_id_index as usize) == Some(&program.program_id)
+}
+
+/// Marinade instructions in execution order, each top level one followed by
+/// the Marinade instructions it invoked
+pub fn parse_transaction(
+    program: &MarinadeProgram,
+    transaction: &VersionedTransaction,
+    meta: Option<&UiTransactionStatusMeta>,
+) -> Result<Vec<ParsedMarinadeIx>, ParseError> {
+    let account_keys = account_keys(transaction, meta)?;
+    let inner = inner_instructions(meta)?;
+    let mut parsed = Vec::new();
+    for (instruction_index, instruction) in transaction.message.instructions().iter().enumerate() {
+        if is_marinade(program, &account_keys, instruction) {
+            parsed.push(ParsedMarinadeIx {
+                instruction_index,
+                inner_index: None,
+                instruction: decode_compiled(program, instruction, &account_keys),
+            });
+        }
+        for (_, instructions) in inner
+            .iter()
+            .filter(|(index, _)| *index == instruction_index)
+        {
+            for (inner_index, instruction) in instructions.iter().enumerate() {
+                if is_marinade(program, &account_keys, instruction) {
+                    parsed.push(ParsedMarinadeIx {
+                        instruction_index,
+                        inner_index: Some(inner_index),
+                        instruction: decode_compiled(program, instruction, &account_keys),
+                    });
+                }
+            }
+        }
+    }
+    Ok(parsed)
+}
+
+/// parse_transaction of a transaction fetched with the base58 or base64 encoding
+pub fn parse_encoded_transaction(
+    program: &MarinadeProgram,
+    transaction: &EncodedTransactionWithStatusMeta,
+) -> Result<Vec<ParsedMarinadeIx>, ParseError> {
+    let decoded = transaction
+        .transaction
+        .decode()
+        .ok_or(ParseError::Encoding)?;
+    parse_transaction(program, &decoded, transaction.meta.as_ref())
+}
@@ -0,0 +1,47 @@
+//! Memoized PDA derivations for builders touching many validators in one transaction.
+//! find_program_address costs up to 255 hashes, so every address is derived at most once.
+
+use std::collections::HashMap;
+
+use solana_program::pubkey::Pubkey;
+
+use crate::state::{
+    stake_system::{StakeAccountPdas, StakeSystem},
+    validator_system::{ValidatorPdas, ValidatorRecord},
+};
+
+#[derive(Clone, Debug)]
+pub struct PdaCache {
+    state: Pubkey,
+    stake_accounts: Option<StakeAccountPdas>,
+    validators: HashMap<Pubkey, ValidatorPdas>,
+}
+
+impl PdaCache {
+    pub fn new(state: Pubkey) -> Self {
+        Self {
+            state,
+            stake_accounts: None,
+            validators: HashMap::new(),
+        }
+    }
+
+    pub fn state(&self) -> &Pubkey {
+        &self.state
+    }
+
+    pub fn validator(&mut self, validator_vote: &Pubkey) -> ValidatorPdas {
+        let state = self.state;
+        *self
+            .validators
+            .entry(*validator_vote)
+            .or_insert_with(|| ValidatorRecord::derive_all(&state, validator_vote))
+    }
+
+    pub fn stake_accounts(&mut self) -> StakeAccountPdas {
+        let state = self.state;
+        *self
+            .stake_accounts
+            .get_or_insert_with(|| StakeSystem::derive_all(&state))
+    }
+}
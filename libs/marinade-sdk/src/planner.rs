@@ -0,0 +1,495 @@
+//! Off-chain planning of validator-manager operations.
+//! Plans are projections: indexes in the first epoch match the lists passed in, later epochs
+//! must be re-planned from fresh lists because removals shift stake list indexes.
+
+use solana_program::pubkey::Pubkey;
+
+use crate::state::{
+    marinade::StakeDelta,
+    stake_system::{StakeDeltaLimits, StakeRecord},
+    validator_system::ValidatorRecord,
+};
+
+/// Stake list record with the vote account it is delegated to (read from the stake account)
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct DelegatedStake {
+    pub stake_index: u32,
+    pub record: StakeRecord,
+    pub validator_vote: Pubkey,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WindDownAction {
+    /// emergency_unstake of the whole stake account
+    EmergencyUnstake {
+        stake_index: u32,
+        validator_index: u32,
+        lamports: u64,
+    },
+    /// partial_unstake splitting lamports off the stake account
+    PartialUnstake {
+        stake_index: u32,
+        validator_index: u32,
+        lamports: u64,
+    },
+}
+
+impl WindDownAction {
+    pub fn lamports(&self) -> u64 {
+        match self {
+            WindDownAction::EmergencyUnstake { lamports, .. } => *lamports,
+            WindDownAction::PartialUnstake { lamports, .. } => *lamports,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct EpochPlan {
+    /// 0 for the current epoch
+    pub epoch_offset: u64,
+    pub actions: Vec<WindDownAction>,
+}
+
+impl EpochPlan {
+    pub fn total_lamports(&self) -> u64 {
+        self.actions.iter().map(|action| action.lamports()).sum()
+    }
+}
+
+/// Validators with score 0 that still hold active stake, i.e. removed or disabled mid-epoch
+pub fn validators_to_wind_down(validators: &[ValidatorRecord]) -> Vec<(u32, &ValidatorRecord)> {
+    validators
+        .iter()
+        .enumerate()
+        .filter(|(_, validator)| validator.score == 0 && validator.active_balance > 0)
+        .map(|(index, validator)| (index as u32, validator))
+        .collect()
+}
+
+/// Unstakes everything delegated to zero score validators, moving at most
+/// max_lamports_per_epoch per epoch and touching every stake account at most once per epoch.
+/// Whole accounts are emergency-unstaked when they fit the remaining epoch budget, otherwise
+/// as much of the budget as possible is split off by partial_unstake keeping both parts
+/// at least min_stake.
+/// Stakes already emergency unstaking are skipped.
+pub fn plan_wind_down(
+    validators: &[ValidatorRecord],
+    stakes: &[DelegatedStake],
+    min_stake: u64,
+    max_lamports_per_epoch: u64,
+) -> Vec<EpochPlan> {
+    // (stake_index, validator_index, remaining lamports)
+    let mut remaining: Vec<(u32, u32, u64)> = Vec::new();
+    for (validator_index, validator) in validators_to_wind_down(validators) {
+        for stake in stakes.iter().filter(|stake| {
+            stake.validator_vote == validator.validator_account
+                && !stake.record.is_emergency_unstaking()
+                && stake.record.last_update_delegated_lamports > 0
+        }) {
+            remaining.push((
+                stake.stake_index,
+                validator_index,
+                stake.record.last_update_delegated_lamports,
+            ));
+        }
+    }
+    // largest first to finish in the least number of epochs
+    remaining.sort_by(|a, b| b.2.cmp(&a.2));
+
+    let mut plans = Vec::new();
+    let mut epoch_offset = 0;
+    while !remaining.is_empty() {
+        let mut budget = max_lamports_per_epoch;
+        let mut plan = EpochPlan {
+            epoch_offset,
+            actions: Vec::new(),
+        };
+        for (stake_index, validator_index, lamports) in remaining.iter_mut() {
+            if *lamports <= budget {
+                plan.actions.push(WindDownAction::EmergencyUnstake {
+                    stake_index: *stake_index,
+                    validator_index: *validator_index,
+                    lamports: *lamports,
+                });
+                budget -= *lamports;
+                *lamports = 0;
+            } else {
+                // both the split and the rest must stay delegable
+                let amount = budget.min(lamports.saturating_sub(min_stake));
+                if amount > 0 && amount >= min_stake {
+                    plan.actions.push(WindDownAction::PartialUnstake {
+                        stake_index: *stake_index,
+                        validator_index: *validator_index,
+                        lamports: amount,
+                    });
+                    *lamports -= amount;
+                    budget -= amount;
+                }
+            }
+        }
+        if plan.actions.is_empty() {
+            // budget too small to move any remaining stake, it is left out of the plan
+            break;
+        }
+        remaining.retain(|(_, _, lamports)| *lamports > 0);
+        plans.push(plan);
+        epoch_offset += 1;
+    }
+    plans
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StakeDeltaAction {
+    /// stake_reserve into a new stake account of the validator
+    Stake { validator_index: u32, lamports: u64 },
+    /// deactivate_stake of (part of) the stake account
+    Deactivate {
+        stake_index: u32,
+        validator_index: u32,
+        lamports: u64,
+    },
+}
+
+impl StakeDeltaAction {
+    pub fn validator_index(&self) -> u32 {
+        match self {
+            StakeDeltaAction::Stake {
+                validator_index, ..
+            } => *validator_index,
+            StakeDeltaAction::Deactivate {
+                validator_index, ..
+            } => *validator_index,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum UpdateAction {
+    /// update_active of a delegated stake account
+    Active {
+        stake_index: u32,
+        validator_index: u32,
+    },
+    /// update_deactivated of a fully deactivated stake account, removing it from the stake list
+    Deactivated { stake_index: u32 },
+}
+
+impl UpdateAction {
+    pub fn stake_index(&self) -> u32 {
+        match self {
+            UpdateAction::Active { stake_index, .. } => *stake_index,
+            UpdateAction::Deactivated { stake_index } => *stake_index,
+        }
+    }
+}
+
+/// Updates of the stakes not updated in epoch yet; is_deactivated tells the fully deactivated
+/// stake accounts (read from the stake account) apart.
+/// update_deactivated moves the last stake list record into the removed one, so the
+/// deactivated stakes come after the active ones, highest index first, which keeps the
+/// indexes of the plan valid. Stakes delegated to a vote account missing from the validator
+/// list can't be update_active'd and are left out.
+pub fn plan_updates<F: Fn(&DelegatedStake) -> bool>(
+    validators: &[ValidatorRecord],
+    stakes: &[DelegatedStake],
+    epoch: u64,
+    is_deactivated: F,
+) -> Vec<UpdateAction> {
+    let overdue = stakes
+        .iter()
+        .filter(|stake| stake.record.last_update_epoch < epoch);
+    let mut active = Vec::new();
+    let mut deactivated = Vec::new();
+    for stake in overdue {
+        if is_deactivated(stake) {
+            deactivated.push(UpdateAction::Deactivated {
+                stake_index: stake.stake_index,
+            });
+        } else if let Some(validator_index) = validators
+            .iter()
+            .position(|validator| validator.validator_account == stake.validator_vote)
+        {
+            active.push(UpdateAction::Active {
+                stake_index: stake.stake_index,
+                validator_index: validator_index as u32,
+            });
+        }
+    }
+    deactivated.sort_by_key(|action| std::cmp::Reverse(action.stake_index()));
+    active.extend(deactivated);
+    active
+}
+
+/// Position in the epoch the plan is going to be executed at
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct EpochPosition {
+    pub epoch: u64,
+    pub slot_index: u64,
+    pub slots_in_epoch: u64,
+}
+
+/// Keeps only what the program accepts in the current stake-delta run:
+/// nothing outside of the stake-delta window, actions in the direction of stake_delta only,
+/// one action per validator and none for validators already processed this epoch
+/// beyond the extra runs granted (each repeat uses one), total clipped to the stake_delta amount
+/// and no stake_reserve below min_stake.
+pub fn clip_stake_delta_plan(
+    actions: &[StakeDeltaAction],
+    validators: &[ValidatorRecord],
+    limits: &StakeDeltaLimits,
+    stake_delta: StakeDelta,
+    position: &EpochPosition,
+) -> Vec<StakeDeltaAction> {
+    if !limits.is_window_open(position.slot_index, position.slots_in_epoch) {
+        return Vec::new();
+    }
+    let mut budget = match stake_delta {
+        StakeDelta::Stake(lamports) | StakeDelta::Unstake(lamports) => lamports,
+        StakeDelta::Neutral => return Vec::new(),
+    };
+    let mut extra_runs = limits.extra_stake_delta_runs;
+    let mut used_validators: Vec<u32> = Vec::new();
+    let mut clipped = Vec::new();
+    for action in actions {
+        let validator_index = action.validator_index();
+        let validator = match validators.get(validator_index as usize) {
+            Some(validator) => validator,
+            None => continue,
+        };
+        let repeat = validator.last_stake_delta_epoch == position.epoch;
+        if used_validators.contains(&validator_index) || (repeat && extra_runs == 0) {
+            continue;
+        }
+        let action = match (*action, stake_delta) {
+            (StakeDeltaAction::Stake { lamports, .. }, StakeDelta::Stake(_)) => {
+                let lamports = lamports.min(budget);
+                if lamports < limits.min_stake {
+                    continue;
+                }
+                StakeDeltaAction::Stake {
+                    validator_index,
+                    lamports,
+                }
+            }
+            (
+                StakeDeltaAction::Deactivate {
+                    stake_index,
+                    lamports,
+                    ..
+                },
+                StakeDelta::Unstake(_),
+            ) => {
+                let lamports = lamports.min(budget);
+                if lamports == 0 {
+                    continue;
+                }
+                StakeDeltaAction::Deactivate {
+                    stake_index,
+                    validator_index,
+                    lamports,
+                }
+            }
+            _ => continue,
+        };
+        budget -= match action {
+            StakeDeltaAction::Stake { lamports, .. } => lamports,
+            StakeDeltaAction::Deactivate { lamports, .. } => lamports,
+        };
+        if repeat {
+            extra_runs -= 1;
+        }
+        used_validators.push(validator_index);
+        clipped.push(action);
+    }
+    clipped
+}
+
+/// stake_reserve of a stake-delta run that stakes: into the validator furthest below its
+/// score based share of the active balance after the stake, among the ones
+/// clip_stake_delta_plan lets through. None outside of the stake-delta window, when
+/// stake_delta is not Stake or when no scored validator can take it
+pub fn next_stake_reserve(
+    validators: &[ValidatorRecord],
+    limits: &StakeDeltaLimits,
+    stake_delta: StakeDelta,
+    position: &EpochPosition,
+) -> Option<StakeDeltaAction> {
+    let lamports = match stake_delta {
+        StakeDelta::Stake(lamports) => lamports,
+        _ => return None,
+    };
+    let total_score: u128 = validators
+        .iter()
+        .map(|validator| validator.score as u128)
+        .sum();
+    if total_score == 0 {
+        return None;
+    }
+    let total_target = validators
+        .iter()
+        .map(|validator| validator.active_balance as u128)
+        .sum::<u128>()
+        + lamports as u128;
+    let mut candidates: Vec<(i128, StakeDeltaAction)> = validators
+        .iter()
+        .enumerate()
+        .filter(|(_, validator)| validator.score > 0)
+        .map(|(index, validator)| {
+            let target = total_target * validator.score as u128 / total_score;
+            (
+                target as i128 - validator.active_balance as i128,
+                StakeDeltaAction::Stake {
+                    validator_index: index as u32,
+                    lamports,
+                },
+            )
+        })
+        .collect();
+    candidates.sort_by_key(|(gap, _)| std::cmp::Reverse(*gap));
+    let actions: Vec<StakeDeltaAction> = candidates.into_iter().map(|(_, action)| action).collect();
+    clip_stake_delta_plan(&actions, validators, limits, stake_delta, position)
+        .first()
+        .copied()
+}
+
+/// Limits of the rebalance simulation per epoch
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RebalanceLimits {
+    /// Most lamports deactivated from over-target validators per epoch
+    pub max_unstake_per_epoch: u64,
+    /// stake_reserve is not run for less than min_stake
+    pub min_stake: u64,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct StakeMovement {
+    pub validator_index: u32,
+    pub validator_account: Pubkey,
+    pub staked: u64,
+    pub unstaked: u64,
+    /// Active balance at the end of the epoch
+    pub balance_after: u64,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct RebalanceEpoch {
+    /// 0 for the current epoch
+    pub epoch_offset: u64,
+    pub movements: Vec<StakeMovement>,
+    /// Deactivated lamports returning to the reserve at the start of the next epoch
+    pub cooling_down: u64,
+    /// Sum of the distances above target of all validators after the epoch
+    pub remaining_excess: u64,
+}
+
+/// Projects how stake moves towards the targets of new_scores (vote account, score) over
+/// at most `epochs` epochs. Validators missing from new_scores keep their score.
+/// Deactivated lamports can only be staked again in the following epoch, once they are
+/// back in the reserve. Each validator is staked or unstaked at most once per epoch.
+/// Stops early when the targets are reached.
+pub fn simulate_rebalance(
+    current_list: &[ValidatorRecord],
+    new_scores: &[(Pubkey, u32)],
+    epochs: u64,
+    limits: &RebalanceLimits,
+) -> Vec<RebalanceEpoch> {
+    let scores: Vec<u64> = current_list
+        .iter()
+        .map(|validator| {
+            new_scores
+                .iter()
+                .find(|(vote, _)| *vote == validator.validator_account)
+                .map_or(validator.score, |(_, score)| *score) as u64
+        })
+        .collect();
+    let total_score: u64 = scores.iter().sum();
+    let mut balances: Vec<u64> = current_list.iter().map(|v| v.active_balance).collect();
+    let total: u64 = balances.iter().sum();
+    let targets: Vec<u64> = scores
+        .iter()
+        .map(|score| {
+            if total_score == 0 {
+                0
+            } else {
+                (total as u128 * *score as u128 / total_score as u128) as u64
+            }
+        })
+        .collect();
+
+    let mut schedule = Vec::new();
+    let mut reserve: u64 = 0;
+    let mut cooling_down: u64 = 0;
+    for epoch_offset in 0..epochs {
+        reserve += cooling_down;
+        let mut movements: Vec<StakeMovement> = Vec::new();
+
+        // unstake the largest excesses first
+        let mut over: Vec<(usize, u64)> = balances
+            .iter()
+            .zip(&targets)
+            .enumerate()
+            .filter(|(_, (balance, target))| balance > target)
+            .map(|(index, (balance, target))| (index, balance - target))
+            .collect();
+        over.sort_by(|a, b| b.1.cmp(&a.1));
+        let mut budget = limits.max_unstake_per_epoch;
+        cooling_down = 0;
+        for (index, excess) in over {
+            let amount = excess.min(budget);
+            if amount == 0 {
+                break;
+            }
+            balances[index] -= amount;
+            budget -= amount;
+            cooling_down += amount;
+            movements.push(StakeMovement {
+                validator_index: index as u32,
+                validator_account: current_list[index].validator_account,
+                staked: 0,
+                unstaked: amount,
+                balance_after: balances[index],
+            });
+        }
+
+        // stake the reserve into the largest deficits
+        let mut under: Vec<(usize, u64)> = balances
+            .iter()
+            .zip(&targets)
+            .enumerate()
+            .filter(|(_, (balance, target))| balance < target)
+            .map(|(index, (balance, target))| (index, target - balance))
+            .collect();
+        under.sort_by(|a, b| b.1.cmp(&a.1));
+        for (index, deficit) in under {
+            let amount = deficit.min(reserve);
+            if amount < limits.min_stake || amount == 0 {
+                continue;
+            }
+            balances[index] += amount;
+            reserve -= amount;
+            movements.push(StakeMovement {
+                validator_index: index as u32,
+                validator_account: current_list[index].validator_account,
+                staked: amount,
+                unstaked: 0,
+                balance_after: balances[index],
+            });
+        }
+
+        if movements.is_empty() && cooling_down == 0 {
+            break;
+        }
+        movements.sort_by_key(|movement| movement.validator_index);
+        schedule.push(RebalanceEpoch {
+            epoch_offset,
+            movements,
+            cooling_down,
+            remaining_excess: balances
+                .iter()
+                .zip(&targets)
+                .map(|(balance, target)| balance.saturating_sub(*target))
+                .sum(),
+        });
+    }
+    schedule
+}
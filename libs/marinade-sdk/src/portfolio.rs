@@ -0,0 +1,95 @@
+//! Aggregation of mSOL and ticket holdings over several wallets
+
+use solana_program::pubkey::Pubkey;
+
+use crate::{
+    error::CommonError,
+    state::{delayed_unstake_ticket::DelayedUnstakeTicket, marinade::Marinade},
+};
+
+/// Raw holdings of one wallet as loaded by the caller
+#[derive(Debug)]
+pub struct WalletHoldings {
+    pub wallet: Pubkey,
+    /// Sum of all mSOL token accounts of the wallet
+    pub msol_amount: u64,
+    /// (ticket account address, ticket)
+    pub tickets: Vec<(Pubkey, DelayedUnstakeTicket)>,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Position {
+    pub msol_amount: u64,
+    /// msol_amount valued at the current mSOL price
+    pub msol_value: u64,
+    pub ticket_count: u64,
+    pub ticket_lamports: u64,
+    /// msol_value + ticket_lamports
+    pub total_value: u64,
+}
+
+impl Position {
+    fn add(&mut self, other: &Position) -> Result<(), CommonError> {
+        let add = |a: u64, b: u64| a.checked_add(b).ok_or(CommonError::CalculationFailure);
+        self.msol_amount = add(self.msol_amount, other.msol_amount)?;
+        self.msol_value = add(self.msol_value, other.msol_value)?;
+        self.ticket_count = add(self.ticket_count, other.ticket_count)?;
+        self.ticket_lamports = add(self.ticket_lamports, other.ticket_lamports)?;
+        self.total_value = add(self.total_value, other.total_value)?;
+        Ok(())
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TicketMaturity {
+    pub wallet: Pubkey,
+    pub ticket: Pubkey,
+    pub lamports_amount: u64,
+    /// First epoch the ticket can be claimed in
+    pub due_epoch: u64,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Portfolio {
+    pub wallets: Vec<(Pubkey, Position)>,
+    pub total: Position,
+    /// Tickets of all wallets ordered by due_epoch
+    pub maturity_schedule: Vec<TicketMaturity>,
+}
+
+pub fn aggregate(
+    holdings: &[WalletHoldings],
+    marinade: &Marinade,
+) -> Result<Portfolio, CommonError> {
+    let mut portfolio = Portfolio::default();
+    for holding in holdings {
+        let mut ticket_lamports: u64 = 0;
+        for (address, ticket) in &holding.tickets {
+            ticket_lamports = ticket_lamports
+                .checked_add(ticket.lamports_amount)
+                .ok_or(CommonError::CalculationFailure)?;
+            portfolio.maturity_schedule.push(TicketMaturity {
+                wallet: holding.wallet,
+                ticket: *address,
+                lamports_amount: ticket.lamports_amount,
+                due_epoch: ticket.due_epoch(),
+            });
+        }
+        let msol_value = marinade.calc_lamports_from_msol_amount(holding.msol_amount)?;
+        let position = Position {
+            msol_amount: holding.msol_amount,
+            msol_value,
+            ticket_count: holding.tickets.len() as u64,
+            ticket_lamports,
+            total_value: msol_value
+                .checked_add(ticket_lamports)
+                .ok_or(CommonError::CalculationFailure)?,
+        };
+        portfolio.total.add(&position)?;
+        portfolio.wallets.push((holding.wallet, position));
+    }
+    portfolio
+        .maturity_schedule
+        .sort_by_key(|maturity| maturity.due_epoch);
+    Ok(portfolio)
+}
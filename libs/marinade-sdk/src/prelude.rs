@@ -0,0 +1,37 @@
+//! Everything a typical integration needs: `use marinade_sdk::prelude::*;`
+
+pub use micro_anchor::{
+    AccountDeserialize, AccountDeserializeError, Discriminator, InstructionBuilder,
+    InstructionData, Owner, ToAccountMetas,
+};
+
+pub use crate::{
+    builders::{
+        AddLiquidityBuilder, BuildError, DepositBuilder, LiquidUnstakeBuilder, OrderUnstakeBuilder,
+        RemoveLiquidityBuilder,
+    },
+    calc::{proportional, shares_from_value, value_from_shares},
+    error::CommonError,
+    instructions::{
+        add_liquidity::AddLiquidityData, config_lp::ConfigLpData,
+        config_marinade::ConfigMarinadeData, deposit::DepositData,
+        deposit_stake_account::DepositStakeAccountData, liquid_unstake::LiquidUnstakeData,
+        order_unstake::OrderUnstakeData, remove_liquidity::RemoveLiquidityData,
+    },
+    located::{KeyedAccount, Located},
+    program::{MarinadeProgram, ProgramLocated},
+    quote::{
+        max_liquid_unstake, quote_deposit, quote_deposit_with_liq_pool, quote_liquid_unstake,
+        quote_max_liquid_unstake, quote_order_unstake, DepositQuote, LiqPoolLegs,
+        LiquidUnstakeTranche, MaxLiquidUnstake, OrderUnstakeQuote, Quote,
+    },
+    state::{
+        delayed_unstake_ticket::DelayedUnstakeTicket,
+        fee::{Fee, FeeCents},
+        liq_pool::{LiqPool, LiqPoolHelpers},
+        marinade::{Marinade, MarinadeExtension, MarinadeHelpers, StakeDelta, StakingCap},
+        stake_system::{StakeRecord, StakeSystem, StakeSystemHelpers},
+        validator_system::{ValidatorRecord, ValidatorSystem},
+    },
+    ID,
+};
@@ -0,0 +1,139 @@
+//! Deployments of the program at another id than crate::ID, e.g. devnet forks or local
+//! validators. MarinadeProgram derives the PDAs of such a deployment, and wrapping the state
+//! with MarinadeProgram::locate makes every helper builder target it.
+//! The static finders (Marinade::find_reserve_address, ...) keep deriving for crate::ID.
+
+use solana_program::pubkey::{Pubkey, PubkeyError};
+
+use crate::{
+    located::Located,
+    state::{
+        liq_pool::LiqPool, marinade::Marinade, stake_system::StakeSystem,
+        validator_system::ValidatorRecord,
+    },
+    ID,
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct MarinadeProgram {
+    pub program_id: Pubkey,
+}
+
+impl Default for MarinadeProgram {
+    fn default() -> Self {
+        Self::new(ID)
+    }
+}
+
+impl MarinadeProgram {
+    pub fn new(program_id: Pubkey) -> Self {
+        Self { program_id }
+    }
+
+    fn find(&self, state: &Pubkey, seed: &[u8]) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[&state.to_bytes()[..32], seed], &self.program_id)
+    }
+
+    pub fn find_msol_mint_authority(&self, state: &Pubkey) -> (Pubkey, u8) {
+        self.find(state, Marinade::MSOL_MINT_AUTHORITY_SEED)
+    }
+
+    pub fn find_reserve_address(&self, state: &Pubkey) -> (Pubkey, u8) {
+        self.find(state, Marinade::RESERVE_SEED)
+    }
+
+    pub fn default_stake_list_address(&self, state: &Pubkey) -> Pubkey {
+        Pubkey::create_with_seed(state, Marinade::STAKE_LIST_SEED, &self.program_id).unwrap()
+    }
+
+    pub fn default_validator_list_address(&self, state: &Pubkey) -> Pubkey {
+        Pubkey::create_with_seed(state, Marinade::VALIDATOR_LIST_SEED, &self.program_id).unwrap()
+    }
+
+    pub fn find_lp_mint_authority(&self, state: &Pubkey) -> (Pubkey, u8) {
+        self.find(state, LiqPool::LP_MINT_AUTHORITY_SEED)
+    }
+
+    pub fn find_liq_pool_sol_leg_address(&self, state: &Pubkey) -> (Pubkey, u8) {
+        self.find(state, LiqPool::SOL_LEG_SEED)
+    }
+
+    pub fn find_liq_pool_msol_leg_authority(&self, state: &Pubkey) -> (Pubkey, u8) {
+        self.find(state, LiqPool::MSOL_LEG_AUTHORITY_SEED)
+    }
+
+    pub fn find_stake_withdraw_authority(&self, state: &Pubkey) -> (Pubkey, u8) {
+        self.find(state, StakeSystem::STAKE_WITHDRAW_SEED)
+    }
+
+    pub fn find_stake_deposit_authority(&self, state: &Pubkey) -> (Pubkey, u8) {
+        self.find(state, StakeSystem::STAKE_DEPOSIT_SEED)
+    }
+
+    pub fn find_duplication_flag(
+        &self,
+        state: &Pubkey,
+        validator_account: &Pubkey,
+    ) -> (Pubkey, u8) {
+        Pubkey::find_program_address(
+            &[
+                &state.to_bytes()[..32],
+                ValidatorRecord::DUPLICATE_FLAG_SEED,
+                &validator_account.to_bytes()[..32],
+            ],
+            &self.program_id,
+        )
+    }
+
+    /// Duplication flag of a validator record from its stored bump seed
+    pub fn duplication_flag_address(
+        &self,
+        record: &ValidatorRecord,
+        state: &Pubkey,
+    ) -> Result<Pubkey, PubkeyError> {
+        record.with_duplication_flag_seeds(state, |seeds| {
+            Pubkey::create_program_address(seeds, &self.program_id)
+        })
+    }
+
+    pub fn ticket_address_with_seed(
+        &self,
+        base: &Pubkey,
+        seed: &str,
+    ) -> Result<Pubkey, PubkeyError> {
+        Pubkey::create_with_seed(base, seed, &self.program_id)
+    }
+
+    /// The state of this deployment, its helper builders target program_id
+    pub fn locate<L>(self, located: L) -> ProgramLocated<L> {
+        ProgramLocated {
+            program: self,
+            located,
+        }
+    }
+}
+
+/// Located account of a MarinadeProgram deployment, see MarinadeProgram::locate
+#[derive(Clone, Debug)]
+pub struct ProgramLocated<L> {
+    pub program: MarinadeProgram,
+    pub located: L,
+}
+
+impl<T, L: Located<T>> Located<T> for ProgramLocated<L> {
+    fn as_ref(&self) -> &T {
+        self.located.as_ref()
+    }
+
+    fn as_mut(&mut self) -> &mut T {
+        self.located.as_mut()
+    }
+
+    fn key(&self) -> Pubkey {
+        self.located.key()
+    }
+
+    fn program_id(&self) -> Pubkey {
+        self.program.program_id
+    }
+}
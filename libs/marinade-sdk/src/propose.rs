@@ -0,0 +1,263 @@
+//! Admin config changes rendered for review and packaged for the chosen admin path:
+//! a transaction signed directly by the admin, an SPL governance proposal or a Squads multisig.
+//! Also filters admin batches down to the instructions not applied on chain yet.
+
+use std::fmt::Display;
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use micro_anchor::Discriminator;
+use solana_program::{instruction::Instruction, message::Message, pubkey::Pubkey};
+
+use crate::{
+    instructions::{
+        add_validator::AddValidatorData, change_authority::ChangeAuthorityData,
+        config_lp::ConfigLpData, config_marinade::ConfigMarinadeData,
+        remove_validator::RemoveValidatorData, set_validator_score::SetValidatorScoreData,
+        SerializedInstruction,
+    },
+    located::Located,
+    state::{
+        marinade::{Marinade, MarinadeHelpers, StakingCap},
+        validator_system::ValidatorRecord,
+    },
+    ID,
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ProposalTarget {
+    /// Serialized legacy message to be signed by the admin authority and fee_payer
+    Transaction { fee_payer: Pubkey },
+    /// Borsh InstructionData for spl-governance InsertTransaction
+    Governance,
+    /// Borsh IncomingInstruction for squads-mpl AddInstruction
+    Squads,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ConfigChange {
+    pub field: &'static str,
+    pub old: String,
+    pub new: String,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Proposal {
+    pub changes: Vec<ConfigChange>,
+    pub instruction: Instruction,
+    pub target: ProposalTarget,
+    pub payload: Vec<u8>,
+}
+
+fn push_change<T: PartialEq + Display>(
+    changes: &mut Vec<ConfigChange>,
+    field: &'static str,
+    old: T,
+    new: Option<T>,
+) {
+    if let Some(new) = new {
+        if new != old {
+            changes.push(ConfigChange {
+                field,
+                old: old.to_string(),
+                new: new.to_string(),
+            });
+        }
+    }
+}
+
+/// Fields of config that differ from the current state
+pub fn config_changes(state: &Marinade, config: &ConfigMarinadeData) -> Vec<ConfigChange> {
+    let mut changes = Vec::new();
+    push_change(
+        &mut changes,
+        "rewards_fee",
+        state.reward_fee,
+        config.rewards_fee,
+    );
+    push_change(
+        &mut changes,
+        "slots_for_stake_delta",
+        state.stake_system.slots_for_stake_delta,
+        config.slots_for_stake_delta,
+    );
+    push_change(
+        &mut changes,
+        "min_stake",
+        state.stake_system.min_stake,
+        config.min_stake,
+    );
+    push_change(
+        &mut changes,
+        "min_deposit",
+        state.min_deposit,
+        config.min_deposit,
+    );
+    push_change(
+        &mut changes,
+        "min_withdraw",
+        state.min_withdraw,
+        config.min_withdraw,
+    );
+    push_change(
+        &mut changes,
+        "staking_sol_cap",
+        state.staking_cap(),
+        config.staking_sol_cap.map(StakingCap::from_raw),
+    );
+    push_change(
+        &mut changes,
+        "liquidity_sol_cap",
+        state.liq_pool.liquidity_sol_cap,
+        config.liquidity_sol_cap,
+    );
+    push_change(
+        &mut changes,
+        "auto_add_validator_enabled",
+        state.validator_system.is_auto_add_validator_enabled(),
+        config.auto_add_validator_enabled,
+    );
+    changes
+}
+
+/// Fields of an LP config that differ from the current state
+pub fn lp_config_changes(state: &Marinade, config: &ConfigLpData) -> Vec<ConfigChange> {
+    let liq_pool = &state.liq_pool;
+    let mut changes = Vec::new();
+    push_change(&mut changes, "min_fee", liq_pool.lp_min_fee, config.min_fee);
+    push_change(&mut changes, "max_fee", liq_pool.lp_max_fee, config.max_fee);
+    push_change(
+        &mut changes,
+        "liquidity_target",
+        liq_pool.lp_liquidity_target,
+        config.liquidity_target,
+    );
+    push_change(
+        &mut changes,
+        "treasury_cut",
+        liq_pool.treasury_cut,
+        config.treasury_cut,
+    );
+    changes
+}
+
+impl Proposal {
+    /// Packages the instruction for target
+    pub fn new(
+        changes: Vec<ConfigChange>,
+        instruction: Instruction,
+        target: ProposalTarget,
+    ) -> Self {
+        let payload = match target {
+            ProposalTarget::Transaction { fee_payer } => {
+                Message::new(&[instruction.clone()], Some(&fee_payer)).serialize()
+            }
+            ProposalTarget::Governance | ProposalTarget::Squads => {
+                SerializedInstruction::from(&instruction)
+                    .try_to_vec()
+                    .unwrap()
+            }
+        };
+        Self {
+            changes,
+            instruction,
+            target,
+            payload,
+        }
+    }
+
+    /// Human readable list of the changes, one per line
+    pub fn summary(&self) -> String {
+        if self.changes.is_empty() {
+            return "No change".to_string();
+        }
+        self.changes
+            .iter()
+            .map(|change| format!("{}: {} -> {}", change.field, change.old, change.new))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+/// Builds the config_marinade instruction for the config diff and packages it for target
+pub fn propose<T: Located<Marinade>>(
+    state: &T,
+    config: ConfigMarinadeData,
+    target: ProposalTarget,
+) -> Proposal {
+    let changes = config_changes(state.as_ref(), &config);
+    Proposal::new(changes, state.config_marinade_instruction(config), target)
+}
+
+/// Current on-chain values an admin batch is compared with
+#[derive(Clone, Copy, Debug)]
+pub struct AdminStateView<'a> {
+    pub state: &'a Marinade,
+    pub validators: &'a [ValidatorRecord],
+}
+
+fn decode<D: Discriminator + BorshDeserialize>(instruction: &Instruction) -> Option<D> {
+    if instruction.data.len() < 8 || instruction.data[..8] != D::DISCRIMINATOR {
+        return None;
+    }
+    D::try_from_slice(&instruction.data[8..]).ok()
+}
+
+fn is_set<T: PartialEq>(new: Option<T>, current: T) -> bool {
+    new.map_or(true, |new| new == current)
+}
+
+impl<'a> AdminStateView<'a> {
+    fn validator(&self, vote: &Pubkey) -> Option<&ValidatorRecord> {
+        self.validators
+            .iter()
+            .find(|validator| validator.validator_account == *vote)
+    }
+
+    /// Whether executing the instruction would not change anything.
+    /// Unknown instructions are never considered applied.
+    pub fn is_applied(&self, instruction: &Instruction) -> bool {
+        if instruction.program_id != ID {
+            return false;
+        }
+        let state = self.state;
+        if let Some(config) = decode::<ConfigMarinadeData>(instruction) {
+            config_changes(state, &config).is_empty()
+        } else if let Some(config) = decode::<ConfigLpData>(instruction) {
+            lp_config_changes(state, &config).is_empty()
+        } else if let Some(change) = decode::<ChangeAuthorityData>(instruction) {
+            is_set(change.admin, state.admin_authority)
+                && is_set(
+                    change.validator_manager,
+                    state.validator_system.manager_authority,
+                )
+                && is_set(
+                    change.operational_sol_account,
+                    state.operational_sol_account,
+                )
+                && is_set(change.treasury_msol_account, state.treasury_msol_account)
+        } else if decode::<AddValidatorData>(instruction).is_some() {
+            // validator_vote is the 4th account of add_validator
+            instruction
+                .accounts
+                .get(3)
+                .map_or(false, |meta| self.validator(&meta.pubkey).is_some())
+        } else if let Some(remove) = decode::<RemoveValidatorData>(instruction) {
+            self.validator(&remove.validator_vote).is_none()
+        } else if let Some(set_score) = decode::<SetValidatorScoreData>(instruction) {
+            self.validator(&set_score.validator_vote)
+                .map_or(false, |validator| validator.score == set_score.score)
+        } else {
+            false
+        }
+    }
+}
+
+/// Drops the instructions of an admin batch whose effect is already on chain,
+/// e.g. to re-submit the rest of a partially executed governance proposal
+pub fn filter_already_applied(batch: &[Instruction], current: &AdminStateView) -> Vec<Instruction> {
+    batch
+        .iter()
+        .filter(|instruction| !current.is_applied(instruction))
+        .cloned()
+        .collect()
+}
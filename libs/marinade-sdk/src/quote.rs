@@ -0,0 +1,333 @@
+//! Quotes tagged with the slot and the state they were computed from,
+//! so UIs can refuse to execute quotes computed minutes ago.
+
+use borsh::BorshSerialize;
+use derive_more::Display;
+use solana_program::{
+    hash::{hash, Hash},
+    program_error::ProgramError,
+    pubkey::Pubkey,
+};
+
+use crate::{
+    calc::{proportional, RoundingPolicy, ON_CHAIN_ROUNDING},
+    error::{CommonError, Mismatch},
+    located::Located,
+    state::{
+        fee::{Fee, FeeCents},
+        marinade::Marinade,
+    },
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct LiquidUnstakeQuote {
+    /// Lamports the user receives from the liq pool SOL leg
+    pub lamports: u64,
+    /// Point of the fee curve at the liquidity left after the unstake
+    pub fee: Fee,
+    /// fee applied to the mSOL paid
+    pub msol_fee: u64,
+    /// Part of msol_fee sent to the treasury, the rest goes to the liq pool mSOL leg
+    pub treasury_msol_cut: u64,
+}
+
+/// Replicates liquid_unstake. The fee goes linearly from lp_max_fee with an empty pool
+/// to lp_min_fee at lp_liquidity_target, taken at the SOL leg liquidity left after the unstake;
+/// an unstake draining the whole leg pays lp_max_fee.
+/// max_lamports is the liq pool SOL leg balance minus its rent exempt reserve
+pub fn liquid_unstake_quote(
+    marinade: &Marinade,
+    max_lamports: u64,
+    msol_amount: u64,
+) -> Result<LiquidUnstakeQuote, CommonError> {
+    let user_lamports = marinade.calc_lamports_from_msol_amount(msol_amount)?;
+    let fee = if user_lamports < max_lamports {
+        marinade.liq_pool.linear_fee(max_lamports - user_lamports)
+    } else {
+        marinade.liq_pool.lp_max_fee
+    };
+    let msol_fee = fee.apply(msol_amount)?;
+    let lamports = marinade.calc_lamports_from_msol_amount(
+        msol_amount
+            .checked_sub(msol_fee)
+            .ok_or(CommonError::CalculationFailure)?,
+    )?;
+    if lamports > max_lamports {
+        return Err(CommonError::InsufficientLiquidity);
+    }
+    Ok(LiquidUnstakeQuote {
+        lamports,
+        fee,
+        msol_fee,
+        treasury_msol_cut: marinade.liq_pool.treasury_cut.apply(msol_fee)?,
+    })
+}
+
+/// Point of the fee schedule: msol_amount unstaked in one liquid_unstake
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct LiquidUnstakeTranche {
+    pub msol_amount: u64,
+    pub quote: LiquidUnstakeQuote,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MaxLiquidUnstake {
+    /// Largest mSOL amount liquid_unstake takes at a fee of at most max_fee,
+    /// 0 when even the smallest unstake pays more
+    pub msol_amount: u64,
+    /// Quotes of msol_amount * i / tranches for i in 1..=tranches, the last one is
+    /// the quote of msol_amount
+    pub tranches: Vec<LiquidUnstakeTranche>,
+}
+
+/// Largest liquid_unstake at a fee of at most max_fee with the current SOL leg liquidity.
+/// The fee only grows with the amount (less liquidity is left), so the amount is
+/// binary searched; amounts the leg can not pay out are over the limit whatever max_fee is.
+/// max_lamports is the liq pool SOL leg balance minus its rent exempt reserve
+pub fn max_liquid_unstake(
+    marinade: &Marinade,
+    max_lamports: u64,
+    max_fee: Fee,
+    tranches: u32,
+) -> Result<MaxLiquidUnstake, CommonError> {
+    let within = |msol_amount: u64| {
+        liquid_unstake_quote(marinade, max_lamports, msol_amount)
+            .ok()
+            .is_some_and(|quote| quote.fee <= max_fee)
+    };
+    let mut msol_amount = 0;
+    if within(0) {
+        let mut over = marinade.calc_msol_from_lamports(max_lamports)?.max(1);
+        while within(over) {
+            msol_amount = over;
+            if over == u64::MAX {
+                break;
+            }
+            over = over.saturating_mul(2);
+        }
+        while over - msol_amount > 1 {
+            let middle = msol_amount + (over - msol_amount) / 2;
+            if within(middle) {
+                msol_amount = middle;
+            } else {
+                over = middle;
+            }
+        }
+    }
+    let tranches = if msol_amount == 0 {
+        vec![]
+    } else {
+        (1..=tranches as u64)
+            .map(|index| {
+                let msol_amount = proportional(msol_amount, index, tranches as u64)?;
+                Ok(LiquidUnstakeTranche {
+                    msol_amount,
+                    quote: liquid_unstake_quote(marinade, max_lamports, msol_amount)?,
+                })
+            })
+            .collect::<Result<_, CommonError>>()?
+    };
+    Ok(MaxLiquidUnstake {
+        msol_amount,
+        tranches,
+    })
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct OrderUnstakeQuote {
+    /// Lamports of the ticket
+    pub lamports: u64,
+    /// delayed_unstake_fee applied to the mSOL paid, sent to the treasury
+    pub msol_fee: u64,
+}
+
+/// Replicates order_unstake. The delayed unstake fee (see MarinadeExtension) is taken on
+/// the mSOL first, the ticket is worth the rest; deployments without the fee pass the default
+pub fn order_unstake_quote(
+    marinade: &Marinade,
+    delayed_unstake_fee: FeeCents,
+    msol_amount: u64,
+) -> Result<OrderUnstakeQuote, CommonError> {
+    let msol_fee = delayed_unstake_fee.apply(msol_amount)?;
+    let lamports = marinade.calc_lamports_from_msol_amount(
+        msol_amount
+            .checked_sub(msol_fee)
+            .ok_or(CommonError::CalculationFailure)?,
+    )?;
+    Ok(OrderUnstakeQuote { lamports, msol_fee })
+}
+
+/// How deposit splits the lamports: mSOL is bought from the liq pool mSOL leg first,
+/// only the rest is staked through the reserve and minted
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct DepositQuote {
+    pub msol_from_liq_pool: u64,
+    pub lamports_to_liq_pool: u64,
+    pub msol_minted: u64,
+    pub lamports_to_reserve: u64,
+}
+
+impl DepositQuote {
+    /// mSOL the user receives
+    pub fn msol_amount(&self) -> u64 {
+        self.msol_from_liq_pool + self.msol_minted
+    }
+}
+
+#[derive(Clone, Copy, Debug, Display, PartialEq, Eq)]
+#[display(
+    fmt = "Quote from slot {} is {} slots old (max {})",
+    slot,
+    age,
+    max_age
+)]
+pub struct StaleQuote {
+    pub slot: u64,
+    pub age: u64,
+    pub max_age: u64,
+}
+
+impl std::error::Error for StaleQuote {}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Quote<T> {
+    pub value: T,
+    /// slot the state was fetched at
+    pub slot: u64,
+    pub state_address: Pubkey,
+    /// hash of the serialized state the quote was computed from
+    pub state_hash: Hash,
+    /// how the amounts of value were rounded, ON_CHAIN_ROUNDING for every quote of this module
+    pub rounding: RoundingPolicy,
+}
+
+pub fn state_hash(state: &Marinade) -> Hash {
+    hash(&state.try_to_vec().unwrap())
+}
+
+impl<T> Quote<T> {
+    fn new<S: Located<Marinade>>(state: &S, slot: u64, value: T) -> Self {
+        Self {
+            value,
+            slot,
+            state_address: state.key(),
+            state_hash: state_hash(state.as_ref()),
+            rounding: ON_CHAIN_ROUNDING,
+        }
+    }
+
+    /// A quote from a slot after current_slot (e.g. another RPC node) is not stale
+    pub fn is_stale(&self, current_slot: u64, max_age: u64) -> bool {
+        current_slot.saturating_sub(self.slot) > max_age
+    }
+
+    /// The value if the quote may still be executed
+    pub fn fresh_value(&self, current_slot: u64, max_age: u64) -> Result<&T, StaleQuote> {
+        if self.is_stale(current_slot, max_age) {
+            Err(StaleQuote {
+                slot: self.slot,
+                age: current_slot - self.slot,
+                max_age,
+            })
+        } else {
+            Ok(&self.value)
+        }
+    }
+
+    /// Whether the state has not changed since the quote was computed
+    pub fn matches_state<S: Located<Marinade>>(&self, state: &S) -> bool {
+        state.key() == self.state_address && state_hash(state.as_ref()) == self.state_hash
+    }
+}
+
+/// Balances of the liq pool token accounts, which the [`LiqPool`] of the state doesn't hold
+///
+/// [`LiqPool`]: crate::state::liq_pool::LiqPool
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct LiqPoolLegs {
+    /// SOL leg balance minus its rent exempt reserve
+    pub sol_leg_balance: u64,
+    /// mSOL leg token amount
+    pub msol_leg_balance: u64,
+}
+
+/// Replicates the deposit instruction, only the mSOL leg of liq_pool is read.
+/// Fails like deposit below min_deposit and when the minted part exceeds the staking cap
+pub fn quote_deposit(
+    marinade: &Marinade,
+    liq_pool: &LiqPoolLegs,
+    lamports: u64,
+) -> Result<DepositQuote, ProgramError> {
+    if lamports < marinade.min_deposit {
+        return Err(
+            CommonError::NumberTooLow(Some(Mismatch::new(marinade.min_deposit, lamports))).into(),
+        );
+    }
+    let msol_buy_order = marinade.calc_msol_from_lamports(lamports)?;
+    let msol_from_liq_pool = msol_buy_order.min(liq_pool.msol_leg_balance);
+    let lamports_to_liq_pool = if msol_from_liq_pool == 0 {
+        0
+    } else if msol_from_liq_pool == msol_buy_order {
+        lamports
+    } else {
+        marinade.calc_lamports_from_msol_amount(msol_from_liq_pool)?
+    };
+    let lamports_to_reserve = lamports.saturating_sub(lamports_to_liq_pool);
+    let msol_minted = if lamports_to_reserve > 0 {
+        marinade.check_staking_cap(lamports_to_reserve)?;
+        marinade.calc_msol_from_lamports(lamports_to_reserve)?
+    } else {
+        0
+    };
+    Ok(DepositQuote {
+        msol_from_liq_pool,
+        lamports_to_liq_pool,
+        msol_minted,
+        lamports_to_reserve,
+    })
+}
+
+/// quote_deposit tagged with the slot the state and liq pool legs were fetched at
+pub fn quote_deposit_with_liq_pool<S: Located<Marinade>>(
+    state: &S,
+    slot: u64,
+    liq_pool: &LiqPoolLegs,
+    lamports: u64,
+) -> Result<Quote<DepositQuote>, ProgramError> {
+    let quote = quote_deposit(state.as_ref(), liq_pool, lamports)?;
+    Ok(Quote::new(state, slot, quote))
+}
+
+/// Ticket created by order_unstake
+pub fn quote_order_unstake<S: Located<Marinade>>(
+    state: &S,
+    slot: u64,
+    msol_amount: u64,
+    delayed_unstake_fee: FeeCents,
+) -> Result<Quote<OrderUnstakeQuote>, CommonError> {
+    let quote = order_unstake_quote(state.as_ref(), delayed_unstake_fee, msol_amount)?;
+    Ok(Quote::new(state, slot, quote))
+}
+
+/// max_lamports is the liq pool SOL leg balance minus its rent exempt reserve
+pub fn quote_liquid_unstake<S: Located<Marinade>>(
+    state: &S,
+    slot: u64,
+    msol_amount: u64,
+    max_lamports: u64,
+) -> Result<Quote<LiquidUnstakeQuote>, CommonError> {
+    let quote = liquid_unstake_quote(state.as_ref(), max_lamports, msol_amount)?;
+    Ok(Quote::new(state, slot, quote))
+}
+
+/// max_liquid_unstake tagged with the slot the SOL leg balance was fetched at
+pub fn quote_max_liquid_unstake<S: Located<Marinade>>(
+    state: &S,
+    slot: u64,
+    max_lamports: u64,
+    max_fee: Fee,
+    tranches: u32,
+) -> Result<Quote<MaxLiquidUnstake>, CommonError> {
+    let max = max_liquid_unstake(state.as_ref(), max_lamports, max_fee, tranches)?;
+    Ok(Quote::new(state, slot, max))
+}
@@ -0,0 +1,189 @@
+//! Token bucket rate limiting of RPC requests with spillover to secondary endpoints.
+//! MarinadeClient (and its async counterpart) routes its requests through an EndpointPool set
+//! with with_rate_limit, the counters are read back with rate_limit_counters.
+
+use std::{
+    collections::HashMap,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+#[derive(Clone, Debug)]
+pub struct TokenBucket {
+    capacity: f64,
+    refill_per_second: f64,
+    tokens: f64,
+    last_refill: Instant,
+}
+
+impl TokenBucket {
+    /// Starts full. capacity is the allowed burst, refill_per_second the sustained rate
+    pub fn new(capacity: u32, refill_per_second: u32) -> Self {
+        Self {
+            capacity: capacity as f64,
+            refill_per_second: refill_per_second as f64,
+            tokens: capacity as f64,
+            last_refill: Instant::now(),
+        }
+    }
+
+    fn refill(&mut self, now: Instant) {
+        let elapsed = now
+            .saturating_duration_since(self.last_refill)
+            .as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.refill_per_second).min(self.capacity);
+        self.last_refill = now;
+    }
+
+    pub fn try_acquire(&mut self, cost: u32) -> bool {
+        self.try_acquire_at(cost, Instant::now())
+    }
+
+    pub fn try_acquire_at(&mut self, cost: u32, now: Instant) -> bool {
+        self.refill(now);
+        if self.tokens >= cost as f64 {
+            self.tokens -= cost as f64;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// How long until cost tokens are available. Duration::MAX if cost > capacity or no refill
+    pub fn time_until_available(&self, cost: u32) -> Duration {
+        let elapsed = self.last_refill.elapsed().as_secs_f64();
+        let available = (self.tokens + elapsed * self.refill_per_second).min(self.capacity);
+        let missing = cost as f64 - available;
+        if missing <= 0.0 {
+            Duration::ZERO
+        } else if cost as f64 > self.capacity || self.refill_per_second == 0.0 {
+            Duration::MAX
+        } else {
+            Duration::from_secs_f64(missing / self.refill_per_second)
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct EndpointCounters {
+    /// requests allowed on this endpoint
+    pub granted: u64,
+    /// granted requests that spilled over from a higher priority endpoint
+    pub spilled_over: u64,
+    /// requests this endpoint had no budget for
+    pub throttled: u64,
+    /// sum of costs of granted requests
+    pub consumed: u64,
+}
+
+#[derive(Clone, Debug)]
+pub struct Endpoint {
+    pub url: String,
+    pub bucket: TokenBucket,
+    pub counters: EndpointCounters,
+}
+
+/// Endpoints in priority order: requests go to the first one with budget left
+#[derive(Clone, Debug, Default)]
+pub struct EndpointPool {
+    endpoints: Vec<Endpoint>,
+}
+
+impl EndpointPool {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_endpoint(mut self, url: &str, capacity: u32, refill_per_second: u32) -> Self {
+        self.endpoints.push(Endpoint {
+            url: url.to_string(),
+            bucket: TokenBucket::new(capacity, refill_per_second),
+            counters: EndpointCounters::default(),
+        });
+        self
+    }
+
+    fn acquire_index(&mut self, cost: u32) -> Option<usize> {
+        let now = Instant::now();
+        for (index, endpoint) in self.endpoints.iter_mut().enumerate() {
+            if endpoint.bucket.try_acquire_at(cost, now) {
+                endpoint.counters.granted += 1;
+                endpoint.counters.consumed += cost as u64;
+                if index > 0 {
+                    endpoint.counters.spilled_over += 1;
+                }
+                return Some(index);
+            }
+            endpoint.counters.throttled += 1;
+        }
+        None
+    }
+
+    /// Url of the endpoint to use for a request of the given cost or None when all are exhausted
+    pub fn acquire(&mut self, cost: u32) -> Option<&str> {
+        let index = self.acquire_index(cost)?;
+        Some(self.endpoints[index].url.as_str())
+    }
+
+    /// Shortest wait until some endpoint has budget for cost. Duration::MAX if none ever has
+    pub fn time_until_available(&self, cost: u32) -> Duration {
+        self.endpoints
+            .iter()
+            .map(|endpoint| endpoint.bucket.time_until_available(cost))
+            .min()
+            .unwrap_or(Duration::MAX)
+    }
+
+    /// Waits until some endpoint has budget. None if no endpoint can ever serve the cost
+    pub fn acquire_blocking(&mut self, cost: u32) -> Option<&str> {
+        loop {
+            if let Some(index) = self.acquire_index(cost) {
+                return Some(self.endpoints[index].url.as_str());
+            }
+            let wait = self.time_until_available(cost);
+            if wait == Duration::MAX {
+                return None;
+            }
+            std::thread::sleep(wait);
+        }
+    }
+
+    pub fn counters(&self) -> Vec<(&str, EndpointCounters)> {
+        self.endpoints
+            .iter()
+            .map(|endpoint| (endpoint.url.as_str(), endpoint.counters))
+            .collect()
+    }
+}
+
+/// EndpointPool of a client, with the clients of the secondary endpoints created on first use
+pub(crate) struct Limiter<C> {
+    pub pool: EndpointPool,
+    secondaries: HashMap<String, Arc<C>>,
+}
+
+impl<C> Limiter<C> {
+    pub fn new(pool: EndpointPool) -> Self {
+        Self {
+            pool,
+            secondaries: HashMap::new(),
+        }
+    }
+
+    /// Client of url, None for the primary endpoint the caller holds the client of
+    pub fn secondary(
+        &mut self,
+        url: &str,
+        primary_url: &str,
+        connect: impl FnOnce(String) -> C,
+    ) -> Option<Arc<C>> {
+        if url == primary_url {
+            return None;
+        }
+        let client = self
+            .secondaries
+            .entry(url.to_string())
+            .or_insert_with(|| Arc::new(connect(url.to_string())));
+        Some(client.clone())
+    }
+}
@@ -0,0 +1,78 @@
+//! Reconciliation of the virtual balances stored in the state with the real account balances.
+//! SOL sent directly to the reserve PDA is not part of available_reserve_balance until
+//! the next update crank aligns it; the crank then realizes it like staking rewards,
+//! raising the mSOL price after taking the reward fee.
+
+use crate::{
+    calc::{proportional, shares_from_value},
+    error::CommonError,
+    state::marinade::Marinade,
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ReserveDonation {
+    /// Lamports above available_reserve_balance and the rent exempt reserve,
+    /// received since the last update
+    pub donated_lamports: u64,
+    /// reward_fee part of the donation, minted to the treasury as mSOL
+    pub fee_lamports: u64,
+    /// mSOL price in Marinade::PRICE_DENOMINATOR units
+    pub price_before: u64,
+    pub price_after: u64,
+}
+
+impl ReserveDonation {
+    pub fn price_bump_bps(&self) -> Result<u64, CommonError> {
+        proportional(
+            10_000,
+            self.price_after.saturating_sub(self.price_before),
+            self.price_before,
+        )
+    }
+}
+
+fn price(total_virtual_staked_lamports: u64, msol_supply: u64) -> Result<u64, CommonError> {
+    proportional(
+        Marinade::PRICE_DENOMINATOR,
+        total_virtual_staked_lamports,
+        msol_supply,
+    )
+}
+
+/// Lamports of the reserve account that the state does not account for.
+/// Negative when the reserve holds less than recorded, which only a program bug can cause
+pub fn reserve_discrepancy(state: &Marinade, reserve_lamports: u64) -> i128 {
+    reserve_lamports as i128
+        - state.rent_exempt_for_token_acc as i128
+        - state.available_reserve_balance as i128
+}
+
+/// Donation waiting in the reserve and the price bump the next update will realize.
+/// None when the reserve holds no more than recorded
+pub fn detect_reserve_donation(
+    state: &Marinade,
+    reserve_lamports: u64,
+) -> Result<Option<ReserveDonation>, CommonError> {
+    let discrepancy = reserve_discrepancy(state, reserve_lamports);
+    if discrepancy <= 0 {
+        return Ok(None);
+    }
+    let donated_lamports =
+        u64::try_from(discrepancy).map_err(|_| CommonError::CalculationFailure)?;
+    let fee_lamports = state.reward_fee.apply(donated_lamports)?;
+    let total_before = state.try_total_virtual_staked_lamports()?;
+    let total_after = total_before
+        .checked_add(donated_lamports)
+        .ok_or(CommonError::CalculationFailure)?;
+    let fee_msol = shares_from_value(fee_lamports, total_after - fee_lamports, state.msol_supply)?;
+    let supply_after = state
+        .msol_supply
+        .checked_add(fee_msol)
+        .ok_or(CommonError::CalculationFailure)?;
+    Ok(Some(ReserveDonation {
+        donated_lamports,
+        fee_lamports,
+        price_before: price(total_before, state.msol_supply)?,
+        price_after: price(total_after, supply_after)?,
+    }))
+}
@@ -0,0 +1,109 @@
+//! Re-simulation of historical transactions against the current cluster state,
+//! reporting how the outcome differs from the original execution.
+
+use solana_client::{
+    client_error::ClientError,
+    rpc_client::RpcClient,
+    rpc_config::{RpcSimulateTransactionConfig, RpcTransactionConfig},
+};
+use solana_sdk::{
+    signature::Signature,
+    transaction::{TransactionError, VersionedTransaction},
+};
+use solana_transaction_status::UiTransactionEncoding;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ExecutionOutcome {
+    pub err: Option<TransactionError>,
+    pub logs: Vec<String>,
+    pub units_consumed: Option<u64>,
+}
+
+#[derive(Clone, Debug)]
+pub struct ReplayReport {
+    pub signature: Signature,
+    /// Slot the original transaction was executed in
+    pub slot: u64,
+    pub transaction: VersionedTransaction,
+    pub original: ExecutionOutcome,
+    pub replay: ExecutionOutcome,
+}
+
+impl ReplayReport {
+    pub fn same_result(&self) -> bool {
+        self.original.err == self.replay.err
+    }
+
+    /// Index of the first log line that differs, None if logs are identical
+    pub fn first_log_divergence(&self) -> Option<usize> {
+        let common = self.original.logs.len().min(self.replay.logs.len());
+        (0..common)
+            .find(|i| self.original.logs[*i] != self.replay.logs[*i])
+            .or(if self.original.logs.len() != self.replay.logs.len() {
+                Some(common)
+            } else {
+                None
+            })
+    }
+
+    pub fn units_delta(&self) -> Option<i64> {
+        Some(self.replay.units_consumed? as i64 - self.original.units_consumed? as i64)
+    }
+}
+
+/// Fetches the transaction and simulates it again with a fresh blockhash
+pub fn replay_transaction(
+    rpc: &RpcClient,
+    signature: &Signature,
+) -> Result<ReplayReport, ClientError> {
+    let confirmed = rpc.get_transaction_with_config(
+        signature,
+        RpcTransactionConfig {
+            encoding: Some(UiTransactionEncoding::Base64),
+            commitment: None,
+            max_supported_transaction_version: Some(0),
+        },
+    )?;
+    let transaction = confirmed.transaction.transaction.decode().ok_or_else(|| {
+        ClientError::from(std::io::Error::new(
+            std::io::ErrorKind::InvalidData,
+            "can not decode transaction",
+        ))
+    })?;
+    let original = match confirmed.transaction.meta {
+        Some(meta) => ExecutionOutcome {
+            err: meta.err,
+            logs: Option::<Vec<String>>::from(meta.log_messages).unwrap_or_default(),
+            units_consumed: meta.compute_units_consumed.into(),
+        },
+        None => ExecutionOutcome {
+            err: None,
+            logs: vec![],
+            units_consumed: None,
+        },
+    };
+
+    let simulation = rpc
+        .simulate_transaction_with_config(
+            &transaction,
+            RpcSimulateTransactionConfig {
+                sig_verify: false,
+                replace_recent_blockhash: true,
+                ..RpcSimulateTransactionConfig::default()
+            },
+        )?
+        .value;
+    let replay = ExecutionOutcome {
+        err: simulation.err,
+        logs: simulation.logs.unwrap_or_default(),
+        units_consumed: simulation.units_consumed,
+    };
+
+    Ok(ReplayReport {
+        signature: *signature,
+        slot: confirmed.slot,
+        transaction,
+        original,
+        replay,
+    })
+}
@@ -0,0 +1,175 @@
+//! Accounts to fetch before quoting and building each user operation, so integrations with
+//! their own data layer can batch-load exactly what is needed.
+//! The state account itself has to be loaded first, the other addresses are read from it.
+//! The client reads through the same specification: fetch_program_state loads
+//! program_state_accounts and fetch_operation_accounts the accounts of one operation.
+
+use solana_program::pubkey::{Pubkey, PubkeyError};
+
+use crate::{
+    located::Located,
+    state::{
+        liq_pool::LiqPoolHelpers,
+        marinade::{Marinade, MarinadeHelpers},
+    },
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AccountRole {
+    State,
+    /// lamports are the SOL liquidity of the pool
+    LiqPoolSolLeg,
+    /// token balance is the mSOL swapped to depositors first
+    LiqPoolMsolLeg,
+    /// lamports pay out claimed tickets
+    Reserve,
+    LpMint,
+    /// token account of the user given to the operation
+    UserTokenAccount,
+    Ticket,
+    StakeAccount,
+    ValidatorList,
+    StakeList,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RequiredAccount {
+    pub role: AccountRole,
+    pub address: Pubkey,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Operation {
+    Deposit {
+        mint_to: Pubkey,
+    },
+    DepositStakeAccount {
+        stake_account: Pubkey,
+        mint_to: Pubkey,
+    },
+    LiquidUnstake {
+        get_msol_from: Pubkey,
+    },
+    OrderUnstake {
+        burn_msol_from: Pubkey,
+    },
+    Claim {
+        ticket_account: Pubkey,
+    },
+    AddLiquidity {
+        mint_to: Pubkey,
+    },
+    RemoveLiquidity {
+        burn_from: Pubkey,
+    },
+}
+
+impl Operation {
+    /// State first, then the accounts the quote and the instruction depend on
+    pub fn required_accounts<T: Located<Marinade>>(
+        &self,
+        state: &T,
+    ) -> Result<Vec<RequiredAccount>, PubkeyError> {
+        let marinade = state.as_ref();
+        let required = |role, address| RequiredAccount { role, address };
+        let mut accounts = vec![required(AccountRole::State, state.key())];
+        match *self {
+            Operation::Deposit { mint_to } => {
+                accounts.push(required(
+                    AccountRole::LiqPoolMsolLeg,
+                    marinade.liq_pool.msol_leg,
+                ));
+                accounts.push(required(AccountRole::UserTokenAccount, mint_to));
+            }
+            Operation::DepositStakeAccount {
+                stake_account,
+                mint_to,
+            } => {
+                accounts.push(required(AccountRole::StakeAccount, stake_account));
+                accounts.push(required(
+                    AccountRole::ValidatorList,
+                    marinade.validator_system.validator_list.account,
+                ));
+                accounts.push(required(
+                    AccountRole::StakeList,
+                    marinade.stake_system.stake_list.account,
+                ));
+                accounts.push(required(AccountRole::UserTokenAccount, mint_to));
+            }
+            Operation::LiquidUnstake { get_msol_from } => {
+                accounts.push(required(
+                    AccountRole::LiqPoolSolLeg,
+                    state.try_liq_pool_sol_leg_address()?,
+                ));
+                accounts.push(required(AccountRole::UserTokenAccount, get_msol_from));
+            }
+            Operation::OrderUnstake { burn_msol_from } => {
+                accounts.push(required(AccountRole::UserTokenAccount, burn_msol_from));
+            }
+            Operation::Claim { ticket_account } => {
+                accounts.push(required(AccountRole::Ticket, ticket_account));
+                accounts.push(required(AccountRole::Reserve, state.try_reserve_address()?));
+            }
+            Operation::AddLiquidity { mint_to } => {
+                accounts.push(required(
+                    AccountRole::LiqPoolSolLeg,
+                    state.try_liq_pool_sol_leg_address()?,
+                ));
+                accounts.push(required(
+                    AccountRole::LiqPoolMsolLeg,
+                    marinade.liq_pool.msol_leg,
+                ));
+                accounts.push(required(AccountRole::LpMint, marinade.liq_pool.lp_mint));
+                accounts.push(required(AccountRole::UserTokenAccount, mint_to));
+            }
+            Operation::RemoveLiquidity { burn_from } => {
+                accounts.push(required(
+                    AccountRole::LiqPoolSolLeg,
+                    state.try_liq_pool_sol_leg_address()?,
+                ));
+                accounts.push(required(
+                    AccountRole::LiqPoolMsolLeg,
+                    marinade.liq_pool.msol_leg,
+                ));
+                accounts.push(required(AccountRole::LpMint, marinade.liq_pool.lp_mint));
+                accounts.push(required(AccountRole::UserTokenAccount, burn_from));
+            }
+        }
+        Ok(accounts)
+    }
+}
+
+/// Accounts of client::MarinadeProgramState besides the state: the lists first, then the liq
+/// pool legs
+pub fn program_state_accounts<T: Located<Marinade>>(
+    state: &T,
+) -> Result<Vec<RequiredAccount>, PubkeyError> {
+    let marinade = state.as_ref();
+    let required = |role, address| RequiredAccount { role, address };
+    Ok(vec![
+        required(
+            AccountRole::ValidatorList,
+            marinade.validator_system.validator_list.account,
+        ),
+        required(
+            AccountRole::StakeList,
+            marinade.stake_system.stake_list.account,
+        ),
+        required(
+            AccountRole::LiqPoolSolLeg,
+            state.try_liq_pool_sol_leg_address()?,
+        ),
+        required(AccountRole::LiqPoolMsolLeg, marinade.liq_pool.msol_leg),
+    ])
+}
+
+/// Addresses only, deduplicated in order, e.g. for getMultipleAccounts
+pub fn addresses(accounts: &[RequiredAccount]) -> Vec<Pubkey> {
+    let mut addresses: Vec<Pubkey> = Vec::with_capacity(accounts.len());
+    for account in accounts {
+        if !addresses.contains(&account.address) {
+            addresses.push(account.address);
+        }
+    }
+    addresses
+}
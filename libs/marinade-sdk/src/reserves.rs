@@ -0,0 +1,124 @@
+//! Proof-of-reserves statements for mSOL custodians.
+//!
+//! A statement lists the mSOL and pending tickets of the custody wallets valued at the
+//! current mSOL price of the state at a stated slot (see portfolio::aggregate). It is published
+//! as canonical JSON: keys sorted, no whitespace, u64 values as decimal strings as in dto.
+//! The ed25519 signature covers exactly those bytes, so anyone holding the JSON and the
+//! signer key can check the statement without the SDK.
+
+use solana_program::pubkey::Pubkey;
+use solana_sdk::{
+    signature::Signature,
+    signer::{Signer, SignerError},
+};
+
+use crate::{
+    error::CommonError,
+    located::Located,
+    portfolio::{aggregate, Position, TicketMaturity, WalletHoldings},
+    state::marinade::Marinade,
+};
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ReserveStatement {
+    pub state: Pubkey,
+    /// Slot the balances, tickets and state were read at
+    pub slot: u64,
+    /// Lamports of one mSOL used for the values, scaled by Marinade::PRICE_DENOMINATOR.
+    /// The current price of the state, not the msol_price stored at the last update
+    pub msol_price: u64,
+    pub wallets: Vec<(Pubkey, Position)>,
+    pub total: Position,
+    /// Pending tickets of all wallets ordered by due_epoch
+    pub tickets: Vec<TicketMaturity>,
+}
+
+impl ReserveStatement {
+    /// Changes whenever the JSON layout does
+    pub const VERSION: u32 = 1;
+
+    pub fn new<S: Located<Marinade>>(
+        state: &S,
+        slot: u64,
+        holdings: &[WalletHoldings],
+    ) -> Result<Self, CommonError> {
+        let portfolio = aggregate(holdings, state.as_ref())?;
+        Ok(Self {
+            state: state.key(),
+            slot,
+            msol_price: state
+                .as_ref()
+                .calc_lamports_from_msol_amount(Marinade::PRICE_DENOMINATOR)?,
+            wallets: portfolio.wallets,
+            total: portfolio.total,
+            tickets: portfolio.maturity_schedule,
+        })
+    }
+
+    pub fn canonical_json(&self) -> String {
+        let wallets: Vec<String> = self
+            .wallets
+            .iter()
+            .map(|(wallet, position)| position_json(position, Some(wallet)))
+            .collect();
+        let tickets: Vec<String> = self
+            .tickets
+            .iter()
+            .map(|ticket| {
+                format!(
+                    r#"{{"due_epoch":"{}","lamports_amount":"{}","ticket":"{}","wallet":"{}"}}"#,
+                    ticket.due_epoch, ticket.lamports_amount, ticket.ticket, ticket.wallet
+                )
+            })
+            .collect();
+        format!(
+            r#"{{"msol_price":"{}","price_denominator":"{}","slot":"{}","state":"{}","tickets":[{}],"total":{},"version":{},"wallets":[{}]}}"#,
+            self.msol_price,
+            Marinade::PRICE_DENOMINATOR,
+            self.slot,
+            self.state,
+            tickets.join(","),
+            position_json(&self.total, None),
+            Self::VERSION,
+            wallets.join(",")
+        )
+    }
+
+    pub fn sign(&self, signer: &dyn Signer) -> Result<SignedStatement, SignerError> {
+        let json = self.canonical_json();
+        let signature = signer.try_sign_message(json.as_bytes())?;
+        Ok(SignedStatement {
+            json,
+            signer: signer.try_pubkey()?,
+            signature,
+        })
+    }
+}
+
+fn position_json(position: &Position, wallet: Option<&Pubkey>) -> String {
+    let wallet = wallet.map_or(String::new(), |wallet| format!(r#","wallet":"{}""#, wallet));
+    format!(
+        r#"{{"msol_amount":"{}","msol_value":"{}","ticket_count":"{}","ticket_lamports":"{}","total_value":"{}"{}}}"#,
+        position.msol_amount,
+        position.msol_value,
+        position.ticket_count,
+        position.ticket_lamports,
+        position.total_value,
+        wallet
+    )
+}
+
+/// Published attestation: the canonical JSON with its ed25519 signature
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SignedStatement {
+    pub json: String,
+    pub signer: Pubkey,
+    pub signature: Signature,
+}
+
+impl SignedStatement {
+    pub fn verify(&self) -> bool {
+        self.signature
+            .verify(self.signer.as_ref(), self.json.as_bytes())
+    }
+}
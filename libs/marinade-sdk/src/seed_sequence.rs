@@ -0,0 +1,161 @@
+//! Deterministic addresses for the stake accounts a crank operator creates.
+//! Addresses are `create_with_seed(base, seed, stake program)` with the seed derived from
+//! (state, epoch, counter) instead of throwaway keypairs. Only the counter of the latest epoch
+//! is persisted, so issued lists that epoch; the accounts of earlier epochs are recomputed by
+//! walking their counters from 0 with address until the first one that does not exist on chain.
+
+use std::{fmt, io};
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use derive_more::{Display, From};
+use solana_program::{
+    instruction::Instruction,
+    pubkey::{Pubkey, PubkeyError},
+    stake, system_instruction,
+};
+
+use crate::{compat::stake_account_len, storage::Storage};
+
+#[derive(Debug, Display, From)]
+pub enum SeedSequenceError<E> {
+    Storage(io::Error),
+    Pubkey(PubkeyError),
+    #[display(fmt = "Can not decode {}", _0)]
+    #[from(ignore)]
+    Decode(String),
+    /// Error of the on-chain collision check
+    #[from(ignore)]
+    Lookup(E),
+    #[display(fmt = "No free address after {} attempts", _0)]
+    #[from(ignore)]
+    Exhausted(u64),
+}
+
+impl<E: fmt::Debug + fmt::Display> std::error::Error for SeedSequenceError<E> {}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SeededAddress {
+    pub seed: String,
+    pub address: Pubkey,
+}
+
+/// Position of the sequence as persisted, the counter restarts with every epoch
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, BorshSerialize, BorshDeserialize)]
+struct Position {
+    epoch: u64,
+    counter: u64,
+}
+
+pub struct SeedSequence<S: Storage> {
+    /// Signer of create_account_with_seed, usually the crank operator
+    pub base: Pubkey,
+    pub state: Pubkey,
+    pub storage: S,
+    /// Addresses already taken on chain that next skips before giving up
+    pub max_collisions: u64,
+}
+
+impl<S: Storage> SeedSequence<S> {
+    /// Characters of the state address in the seed, keeps the seed within MAX_SEED_LEN
+    const STATE_PREFIX_LEN: usize = 8;
+
+    pub fn new(base: Pubkey, state: Pubkey, storage: S) -> Self {
+        Self {
+            base,
+            state,
+            storage,
+            max_collisions: 100,
+        }
+    }
+
+    fn key(&self) -> String {
+        format!("seed-sequence-{}-{}", self.state, self.base)
+    }
+
+    pub fn seed(&self, epoch: u64, counter: u64) -> String {
+        let state = self.state.to_string();
+        format!("{}-{}-{}", &state[..Self::STATE_PREFIX_LEN], epoch, counter)
+    }
+
+    pub fn address(&self, epoch: u64, counter: u64) -> Result<SeededAddress, PubkeyError> {
+        let seed = self.seed(epoch, counter);
+        let address = Pubkey::create_with_seed(&self.base, &seed, &stake::program::ID)?;
+        Ok(SeededAddress { seed, address })
+    }
+
+    fn position<E>(&self) -> Result<Position, SeedSequenceError<E>> {
+        match self.storage.load(&self.key())? {
+            Some(data) => {
+                Position::try_from_slice(&data).map_err(|_| SeedSequenceError::Decode(self.key()))
+            }
+            None => Ok(Position::default()),
+        }
+    }
+
+    /// Counter of the next address of epoch
+    pub fn counter<E>(&self, epoch: u64) -> Result<u64, SeedSequenceError<E>> {
+        let position = self.position()?;
+        Ok(if position.epoch == epoch {
+            position.counter
+        } else {
+            0
+        })
+    }
+
+    /// Every address handed out in epoch, including skipped collisions.
+    /// Empty for the epochs before the latest one, their counters are not kept
+    pub fn issued<E>(&self, epoch: u64) -> Result<Vec<SeededAddress>, SeedSequenceError<E>> {
+        (0..self.counter(epoch)?)
+            .map(|counter| Ok(self.address(epoch, counter)?))
+            .collect()
+    }
+
+    /// Next address of epoch for which is_used (e.g. an account lookup) returns false.
+    /// The counter is stored before returning, so an address is never handed out twice
+    /// even when the transaction creating it fails
+    pub fn next<F, E>(
+        &mut self,
+        epoch: u64,
+        mut is_used: F,
+    ) -> Result<SeededAddress, SeedSequenceError<E>>
+    where
+        F: FnMut(&Pubkey) -> Result<bool, E>,
+    {
+        let first = self.counter(epoch)?;
+        let last = first.saturating_add(self.max_collisions);
+        for counter in first..=last {
+            let seeded = self.address(epoch, counter)?;
+            if is_used(&seeded.address).map_err(SeedSequenceError::Lookup)? {
+                continue;
+            }
+            let position = Position {
+                epoch,
+                counter: counter.saturating_add(1),
+            };
+            let key = self.key();
+            self.storage.store(&key, &position.try_to_vec()?)?;
+            return Ok(seeded);
+        }
+        Err(SeedSequenceError::Exhausted(
+            (last - first).saturating_add(1),
+        ))
+    }
+
+    /// Allocates the stake account at seeded, base has to sign
+    pub fn create_stake_account(
+        &self,
+        seeded: &SeededAddress,
+        payer: &Pubkey,
+        lamports: u64,
+    ) -> Instruction {
+        system_instruction::create_account_with_seed(
+            payer,
+            &seeded.address,
+            &self.base,
+            &seeded.seed,
+            lamports,
+            stake_account_len() as u64,
+            &stake::program::ID,
+        )
+    }
+}
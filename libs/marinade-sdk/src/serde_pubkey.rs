@@ -0,0 +1,16 @@
+//! Serde representation of the pubkeys of the state types (feature serde): base58 strings,
+//! as explorers and the RPC JSON show them, instead of the 32 byte array of Pubkey's own impl.
+//! Use it on a Pubkey field with `#[serde(with = "marinade_sdk::serde_pubkey")]`.
+
+use serde::{de::Error, Deserialize, Deserializer, Serializer};
+use solana_program::pubkey::Pubkey;
+
+pub fn serialize<S: Serializer>(pubkey: &Pubkey, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.collect_str(pubkey)
+}
+
+pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Pubkey, D::Error> {
+    String::deserialize(deserializer)?
+        .parse()
+        .map_err(|err| D::Error::custom(format!("invalid pubkey: {}", err)))
+}
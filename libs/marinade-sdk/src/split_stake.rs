@@ -0,0 +1,256 @@
+//! Lifecycle of the split stake accounts created by partial_unstake.
+//! The split account is a fresh signer funded by split_stake_rent_payer; it ends either merged
+//! into another stake account of the same validator or withdrawn to the reserve once deactivated.
+//! The manifest keeps track of every created account so validator-manager bots can find the
+//! ones that are still outstanding.
+
+use std::{
+    fs::{self, File},
+    io::{self, Write},
+    path::Path,
+};
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use micro_anchor::InstructionBuilder;
+use solana_program::{
+    instruction::Instruction,
+    pubkey::{Pubkey, PubkeyError},
+    stake, system_program,
+    sysvar::{clock, rent, stake_history},
+};
+
+use crate::{
+    instructions::{
+        merge_stakes::{MergeStakesAccounts, MergeStakesData},
+        partial_unstake::{PartialUnstakeAccounts, PartialUnstakeData},
+    },
+    located::Located,
+    state::{
+        marinade::{Marinade, MarinadeHelpers},
+        stake_system::StakeSystemHelpers,
+    },
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, BorshSerialize, BorshDeserialize)]
+pub enum SplitStakeStatus {
+    /// partial_unstake instruction built, not confirmed yet
+    Pending,
+    /// partial_unstake confirmed, the split account is in the stake list
+    Created,
+    /// merge_stakes confirmed, the split account was merged into destination
+    Merged { destination: Pubkey },
+    /// no longer in the stake list, lamports went back to the reserve
+    Reclaimed,
+    /// merge_stakes into destination built, not confirmed yet
+    Merging { destination: Pubkey },
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, BorshSerialize, BorshDeserialize)]
+pub struct SplitStakeEntry {
+    pub split_stake_account: Pubkey,
+    pub source_stake_account: Pubkey,
+    pub rent_payer: Pubkey,
+    pub amount: u64,
+    pub created_epoch: u64,
+    pub status: SplitStakeStatus,
+}
+
+impl SplitStakeEntry {
+    pub fn is_outstanding(&self) -> bool {
+        matches!(
+            self.status,
+            SplitStakeStatus::Pending
+                | SplitStakeStatus::Created
+                | SplitStakeStatus::Merging { .. }
+        )
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, BorshSerialize, BorshDeserialize)]
+pub struct SplitStakeManifest {
+    pub entries: Vec<SplitStakeEntry>,
+}
+
+impl SplitStakeManifest {
+    /// Missing file means an empty manifest
+    pub fn load(path: &Path) -> io::Result<Self> {
+        match fs::read(path) {
+            Ok(data) => Self::try_from_slice(&data),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Writes and syncs a temporary file first so a crash never leaves a truncated manifest
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        let tmp = path.with_extension("tmp");
+        let mut file = File::create(&tmp)?;
+        file.write_all(&self.try_to_vec()?)?;
+        file.sync_all()?;
+        fs::rename(tmp, path)
+    }
+
+    pub fn get(&self, split_stake_account: &Pubkey) -> Option<&SplitStakeEntry> {
+        self.entries
+            .iter()
+            .find(|entry| entry.split_stake_account == *split_stake_account)
+    }
+
+    fn get_mut(&mut self, split_stake_account: &Pubkey) -> Option<&mut SplitStakeEntry> {
+        self.entries
+            .iter_mut()
+            .find(|entry| entry.split_stake_account == *split_stake_account)
+    }
+
+    pub fn outstanding(&self) -> impl Iterator<Item = &SplitStakeEntry> {
+        self.entries.iter().filter(|entry| entry.is_outstanding())
+    }
+
+    /// Outstanding entries created more than max_age epochs ago
+    pub fn stale(&self, current_epoch: u64, max_age: u64) -> Vec<&SplitStakeEntry> {
+        self.outstanding()
+            .filter(|entry| current_epoch.saturating_sub(entry.created_epoch) > max_age)
+            .collect()
+    }
+
+    /// Builds partial_unstake and records its split account as Pending.
+    /// split_stake_account must be a fresh keypair signing the transaction.
+    #[allow(clippy::too_many_arguments)]
+    pub fn partial_unstake<T: Located<Marinade>>(
+        &mut self,
+        state: &T,
+        data: PartialUnstakeData,
+        validator_manager_authority: Pubkey,
+        stake_account: Pubkey,
+        split_stake_account: Pubkey,
+        split_stake_rent_payer: Pubkey,
+        current_epoch: u64,
+    ) -> Result<Instruction, PubkeyError> {
+        let stake_deposit_authority = state.try_stake_deposit_authority()?;
+        let reserve_pda = state.try_reserve_address()?;
+        self.entries.push(SplitStakeEntry {
+            split_stake_account,
+            source_stake_account: stake_account,
+            rent_payer: split_stake_rent_payer,
+            amount: data.desired_unstake_amount,
+            created_epoch: current_epoch,
+            status: SplitStakeStatus::Pending,
+        });
+        let builder = InstructionBuilder {
+            accounts: PartialUnstakeAccounts {
+                marinade: state.key(),
+                validator_manager_authority,
+                validator_list: state.as_ref().validator_system.validator_list.account,
+                stake_list: state.as_ref().stake_system.stake_list.account,
+                stake_account,
+                stake_deposit_authority,
+                reserve_pda,
+                split_stake_account,
+                split_stake_rent_payer,
+                clock: clock::ID,
+                rent: rent::ID,
+                stake_history: stake_history::ID,
+                system_program: system_program::ID,
+                stake_program: stake::program::ID,
+            },
+            data,
+        };
+        Ok(builder.instruction(state.program_id()))
+    }
+
+    /// Builds merge_stakes of a tracked split account into destination and records it as
+    /// Merging until confirm_merge (or reconcile) sees it land, abort_merge when it does not.
+    /// Returns None when the split account is not Pending or Created in the manifest.
+    pub fn merge<T: Located<Marinade>>(
+        &mut self,
+        state: &T,
+        data: MergeStakesData,
+        destination_stake: Pubkey,
+        split_stake_account: Pubkey,
+    ) -> Result<Option<Instruction>, PubkeyError> {
+        let stake_deposit_authority = state.try_stake_deposit_authority()?;
+        let stake_withdraw_authority = state.try_stake_withdraw_authority()?;
+        let entry = match self.get_mut(&split_stake_account) {
+            Some(entry) => entry,
+            None => return Ok(None),
+        };
+        if !matches!(
+            entry.status,
+            SplitStakeStatus::Pending | SplitStakeStatus::Created
+        ) {
+            return Ok(None);
+        }
+        entry.status = SplitStakeStatus::Merging {
+            destination: destination_stake,
+        };
+        let builder = InstructionBuilder {
+            accounts: MergeStakesAccounts {
+                marinade: state.key(),
+                stake_list: state.as_ref().stake_system.stake_list.account,
+                validator_list: state.as_ref().validator_system.validator_list.account,
+                destination_stake,
+                source_stake: split_stake_account,
+                stake_deposit_authority,
+                stake_withdraw_authority,
+                operational_sol_account: state.as_ref().operational_sol_account,
+                clock: clock::ID,
+                stake_history: stake_history::ID,
+                stake_program: stake::program::ID,
+            },
+            data,
+        };
+        Ok(Some(builder.instruction(state.program_id())))
+    }
+
+    /// The merge_stakes of split_stake_account was confirmed: Merging becomes Merged.
+    /// false when the entry is not Merging
+    pub fn confirm_merge(&mut self, split_stake_account: &Pubkey) -> bool {
+        match self.get_mut(split_stake_account) {
+            Some(entry) => match entry.status {
+                SplitStakeStatus::Merging { destination } => {
+                    entry.status = SplitStakeStatus::Merged { destination };
+                    true
+                }
+                _ => false,
+            },
+            None => false,
+        }
+    }
+
+    /// The merge_stakes of split_stake_account failed or expired: Merging goes back to Created
+    /// so merge can build it again
+    pub fn abort_merge(&mut self, split_stake_account: &Pubkey) {
+        if let Some(entry) = self.get_mut(split_stake_account) {
+            if let SplitStakeStatus::Merging { .. } = entry.status {
+                entry.status = SplitStakeStatus::Created;
+            }
+        }
+    }
+
+    /// Updates statuses from the stake accounts currently in the stake list:
+    /// Pending entries found there become Created, Created entries missing from it become
+    /// Reclaimed and Merging entries missing from it Merged (merge_stakes removes the source
+    /// from the list). Pending and Merging entries still as they were are left as they are
+    /// (transaction not landed yet).
+    pub fn reconcile(&mut self, stake_list_accounts: &[Pubkey]) {
+        for entry in self.entries.iter_mut() {
+            let listed = stake_list_accounts.contains(&entry.split_stake_account);
+            entry.status = match entry.status {
+                SplitStakeStatus::Pending if listed => SplitStakeStatus::Created,
+                SplitStakeStatus::Created if !listed => SplitStakeStatus::Reclaimed,
+                SplitStakeStatus::Merging { destination } if !listed => {
+                    SplitStakeStatus::Merged { destination }
+                }
+                status => status,
+            };
+        }
+    }
+
+    /// Drops Pending entries whose transaction never landed
+    pub fn forget_pending(&mut self, split_stake_account: &Pubkey) {
+        self.entries.retain(|entry| {
+            entry.split_stake_account != *split_stake_account
+                || entry.status != SplitStakeStatus::Pending
+        });
+    }
+}
@@ -3,13 +3,21 @@ use micro_anchor::{AccountDeserialize, Discriminator, Owner};
 use solana_program::pubkey::Pubkey;
 
 #[derive(Debug, BorshDeserialize, BorshSerialize)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct DelayedUnstakeTicket {
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_pubkey"))]
     pub state_address: Pubkey, // instance of marinade state this ticket belongs to
-    pub beneficiary: Pubkey,   // main account where to send SOL when claimed
-    pub lamports_amount: u64,  // amount this ticked is worth
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_pubkey"))]
+    pub beneficiary: Pubkey, // main account where to send SOL when claimed
+    pub lamports_amount: u64, // amount this ticked is worth
     pub created_epoch: u64, // epoch when this acc was created (epoch when delayed-unstake was requested)
 }
 
+impl DelayedUnstakeTicket {
+    /// Discriminator and fields
+    pub const ACCOUNT_LEN: usize = 8 + 32 + 32 + 8 + 8;
+}
+
 impl Discriminator for DelayedUnstakeTicket {
     const DISCRIMINATOR: [u8; 8] = [133, 77, 18, 98, 211, 1, 231, 3];
 }
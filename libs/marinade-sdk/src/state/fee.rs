@@ -2,11 +2,12 @@ use std::{fmt::Display, str::FromStr};
 
 use borsh::{BorshDeserialize, BorshSerialize};
 
-use crate::error::CommonError;
+use crate::error::{CommonError, Mismatch};
 
 #[derive(
     Clone, Copy, Debug, Default, BorshSerialize, BorshDeserialize, PartialEq, Eq, PartialOrd, Ord,
 )]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Fee {
     pub basis_points: u32,
 }
@@ -18,6 +19,11 @@ impl Display for Fee {
 }
 
 impl Fee {
+    /// 100%, the highest fee check accepts
+    pub const MAX: Fee = Fee {
+        basis_points: 10_000,
+    };
+
     pub fn from_basis_points(basis_points: u32) -> Self {
         Self { basis_points }
     }
@@ -25,22 +31,43 @@ impl Fee {
     /// generic check, capped Fee
     pub fn check_max(&self, max_basis_points: u32) -> Result<(), CommonError> {
         if self.basis_points > max_basis_points {
-            Err(CommonError::FeeTooHigh)
+            Err(CommonError::FeeTooHigh(Some(Mismatch::new(
+                max_basis_points,
+                self.basis_points,
+            ))))
         } else {
             Ok(())
         }
     }
     /// base check, Fee <= 100%
     pub fn check(&self) -> Result<(), CommonError> {
-        self.check_max(10_000)
+        self.check_max(Self::MAX.basis_points)
     }
 
-    pub fn apply(&self, lamports: u64) -> u64 {
+    /// Share of lamports, rounded down as by the program. Fails for fees above 100%
+    pub fn apply(&self, lamports: u64) -> Result<u64, CommonError> {
+        self.check()?;
         // LMT no error possible
-        (lamports as u128 * self.basis_points as u128 / 10_000_u128) as u64
+        Ok((lamports as u128 * self.basis_points as u128 / 10_000_u128) as u64)
     }
 }
 
+/// Fee within min..=max, e.g. a client side bound on a config_lp or config_marinade fee.
+/// The bounds themselves must satisfy min <= max <= Fee::MAX
+pub fn check_fee_range(fee: Fee, min: Fee, max: Fee) -> Result<(), CommonError> {
+    max.check()?;
+    if min > max {
+        return Err(CommonError::FeesWrongWayRound);
+    }
+    if fee < min {
+        return Err(CommonError::NumberTooLow(Some(Mismatch::new(
+            min.basis_points as u64,
+            fee.basis_points as u64,
+        ))));
+    }
+    fee.check_max(max.basis_points)
+}
+
 impl TryFrom<f64> for Fee {
     type Error = CommonError;
 
@@ -61,3 +88,43 @@ impl FromStr for Fee {
         f64::try_into(s.parse().map_err(|_| CommonError::CalculationFailure)?)
     }
 }
+
+/// Fee in hundredths of a basis point, as newer program versions store their finer fees
+#[derive(
+    Clone, Copy, Debug, Default, BorshSerialize, BorshDeserialize, PartialEq, Eq, PartialOrd, Ord,
+)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FeeCents {
+    pub bp_cents: u32,
+}
+
+impl Display for FeeCents {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}%", self.bp_cents as f64 / 10_000.0)
+    }
+}
+
+impl FeeCents {
+    pub fn from_bp_cents(bp_cents: u32) -> Self {
+        Self { bp_cents }
+    }
+
+    /// base check, FeeCents <= 100%
+    pub fn check(&self) -> Result<(), CommonError> {
+        if self.bp_cents > 1_000_000 {
+            Err(CommonError::FeeTooHigh(Some(Mismatch::new(
+                1_000_000,
+                self.bp_cents,
+            ))))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Share of amount, rounded down as by the program. Fails for fees above 100%
+    pub fn apply(&self, amount: u64) -> Result<u64, CommonError> {
+        self.check()?;
+        // LMT no error possible
+        Ok((amount as u128 * self.bp_cents as u128 / 1_000_000_u128) as u64)
+    }
+}
@@ -0,0 +1,174 @@
+use solana_program::pubkey::Pubkey;
+
+use crate::state::{liq_pool::LiqPool, marinade::Marinade, stake_system::StakeSystem};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Invariant {
+    /// total_active_balance + delayed and emergency cooling down + available_reserve_balance
+    /// fits in u64
+    TotalUnderControlOverflow,
+    /// circulating_ticket_balance <= total lamports under control
+    TicketsCovered,
+    /// no ticket balance without tickets and the other way round
+    TicketCount,
+    /// stored bump seed re-derives the canonical PDA
+    BumpSeed(&'static str),
+    /// liq pool min fee <= max fee <= 100%, treasury cut <= 100%
+    LiqPoolFees,
+    /// list count fits in the list account
+    ListCapacity(&'static str),
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct InvariantViolation {
+    pub invariant: Invariant,
+    pub message: String,
+}
+
+/// Data lengths of the list accounts, needed for the capacity checks
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ListAccountLens {
+    pub validator_list: usize,
+    pub stake_list: usize,
+}
+
+impl Marinade {
+    /// Cross-field consistency audit returning every violation found.
+    /// List capacity is only checked when list_lens is given.
+    pub fn check_invariants(
+        &self,
+        state_address: &Pubkey,
+        list_lens: Option<ListAccountLens>,
+    ) -> Vec<InvariantViolation> {
+        let mut violations = Vec::new();
+        let mut violation = |invariant: Invariant, message: String| {
+            violations.push(InvariantViolation { invariant, message })
+        };
+
+        let total_under_control = self
+            .stake_system
+            .delayed_unstake_cooling_down
+            .checked_add(self.emergency_cooling_down)
+            .and_then(|cooling_down| {
+                cooling_down.checked_add(self.validator_system.total_active_balance)
+            })
+            .and_then(|staked| staked.checked_add(self.available_reserve_balance));
+        match total_under_control {
+            None => violation(
+                Invariant::TotalUnderControlOverflow,
+                "Total lamports under control overflow".to_string(),
+            ),
+            Some(total) => {
+                if self.circulating_ticket_balance > total {
+                    violation(
+                        Invariant::TicketsCovered,
+                        format!(
+                            "Circulating ticket balance {} above total under control {}",
+                            self.circulating_ticket_balance, total
+                        ),
+                    );
+                }
+            }
+        }
+        if (self.circulating_ticket_count == 0) != (self.circulating_ticket_balance == 0) {
+            violation(
+                Invariant::TicketCount,
+                format!(
+                    "{} circulating tickets with balance {}",
+                    self.circulating_ticket_count, self.circulating_ticket_balance
+                ),
+            );
+        }
+
+        let bumps = [
+            (
+                "reserve",
+                Marinade::find_reserve_address(state_address).1,
+                self.reserve_bump_seed,
+            ),
+            (
+                "msol_mint_authority",
+                Marinade::find_msol_mint_authority(state_address).1,
+                self.msol_mint_authority_bump_seed,
+            ),
+            (
+                "stake_deposit_authority",
+                StakeSystem::find_stake_deposit_authority(state_address).1,
+                self.stake_system.stake_deposit_bump_seed,
+            ),
+            (
+                "stake_withdraw_authority",
+                StakeSystem::find_stake_withdraw_authority(state_address).1,
+                self.stake_system.stake_withdraw_bump_seed,
+            ),
+            (
+                "lp_mint_authority",
+                LiqPool::find_lp_mint_authority(state_address).1,
+                self.liq_pool.lp_mint_authority_bump_seed,
+            ),
+            (
+                "liq_pool_sol_leg",
+                LiqPool::find_sol_leg_address(state_address).1,
+                self.liq_pool.sol_leg_bump_seed,
+            ),
+            (
+                "liq_pool_msol_leg_authority",
+                LiqPool::find_msol_leg_authority(state_address).1,
+                self.liq_pool.msol_leg_authority_bump_seed,
+            ),
+        ];
+        for (name, expected, stored) in bumps {
+            if expected != stored {
+                violation(
+                    Invariant::BumpSeed(name),
+                    format!("{} bump seed {} instead of {}", name, stored, expected),
+                );
+            }
+        }
+
+        let liq_pool = &self.liq_pool;
+        if liq_pool.lp_min_fee > liq_pool.lp_max_fee
+            || liq_pool.lp_max_fee.basis_points > 10_000
+            || liq_pool.treasury_cut.basis_points > 10_000
+        {
+            violation(
+                Invariant::LiqPoolFees,
+                format!(
+                    "Liq pool fees min {} max {} treasury cut {}",
+                    liq_pool.lp_min_fee, liq_pool.lp_max_fee, liq_pool.treasury_cut
+                ),
+            );
+        }
+
+        if let Some(lens) = list_lens {
+            let lists = [
+                (
+                    "validator_list",
+                    self.validator_system
+                        .validator_list_capacity(lens.validator_list),
+                    self.validator_system.validators_len(),
+                ),
+                (
+                    "stake_list",
+                    self.stake_system.stake_list_capacity(lens.stake_list),
+                    self.stake_system.stakes_len(),
+                ),
+            ];
+            for (name, capacity, count) in lists {
+                match capacity {
+                    Ok(capacity) if count <= capacity => {}
+                    Ok(capacity) => violation(
+                        Invariant::ListCapacity(name),
+                        format!("{} count {} above capacity {}", name, count, capacity),
+                    ),
+                    Err(err) => violation(
+                        Invariant::ListCapacity(name),
+                        format!("{} capacity: {}", name, err),
+                    ),
+                }
+            }
+        }
+
+        violations
+    }
+}
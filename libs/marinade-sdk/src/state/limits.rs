@@ -0,0 +1,87 @@
+use crate::instructions::config_marinade::ConfigMarinadeData;
+
+/// All user facing limits configured by the DAO
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Limits {
+    pub min_deposit: u64,
+    pub min_withdraw: u64,
+    pub min_stake: u64,
+    pub staking_sol_cap: u64,
+    pub liquidity_sol_cap: u64,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LimitKind {
+    MinDeposit,
+    MinWithdraw,
+    MinStake,
+    StakingSolCap,
+    LiquiditySolCap,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct LimitChange {
+    pub limit: LimitKind,
+    pub old: u64,
+    pub new: u64,
+}
+
+impl Limits {
+    fn values(&self) -> [(LimitKind, u64); 5] {
+        [
+            (LimitKind::MinDeposit, self.min_deposit),
+            (LimitKind::MinWithdraw, self.min_withdraw),
+            (LimitKind::MinStake, self.min_stake),
+            (LimitKind::StakingSolCap, self.staking_sol_cap),
+            (LimitKind::LiquiditySolCap, self.liquidity_sol_cap),
+        ]
+    }
+
+    /// Limits that differ between two snapshots (e.g. state before and after a config_marinade)
+    pub fn changes(&self, newer: &Limits) -> Vec<LimitChange> {
+        self.values()
+            .iter()
+            .zip(newer.values().iter())
+            .filter(|((_, old), (_, new))| old != new)
+            .map(|((limit, old), (_, new))| LimitChange {
+                limit: *limit,
+                old: *old,
+                new: *new,
+            })
+            .collect()
+    }
+
+    /// Limits after applying config_marinade instruction data
+    pub fn with_config(&self, config: &ConfigMarinadeData) -> Limits {
+        Limits {
+            min_deposit: config.min_deposit.unwrap_or(self.min_deposit),
+            min_withdraw: config.min_withdraw.unwrap_or(self.min_withdraw),
+            min_stake: config.min_stake.unwrap_or(self.min_stake),
+            staking_sol_cap: config.staking_sol_cap.unwrap_or(self.staking_sol_cap),
+            liquidity_sol_cap: config.liquidity_sol_cap.unwrap_or(self.liquidity_sol_cap),
+        }
+    }
+}
+
+/// Ordered record of limit changes, e.g. built from successive state snapshots
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct LimitsHistory {
+    pub entries: Vec<(u64, LimitChange)>, // (slot, change)
+    last: Option<Limits>,
+}
+
+impl LimitsHistory {
+    /// Record a snapshot observed at slot. Snapshots must be pushed in slot order.
+    pub fn observe(&mut self, slot: u64, limits: Limits) {
+        if let Some(last) = &self.last {
+            for change in last.changes(&limits) {
+                self.entries.push((slot, change));
+            }
+        }
+        self.last = Some(limits);
+    }
+
+    pub fn current(&self) -> Option<&Limits> {
+        self.last.as_ref()
+    }
+}
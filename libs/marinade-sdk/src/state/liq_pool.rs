@@ -1,17 +1,30 @@
 use borsh::{BorshDeserialize, BorshSerialize};
-use solana_program::{entrypoint::ProgramResult, msg, program_error::ProgramError, pubkey::Pubkey};
+use solana_program::{
+    entrypoint::ProgramResult,
+    msg,
+    program_error::ProgramError,
+    pubkey::{Pubkey, PubkeyError},
+};
 
 use crate::{
-    calc::proportional, checks::check_address, error::CommonError, located::Located,
-    state::fee::Fee, state::marinade::Marinade, ID,
+    calc::{proportional, quote_remove_liquidity, Ratio},
+    checks::check_address,
+    error::{CommonError, Mismatch},
+    located::Located,
+    program::MarinadeProgram,
+    state::fee::Fee,
+    state::marinade::Marinade,
 };
 
 #[derive(Clone, BorshDeserialize, BorshSerialize, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct LiqPool {
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_pubkey"))]
     pub lp_mint: Pubkey,
     pub lp_mint_authority_bump_seed: u8,
     pub sol_leg_bump_seed: u8,
     pub msol_leg_authority_bump_seed: u8,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_pubkey"))]
     pub msol_leg: Pubkey,
 
     //The next 3 values define the SOL/mSOL Liquidity pool fee curve params
@@ -37,21 +50,15 @@ impl LiqPool {
     pub const MSOL_LEG_SEED: &'static str = "liq_st_sol";
 
     pub fn find_lp_mint_authority(state: &Pubkey) -> (Pubkey, u8) {
-        Pubkey::find_program_address(
-            &[&state.to_bytes()[..32], Self::LP_MINT_AUTHORITY_SEED],
-            &ID,
-        )
+        MarinadeProgram::default().find_lp_mint_authority(state)
     }
 
     pub fn find_sol_leg_address(state: &Pubkey) -> (Pubkey, u8) {
-        Pubkey::find_program_address(&[&state.to_bytes()[..32], Self::SOL_LEG_SEED], &ID)
+        MarinadeProgram::default().find_liq_pool_sol_leg_address(state)
     }
 
     pub fn find_msol_leg_authority(state: &Pubkey) -> (Pubkey, u8) {
-        Pubkey::find_program_address(
-            &[&state.to_bytes()[..32], Self::MSOL_LEG_AUTHORITY_SEED],
-            &ID,
-        )
+        MarinadeProgram::default().find_liq_pool_msol_leg_authority(state)
     }
 
     pub fn default_msol_leg_address(state: &Pubkey) -> Pubkey {
@@ -85,11 +92,71 @@ impl LiqPool {
         }
     }
 
+    /// Share of the pool owned by lp_amount, fails with NumberTooHigh above lp_supply
+    pub fn share_of_pool(lp_amount: u64, lp_supply: u64) -> Result<Ratio, CommonError> {
+        Ratio::new(lp_amount, lp_supply)
+    }
+
+    /// Pro-rata part of both legs owned by lp_amount (rounded down), i.e. what removing the
+    /// liquidity pays out, see [`quote_remove_liquidity`]. Fails with NumberTooHigh above lp_supply.
+    pub fn underlying_for_lp(
+        lp_amount: u64,
+        sol_leg: u64,
+        msol_leg: u64,
+        lp_supply: u64,
+    ) -> Result<LpUnderlying, CommonError> {
+        quote_remove_liquidity(lp_amount, sol_leg, msol_leg, lp_supply)
+    }
+
+    /// Checks how removing `tokens` affects the SOL leg relative to lp_liquidity_target.
+    /// `sol_leg_balance` is the SOL leg lamports minus the rent exempt reserve.
+    pub fn plan_remove_liquidity(
+        &self,
+        tokens: u64,
+        sol_leg_balance: u64,
+    ) -> Result<RemoveLiquidityPlan, CommonError> {
+        if tokens > self.lp_supply {
+            return Err(CommonError::NumberTooHigh(Some(Mismatch::new(
+                self.lp_supply,
+                tokens,
+            ))));
+        }
+        let sol_out = proportional(tokens, sol_leg_balance, self.lp_supply)?;
+        let sol_leg_after = sol_leg_balance
+            .checked_sub(sol_out)
+            .ok_or(CommonError::CalculationFailure)?;
+        let max_removable_tokens = if sol_leg_balance <= self.lp_liquidity_target {
+            0
+        } else {
+            proportional(
+                sol_leg_balance - self.lp_liquidity_target,
+                self.lp_supply,
+                sol_leg_balance,
+            )?
+        };
+        let tokens_within_target = tokens.min(max_removable_tokens);
+        Ok(RemoveLiquidityPlan {
+            sol_out,
+            sol_leg_after,
+            max_removable_tokens,
+            tokens_within_target,
+            tokens_over_target: tokens - tokens_within_target,
+            fee_before: self.linear_fee(sol_leg_balance),
+            fee_after: self.linear_fee(sol_leg_after),
+        })
+    }
+
+    #[deprecated(note = "panics on overflow, use `try_on_lp_mint`")]
     pub fn on_lp_mint(&mut self, amount: u64) {
+        self.try_on_lp_mint(amount).expect("lp_supply overflow");
+    }
+
+    pub fn try_on_lp_mint(&mut self, amount: u64) -> Result<(), CommonError> {
         self.lp_supply = self
             .lp_supply
             .checked_add(amount)
-            .expect("lp_supply overflow");
+            .ok_or(CommonError::CalculationFailure)?;
+        Ok(())
     }
 
     pub fn on_lp_burn(&mut self, amount: u64) -> ProgramResult {
@@ -117,21 +184,97 @@ impl LiqPool {
                 result_amount,
                 self.liquidity_sol_cap
             );
-            return Err(ProgramError::Custom(3782));
+            return Err(CommonError::CapReached(Some(Mismatch::new(
+                self.liquidity_sol_cap,
+                result_amount,
+            )))
+            .into());
         }
         Ok(())
     }
 }
 
+/// Outcome of removing liquidity at current pool conditions
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RemoveLiquidityPlan {
+    /// SOL leg lamports leaving the pool for the requested tokens
+    pub sol_out: u64,
+    /// SOL leg left after the removal
+    pub sol_leg_after: u64,
+    /// Largest amount of LP tokens that keeps the SOL leg at or above lp_liquidity_target
+    pub max_removable_tokens: u64,
+    /// Part of the requested tokens that can be removed now without going under the target
+    pub tokens_within_target: u64,
+    /// Remainder that would push the pool under the target; withdraw it later
+    pub tokens_over_target: u64,
+    /// Liquid unstake fee before and after the removal
+    pub fee_before: Fee,
+    pub fee_after: Fee,
+}
+
+impl RemoveLiquidityPlan {
+    /// Removal pushes the SOL leg under the liquidity target and raises unstake fees
+    pub fn drops_below_target(&self) -> bool {
+        self.tokens_over_target > 0
+    }
+}
+
+/// SOL and mSOL that an LP position currently represents
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct LpUnderlying {
+    pub sol_amount: u64,
+    pub msol_amount: u64,
+}
+
+/// Breakdown of an LP position valued in lamports.
+/// The pool gives SOL for mSOL on liquid unstakes, so the msol part grows when
+/// users unstake and shrinks back when deposits swap against it. The LP's exposure
+/// to mSOL therefore changes without any action from the LP.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct LpExposure {
+    pub sol_amount: u64,
+    pub msol_amount: u64,
+    /// msol_amount valued at the current mSOL price
+    pub msol_value: u64,
+    /// sol_amount + msol_value
+    pub total_value: u64,
+    /// Share of total_value held as mSOL
+    pub msol_exposure: Ratio,
+}
+
+impl LpUnderlying {
+    pub fn exposure(&self, marinade: &Marinade) -> Result<LpExposure, CommonError> {
+        let msol_value = marinade.calc_lamports_from_msol_amount(self.msol_amount)?;
+        let total_value = self
+            .sol_amount
+            .checked_add(msol_value)
+            .ok_or(CommonError::CalculationFailure)?;
+        let msol_exposure = Ratio::new(msol_value, total_value)?;
+        Ok(LpExposure {
+            sol_amount: self.sol_amount,
+            msol_amount: self.msol_amount,
+            msol_value,
+            total_value,
+            msol_exposure,
+        })
+    }
+}
+
 pub trait LiqPoolHelpers {
     fn with_lp_mint_authority_seeds<R, F: FnOnce(&[&[u8]]) -> R>(&self, f: F) -> R;
+    #[deprecated(note = "panics on a corrupted bump seed, use `try_lp_mint_authority`")]
     fn lp_mint_authority(&self) -> Pubkey;
+    fn try_lp_mint_authority(&self) -> Result<Pubkey, PubkeyError>;
 
     fn with_liq_pool_sol_leg_seeds<R, F: FnOnce(&[&[u8]]) -> R>(&self, f: F) -> R;
+    #[deprecated(note = "panics on a corrupted bump seed, use `try_liq_pool_sol_leg_address`")]
     fn liq_pool_sol_leg_address(&self) -> Pubkey;
+    fn try_liq_pool_sol_leg_address(&self) -> Result<Pubkey, PubkeyError>;
 
     fn with_liq_pool_msol_leg_authority_seeds<R, F: FnOnce(&[&[u8]]) -> R>(&self, f: F) -> R;
+    #[deprecated(note = "panics on a corrupted bump seed, use `try_liq_pool_msol_leg_authority`")]
     fn liq_pool_msol_leg_authority(&self) -> Pubkey;
+    fn try_liq_pool_msol_leg_authority(&self) -> Result<Pubkey, PubkeyError>;
 
     fn check_lp_mint_authority(&self, lp_mint_authority: &Pubkey) -> ProgramResult;
     fn check_liq_pool_sol_leg_pda(&self, liq_pool_sol_leg_pda: &Pubkey) -> ProgramResult;
@@ -155,8 +298,12 @@ where
     }
 
     fn lp_mint_authority(&self) -> Pubkey {
+        self.try_lp_mint_authority().unwrap()
+    }
+
+    fn try_lp_mint_authority(&self) -> Result<Pubkey, PubkeyError> {
         self.with_lp_mint_authority_seeds(|seeds| {
-            Pubkey::create_program_address(seeds, &ID).unwrap()
+            Pubkey::create_program_address(seeds, &self.program_id())
         })
     }
 
@@ -169,8 +316,12 @@ where
     }
 
     fn liq_pool_sol_leg_address(&self) -> Pubkey {
+        self.try_liq_pool_sol_leg_address().unwrap()
+    }
+
+    fn try_liq_pool_sol_leg_address(&self) -> Result<Pubkey, PubkeyError> {
         self.with_liq_pool_sol_leg_seeds(|seeds| {
-            Pubkey::create_program_address(seeds, &ID).unwrap()
+            Pubkey::create_program_address(seeds, &self.program_id())
         })
     }
 
@@ -183,15 +334,19 @@ where
     }
 
     fn liq_pool_msol_leg_authority(&self) -> Pubkey {
+        self.try_liq_pool_msol_leg_authority().unwrap()
+    }
+
+    fn try_liq_pool_msol_leg_authority(&self) -> Result<Pubkey, PubkeyError> {
         self.with_liq_pool_msol_leg_authority_seeds(|seeds| {
-            Pubkey::create_program_address(seeds, &ID).unwrap()
+            Pubkey::create_program_address(seeds, &self.program_id())
         })
     }
 
     fn check_lp_mint_authority(&self, lp_mint_authority: &Pubkey) -> ProgramResult {
         check_address(
             lp_mint_authority,
-            &self.lp_mint_authority(),
+            &self.try_lp_mint_authority()?,
             "lp_mint_authority",
         )
     }
@@ -199,7 +354,7 @@ where
     fn check_liq_pool_sol_leg_pda(&self, liq_pool_sol_leg_pda: &Pubkey) -> ProgramResult {
         check_address(
             liq_pool_sol_leg_pda,
-            &self.liq_pool_sol_leg_address(),
+            &self.try_liq_pool_sol_leg_address()?,
             "liq_pool_sol_leg_pda",
         )
     }
@@ -210,7 +365,7 @@ where
     ) -> ProgramResult {
         check_address(
             liq_pool_msol_leg_authority,
-            &self.liq_pool_msol_leg_authority(),
+            &self.try_liq_pool_msol_leg_authority()?,
             "liq_pool_msol_leg_authority",
         )
     }
@@ -1,14 +1,19 @@
 use borsh::{BorshDeserialize, BorshSchema, BorshSerialize};
+use std::marker::PhantomData;
+
 use solana_program::{msg, program_error::ProgramError, pubkey::Pubkey};
 
 use crate::error::CommonError;
 
 #[derive(Default, Clone, BorshSerialize, BorshDeserialize, BorshSchema, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct List {
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_pubkey"))]
     pub account: Pubkey,
     pub item_size: u32,
     pub count: u32,
     // For chunked change account
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_pubkey"))]
     pub new_account: Pubkey,
     pub copied_count: u32,
 }
@@ -69,3 +74,87 @@ impl List {
             .map_err(|err| ProgramError::BorshIoError(err.to_string()))
     }
 }
+
+/// Record type of a list account
+pub trait ListRecord: BorshDeserialize {
+    /// First 8 bytes of the list account
+    const DISCRIMINATOR: &'static [u8; 8];
+    /// Borsh size of the record, list items may be larger
+    const RECORD_LEN: usize;
+    /// Name of the list in the program log
+    const LIST_NAME: &'static str;
+}
+
+/// Records of a list account read in place, one record at a time.
+/// The record count lives in the state, not in the list account
+#[derive(Debug)]
+pub struct ListView<'a, R> {
+    data: &'a [u8],
+    item_size: usize,
+    len: u32,
+    record: PhantomData<R>,
+}
+
+impl<'a, R> Clone for ListView<'a, R> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<'a, R> Copy for ListView<'a, R> {}
+
+impl<'a, R: ListRecord> ListView<'a, R> {
+    pub fn new(list: &List, data: &'a [u8]) -> Result<Self, ProgramError> {
+        if data.len() < 8 || &data[0..8] != R::DISCRIMINATOR {
+            msg!("Wrong {} account discriminator", R::LIST_NAME);
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let item_size = list.item_size() as usize;
+        if item_size < R::RECORD_LEN
+            || data.len() < List::bytes_for(list.item_size(), list.len()) as usize
+        {
+            msg!(
+                "{} account too small for {} records",
+                R::LIST_NAME,
+                list.len()
+            );
+            return Err(ProgramError::AccountDataTooSmall);
+        }
+        Ok(Self {
+            data,
+            item_size,
+            len: list.len(),
+            record: PhantomData,
+        })
+    }
+
+    pub fn len(&self) -> u32 {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Raw bytes of the record at index
+    pub fn record_data(&self, index: u32) -> Option<&'a [u8]> {
+        if index >= self.len {
+            return None;
+        }
+        let start = 8 + index as usize * self.item_size;
+        Some(&self.data[start..start + self.item_size])
+    }
+
+    pub fn get(&self, index: u32) -> Option<R> {
+        self.record_data(index)
+            .and_then(|mut data| R::deserialize(&mut data).ok())
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = R> + 'a
+    where
+        R: 'a,
+    {
+        let list = *self;
+        (0..list.len).filter_map(move |index| list.get(index))
+    }
+}
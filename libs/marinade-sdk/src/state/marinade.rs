@@ -6,48 +6,71 @@ use solana_program::{
     msg,
     program_error::ProgramError,
     program_pack::Pack,
-    pubkey::Pubkey,
+    pubkey::{Pubkey, PubkeyError},
     stake, system_program,
-    sysvar::{clock, rent},
+    sysvar::{clock, epoch_schedule, rent, stake_history},
 };
 
 use crate::instructions::add_liquidity::{AddLiquidityAccounts, AddLiquidityData};
+use crate::instructions::add_validator::{AddValidatorAccounts, AddValidatorData};
 use crate::instructions::change_authority::{ChangeAuthorityAccounts, ChangeAuthorityData};
 use crate::instructions::claim::{ClaimAccounts, ClaimData};
 use crate::instructions::config_lp::{ConfigLpAccounts, ConfigLpData};
+use crate::instructions::config_marinade::{ConfigMarinadeAccounts, ConfigMarinadeData};
+use crate::instructions::config_validator_system::{
+    ConfigValidatorSystemAccounts, ConfigValidatorSystemData,
+};
+use crate::instructions::deactivate_stake::{DeactivateStakeAccounts, DeactivateStakeData};
 use crate::instructions::deposit::{DepositAccounts, DepositData};
 use crate::instructions::deposit_stake_account::{
     DepositStakeAccountAccounts, DepositStakeAccountData,
 };
+use crate::instructions::emergency_unstake::{EmergencyUnstakeAccounts, EmergencyUnstakeData};
+use crate::instructions::initialize::{
+    InitializeAccounts, InitializeData, LiqPoolInitializeAccounts,
+};
 use crate::instructions::liquid_unstake::{LiquidUnstakeAccounts, LiquidUnstakeData};
+use crate::instructions::merge_stakes::{MergeStakesAccounts, MergeStakesData};
 use crate::instructions::order_unstake::{OrderUnstakeAccounts, OrderUnstakeData};
+use crate::instructions::partial_unstake::{PartialUnstakeAccounts, PartialUnstakeData};
+use crate::instructions::pause::{PauseAccounts, PauseData, ResumeAccounts, ResumeData};
 use crate::instructions::remove_liquidity::{RemoveLiquidityAccounts, RemoveLiquidityData};
+use crate::instructions::remove_validator::{RemoveValidatorAccounts, RemoveValidatorData};
+use crate::instructions::set_validator_score::{SetValidatorScoreAccounts, SetValidatorScoreData};
+use crate::instructions::stake_reserve::{StakeReserveAccounts, StakeReserveData};
 use crate::{
-    calc::{shares_from_value, value_from_shares},
+    calc::{shares_from_value, value_from_shares, Ratio},
+    capabilities::{Capabilities, Capability, UnsupportedByProgram},
     checks::check_address,
-    error::CommonError,
+    error::{CommonError, Mismatch},
     located::Located,
+    program::MarinadeProgram,
     state::{
-        fee::Fee,
+        fee::{Fee, FeeCents},
+        limits::Limits,
         liq_pool::{LiqPool, LiqPoolHelpers},
-        stake_system::StakeSystem,
-        validator_system::{ValidatorRecord, ValidatorSystem},
+        stake_system::{StakeSystem, StakeSystemHelpers},
+        validator_system::ValidatorSystem,
     },
-    ID,
 };
 use micro_anchor::{AccountDeserialize, Discriminator, InstructionBuilder, Owner};
 use std::mem::MaybeUninit;
 
 #[derive(Debug, BorshSerialize, BorshDeserialize, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Marinade {
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_pubkey"))]
     pub msol_mint: Pubkey,
 
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_pubkey"))]
     pub admin_authority: Pubkey,
 
     // Target for withdrawing rent reserve SOLs. Save bot wallet account here
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_pubkey"))]
     pub operational_sol_account: Pubkey,
     // treasury - external accounts managed by marinade DAO
     // pub treasury_sol_account: Pubkey,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_pubkey"))]
     pub treasury_msol_account: Pubkey,
 
     // Bump seeds:
@@ -85,6 +108,92 @@ pub struct Marinade {
     pub emergency_cooling_down: u64,
 }
 
+/// Fields newer program versions append to the state account after emergency_cooling_down.
+/// Older deployments leave that space zeroed, which reads as not paused and no fee
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, BorshSerialize, BorshDeserialize)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MarinadeExtension {
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_pubkey"))]
+    pub pause_authority: Pubkey,
+    pub paused: bool,
+    /// Taken on the mSOL of order_unstake, zero unless enabled by config
+    pub delayed_unstake_fee: FeeCents,
+}
+
+impl MarinadeExtension {
+    /// Reads the extension from the state account data (discriminator included),
+    /// the default when the account is too short to hold it
+    pub fn from_account_data(data: &[u8]) -> Self {
+        data.get(Marinade::serialized_len()..)
+            .and_then(|mut extension| Self::deserialize(&mut extension).ok())
+            .unwrap_or_default()
+    }
+}
+
+/// Result of stake-delta calculation
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StakeDelta {
+    /// lamports available in reserve to be staked
+    Stake(u64),
+    /// lamports that must be unstaked to satisfy circulating tickets
+    Unstake(u64),
+    Neutral,
+}
+
+/// Interpretation of staking_sol_cap, where u64::MAX means there is no cap
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StakingCap {
+    Unlimited,
+    Limited(u64),
+}
+
+impl StakingCap {
+    pub const UNLIMITED_SENTINEL: u64 = u64::MAX;
+
+    pub fn from_raw(staking_sol_cap: u64) -> Self {
+        if staking_sol_cap == Self::UNLIMITED_SENTINEL {
+            StakingCap::Unlimited
+        } else {
+            StakingCap::Limited(staking_sol_cap)
+        }
+    }
+
+    pub fn to_raw(self) -> u64 {
+        match self {
+            StakingCap::Unlimited => Self::UNLIMITED_SENTINEL,
+            StakingCap::Limited(cap) => cap,
+        }
+    }
+
+    pub fn is_unlimited(&self) -> bool {
+        matches!(self, StakingCap::Unlimited)
+    }
+}
+
+impl std::fmt::Display for StakingCap {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            StakingCap::Unlimited => write!(f, "unlimited"),
+            StakingCap::Limited(cap) => write!(f, "{} lamports", cap),
+        }
+    }
+}
+
+/// Lamports being deactivated by emergency_unstake/partial_unstake.
+/// They still count in total_lamports_under_control (so mSOL price is not affected),
+/// but stake-delta never re-stakes them: they return to the reserve only after the stake
+/// accounts finish deactivating (next epoch) and the crank runs update_deactivated.
+/// Until then stake-delta may unstake less than the tickets need, because it expects these
+/// lamports to come back to the reserve.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct EmergencyCoolingDown {
+    pub lamports: u64,
+    /// Share of total_lamports_under_control
+    pub share_of_tvl: Ratio,
+    /// Part of the stake-delta unstake amount covered by these lamports
+    pub covers_unstake: u64,
+}
+
 impl Marinade {
     pub const PRICE_DENOMINATOR: u64 = 0x1_0000_0000;
     /// Suffix for reserve account seed
@@ -104,22 +213,19 @@ impl Marinade {
     }
 
     pub fn find_msol_mint_authority(state: &Pubkey) -> (Pubkey, u8) {
-        Pubkey::find_program_address(
-            &[&state.to_bytes()[..32], Marinade::MSOL_MINT_AUTHORITY_SEED],
-            &ID,
-        )
+        MarinadeProgram::default().find_msol_mint_authority(state)
     }
 
     pub fn find_reserve_address(state: &Pubkey) -> (Pubkey, u8) {
-        Pubkey::find_program_address(&[&state.to_bytes()[..32], Self::RESERVE_SEED], &ID)
+        MarinadeProgram::default().find_reserve_address(state)
     }
 
     pub fn default_stake_list_address(state: &Pubkey) -> Pubkey {
-        Pubkey::create_with_seed(state, Self::STAKE_LIST_SEED, &ID).unwrap()
+        MarinadeProgram::default().default_stake_list_address(state)
     }
 
     pub fn default_validator_list_address(state: &Pubkey) -> Pubkey {
-        Pubkey::create_with_seed(state, Self::VALIDATOR_LIST_SEED, &ID).unwrap()
+        MarinadeProgram::default().default_validator_list_address(state)
     }
 
     pub fn check_admin_authority(&self, admin_authority: &Pubkey) -> ProgramResult {
@@ -188,26 +294,102 @@ impl Marinade {
         check_address(msol_mint, &self.msol_mint, "msol_mint")
     }
 
+    pub fn validators_len(&self) -> u32 {
+        self.validator_system.validators_len()
+    }
+
+    pub fn stakes_len(&self) -> u32 {
+        self.stake_system.stakes_len()
+    }
+
+    pub fn limits(&self) -> Limits {
+        Limits {
+            min_deposit: self.min_deposit,
+            min_withdraw: self.min_withdraw,
+            min_stake: self.stake_system.min_stake,
+            staking_sol_cap: self.staking_sol_cap,
+            liquidity_sol_cap: self.liq_pool.liquidity_sol_cap,
+        }
+    }
+
+    pub fn is_emergency_unstake_in_progress(&self) -> bool {
+        self.emergency_cooling_down > 0
+    }
+
+    pub fn emergency_cooling_down_status(
+        &self,
+        reserve_balance: u64,
+    ) -> Result<Option<EmergencyCoolingDown>, CommonError> {
+        if !self.is_emergency_unstake_in_progress() {
+            return Ok(None);
+        }
+        let share_of_tvl = Ratio::new(
+            self.emergency_cooling_down,
+            self.try_total_lamports_under_control()?,
+        )?;
+        // raw delta without emergency; negative part covered by emergency_cooling_down
+        let without_emergency = self.stake_delta_without_emergency(reserve_balance);
+        let covers_unstake = if without_emergency < 0 {
+            ((-without_emergency).min(self.emergency_cooling_down as i128)) as u64
+        } else {
+            0
+        };
+        Ok(Some(EmergencyCoolingDown {
+            lamports: self.emergency_cooling_down,
+            share_of_tvl,
+            covers_unstake,
+        }))
+    }
+
+    #[deprecated(note = "panics on overflow, use `try_total_cooling_down`")]
     pub fn total_cooling_down(&self) -> u64 {
+        self.try_total_cooling_down()
+            .expect("Total cooling down overflow")
+    }
+
+    pub fn try_total_cooling_down(&self) -> Result<u64, CommonError> {
         self.stake_system
             .delayed_unstake_cooling_down
             .checked_add(self.emergency_cooling_down)
-            .expect("Total cooling down overflow")
+            .ok_or(CommonError::CalculationFailure)
     }
 
-    /// total_active_balance + total_cooling_down + available_reserve_balance
+    #[deprecated(note = "panics on overflow, use `try_total_lamports_under_control`")]
     pub fn total_lamports_under_control(&self) -> u64 {
+        self.try_total_lamports_under_control()
+            .expect("Total SOLs under control overflow")
+    }
+
+    /// total_active_balance + total_cooling_down + available_reserve_balance
+    pub fn try_total_lamports_under_control(&self) -> Result<u64, CommonError> {
         self.validator_system
             .total_active_balance
-            .checked_add(self.total_cooling_down())
-            .expect("Stake balance overflow")
-            .checked_add(self.available_reserve_balance) // reserve_pda.lamports() - self.rent_exempt_for_token_acc
-            .expect("Total SOLs under control overflow")
+            .checked_add(self.try_total_cooling_down()?)
+            .and_then(|lamports| lamports.checked_add(self.available_reserve_balance)) // reserve_pda.lamports() - self.rent_exempt_for_token_acc
+            .ok_or(CommonError::CalculationFailure)
+    }
+
+    pub fn staking_cap(&self) -> StakingCap {
+        StakingCap::from_raw(self.staking_sol_cap)
     }
 
+    /// Lamports that can still be staked before reaching the cap. None if there is no cap
+    pub fn staking_cap_headroom(&self) -> Option<u64> {
+        match self.staking_cap() {
+            StakingCap::Unlimited => None,
+            // lamports under control past u64::MAX are past any cap
+            StakingCap::Limited(cap) => Some(
+                self.try_total_lamports_under_control()
+                    .map_or(0, |lamports| cap.saturating_sub(lamports)),
+            ),
+        }
+    }
+
+    /// The program check, with no special case for the unlimited cap: u64::MAX is never exceeded
+    /// without overflowing first
     pub fn check_staking_cap(&self, transfering_lamports: u64) -> ProgramResult {
         let result_amount = self
-            .total_lamports_under_control()
+            .try_total_lamports_under_control()?
             .checked_add(transfering_lamports)
             .ok_or_else(|| {
                 msg!("SOL overflow");
@@ -219,22 +401,33 @@ impl Marinade {
                 result_amount,
                 self.staking_sol_cap
             );
-            return Err(ProgramError::Custom(3782));
+            return Err(CommonError::CapReached(Some(Mismatch::new(
+                self.staking_sol_cap,
+                result_amount,
+            )))
+            .into());
         }
         Ok(())
     }
 
+    #[deprecated(note = "panics on overflow, use `try_total_virtual_staked_lamports`")]
     pub fn total_virtual_staked_lamports(&self) -> u64 {
+        self.try_total_virtual_staked_lamports()
+            .expect("Total SOLs under control overflow")
+    }
+
+    pub fn try_total_virtual_staked_lamports(&self) -> Result<u64, CommonError> {
         // if we get slashed it may be negative but we must use 0 instead
-        self.total_lamports_under_control()
-            .saturating_sub(self.circulating_ticket_balance) //tickets created -> cooling down lamports or lamports already in reserve and not claimed yet
+        Ok(self
+            .try_total_lamports_under_control()?
+            .saturating_sub(self.circulating_ticket_balance)) //tickets created -> cooling down lamports or lamports already in reserve and not claimed yet
     }
 
     /// calculate the amount of msol tokens corresponding to certain lamport amount
     pub fn calc_msol_from_lamports(&self, stake_lamports: u64) -> Result<u64, CommonError> {
         shares_from_value(
             stake_lamports,
-            self.total_virtual_staked_lamports(),
+            self.try_total_virtual_staked_lamports()?,
             self.msol_supply,
         )
     }
@@ -243,13 +436,39 @@ impl Marinade {
     pub fn calc_lamports_from_msol_amount(&self, msol_amount: u64) -> Result<u64, CommonError> {
         value_from_shares(
             msol_amount,
-            self.total_virtual_staked_lamports(),
+            self.try_total_virtual_staked_lamports()?,
             self.msol_supply,
         )
     }
 
-    // **i128**: when do staking/unstaking use real reserve balance instead of virtual field
+    #[deprecated(note = "use `stake_delta_direction` which returns a typed StakeDelta")]
     pub fn stake_delta(&self, reserve_balance: u64) -> i128 {
+        self.raw_stake_delta(reserve_balance)
+    }
+
+    /// What the stake-delta crank has to do given the real reserve balance (not the virtual field).
+    /// Positive raw delta means lamports in reserve can be staked, negative means stake must be
+    /// deactivated to cover tickets. Lamports of emergency_cooling_down are never staked again
+    /// directly, but they do reduce the amount to unstake because they will return to the reserve.
+    pub fn stake_delta_direction(&self, reserve_balance: u64) -> Result<StakeDelta, CommonError> {
+        let delta = self.raw_stake_delta(reserve_balance);
+        Ok(if delta > 0 {
+            StakeDelta::Stake(u64::try_from(delta).map_err(|_| CommonError::CalculationFailure)?)
+        } else if delta < 0 {
+            StakeDelta::Unstake(u64::try_from(-delta).map_err(|_| CommonError::CalculationFailure)?)
+        } else {
+            StakeDelta::Neutral
+        })
+    }
+
+    fn stake_delta_without_emergency(&self, reserve_balance: u64) -> i128 {
+        reserve_balance.saturating_sub(self.rent_exempt_for_token_acc) as i128
+            + self.stake_system.delayed_unstake_cooling_down as i128
+            - self.circulating_ticket_balance as i128
+    }
+
+    // **i128**: when do staking/unstaking use real reserve balance instead of virtual field
+    fn raw_stake_delta(&self, reserve_balance: u64) -> i128 {
         // Never try to stake lamports from emergency_cooling_down
         // (we must wait for update-deactivated first to keep SOLs for claiming on reserve)
         // But if we need to unstake without counting emergency_cooling_down and we have emergency cooling down
@@ -257,9 +476,7 @@ impl Marinade {
         // preventing unstake duplication by recalculating stake-delta for negative values
 
         // OK. Lets get stake_delta without emergency first
-        let raw = reserve_balance.saturating_sub(self.rent_exempt_for_token_acc) as i128
-            + self.stake_system.delayed_unstake_cooling_down as i128
-            - self.circulating_ticket_balance as i128;
+        let raw = self.stake_delta_without_emergency(reserve_balance);
         if raw >= 0 {
             // When it >= 0 it is right value to use
             raw
@@ -271,11 +488,18 @@ impl Marinade {
         }
     }
 
+    #[deprecated(note = "panics on overflow, use `try_on_transfer_to_reserve`")]
     pub fn on_transfer_to_reserve(&mut self, amount: u64) {
+        self.try_on_transfer_to_reserve(amount)
+            .expect("reserve balance overflow");
+    }
+
+    pub fn try_on_transfer_to_reserve(&mut self, amount: u64) -> Result<(), CommonError> {
         self.available_reserve_balance = self
             .available_reserve_balance
             .checked_add(amount)
-            .expect("reserve balance overflow");
+            .ok_or(CommonError::CalculationFailure)?;
+        Ok(())
     }
 
     pub fn on_transfer_from_reserve(&mut self, amount: u64) -> ProgramResult {
@@ -286,11 +510,17 @@ impl Marinade {
         Ok(())
     }
 
+    #[deprecated(note = "panics on overflow, use `try_on_msol_mint`")]
     pub fn on_msol_mint(&mut self, amount: u64) {
+        self.try_on_msol_mint(amount).expect("msol supply overflow");
+    }
+
+    pub fn try_on_msol_mint(&mut self, amount: u64) -> Result<(), CommonError> {
         self.msol_supply = self
             .msol_supply
             .checked_add(amount)
-            .expect("msol supply overflow");
+            .ok_or(CommonError::CalculationFailure)?;
+        Ok(())
     }
 
     pub fn on_msol_burn(&mut self, amount: u64) -> ProgramResult {
@@ -303,18 +533,47 @@ impl Marinade {
 }
 
 pub trait MarinadeHelpers {
+    #[deprecated(note = "panics on a corrupted bump seed, use `try_msol_mint_authority`")]
     fn msol_mint_authority(&self) -> Pubkey;
+    fn try_msol_mint_authority(&self) -> Result<Pubkey, PubkeyError>;
     fn with_msol_mint_authority_seeds<R, F: FnOnce(&[&[u8]]) -> R>(&self, f: F) -> R;
 
+    #[deprecated(note = "panics on a corrupted bump seed, use `try_reserve_address`")]
     fn reserve_address(&self) -> Pubkey;
+    fn try_reserve_address(&self) -> Result<Pubkey, PubkeyError>;
     fn with_reserve_seeds<R, F: FnOnce(&[&[u8]]) -> R>(&self, f: F) -> R;
 
     fn check_reserve_address(&self, reserve: &Pubkey) -> ProgramResult;
     fn check_msol_mint_authority(&self, msol_mint_authority: &Pubkey) -> ProgramResult;
 
     // Instructions
+    // The ones deriving PDAs from the stored bump seeds fail on a corrupted bump instead of panicking
     fn config_lp_instruction(&self, data: ConfigLpData) -> Instruction;
+    fn config_marinade_instruction(&self, data: ConfigMarinadeData) -> Instruction;
     fn change_authority_instruction(&self, data: ChangeAuthorityData) -> Instruction;
+    /// pause_authority is MarinadeExtension::pause_authority. Fails when capabilities (see
+    /// client::detect_capabilities) lack Capability::Pause
+    fn pause_instruction(
+        &self,
+        capabilities: &Capabilities,
+        pause_authority: Pubkey,
+    ) -> Result<Instruction, UnsupportedByProgram>;
+    /// See pause_instruction
+    fn resume_instruction(
+        &self,
+        capabilities: &Capabilities,
+        pause_authority: Pubkey,
+    ) -> Result<Instruction, UnsupportedByProgram>;
+    fn deposit_stake_account(
+        &self,
+        data: DepositStakeAccountData,
+        stake_account: Pubkey,
+        stake_authority: Pubkey,
+        mint_to: Pubkey,
+        validator_vote: Pubkey,
+        rent_payer: Pubkey,
+    ) -> Result<Instruction, PubkeyError>;
+    #[deprecated(note = "renamed to `deposit_stake_account`")]
     fn deposit_stake_accounts(
         &self,
         data: DepositStakeAccountData,
@@ -323,14 +582,28 @@ pub trait MarinadeHelpers {
         mint_to: Pubkey,
         validator_vote: Pubkey,
         rent_payer: Pubkey,
-    ) -> Instruction;
-    fn deposit(&self, data: DepositData, transfer_from: Pubkey, mint_to: Pubkey) -> Instruction;
+    ) -> Result<Instruction, PubkeyError> {
+        self.deposit_stake_account(
+            data,
+            stake_account,
+            stake_authority,
+            mint_to,
+            validator_vote,
+            rent_payer,
+        )
+    }
+    fn deposit(
+        &self,
+        data: DepositData,
+        transfer_from: Pubkey,
+        mint_to: Pubkey,
+    ) -> Result<Instruction, PubkeyError>;
     fn add_liquidity(
         &self,
         data: AddLiquidityData,
         transfer_from: Pubkey,
         mint_to: Pubkey,
-    ) -> Instruction;
+    ) -> Result<Instruction, PubkeyError>;
     fn remove_liquidity(
         &self,
         data: RemoveLiquidityData,
@@ -338,15 +611,19 @@ pub trait MarinadeHelpers {
         burn_from_authority: Pubkey,
         transfer_sol_to: Pubkey,
         transfer_msol_to: Pubkey,
-    ) -> Instruction;
-    fn claim(&self, ticket_account: Pubkey, transfer_sol_to: Pubkey) -> Instruction;
+    ) -> Result<Instruction, PubkeyError>;
+    fn claim(
+        &self,
+        ticket_account: Pubkey,
+        transfer_sol_to: Pubkey,
+    ) -> Result<Instruction, PubkeyError>;
     fn liquid_unstake(
         &self,
         data: LiquidUnstakeData,
         get_msol_from: Pubkey,
         get_msol_from_authority: Pubkey,
         transfer_sol_to: Pubkey,
-    ) -> Instruction;
+    ) -> Result<Instruction, PubkeyError>;
     fn order_unstake(
         &self,
         data: OrderUnstakeData,
@@ -354,6 +631,52 @@ pub trait MarinadeHelpers {
         burn_msol_authority: Pubkey, // delegated or owner
         new_ticket_account: Pubkey,
     ) -> Instruction;
+
+    // Validator manager and bot instructions, signed by the validator manager authority
+    // where the program requires it
+    fn config_validator_system_instruction(&self, data: ConfigValidatorSystemData) -> Instruction;
+    fn add_validator(
+        &self,
+        data: AddValidatorData,
+        validator_vote: Pubkey,
+        rent_payer: Pubkey,
+    ) -> Instruction;
+    fn remove_validator(&self, data: RemoveValidatorData) -> Instruction;
+    fn set_validator_score(&self, data: SetValidatorScoreData) -> Instruction;
+    fn stake_reserve(
+        &self,
+        data: StakeReserveData,
+        validator_vote: Pubkey,
+        stake_account: Pubkey, // new signer, created beforehand by the caller
+    ) -> Result<Instruction, PubkeyError>;
+    fn deactivate_stake(
+        &self,
+        data: DeactivateStakeData,
+        stake_account: Pubkey,
+        split_stake_account: Pubkey,
+        split_stake_rent_payer: Pubkey,
+    ) -> Result<Instruction, PubkeyError>;
+    fn emergency_unstake(
+        &self,
+        data: EmergencyUnstakeData,
+        stake_account: Pubkey,
+    ) -> Result<Instruction, PubkeyError>;
+    fn partial_unstake(
+        &self,
+        data: PartialUnstakeData,
+        stake_account: Pubkey,
+        split_stake_account: Pubkey,
+        split_stake_rent_payer: Pubkey,
+    ) -> Result<Instruction, PubkeyError>;
+    fn merge_stakes(
+        &self,
+        data: MergeStakesData,
+        destination_stake: Pubkey,
+        source_stake: Pubkey,
+    ) -> Result<Instruction, PubkeyError>;
+    /// For a state account not created yet: the PDAs are searched instead of using the stored
+    /// bump seeds, the other addresses are taken from the prepared state
+    fn initialize(&self, data: InitializeData, creator_authority: Pubkey) -> Instruction;
 }
 
 impl<T> MarinadeHelpers for T
@@ -361,8 +684,12 @@ where
     T: Located<Marinade>,
 {
     fn msol_mint_authority(&self) -> Pubkey {
+        self.try_msol_mint_authority().unwrap()
+    }
+
+    fn try_msol_mint_authority(&self) -> Result<Pubkey, PubkeyError> {
         self.with_msol_mint_authority_seeds(|seeds| {
-            Pubkey::create_program_address(seeds, &ID).unwrap()
+            Pubkey::create_program_address(seeds, &self.program_id())
         })
     }
 
@@ -375,7 +702,11 @@ where
     }
 
     fn reserve_address(&self) -> Pubkey {
-        self.with_reserve_seeds(|seeds| Pubkey::create_program_address(seeds, &ID).unwrap())
+        self.try_reserve_address().unwrap()
+    }
+
+    fn try_reserve_address(&self) -> Result<Pubkey, PubkeyError> {
+        self.with_reserve_seeds(|seeds| Pubkey::create_program_address(seeds, &self.program_id()))
     }
 
     fn with_reserve_seeds<R, F: FnOnce(&[&[u8]]) -> R>(&self, f: F) -> R {
@@ -387,13 +718,13 @@ where
     }
 
     fn check_reserve_address(&self, reserve: &Pubkey) -> ProgramResult {
-        check_address(reserve, &self.reserve_address(), "reserve")
+        check_address(reserve, &self.try_reserve_address()?, "reserve")
     }
 
     fn check_msol_mint_authority(&self, msol_mint_authority: &Pubkey) -> ProgramResult {
         check_address(
             msol_mint_authority,
-            &self.msol_mint_authority(),
+            &self.try_msol_mint_authority()?,
             "msol_mint_authority",
         )
     }
@@ -407,7 +738,18 @@ where
             },
             data,
         };
-        (&builder).into()
+        builder.instruction(self.program_id())
+    }
+
+    fn config_marinade_instruction(&self, data: ConfigMarinadeData) -> Instruction {
+        let builder = InstructionBuilder {
+            accounts: ConfigMarinadeAccounts {
+                marinade: self.key(),
+                admin_authority: self.as_ref().admin_authority,
+            },
+            data,
+        };
+        builder.instruction(self.program_id())
     }
 
     fn change_authority_instruction(&self, data: ChangeAuthorityData) -> Instruction {
@@ -418,10 +760,42 @@ where
             },
             data,
         };
-        (&builder).into()
+        builder.instruction(self.program_id())
     }
 
-    fn deposit_stake_accounts(
+    fn pause_instruction(
+        &self,
+        capabilities: &Capabilities,
+        pause_authority: Pubkey,
+    ) -> Result<Instruction, UnsupportedByProgram> {
+        capabilities.require(Capability::Pause)?;
+        let builder = InstructionBuilder {
+            accounts: PauseAccounts {
+                marinade: self.key(),
+                pause_authority,
+            },
+            data: PauseData::new(),
+        };
+        Ok(builder.instruction(self.program_id()))
+    }
+
+    fn resume_instruction(
+        &self,
+        capabilities: &Capabilities,
+        pause_authority: Pubkey,
+    ) -> Result<Instruction, UnsupportedByProgram> {
+        capabilities.require(Capability::Pause)?;
+        let builder = InstructionBuilder {
+            accounts: ResumeAccounts {
+                marinade: self.key(),
+                pause_authority,
+            },
+            data: ResumeData::new(),
+        };
+        Ok(builder.instruction(self.program_id()))
+    }
+
+    fn deposit_stake_account(
         &self,
         data: DepositStakeAccountData,
         stake_account: Pubkey,
@@ -429,7 +803,7 @@ where
         mint_to: Pubkey,
         validator_vote: Pubkey,
         rent_payer: Pubkey,
-    ) -> Instruction {
+    ) -> Result<Instruction, PubkeyError> {
         let builder = InstructionBuilder {
             accounts: DepositStakeAccountAccounts {
                 marinade: self.key(),
@@ -437,15 +811,13 @@ where
                 stake_list: *self.as_ref().stake_system.stake_list_address(),
                 stake_account,
                 stake_authority,
-                duplication_flag: ValidatorRecord::find_duplication_flag(
-                    &self.key(),
-                    &validator_vote,
-                )
-                .0,
+                duplication_flag: MarinadeProgram::new(self.program_id())
+                    .find_duplication_flag(&self.key(), &validator_vote)
+                    .0,
                 rent_payer,
                 msol_mint: self.as_ref().msol_mint,
                 mint_to,
-                msol_mint_authority: self.msol_mint_authority(),
+                msol_mint_authority: self.try_msol_mint_authority()?,
                 clock: clock::id(),
                 rent: rent::id(),
                 system_program: system_program::ID,
@@ -454,27 +826,32 @@ where
             },
             data,
         };
-        (&builder).into()
+        Ok(builder.instruction(self.program_id()))
     }
 
-    fn deposit(&self, data: DepositData, transfer_from: Pubkey, mint_to: Pubkey) -> Instruction {
+    fn deposit(
+        &self,
+        data: DepositData,
+        transfer_from: Pubkey,
+        mint_to: Pubkey,
+    ) -> Result<Instruction, PubkeyError> {
         let builder = InstructionBuilder {
             accounts: DepositAccounts {
                 marinade: self.key(),
                 msol_mint: self.as_ref().msol_mint,
-                liq_pool_sol_leg_pda: self.liq_pool_sol_leg_address(),
+                liq_pool_sol_leg_pda: self.try_liq_pool_sol_leg_address()?,
                 liq_pool_msol_leg: self.as_ref().liq_pool.msol_leg,
-                liq_pool_msol_leg_authority: self.liq_pool_msol_leg_authority(),
-                reserve_pda: self.reserve_address(),
+                liq_pool_msol_leg_authority: self.try_liq_pool_msol_leg_authority()?,
+                reserve_pda: self.try_reserve_address()?,
                 transfer_from,
                 mint_to,
-                msol_mint_authority: self.msol_mint_authority(),
+                msol_mint_authority: self.try_msol_mint_authority()?,
                 system_program: system_program::ID,
                 token_program: spl_token::ID,
             },
             data,
         };
-        (&builder).into()
+        Ok(builder.instruction(self.program_id()))
     }
 
     fn add_liquidity(
@@ -482,13 +859,13 @@ where
         data: AddLiquidityData,
         transfer_from: Pubkey,
         mint_to: Pubkey,
-    ) -> Instruction {
+    ) -> Result<Instruction, PubkeyError> {
         let builder = InstructionBuilder {
             accounts: AddLiquidityAccounts {
                 marinade: self.key(),
                 lp_mint: self.as_ref().liq_pool.lp_mint,
-                lp_mint_authority: self.lp_mint_authority(),
-                liq_pool_sol_leg_pda: self.liq_pool_sol_leg_address(),
+                lp_mint_authority: self.try_lp_mint_authority()?,
+                liq_pool_sol_leg_pda: self.try_liq_pool_sol_leg_address()?,
                 liq_pool_msol_leg: self.as_ref().liq_pool.msol_leg,
                 transfer_from,
                 mint_to,
@@ -497,7 +874,7 @@ where
             },
             data,
         };
-        (&builder).into()
+        Ok(builder.instruction(self.program_id()))
     }
 
     fn remove_liquidity(
@@ -507,7 +884,7 @@ where
         burn_from_authority: Pubkey,
         transfer_sol_to: Pubkey,
         transfer_msol_to: Pubkey,
-    ) -> Instruction {
+    ) -> Result<Instruction, PubkeyError> {
         let builder = InstructionBuilder {
             accounts: RemoveLiquidityAccounts {
                 marinade: self.key(),
@@ -516,23 +893,27 @@ where
                 burn_from_authority,
                 transfer_sol_to,
                 transfer_msol_to,
-                liq_pool_sol_leg_pda: self.liq_pool_sol_leg_address(),
+                liq_pool_sol_leg_pda: self.try_liq_pool_sol_leg_address()?,
                 liq_pool_msol_leg: self.as_ref().liq_pool.msol_leg,
-                liq_pool_msol_leg_authority: self.liq_pool_msol_leg_authority(),
+                liq_pool_msol_leg_authority: self.try_liq_pool_msol_leg_authority()?,
                 system_program: system_program::ID,
                 token_program: spl_token::ID,
             },
             data,
         };
-        (&builder).into()
+        Ok(builder.instruction(self.program_id()))
     }
 
-    fn claim(&self, ticket_account: Pubkey, transfer_sol_to: Pubkey) -> Instruction {
+    fn claim(
+        &self,
+        ticket_account: Pubkey,
+        transfer_sol_to: Pubkey,
+    ) -> Result<Instruction, PubkeyError> {
         let data = ClaimData {};
         let builder = InstructionBuilder {
             accounts: ClaimAccounts {
                 marinade: self.key(),
-                reserve_pda: self.reserve_address(),
+                reserve_pda: self.try_reserve_address()?,
                 ticket_account,
                 transfer_sol_to,
                 system_program: system_program::ID,
@@ -540,7 +921,7 @@ where
             },
             data,
         };
-        (&builder).into()
+        Ok(builder.instruction(self.program_id()))
     }
 
     fn liquid_unstake(
@@ -549,12 +930,12 @@ where
         get_msol_from: Pubkey,
         get_msol_from_authority: Pubkey,
         transfer_sol_to: Pubkey,
-    ) -> Instruction {
+    ) -> Result<Instruction, PubkeyError> {
         let builder = InstructionBuilder {
             accounts: LiquidUnstakeAccounts {
                 marinade: self.key(),
                 msol_mint: self.as_ref().msol_mint,
-                liq_pool_sol_leg_pda: self.liq_pool_sol_leg_address(),
+                liq_pool_sol_leg_pda: self.try_liq_pool_sol_leg_address()?,
                 liq_pool_msol_leg: self.as_ref().liq_pool.msol_leg,
                 get_msol_from,
                 get_msol_from_authority,
@@ -565,7 +946,7 @@ where
             },
             data,
         };
-        (&builder).into()
+        Ok(builder.instruction(self.program_id()))
     }
 
     fn order_unstake(
@@ -588,7 +969,229 @@ where
             },
             data,
         };
-        (&builder).into()
+        builder.instruction(self.program_id())
+    }
+
+    fn config_validator_system_instruction(&self, data: ConfigValidatorSystemData) -> Instruction {
+        let builder = InstructionBuilder {
+            accounts: ConfigValidatorSystemAccounts {
+                marinade: self.key(),
+                manager_authority: self.as_ref().validator_system.manager_authority,
+            },
+            data,
+        };
+        builder.instruction(self.program_id())
+    }
+
+    fn add_validator(
+        &self,
+        data: AddValidatorData,
+        validator_vote: Pubkey,
+        rent_payer: Pubkey,
+    ) -> Instruction {
+        let builder = InstructionBuilder {
+            accounts: AddValidatorAccounts {
+                marinade: self.key(),
+                manager_authority: self.as_ref().validator_system.manager_authority,
+                validator_list: *self.as_ref().validator_system.validator_list_address(),
+                validator_vote,
+                duplication_flag: MarinadeProgram::new(self.program_id())
+                    .find_duplication_flag(&self.key(), &validator_vote)
+                    .0,
+                rent_payer,
+                clock: clock::ID,
+                rent: rent::ID,
+                system_program: system_program::ID,
+            },
+            data,
+        };
+        builder.instruction(self.program_id())
+    }
+
+    fn remove_validator(&self, data: RemoveValidatorData) -> Instruction {
+        let builder = InstructionBuilder {
+            accounts: RemoveValidatorAccounts {
+                marinade: self.key(),
+                manager_authority: self.as_ref().validator_system.manager_authority,
+                validator_list: *self.as_ref().validator_system.validator_list_address(),
+                duplication_flag: MarinadeProgram::new(self.program_id())
+                    .find_duplication_flag(&self.key(), &data.validator_vote)
+                    .0,
+                operational_sol_account: self.as_ref().operational_sol_account,
+            },
+            data,
+        };
+        builder.instruction(self.program_id())
+    }
+
+    fn set_validator_score(&self, data: SetValidatorScoreData) -> Instruction {
+        let builder = InstructionBuilder {
+            accounts: SetValidatorScoreAccounts {
+                marinade: self.key(),
+                manager_authority: self.as_ref().validator_system.manager_authority,
+                validator_list: *self.as_ref().validator_system.validator_list_address(),
+            },
+            data,
+        };
+        builder.instruction(self.program_id())
+    }
+
+    fn stake_reserve(
+        &self,
+        data: StakeReserveData,
+        validator_vote: Pubkey,
+        stake_account: Pubkey,
+    ) -> Result<Instruction, PubkeyError> {
+        let builder = InstructionBuilder {
+            accounts: StakeReserveAccounts {
+                marinade: self.key(),
+                validator_list: *self.as_ref().validator_system.validator_list_address(),
+                stake_list: *self.as_ref().stake_system.stake_list_address(),
+                validator_vote,
+                reserve_pda: self.try_reserve_address()?,
+                stake_account,
+                stake_deposit_authority: self.try_stake_deposit_authority()?,
+                clock: clock::ID,
+                epoch_schedule: epoch_schedule::ID,
+                rent: rent::ID,
+                stake_history: stake_history::ID,
+                stake_config: stake::config::ID,
+                system_program: system_program::ID,
+                stake_program: stake::program::ID,
+            },
+            data,
+        };
+        Ok(builder.instruction(self.program_id()))
+    }
+
+    fn deactivate_stake(
+        &self,
+        data: DeactivateStakeData,
+        stake_account: Pubkey,
+        split_stake_account: Pubkey,
+        split_stake_rent_payer: Pubkey,
+    ) -> Result<Instruction, PubkeyError> {
+        let builder = InstructionBuilder {
+            accounts: DeactivateStakeAccounts {
+                marinade: self.key(),
+                reserve_pda: self.try_reserve_address()?,
+                validator_list: *self.as_ref().validator_system.validator_list_address(),
+                stake_list: *self.as_ref().stake_system.stake_list_address(),
+                stake_account,
+                stake_deposit_authority: self.try_stake_deposit_authority()?,
+                split_stake_account,
+                split_stake_rent_payer,
+                clock: clock::ID,
+                rent: rent::ID,
+                epoch_schedule: epoch_schedule::ID,
+                stake_history: stake_history::ID,
+                system_program: system_program::ID,
+                stake_program: stake::program::ID,
+            },
+            data,
+        };
+        Ok(builder.instruction(self.program_id()))
+    }
+
+    fn emergency_unstake(
+        &self,
+        data: EmergencyUnstakeData,
+        stake_account: Pubkey,
+    ) -> Result<Instruction, PubkeyError> {
+        let builder = InstructionBuilder {
+            accounts: EmergencyUnstakeAccounts {
+                marinade: self.key(),
+                validator_manager_authority: self.as_ref().validator_system.manager_authority,
+                validator_list: *self.as_ref().validator_system.validator_list_address(),
+                stake_list: *self.as_ref().stake_system.stake_list_address(),
+                stake_account,
+                stake_deposit_authority: self.try_stake_deposit_authority()?,
+                clock: clock::ID,
+                stake_program: stake::program::ID,
+            },
+            data,
+        };
+        Ok(builder.instruction(self.program_id()))
+    }
+
+    fn partial_unstake(
+        &self,
+        data: PartialUnstakeData,
+        stake_account: Pubkey,
+        split_stake_account: Pubkey,
+        split_stake_rent_payer: Pubkey,
+    ) -> Result<Instruction, PubkeyError> {
+        let builder = InstructionBuilder {
+            accounts: PartialUnstakeAccounts {
+                marinade: self.key(),
+                validator_manager_authority: self.as_ref().validator_system.manager_authority,
+                validator_list: *self.as_ref().validator_system.validator_list_address(),
+                stake_list: *self.as_ref().stake_system.stake_list_address(),
+                stake_account,
+                stake_deposit_authority: self.try_stake_deposit_authority()?,
+                reserve_pda: self.try_reserve_address()?,
+                split_stake_account,
+                split_stake_rent_payer,
+                clock: clock::ID,
+                rent: rent::ID,
+                stake_history: stake_history::ID,
+                system_program: system_program::ID,
+                stake_program: stake::program::ID,
+            },
+            data,
+        };
+        Ok(builder.instruction(self.program_id()))
+    }
+
+    fn merge_stakes(
+        &self,
+        data: MergeStakesData,
+        destination_stake: Pubkey,
+        source_stake: Pubkey,
+    ) -> Result<Instruction, PubkeyError> {
+        let builder = InstructionBuilder {
+            accounts: MergeStakesAccounts {
+                marinade: self.key(),
+                stake_list: *self.as_ref().stake_system.stake_list_address(),
+                validator_list: *self.as_ref().validator_system.validator_list_address(),
+                destination_stake,
+                source_stake,
+                stake_deposit_authority: self.try_stake_deposit_authority()?,
+                stake_withdraw_authority: self.try_stake_withdraw_authority()?,
+                operational_sol_account: self.as_ref().operational_sol_account,
+                clock: clock::ID,
+                stake_history: stake_history::ID,
+                stake_program: stake::program::ID,
+            },
+            data,
+        };
+        Ok(builder.instruction(self.program_id()))
+    }
+
+    fn initialize(&self, data: InitializeData, creator_authority: Pubkey) -> Instruction {
+        let state = self.key();
+        let program = MarinadeProgram::new(self.program_id());
+        let builder = InstructionBuilder {
+            accounts: InitializeAccounts {
+                creator_authority,
+                marinade: state,
+                reserve_pda: program.find_reserve_address(&state).0,
+                stake_list: *self.as_ref().stake_system.stake_list_address(),
+                validator_list: *self.as_ref().validator_system.validator_list_address(),
+                msol_mint: self.as_ref().msol_mint,
+                operational_sol_account: self.as_ref().operational_sol_account,
+                liq_pool: LiqPoolInitializeAccounts {
+                    lp_mint: self.as_ref().liq_pool.lp_mint,
+                    sol_leg_pda: program.find_liq_pool_sol_leg_address(&state).0,
+                    msol_leg: self.as_ref().liq_pool.msol_leg,
+                },
+                treasury_msol_account: self.as_ref().treasury_msol_account,
+                clock: clock::ID,
+                rent: rent::ID,
+            },
+            data,
+        };
+        builder.instruction(self.program_id())
     }
 }
 
@@ -1,7 +1,10 @@
 pub mod delayed_unstake_ticket;
 pub mod fee;
+pub mod invariants;
+pub mod limits;
 pub mod liq_pool;
 pub mod list;
 pub mod marinade;
 pub mod stake_system;
+pub mod ticket;
 pub mod validator_system;
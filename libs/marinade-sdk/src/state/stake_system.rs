@@ -1,28 +1,56 @@
 use crate::{
     checks::check_address,
     located::Located,
-    state::{list::List, marinade::Marinade},
-    ID,
+    program::MarinadeProgram,
+    state::{
+        list::{List, ListRecord, ListView},
+        marinade::Marinade,
+    },
 };
 use borsh::{BorshDeserialize, BorshSerialize};
 use solana_program::{
-    account_info::AccountInfo, entrypoint::ProgramResult, msg, program_error::ProgramError,
-    pubkey::Pubkey,
+    account_info::AccountInfo,
+    entrypoint::ProgramResult,
+    msg,
+    program_error::ProgramError,
+    pubkey::{Pubkey, PubkeyError},
 };
 
+/// Program derived authorities shared by all stake accounts of the pool
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct StakeAccountPdas {
+    pub deposit_authority: Pubkey,
+    pub deposit_bump: u8,
+    pub withdraw_authority: Pubkey,
+    pub withdraw_bump: u8,
+}
+
 #[derive(Clone, Copy, Debug, Default, PartialEq, BorshSerialize, BorshDeserialize)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct StakeRecord {
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_pubkey"))]
     pub stake_account: Pubkey,
     pub last_update_delegated_lamports: u64,
     pub last_update_epoch: u64,
     pub is_emergency_unstaking: u8, // 1 for cooling down after emergency unstake, 0 otherwise
 }
 
+impl ListRecord for StakeRecord {
+    const DISCRIMINATOR: &'static [u8; 8] = StakeRecord::DISCRIMINATOR;
+    const RECORD_LEN: usize = 32 + 8 + 8 + 1;
+    const LIST_NAME: &'static str = "stake list";
+}
+
 impl StakeRecord {
     pub const DISCRIMINATOR: &'static [u8; 8] = b"staker__";
+
+    pub fn is_emergency_unstaking(&self) -> bool {
+        self.is_emergency_unstaking != 0
+    }
 }
 
 #[derive(Clone, BorshSerialize, BorshDeserialize, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct StakeSystem {
     pub stake_list: List,
     //pub last_update_epoch: u64,
@@ -55,11 +83,22 @@ impl StakeSystem {
     }
 
     pub fn find_stake_withdraw_authority(state: &Pubkey) -> (Pubkey, u8) {
-        Pubkey::find_program_address(&[&state.to_bytes()[..32], Self::STAKE_WITHDRAW_SEED], &ID)
+        MarinadeProgram::default().find_stake_withdraw_authority(state)
     }
 
     pub fn find_stake_deposit_authority(state: &Pubkey) -> (Pubkey, u8) {
-        Pubkey::find_program_address(&[&state.to_bytes()[..32], Self::STAKE_DEPOSIT_SEED], &ID)
+        MarinadeProgram::default().find_stake_deposit_authority(state)
+    }
+
+    pub fn derive_all(state: &Pubkey) -> StakeAccountPdas {
+        let (deposit_authority, deposit_bump) = Self::find_stake_deposit_authority(state);
+        let (withdraw_authority, withdraw_bump) = Self::find_stake_withdraw_authority(state);
+        StakeAccountPdas {
+            deposit_authority,
+            deposit_bump,
+            withdraw_authority,
+            withdraw_bump,
+        }
     }
 
     pub fn stake_list_address(&self) -> &Pubkey {
@@ -70,6 +109,14 @@ impl StakeSystem {
         self.stake_list.len()
     }
 
+    pub fn stakes_len(&self) -> u32 {
+        self.stake_list.len()
+    }
+
+    pub fn delayed_unstake_cooling_down(&self) -> u64 {
+        self.delayed_unstake_cooling_down
+    }
+
     pub fn stake_list_capacity(&self, stake_list_len: usize) -> Result<u32, ProgramError> {
         self.stake_list.capacity(stake_list_len)
     }
@@ -82,6 +129,20 @@ impl StakeSystem {
         self.stake_list.get(stake_list_data, index, "stake_list")
     }
 
+    /// Borrowing view of the stake list account data
+    pub fn stake_list<'a>(&self, stake_list_data: &'a [u8]) -> Result<StakeList<'a>, ProgramError> {
+        StakeList::new(&self.stake_list, stake_list_data)
+    }
+
+    pub fn stake_delta_limits(&self) -> StakeDeltaLimits {
+        StakeDeltaLimits {
+            slots_for_stake_delta: self.slots_for_stake_delta,
+            last_stake_delta_epoch: self.last_stake_delta_epoch,
+            extra_stake_delta_runs: self.extra_stake_delta_runs,
+            min_stake: self.min_stake,
+        }
+    }
+
     pub fn check_stake_list<'info>(&self, stake_list: &AccountInfo<'info>) -> ProgramResult {
         check_address(stake_list.key, self.stake_list_address(), "stake_list")?;
         if &stake_list.data.borrow().as_ref()[0..8] != StakeRecord::DISCRIMINATOR {
@@ -92,12 +153,83 @@ impl StakeSystem {
     }
 }
 
+/// Records of the stake list account read in place
+pub type StakeList<'a> = ListView<'a, StakeRecord>;
+
+impl<'a> StakeList<'a> {
+    /// Stake account address at index without decoding the rest of the record
+    pub fn stake_account(&self, index: u32) -> Option<Pubkey> {
+        self.record_data(index)
+            .map(|data| Pubkey::new_from_array(data[..32].try_into().unwrap()))
+    }
+
+    /// Index of stake_account, e.g. for the stake_index of deactivate_stake
+    pub fn find(&self, stake_account: &Pubkey) -> Option<u32> {
+        (0..self.len()).find(|index| self.stake_account(*index).as_ref() == Some(stake_account))
+    }
+}
+
+/// Per-epoch constraints the program puts on stake-delta (stake_reserve/deactivate_stake) runs
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct StakeDeltaLimits {
+    /// stake-delta can only run during the last slots_for_stake_delta slots of the epoch
+    pub slots_for_stake_delta: u64,
+    pub last_stake_delta_epoch: u64,
+    /// additional runs allowed for validators already processed this epoch
+    pub extra_stake_delta_runs: u32,
+    /// minimal delegation of a created stake account
+    pub min_stake: u64,
+}
+
+impl StakeDeltaLimits {
+    pub fn is_window_open(&self, slot_index: u64, slots_in_epoch: u64) -> bool {
+        slot_index.saturating_add(self.slots_for_stake_delta) >= slots_in_epoch
+    }
+
+    /// A validator is processed once per epoch unless extra runs were granted. Only for
+    /// a single run, every repeat uses up one of the extra runs (see clip_stake_delta_plan)
+    pub fn can_run_for(&self, validator_last_stake_delta_epoch: u64, epoch: u64) -> bool {
+        validator_last_stake_delta_epoch != epoch || self.extra_stake_delta_runs > 0
+    }
+}
+
+/// Stake system aggregates readable from the state account alone
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct StakeSystemSummary {
+    pub stakes_len: u32,
+    pub delayed_unstake_cooling_down: u64,
+    pub emergency_cooling_down: u64,
+    pub last_stake_delta_epoch: u64,
+    pub slots_for_stake_delta: u64,
+    pub min_stake: u64,
+    pub extra_stake_delta_runs: u32,
+}
+
+impl From<&Marinade> for StakeSystemSummary {
+    fn from(marinade: &Marinade) -> Self {
+        let stake_system = &marinade.stake_system;
+        Self {
+            stakes_len: stake_system.stakes_len(),
+            delayed_unstake_cooling_down: stake_system.delayed_unstake_cooling_down,
+            emergency_cooling_down: marinade.emergency_cooling_down,
+            last_stake_delta_epoch: stake_system.last_stake_delta_epoch,
+            slots_for_stake_delta: stake_system.slots_for_stake_delta,
+            min_stake: stake_system.min_stake,
+            extra_stake_delta_runs: stake_system.extra_stake_delta_runs,
+        }
+    }
+}
+
 pub trait StakeSystemHelpers {
+    #[deprecated(note = "panics on a corrupted bump seed, use `try_stake_withdraw_authority`")]
     fn stake_withdraw_authority(&self) -> Pubkey;
+    fn try_stake_withdraw_authority(&self) -> Result<Pubkey, PubkeyError>;
     fn with_stake_withdraw_authority_seeds<R, F: FnOnce(&[&[u8]]) -> R>(&self, f: F) -> R;
     fn check_stake_withdraw_authority(&self, stake_withdraw_authority: &Pubkey) -> ProgramResult;
 
+    #[deprecated(note = "panics on a corrupted bump seed, use `try_stake_deposit_authority`")]
     fn stake_deposit_authority(&self) -> Pubkey;
+    fn try_stake_deposit_authority(&self) -> Result<Pubkey, PubkeyError>;
     fn with_stake_deposit_authority_seeds<R, F: FnOnce(&[&[u8]]) -> R>(&self, f: F) -> R;
     fn check_stake_deposit_authority(&self, stake_deposit_authority: &Pubkey) -> ProgramResult;
 }
@@ -107,8 +239,12 @@ where
     T: Located<Marinade>,
 {
     fn stake_withdraw_authority(&self) -> Pubkey {
+        self.try_stake_withdraw_authority().unwrap()
+    }
+
+    fn try_stake_withdraw_authority(&self) -> Result<Pubkey, PubkeyError> {
         self.with_stake_withdraw_authority_seeds(|seeds| {
-            Pubkey::create_program_address(seeds, &ID).unwrap()
+            Pubkey::create_program_address(seeds, &self.program_id())
         })
     }
 
@@ -123,14 +259,18 @@ where
     fn check_stake_withdraw_authority(&self, stake_withdraw_authority: &Pubkey) -> ProgramResult {
         check_address(
             stake_withdraw_authority,
-            &self.stake_withdraw_authority(),
+            &self.try_stake_withdraw_authority()?,
             "stake_withdraw_authority",
         )
     }
 
     fn stake_deposit_authority(&self) -> Pubkey {
+        self.try_stake_deposit_authority().unwrap()
+    }
+
+    fn try_stake_deposit_authority(&self) -> Result<Pubkey, PubkeyError> {
         self.with_stake_deposit_authority_seeds(|seeds| {
-            Pubkey::create_program_address(seeds, &ID).unwrap()
+            Pubkey::create_program_address(seeds, &self.program_id())
         })
     }
 
@@ -145,7 +285,7 @@ where
     fn check_stake_deposit_authority(&self, stake_deposit_authority: &Pubkey) -> ProgramResult {
         check_address(
             stake_deposit_authority,
-            &self.stake_deposit_authority(),
+            &self.try_stake_deposit_authority()?,
             "stake_deposit_authority",
         )
     }
@@ -0,0 +1,37 @@
+//! The delayed unstake ticket account under the name the Marinade program uses for it,
+//! with the claim rules of the program.
+
+use solana_program::clock::Clock;
+
+pub use super::delayed_unstake_ticket::DelayedUnstakeTicket as TicketAccountData;
+
+impl TicketAccountData {
+    /// Time after the start of the due epoch before claim stops failing with TicketNotReady,
+    /// the stake deactivated at the epoch boundary reaches the reserve only with the next update
+    pub const EXTRA_WAIT_SECONDS: i64 = 30 * 60;
+
+    /// Paid out by claim
+    pub fn lamports_amount(&self) -> u64 {
+        self.lamports_amount
+    }
+
+    /// First epoch claim accepts the ticket in
+    pub fn due_epoch(&self) -> u64 {
+        self.created_epoch.saturating_add(1)
+    }
+
+    /// Whether claim would accept the ticket at clock, given the reserve holds enough SOL
+    pub fn is_claimable(&self, clock: &Clock) -> bool {
+        Self::is_due(self.due_epoch(), clock)
+    }
+
+    /// is_claimable of a ticket with due_epoch, when only that was kept
+    pub fn is_due(due_epoch: u64, clock: &Clock) -> bool {
+        clock.epoch > due_epoch
+            || clock.epoch == due_epoch
+                && clock
+                    .unix_timestamp
+                    .saturating_sub(clock.epoch_start_timestamp)
+                    >= Self::EXTRA_WAIT_SECONDS
+    }
+}
@@ -1,6 +1,15 @@
 //use std::convert::TryInto;
 
-use crate::{calc::proportional, checks::check_address, error::CommonError, state::list::List, ID};
+use crate::{
+    calc::proportional,
+    checks::check_address,
+    error::CommonError,
+    program::MarinadeProgram,
+    state::{
+        list::{List, ListRecord, ListView},
+        marinade::Marinade,
+    },
+};
 use borsh::{BorshDeserialize, BorshSerialize};
 use solana_program::{
     account_info::AccountInfo, entrypoint::ProgramResult, msg, program_error::ProgramError,
@@ -8,8 +17,10 @@ use solana_program::{
 };
 
 #[derive(Clone, Copy, Debug, Default, PartialEq, BorshSerialize, BorshDeserialize)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ValidatorRecord {
     /// Validator vote pubkey
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_pubkey"))]
     pub validator_account: Pubkey,
 
     /// Validator total balance in lamports
@@ -19,19 +30,33 @@ pub struct ValidatorRecord {
     pub duplication_flag_bump_seed: u8,
 }
 
+/// Program derived addresses of a validator
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ValidatorPdas {
+    pub duplication_flag: Pubkey,
+    pub bump: u8,
+}
+
+impl ListRecord for ValidatorRecord {
+    const DISCRIMINATOR: &'static [u8; 8] = ValidatorRecord::DISCRIMINATOR;
+    const RECORD_LEN: usize = 32 + 8 + 4 + 8 + 1;
+    const LIST_NAME: &'static str = "validator list";
+}
+
 impl ValidatorRecord {
     pub const DISCRIMINATOR: &'static [u8; 8] = b"validatr";
     pub const DUPLICATE_FLAG_SEED: &'static [u8] = b"unique_validator";
 
     pub fn find_duplication_flag(state: &Pubkey, validator_account: &Pubkey) -> (Pubkey, u8) {
-        Pubkey::find_program_address(
-            &[
-                &state.to_bytes()[..32],
-                Self::DUPLICATE_FLAG_SEED,
-                &validator_account.to_bytes()[..32],
-            ],
-            &ID,
-        )
+        MarinadeProgram::default().find_duplication_flag(state, validator_account)
+    }
+
+    pub fn derive_all(state: &Pubkey, validator_account: &Pubkey) -> ValidatorPdas {
+        let (duplication_flag, bump) = Self::find_duplication_flag(state, validator_account);
+        ValidatorPdas {
+            duplication_flag,
+            bump,
+        }
     }
 
     pub fn with_duplication_flag_seeds<R, F: FnOnce(&[&[u8]]) -> R>(
@@ -48,7 +73,8 @@ impl ValidatorRecord {
     }
 
     pub fn duplication_flag_address(&self, state: &Pubkey) -> Pubkey {
-        self.with_duplication_flag_seeds(state, |seeds| Pubkey::create_program_address(seeds, &ID))
+        MarinadeProgram::default()
+            .duplication_flag_address(self, state)
             .unwrap()
     }
 
@@ -79,8 +105,10 @@ impl ValidatorRecord {
 }
 
 #[derive(Clone, BorshSerialize, BorshDeserialize, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ValidatorSystem {
     pub validator_list: List,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_pubkey"))]
     pub manager_authority: Pubkey,
     pub total_validator_score: u32,
     /// sum of all active lamports staked
@@ -105,6 +133,22 @@ impl ValidatorSystem {
         self.validator_list.len()
     }
 
+    pub fn validators_len(&self) -> u32 {
+        self.validator_list.len()
+    }
+
+    pub fn total_active_balance(&self) -> u64 {
+        self.total_active_balance
+    }
+
+    pub fn total_validator_score(&self) -> u32 {
+        self.total_validator_score
+    }
+
+    pub fn is_auto_add_validator_enabled(&self) -> bool {
+        self.auto_add_validator_enabled != 0
+    }
+
     pub fn validator_list_capacity(&self, validator_list_len: usize) -> Result<u32, ProgramError> {
         self.validator_list.capacity(validator_list_len)
     }
@@ -122,6 +166,14 @@ impl ValidatorSystem {
             .get(validator_list_data, index, "validator_list")
     }
 
+    /// Borrowing view of the validator list account data
+    pub fn validator_list<'a>(
+        &self,
+        validator_list_data: &'a [u8],
+    ) -> Result<ValidatorList<'a>, ProgramError> {
+        ValidatorList::new(&self.validator_list, validator_list_data)
+    }
+
     pub fn validator_stake_target(
         &self,
         validator: &ValidatorRecord,
@@ -161,3 +213,48 @@ impl ValidatorSystem {
         )
     }
 }
+
+/// Records of the validator list account read in place
+pub type ValidatorList<'a> = ListView<'a, ValidatorRecord>;
+
+impl<'a> ValidatorList<'a> {
+    /// Offset of the score in a record
+    const SCORE_OFFSET: usize = 32 + 8;
+
+    /// Score of the validator at index without decoding the rest of the record
+    pub fn score(&self, index: u32) -> Option<u32> {
+        self.record_data(index).map(|data| {
+            let mut bytes = [0; 4];
+            bytes.copy_from_slice(&data[Self::SCORE_OFFSET..Self::SCORE_OFFSET + 4]);
+            u32::from_le_bytes(bytes)
+        })
+    }
+}
+
+/// Validator system aggregates readable from the state account alone
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ValidatorSystemSummary {
+    pub validators_len: u32,
+    pub total_validator_score: u32,
+    pub total_active_balance: u64,
+    pub auto_add_validator_enabled: bool,
+    pub manager_authority: Pubkey,
+}
+
+impl From<&ValidatorSystem> for ValidatorSystemSummary {
+    fn from(validator_system: &ValidatorSystem) -> Self {
+        Self {
+            validators_len: validator_system.validators_len(),
+            total_validator_score: validator_system.total_validator_score,
+            total_active_balance: validator_system.total_active_balance,
+            auto_add_validator_enabled: validator_system.is_auto_add_validator_enabled(),
+            manager_authority: validator_system.manager_authority,
+        }
+    }
+}
+
+impl From<&Marinade> for ValidatorSystemSummary {
+    fn from(marinade: &Marinade) -> Self {
+        (&marinade.validator_system).into()
+    }
+}
@@ -0,0 +1,81 @@
+//! Small key-value persistence used by long running SDK components (cursors, counters, queues)
+//! to keep their progress across restarts. Values are opaque bytes, usually borsh encoded.
+
+use std::{
+    collections::HashMap,
+    fs::{self, File},
+    io::{self, Write},
+    path::PathBuf,
+};
+
+pub trait Storage {
+    /// None when nothing was stored under key
+    fn load(&self, key: &str) -> io::Result<Option<Vec<u8>>>;
+    /// Replaces the value of key. The new value must be complete or not stored at all
+    fn store(&mut self, key: &str, value: &[u8]) -> io::Result<()>;
+}
+
+/// Process memory only, e.g. for tests and one-off runs
+#[derive(Clone, Debug, Default)]
+pub struct MemoryStorage {
+    values: HashMap<String, Vec<u8>>,
+}
+
+impl MemoryStorage {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl Storage for MemoryStorage {
+    fn load(&self, key: &str) -> io::Result<Option<Vec<u8>>> {
+        Ok(self.values.get(key).cloned())
+    }
+
+    fn store(&mut self, key: &str, value: &[u8]) -> io::Result<()> {
+        self.values.insert(key.to_string(), value.to_vec());
+        Ok(())
+    }
+}
+
+/// One file per key in a directory
+#[derive(Clone, Debug)]
+pub struct FileStorage {
+    pub dir: PathBuf,
+}
+
+impl FileStorage {
+    pub fn new(dir: impl Into<PathBuf>) -> io::Result<Self> {
+        let dir = dir.into();
+        fs::create_dir_all(&dir)?;
+        Ok(Self { dir })
+    }
+
+    fn path(&self, key: &str) -> PathBuf {
+        self.dir.join(key)
+    }
+}
+
+impl Storage for FileStorage {
+    fn load(&self, key: &str) -> io::Result<Option<Vec<u8>>> {
+        match fs::read(self.path(key)) {
+            Ok(data) => Ok(Some(data)),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Writes and syncs a temporary file first so a crash never leaves a truncated value,
+    /// and syncs the directory after the rename so the stored value survives a power loss
+    fn store(&mut self, key: &str, value: &[u8]) -> io::Result<()> {
+        let tmp = self.path(&format!("{}.tmp", key));
+        let mut file = File::create(&tmp)?;
+        file.write_all(value)?;
+        file.sync_all()?;
+        fs::rename(tmp, self.path(key))?;
+        // directories can not be opened as files on windows
+        #[cfg(unix)]
+        File::open(&self.dir)?.sync_all()?;
+        Ok(())
+    }
+}
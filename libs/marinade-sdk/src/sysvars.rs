@@ -0,0 +1,66 @@
+//! The sysvars the SDK calculations read, taken together so they describe the same slot.
+//! Fetch them with client::fetch_sysvars (feature rpc) or fill them from a program test bank.
+
+use solana_program::{
+    clock::Clock,
+    epoch_schedule::EpochSchedule,
+    pubkey::Pubkey,
+    rent::Rent,
+    stake_history::{StakeHistory, StakeHistoryEntry},
+    sysvar,
+};
+
+use crate::{planner::EpochPosition, state::ticket::TicketAccountData};
+
+#[derive(Clone, Debug, Default)]
+pub struct SysvarCache {
+    pub clock: Clock,
+    pub epoch_schedule: EpochSchedule,
+    pub rent: Rent,
+    pub stake_history: StakeHistory,
+}
+
+impl SysvarCache {
+    /// Addresses in the order client::fetch_sysvars reads them
+    pub fn addresses() -> [Pubkey; 4] {
+        [
+            sysvar::clock::ID,
+            sysvar::epoch_schedule::ID,
+            sysvar::rent::ID,
+            sysvar::stake_history::ID,
+        ]
+    }
+
+    pub fn epoch(&self) -> u64 {
+        self.clock.epoch
+    }
+
+    pub fn epoch_position(&self) -> EpochPosition {
+        let (epoch, slot_index) = self
+            .epoch_schedule
+            .get_epoch_and_slot_index(self.clock.slot);
+        EpochPosition {
+            epoch,
+            slot_index,
+            slots_in_epoch: self.epoch_schedule.get_slots_in_epoch(epoch),
+        }
+    }
+
+    pub fn slots_until_epoch_end(&self) -> u64 {
+        let position = self.epoch_position();
+        position.slots_in_epoch.saturating_sub(position.slot_index)
+    }
+
+    pub fn is_ticket_claimable(&self, ticket: &TicketAccountData) -> bool {
+        ticket.is_claimable(&self.clock)
+    }
+
+    /// Cluster wide effective, activating and deactivating stake of a past epoch
+    pub fn stake_history_entry(&self, epoch: u64) -> Option<&StakeHistoryEntry> {
+        self.stake_history.get(epoch)
+    }
+
+    pub fn minimum_balance(&self, data_len: usize) -> u64 {
+        self.rent.minimum_balance(data_len)
+    }
+}
@@ -0,0 +1,437 @@
+//! A sample of every instruction, built once. Their account structs give the expected accounts
+//! in order with their signer and writable flags: lint checks modified instructions against them
+//! and test_vectors publishes them as the golden wire format samples.
+//! Account keys are `template_key(n)` numbered in account order starting from 1,
+//! pubkeys inside instruction data start from `template_key(101)`.
+
+use std::sync::OnceLock;
+
+use micro_anchor::{InstructionBuilder, ToAccountMetas};
+use solana_program::{instruction::Instruction, pubkey::Pubkey};
+
+use crate::{
+    instructions::{
+        add_liquidity::{AddLiquidityAccounts, AddLiquidityData},
+        add_validator::{AddValidatorAccounts, AddValidatorData},
+        change_authority::{ChangeAuthorityAccounts, ChangeAuthorityData},
+        claim::{ClaimAccounts, ClaimData},
+        config_lp::{ConfigLpAccounts, ConfigLpData},
+        config_marinade::{ConfigMarinadeAccounts, ConfigMarinadeData},
+        config_validator_system::{ConfigValidatorSystemAccounts, ConfigValidatorSystemData},
+        deactivate_stake::{DeactivateStakeAccounts, DeactivateStakeData},
+        deposit::{DepositAccounts, DepositData},
+        deposit_stake_account::{DepositStakeAccountAccounts, DepositStakeAccountData},
+        emergency_unstake::{EmergencyUnstakeAccounts, EmergencyUnstakeData},
+        initialize::{
+            InitializeAccounts, InitializeData, LiqPoolInitializeAccounts, LiqPoolInitializeData,
+        },
+        liquid_unstake::{LiquidUnstakeAccounts, LiquidUnstakeData},
+        merge_stakes::{MergeStakesAccounts, MergeStakesData},
+        order_unstake::{OrderUnstakeAccounts, OrderUnstakeData},
+        partial_unstake::{PartialUnstakeAccounts, PartialUnstakeData},
+        remove_liquidity::{RemoveLiquidityAccounts, RemoveLiquidityData},
+        remove_validator::{RemoveValidatorAccounts, RemoveValidatorData},
+        set_validator_score::{SetValidatorScoreAccounts, SetValidatorScoreData},
+        stake_reserve::{StakeReserveAccounts, StakeReserveData},
+        update_active::{UpdateActiveAccounts, UpdateActiveData},
+        update_deactivated::{UpdateDeactivatedAccounts, UpdateDeactivatedData},
+    },
+    state::fee::Fee,
+};
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct InstructionTemplate {
+    /// snake_case instruction name, as in the instructions module
+    pub name: &'static str,
+    pub instruction: Instruction,
+}
+
+impl InstructionTemplate {
+    pub fn discriminator(&self) -> &[u8] {
+        &self.instruction.data[..8]
+    }
+}
+
+/// Fixed pubkey with all 32 bytes set to n
+pub fn template_key(n: u8) -> Pubkey {
+    Pubkey::new_from_array([n; 32])
+}
+
+fn template<A: ToAccountMetas>(
+    name: &'static str,
+    accounts: A,
+    data: A::Data,
+) -> InstructionTemplate {
+    InstructionTemplate {
+        name,
+        instruction: (&InstructionBuilder { accounts, data }).into(),
+    }
+}
+
+/// One template per instruction, ordered by name
+pub fn instruction_templates() -> &'static [InstructionTemplate] {
+    static TEMPLATES: OnceLock<Vec<InstructionTemplate>> = OnceLock::new();
+    TEMPLATES.get_or_init(build_templates)
+}
+
+/// Template of the instruction starting with the discriminator of data
+pub fn find_template(data: &[u8]) -> Option<&'static InstructionTemplate> {
+    let discriminator = data.get(..8)?;
+    instruction_templates()
+        .iter()
+        .find(|template| template.discriminator() == discriminator)
+}
+
+fn build_templates() -> Vec<InstructionTemplate> {
+    vec![
+        template(
+            "add_liquidity",
+            AddLiquidityAccounts {
+                marinade: template_key(1),
+                lp_mint: template_key(2),
+                lp_mint_authority: template_key(3),
+                liq_pool_msol_leg: template_key(4),
+                liq_pool_sol_leg_pda: template_key(5),
+                transfer_from: template_key(6),
+                mint_to: template_key(7),
+                system_program: template_key(8),
+                token_program: template_key(9),
+            },
+            AddLiquidityData::new(1_000_000_000),
+        ),
+        template(
+            "add_validator",
+            AddValidatorAccounts {
+                marinade: template_key(1),
+                manager_authority: template_key(2),
+                validator_list: template_key(3),
+                validator_vote: template_key(4),
+                duplication_flag: template_key(5),
+                rent_payer: template_key(6),
+                clock: template_key(7),
+                rent: template_key(8),
+                system_program: template_key(9),
+            },
+            AddValidatorData::new(100),
+        ),
+        template(
+            "change_authority",
+            ChangeAuthorityAccounts {
+                marinade: template_key(1),
+                admin_authority: template_key(2),
+            },
+            ChangeAuthorityData::default()
+                .with_admin(template_key(101))
+                .with_treasury_msol_account(template_key(102)),
+        ),
+        template(
+            "claim",
+            ClaimAccounts {
+                marinade: template_key(1),
+                reserve_pda: template_key(2),
+                ticket_account: template_key(3),
+                transfer_sol_to: template_key(4),
+                clock: template_key(5),
+                system_program: template_key(6),
+            },
+            ClaimData::new(),
+        ),
+        template(
+            "config_lp",
+            ConfigLpAccounts {
+                marinade: template_key(1),
+                admin_authority: template_key(2),
+            },
+            ConfigLpData::default()
+                .with_min_fee(Fee::from_basis_points(30))
+                .with_liquidity_target(10_000_000_000_000),
+        ),
+        template(
+            "config_marinade",
+            ConfigMarinadeAccounts {
+                marinade: template_key(1),
+                admin_authority: template_key(2),
+            },
+            ConfigMarinadeData::default()
+                .with_rewards_fee(Fee::from_basis_points(200))
+                .with_min_stake(1_000_000_000)
+                .with_staking_sol_cap(u64::MAX)
+                .with_auto_add_validator_enabled(true),
+        ),
+        template(
+            "config_validator_system",
+            ConfigValidatorSystemAccounts {
+                marinade: template_key(1),
+                manager_authority: template_key(2),
+            },
+            ConfigValidatorSystemData::new(2),
+        ),
+        template(
+            "deactivate_stake",
+            DeactivateStakeAccounts {
+                marinade: template_key(1),
+                reserve_pda: template_key(2),
+                validator_list: template_key(3),
+                stake_list: template_key(4),
+                stake_account: template_key(5),
+                stake_deposit_authority: template_key(6),
+                split_stake_account: template_key(7),
+                split_stake_rent_payer: template_key(8),
+                clock: template_key(9),
+                rent: template_key(10),
+                epoch_schedule: template_key(11),
+                stake_history: template_key(12),
+                system_program: template_key(13),
+                stake_program: template_key(14),
+            },
+            DeactivateStakeData::new(3, 7),
+        ),
+        template(
+            "deposit",
+            DepositAccounts {
+                marinade: template_key(1),
+                msol_mint: template_key(2),
+                liq_pool_sol_leg_pda: template_key(3),
+                liq_pool_msol_leg: template_key(4),
+                liq_pool_msol_leg_authority: template_key(5),
+                reserve_pda: template_key(6),
+                transfer_from: template_key(7),
+                mint_to: template_key(8),
+                msol_mint_authority: template_key(9),
+                system_program: template_key(10),
+                token_program: template_key(11),
+            },
+            DepositData::new(2_500_000_000),
+        ),
+        template(
+            "deposit_stake_account",
+            DepositStakeAccountAccounts {
+                marinade: template_key(1),
+                validator_list: template_key(2),
+                stake_list: template_key(3),
+                stake_account: template_key(4),
+                stake_authority: template_key(5),
+                duplication_flag: template_key(6),
+                rent_payer: template_key(7),
+                msol_mint: template_key(8),
+                mint_to: template_key(9),
+                msol_mint_authority: template_key(10),
+                clock: template_key(11),
+                rent: template_key(12),
+                system_program: template_key(13),
+                token_program: template_key(14),
+                stake_program: template_key(15),
+            },
+            DepositStakeAccountData::new(7),
+        ),
+        template(
+            "emergency_unstake",
+            EmergencyUnstakeAccounts {
+                marinade: template_key(1),
+                validator_manager_authority: template_key(2),
+                validator_list: template_key(3),
+                stake_list: template_key(4),
+                stake_account: template_key(5),
+                stake_deposit_authority: template_key(6),
+                clock: template_key(7),
+                stake_program: template_key(8),
+            },
+            EmergencyUnstakeData::new(3, 7),
+        ),
+        template(
+            "initialize",
+            InitializeAccounts {
+                creator_authority: template_key(1),
+                marinade: template_key(2),
+                reserve_pda: template_key(3),
+                stake_list: template_key(4),
+                validator_list: template_key(5),
+                msol_mint: template_key(6),
+                operational_sol_account: template_key(7),
+                liq_pool: LiqPoolInitializeAccounts {
+                    lp_mint: template_key(8),
+                    sol_leg_pda: template_key(9),
+                    msol_leg: template_key(10),
+                },
+                treasury_msol_account: template_key(11),
+                clock: template_key(12),
+                rent: template_key(13),
+            },
+            InitializeData::new(template_key(101), template_key(102))
+                .with_min_stake(1_000_000_000)
+                .with_reward_fee(Fee::from_basis_points(200))
+                .with_liq_pool(LiqPoolInitializeData::new(
+                    10_000_000_000_000,
+                    Fee::from_basis_points(300),
+                    Fee::from_basis_points(30),
+                    Fee::from_basis_points(2500),
+                ))
+                .with_additional_stake_record_space(8)
+                .with_additional_validator_record_space(8)
+                .with_slots_for_stake_delta(3000),
+        ),
+        template(
+            "liquid_unstake",
+            LiquidUnstakeAccounts {
+                marinade: template_key(1),
+                msol_mint: template_key(2),
+                liq_pool_sol_leg_pda: template_key(3),
+                liq_pool_msol_leg: template_key(4),
+                treasury_msol_account: template_key(5),
+                get_msol_from: template_key(6),
+                get_msol_from_authority: template_key(7),
+                transfer_sol_to: template_key(8),
+                system_program: template_key(9),
+                token_program: template_key(10),
+            },
+            LiquidUnstakeData::new(1_500_000_000),
+        ),
+        template(
+            "merge_stakes",
+            MergeStakesAccounts {
+                marinade: template_key(1),
+                stake_list: template_key(2),
+                validator_list: template_key(3),
+                destination_stake: template_key(4),
+                source_stake: template_key(5),
+                stake_deposit_authority: template_key(6),
+                stake_withdraw_authority: template_key(7),
+                operational_sol_account: template_key(8),
+                clock: template_key(9),
+                stake_history: template_key(10),
+                stake_program: template_key(11),
+            },
+            MergeStakesData::new(1, 2, 7),
+        ),
+        template(
+            "order_unstake",
+            OrderUnstakeAccounts {
+                marinade: template_key(1),
+                msol_mint: template_key(2),
+                burn_msol_from: template_key(3),
+                burn_msol_authority: template_key(4),
+                new_ticket_account: template_key(5),
+                clock: template_key(6),
+                rent: template_key(7),
+                token_program: template_key(8),
+            },
+            OrderUnstakeData::new(1_500_000_000),
+        ),
+        template(
+            "partial_unstake",
+            PartialUnstakeAccounts {
+                marinade: template_key(1),
+                validator_manager_authority: template_key(2),
+                validator_list: template_key(3),
+                stake_list: template_key(4),
+                stake_account: template_key(5),
+                stake_deposit_authority: template_key(6),
+                reserve_pda: template_key(7),
+                split_stake_account: template_key(8),
+                split_stake_rent_payer: template_key(9),
+                clock: template_key(10),
+                rent: template_key(11),
+                stake_history: template_key(12),
+                system_program: template_key(13),
+                stake_program: template_key(14),
+            },
+            PartialUnstakeData::new(3, 7, 1_000_000_000),
+        ),
+        template(
+            "remove_liquidity",
+            RemoveLiquidityAccounts {
+                marinade: template_key(1),
+                lp_mint: template_key(2),
+                burn_from: template_key(3),
+                burn_from_authority: template_key(4),
+                transfer_sol_to: template_key(5),
+                transfer_msol_to: template_key(6),
+                liq_pool_sol_leg_pda: template_key(7),
+                liq_pool_msol_leg: template_key(8),
+                liq_pool_msol_leg_authority: template_key(9),
+                system_program: template_key(10),
+                token_program: template_key(11),
+            },
+            RemoveLiquidityData::new(500_000_000),
+        ),
+        template(
+            "remove_validator",
+            RemoveValidatorAccounts {
+                marinade: template_key(1),
+                manager_authority: template_key(2),
+                validator_list: template_key(3),
+                duplication_flag: template_key(4),
+                operational_sol_account: template_key(5),
+            },
+            RemoveValidatorData::new(7, template_key(101)),
+        ),
+        template(
+            "set_validator_score",
+            SetValidatorScoreAccounts {
+                marinade: template_key(1),
+                manager_authority: template_key(2),
+                validator_list: template_key(3),
+            },
+            SetValidatorScoreData::new(7, template_key(101), 250),
+        ),
+        template(
+            "stake_reserve",
+            StakeReserveAccounts {
+                marinade: template_key(1),
+                validator_list: template_key(2),
+                stake_list: template_key(3),
+                validator_vote: template_key(4),
+                reserve_pda: template_key(5),
+                stake_account: template_key(6),
+                stake_deposit_authority: template_key(7),
+                clock: template_key(8),
+                epoch_schedule: template_key(9),
+                rent: template_key(10),
+                stake_history: template_key(11),
+                stake_config: template_key(12),
+                system_program: template_key(13),
+                stake_program: template_key(14),
+            },
+            StakeReserveData::new(7),
+        ),
+        template(
+            "update_active",
+            UpdateActiveAccounts {
+                marinade: template_key(1),
+                stake_list: template_key(2),
+                stake_account: template_key(3),
+                stake_withdraw_authority: template_key(4),
+                reserve_pda: template_key(5),
+                msol_mint: template_key(6),
+                msol_mint_authority: template_key(7),
+                treasury_msol_account: template_key(8),
+                clock: template_key(9),
+                stake_history: template_key(10),
+                stake_program: template_key(11),
+                token_program: template_key(12),
+                validator_list: template_key(13),
+            },
+            UpdateActiveData::new(3, 4),
+        ),
+        template(
+            "update_deactivated",
+            UpdateDeactivatedAccounts {
+                marinade: template_key(1),
+                stake_list: template_key(2),
+                stake_account: template_key(3),
+                stake_withdraw_authority: template_key(4),
+                reserve_pda: template_key(5),
+                msol_mint: template_key(6),
+                msol_mint_authority: template_key(7),
+                treasury_msol_account: template_key(8),
+                clock: template_key(9),
+                stake_history: template_key(10),
+                stake_program: template_key(11),
+                token_program: template_key(12),
+                operational_sol_account: template_key(13),
+                system_program: template_key(14),
+            },
+            UpdateDeactivatedData::new(3),
+        ),
+    ]
+}
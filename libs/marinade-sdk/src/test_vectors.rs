@@ -0,0 +1,26 @@
+//! Deterministic wire format samples of every instruction, the instruction templates.
+//! Account keys are `test_key(n)` numbered in account order starting from 1,
+//! pubkeys inside instruction data start from `test_key(101)`.
+//! The same vectors are published in `test_vectors/instructions.json` for other implementations.
+
+use solana_program::instruction::Instruction;
+
+use crate::templates::instruction_templates;
+pub use crate::templates::template_key as test_key;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TestVector {
+    /// snake_case instruction name, as in the instructions module
+    pub name: &'static str,
+    pub instruction: Instruction,
+}
+
+pub fn test_vectors() -> Vec<TestVector> {
+    instruction_templates()
+        .iter()
+        .map(|template| TestVector {
+            name: template.name,
+            instruction: template.instruction.clone(),
+        })
+        .collect()
+}
@@ -0,0 +1,71 @@
+//! Test support for integrations, meant for dev-dependencies:
+//! `marinade-sdk = { version = "...", features = ["test-utils"] }`.
+//!
+//! - test_state: a zeroed state with valid bump seeds, priced 1:1
+//! - program_accounts: the accounts of such a state, for ProgramTest::add_account or MockRpc
+//! - MockRpc: an RpcClient answering account requests from a fixed account set and
+//!   accepting transactions without executing them, so client::fetch_program_state,
+//!   MarinadeClient and SignerClient work offline
+//! - sysvar_account and ticket_account fixtures for the claim flow
+//! - the golden instruction vectors of test_vectors
+//!
+//! These items follow the crate's semver like the rest of the API, and the golden vectors only
+//! change together with the wire format, i.e. in a breaking release.
+
+use borsh::BorshDeserialize;
+use solana_program::pubkey::Pubkey;
+
+pub use crate::test_vectors::{test_key, test_vectors, TestVector};
+use crate::{
+    located::Located,
+    state::{liq_pool::LiqPool, marinade::Marinade, stake_system::StakeSystem},
+};
+
+mod accounts;
+mod mock_rpc;
+
+pub use accounts::*;
+pub use mock_rpc::*;
+
+pub struct TestState {
+    pub key: Pubkey,
+    pub state: Marinade,
+}
+
+impl Located<Marinade> for TestState {
+    fn as_ref(&self) -> &Marinade {
+        &self.state
+    }
+
+    fn as_mut(&mut self) -> &mut Marinade {
+        &mut self.state
+    }
+
+    fn key(&self) -> Pubkey {
+        self.key
+    }
+}
+
+/// Zeroed state with correct bump seeds and unique addresses
+pub fn test_state() -> TestState {
+    let key = Pubkey::new_unique();
+    let mut state = Marinade::try_from_slice(&vec![0u8; Marinade::serialized_len() - 8]).unwrap();
+    state.msol_mint = Pubkey::new_unique();
+    state.admin_authority = Pubkey::new_unique();
+    state.operational_sol_account = Pubkey::new_unique();
+    state.treasury_msol_account = Pubkey::new_unique();
+    state.msol_mint_authority_bump_seed = Marinade::find_msol_mint_authority(&key).1;
+    state.reserve_bump_seed = Marinade::find_reserve_address(&key).1;
+    state.validator_system.validator_list.account = Marinade::default_validator_list_address(&key);
+    state.validator_system.manager_authority = Pubkey::new_unique();
+    state.stake_system.stake_list.account = Marinade::default_stake_list_address(&key);
+    state.stake_system.stake_deposit_bump_seed = StakeSystem::find_stake_deposit_authority(&key).1;
+    state.stake_system.stake_withdraw_bump_seed =
+        StakeSystem::find_stake_withdraw_authority(&key).1;
+    state.liq_pool.lp_mint = Pubkey::new_unique();
+    state.liq_pool.msol_leg = LiqPool::default_msol_leg_address(&key);
+    state.liq_pool.lp_mint_authority_bump_seed = LiqPool::find_lp_mint_authority(&key).1;
+    state.liq_pool.sol_leg_bump_seed = LiqPool::find_sol_leg_address(&key).1;
+    state.liq_pool.msol_leg_authority_bump_seed = LiqPool::find_msol_leg_authority(&key).1;
+    TestState { key, state }
+}
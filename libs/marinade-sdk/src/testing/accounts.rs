@@ -0,0 +1,164 @@
+use borsh::BorshSerialize;
+use micro_anchor::Discriminator;
+use solana_program::{
+    program_option::COption,
+    program_pack::Pack,
+    pubkey::Pubkey,
+    sysvar::{self, Sysvar},
+};
+use solana_sdk::{account::Account, rent::Rent, system_program};
+
+use crate::{
+    located::Located,
+    state::{
+        delayed_unstake_ticket::DelayedUnstakeTicket,
+        liq_pool::LiqPoolHelpers,
+        marinade::{Marinade, MarinadeHelpers},
+        stake_system::StakeRecord,
+        validator_system::ValidatorRecord,
+    },
+    ID,
+};
+
+fn rent_exempt(data: Vec<u8>, owner: Pubkey) -> Account {
+    Account {
+        lamports: Rent::default().minimum_balance(data.len()),
+        data,
+        owner,
+        executable: false,
+        rent_epoch: 0,
+    }
+}
+
+/// State account as the program stores it
+pub fn state_account(state: &Marinade) -> Account {
+    let mut data = Marinade::DISCRIMINATOR.to_vec();
+    data.extend(state.try_to_vec().unwrap());
+    data.resize(Marinade::serialized_len(), 0);
+    rent_exempt(data, ID)
+}
+
+/// List account with its discriminator and zeroed records, sized for count records
+pub fn list_account(discriminator: &[u8; 8], item_size: u32, count: u32) -> Account {
+    let mut data = discriminator.to_vec();
+    data.resize(8 + item_size as usize * count as usize, 0);
+    rent_exempt(data, ID)
+}
+
+pub fn mint_account(mint_authority: Pubkey, supply: u64) -> Account {
+    let mut data = vec![0; spl_token::state::Mint::LEN];
+    spl_token::state::Mint {
+        mint_authority: COption::Some(mint_authority),
+        supply,
+        decimals: 9,
+        is_initialized: true,
+        freeze_authority: COption::None,
+    }
+    .pack_into_slice(&mut data);
+    rent_exempt(data, spl_token::ID)
+}
+
+pub fn token_account(mint: Pubkey, owner: Pubkey, amount: u64) -> Account {
+    let mut data = vec![0; spl_token::state::Account::LEN];
+    spl_token::state::Account {
+        mint,
+        owner,
+        amount,
+        state: spl_token::state::AccountState::Initialized,
+        ..Default::default()
+    }
+    .pack_into_slice(&mut data);
+    rent_exempt(data, spl_token::ID)
+}
+
+pub fn system_account(lamports: u64) -> Account {
+    Account::new(lamports, 0, &system_program::ID)
+}
+
+/// Sysvar account holding sysvar, e.g. a Clock for client::fetch_sysvar::<Clock>
+pub fn sysvar_account<S: Sysvar>(sysvar: &S) -> Account {
+    let mut data = bincode::serialize(sysvar).unwrap();
+    data.resize(data.len().max(S::size_of()), 0);
+    rent_exempt(data, sysvar::ID)
+}
+
+/// Ticket account as order_unstake leaves it
+pub fn ticket_account(
+    state_address: Pubkey,
+    beneficiary: Pubkey,
+    lamports_amount: u64,
+    created_epoch: u64,
+) -> Account {
+    let mut data = DelayedUnstakeTicket::DISCRIMINATOR.to_vec();
+    DelayedUnstakeTicket {
+        state_address,
+        beneficiary,
+        lamports_amount,
+        created_epoch,
+    }
+    .serialize(&mut data)
+    .unwrap();
+    rent_exempt(data, ID)
+}
+
+/// State, lists, mints, reserve, liq-pool legs and treasury of a state, with the balances
+/// it records; the lists hold zeroed records
+pub fn program_accounts<T: Located<Marinade>>(state: &T) -> Vec<(Pubkey, Account)> {
+    let marinade = state.as_ref();
+    let rent_exempt = marinade.rent_exempt_for_token_acc;
+    let validator_list = &marinade.validator_system.validator_list;
+    let stake_list = &marinade.stake_system.stake_list;
+    vec![
+        (state.key(), state_account(marinade)),
+        (
+            validator_list.account,
+            list_account(
+                ValidatorRecord::DISCRIMINATOR,
+                validator_list.item_size,
+                validator_list.count,
+            ),
+        ),
+        (
+            stake_list.account,
+            list_account(
+                StakeRecord::DISCRIMINATOR,
+                stake_list.item_size,
+                stake_list.count,
+            ),
+        ),
+        (
+            marinade.msol_mint,
+            mint_account(
+                state.try_msol_mint_authority().unwrap(),
+                marinade.msol_supply,
+            ),
+        ),
+        (
+            marinade.liq_pool.lp_mint,
+            mint_account(
+                state.try_lp_mint_authority().unwrap(),
+                marinade.liq_pool.lp_supply,
+            ),
+        ),
+        (
+            state.try_reserve_address().unwrap(),
+            system_account(marinade.available_reserve_balance + rent_exempt),
+        ),
+        (
+            state.try_liq_pool_sol_leg_address().unwrap(),
+            system_account(rent_exempt),
+        ),
+        (
+            marinade.liq_pool.msol_leg,
+            token_account(
+                marinade.msol_mint,
+                state.try_liq_pool_msol_leg_authority().unwrap(),
+                0,
+            ),
+        ),
+        (
+            marinade.treasury_msol_account,
+            token_account(marinade.msol_mint, Pubkey::new_unique(), 0),
+        ),
+    ]
+}
@@ -0,0 +1,380 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::{Arc, Mutex},
+};
+
+use async_trait::async_trait;
+use serde_json::{json, Value};
+use solana_account_decoder::{UiAccount, UiAccountEncoding};
+use solana_client::{
+    client_error::{ClientError, ClientErrorKind, Result as ClientResult},
+    rpc_client::{RpcClient, RpcClientConfig},
+    rpc_config::{RpcAccountInfoConfig, RpcProgramAccountsConfig},
+    rpc_filter::RpcFilterType,
+    rpc_request::{RpcError, RpcRequest, RpcResponseErrorData},
+    rpc_sender::{RpcSender, RpcTransportStats},
+};
+use solana_program::{
+    epoch_schedule::EpochSchedule, hash::Hash, program_pack::Pack, pubkey::Pubkey,
+};
+use solana_sdk::{account::Account, commitment_config::CommitmentConfig, transaction::Transaction};
+
+/// JSON-RPC error code of requests with a minContextSlot the node has not reached
+const MIN_CONTEXT_SLOT_NOT_REACHED: i64 = -32016;
+/// JSON-RPC error code of block requests before the first block the node retains
+const BLOCK_CLEANED_UP: i64 = -32001;
+/// JSON-RPC error code of getBlockTime for a slot without a block
+const BLOCK_NOT_AVAILABLE: i64 = -32004;
+
+/// Transactions sent through a MockRpc, oldest first
+pub type SentTransactions = Arc<Mutex<Vec<Transaction>>>;
+
+/// RpcSender serving getAccountInfo, getMultipleAccounts and getProgramAccounts from a fixed
+/// account set at a fixed slot, encoding, dataSlice, filters and minContextSlot included, as
+/// well as getSlot, getVersion, getLatestBlockhash, getEpochInfo and getEpochSchedule.
+/// A minContextSlot past the slot fails like on a lagging node. getBlocksWithLimit and
+/// getBlockTime answer from the block times set, the node retaining no block before the
+/// first one. Sent transactions are recorded, not executed, and reported finalized by
+/// getSignatureStatuses, up to an optional send limit; other requests fail
+#[derive(Clone, Debug, Default)]
+pub struct MockRpc {
+    pub accounts: HashMap<Pubkey, Account>,
+    pub slot: u64,
+    /// Data length getMultipleAccounts cuts the accounts at, like providers truncating large
+    /// responses; getAccountInfo is not limited
+    pub multiple_accounts_data_limit: Option<usize>,
+    /// Number of transactions sendTransaction accepts, the next ones are rejected like by a
+    /// node failing their preflight
+    pub send_limit: Option<usize>,
+    pub epoch_schedule: EpochSchedule,
+    /// Unix timestamps of the produced blocks by slot, the other slots are skipped (or cleaned
+    /// up before the first)
+    pub block_times: BTreeMap<u64, i64>,
+    sent: SentTransactions,
+}
+
+impl MockRpc {
+    pub fn new<I: IntoIterator<Item = (Pubkey, Account)>>(accounts: I) -> Self {
+        Self {
+            accounts: accounts.into_iter().collect(),
+            slot: 0,
+            multiple_accounts_data_limit: None,
+            send_limit: None,
+            epoch_schedule: EpochSchedule::default(),
+            block_times: BTreeMap::new(),
+            sent: SentTransactions::default(),
+        }
+    }
+
+    pub fn with_slot(mut self, slot: u64) -> Self {
+        self.slot = slot;
+        self
+    }
+
+    pub fn with_multiple_accounts_data_limit(mut self, limit: usize) -> Self {
+        self.multiple_accounts_data_limit = Some(limit);
+        self
+    }
+
+    pub fn with_send_limit(mut self, limit: usize) -> Self {
+        self.send_limit = Some(limit);
+        self
+    }
+
+    pub fn with_epoch_schedule(mut self, epoch_schedule: EpochSchedule) -> Self {
+        self.epoch_schedule = epoch_schedule;
+        self
+    }
+
+    pub fn with_block_times<I: IntoIterator<Item = (u64, i64)>>(mut self, block_times: I) -> Self {
+        self.block_times.extend(block_times);
+        self
+    }
+
+    pub fn set_account(&mut self, address: Pubkey, account: Account) {
+        self.accounts.insert(address, account);
+    }
+
+    /// Handle on the transactions sent through the client, to keep before into_client
+    pub fn sent_transactions(&self) -> SentTransactions {
+        self.sent.clone()
+    }
+
+    pub fn into_client(self) -> RpcClient {
+        RpcClient::new_sender(
+            self,
+            RpcClientConfig::with_commitment(CommitmentConfig::confirmed()),
+        )
+    }
+
+    #[cfg(feature = "async")]
+    pub fn into_nonblocking_client(self) -> solana_client::nonblocking::rpc_client::RpcClient {
+        solana_client::nonblocking::rpc_client::RpcClient::new_sender(
+            self,
+            RpcClientConfig::with_commitment(CommitmentConfig::confirmed()),
+        )
+    }
+
+    fn encode(
+        &self,
+        address: &str,
+        config: &RpcAccountInfoConfig,
+        data_limit: Option<usize>,
+    ) -> ClientResult<Value> {
+        let address = parse_pubkey(address)?;
+        Ok(match self.accounts.get(&address) {
+            Some(account) => match data_limit {
+                Some(limit) if account.data.len() > limit => {
+                    let mut truncated = account.clone();
+                    truncated.data.truncate(limit);
+                    ui_account(&address, &truncated, config)
+                }
+                _ => ui_account(&address, account, config),
+            },
+            None => Value::Null,
+        })
+    }
+
+    /// The error of nodes behind min_context_slot
+    fn check_min_context_slot(&self, min_context_slot: Option<u64>) -> ClientResult<()> {
+        match min_context_slot {
+            Some(min_context_slot) if min_context_slot > self.slot => {
+                Err(ClientErrorKind::RpcError(RpcError::RpcResponseError {
+                    code: MIN_CONTEXT_SLOT_NOT_REACHED,
+                    message: "Minimum context slot has not been reached".to_string(),
+                    data: RpcResponseErrorData::Empty,
+                })
+                .into())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn program_accounts(&self, params: &Value) -> ClientResult<Value> {
+        let program_id = parse_pubkey(params[0].as_str().unwrap_or_default())?;
+        let config: RpcProgramAccountsConfig = match params.get(1) {
+            Some(config) => serde_json::from_value(config.clone())
+                .map_err(|err| mock_error(format!("invalid config: {}", err)))?,
+            None => RpcProgramAccountsConfig::default(),
+        };
+        self.check_min_context_slot(config.account_config.min_context_slot)?;
+        let filters = config.filters.unwrap_or_default();
+        let accounts = self
+            .accounts
+            .iter()
+            .filter(|(_, account)| {
+                account.owner == program_id
+                    && filters
+                        .iter()
+                        .all(|filter| filter_matches(filter, &account.data))
+            })
+            .map(|(address, account)| {
+                json!({
+                    "pubkey": address.to_string(),
+                    "account": ui_account(address, account, &config.account_config),
+                })
+            })
+            .collect();
+        Ok(match config.with_context {
+            Some(true) => self.response(Value::Array(accounts)),
+            _ => Value::Array(accounts),
+        })
+    }
+
+    fn signature_statuses(&self, params: &Value) -> ClientResult<Value> {
+        let sent = self.sent.lock().unwrap();
+        let statuses = params[0]
+            .as_array()
+            .ok_or_else(|| mock_error("signatures expected".to_string()))?
+            .iter()
+            .map(|signature| {
+                let signature = signature.as_str().unwrap_or_default();
+                let found = sent.iter().any(|transaction| {
+                    transaction
+                        .signatures
+                        .first()
+                        .map_or(false, |first| first.to_string() == signature)
+                });
+                if found {
+                    json!({
+                        "slot": self.slot,
+                        "confirmations": null,
+                        "err": null,
+                        "status": { "Ok": null },
+                        "confirmationStatus": "finalized",
+                    })
+                } else {
+                    Value::Null
+                }
+            })
+            .collect();
+        Ok(self.response(Value::Array(statuses)))
+    }
+
+    fn epoch_info(&self) -> Value {
+        let (epoch, slot_index) = self.epoch_schedule.get_epoch_and_slot_index(self.slot);
+        json!({
+            "absoluteSlot": self.slot,
+            "blockHeight": self.slot,
+            "epoch": epoch,
+            "slotIndex": slot_index,
+            "slotsInEpoch": self.epoch_schedule.get_slots_in_epoch(epoch),
+            "transactionCount": null,
+        })
+    }
+
+    fn blocks_with_limit(&self, params: &Value) -> ClientResult<Value> {
+        let start_slot = params[0]
+            .as_u64()
+            .ok_or_else(|| mock_error("start slot expected".to_string()))?;
+        let limit = params[1]
+            .as_u64()
+            .ok_or_else(|| mock_error("limit expected".to_string()))?;
+        if let Some(first) = self.block_times.keys().next() {
+            if start_slot < *first {
+                return Err(ClientErrorKind::RpcError(RpcError::RpcResponseError {
+                    code: BLOCK_CLEANED_UP,
+                    message: format!(
+                        "Block {} cleaned up, does not exist on node. First available block: {}",
+                        start_slot, first
+                    ),
+                    data: RpcResponseErrorData::Empty,
+                })
+                .into());
+            }
+        }
+        Ok(json!(self
+            .block_times
+            .range(start_slot..)
+            .take(limit as usize)
+            .map(|(slot, _)| *slot)
+            .collect::<Vec<_>>()))
+    }
+
+    fn block_time(&self, params: &Value) -> ClientResult<Value> {
+        let slot = params[0]
+            .as_u64()
+            .ok_or_else(|| mock_error("slot expected".to_string()))?;
+        match self.block_times.get(&slot) {
+            Some(timestamp) => Ok(json!(timestamp)),
+            None => Err(ClientErrorKind::RpcError(RpcError::RpcResponseError {
+                code: BLOCK_NOT_AVAILABLE,
+                message: format!("Block not available for slot {}", slot),
+                data: RpcResponseErrorData::Empty,
+            })
+            .into()),
+        }
+    }
+
+    fn response(&self, value: Value) -> Value {
+        json!({ "context": { "slot": self.slot }, "value": value })
+    }
+}
+
+fn mock_error(message: String) -> ClientError {
+    ClientErrorKind::Custom(message).into()
+}
+
+fn parse_pubkey(address: &str) -> ClientResult<Pubkey> {
+    address
+        .parse()
+        .map_err(|_| mock_error(format!("invalid pubkey {}", address)))
+}
+
+fn ui_account(address: &Pubkey, account: &Account, config: &RpcAccountInfoConfig) -> Value {
+    json!(UiAccount::encode(
+        address,
+        account,
+        config.encoding.unwrap_or(UiAccountEncoding::Base64),
+        None,
+        config.data_slice,
+    ))
+}
+
+fn filter_matches(filter: &RpcFilterType, data: &[u8]) -> bool {
+    match filter {
+        RpcFilterType::DataSize(size) => data.len() as u64 == *size,
+        RpcFilterType::Memcmp(memcmp) => memcmp.bytes_match(data),
+        RpcFilterType::TokenAccountState => spl_token::state::Account::unpack(data).is_ok(),
+    }
+}
+
+/// Transaction of sendTransaction params, base64 encoded as RpcClient sends it
+fn sent_transaction(params: &Value) -> ClientResult<Transaction> {
+    let encoded = params[0]
+        .as_str()
+        .ok_or_else(|| mock_error("transaction expected".to_string()))?;
+    let data = base64::decode(encoded)
+        .map_err(|err| mock_error(format!("invalid base64 transaction: {}", err)))?;
+    bincode::deserialize(&data).map_err(|err| mock_error(format!("invalid transaction: {}", err)))
+}
+
+fn config(params: &Value) -> ClientResult<RpcAccountInfoConfig> {
+    match params.get(1) {
+        Some(config) => serde_json::from_value(config.clone())
+            .map_err(|err| mock_error(format!("invalid config: {}", err))),
+        None => Ok(RpcAccountInfoConfig::default()),
+    }
+}
+
+#[async_trait]
+impl RpcSender for MockRpc {
+    async fn send(&self, request: RpcRequest, params: Value) -> ClientResult<Value> {
+        match request {
+            RpcRequest::GetAccountInfo => {
+                let address = params[0].as_str().unwrap_or_default();
+                let config = config(&params)?;
+                self.check_min_context_slot(config.min_context_slot)?;
+                Ok(self.response(self.encode(address, &config, None)?))
+            }
+            RpcRequest::GetMultipleAccounts => {
+                let config = config(&params)?;
+                self.check_min_context_slot(config.min_context_slot)?;
+                let accounts = params[0]
+                    .as_array()
+                    .ok_or_else(|| mock_error("addresses expected".to_string()))?
+                    .iter()
+                    .map(|address| {
+                        self.encode(
+                            address.as_str().unwrap_or_default(),
+                            &config,
+                            self.multiple_accounts_data_limit,
+                        )
+                    })
+                    .collect::<ClientResult<Vec<_>>>()?;
+                Ok(self.response(Value::Array(accounts)))
+            }
+            RpcRequest::GetProgramAccounts => self.program_accounts(&params),
+            RpcRequest::GetSlot => Ok(json!(self.slot)),
+            RpcRequest::GetVersion => Ok(json!({ "solana-core": "1.16.0", "feature-set": 0 })),
+            RpcRequest::GetLatestBlockhash => Ok(self.response(json!({
+                "blockhash": Hash::default().to_string(),
+                "lastValidBlockHeight": self.slot + 150,
+            }))),
+            RpcRequest::SendTransaction => {
+                let transaction = sent_transaction(&params)?;
+                let signature = transaction.signatures.first().copied().unwrap_or_default();
+                let mut sent = self.sent.lock().unwrap();
+                if self.send_limit.map_or(false, |limit| sent.len() >= limit) {
+                    return Err(mock_error("transaction rejected".to_string()));
+                }
+                sent.push(transaction);
+                Ok(json!(signature.to_string()))
+            }
+            RpcRequest::GetSignatureStatuses => self.signature_statuses(&params),
+            RpcRequest::GetEpochInfo => Ok(self.epoch_info()),
+            RpcRequest::GetEpochSchedule => Ok(json!(self.epoch_schedule)),
+            RpcRequest::GetBlocksWithLimit => self.blocks_with_limit(&params),
+            RpcRequest::GetBlockTime => self.block_time(&params),
+            request => Err(mock_error(format!("{} is not mocked", request))),
+        }
+    }
+
+    fn get_transport_stats(&self) -> RpcTransportStats {
+        RpcTransportStats::default()
+    }
+
+    fn url(&self) -> String {
+        "mock".to_string()
+    }
+}
@@ -0,0 +1,283 @@
+//! Ticket accounts at addresses derived from a base key and a seed, so a retried
+//! order_unstake transaction can be rebuilt for the same ticket without a new keypair
+//! and without failing on "account already exists".
+//!
+//! Also helpers for trading tickets. The program pays a claimed ticket only to its beneficiary
+//! and has no instruction changing it: an existing ticket can not be sold, whoever holds
+//! the ticket keypair or address. A deal has to create the ticket for the buyer instead,
+//! see otc_ticket_sale.
+//!
+//! plan_claims groups the claims of every matured ticket of a beneficiary into transactions,
+//! client::claim_all finds the tickets with getProgramAccounts. claim_checked guards a single
+//! claim against paying an account other than the beneficiary.
+
+use derive_more::{Display, From};
+use micro_anchor::{legacy_transaction_size, Discriminator, PACKET_DATA_SIZE};
+use solana_program::{
+    clock::Clock,
+    instruction::Instruction,
+    program_error::ProgramError,
+    pubkey::{Pubkey, PubkeyError},
+    system_instruction,
+};
+
+use crate::{
+    error::{CommonError, Mismatch},
+    instructions::order_unstake::OrderUnstakeData,
+    located::Located,
+    program::MarinadeProgram,
+    state::{
+        delayed_unstake_ticket::DelayedUnstakeTicket,
+        fee::Fee,
+        marinade::{Marinade, MarinadeHelpers},
+    },
+};
+
+#[derive(Debug, Display, From)]
+pub enum TicketError {
+    Pubkey(PubkeyError),
+    /// the ticket was already created, e.g. by an earlier attempt that did land
+    #[display(fmt = "Ticket {} is already in use", _0)]
+    #[from(ignore)]
+    InUse(Pubkey),
+    #[display(fmt = "Account {} is not an empty ticket account", _0)]
+    #[from(ignore)]
+    NotATicketAccount(Pubkey),
+    /// claims of the ticket pay this beneficiary, never the buyer
+    #[display(
+        fmt = "Ticket pays {}, its beneficiary can not be changed to {}",
+        beneficiary,
+        buyer
+    )]
+    #[from(ignore)]
+    BeneficiaryNotBuyer {
+        beneficiary: Pubkey,
+        buyer: Pubkey,
+    },
+    Token(ProgramError),
+    /// claim_checked to another account than the beneficiary, which the program rejects
+    #[display(
+        fmt = "Ticket {} pays only its beneficiary {}, not {}",
+        ticket,
+        beneficiary,
+        recipient
+    )]
+    #[from(ignore)]
+    ThirdPartyRecipient {
+        ticket: Pubkey,
+        beneficiary: Pubkey,
+        recipient: Pubkey,
+    },
+}
+
+impl std::error::Error for TicketError {}
+
+pub fn ticket_address_with_seed(base: &Pubkey, seed: &str) -> Result<Pubkey, PubkeyError> {
+    MarinadeProgram::default().ticket_address_with_seed(base, seed)
+}
+
+/// Fetched ticket account, None when it does not exist
+#[derive(Clone, Copy, Debug)]
+pub struct ExistingTicketAccount<'a> {
+    pub owner: &'a Pubkey,
+    pub data: &'a [u8],
+}
+
+/// order_unstake into the ticket at base+seed, preceded by create_account_with_seed only when
+/// the account does not exist yet. base must sign, payer funds the rent.
+/// Returns the ticket address with the instructions.
+#[allow(clippy::too_many_arguments)]
+pub fn order_unstake_with_seed<T: Located<Marinade>>(
+    state: &T,
+    data: OrderUnstakeData,
+    burn_msol_from: Pubkey,
+    burn_msol_authority: Pubkey,
+    payer: Pubkey,
+    base: Pubkey,
+    seed: &str,
+    rent_exempt_lamports: u64,
+    existing: Option<ExistingTicketAccount>,
+) -> Result<(Pubkey, Vec<Instruction>), TicketError> {
+    let program_id = state.program_id();
+    let ticket = MarinadeProgram::new(program_id).ticket_address_with_seed(&base, seed)?;
+    let mut instructions = Vec::with_capacity(2);
+    match existing {
+        None => instructions.push(system_instruction::create_account_with_seed(
+            &payer,
+            &ticket,
+            &base,
+            seed,
+            rent_exempt_lamports,
+            DelayedUnstakeTicket::ACCOUNT_LEN as u64,
+            &program_id,
+        )),
+        Some(account) => {
+            if *account.owner != program_id
+                || account.data.len() != DelayedUnstakeTicket::ACCOUNT_LEN
+            {
+                return Err(TicketError::NotATicketAccount(ticket));
+            }
+            if account.data[..8] == DelayedUnstakeTicket::DISCRIMINATOR {
+                return Err(TicketError::InUse(ticket));
+            }
+        }
+    }
+    instructions.push(state.order_unstake(data, burn_msol_from, burn_msol_authority, ticket));
+    Ok((ticket, instructions))
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TicketValuation {
+    /// Paid by claim, fixed when the ticket was created
+    pub lamports_amount: u64,
+    /// First epoch the ticket can be claimed in
+    pub due_epoch: u64,
+    pub epochs_to_maturity: u64,
+    /// lamports_amount less discount_per_epoch for every epoch to maturity
+    pub present_lamports: u64,
+    /// present_lamports in mSOL at the current price
+    pub msol_equivalent: u64,
+}
+
+/// Most epochs value_ticket discounts for. A ticket is due the epoch after it was created,
+/// more epochs to maturity mean current_epoch is not the cluster epoch
+pub const MAX_DISCOUNTED_EPOCHS: u64 = 16;
+
+/// Value of a ticket for a buyer at the current mSOL price.
+/// discount_per_epoch is the buyer's compensation for waiting, zero for face value.
+/// Fails with NumberTooHigh beyond MAX_DISCOUNTED_EPOCHS epochs to maturity
+pub fn value_ticket(
+    ticket: &DelayedUnstakeTicket,
+    marinade: &Marinade,
+    current_epoch: u64,
+    discount_per_epoch: Fee,
+) -> Result<TicketValuation, CommonError> {
+    discount_per_epoch.check()?;
+    let due_epoch = ticket.due_epoch();
+    let epochs_to_maturity = due_epoch.saturating_sub(current_epoch);
+    if epochs_to_maturity > MAX_DISCOUNTED_EPOCHS {
+        return Err(CommonError::NumberTooHigh(Some(Mismatch::new(
+            MAX_DISCOUNTED_EPOCHS,
+            epochs_to_maturity,
+        ))));
+    }
+    let mut present_lamports = ticket.lamports_amount;
+    for _ in 0..epochs_to_maturity {
+        present_lamports -= discount_per_epoch.apply(present_lamports)?;
+    }
+    Ok(TicketValuation {
+        lamports_amount: ticket.lamports_amount,
+        due_epoch,
+        epochs_to_maturity,
+        present_lamports,
+        msol_equivalent: marinade.calc_msol_from_lamports(present_lamports)?,
+    })
+}
+
+/// Ticket listed for sale is worth something to buyer only if buyer is already its beneficiary
+pub fn check_ticket_purchase(
+    ticket: &DelayedUnstakeTicket,
+    buyer: &Pubkey,
+) -> Result<(), TicketError> {
+    if ticket.beneficiary != *buyer {
+        return Err(TicketError::BeneficiaryNotBuyer {
+            beneficiary: ticket.beneficiary,
+            buyer: *buyer,
+        });
+    }
+    Ok(())
+}
+
+/// Example of a safe OTC ticket deal, to be sent as one transaction signed by both parties:
+/// the buyer pays the seller, the seller delegates the mSOL to the buyer and the buyer
+/// orders the unstake into a new ticket at buyer+seed, so the buyer is its beneficiary.
+/// Either everything lands or nothing does, no party has to trust the other.
+/// The delegation is consumed by the burn, nothing is left approved.
+#[allow(clippy::too_many_arguments)]
+pub fn otc_ticket_sale<T: Located<Marinade>>(
+    state: &T,
+    msol_amount: u64,
+    price_lamports: u64,
+    seller: Pubkey,
+    seller_msol_account: Pubkey,
+    buyer: Pubkey,
+    seed: &str,
+    rent_exempt_lamports: u64,
+) -> Result<(Pubkey, Vec<Instruction>), TicketError> {
+    let mut instructions = vec![
+        system_instruction::transfer(&buyer, &seller, price_lamports),
+        spl_token::instruction::approve(
+            &spl_token::ID,
+            &seller_msol_account,
+            &buyer,
+            &seller,
+            &[],
+            msol_amount,
+        )?,
+    ];
+    let (ticket, order) = order_unstake_with_seed(
+        state,
+        OrderUnstakeData::new(msol_amount),
+        seller_msol_account,
+        buyer,
+        buyer,
+        buyer,
+        seed,
+        rent_exempt_lamports,
+        None,
+    )?;
+    instructions.extend(order);
+    Ok((ticket, instructions))
+}
+
+/// Claims of the tickets of beneficiary claimable at clock, packed into as few legacy
+/// transactions as the size limit allows (a separate fee payer counted). Tickets of another
+/// state or beneficiary are left out, claim pays the SOL to the beneficiary only
+pub fn plan_claims<T: Located<Marinade>>(
+    state: &T,
+    beneficiary: &Pubkey,
+    tickets: &[(Pubkey, DelayedUnstakeTicket)],
+    clock: &Clock,
+) -> Result<Vec<Vec<Instruction>>, PubkeyError> {
+    let mut transactions: Vec<Vec<Instruction>> = Vec::new();
+    for (address, ticket) in tickets {
+        if ticket.state_address != state.key()
+            || ticket.beneficiary != *beneficiary
+            || !ticket.is_claimable(clock)
+        {
+            continue;
+        }
+        let claim = state.claim(*address, *beneficiary)?;
+        match transactions.last_mut() {
+            Some(instructions) => {
+                instructions.push(claim);
+                if legacy_transaction_size(instructions) > PACKET_DATA_SIZE {
+                    let claim = instructions.pop().unwrap();
+                    transactions.push(vec![claim]);
+                }
+            }
+            None => transactions.push(vec![claim]),
+        }
+    }
+    Ok(transactions)
+}
+
+/// claim of the ticket at ticket_account paying transfer_sol_to, refused with
+/// TicketError::ThirdPartyRecipient when that is not the ticket beneficiary: the program
+/// fails such a claim with WrongBeneficiary, so there is no way to allow it. Catches
+/// recipients copy-pasted from another ticket or wallet before the transaction is sent.
+pub fn claim_checked<T: Located<Marinade>>(
+    state: &T,
+    ticket_account: Pubkey,
+    ticket: &DelayedUnstakeTicket,
+    transfer_sol_to: Pubkey,
+) -> Result<Instruction, TicketError> {
+    if transfer_sol_to != ticket.beneficiary {
+        return Err(TicketError::ThirdPartyRecipient {
+            ticket: ticket_account,
+            beneficiary: ticket.beneficiary,
+            recipient: transfer_sol_to,
+        });
+    }
+    Ok(state.claim(ticket_account, transfer_sol_to)?)
+}
@@ -0,0 +1,287 @@
+//! Transactions chaining several Marinade operations, e.g. creating the mSOL ATA and
+//! depositing, or creating and funding a ticket account and ordering an unstake into it.
+//! The builder keeps the keypairs it generates (ticket accounts) and signs with them itself,
+//! the caller provides the other signers (see MarinadeTransactionBuilder::signers).
+//! order_unstake_with_ticket is the ticket creation on its own, for callers assembling
+//! their transactions themselves.
+
+use derive_more::{Display, From};
+use solana_program::{instruction::Instruction, pubkey::Pubkey, rent::Rent, system_instruction};
+use solana_sdk::{
+    address_lookup_table_account::AddressLookupTableAccount,
+    hash::Hash,
+    message::{v0, CompileError, Message, VersionedMessage},
+    signature::Keypair,
+    signer::{Signer, SignerError},
+    transaction::{Transaction, VersionedTransaction},
+};
+
+use crate::{
+    ata::{associated_token_address, create_associated_token_account_idempotent},
+    builders::{
+        AddLiquidityBuilder, BuildError, DepositBuilder, LiquidUnstakeBuilder, OrderUnstakeBuilder,
+    },
+    instructions::order_unstake::OrderUnstakeData,
+    located::Located,
+    state::{
+        delayed_unstake_ticket::DelayedUnstakeTicket,
+        marinade::{Marinade, MarinadeHelpers},
+    },
+};
+
+#[derive(Debug, Display, From)]
+pub enum TxError {
+    Build(BuildError),
+    Compile(CompileError),
+    Signer(SignerError),
+    #[display(fmt = "Signer {} is missing", _0)]
+    #[from(ignore)]
+    MissingSigner(Pubkey),
+}
+
+impl std::error::Error for TxError {}
+
+/// Lamports a ticket account needs to be rent exempt
+pub fn ticket_rent_exempt_lamports(rent: &Rent) -> u64 {
+    rent.minimum_balance(DelayedUnstakeTicket::ACCOUNT_LEN)
+}
+
+fn create_ticket_account(
+    payer: &Pubkey,
+    ticket: &Pubkey,
+    rent_exempt_lamports: u64,
+    program_id: &Pubkey,
+) -> Instruction {
+    system_instruction::create_account(
+        payer,
+        ticket,
+        rent_exempt_lamports,
+        DelayedUnstakeTicket::ACCOUNT_LEN as u64,
+        program_id,
+    )
+}
+
+/// create_account of a generated ticket keypair, funded by payer with the rent exemption of
+/// a ticket, followed by order_unstake into it. The ticket keypair signs the transaction
+/// with payer and burn_msol_authority
+pub fn order_unstake_with_ticket<T: Located<Marinade>>(
+    state: &T,
+    data: OrderUnstakeData,
+    burn_msol_from: Pubkey,
+    burn_msol_authority: Pubkey,
+    payer: Pubkey,
+    rent: &Rent,
+) -> (Keypair, Vec<Instruction>) {
+    let ticket = Keypair::new();
+    let instructions = vec![
+        create_ticket_account(
+            &payer,
+            &ticket.pubkey(),
+            ticket_rent_exempt_lamports(rent),
+            &state.program_id(),
+        ),
+        state.order_unstake(data, burn_msol_from, burn_msol_authority, ticket.pubkey()),
+    ];
+    (ticket, instructions)
+}
+
+pub struct MarinadeTransactionBuilder<'a, T> {
+    state: &'a T,
+    payer: Pubkey,
+    instructions: Vec<Instruction>,
+    keypairs: Vec<Keypair>,
+}
+
+impl<'a, T: Located<Marinade>> MarinadeTransactionBuilder<'a, T> {
+    /// payer signs, pays the fees and funds the accounts created on the way
+    pub fn new(state: &'a T, payer: Pubkey) -> Self {
+        Self {
+            state,
+            payer,
+            instructions: vec![],
+            keypairs: vec![],
+        }
+    }
+
+    pub fn payer(&self) -> Pubkey {
+        self.payer
+    }
+
+    pub fn instructions(&self) -> &[Instruction] {
+        &self.instructions
+    }
+
+    /// Accounts generated by the builder, they sign the transaction
+    pub fn keypairs(&self) -> &[Keypair] {
+        &self.keypairs
+    }
+
+    /// Any other instruction, e.g. a compute budget or memo
+    pub fn instruction(&mut self, instruction: Instruction) -> &mut Self {
+        self.instructions.push(instruction);
+        self
+    }
+
+    /// Idempotent creation of the ATA of wallet for mint
+    pub fn create_ata(&mut self, wallet: &Pubkey, mint: &Pubkey) -> &mut Self {
+        self.instruction(create_associated_token_account_idempotent(
+            &self.payer,
+            wallet,
+            mint,
+        ))
+    }
+
+    /// deposit from wallet into its mSOL ATA, created first with create_ata
+    pub fn deposit(
+        &mut self,
+        wallet: Pubkey,
+        lamports: u64,
+        create_ata: bool,
+    ) -> Result<&mut Self, BuildError> {
+        let deposit = DepositBuilder::new(self.state)
+            .lamports(lamports)
+            .transfer_from(wallet)
+            .build()?;
+        if create_ata {
+            let msol_mint = self.state.as_ref().msol_mint;
+            self.create_ata(&wallet, &msol_mint);
+        }
+        Ok(self.instruction(deposit))
+    }
+
+    /// liquid_unstake from the mSOL ATA of wallet, the SOL goes to wallet
+    pub fn liquid_unstake(
+        &mut self,
+        wallet: Pubkey,
+        msol_amount: u64,
+    ) -> Result<&mut Self, BuildError> {
+        let unstake = LiquidUnstakeBuilder::new(self.state)
+            .msol_amount(msol_amount)
+            .owner(wallet)
+            .build()?;
+        Ok(self.instruction(unstake))
+    }
+
+    /// add_liquidity from wallet into its LP ATA, created first with create_ata
+    pub fn add_liquidity(
+        &mut self,
+        wallet: Pubkey,
+        lamports: u64,
+        create_ata: bool,
+    ) -> Result<&mut Self, BuildError> {
+        let add_liquidity = AddLiquidityBuilder::new(self.state)
+            .lamports(lamports)
+            .transfer_from(wallet)
+            .build()?;
+        if create_ata {
+            let lp_mint = self.state.as_ref().liq_pool.lp_mint;
+            self.create_ata(&wallet, &lp_mint);
+        }
+        Ok(self.instruction(add_liquidity))
+    }
+
+    /// order_unstake from the mSOL ATA of wallet into a new ticket account, created with a
+    /// generated keypair and funded with rent_exempt_lamports (see ticket_rent_exempt_lamports)
+    /// by the payer.
+    /// The ticket address is the last of keypairs()
+    pub fn order_unstake(
+        &mut self,
+        wallet: Pubkey,
+        msol_amount: u64,
+        rent_exempt_lamports: u64,
+    ) -> Result<&mut Self, BuildError> {
+        let ticket = Keypair::new();
+        let order_unstake = OrderUnstakeBuilder::new(self.state)
+            .msol_amount(msol_amount)
+            .owner(wallet)
+            .new_ticket_account(ticket.pubkey())
+            .build()?;
+        self.instruction(create_ticket_account(
+            &self.payer,
+            &ticket.pubkey(),
+            rent_exempt_lamports,
+            &self.state.program_id(),
+        ));
+        self.keypairs.push(ticket);
+        Ok(self.instruction(order_unstake))
+    }
+
+    /// mSOL ATA of wallet, the account deposit mints to and liquid_unstake burns from
+    pub fn msol_account(&self, wallet: &Pubkey) -> Pubkey {
+        associated_token_address(wallet, &self.state.as_ref().msol_mint)
+    }
+
+    /// Required signers, payer first; the ones of keypairs() are signed by the builder
+    pub fn signers(&self) -> Vec<Pubkey> {
+        let mut signers = vec![self.payer];
+        for meta in self.instructions.iter().flat_map(|ix| &ix.accounts) {
+            if meta.is_signer && !signers.contains(&meta.pubkey) {
+                signers.push(meta.pubkey);
+            }
+        }
+        signers
+    }
+
+    pub fn message(&self) -> Message {
+        Message::new(&self.instructions, Some(&self.payer))
+    }
+
+    /// v0 message, accounts found in the lookup tables are loaded from them
+    pub fn v0_message(
+        &self,
+        address_lookup_tables: &[AddressLookupTableAccount],
+        recent_blockhash: Hash,
+    ) -> Result<VersionedMessage, CompileError> {
+        Ok(VersionedMessage::V0(v0::Message::try_compile(
+            &self.payer,
+            &self.instructions,
+            address_lookup_tables,
+            recent_blockhash,
+        )?))
+    }
+
+    /// The signers of signer_keys in their order, from the provided ones and keypairs()
+    fn ordered_signers<'s>(
+        &'s self,
+        signer_keys: &[Pubkey],
+        signers: &[&'s dyn Signer],
+    ) -> Result<Vec<&'s dyn Signer>, TxError> {
+        signer_keys
+            .iter()
+            .map(|key| {
+                signers
+                    .iter()
+                    .copied()
+                    .chain(self.keypairs.iter().map(|keypair| keypair as &dyn Signer))
+                    .find(|signer| signer.pubkey() == *key)
+                    .ok_or(TxError::MissingSigner(*key))
+            })
+            .collect()
+    }
+
+    pub fn legacy_transaction(
+        &self,
+        signers: &[&dyn Signer],
+        recent_blockhash: Hash,
+    ) -> Result<Transaction, TxError> {
+        let message = self.message();
+        let signer_keys = &message.account_keys[..message.header.num_required_signatures as usize];
+        let signers = self.ordered_signers(signer_keys, signers)?;
+        let mut transaction = Transaction::new_unsigned(message);
+        transaction.try_sign(&signers, recent_blockhash)?;
+        Ok(transaction)
+    }
+
+    pub fn versioned_transaction(
+        &self,
+        signers: &[&dyn Signer],
+        address_lookup_tables: &[AddressLookupTableAccount],
+        recent_blockhash: Hash,
+    ) -> Result<VersionedTransaction, TxError> {
+        let message = self.v0_message(address_lookup_tables, recent_blockhash)?;
+        let signer_keys =
+            &message.static_account_keys()[..message.header().num_required_signatures as usize];
+        let signers = self.ordered_signers(signer_keys, signers)?;
+        Ok(VersionedTransaction::try_new(message, &signers)?)
+    }
+}
@@ -0,0 +1,125 @@
+//! Decentralization metrics of the stake Marinade delegates, computed the same way for every
+//! consumer: Nakamoto coefficient, top-N share and exposure to the network superminority.
+
+use std::collections::HashSet;
+
+use solana_program::pubkey::Pubkey;
+
+use crate::{
+    calc::proportional,
+    error::CommonError,
+    state::{fee::Fee, validator_system::ValidatorRecord},
+};
+
+/// Validator list records with the live stake of every vote account of the cluster
+/// (activated_stake of getVoteAccounts, current and delinquent)
+#[derive(Clone, Debug)]
+pub struct ValidatorReport {
+    pub validators: Vec<ValidatorRecord>,
+    pub network_stakes: Vec<(Pubkey, u64)>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Concentration {
+    /// Validators with Marinade stake
+    pub staked_validators: u32,
+    pub total_stake: u64,
+    /// Fewest validators holding more than a third of the Marinade stake
+    pub nakamoto_coefficient: u32,
+    pub top_10_share: Fee,
+    /// Marinade validators that are part of the network superminority
+    pub superminority_validators: u32,
+    /// Share of the Marinade stake delegated to the network superminority
+    pub superminority_exposure: Fee,
+}
+
+fn share(part: u64, total: u64) -> Result<Fee, CommonError> {
+    if total == 0 {
+        return Ok(Fee::from_basis_points(0));
+    }
+    Ok(Fee::from_basis_points(
+        proportional(10_000, part, total)? as u32
+    ))
+}
+
+/// Stakes in descending order
+fn sorted_desc(stakes: impl Iterator<Item = u64>) -> Vec<u64> {
+    let mut stakes: Vec<u64> = stakes.filter(|stake| *stake > 0).collect();
+    stakes.sort_unstable_by(|a, b| b.cmp(a));
+    stakes
+}
+
+/// Number of the largest stakes needed to exceed a third of the total
+fn nakamoto(sorted: &[u64]) -> u32 {
+    let total: u128 = sorted.iter().map(|stake| *stake as u128).sum();
+    let mut cumulative: u128 = 0;
+    for (index, stake) in sorted.iter().enumerate() {
+        cumulative += *stake as u128;
+        if cumulative * 3 > total {
+            return index as u32 + 1;
+        }
+    }
+    0
+}
+
+impl ValidatorReport {
+    pub fn new(validators: Vec<ValidatorRecord>, network_stakes: Vec<(Pubkey, u64)>) -> Self {
+        Self {
+            validators,
+            network_stakes,
+        }
+    }
+
+    pub fn total_stake(&self) -> u64 {
+        self.validators
+            .iter()
+            .map(|validator| validator.active_balance)
+            .sum()
+    }
+
+    /// Share of the Marinade stake held by the n largest validators
+    pub fn top_n_share(&self, n: usize) -> Result<Fee, CommonError> {
+        let sorted = sorted_desc(self.validators.iter().map(|v| v.active_balance));
+        share(sorted.iter().take(n).sum(), sorted.iter().sum())
+    }
+
+    /// Vote accounts of the smallest set of largest network validators holding more than a third
+    /// of the cluster stake
+    pub fn superminority(&self) -> HashSet<Pubkey> {
+        let mut network: Vec<&(Pubkey, u64)> = self
+            .network_stakes
+            .iter()
+            .filter(|(_, stake)| *stake > 0)
+            .collect();
+        network.sort_unstable_by(|a, b| b.1.cmp(&a.1));
+        let count = nakamoto(&network.iter().map(|(_, stake)| *stake).collect::<Vec<_>>());
+        network
+            .iter()
+            .take(count as usize)
+            .map(|(vote, _)| *vote)
+            .collect()
+    }
+
+    pub fn concentration(&self) -> Result<Concentration, CommonError> {
+        let sorted = sorted_desc(self.validators.iter().map(|v| v.active_balance));
+        let total_stake: u64 = sorted.iter().sum();
+        let superminority = self.superminority();
+        let (superminority_validators, superminority_stake) = self
+            .validators
+            .iter()
+            .filter(|validator| {
+                validator.active_balance > 0 && superminority.contains(&validator.validator_account)
+            })
+            .fold((0, 0), |(count, stake), validator| {
+                (count + 1, stake + validator.active_balance)
+            });
+        Ok(Concentration {
+            staked_validators: sorted.len() as u32,
+            total_stake,
+            nakamoto_coefficient: nakamoto(&sorted),
+            top_10_share: share(sorted.iter().take(10).sum(), total_stake)?,
+            superminority_validators,
+            superminority_exposure: share(superminority_stake, total_stake)?,
+        })
+    }
+}
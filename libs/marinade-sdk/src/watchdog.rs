@@ -0,0 +1,235 @@
+//! Watchdog over the per-epoch maintenance crank: every stake account has to be updated
+//! early in the epoch and the stake-delta has to run at its end. Check it periodically
+//! (e.g. every few minutes) with the stake list, the vote accounts the stakes are delegated
+//! to and the current epoch position.
+//! With self_heal the report also carries the overdue updates (planner::plan_updates), which
+//! recovery_instructions turns into transactions anyone can send: update instructions are
+//! permissionless. The stake-delta itself is left to the operator's crank.
+
+use derive_more::Display;
+use micro_anchor::{legacy_transaction_size, InstructionBuilder, PACKET_DATA_SIZE};
+use solana_program::{
+    instruction::Instruction,
+    pubkey::{Pubkey, PubkeyError},
+    stake, system_program,
+    sysvar::{clock, stake_history},
+};
+
+use crate::{
+    instructions::{
+        update_active::{UpdateActiveAccounts, UpdateActiveData},
+        update_deactivated::{UpdateDeactivatedAccounts, UpdateDeactivatedData},
+    },
+    located::Located,
+    planner::{plan_updates, DelegatedStake, EpochPosition, UpdateAction},
+    state::{
+        marinade::{Marinade, MarinadeHelpers},
+        stake_system::StakeSystemHelpers,
+        validator_system::ValidatorRecord,
+    },
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct WatchdogConfig {
+    /// Slots after the start of the epoch the crank has to update every stake account in
+    pub update_window_slots: u64,
+    /// Plan the overdue updates when the window passed
+    pub self_heal: bool,
+}
+
+impl Default for WatchdogConfig {
+    fn default() -> Self {
+        Self {
+            // about 4 hours
+            update_window_slots: 36_000,
+            self_heal: false,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Display, PartialEq, Eq)]
+pub enum CrankAlert {
+    #[display(
+        fmt = "{} stake accounts not updated {} slots into epoch {}",
+        stakes,
+        slot_index,
+        epoch
+    )]
+    UpdatesOverdue {
+        epoch: u64,
+        slot_index: u64,
+        stakes: u32,
+    },
+    /// The stake-delta did not run in the previous epoch. It is not needed when the delta is
+    /// neutral, which is rare on mainnet
+    #[display(fmt = "Stake delta did not run since epoch {}", _0)]
+    StakeDeltaMissed(u64),
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct WatchdogReport {
+    pub alerts: Vec<CrankAlert>,
+    /// Overdue updates, empty without self_heal or before the update window passed
+    pub recovery: Vec<UpdateAction>,
+}
+
+impl WatchdogReport {
+    pub fn is_ok(&self) -> bool {
+        self.alerts.is_empty()
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Watchdog {
+    pub config: WatchdogConfig,
+}
+
+impl Watchdog {
+    pub fn new(config: WatchdogConfig) -> Self {
+        Self { config }
+    }
+
+    /// is_deactivated tells the fully deactivated stake accounts apart, as for plan_updates
+    pub fn check<F: Fn(&DelegatedStake) -> bool>(
+        &self,
+        marinade: &Marinade,
+        validators: &[ValidatorRecord],
+        stakes: &[DelegatedStake],
+        position: &EpochPosition,
+        is_deactivated: F,
+    ) -> WatchdogReport {
+        let mut report = WatchdogReport::default();
+        let last_stake_delta_epoch = marinade.stake_system.last_stake_delta_epoch;
+        if last_stake_delta_epoch.saturating_add(1) < position.epoch {
+            report
+                .alerts
+                .push(CrankAlert::StakeDeltaMissed(last_stake_delta_epoch));
+        }
+        if position.slot_index < self.config.update_window_slots {
+            return report;
+        }
+        let overdue = stakes
+            .iter()
+            .filter(|stake| stake.record.last_update_epoch < position.epoch)
+            .count() as u32;
+        if overdue > 0 {
+            report.alerts.push(CrankAlert::UpdatesOverdue {
+                epoch: position.epoch,
+                slot_index: position.slot_index,
+                stakes: overdue,
+            });
+            if self.config.self_heal {
+                report.recovery = plan_updates(validators, stakes, position.epoch, is_deactivated);
+            }
+        }
+        report
+    }
+}
+
+/// Records the rewards of an active stake account, once per epoch
+fn update_active<T: Located<Marinade>>(
+    state: &T,
+    data: UpdateActiveData,
+    stake_account: Pubkey,
+) -> Result<Instruction, PubkeyError> {
+    let builder = InstructionBuilder {
+        accounts: UpdateActiveAccounts {
+            marinade: state.key(),
+            stake_list: *state.as_ref().stake_system.stake_list_address(),
+            stake_account,
+            stake_withdraw_authority: state.try_stake_withdraw_authority()?,
+            reserve_pda: state.try_reserve_address()?,
+            msol_mint: state.as_ref().msol_mint,
+            msol_mint_authority: state.try_msol_mint_authority()?,
+            treasury_msol_account: state.as_ref().treasury_msol_account,
+            clock: clock::ID,
+            stake_history: stake_history::ID,
+            stake_program: stake::program::ID,
+            token_program: spl_token::ID,
+            validator_list: *state.as_ref().validator_system.validator_list_address(),
+        },
+        data,
+    };
+    Ok(builder.instruction(state.program_id()))
+}
+
+/// Moves the lamports of a fully deactivated stake account to the reserve and removes it
+/// from the stake list
+fn update_deactivated<T: Located<Marinade>>(
+    state: &T,
+    data: UpdateDeactivatedData,
+    stake_account: Pubkey,
+) -> Result<Instruction, PubkeyError> {
+    let builder = InstructionBuilder {
+        accounts: UpdateDeactivatedAccounts {
+            marinade: state.key(),
+            stake_list: *state.as_ref().stake_system.stake_list_address(),
+            stake_account,
+            stake_withdraw_authority: state.try_stake_withdraw_authority()?,
+            reserve_pda: state.try_reserve_address()?,
+            msol_mint: state.as_ref().msol_mint,
+            msol_mint_authority: state.try_msol_mint_authority()?,
+            treasury_msol_account: state.as_ref().treasury_msol_account,
+            clock: clock::ID,
+            stake_history: stake_history::ID,
+            stake_program: stake::program::ID,
+            token_program: spl_token::ID,
+            operational_sol_account: state.as_ref().operational_sol_account,
+            system_program: system_program::ID,
+        },
+        data,
+    };
+    Ok(builder.instruction(state.program_id()))
+}
+
+/// The update instructions of actions packed into legacy transactions, in plan order, at most
+/// max_per_transaction each: updates mint the rewards fee and take far more compute than a
+/// transfer. Actions of stakes missing from stakes are skipped
+pub fn recovery_instructions<T: Located<Marinade>>(
+    state: &T,
+    stakes: &[DelegatedStake],
+    actions: &[UpdateAction],
+    max_per_transaction: usize,
+) -> Result<Vec<Vec<Instruction>>, PubkeyError> {
+    let stake_account = |stake_index: u32| -> Option<Pubkey> {
+        stakes
+            .iter()
+            .find(|stake| stake.stake_index == stake_index)
+            .map(|stake| stake.record.stake_account)
+    };
+    let mut transactions: Vec<Vec<Instruction>> = Vec::new();
+    for action in actions {
+        let stake_account = match stake_account(action.stake_index()) {
+            Some(stake_account) => stake_account,
+            None => continue,
+        };
+        let update = match *action {
+            UpdateAction::Active {
+                stake_index,
+                validator_index,
+            } => update_active(
+                state,
+                UpdateActiveData::new(stake_index, validator_index),
+                stake_account,
+            )?,
+            UpdateAction::Deactivated { stake_index } => update_deactivated(
+                state,
+                UpdateDeactivatedData::new(stake_index),
+                stake_account,
+            )?,
+        };
+        match transactions.last_mut() {
+            Some(instructions) => {
+                instructions.push(update);
+                if instructions.len() > max_per_transaction
+                    || legacy_transaction_size(instructions) > PACKET_DATA_SIZE
+                {
+                    let update = instructions.pop().unwrap();
+                    transactions.push(vec![update]);
+                }
+            }
+            None => transactions.push(vec![update]),
+        }
+    }
+    Ok(transactions)
+}
@@ -0,0 +1,238 @@
+//! Delayed unstake for custodians: user withdrawal requests become tickets of the custodian,
+//! claimed into the custodian wallet once they mature. The custodian credits the users off chain.
+//!
+//! Tickets are created at custodian+seed addresses (see ticket::order_unstake_with_seed) with
+//! a seed derived from the request id, so every ticket can be found again after a restart.
+//! The queue is persisted in a Storage before any instruction is handed out; the caller sends
+//! them and calls sync with the ticket accounts it fetched to move the requests forward.
+
+use std::{fmt, io};
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use derive_more::{Display, From};
+use solana_program::{
+    clock::Clock,
+    instruction::Instruction,
+    pubkey::{Pubkey, PubkeyError},
+};
+
+use crate::{
+    instructions::order_unstake::OrderUnstakeData,
+    located::Located,
+    state::{
+        marinade::{Marinade, MarinadeHelpers},
+        ticket::TicketAccountData,
+    },
+    storage::Storage,
+    ticket::{order_unstake_with_seed, TicketError},
+};
+
+#[derive(Debug, Display, From)]
+pub enum WithdrawalQueueError<E> {
+    Storage(io::Error),
+    Pubkey(PubkeyError),
+    Ticket(TicketError),
+    #[display(fmt = "Can not decode {}", _0)]
+    #[from(ignore)]
+    Decode(String),
+    /// Error of the ticket account lookup
+    #[from(ignore)]
+    Lookup(E),
+}
+
+impl<E: fmt::Debug + fmt::Display> std::error::Error for WithdrawalQueueError<E> {}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, BorshSerialize, BorshDeserialize)]
+pub enum WithdrawalStatus {
+    /// order_unstake handed out, the ticket was not seen on chain yet
+    Ordered,
+    /// Ticket created, claimable from due_epoch on
+    Open {
+        lamports_amount: u64,
+        due_epoch: u64,
+    },
+    /// claim handed out, the ticket account was not seen closed yet
+    Claiming { lamports_amount: u64 },
+    /// Ticket claimed, lamports_amount was paid to the custodian
+    Claimed { lamports_amount: u64 },
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, BorshSerialize, BorshDeserialize)]
+pub struct WithdrawalRequest {
+    pub id: u64,
+    pub user: Pubkey,
+    pub msol_amount: u64,
+    pub ticket: Pubkey,
+    pub status: WithdrawalStatus,
+}
+
+#[derive(Clone, Debug, Default, BorshSerialize, BorshDeserialize)]
+struct Queue {
+    next_id: u64,
+    requests: Vec<WithdrawalRequest>,
+}
+
+pub struct WithdrawalQueue<S: Storage> {
+    pub state: Pubkey,
+    /// Owner of the burned mSOL, base of the ticket addresses and receiver of the claims
+    pub custodian: Pubkey,
+    pub custodian_msol_account: Pubkey,
+    pub storage: S,
+}
+
+impl<S: Storage> WithdrawalQueue<S> {
+    /// Characters of the state address in the seed, keeps the seed within MAX_SEED_LEN
+    const STATE_PREFIX_LEN: usize = 8;
+
+    pub fn new(
+        state: Pubkey,
+        custodian: Pubkey,
+        custodian_msol_account: Pubkey,
+        storage: S,
+    ) -> Self {
+        Self {
+            state,
+            custodian,
+            custodian_msol_account,
+            storage,
+        }
+    }
+
+    fn key(&self) -> String {
+        format!("withdrawal-queue-{}-{}", self.state, self.custodian)
+    }
+
+    pub fn seed(&self, id: u64) -> String {
+        let state = self.state.to_string();
+        format!("wq-{}-{}", &state[..Self::STATE_PREFIX_LEN], id)
+    }
+
+    fn load<E>(&self) -> Result<Queue, WithdrawalQueueError<E>> {
+        match self.storage.load(&self.key())? {
+            Some(data) => {
+                Queue::try_from_slice(&data).map_err(|_| WithdrawalQueueError::Decode(self.key()))
+            }
+            None => Ok(Queue::default()),
+        }
+    }
+
+    fn save<E>(&mut self, queue: &Queue) -> Result<(), WithdrawalQueueError<E>> {
+        let key = self.key();
+        self.storage.store(&key, &queue.try_to_vec()?)?;
+        Ok(())
+    }
+
+    pub fn requests<E>(&self) -> Result<Vec<WithdrawalRequest>, WithdrawalQueueError<E>> {
+        Ok(self.load()?.requests)
+    }
+
+    /// Every request of user, oldest first
+    pub fn user_requests<E>(
+        &self,
+        user: &Pubkey,
+    ) -> Result<Vec<WithdrawalRequest>, WithdrawalQueueError<E>> {
+        let mut requests = self.load()?.requests;
+        requests.retain(|request| request.user == *user);
+        Ok(requests)
+    }
+
+    /// Opens a ticket for msol_amount of user's custodied mSOL. The custodian signs and pays
+    /// the ticket rent. The request is stored before returning, so its id and ticket address
+    /// are never reused even when the transaction fails
+    pub fn request<T: Located<Marinade>, E>(
+        &mut self,
+        marinade: &T,
+        user: Pubkey,
+        msol_amount: u64,
+        rent_exempt_lamports: u64,
+    ) -> Result<(WithdrawalRequest, Vec<Instruction>), WithdrawalQueueError<E>> {
+        let mut queue = self.load()?;
+        let id = queue.next_id;
+        let (ticket, instructions) = order_unstake_with_seed(
+            marinade,
+            OrderUnstakeData::new(msol_amount),
+            self.custodian_msol_account,
+            self.custodian,
+            self.custodian,
+            self.custodian,
+            &self.seed(id),
+            rent_exempt_lamports,
+            None,
+        )?;
+        let request = WithdrawalRequest {
+            id,
+            user,
+            msol_amount,
+            ticket,
+            status: WithdrawalStatus::Ordered,
+        };
+        queue.next_id += 1;
+        queue.requests.push(request.clone());
+        self.save(&queue)?;
+        Ok((request, instructions))
+    }
+
+    /// Moves the requests forward from the ticket accounts fetched by ticket (None when the
+    /// account does not exist). A failed claim puts the request back to Open.
+    /// Ordered requests whose ticket never appears stay Ordered, the caller decides when
+    /// to give up on them
+    pub fn sync<F, E>(&mut self, mut ticket: F) -> Result<(), WithdrawalQueueError<E>>
+    where
+        F: FnMut(&Pubkey) -> Result<Option<TicketAccountData>, E>,
+    {
+        let mut queue = self.load()?;
+        for request in &mut queue.requests {
+            if matches!(request.status, WithdrawalStatus::Claimed { .. }) {
+                continue;
+            }
+            let account = ticket(&request.ticket).map_err(WithdrawalQueueError::Lookup)?;
+            request.status = match (request.status, account) {
+                (_, Some(account)) => WithdrawalStatus::Open {
+                    lamports_amount: account.lamports_amount(),
+                    due_epoch: account.due_epoch(),
+                },
+                (WithdrawalStatus::Ordered, None) => WithdrawalStatus::Ordered,
+                (
+                    WithdrawalStatus::Open {
+                        lamports_amount, ..
+                    },
+                    None,
+                )
+                | (WithdrawalStatus::Claiming { lamports_amount }, None)
+                | (WithdrawalStatus::Claimed { lamports_amount }, None) => {
+                    WithdrawalStatus::Claimed { lamports_amount }
+                }
+            };
+        }
+        self.save(&queue)
+    }
+
+    /// Claims of the open tickets claimable at clock, paid to the custodian.
+    /// Call it on a schedule after sync; the requests are marked Claiming before returning
+    pub fn drain<T: Located<Marinade>, E>(
+        &mut self,
+        marinade: &T,
+        clock: &Clock,
+    ) -> Result<Vec<(WithdrawalRequest, Instruction)>, WithdrawalQueueError<E>> {
+        let mut queue = self.load()?;
+        let mut claims = Vec::new();
+        for request in &mut queue.requests {
+            if let WithdrawalStatus::Open {
+                lamports_amount,
+                due_epoch,
+            } = request.status
+            {
+                if !TicketAccountData::is_due(due_epoch, clock) {
+                    continue;
+                }
+                let claim = marinade.claim(request.ticket, self.custodian)?;
+                request.status = WithdrawalStatus::Claiming { lamports_amount };
+                claims.push((request.clone(), claim));
+            }
+        }
+        if !claims.is_empty() {
+            self.save(&queue)?;
+        }
+        Ok(claims)
+    }
+}
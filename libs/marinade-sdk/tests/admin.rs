@@ -0,0 +1,63 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use crate::common::test_state;
+    use marinade_sdk::{
+        admin::{with_rollback, ConfigUpdate},
+        instructions::{config_lp::ConfigLpData, config_marinade::ConfigMarinadeData},
+        propose::{AdminStateView, ProposalTarget},
+        state::{fee::Fee, marinade::MarinadeHelpers},
+    };
+
+    #[test]
+    fn test_rollback_restores_snapshot() {
+        let mut state = test_state();
+        state.state.min_deposit = 1;
+        let update = ConfigUpdate::Marinade(
+            ConfigMarinadeData::default()
+                .with_min_deposit(5)
+                .with_min_withdraw(0),
+        );
+        let (apply, rollback) = with_rollback(&state, update, ProposalTarget::Squads);
+        assert_eq!(apply.summary(), "min_deposit: 1 -> 5");
+        assert_eq!(rollback.summary(), "min_deposit: 5 -> 1");
+        assert_eq!(
+            rollback.instruction,
+            state.config_marinade_instruction(
+                ConfigMarinadeData::default()
+                    .with_min_deposit(1)
+                    .with_min_withdraw(0)
+            )
+        );
+
+        // once applied, the rollback is what changes the state
+        state.state.min_deposit = 5;
+        let view = AdminStateView {
+            state: &state.state,
+            validators: &[],
+        };
+        assert!(view.is_applied(&apply.instruction));
+        assert!(!view.is_applied(&rollback.instruction));
+    }
+
+    #[test]
+    fn test_lp_rollback() {
+        let mut state = test_state();
+        state.state.liq_pool.lp_max_fee = Fee::from_basis_points(300);
+        let update =
+            ConfigUpdate::Lp(ConfigLpData::default().with_max_fee(Fee::from_basis_points(200)));
+        let (apply, rollback) = with_rollback(&state, update, ProposalTarget::Governance);
+        assert_eq!(apply.changes.len(), 1);
+        assert_eq!(
+            rollback.instruction,
+            state.config_lp_instruction(
+                ConfigLpData::default().with_max_fee(Fee::from_basis_points(300))
+            )
+        );
+        assert_eq!(
+            update.snapshot(&state.state),
+            ConfigUpdate::Lp(ConfigLpData::default().with_max_fee(Fee::from_basis_points(300)))
+        );
+    }
+}
@@ -0,0 +1,79 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use crate::common::test_state;
+    use marinade_sdk::{
+        alerts::{AlertContext, AlertEngine, AlertRule},
+        state::marinade::Marinade,
+    };
+
+    #[test]
+    fn test_evaluate() {
+        let mut state = test_state();
+        state.state.available_reserve_balance = 500;
+        state.state.circulating_ticket_balance = 2_000;
+        state.state.circulating_ticket_count = 3;
+        state.state.msol_price = Marinade::PRICE_DENOMINATOR + Marinade::PRICE_DENOMINATOR / 100;
+        let mut engine = AlertEngine::new();
+        engine
+            .register("reserve", AlertRule::ReserveBelow(1_000))
+            .register("backlog", AlertRule::TicketBacklogAbove(5_000))
+            .register(
+                "price",
+                AlertRule::PriceDeviation {
+                    reference_price: Marinade::PRICE_DENOMINATOR,
+                    max_bps: 50,
+                },
+            )
+            .register("crank", AlertRule::CrankNotRunForSlots(100))
+            .register("emergency", AlertRule::EmergencyUnstakeInProgress);
+        let events = engine.evaluate(&AlertContext {
+            state: &state.state,
+            current_slot: 1_000,
+            last_crank_slot: Some(950),
+        });
+        let fired: Vec<(&str, u64)> = events
+            .iter()
+            .map(|event| (event.name.as_str(), event.observed))
+            .collect();
+        assert_eq!(fired, vec![("reserve", 500), ("price", 100)]);
+        assert_eq!(events[0].slot, 1_000);
+        assert_eq!(events[0].message, "Reserve balance 500 below 1000");
+
+        state.state.emergency_cooling_down = 7;
+        let events = engine.evaluate(&AlertContext {
+            state: &state.state,
+            current_slot: 1_000,
+            last_crank_slot: None,
+        });
+        let names: Vec<&str> = events.iter().map(|event| event.name.as_str()).collect();
+        assert_eq!(names, vec!["reserve", "price", "crank", "emergency"]);
+    }
+
+    #[test]
+    fn test_rule_display_is_stable() {
+        let rules = [
+            (AlertRule::ReserveBelow(1_000), "reserve_below(1000)"),
+            (AlertRule::TicketBacklogAbove(5), "ticket_backlog_above(5)"),
+            (
+                AlertRule::PriceDeviation {
+                    reference_price: 42,
+                    max_bps: 50,
+                },
+                "price_deviation(reference_price=42, max_bps=50)",
+            ),
+            (
+                AlertRule::CrankNotRunForSlots(100),
+                "crank_not_run_for_slots(100)",
+            ),
+            (
+                AlertRule::EmergencyUnstakeInProgress,
+                "emergency_unstake_in_progress",
+            ),
+        ];
+        for (rule, display) in rules {
+            assert_eq!(rule.to_string(), display);
+        }
+    }
+}
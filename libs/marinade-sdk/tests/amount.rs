@@ -0,0 +1,108 @@
+#[cfg(test)]
+mod tests {
+    use marinade_sdk::amount::{
+        format_amount, format_msol, format_sol, parse_amount, parse_msol, parse_sol,
+        ParseAmountError, MAX_DECIMALS,
+    };
+
+    #[test]
+    fn test_format() {
+        assert_eq!(format_sol(0), "0");
+        assert_eq!(format_sol(1), "0.000000001");
+        assert_eq!(format_sol(1_500_000_000), "1.5");
+        assert_eq!(format_sol(2_000_000_000), "2");
+        assert_eq!(format_msol(999_999_999), "0.999999999");
+        assert_eq!(format_sol(u64::MAX), "18446744073.709551615");
+        assert_eq!(format_amount(1_234_500, 6).unwrap(), "1.2345");
+        assert_eq!(format_amount(7, 0).unwrap(), "7");
+        assert_eq!(
+            format_amount(1, MAX_DECIMALS).unwrap(),
+            format!("0.{}1", "0".repeat(MAX_DECIMALS as usize - 1))
+        );
+    }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(parse_sol("1.5"), Ok(1_500_000_000));
+        assert_eq!(parse_sol(" 2 "), Ok(2_000_000_000));
+        assert_eq!(parse_sol(".5"), Ok(500_000_000));
+        assert_eq!(parse_sol("3."), Ok(3_000_000_000));
+        assert_eq!(parse_msol("0.000000001"), Ok(1));
+        // 0.1 + 0.2 is exact without floats
+        assert_eq!(
+            parse_sol("0.1").unwrap() + parse_sol("0.2").unwrap(),
+            parse_sol("0.3").unwrap()
+        );
+        assert_eq!(parse_amount("1.2345", 6), Ok(1_234_500));
+        assert_eq!(parse_sol("18446744073.709551615"), Ok(u64::MAX));
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(parse_sol(""), Err(ParseAmountError::Empty));
+        assert_eq!(parse_sol("."), Err(ParseAmountError::Empty));
+        assert_eq!(
+            parse_sol("-1"),
+            Err(ParseAmountError::InvalidCharacter('-'))
+        );
+        assert_eq!(
+            parse_sol("1.2.3"),
+            Err(ParseAmountError::InvalidCharacter('.'))
+        );
+        assert_eq!(
+            parse_sol("1e9"),
+            Err(ParseAmountError::InvalidCharacter('e'))
+        );
+        assert_eq!(
+            parse_sol("0.0000000001"),
+            Err(ParseAmountError::TooManyDecimals(9))
+        );
+        assert_eq!(
+            parse_sol("18446744073.709551616"),
+            Err(ParseAmountError::Overflow)
+        );
+        assert_eq!(
+            parse_sol("99999999999999999999999999999999999999999"),
+            Err(ParseAmountError::Overflow)
+        );
+    }
+
+    #[test]
+    fn test_unsupported_decimals() {
+        assert_eq!(
+            format_amount(1, 39),
+            Err(ParseAmountError::UnsupportedDecimals(39))
+        );
+        assert_eq!(
+            format_amount(1, u8::MAX),
+            Err(ParseAmountError::UnsupportedDecimals(u8::MAX))
+        );
+        assert_eq!(
+            parse_amount("1", 39),
+            Err(ParseAmountError::UnsupportedDecimals(39))
+        );
+        assert_eq!(
+            parse_amount("0.1", 39),
+            Err(ParseAmountError::UnsupportedDecimals(39))
+        );
+        assert_eq!(
+            parse_amount("1", MAX_DECIMALS),
+            Err(ParseAmountError::Overflow)
+        );
+    }
+
+    #[test]
+    fn test_round_trip() {
+        for lamports in [
+            0,
+            1,
+            10,
+            123_456_789,
+            1_000_000_000,
+            42_000_000_001,
+            u64::MAX,
+        ] {
+            assert_eq!(parse_sol(&format_sol(lamports)), Ok(lamports));
+        }
+    }
+}
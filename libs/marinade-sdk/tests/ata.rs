@@ -0,0 +1,63 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use crate::common::test_state;
+    use marinade_sdk::{
+        ata::{
+            add_liquidity_to_ata, associated_token_address, deposit_to_ata,
+            liquid_unstake_from_ata, UserTokenAccounts, ASSOCIATED_TOKEN_PROGRAM_ID,
+        },
+        instructions::deposit::DepositData,
+        state::marinade::MarinadeHelpers,
+    };
+    use solana_program::pubkey::Pubkey;
+
+    #[test]
+    fn test_user_token_accounts() {
+        let state = test_state();
+        let wallet = Pubkey::new_unique();
+        let accounts = UserTokenAccounts::new(&state, wallet);
+        assert_eq!(
+            accounts.msol_account,
+            associated_token_address(&wallet, &state.state.msol_mint)
+        );
+        assert_eq!(
+            accounts.lp_account,
+            associated_token_address(&wallet, &state.state.liq_pool.lp_mint)
+        );
+        assert_ne!(accounts.msol_account, accounts.lp_account);
+    }
+
+    #[test]
+    fn test_flows_prepend_ata_creation() {
+        let state = test_state();
+        let wallet = Pubkey::new_unique();
+        let accounts = UserTokenAccounts::new(&state, wallet);
+
+        let deposit = deposit_to_ata(&state, wallet, 1_000, true).unwrap();
+        assert_eq!(deposit.len(), 2);
+        assert_eq!(deposit[0].program_id, ASSOCIATED_TOKEN_PROGRAM_ID);
+        assert_eq!(deposit[0].data, vec![1]);
+        assert_eq!(deposit[0].accounts[1].pubkey, accounts.msol_account);
+        assert_eq!(
+            deposit[1],
+            state
+                .deposit(DepositData::new(1_000), wallet, accounts.msol_account)
+                .unwrap()
+        );
+        assert_eq!(
+            deposit_to_ata(&state, wallet, 1_000, false).unwrap(),
+            deposit[1..].to_vec()
+        );
+
+        let add_liquidity = add_liquidity_to_ata(&state, wallet, 1_000, true).unwrap();
+        assert_eq!(add_liquidity[0].accounts[1].pubkey, accounts.lp_account);
+        assert_eq!(
+            liquid_unstake_from_ata(&state, wallet, 10, false)
+                .unwrap()
+                .len(),
+            1
+        );
+    }
+}
@@ -0,0 +1,96 @@
+#![cfg(feature = "history")]
+
+#[cfg(test)]
+mod tests {
+    use marinade_sdk::{
+        backfill::{rebuild_positions, Discrepancy, OnChainPosition},
+        history::{ClassifiedTransaction, HistoryTransaction, TokenBalanceChange},
+        instructions::{claim::ClaimData, order_unstake::OrderUnstakeData},
+        program::MarinadeProgram,
+        ID,
+    };
+    use micro_anchor::InstructionData;
+    use solana_program::{instruction::CompiledInstruction, pubkey::Pubkey};
+    use solana_sdk::signature::Signature;
+
+    fn transaction(
+        slot: u64,
+        account_keys: Vec<Pubkey>,
+        instruction: CompiledInstruction,
+        token_balances: Vec<TokenBalanceChange>,
+    ) -> ClassifiedTransaction {
+        ClassifiedTransaction {
+            signature: Signature::new_unique(),
+            slot,
+            block_time: None,
+            failed: false,
+            instructions: vec![],
+            transaction: HistoryTransaction {
+                account_keys,
+                instructions: vec![instruction],
+                inner_instructions: vec![],
+                token_balances,
+            },
+        }
+    }
+
+    #[test]
+    fn test_rebuild_and_reconcile() {
+        let msol_mint = Pubkey::new_unique();
+        let wallet = Pubkey::new_unique();
+        let token_account = Pubkey::new_unique();
+        let ticket = Pubkey::new_unique();
+        let claimed_ticket = Pubkey::new_unique();
+        let state = Pubkey::new_unique();
+        // program, state, mint, token account, wallet, ticket
+        let order = |slot, ticket| {
+            transaction(
+                slot,
+                vec![ID, state, msol_mint, token_account, wallet, ticket],
+                CompiledInstruction::new_from_raw_parts(
+                    0,
+                    OrderUnstakeData::new(10).data(),
+                    vec![1, 2, 3, 4, 5],
+                ),
+                vec![TokenBalanceChange {
+                    account: token_account,
+                    mint: msol_mint,
+                    owner: Some(wallet),
+                    pre: 100,
+                    post: 90,
+                }],
+            )
+        };
+        let claim = transaction(
+            3,
+            vec![ID, state, Pubkey::new_unique(), claimed_ticket],
+            CompiledInstruction::new_from_raw_parts(0, ClaimData::new().data(), vec![1, 2, 3]),
+            vec![],
+        );
+        let history = vec![order(1, ticket), order(2, claimed_ticket), claim];
+
+        let positions = rebuild_positions(&MarinadeProgram::default(), &history, &msol_mint, ..);
+        assert_eq!(positions.applied_transactions, 3);
+        let position = &positions.wallets[&wallet];
+        assert_eq!(position.msol_amount, -20);
+        assert_eq!(position.tickets.len(), 1);
+        assert!(position.tickets.contains(&ticket));
+
+        let partial = rebuild_positions(&MarinadeProgram::default(), &history, &msol_mint, ..3);
+        assert_eq!(partial.wallets[&wallet].tickets.len(), 2);
+
+        let discrepancies = positions.reconcile(&[OnChainPosition {
+            wallet,
+            msol_amount: 80,
+            tickets: vec![ticket],
+        }]);
+        assert_eq!(
+            discrepancies,
+            vec![Discrepancy::MsolBalance {
+                wallet,
+                rebuilt: -20,
+                on_chain: 80
+            }]
+        );
+    }
+}
@@ -0,0 +1,65 @@
+#![cfg(feature = "batch")]
+
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use crate::common::test_state;
+    use marinade_sdk::{
+        batch::{plan_deposits, BatchError, BatchLimits},
+        error::CommonError,
+    };
+    use micro_anchor::{legacy_transaction_size, PACKET_DATA_SIZE};
+    use solana_program::pubkey::Pubkey;
+
+    #[test]
+    fn test_plan_deposits() {
+        let mut state = test_state();
+        state.state.min_deposit = 1_000;
+        let custodian = Pubkey::new_unique();
+        let wallets: Vec<Pubkey> = (0..50).map(|_| Pubkey::new_unique()).collect();
+        let mut deposits: Vec<(Pubkey, u64)> =
+            wallets.iter().map(|wallet| (*wallet, 5_000)).collect();
+        deposits.push((wallets[3], 2_000));
+        deposits.push((Pubkey::new_unique(), 999));
+
+        let limits = BatchLimits::default();
+        let plan = plan_deposits(&state, &custodian, deposits, &limits).unwrap();
+        assert_eq!(plan.below_min_deposit.len(), 1);
+        assert!(plan.transactions.len() > 1);
+        let planned: Vec<(Pubkey, u64)> = plan
+            .transactions
+            .iter()
+            .flat_map(|transaction| transaction.deposits.clone())
+            .collect();
+        assert_eq!(planned.len(), 50);
+        assert_eq!(planned[3], (wallets[3], 7_000));
+        for transaction in &plan.transactions {
+            assert_eq!(
+                transaction.instructions.len(),
+                1 + 2 * transaction.deposits.len()
+            );
+            assert!(legacy_transaction_size(&transaction.instructions) <= PACKET_DATA_SIZE);
+            assert!(
+                transaction.deposits.len() as u32 * limits.user_compute_units()
+                    <= limits.max_compute_units
+            );
+        }
+    }
+
+    #[test]
+    fn test_merged_amounts_overflow() {
+        let state = test_state();
+        let wallet = Pubkey::new_unique();
+        let result = plan_deposits(
+            &state,
+            &Pubkey::new_unique(),
+            vec![(wallet, u64::MAX), (wallet, 1)],
+            &BatchLimits::default(),
+        );
+        assert!(matches!(
+            result,
+            Err(BatchError::Calculation(CommonError::CalculationFailure))
+        ));
+    }
+}
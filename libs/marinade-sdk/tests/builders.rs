@@ -0,0 +1,152 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use crate::common::test_state;
+    use marinade_sdk::{
+        builders::{
+            BuildError, DepositBuilder, LiquidUnstakeBuilder, OrderUnstakeBuilder,
+            RemoveLiquidityBuilder,
+        },
+        checks::{CheckFailure, CheckMode},
+        error::{CommonError, Mismatch},
+        instructions::{
+            deposit::DepositData, liquid_unstake::LiquidUnstakeData,
+            remove_liquidity::RemoveLiquidityData,
+        },
+        state::marinade::MarinadeHelpers,
+    };
+    use solana_program::{program_error::ProgramError, pubkey::Pubkey};
+
+    fn ata(wallet: &Pubkey, mint: &Pubkey) -> Pubkey {
+        Pubkey::find_program_address(
+            &[wallet.as_ref(), spl_token::ID.as_ref(), mint.as_ref()],
+            &solana_program::pubkey!("ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL"),
+        )
+        .0
+    }
+
+    #[test]
+    fn test_deposit_builder() {
+        let mut state = test_state();
+        let user = Pubkey::new_unique();
+        let msol_account = ata(&user, &state.state.msol_mint);
+        assert_eq!(
+            DepositBuilder::new(&state)
+                .lamports(1_000)
+                .transfer_from(user)
+                .build()
+                .unwrap(),
+            state
+                .deposit(DepositData::new(1_000), user, msol_account)
+                .unwrap()
+        );
+        assert_eq!(
+            DepositBuilder::new(&state).lamports(1_000).build(),
+            Err(BuildError::Missing("transfer_from"))
+        );
+        assert_eq!(
+            DepositBuilder::new(&state)
+                .lamports(0)
+                .transfer_from(user)
+                .build(),
+            Err(BuildError::ZeroAmount("lamports"))
+        );
+
+        state.state.min_deposit = 10_000;
+        assert_eq!(
+            DepositBuilder::new(&state)
+                .lamports(1_000)
+                .transfer_from(user)
+                .build(),
+            Err(BuildError::BelowMinimum {
+                name: "lamports",
+                amount: 1_000,
+                min: 10_000
+            })
+        );
+    }
+
+    #[test]
+    fn test_builder_checks_report_every_problem() {
+        let mut state = test_state();
+        state.state.min_deposit = 10_000;
+        let deposit = DepositBuilder::new(&state).lamports(1_000);
+        let failures = deposit.checks(CheckMode::AllErrors).finish().unwrap_err();
+        assert_eq!(
+            failures,
+            vec![
+                CheckFailure {
+                    message: "lamports 1000 is below the minimum 10000".to_string(),
+                    error: CommonError::NumberTooLow(Some(Mismatch::new(10_000, 1_000))).into(),
+                },
+                CheckFailure {
+                    message: "transfer_from is required".to_string(),
+                    error: ProgramError::NotEnoughAccountKeys,
+                },
+            ]
+        );
+        assert_eq!(
+            deposit.checks(CheckMode::FirstError).failures(),
+            &failures[..1]
+        );
+
+        let order_unstake = OrderUnstakeBuilder::new(&state).msol_amount(0);
+        assert_eq!(
+            order_unstake
+                .checks(CheckMode::AllErrors)
+                .failures()
+                .iter()
+                .map(|failure| failure.message.as_str())
+                .collect::<Vec<_>>(),
+            [
+                "msol_amount must not be zero",
+                "owner is required",
+                "new_ticket_account is required"
+            ]
+        );
+        assert!(OrderUnstakeBuilder::new(&state)
+            .msol_amount(1)
+            .owner(Pubkey::new_unique())
+            .new_ticket_account(Pubkey::new_unique())
+            .checks(CheckMode::AllErrors)
+            .finish()
+            .is_ok());
+    }
+
+    #[test]
+    fn test_unstake_builders_default_to_owner() {
+        let state = test_state();
+        let owner = Pubkey::new_unique();
+        let msol_account = ata(&owner, &state.state.msol_mint);
+        assert_eq!(
+            LiquidUnstakeBuilder::new(&state)
+                .msol_amount(5)
+                .owner(owner)
+                .build()
+                .unwrap(),
+            state
+                .liquid_unstake(LiquidUnstakeData::new(5), msol_account, owner, owner)
+                .unwrap()
+        );
+
+        let sol_to = Pubkey::new_unique();
+        assert_eq!(
+            RemoveLiquidityBuilder::new(&state)
+                .tokens(7)
+                .owner(owner)
+                .transfer_sol_to(sol_to)
+                .build()
+                .unwrap(),
+            state
+                .remove_liquidity(
+                    RemoveLiquidityData::new(7),
+                    ata(&owner, &state.state.liq_pool.lp_mint),
+                    owner,
+                    sol_to,
+                    msol_account,
+                )
+                .unwrap()
+        );
+    }
+}
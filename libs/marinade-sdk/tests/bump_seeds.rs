@@ -0,0 +1,33 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use crate::common::test_state;
+    use marinade_sdk::{
+        instructions::deposit::DepositData,
+        state::marinade::{Marinade, MarinadeHelpers},
+    };
+    use solana_program::pubkey::Pubkey;
+
+    #[test]
+    fn test_corrupted_bump_seed_is_an_error() {
+        let mut state = test_state();
+        let user = Pubkey::new_unique();
+        assert!(state.deposit(DepositData::new(1), user, user).is_ok());
+
+        // a bump seed giving an on-curve address, so no valid PDA
+        state.state.reserve_bump_seed = (0..=u8::MAX)
+            .rev()
+            .find(|bump| {
+                Pubkey::create_program_address(
+                    &[&state.key.to_bytes(), Marinade::RESERVE_SEED, &[*bump]],
+                    &marinade_sdk::ID,
+                )
+                .is_err()
+            })
+            .unwrap();
+        assert!(state.try_reserve_address().is_err());
+        assert!(state.deposit(DepositData::new(1), user, user).is_err());
+        assert!(state.claim(Pubkey::new_unique(), user).is_err());
+    }
+}
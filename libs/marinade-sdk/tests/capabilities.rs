@@ -0,0 +1,80 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use crate::common::test_state;
+    use marinade_sdk::{
+        capabilities::{
+            Capabilities, Capability, UnsupportedByProgram, INSTRUCTION_FALLBACK_NOT_FOUND,
+        },
+        instructions::pause::{PauseData, ResumeData},
+        state::marinade::MarinadeHelpers,
+    };
+    use micro_anchor::Discriminator;
+    use solana_program::{instruction::InstructionError, pubkey::Pubkey};
+
+    #[test]
+    fn test_capabilities_set() {
+        let mut capabilities = Capabilities::default();
+        assert_eq!(
+            capabilities.require(Capability::Pause),
+            Err(UnsupportedByProgram(Capability::Pause))
+        );
+        capabilities.insert(Capability::Pause);
+        capabilities.insert(Capability::Realloc);
+        assert!(capabilities.require(Capability::Pause).is_ok());
+        assert!(!capabilities.contains(Capability::WithdrawStakeAccount));
+        assert_eq!(
+            capabilities.iter().collect::<Vec<_>>(),
+            vec![Capability::Pause, Capability::Realloc]
+        );
+        assert_eq!(Capabilities::all().iter().count(), Capability::ALL.len());
+    }
+
+    #[test]
+    fn test_probe() {
+        let program_id = Pubkey::new_unique();
+        let probe = Capability::WithdrawStakeAccount.probe(program_id);
+        assert_eq!(probe.program_id, program_id);
+        assert!(probe.accounts.is_empty());
+        assert_eq!(probe.data.len(), 8);
+        assert_ne!(probe.data, Capability::Pause.discriminator());
+
+        assert!(!Capability::is_supported_by(&InstructionError::Custom(
+            INSTRUCTION_FALLBACK_NOT_FOUND
+        )));
+        // anchor's AccountNotEnoughKeys
+        assert!(Capability::is_supported_by(&InstructionError::Custom(3005)));
+        assert!(Capability::is_supported_by(
+            &InstructionError::NotEnoughAccountKeys
+        ));
+    }
+
+    #[test]
+    fn test_pause_builders_require_the_capability() {
+        let state = test_state();
+        let pause_authority = Pubkey::new_unique();
+        let old_program = Capabilities::default();
+        assert_eq!(
+            state.pause_instruction(&old_program, pause_authority),
+            Err(UnsupportedByProgram(Capability::Pause))
+        );
+        assert_eq!(
+            state.resume_instruction(&old_program, pause_authority),
+            Err(UnsupportedByProgram(Capability::Pause))
+        );
+
+        let capabilities: Capabilities = [Capability::Pause].into_iter().collect();
+        let pause = state
+            .pause_instruction(&capabilities, pause_authority)
+            .unwrap();
+        assert_eq!(pause.data, PauseData::DISCRIMINATOR);
+        assert_eq!(pause.data, Capability::Pause.discriminator());
+        assert_eq!(pause.accounts[0].pubkey, state.key);
+        assert!(pause.accounts[1].is_signer);
+        let resume = state
+            .resume_instruction(&capabilities, pause_authority)
+            .unwrap();
+        assert_eq!(resume.data, ResumeData::DISCRIMINATOR);
+    }
+}
@@ -0,0 +1,46 @@
+#[cfg(test)]
+mod tests {
+    use marinade_sdk::{
+        checks::{CheckMode, Checks},
+        error::{CommonError, Mismatch},
+    };
+    use solana_program::{program_error::ProgramError, pubkey::Pubkey};
+
+    fn failing(mode: CheckMode) -> Checks {
+        let expected = Pubkey::new_unique();
+        Checks::new(mode)
+            .address(&Pubkey::new_unique(), &expected, "msol_mint")
+            .address(&expected, &expected, "reserve")
+            .min_amount(1, 10, "Deposit")
+    }
+
+    #[test]
+    fn test_all_errors() {
+        let failures = failing(CheckMode::AllErrors).finish().unwrap_err();
+        assert_eq!(failures.len(), 2);
+        assert_eq!(failures[0].error, ProgramError::InvalidArgument);
+        assert!(failures[0].message.starts_with("Invalid msol_mint address"));
+        assert_eq!(
+            failures[1].error,
+            CommonError::NumberTooLow(Some(Mismatch::new(10, 1))).into()
+        );
+        assert_eq!(
+            failures[1].to_string(),
+            "Deposit: Number too low 1 (min is 10)"
+        );
+    }
+
+    #[test]
+    fn test_first_error() {
+        let checks = failing(CheckMode::FirstError);
+        assert_eq!(checks.failures().len(), 1);
+        assert_eq!(
+            checks.into_program_result(),
+            Err(ProgramError::InvalidArgument)
+        );
+        assert_eq!(
+            Checks::new(CheckMode::AllErrors).check(|| Ok(())).finish(),
+            Ok(())
+        );
+    }
+}
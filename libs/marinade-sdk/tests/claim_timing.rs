@@ -0,0 +1,116 @@
+#[cfg(test)]
+mod tests {
+    use marinade_sdk::{
+        claim_timing::{ClaimTimeEstimate, EpochDurationModel},
+        sysvars::SysvarCache,
+    };
+    use solana_program::{clock::Clock, epoch_schedule::EpochSchedule};
+
+    const NOW: i64 = 1_000_000;
+    const WAIT: i64 = 30 * 60;
+
+    /// Halfway through epoch 10 of 432_000 slots, started 100_000 seconds ago
+    fn sysvars() -> SysvarCache {
+        SysvarCache {
+            clock: Clock {
+                slot: 10 * 432_000 + 216_000,
+                epoch: 10,
+                unix_timestamp: NOW,
+                epoch_start_timestamp: NOW - 100_000,
+                ..Clock::default()
+            },
+            epoch_schedule: EpochSchedule::custom(432_000, 432_000, false),
+            ..SysvarCache::default()
+        }
+    }
+
+    #[test]
+    fn test_interval_from_recent_epochs() {
+        let model = EpochDurationModel::new([200_000, 160_000, 180_000, 170_000, 190_000]).unwrap();
+        assert_eq!(model.percentile(0), 160_000);
+        assert_eq!(model.percentile(50), 180_000);
+        assert_eq!(model.percentile(100), 200_000);
+
+        // one and a half epochs to the start of epoch 12
+        assert_eq!(
+            model.estimate_claim(12, &sysvars(), 80),
+            ClaimTimeEstimate {
+                earliest: NOW + 240_000 + WAIT,
+                expected: NOW + 270_000 + WAIT,
+                latest: NOW + 300_000 + WAIT,
+            }
+        );
+
+        let fixed = EpochDurationModel::fixed(180_000).estimate_claim(12, &sysvars(), 80);
+        assert_eq!(fixed.earliest, fixed.latest);
+        assert_eq!(fixed.expected, NOW + 270_000 + WAIT);
+
+        assert!(EpochDurationModel::new(Vec::new()).is_none());
+    }
+
+    #[test]
+    fn test_due_tickets() {
+        let model = EpochDurationModel::fixed(180_000);
+        // due epoch running for longer than the wait
+        assert_eq!(model.estimate_claim(10, &sysvars(), 80).expected, NOW);
+        assert_eq!(model.estimate_claim(9, &sysvars(), 80).latest, NOW);
+
+        // due epoch just started
+        let mut started = sysvars();
+        started.clock.epoch_start_timestamp = NOW - 60;
+        assert_eq!(
+            model.estimate_claim(10, &started, 80),
+            ClaimTimeEstimate {
+                earliest: NOW - 60 + WAIT,
+                expected: NOW - 60 + WAIT,
+                latest: NOW - 60 + WAIT,
+            }
+        );
+    }
+
+    #[cfg(feature = "test-utils")]
+    #[test]
+    fn test_fetch_epoch_durations() {
+        use marinade_sdk::{
+            claim_timing::EpochDuration,
+            client::{fetch_epoch_durations, FetchError},
+            testing::MockRpc,
+        };
+
+        // epoch 10 of 100 slots, the first slot of epoch 8 skipped
+        let rpc = MockRpc::default()
+            .with_slot(1_050)
+            .with_epoch_schedule(EpochSchedule::custom(100, 100, false))
+            .with_block_times([
+                (700, 7_000),
+                (750, 7_500),
+                (801, 8_050),
+                (900, 9_000),
+                (1_000, 10_100),
+            ])
+            .into_client();
+        assert_eq!(
+            fetch_epoch_durations(&rpc, 3).unwrap(),
+            vec![
+                EpochDuration {
+                    epoch: 7,
+                    seconds: 1_050,
+                },
+                EpochDuration {
+                    epoch: 8,
+                    seconds: 950,
+                },
+                EpochDuration {
+                    epoch: 9,
+                    seconds: 1_100,
+                },
+            ]
+        );
+        assert!(fetch_epoch_durations(&rpc, 0).unwrap().is_empty());
+        // the node cleaned up the blocks of epoch 6
+        assert!(matches!(
+            fetch_epoch_durations(&rpc, 4),
+            Err(FetchError::Client(_))
+        ));
+    }
+}
@@ -0,0 +1,427 @@
+#![cfg(feature = "rpc")]
+
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use crate::common::test_state;
+    use marinade_sdk::{
+        capabilities::INSTRUCTION_FALLBACK_NOT_FOUND,
+        client::{
+            check_integrity, list_data_len, probe_supported, AccountEncoding, FetchError,
+            MarinadeProgramState, ReadClient, SignerClient,
+        },
+        instructions::deposit::DepositData,
+        program::MarinadeProgram,
+        state::marinade::{MarinadeExtension, MarinadeHelpers},
+    };
+    use solana_client::rpc_client::RpcClient;
+    use solana_program::{hash::hash, instruction::InstructionError, pubkey::Pubkey};
+    use solana_sdk::{signature::Keypair, signer::Signer, transaction::TransactionError};
+
+    #[test]
+    fn test_list_accounts_are_compressed() {
+        assert_eq!(AccountEncoding::for_len(200), AccountEncoding::Base64);
+        assert_eq!(
+            AccountEncoding::for_len(8 + 61 * 5000),
+            AccountEncoding::Base64Zstd
+        );
+    }
+
+    #[test]
+    fn test_program_state_builds_instructions() {
+        let test = test_state();
+        let expected = test
+            .deposit(DepositData::new(1), Pubkey::default(), Pubkey::default())
+            .unwrap();
+        let state = MarinadeProgramState {
+            key: test.key,
+            program: MarinadeProgram::default(),
+            state: test.state,
+            extension: MarinadeExtension::default(),
+            validators: vec![],
+            stakes: vec![],
+            liq_pool_sol_leg_lamports: 0,
+            liq_pool_msol_leg_amount: 0,
+            slot: 0,
+        };
+        assert_eq!(
+            state
+                .deposit(DepositData::new(1), Pubkey::default(), Pubkey::default())
+                .unwrap(),
+            expected
+        );
+    }
+
+    #[test]
+    fn test_signer_client_hands_out_only_a_read_client() {
+        let state_address = Pubkey::new_unique();
+        let signer = Keypair::new();
+        let pubkey = signer.pubkey();
+        let mut client = SignerClient::connect(
+            RpcClient::new("http://127.0.0.1:1".to_string()),
+            state_address,
+            signer,
+        );
+        assert_eq!(client.pubkey(), pubkey);
+        assert_eq!(client.state_address, state_address);
+        assert!(matches!(client.send(&[], &[]), Err(FetchError::Client(_))));
+
+        let read: ReadClient = client.into_read_client();
+        assert_eq!(read.state_address(), state_address);
+    }
+
+    #[cfg(feature = "test-utils")]
+    #[test]
+    fn test_reads_need_no_signer_and_sends_are_signed_by_the_signer() {
+        use marinade_sdk::testing::{program_accounts, MockRpc};
+
+        let mut state = test_state();
+        state.state.msol_supply = 1_000;
+        let mock = MockRpc::new(program_accounts(&state)).with_slot(7);
+        let sent = mock.sent_transactions();
+        let mut read = ReadClient::new(mock.clone().into_client(), state.key);
+        assert_eq!(read.state().unwrap().state.msol_supply, 1_000);
+        assert_eq!(read.state().unwrap().slot, 7);
+        assert!(sent.lock().unwrap().is_empty());
+
+        let signer = Keypair::new();
+        let pubkey = signer.pubkey();
+        let mut client = SignerClient::connect(mock.into_client(), state.key, signer);
+        let instruction = client
+            .state()
+            .unwrap()
+            .deposit(DepositData::new(1), pubkey, Pubkey::new_unique())
+            .unwrap();
+        let signature = client.send(&[instruction], &[]).unwrap();
+        let sent_transaction = sent.lock().unwrap()[0].clone();
+        assert_eq!(sent_transaction.signatures[0], signature);
+        assert_eq!(sent_transaction.message.account_keys[0], pubkey);
+        assert!(sent_transaction.verify().is_ok());
+
+        // the read side is kept, without the signer
+        let mut read = client.into_read_client();
+        assert_eq!(read.state().unwrap().state.msol_supply, 1_000);
+        assert_eq!(sent.lock().unwrap().len(), 1);
+    }
+
+    #[cfg(feature = "test-utils")]
+    #[test]
+    fn test_operation_accounts_are_fetched_alone() {
+        use marinade_sdk::{
+            client::{fetch_operation_accounts, fetch_program_state},
+            required_accounts::{AccountRole, Operation},
+            testing::{program_accounts, token_account, MockRpc},
+        };
+
+        let state = test_state();
+        let mint_to = Pubkey::new_unique();
+        // the state, the mSOL leg and the user token account, no lists
+        let mut mock = MockRpc::new(program_accounts(&state).into_iter().filter(|(address, _)| {
+            *address == state.key || *address == state.state.liq_pool.msol_leg
+        }))
+        .with_slot(9);
+        mock.set_account(
+            mint_to,
+            token_account(state.state.msol_mint, Pubkey::new_unique(), 5),
+        );
+        let rpc = mock.into_client();
+        assert!(fetch_program_state(&rpc, &MarinadeProgram::default(), &state.key).is_err());
+
+        let operation = Operation::Deposit { mint_to };
+        let fetched =
+            fetch_operation_accounts(&rpc, &MarinadeProgram::default(), &state.key, &operation)
+                .unwrap();
+        assert_eq!(fetched.state.key, state.key);
+        assert_eq!(fetched.slot, 9);
+        let required = operation.required_accounts(&state).unwrap();
+        assert_eq!(
+            fetched
+                .accounts
+                .iter()
+                .map(|(required, _)| *required)
+                .collect::<Vec<_>>(),
+            required[1..].to_vec()
+        );
+        assert!(fetched.get(AccountRole::LiqPoolMsolLeg).is_some());
+        assert!(fetched.get(AccountRole::UserTokenAccount).is_some());
+        assert!(fetched.get(AccountRole::Reserve).is_none());
+
+        // an account not created yet is reported, not an error
+        let operation = Operation::Deposit {
+            mint_to: Pubkey::new_unique(),
+        };
+        let fetched =
+            fetch_operation_accounts(&rpc, &MarinadeProgram::default(), &state.key, &operation)
+                .unwrap();
+        assert_eq!(fetched.accounts.len(), 2);
+        assert!(fetched.get(AccountRole::UserTokenAccount).is_none());
+    }
+
+    #[cfg(feature = "test-utils")]
+    #[test]
+    fn test_stale_quotes_are_not_sent() {
+        use marinade_sdk::{
+            quote::{quote_deposit_with_liq_pool, LiqPoolLegs},
+            testing::{program_accounts, MockRpc},
+        };
+
+        let mut state = test_state();
+        state.state.staking_sol_cap = u64::MAX;
+        let mock = MockRpc::new(program_accounts(&state)).with_slot(100);
+        let sent = mock.sent_transactions();
+        let mut client = SignerClient::connect(mock.into_client(), state.key, Keypair::new());
+        let pubkey = client.pubkey();
+        let instruction = client
+            .state()
+            .unwrap()
+            .deposit(DepositData::new(1), pubkey, Pubkey::new_unique())
+            .unwrap();
+
+        let stale = quote_deposit_with_liq_pool(&state, 80, &LiqPoolLegs::default(), 1).unwrap();
+        assert!(matches!(
+            client.send_quoted(&stale, 10, &[instruction.clone()], &[]),
+            Err(FetchError::StaleQuote(stale)) if stale.age == 20
+        ));
+        let mut other_state = test_state();
+        other_state.state = state.state.clone();
+        let other =
+            quote_deposit_with_liq_pool(&other_state, 100, &LiqPoolLegs::default(), 1).unwrap();
+        assert!(matches!(
+            client.send_quoted(&other, 10, &[instruction.clone()], &[]),
+            Err(FetchError::QuoteStateMismatch(..))
+        ));
+        assert!(sent.lock().unwrap().is_empty());
+
+        let fresh = quote_deposit_with_liq_pool(&state, 95, &LiqPoolLegs::default(), 1).unwrap();
+        client.send_quoted(&fresh, 10, &[instruction], &[]).unwrap();
+        assert_eq!(sent.lock().unwrap().len(), 1);
+    }
+
+    #[cfg(feature = "test-utils")]
+    #[test]
+    fn test_recovery_reports_the_confirmed_transactions() {
+        use marinade_sdk::{
+            client::execute_recovery,
+            testing::{program_accounts, MockRpc},
+        };
+
+        let state = test_state();
+        let mock = MockRpc::new(program_accounts(&state)).with_send_limit(1);
+        let sent = mock.sent_transactions();
+        let rpc = mock.into_client();
+        let payer = Keypair::new();
+        let instruction = state
+            .deposit(DepositData::new(1), payer.pubkey(), Pubkey::new_unique())
+            .unwrap();
+        let transactions = vec![vec![instruction.clone()], vec![instruction]];
+
+        match execute_recovery(&rpc, &transactions, &payer) {
+            Err(FetchError::Recovery { confirmed, .. }) => {
+                assert_eq!(confirmed, vec![sent.lock().unwrap()[0].signatures[0]]);
+            }
+            other => panic!("expected a recovery error, got {:?}", other),
+        }
+        assert_eq!(sent.lock().unwrap().len(), 1);
+    }
+
+    #[cfg(feature = "test-utils")]
+    #[test]
+    fn test_claim_all_reports_each_transaction() {
+        use marinade_sdk::{
+            client::MarinadeClient,
+            testing::{program_accounts, sysvar_account, ticket_account, MockRpc},
+        };
+        use solana_program::{clock::Clock, sysvar::SysvarId};
+
+        let state = test_state();
+        let signer = Keypair::new();
+        // the node rejects every transaction after the first one
+        let mut mock = MockRpc::new(program_accounts(&state)).with_send_limit(1);
+        let clock = Clock {
+            epoch: 10,
+            unix_timestamp: 1_000_000,
+            ..Clock::default()
+        };
+        mock.set_account(Clock::id(), sysvar_account(&clock));
+        // more due tickets than one transaction holds
+        for _ in 0..100 {
+            let ticket = ticket_account(state.key, signer.pubkey(), 1_000, 5);
+            mock.set_account(Pubkey::new_unique(), ticket);
+        }
+        let sent = mock.sent_transactions();
+        let mut client =
+            SignerClient::new(MarinadeClient::new(mock.into_client(), state.key), signer);
+
+        let results = client.claim_all().unwrap();
+        assert!(results.len() > 1);
+        assert!(results[0].is_ok());
+        // the failures did not stop the remaining claims from being sent
+        assert!(results[1..]
+            .iter()
+            .all(|result| matches!(result, Err(FetchError::Client(_)))));
+        assert_eq!(sent.lock().unwrap().len(), 1);
+    }
+
+    #[cfg(feature = "test-utils")]
+    #[test]
+    fn test_fork_program_end_to_end() {
+        use marinade_sdk::{
+            client::MarinadeClient,
+            located::Located,
+            testing::{program_accounts, sysvar_account, ticket_account, MockRpc},
+        };
+        use solana_program::{clock::Clock, sysvar::SysvarId};
+
+        let program = MarinadeProgram::new(Pubkey::new_unique());
+        let mut test = test_state();
+        let key = test.key;
+        test.state.reserve_bump_seed = program.find_reserve_address(&key).1;
+        test.state.msol_mint_authority_bump_seed = program.find_msol_mint_authority(&key).1;
+        test.state.liq_pool.lp_mint_authority_bump_seed = program.find_lp_mint_authority(&key).1;
+        test.state.liq_pool.sol_leg_bump_seed = program.find_liq_pool_sol_leg_address(&key).1;
+        test.state.liq_pool.msol_leg_authority_bump_seed =
+            program.find_liq_pool_msol_leg_authority(&key).1;
+        test.state.staking_sol_cap = u64::MAX;
+        let fork = program.locate(test);
+
+        let signer = Keypair::new();
+        let mut mock = MockRpc::new(program_accounts(&fork));
+        let clock = Clock {
+            epoch: 10,
+            unix_timestamp: 1_000_000,
+            ..Clock::default()
+        };
+        mock.set_account(Clock::id(), sysvar_account(&clock));
+        let mut ticket = ticket_account(key, signer.pubkey(), 1_000, 5);
+        ticket.owner = program.program_id;
+        let fork_ticket = Pubkey::new_unique();
+        mock.set_account(fork_ticket, ticket);
+        // same state and beneficiary but owned by crate::ID, not a ticket of the fork
+        mock.set_account(
+            Pubkey::new_unique(),
+            ticket_account(key, signer.pubkey(), 1_000, 5),
+        );
+        let sent = mock.sent_transactions();
+        let mut marinade = MarinadeClient::new(mock.into_client(), key);
+        marinade.program = program;
+        let mut client = SignerClient::new(marinade, signer);
+
+        let state = client.state().unwrap().clone();
+        assert_eq!(state.program, program);
+        assert_eq!(state.program_id(), program.program_id);
+        assert_eq!(
+            state.liq_pool_sol_leg_lamports,
+            state.state.rent_exempt_for_token_acc
+        );
+        let deposit = state
+            .deposit(DepositData::new(1), client.pubkey(), Pubkey::new_unique())
+            .unwrap();
+        assert_eq!(deposit.program_id, program.program_id);
+        assert!(deposit
+            .accounts
+            .iter()
+            .any(|meta| meta.pubkey == program.find_reserve_address(&key).0));
+
+        let results = client.claim_all().unwrap();
+        assert_eq!(results.len(), 1);
+        assert!(results[0].is_ok());
+        let sent = sent.lock().unwrap();
+        let message = &sent[0].message;
+        assert_eq!(message.instructions.len(), 1);
+        assert_eq!(
+            message.account_keys[message.instructions[0].program_id_index as usize],
+            program.program_id
+        );
+        assert!(message.account_keys.contains(&fork_ticket));
+    }
+
+    #[cfg(feature = "async")]
+    #[tokio::test]
+    async fn test_nonblocking_client_reports_client_errors() {
+        use marinade_sdk::client::{nonblocking::MarinadeClient, FetchError};
+        use solana_client::nonblocking::rpc_client::RpcClient;
+
+        let mut client = MarinadeClient::new(
+            RpcClient::new("http://127.0.0.1:1".to_string()),
+            Pubkey::new_unique(),
+        );
+        assert!(matches!(client.state().await, Err(FetchError::Client(_))));
+    }
+
+    #[test]
+    fn test_only_encoding_errors_fall_back_to_base64() {
+        use marinade_sdk::client::is_unsupported_encoding;
+        use solana_client::{
+            client_error::{ClientError, ClientErrorKind},
+            rpc_request::{RpcError, RpcResponseErrorData},
+        };
+
+        let response_error = |code, message: &str| -> ClientError {
+            ClientErrorKind::RpcError(RpcError::RpcResponseError {
+                code,
+                message: message.to_string(),
+                data: RpcResponseErrorData::Empty,
+            })
+            .into()
+        };
+        assert!(is_unsupported_encoding(&response_error(
+            -32602,
+            "Invalid params: unknown variant `base64+zstd`, expected one of `binary`, `base64`"
+        )));
+        // other rejected parameters, rate limits and lagging nodes are real errors
+        assert!(!is_unsupported_encoding(&response_error(
+            -32602,
+            "Invalid param: WrongSize"
+        )));
+        assert!(!is_unsupported_encoding(&response_error(
+            429,
+            "Too many requests for a specific RPC call"
+        )));
+        assert!(!is_unsupported_encoding(&response_error(
+            -32016,
+            "Minimum context slot has not been reached"
+        )));
+        assert!(!is_unsupported_encoding(
+            &ClientErrorKind::Custom("zstd".to_string()).into()
+        ));
+    }
+
+    #[test]
+    fn test_probe_supported() {
+        assert!(!probe_supported(Some(TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(INSTRUCTION_FALLBACK_NOT_FOUND)
+        )))
+        .unwrap());
+        assert!(probe_supported(Some(TransactionError::InstructionError(
+            0,
+            InstructionError::NotEnoughAccountKeys
+        )))
+        .unwrap());
+        assert!(matches!(
+            probe_supported(Some(TransactionError::InsufficientFundsForFee)),
+            Err(FetchError::Simulation(_))
+        ));
+    }
+
+    #[test]
+    fn test_chunked_data_integrity() {
+        let mut state = test_state().state;
+        state.stake_system.stake_list.item_size = 57;
+        state.stake_system.stake_list.count = 3;
+        assert_eq!(list_data_len(&state.stake_system.stake_list), 8 + 3 * 57);
+
+        let address = Pubkey::new_unique();
+        let data = vec![7u8; 179];
+        assert!(check_integrity(&address, &data, 179, Some(&hash(&data))).is_ok());
+        assert!(matches!(
+            check_integrity(&address, &data[..100], 179, None),
+            Err(FetchError::Integrity(_, "length"))
+        ));
+        assert!(matches!(
+            check_integrity(&address, &data, 179, Some(&hash(&[0]))),
+            Err(FetchError::Integrity(_, "hash"))
+        ));
+    }
+}
@@ -0,0 +1,4 @@
+//! Fixtures shared by the test files, see marinade_sdk::testing
+#![allow(unused_imports)]
+
+pub use marinade_sdk::testing::*;
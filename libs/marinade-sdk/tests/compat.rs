@@ -0,0 +1,24 @@
+#[cfg(test)]
+mod tests {
+    use marinade_sdk::{
+        checks::check_stake_amount_and_validator,
+        compat::{stake_account_len, StakeState},
+        error::CommonError,
+    };
+    use solana_program::{program_error::ProgramError, pubkey::Pubkey};
+
+    #[test]
+    fn test_stake_account_len() {
+        assert_eq!(stake_account_len(), 200);
+    }
+
+    #[test]
+    fn test_undelegated_stake_state() {
+        let result =
+            check_stake_amount_and_validator(&StakeState::default(), 0, &Pubkey::default());
+        assert_eq!(
+            result,
+            Err(ProgramError::from(CommonError::StakeNotDelegated))
+        );
+    }
+}
@@ -0,0 +1,79 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use crate::common::test_state;
+    use marinade_sdk::{
+        crank_progress::CrankProgress, planner::EpochPosition, state::stake_system::StakeRecord,
+    };
+    use solana_program::pubkey::Pubkey;
+
+    const SOL: u64 = 1_000_000_000;
+    const RENT: u64 = 2_039_280;
+
+    fn stake(last_update_epoch: u64) -> StakeRecord {
+        StakeRecord {
+            stake_account: Pubkey::new_unique(),
+            last_update_delegated_lamports: 100 * SOL,
+            last_update_epoch,
+            is_emergency_unstaking: 0,
+        }
+    }
+
+    fn position(slot_index: u64) -> EpochPosition {
+        EpochPosition {
+            epoch: 500,
+            slot_index,
+            slots_in_epoch: 432_000,
+        }
+    }
+
+    #[test]
+    fn test_update_phase() {
+        let mut state = test_state();
+        state.state.rent_exempt_for_token_acc = RENT;
+        state.state.stake_system.slots_for_stake_delta = 3_000;
+        let stakes = [stake(500), stake(499), stake(499), stake(500)];
+
+        let progress =
+            CrankProgress::new(&state.state, &stakes, RENT, &position(1_000), None).unwrap();
+        assert_eq!(progress.stakes_updated, 2);
+        assert_eq!(progress.stakes_total, 4);
+        assert_eq!(progress.delta_target, 0);
+        assert_eq!(progress.window_slots_remaining, 3_000);
+        assert_eq!(progress.percent().unwrap(), 75);
+        assert!(!progress.is_done());
+    }
+
+    #[test]
+    fn test_delta_phase() {
+        let mut state = test_state();
+        state.state.rent_exempt_for_token_acc = RENT;
+        state.state.stake_system.slots_for_stake_delta = 3_000;
+        let stakes = [stake(500), stake(500)];
+        // 10 SOL in the reserve were 40 SOL when the window opened
+        let progress = CrankProgress::new(
+            &state.state,
+            &stakes,
+            RENT + 10 * SOL,
+            &position(430_000),
+            Some(40 * SOL),
+        )
+        .unwrap();
+        assert_eq!(progress.delta_applied, 30 * SOL);
+        assert_eq!(progress.delta_target, 40 * SOL);
+        assert_eq!(progress.window_slots_remaining, 2_000);
+        assert_eq!(progress.percent().unwrap(), 87);
+
+        let done = CrankProgress::new(
+            &state.state,
+            &stakes,
+            RENT,
+            &position(431_000),
+            Some(40 * SOL),
+        )
+        .unwrap();
+        assert!(done.is_done());
+        assert_eq!(done.percent().unwrap(), 100);
+    }
+}
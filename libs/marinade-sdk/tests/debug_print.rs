@@ -0,0 +1,47 @@
+#![cfg(feature = "debug-print")]
+
+#[cfg(test)]
+mod tests {
+    use marinade_sdk::{
+        instructions::deposit::{DepositAccounts, DepositData},
+        ID,
+    };
+    use micro_anchor::InstructionBuilder;
+    use solana_program::pubkey::Pubkey;
+
+    #[test]
+    fn test_print_named_accounts() {
+        let key = |n| Pubkey::new_from_array([n; 32]);
+        let builder = InstructionBuilder {
+            accounts: DepositAccounts {
+                marinade: key(1),
+                msol_mint: key(2),
+                liq_pool_sol_leg_pda: key(3),
+                liq_pool_msol_leg: key(4),
+                liq_pool_msol_leg_authority: key(5),
+                reserve_pda: key(6),
+                transfer_from: key(7),
+                mint_to: key(8),
+                msol_mint_authority: key(9),
+                system_program: key(10),
+                token_program: key(11),
+            },
+            data: DepositData::new(5),
+        };
+
+        let compact = builder.compact_print();
+        assert!(compact.starts_with(&format!(
+            "program={} data=DepositData {{ lamports: 5 }} accounts=[marinade={}:w ",
+            ID,
+            key(1)
+        )));
+        assert!(compact.contains(&format!("transfer_from={}:ws", key(7))));
+        assert!(compact.ends_with(&format!("token_program={}:r]", key(11))));
+
+        let pretty = builder.pretty_print();
+        assert_eq!(pretty.lines().count(), 3 + 11);
+        assert!(pretty
+            .lines()
+            .any(|line| line.ends_with("msol_mint_authority")));
+    }
+}
@@ -0,0 +1,136 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use crate::common::test_state;
+    use marinade_sdk::{
+        decode::{decode, decode_compiled, decode_instruction, DecodeError, MarinadeInstruction},
+        instructions::{deposit::DepositData, order_unstake::OrderUnstakeData},
+        program::MarinadeProgram,
+        state::marinade::MarinadeHelpers,
+    };
+    use solana_program::{instruction::CompiledInstruction, pubkey::Pubkey};
+
+    #[test]
+    fn test_decode_builder_output() {
+        let program = MarinadeProgram::default();
+        let state = test_state();
+        let user = Pubkey::new_unique();
+        let mint_to = Pubkey::new_unique();
+        let instruction = state.deposit(DepositData::new(42), user, mint_to).unwrap();
+        match decode_instruction(&program, &instruction).unwrap() {
+            MarinadeInstruction::Deposit { accounts, data } => {
+                assert_eq!(data, DepositData::new(42));
+                assert_eq!(accounts.marinade, state.key);
+                assert_eq!(accounts.transfer_from, user);
+                assert_eq!(accounts.mint_to, mint_to);
+            }
+            other => panic!("decoded as {}", other.name()),
+        }
+
+        let ticket = Pubkey::new_unique();
+        let instruction = state.order_unstake(OrderUnstakeData::new(7), user, user, ticket);
+        let decoded = decode_instruction(&program, &instruction).unwrap();
+        assert_eq!(decoded.name(), "order_unstake");
+        // same instruction compiled in a message with the program id last
+        let mut account_keys: Vec<Pubkey> = instruction
+            .accounts
+            .iter()
+            .map(|meta| meta.pubkey)
+            .collect();
+        account_keys.dedup();
+        account_keys.push(instruction.program_id);
+        let compiled = CompiledInstruction {
+            program_id_index: (account_keys.len() - 1) as u8,
+            accounts: instruction
+                .accounts
+                .iter()
+                .map(|meta| {
+                    account_keys
+                        .iter()
+                        .position(|key| *key == meta.pubkey)
+                        .unwrap() as u8
+                })
+                .collect(),
+            data: instruction.data.clone(),
+        };
+        assert_eq!(
+            decode_compiled(&program, &compiled, &account_keys).unwrap(),
+            decoded
+        );
+    }
+
+    #[test]
+    fn test_decode_errors() {
+        let program = MarinadeProgram::default();
+        let state = test_state();
+        let instruction = state
+            .deposit(
+                DepositData::new(42),
+                Pubkey::new_unique(),
+                Pubkey::new_unique(),
+            )
+            .unwrap();
+        let keys: Vec<Pubkey> = instruction
+            .accounts
+            .iter()
+            .map(|meta| meta.pubkey)
+            .collect();
+        let other_program = Pubkey::new_unique();
+        assert_eq!(
+            decode(&program, &other_program, &keys, &instruction.data).unwrap_err(),
+            DecodeError::NotMarinade(other_program)
+        );
+        assert_eq!(
+            decode(
+                &program,
+                &instruction.program_id,
+                &keys,
+                &instruction.data[..4]
+            )
+            .unwrap_err(),
+            DecodeError::MissingDiscriminator
+        );
+        assert_eq!(
+            decode(&program, &instruction.program_id, &keys, &[0; 16]).unwrap_err(),
+            DecodeError::UnknownDiscriminator([0; 8])
+        );
+        assert_eq!(
+            decode(
+                &program,
+                &instruction.program_id,
+                &keys,
+                &instruction.data[..12]
+            )
+            .unwrap_err(),
+            DecodeError::InvalidData("deposit")
+        );
+        assert_eq!(
+            decode(
+                &program,
+                &instruction.program_id,
+                &keys[..5],
+                &instruction.data
+            )
+            .unwrap_err(),
+            DecodeError::NotEnoughAccounts("deposit")
+        );
+    }
+
+    #[test]
+    fn test_decode_fork_instructions() {
+        let fork = MarinadeProgram::new(Pubkey::new_unique());
+        let state = fork.locate(test_state());
+        let user = Pubkey::new_unique();
+        let instruction =
+            state.order_unstake(OrderUnstakeData::new(7), user, user, Pubkey::new_unique());
+        assert_eq!(
+            decode_instruction(&fork, &instruction).unwrap().name(),
+            "order_unstake"
+        );
+        assert_eq!(
+            decode_instruction(&MarinadeProgram::default(), &instruction).unwrap_err(),
+            DecodeError::NotMarinade(fork.program_id)
+        );
+    }
+}
@@ -0,0 +1,76 @@
+#![cfg(feature = "devnet-tools")]
+
+#[cfg(test)]
+mod tests {
+    use marinade_sdk::{
+        devnet::{deposit_unstake_round_trip, DevnetError, RoundTripReport},
+        error::CommonError,
+    };
+    use solana_client::rpc_client::RpcClient;
+    use solana_program::pubkey::Pubkey;
+    use solana_sdk::signature::{Keypair, Signature};
+
+    fn report() -> RoundTripReport {
+        RoundTripReport {
+            deposited_lamports: 1_000_000,
+            msol_received: 900_000,
+            sol_returned: 990_000,
+            msol_price_before: 100,
+            msol_price_after: 100,
+            deposit_signature: Signature::default(),
+            unstake_signature: Signature::default(),
+        }
+    }
+
+    #[test]
+    fn test_round_trip_invariants() {
+        report().check().unwrap();
+        for broken in [
+            RoundTripReport {
+                msol_received: 0,
+                ..report()
+            },
+            RoundTripReport {
+                sol_returned: 1_000_001,
+                ..report()
+            },
+            RoundTripReport {
+                msol_price_after: 99,
+                ..report()
+            },
+        ] {
+            assert!(matches!(
+                broken.check(),
+                Err(DevnetError::InvariantViolated(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn test_required_balance_overflow() {
+        // fails before any request, the url is never reached
+        let rpc = RpcClient::new("http://127.0.0.1:1".to_string());
+        assert!(matches!(
+            deposit_unstake_round_trip(&rpc, &Pubkey::new_unique(), &Keypair::new(), u64::MAX),
+            Err(DevnetError::Calculation(CommonError::CalculationFailure))
+        ));
+    }
+
+    /// MARINADE_DEVNET_STATE is the state of the deployment, MARINADE_DEVNET_URL the RPC
+    /// provider to check (api.devnet.solana.com by default)
+    #[test]
+    #[ignore = "needs a devnet Marinade deployment in MARINADE_DEVNET_STATE"]
+    fn test_devnet_round_trip() {
+        let state: Pubkey = std::env::var("MARINADE_DEVNET_STATE")
+            .expect("MARINADE_DEVNET_STATE")
+            .parse()
+            .unwrap();
+        let url = std::env::var("MARINADE_DEVNET_URL")
+            .unwrap_or_else(|_| "https://api.devnet.solana.com".to_string());
+        let report =
+            deposit_unstake_round_trip(&RpcClient::new(url), &state, &Keypair::new(), 100_000_000)
+                .unwrap();
+        assert_eq!(report.deposited_lamports, 100_000_000);
+        assert!(report.msol_received > 0);
+    }
+}
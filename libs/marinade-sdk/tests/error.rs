@@ -0,0 +1,105 @@
+#[cfg(test)]
+mod tests {
+    use marinade_sdk::error::{CommonError, Mismatch};
+    use solana_program::program_error::ProgramError;
+
+    #[test]
+    fn test_custom_code_roundtrip() {
+        // every code the program raises: the codes with the offset and the cap code
+        let errors: Vec<CommonError> = (0..=u16::MAX as u32)
+            .filter_map(CommonError::from_custom_code)
+            .collect();
+        assert_eq!(errors.len(), 29);
+        for error in errors {
+            assert_eq!(
+                CommonError::from_custom_code(error.custom_code()),
+                Some(error)
+            );
+            assert_eq!(error.to_string(), error.name());
+        }
+        assert_eq!(
+            ProgramError::from(CommonError::CalculationFailure),
+            ProgramError::Custom(51919)
+        );
+        assert_eq!(CommonError::CapReached(None).custom_code(), 3782);
+        assert_eq!(CommonError::from_custom_code(6000), None);
+        assert_eq!(CommonError::from_custom_code(3), None);
+    }
+
+    #[test]
+    fn test_context_display() {
+        let error = CommonError::NumberTooLow(Some(Mismatch::new(10, 1)));
+        assert_eq!(error.to_string(), "NumberTooLow: expected 10, got 1");
+        // context is lost in the program error code
+        assert_eq!(
+            CommonError::from_custom_code(error.custom_code()),
+            Some(CommonError::NumberTooLow(None))
+        );
+        assert_eq!(CommonError::TicketNotDue.to_string(), "TicketNotDue");
+    }
+
+    #[cfg(feature = "rpc")]
+    #[test]
+    fn test_decode_program_error() {
+        use marinade_sdk::{
+            error::{decode_message_error, decode_program_error},
+            ID,
+        };
+        use solana_program::{
+            instruction::{Instruction, InstructionError},
+            message::Message,
+            pubkey::Pubkey,
+        };
+        use solana_sdk::transaction::TransactionError;
+
+        let error = TransactionError::InstructionError(1, InstructionError::Custom(4355));
+        let info = decode_program_error(&error).unwrap();
+        assert_eq!(info.instruction_index, 1);
+        assert_eq!(info.error, CommonError::TicketNotDue);
+        assert!(info
+            .to_string()
+            .starts_with("Instruction 1 failed with TicketNotDue (4355)"));
+        assert_eq!(decode_program_error(&TransactionError::AccountInUse), None);
+        assert_eq!(
+            decode_program_error(&TransactionError::InstructionError(
+                0,
+                InstructionError::Custom(6000)
+            )),
+            None
+        );
+
+        let other_program = Pubkey::new_unique();
+        let message = Message::new(
+            &[
+                Instruction::new_with_bytes(other_program, &[], vec![]),
+                Instruction::new_with_bytes(ID, &[], vec![]),
+            ],
+            Some(&Pubkey::new_unique()),
+        );
+        assert_eq!(decode_message_error(&error, &message, &ID), Some(info));
+        let first = TransactionError::InstructionError(0, InstructionError::Custom(4355));
+        assert_eq!(decode_message_error(&first, &message, &ID), None);
+    }
+
+    #[cfg(feature = "rpc")]
+    #[test]
+    fn test_decode_logged_error() {
+        use marinade_sdk::{error::decode_logged_error, ID};
+        use solana_program::{instruction::InstructionError, pubkey::Pubkey};
+        use solana_sdk::transaction::TransactionError;
+
+        let caller = Pubkey::new_unique();
+        // Marinade failing inside a CPI, the error is reported against the caller
+        let error = TransactionError::InstructionError(0, InstructionError::Custom(4355));
+        let logs = [
+            format!("Program {} invoke [1]", caller),
+            format!("Program {} invoke [2]", ID),
+            format!("Program {} failed: custom program error: 0x1103", ID),
+            format!("Program {} failed: custom program error: 0x1103", caller),
+        ];
+        let info = decode_logged_error(&error, &logs, &ID).unwrap();
+        assert_eq!(info.error, CommonError::TicketNotDue);
+        // the same code raised by another program only
+        assert_eq!(decode_logged_error(&error, &logs[3..], &ID), None);
+    }
+}
@@ -0,0 +1,61 @@
+#![cfg(feature = "events")]
+
+#[cfg(test)]
+mod tests {
+    use borsh::BorshSerialize;
+    use marinade_sdk::{
+        events::{parse_events, program_messages, ClaimEvent, MarinadeEvent},
+        program::MarinadeProgram,
+        ID,
+    };
+    use micro_anchor::Discriminator;
+    use solana_program::pubkey::Pubkey;
+
+    fn program_data<E: BorshSerialize + Discriminator>(event: &E) -> String {
+        let mut data = E::DISCRIMINATOR.to_vec();
+        data.extend(event.try_to_vec().unwrap());
+        format!("Program data: {}", base64::encode(data))
+    }
+
+    #[test]
+    fn test_only_marinade_output_is_parsed() {
+        let claim = ClaimEvent {
+            state: Pubkey::new_unique(),
+            epoch: 500,
+            ticket: Pubkey::new_unique(),
+            beneficiary: Pubkey::new_unique(),
+            circulating_ticket_balance: 10,
+            circulating_ticket_count: 1,
+            reserve_balance: 1_000,
+            user_balance: 20,
+            amount: 10,
+        };
+        let caller = Pubkey::new_unique();
+        let logs: Vec<String> = vec![
+            format!("Program {} invoke [1]", caller),
+            // forged by the caller
+            program_data(&claim),
+            format!("Program {} invoke [2]", ID),
+            "Program log: Instruction: Claim".to_string(),
+            "Program 11111111111111111111111111111111 invoke [3]".to_string(),
+            "Program log: not marinade".to_string(),
+            "Program 11111111111111111111111111111111 success".to_string(),
+            program_data(&claim),
+            format!("Program {} consumed 20000 of 180000 compute units", ID),
+            format!("Program {} success", ID),
+            format!("Program {} success", caller),
+        ];
+        let program = MarinadeProgram::default();
+        assert_eq!(
+            parse_events(&program, &logs).unwrap(),
+            vec![MarinadeEvent::Claim(claim)]
+        );
+        assert_eq!(
+            program_messages(&program, &logs),
+            vec!["Instruction: Claim"]
+        );
+        // the same logs for a fork at another id hold no event of the fork
+        let fork = MarinadeProgram::new(Pubkey::new_unique());
+        assert!(parse_events(&fork, &logs).unwrap().is_empty());
+    }
+}
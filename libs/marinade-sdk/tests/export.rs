@@ -0,0 +1,227 @@
+#![cfg(feature = "export")]
+
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use crate::common::test_state;
+    use marinade_sdk::{
+        export::{write_csv, write_csv_with_address, CsvRecord, EpochReport, ValidatorReportRow},
+        state::{
+            delayed_unstake_ticket::DelayedUnstakeTicket, stake_system::StakeRecord,
+            validator_system::ValidatorRecord,
+        },
+        validator_report::ValidatorReport,
+    };
+    use solana_program::pubkey::Pubkey;
+
+    struct Note(&'static str);
+
+    impl CsvRecord for Note {
+        const COLUMNS: &'static [&'static str] = &["id", "note"];
+
+        fn csv_fields(&self) -> Vec<String> {
+            vec!["1".to_string(), self.0.to_string()]
+        }
+    }
+
+    fn csv<R: CsvRecord>(records: &[R]) -> String {
+        let mut out = Vec::new();
+        write_csv(&mut out, records).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn test_ticket_rows() {
+        let ticket = DelayedUnstakeTicket {
+            state_address: Pubkey::new_unique(),
+            beneficiary: Pubkey::new_unique(),
+            lamports_amount: 5_000,
+            created_epoch: 42,
+        };
+        assert_eq!(
+            csv(std::slice::from_ref(&ticket)),
+            format!(
+                "state_address,beneficiary,lamports_amount,created_epoch\n{},{},5000,42\n",
+                ticket.state_address, ticket.beneficiary
+            )
+        );
+        assert_eq!(
+            csv::<DelayedUnstakeTicket>(&[]),
+            "state_address,beneficiary,lamports_amount,created_epoch\n"
+        );
+    }
+
+    #[test]
+    fn test_rows_with_address() {
+        let address = Pubkey::new_unique();
+        let record = StakeRecord {
+            stake_account: Pubkey::new_unique(),
+            last_update_delegated_lamports: 7,
+            last_update_epoch: 3,
+            is_emergency_unstaking: 1,
+        };
+        let mut out = Vec::new();
+        write_csv_with_address(&mut out, [(&address, &record)]).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            format!(
+                "address,stake_account,last_update_delegated_lamports,last_update_epoch,\
+                 is_emergency_unstaking\n{},{},7,3,1\n",
+                address, record.stake_account
+            )
+        );
+    }
+
+    #[test]
+    fn test_fields_are_quoted() {
+        assert_eq!(csv(&[Note("plain")]), "id,note\n1,plain\n");
+        assert_eq!(csv(&[Note("a,b")]), "id,note\n1,\"a,b\"\n");
+        assert_eq!(
+            csv(&[Note("say \"hi\"")]),
+            "id,note\n1,\"say \"\"hi\"\"\"\n"
+        );
+        assert_eq!(csv(&[Note("two\nlines")]), "id,note\n1,\"two\nlines\"\n");
+    }
+
+    fn validator(active_balance: u64) -> ValidatorRecord {
+        ValidatorRecord {
+            validator_account: Pubkey::new_unique(),
+            active_balance,
+            score: 10,
+            last_stake_delta_epoch: 0,
+            duplication_flag_bump_seed: 255,
+        }
+    }
+
+    #[test]
+    fn test_validator_report_rows() {
+        let validators = vec![validator(3_000), validator(1_000)];
+        let report = ValidatorReport::new(
+            validators.clone(),
+            vec![
+                (validators[0].validator_account, 900_000),
+                (Pubkey::new_unique(), 100_000),
+            ],
+        );
+        let rows = report.rows().unwrap();
+        assert_eq!(
+            rows,
+            vec![
+                ValidatorReportRow {
+                    validator_account: validators[0].validator_account,
+                    active_balance: 3_000,
+                    score: 10,
+                    stake_share_bps: 7_500,
+                    network_stake: 900_000,
+                    superminority: true,
+                },
+                ValidatorReportRow {
+                    validator_account: validators[1].validator_account,
+                    active_balance: 1_000,
+                    score: 10,
+                    stake_share_bps: 2_500,
+                    network_stake: 0,
+                    superminority: false,
+                },
+            ]
+        );
+        assert_eq!(
+            csv(&rows[1..]),
+            format!(
+                "validator_account,active_balance,score,stake_share_bps,network_stake,\
+                 superminority\n{},1000,10,2500,0,false\n",
+                validators[1].validator_account
+            )
+        );
+        assert_eq!(
+            ValidatorReport::new(vec![validator(0)], vec![])
+                .rows()
+                .unwrap()[0]
+                .stake_share_bps,
+            0
+        );
+    }
+
+    #[test]
+    fn test_epoch_report_row() {
+        let mut state = test_state();
+        state.state.msol_supply = 900;
+        state.state.validator_system.total_active_balance = 700;
+        state.state.stake_system.delayed_unstake_cooling_down = 20;
+        state.state.emergency_cooling_down = 10;
+        state.state.available_reserve_balance = 300;
+        state.state.circulating_ticket_balance = 5;
+
+        let report = EpochReport::new(42, &state.state).unwrap();
+        assert_eq!(report.total_cooling_down, 30);
+        assert_eq!(report.total_lamports_under_control, 1_030);
+        assert_eq!(
+            csv(&[report]),
+            format!(
+                "epoch,msol_price,msol_supply,total_active_balance,total_cooling_down,\
+                 available_reserve_balance,circulating_ticket_balance,\
+                 total_lamports_under_control\n42,{},900,700,30,300,5,1030\n",
+                state.state.msol_price
+            )
+        );
+    }
+
+    #[cfg(feature = "parquet")]
+    #[test]
+    fn test_parquet_columns() {
+        use arrow2::{
+            array::{UInt64Array, Utf8Array},
+            datatypes::DataType,
+            io::parquet::read::{infer_schema, read_metadata, FileReader},
+        };
+        use marinade_sdk::export::parquet::{write_parquet, write_parquet_with_address};
+        use std::io::Cursor;
+
+        let ticket = DelayedUnstakeTicket {
+            state_address: Pubkey::new_unique(),
+            beneficiary: Pubkey::new_unique(),
+            lamports_amount: 5_000,
+            created_epoch: 42,
+        };
+        let address = Pubkey::new_unique();
+        let mut out = Vec::new();
+        write_parquet_with_address(&mut out, [(&address, &ticket)]).unwrap();
+
+        let mut reader = Cursor::new(out);
+        let metadata = read_metadata(&mut reader).unwrap();
+        assert_eq!(metadata.num_rows, 1);
+        let schema = infer_schema(&metadata).unwrap();
+        assert_eq!(
+            schema
+                .fields
+                .iter()
+                .map(|field| (field.name.as_str(), field.data_type.clone()))
+                .collect::<Vec<_>>(),
+            [
+                ("address", DataType::Utf8),
+                ("state_address", DataType::Utf8),
+                ("beneficiary", DataType::Utf8),
+                ("lamports_amount", DataType::UInt64),
+                ("created_epoch", DataType::UInt64),
+            ]
+        );
+        let chunk = FileReader::new(reader, metadata.row_groups, schema, None, None, None)
+            .next()
+            .unwrap()
+            .unwrap();
+        let columns = chunk.columns();
+        let addresses = columns[0]
+            .as_any()
+            .downcast_ref::<Utf8Array<i32>>()
+            .unwrap();
+        assert_eq!(addresses.value(0), address.to_string());
+        let lamports = columns[3].as_any().downcast_ref::<UInt64Array>().unwrap();
+        assert_eq!(lamports.value(0), 5_000);
+
+        let mut out = Vec::new();
+        write_parquet::<_, DelayedUnstakeTicket, _>(&mut out, []).unwrap();
+        let metadata = read_metadata(&mut Cursor::new(out)).unwrap();
+        assert_eq!(metadata.num_rows, 0);
+    }
+}
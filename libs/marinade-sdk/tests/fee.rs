@@ -0,0 +1,63 @@
+#[cfg(test)]
+mod tests {
+    use marinade_sdk::{
+        error::{CommonError, Mismatch},
+        state::fee::{check_fee_range, Fee, FeeCents},
+    };
+
+    #[test]
+    fn test_apply() {
+        let fee = Fee::from_basis_points(250);
+        assert_eq!(fee.apply(1_000_000).unwrap(), 25_000);
+        // rounded down
+        assert_eq!(fee.apply(39).unwrap(), 0);
+        assert_eq!(Fee::MAX.apply(u64::MAX).unwrap(), u64::MAX);
+        assert_eq!(
+            Fee::from_basis_points(10_001).apply(1),
+            Err(CommonError::FeeTooHigh(Some(Mismatch::new(10_000, 10_001))))
+        );
+    }
+
+    #[test]
+    fn test_fee_cents_apply() {
+        let fee = FeeCents::from_bp_cents(250);
+        assert_eq!(fee.apply(1_000_000).unwrap(), 250);
+        // rounded down
+        assert_eq!(fee.apply(3_999).unwrap(), 0);
+        assert_eq!(
+            FeeCents::from_bp_cents(1_000_000).apply(u64::MAX).unwrap(),
+            u64::MAX
+        );
+        assert_eq!(
+            FeeCents::from_bp_cents(1_000_001).apply(1),
+            Err(CommonError::FeeTooHigh(Some(Mismatch::new(
+                1_000_000, 1_000_001
+            ))))
+        );
+    }
+
+    #[test]
+    fn test_check_fee_range() {
+        let (min, max) = (Fee::from_basis_points(10), Fee::from_basis_points(1_000));
+        check_fee_range(min, min, max).unwrap();
+        check_fee_range(max, min, max).unwrap();
+        assert_eq!(
+            check_fee_range(Fee::from_basis_points(5), min, max),
+            Err(CommonError::NumberTooLow(Some(Mismatch::new(10, 5))))
+        );
+        assert!(matches!(
+            check_fee_range(Fee::from_basis_points(1_001), min, max),
+            Err(CommonError::FeeTooHigh(_))
+        ));
+        assert!(Fee::from_basis_points(30) < Fee::MAX);
+
+        assert_eq!(
+            check_fee_range(min, min, Fee::from_basis_points(10_001)),
+            Err(CommonError::FeeTooHigh(Some(Mismatch::new(10_000, 10_001))))
+        );
+        assert_eq!(
+            check_fee_range(min, max, min),
+            Err(CommonError::FeesWrongWayRound)
+        );
+    }
+}
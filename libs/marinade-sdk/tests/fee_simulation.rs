@@ -0,0 +1,124 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use crate::common::test_state;
+    use marinade_sdk::{
+        fee_simulation::{
+            simulate_fee_change, simulate_fee_change_with_extension, FeeAssumptions, FeeProposal,
+        },
+        instructions::{config_lp::ConfigLpData, config_marinade::ConfigMarinadeData},
+        state::{
+            fee::{Fee, FeeCents},
+            marinade::MarinadeExtension,
+        },
+    };
+
+    #[test]
+    fn test_simulate_fee_change() {
+        let mut state = test_state();
+        let marinade = &mut state.state;
+        marinade.available_reserve_balance = 1_000_000_000_000;
+        marinade.msol_supply = 1_000_000_000_000;
+        marinade.reward_fee = Fee::from_basis_points(200);
+        marinade.liq_pool.lp_liquidity_target = 10_000_000_000;
+        marinade.liq_pool.lp_min_fee = Fee::from_basis_points(30);
+        marinade.liq_pool.lp_max_fee = Fee::from_basis_points(300);
+        marinade.liq_pool.treasury_cut = Fee::from_basis_points(2_500);
+
+        let proposal = FeeProposal {
+            lp: ConfigLpData::default().with_max_fee(Fee::from_basis_points(900)),
+            marinade: ConfigMarinadeData::default().with_rewards_fee(Fee::from_basis_points(600)),
+            delayed_unstake_fee: None,
+        };
+        let assumptions = FeeAssumptions {
+            gross_staking_apy: Fee::from_basis_points(700),
+            liquid_unstake_volume: 100_000_000_000,
+            delayed_unstake_volume: 0,
+            sol_leg: 0,
+            liquidity_levels: vec![],
+        };
+        let impact = simulate_fee_change(&state.state, &proposal, &assumptions).unwrap();
+
+        assert_eq!(impact.unstake_fees.len(), 6);
+        assert_eq!(impact.unstake_fees[0].before, Fee::from_basis_points(300));
+        assert_eq!(impact.unstake_fees[0].after, Fee::from_basis_points(900));
+        assert_eq!(impact.unstake_fees[5].after, Fee::from_basis_points(30));
+
+        // 7% of 1000 SOL = 70 SOL of rewards
+        assert_eq!(impact.before.reward_fee_revenue, 1_400_000_000);
+        assert_eq!(impact.after.reward_fee_revenue, 4_200_000_000);
+        // 25% of 3% (9%) of 100 SOL
+        assert_eq!(impact.before.unstake_fee_revenue, 750_000_000);
+        assert_eq!(impact.after.unstake_fee_revenue, 2_250_000_000);
+        assert_eq!(impact.before.depositor_apy, Fee::from_basis_points(686));
+        assert_eq!(impact.after.depositor_apy, Fee::from_basis_points(658));
+        let markdown = impact.to_markdown();
+        assert!(markdown.contains("| Depositor APY | 6.86% | 6.58% |"));
+        assert!(markdown.contains("| Reward fee revenue | 1.40 SOL | 4.20 SOL |"));
+        assert!(markdown.contains("| 0.00 SOL | 3% | 9% |"));
+        assert!(markdown.contains("| 2.50 SOL |"));
+    }
+
+    #[test]
+    fn test_reject_inverted_fees() {
+        let state = test_state();
+        let proposal = FeeProposal {
+            lp: ConfigLpData::default().with_min_fee(Fee::from_basis_points(100)),
+            ..FeeProposal::default()
+        };
+        let assumptions = FeeAssumptions {
+            gross_staking_apy: Fee::from_basis_points(700),
+            liquid_unstake_volume: 0,
+            delayed_unstake_volume: 0,
+            sol_leg: 0,
+            liquidity_levels: vec![0],
+        };
+        assert!(simulate_fee_change(&state.state, &proposal, &assumptions).is_err());
+    }
+
+    #[test]
+    fn test_simulate_delayed_unstake_fee() {
+        let mut state = test_state();
+        state.state.available_reserve_balance = 1_000_000_000_000;
+        state.state.msol_supply = 1_000_000_000_000;
+        let extension = MarinadeExtension {
+            delayed_unstake_fee: FeeCents::from_bp_cents(100),
+            ..MarinadeExtension::default()
+        };
+        let proposal = FeeProposal {
+            delayed_unstake_fee: Some(FeeCents::from_bp_cents(1_000)),
+            ..FeeProposal::default()
+        };
+        let assumptions = FeeAssumptions {
+            gross_staking_apy: Fee::from_basis_points(700),
+            liquid_unstake_volume: 0,
+            delayed_unstake_volume: 1_000_000_000_000,
+            sol_leg: 0,
+            liquidity_levels: vec![0],
+        };
+        let impact =
+            simulate_fee_change_with_extension(&state.state, &extension, &proposal, &assumptions)
+                .unwrap();
+
+        // 0.01% and 0.1% of 1000 SOL
+        assert_eq!(impact.before.delayed_unstake_fee_revenue, 100_000_000);
+        assert_eq!(impact.after.delayed_unstake_fee_revenue, 1_000_000_000);
+        assert_eq!(impact.after.treasury_revenue(), 1_000_000_000);
+        assert!(impact
+            .to_markdown()
+            .contains("| Delayed unstake fee | 0.01% | 0.1% |"));
+
+        let too_high = FeeProposal {
+            delayed_unstake_fee: Some(FeeCents::from_bp_cents(1_000_001)),
+            ..FeeProposal::default()
+        };
+        assert!(simulate_fee_change_with_extension(
+            &state.state,
+            &extension,
+            &too_high,
+            &assumptions
+        )
+        .is_err());
+    }
+}
@@ -0,0 +1,75 @@
+#![cfg(feature = "rpc")]
+
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use borsh::BorshSerialize;
+    use marinade_sdk::{
+        filters,
+        state::{delayed_unstake_ticket::DelayedUnstakeTicket, marinade::Marinade},
+    };
+    use micro_anchor::Discriminator;
+    use solana_client::rpc_filter::RpcFilterType;
+    use solana_program::pubkey::Pubkey;
+
+    use crate::common::test_state;
+
+    fn matches(filters: &[RpcFilterType], data: &[u8]) -> bool {
+        filters.iter().all(|filter| match filter {
+            RpcFilterType::DataSize(size) => data.len() as u64 == *size,
+            RpcFilterType::Memcmp(memcmp) => memcmp.bytes_match(data),
+            _ => unreachable!(),
+        })
+    }
+
+    #[test]
+    fn test_ticket_filters_match_the_serialized_layout() {
+        let state = Pubkey::new_unique();
+        let beneficiary = Pubkey::new_unique();
+        let ticket = DelayedUnstakeTicket {
+            state_address: state,
+            beneficiary,
+            lamports_amount: 1_000_000_000,
+            created_epoch: 500,
+        };
+        let mut data = DelayedUnstakeTicket::DISCRIMINATOR.to_vec();
+        data.extend(ticket.try_to_vec().unwrap());
+
+        assert!(matches(&filters::tickets(), &data));
+        assert!(matches(&filters::tickets_by_state(&state), &data));
+        assert!(matches(
+            &filters::tickets_by_beneficiary(&beneficiary),
+            &data
+        ));
+        assert!(matches(&filters::tickets_of(&state, &beneficiary), &data));
+        assert!(!matches(&filters::tickets_of(&beneficiary, &state), &data));
+        assert!(!matches(
+            &filters::tickets_by_state(&Pubkey::new_unique()),
+            &data
+        ));
+        assert!(!matches(&filters::marinade_state(), &data));
+    }
+
+    #[test]
+    fn test_state_filters_match_the_serialized_layout() {
+        let test = test_state();
+        let mut data = Marinade::DISCRIMINATOR.to_vec();
+        data.extend(test.state.try_to_vec().unwrap());
+
+        assert!(matches(&filters::marinade_state(), &data));
+        assert!(matches(
+            &filters::marinade_state_by_msol_mint(&test.state.msol_mint),
+            &data
+        ));
+        assert!(matches(
+            &filters::marinade_state_by_admin(&test.state.admin_authority),
+            &data
+        ));
+        assert!(!matches(
+            &filters::marinade_state_by_admin(&test.state.msol_mint),
+            &data
+        ));
+        assert!(!matches(&filters::tickets(), &data));
+    }
+}
@@ -0,0 +1,76 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use crate::common::test_state;
+    use marinade_sdk::{
+        checks::CheckMode,
+        error::{CommonError, Mismatch},
+        guard::{GuardViolation, GuardedInstructions, StateGuard},
+        state::fee::Fee,
+    };
+    use solana_program::pubkey::Pubkey;
+
+    #[test]
+    fn test_guard_release() {
+        let mut state = test_state();
+        state.state.msol_supply = 1_000_000;
+        state.state.available_reserve_balance = 1_000_000;
+        let guard = StateGuard::new(&state, Fee::from_basis_points(100))
+            .unwrap()
+            .with_max_epoch(10);
+        let guarded = GuardedInstructions::new(guard, vec![]);
+        assert!(guarded.release(&state, 10).is_ok());
+        assert!(matches!(
+            guarded.release(&state, 11),
+            Err(GuardViolation::Expired { .. })
+        ));
+
+        // 2% rewards moved the price out of the 1% tolerance
+        state.state.available_reserve_balance = 1_020_000;
+        assert!(matches!(
+            guarded.release(&state, 10),
+            Err(GuardViolation::PriceOutOfBounds { .. })
+        ));
+    }
+
+    #[test]
+    fn test_guard_checks_report_every_violation() {
+        let mut state = test_state();
+        state.state.msol_supply = 1_000_000;
+        state.state.available_reserve_balance = 1_000_000;
+        let guard = StateGuard::new(&state, Fee::from_basis_points(100))
+            .unwrap()
+            .with_max_epoch(10);
+        assert!(guard
+            .checks(&state, 10, CheckMode::AllErrors)
+            .finish()
+            .is_ok());
+
+        state.state.available_reserve_balance = 1_020_000;
+        state.key = Pubkey::new_unique();
+        let failures = guard
+            .checks(&state, 11, CheckMode::AllErrors)
+            .finish()
+            .unwrap_err();
+        assert_eq!(failures.len(), 3);
+        assert!(failures[0].message.starts_with("Guard is for state"));
+        assert_eq!(
+            failures[0].error,
+            CommonError::UnexpectedAccount(Some(Mismatch::new(guard.state_address, state.key)))
+                .into()
+        );
+        assert_eq!(
+            failures[1].message,
+            "Guard expired at epoch 10 (current 11)"
+        );
+        assert!(failures[2].message.starts_with("mSOL price"));
+        assert_eq!(
+            guard
+                .checks(&state, 11, CheckMode::FirstError)
+                .failures()
+                .len(),
+            1
+        );
+    }
+}
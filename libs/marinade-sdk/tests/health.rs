@@ -0,0 +1,80 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::common::test_state;
+    use marinade_sdk::{
+        health::{Health, HealthIssue, HealthPolicy, StateHealth},
+        state::{marinade::MarinadeExtension, stake_system::StakeRecord},
+    };
+    use solana_program::pubkey::Pubkey;
+
+    fn stake(last_update_epoch: u64) -> StakeRecord {
+        StakeRecord {
+            stake_account: Pubkey::new_unique(),
+            last_update_delegated_lamports: 0,
+            last_update_epoch,
+            is_emergency_unstaking: 0,
+        }
+    }
+
+    #[test]
+    fn test_state_health() {
+        let mut state = test_state();
+        state.state.stake_system.last_stake_delta_epoch = 99;
+        let health = StateHealth::new(
+            &state.state,
+            &MarinadeExtension::default(),
+            &[stake(100), stake(98)],
+            1_000,
+            1_200,
+            100,
+        );
+        assert_eq!(health.slot_lag, 200);
+        assert_eq!(health.stake_update_epochs_behind, 2);
+        assert_eq!(health.stake_delta_epochs_behind, 1);
+        assert!(!health.paused);
+    }
+
+    #[test]
+    fn test_policy() {
+        let healthy = Health {
+            rpc_error: None,
+            rpc_latency: Duration::from_millis(200),
+            state: Some(StateHealth {
+                slot_lag: 10,
+                epoch: 100,
+                stake_update_epochs_behind: 1,
+                stake_delta_epochs_behind: 0,
+                paused: false,
+            }),
+        };
+        let policy = HealthPolicy::default();
+        assert!(healthy.is_healthy(&policy));
+
+        let mut lagging = healthy.clone();
+        lagging.state.as_mut().unwrap().slot_lag = 1_000;
+        lagging.state.as_mut().unwrap().paused = true;
+        assert_eq!(
+            lagging.issues(&policy),
+            vec![HealthIssue::SlotLag(1_000), HealthIssue::Paused]
+        );
+        assert!(lagging
+            .issues(&HealthPolicy {
+                allow_paused: true,
+                max_slot_lag: 1_000,
+                ..policy
+            })
+            .is_empty());
+
+        let unreachable = Health {
+            rpc_error: Some("connection refused".to_string()),
+            rpc_latency: Duration::from_secs(10),
+            state: None,
+        };
+        assert_eq!(unreachable.issues(&policy).len(), 3);
+        assert!(!unreachable.is_healthy(&policy));
+    }
+}
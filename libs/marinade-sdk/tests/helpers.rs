@@ -0,0 +1,113 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use crate::common::test_state;
+    use marinade_sdk::{
+        instructions::{
+            add_validator::AddValidatorData, config_validator_system::ConfigValidatorSystemData,
+            deactivate_stake::DeactivateStakeData, emergency_unstake::EmergencyUnstakeData,
+            initialize::InitializeData, merge_stakes::MergeStakesData,
+            partial_unstake::PartialUnstakeData, remove_validator::RemoveValidatorData,
+            set_validator_score::SetValidatorScoreData, stake_reserve::StakeReserveData,
+        },
+        lint::lint_instruction,
+        program::MarinadeProgram,
+        state::{
+            marinade::{Marinade, MarinadeHelpers},
+            stake_system::StakeSystemHelpers,
+            validator_system::ValidatorRecord,
+        },
+    };
+    use solana_program::pubkey::Pubkey;
+
+    #[test]
+    fn test_validator_system_builders() {
+        let state = test_state();
+        let vote = Pubkey::new_unique();
+        let stake_account = Pubkey::new_unique();
+        let split = Pubkey::new_unique();
+        let payer = Pubkey::new_unique();
+        let instructions = vec![
+            (
+                "config_validator_system",
+                state.config_validator_system_instruction(ConfigValidatorSystemData::new(1)),
+            ),
+            (
+                "add_validator",
+                state.add_validator(AddValidatorData::new(100), vote, payer),
+            ),
+            (
+                "remove_validator",
+                state.remove_validator(RemoveValidatorData::new(0, vote)),
+            ),
+            (
+                "set_validator_score",
+                state.set_validator_score(SetValidatorScoreData::new(0, vote, 50)),
+            ),
+            (
+                "stake_reserve",
+                state
+                    .stake_reserve(StakeReserveData::new(0), vote, stake_account)
+                    .unwrap(),
+            ),
+            (
+                "deactivate_stake",
+                state
+                    .deactivate_stake(DeactivateStakeData::new(0, 0), stake_account, split, payer)
+                    .unwrap(),
+            ),
+            (
+                "emergency_unstake",
+                state
+                    .emergency_unstake(EmergencyUnstakeData::new(0, 0), stake_account)
+                    .unwrap(),
+            ),
+            (
+                "partial_unstake",
+                state
+                    .partial_unstake(
+                        PartialUnstakeData::new(0, 0, 1_000_000_000),
+                        stake_account,
+                        split,
+                        payer,
+                    )
+                    .unwrap(),
+            ),
+            (
+                "merge_stakes",
+                state
+                    .merge_stakes(MergeStakesData::new(0, 1, 0), stake_account, split)
+                    .unwrap(),
+            ),
+            (
+                "initialize",
+                state.initialize(
+                    InitializeData::new(Pubkey::new_unique(), Pubkey::new_unique()),
+                    payer,
+                ),
+            ),
+        ];
+        for (name, instruction) in &instructions {
+            let report = lint_instruction(instruction, &MarinadeProgram::default());
+            assert_eq!(report.instruction, Some(*name));
+            assert!(report.issues.is_empty(), "{}: {:?}", name, report.issues);
+        }
+
+        let duplication_flag = ValidatorRecord::find_duplication_flag(&state.key, &vote).0;
+        assert_eq!(instructions[1].1.accounts[4].pubkey, duplication_flag);
+        assert_eq!(instructions[2].1.accounts[3].pubkey, duplication_flag);
+        assert_eq!(
+            instructions[1].1.accounts[1].pubkey,
+            state.state.validator_system.manager_authority
+        );
+        assert_eq!(
+            instructions[4].1.accounts[6].pubkey,
+            state.try_stake_deposit_authority().unwrap()
+        );
+        assert_eq!(
+            instructions[9].1.accounts[2].pubkey,
+            Marinade::find_reserve_address(&state.key).0
+        );
+    }
+}
@@ -0,0 +1,145 @@
+#![cfg(feature = "history")]
+
+#[cfg(test)]
+mod tests {
+    use std::cell::{Cell, RefCell};
+
+    use marinade_sdk::{
+        history::{
+            HistoryCursor, HistoryError, HistorySource, HistoryTransaction, InstructionKind,
+            SignatureInfo,
+        },
+        instructions::deposit::DepositData,
+        storage::MemoryStorage,
+        ID,
+    };
+    use micro_anchor::InstructionData;
+    use solana_program::{instruction::CompiledInstruction, pubkey::Pubkey};
+    use solana_sdk::signature::Signature;
+
+    /// Newest first like the RPC
+    struct FakeSource {
+        signatures: Vec<SignatureInfo>,
+        fetched: Cell<usize>,
+    }
+
+    impl HistorySource for FakeSource {
+        fn signatures(
+            &self,
+            _address: &Pubkey,
+            before: Option<Signature>,
+            until: Option<Signature>,
+            limit: usize,
+        ) -> Result<Vec<SignatureInfo>, HistoryError> {
+            Ok(self
+                .signatures
+                .iter()
+                .skip_while(|info| before.map_or(false, |before| info.signature != before))
+                .skip(before.map_or(0, |_| 1))
+                .take_while(|info| Some(info.signature) != until)
+                .take(limit)
+                .cloned()
+                .collect())
+        }
+
+        fn transaction(&self, _signature: &Signature) -> Result<HistoryTransaction, HistoryError> {
+            self.fetched.set(self.fetched.get() + 1);
+            Ok(HistoryTransaction {
+                account_keys: vec![Pubkey::new_unique(), ID],
+                instructions: vec![CompiledInstruction::new_from_raw_parts(
+                    1,
+                    DepositData::new(1).data(),
+                    vec![0],
+                )],
+                inner_instructions: vec![],
+                token_balances: vec![],
+            })
+        }
+    }
+
+    fn info(slot: u64) -> SignatureInfo {
+        SignatureInfo {
+            signature: Signature::new_unique(),
+            slot,
+            block_time: None,
+            failed: false,
+        }
+    }
+
+    #[test]
+    fn test_resume_after_last_handled() {
+        let mut source = FakeSource {
+            signatures: (0..5).rev().map(info).collect(),
+            fetched: Cell::new(0),
+        };
+        let mut cursor = HistoryCursor::new(ID, MemoryStorage::new());
+        cursor.page_size = 2;
+
+        let mut slots = Vec::new();
+        let handled = cursor
+            .ingest(&source, |transaction| {
+                assert_eq!(transaction.instructions, vec![InstructionKind::Deposit]);
+                slots.push(transaction.slot);
+                Ok(())
+            })
+            .unwrap();
+        assert_eq!(handled, 5);
+        assert_eq!(slots, vec![0, 1, 2, 3, 4]);
+
+        source.signatures.insert(0, info(5));
+        assert_eq!(cursor.pending(&source).unwrap().count(), 1);
+        let handled = cursor.ingest(&source, |_| Ok(())).unwrap();
+        assert_eq!(handled, 1);
+        assert_eq!(source.fetched.get(), 6);
+        assert_eq!(
+            cursor.resume_point().unwrap(),
+            Some(source.signatures[0].signature)
+        );
+    }
+
+    /// Lets the test add signatures while pending iterates
+    struct GrowingSource(RefCell<FakeSource>);
+
+    impl HistorySource for GrowingSource {
+        fn signatures(
+            &self,
+            address: &Pubkey,
+            before: Option<Signature>,
+            until: Option<Signature>,
+            limit: usize,
+        ) -> Result<Vec<SignatureInfo>, HistoryError> {
+            self.0.borrow().signatures(address, before, until, limit)
+        }
+
+        fn transaction(&self, signature: &Signature) -> Result<HistoryTransaction, HistoryError> {
+            self.0.borrow().transaction(signature)
+        }
+    }
+
+    #[test]
+    fn test_pending_pages_oldest_first() {
+        let source = GrowingSource(RefCell::new(FakeSource {
+            signatures: (0..7).rev().map(info).collect(),
+            fetched: Cell::new(0),
+        }));
+        let mut cursor = HistoryCursor::new(ID, MemoryStorage::new());
+        cursor.page_size = 2;
+
+        let mut pending = cursor.pending(&source).unwrap();
+        assert_eq!(pending.next().unwrap().unwrap().slot, 0);
+        // arrives after the walk, left for the next run
+        source.0.borrow_mut().signatures.insert(0, info(7));
+        let slots: Vec<u64> = pending.map(|info| info.unwrap().slot).collect();
+        assert_eq!(slots, vec![1, 2, 3, 4, 5, 6]);
+
+        let mut slots = Vec::new();
+        cursor
+            .ingest(&source, |transaction| {
+                slots.push(transaction.slot);
+                Ok(())
+            })
+            .unwrap();
+        assert_eq!(slots, (0..8).collect::<Vec<_>>());
+        assert_eq!(cursor.pending(&source).unwrap().count(), 0);
+    }
+}
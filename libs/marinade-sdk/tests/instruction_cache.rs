@@ -0,0 +1,48 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use crate::common::test_state;
+    use marinade_sdk::{
+        instruction_cache::InstructionCache, instructions::deposit::DepositData,
+        located::KeyedAccount, state::marinade::MarinadeHelpers,
+    };
+    use solana_program::pubkey::Pubkey;
+
+    #[test]
+    fn test_cached_deposit_until_state_changes() {
+        let test = test_state();
+        let expected = test
+            .deposit(
+                DepositData::new(1_000),
+                Pubkey::default(),
+                Pubkey::default(),
+            )
+            .unwrap();
+        let state = KeyedAccount::new(test.key, test.state);
+        let mut cache = InstructionCache::new(state.clone());
+        let (from, to) = (Pubkey::default(), Pubkey::default());
+
+        assert_eq!(
+            cache.deposit(DepositData::new(1_000), from, to).unwrap(),
+            expected
+        );
+        assert_eq!(
+            cache.deposit(DepositData::new(1_000), from, to).unwrap(),
+            expected
+        );
+        cache.deposit(DepositData::new(2_000), from, to).unwrap();
+        assert_eq!(cache.stats(), (1, 2));
+        assert_eq!(cache.len(), 2);
+
+        assert!(!cache.update(state.clone()));
+        assert_eq!(cache.len(), 2);
+
+        let mut changed = state;
+        changed.account.msol_mint = Pubkey::new_unique();
+        assert!(cache.update(changed));
+        assert!(cache.is_empty());
+        let rebuilt = cache.deposit(DepositData::new(1_000), from, to).unwrap();
+        assert_ne!(rebuilt, expected);
+    }
+}
@@ -0,0 +1,72 @@
+#[cfg(test)]
+mod tests {
+    use borsh::BorshSerialize;
+    use marinade_sdk::{
+        instructions::{
+            deposit::DepositData,
+            initialize::{InitializeData, LiqPoolInitializeData},
+            merge_stakes::MergeStakesData,
+            partial_unstake::PartialUnstakeData,
+            set_validator_score::SetValidatorScoreData,
+        },
+        state::fee::Fee,
+    };
+    use micro_anchor::{Discriminator, InstructionData};
+    use solana_program::pubkey::Pubkey;
+
+    #[test]
+    fn test_constructors_keep_the_wire_order() {
+        let mut data = DepositData::DISCRIMINATOR.to_vec();
+        data.extend(5u64.to_le_bytes());
+        assert_eq!(DepositData::new(5).data(), data);
+
+        assert_eq!(
+            MergeStakesData::new(1, 2, 3).try_to_vec().unwrap(),
+            [1, 0, 0, 0, 2, 0, 0, 0, 3, 0, 0, 0]
+        );
+
+        let unstake = PartialUnstakeData::new(4, 7, 500);
+        assert_eq!(unstake.stake_index, 4);
+        assert_eq!(unstake.validator_index, 7);
+        assert_eq!(unstake.desired_unstake_amount, 500);
+
+        let vote = Pubkey::new_unique();
+        let mut score = 9u32.try_to_vec().unwrap();
+        score.extend(vote.to_bytes());
+        score.extend(100u32.to_le_bytes());
+        assert_eq!(
+            SetValidatorScoreData::new(9, vote, 100)
+                .try_to_vec()
+                .unwrap(),
+            score
+        );
+    }
+
+    #[test]
+    fn test_initialize_builder() {
+        let admin = Pubkey::new_unique();
+        let manager = Pubkey::new_unique();
+        let liq_pool = LiqPoolInitializeData::new(
+            10_000,
+            Fee::from_basis_points(300),
+            Fee::from_basis_points(30),
+            Fee::from_basis_points(2_500),
+        );
+        let data = InitializeData::new(admin, manager)
+            .with_min_stake(7)
+            .with_reward_fee(Fee::from_basis_points(200))
+            .with_liq_pool(liq_pool)
+            .with_slots_for_stake_delta(9);
+
+        assert_eq!(data.admin_authority, admin);
+        assert_eq!(data.validator_manager_authority, manager);
+        assert_eq!(data.min_stake, 7);
+        assert_eq!(data.reward_fee, Fee::from_basis_points(200));
+        assert_eq!(data.liq_pool, liq_pool);
+        assert_eq!(data.slots_for_stake_delta, 9);
+        // the fields left out keep their defaults
+        assert_eq!(data.additional_stake_record_space, 0);
+        assert_eq!(data.additional_validator_record_space, 0);
+        assert_eq!(data.liq_pool.lp_liquidity_target, 10_000);
+    }
+}
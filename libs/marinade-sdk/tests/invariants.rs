@@ -0,0 +1,30 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use crate::common::test_state;
+    use marinade_sdk::{located::Located, state::invariants::Invariant};
+
+    #[test]
+    fn test_check_invariants() {
+        let mut state = test_state();
+        assert_eq!(state.state.check_invariants(&state.key(), None), vec![]);
+
+        state.state.reserve_bump_seed = state.state.reserve_bump_seed.wrapping_add(1);
+        state.state.circulating_ticket_balance = 1;
+        let invariants: Vec<Invariant> = state
+            .state
+            .check_invariants(&state.key(), None)
+            .into_iter()
+            .map(|violation| violation.invariant)
+            .collect();
+        assert_eq!(
+            invariants,
+            vec![
+                Invariant::TicketsCovered,
+                Invariant::TicketCount,
+                Invariant::BumpSeed("reserve")
+            ]
+        );
+    }
+}
@@ -0,0 +1,85 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use crate::common::test_state;
+    use marinade_sdk::{
+        instructions::config_marinade::ConfigMarinadeData,
+        state::limits::{LimitChange, LimitKind, Limits, LimitsHistory},
+    };
+
+    #[test]
+    fn test_limits_follow_the_config() {
+        let mut state = test_state();
+        state.state.min_deposit = 1;
+        state.state.stake_system.min_stake = 1_000;
+        state.state.liq_pool.liquidity_sol_cap = u64::MAX;
+        let limits = state.state.limits();
+        assert_eq!(
+            limits,
+            Limits {
+                min_deposit: 1,
+                min_withdraw: 0,
+                min_stake: 1_000,
+                staking_sol_cap: 0,
+                liquidity_sol_cap: u64::MAX,
+            }
+        );
+
+        let configured = limits.with_config(
+            &ConfigMarinadeData::default()
+                .with_min_deposit(5)
+                .with_min_stake(1_000),
+        );
+        assert_eq!(configured.min_deposit, 5);
+        assert_eq!(configured.min_withdraw, 0);
+        // unchanged values are not reported
+        assert_eq!(
+            limits.changes(&configured),
+            vec![LimitChange {
+                limit: LimitKind::MinDeposit,
+                old: 1,
+                new: 5,
+            }]
+        );
+        assert!(limits.changes(&limits).is_empty());
+    }
+
+    #[test]
+    fn test_limits_history() {
+        let mut history = LimitsHistory::default();
+        let first = Limits::default();
+        let second = Limits {
+            min_deposit: 5,
+            staking_sol_cap: 100,
+            ..first
+        };
+        history.observe(10, first);
+        assert!(history.entries.is_empty());
+        history.observe(20, second);
+        history.observe(30, second);
+
+        assert_eq!(
+            history.entries,
+            vec![
+                (
+                    20,
+                    LimitChange {
+                        limit: LimitKind::MinDeposit,
+                        old: 0,
+                        new: 5,
+                    }
+                ),
+                (
+                    20,
+                    LimitChange {
+                        limit: LimitKind::StakingSolCap,
+                        old: 0,
+                        new: 100,
+                    }
+                ),
+            ]
+        );
+        assert_eq!(history.current(), Some(&second));
+    }
+}
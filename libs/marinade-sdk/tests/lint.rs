@@ -0,0 +1,86 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use crate::common::test_state;
+    use marinade_sdk::{
+        instructions::deposit::DepositData,
+        lint::{lint_instruction, LintIssue},
+        program::MarinadeProgram,
+        state::marinade::MarinadeHelpers,
+        templates::instruction_templates,
+        ID,
+    };
+    use solana_program::{instruction::AccountMeta, pubkey::Pubkey};
+
+    #[test]
+    fn test_templates_pass() {
+        for template in instruction_templates() {
+            let report = lint_instruction(&template.instruction, &MarinadeProgram::default());
+            assert_eq!(report.instruction, Some(template.name));
+            assert!(report.issues.is_empty(), "{}", template.name);
+        }
+    }
+
+    #[test]
+    fn test_fork_program() {
+        let program = MarinadeProgram::new(Pubkey::new_unique());
+        let mut instruction = instruction_templates()[0].instruction.clone();
+        assert_eq!(
+            lint_instruction(&instruction, &program).issues,
+            vec![LintIssue::WrongProgram {
+                expected: program.program_id,
+                actual: ID
+            }]
+        );
+        instruction.program_id = program.program_id;
+        assert!(lint_instruction(&instruction, &program).issues.is_empty());
+    }
+
+    #[test]
+    fn test_modified_deposit() {
+        let transfer_from = Pubkey::new_unique();
+        let mut instruction = test_state()
+            .deposit(DepositData::new(1_000), transfer_from, Pubkey::new_unique())
+            .unwrap();
+        let index = instruction
+            .accounts
+            .iter()
+            .position(|meta| meta.pubkey == transfer_from)
+            .unwrap();
+        instruction.accounts[index] = AccountMeta::new_readonly(transfer_from, false);
+        instruction
+            .accounts
+            .push(AccountMeta::new(Pubkey::new_unique(), false));
+
+        let report = lint_instruction(&instruction, &MarinadeProgram::default());
+        assert_eq!(report.instruction, Some("deposit"));
+        assert!(report.issues.contains(&LintIssue::MissingSigner {
+            index,
+            pubkey: transfer_from
+        }));
+        assert!(report
+            .issues
+            .contains(&LintIssue::ReadonlyShouldBeWritable {
+                index,
+                pubkey: transfer_from
+            }));
+        assert!(matches!(
+            report.issues.last(),
+            Some(LintIssue::ExtraAccounts { .. })
+        ));
+        assert!(!report.is_ok());
+    }
+
+    #[test]
+    fn test_unknown_instruction() {
+        let mut instruction = instruction_templates()[0].instruction.clone();
+        instruction.data = vec![1, 2, 3];
+        let report = lint_instruction(&instruction, &MarinadeProgram::default());
+        assert_eq!(report.instruction, None);
+        assert_eq!(
+            report.issues,
+            vec![LintIssue::UnknownInstruction([1, 2, 3, 0, 0, 0, 0, 0])]
+        );
+    }
+}
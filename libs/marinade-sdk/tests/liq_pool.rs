@@ -0,0 +1,105 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use crate::common::test_state;
+    use marinade_sdk::{
+        calc::{quote_remove_liquidity, Ratio},
+        error::{CommonError, Mismatch},
+        state::{fee::Fee, liq_pool::LiqPool, marinade::Marinade},
+    };
+
+    #[test]
+    fn test_share_of_pool() {
+        let share = LiqPool::share_of_pool(250, 1_000).unwrap();
+        assert_eq!(share.basis_points(), 2_500);
+        assert_eq!(
+            share.scaled(Marinade::PRICE_DENOMINATOR),
+            Marinade::PRICE_DENOMINATOR / 4
+        );
+        assert_eq!(share.apply(400), 100);
+        assert_eq!(LiqPool::share_of_pool(0, 0).unwrap().basis_points(), 0);
+        assert_eq!(
+            LiqPool::share_of_pool(1_001, 1_000),
+            Err(CommonError::NumberTooHigh(Some(Mismatch::new(
+                1_000, 1_001
+            ))))
+        );
+    }
+
+    #[test]
+    fn test_lp_exposure() {
+        let state = test_state();
+        let underlying = LiqPool::underlying_for_lp(500, 3_000, 1_000, 1_000).unwrap();
+        assert_eq!(underlying.sol_amount, 1_500);
+        assert_eq!(underlying.msol_amount, 500);
+        // price 1 without any stake
+        let exposure = underlying.exposure(&state.state).unwrap();
+        assert_eq!(exposure.total_value, 2_000);
+        assert_eq!(
+            exposure.msol_exposure,
+            Ratio {
+                part: 500,
+                whole: 2_000
+            }
+        );
+        assert_eq!(exposure.msol_exposure.to_string(), "25%");
+
+        let empty = LiqPool::underlying_for_lp(0, 0, 0, 0)
+            .unwrap()
+            .exposure(&state.state)
+            .unwrap();
+        assert_eq!(empty.msol_exposure.basis_points(), 0);
+    }
+
+    #[test]
+    fn test_underlying_is_the_removal_quote() {
+        assert_eq!(
+            LiqPool::underlying_for_lp(333, 3_000, 1_000, 1_000),
+            quote_remove_liquidity(333, 3_000, 1_000, 1_000)
+        );
+        // an empty pool has no tokens to redeem
+        assert_eq!(
+            LiqPool::underlying_for_lp(5, 0, 0, 0),
+            Err(CommonError::NumberTooHigh(Some(Mismatch::new(0, 5))))
+        );
+    }
+
+    #[test]
+    fn test_plan_remove_liquidity() {
+        let mut state = test_state();
+        let liq_pool = &mut state.state.liq_pool;
+        liq_pool.lp_supply = 1_000;
+        liq_pool.lp_liquidity_target = 600;
+        liq_pool.lp_max_fee = Fee::from_basis_points(300);
+        liq_pool.lp_min_fee = Fee::from_basis_points(30);
+
+        let plan = liq_pool.plan_remove_liquidity(500, 1_000).unwrap();
+        assert_eq!(plan.sol_out, 500);
+        assert_eq!(plan.sol_leg_after, 500);
+        // 400 of the 1_000 lamports are above the target
+        assert_eq!(plan.max_removable_tokens, 400);
+        assert_eq!(plan.tokens_within_target, 400);
+        assert_eq!(plan.tokens_over_target, 100);
+        assert!(plan.drops_below_target());
+        assert_eq!(plan.fee_before, Fee::from_basis_points(30));
+        // 300 - 270 * 500 / 600
+        assert_eq!(plan.fee_after, Fee::from_basis_points(75));
+
+        let plan = liq_pool.plan_remove_liquidity(400, 1_000).unwrap();
+        assert!(!plan.drops_below_target());
+        assert_eq!(plan.fee_after, Fee::from_basis_points(30));
+
+        // nothing is removable once the SOL leg is under the target
+        let plan = liq_pool.plan_remove_liquidity(100, 500).unwrap();
+        assert_eq!(plan.max_removable_tokens, 0);
+        assert_eq!(plan.tokens_over_target, 100);
+
+        assert_eq!(
+            liq_pool.plan_remove_liquidity(1_001, 1_000),
+            Err(CommonError::NumberTooHigh(Some(Mismatch::new(
+                1_000, 1_001
+            ))))
+        );
+    }
+}
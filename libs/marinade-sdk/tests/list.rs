@@ -0,0 +1,126 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use crate::common::test_state;
+    use borsh::BorshSerialize;
+    use marinade_sdk::state::{
+        list::{List, ListRecord, ListView},
+        stake_system::StakeRecord,
+        validator_system::ValidatorRecord,
+    };
+    use solana_program::{program_error::ProgramError, pubkey::Pubkey};
+    use std::fmt::Debug;
+
+    fn stake(last_update_epoch: u64, is_emergency_unstaking: u8) -> StakeRecord {
+        StakeRecord {
+            stake_account: Pubkey::new_unique(),
+            last_update_delegated_lamports: last_update_epoch * 1_000,
+            last_update_epoch,
+            is_emergency_unstaking,
+        }
+    }
+
+    fn validator(score: u32) -> ValidatorRecord {
+        ValidatorRecord {
+            validator_account: Pubkey::new_unique(),
+            active_balance: score as u64 * 1_000,
+            score,
+            last_stake_delta_epoch: 400,
+            duplication_flag_bump_seed: 255,
+        }
+    }
+
+    /// List account data with item_size bytes per record and room for one more
+    fn list_data<R: ListRecord + BorshSerialize>(records: &[R], item_size: u32) -> Vec<u8> {
+        let mut data = R::DISCRIMINATOR.to_vec();
+        for record in records {
+            let mut record = record.try_to_vec().unwrap();
+            record.resize(item_size as usize, 0);
+            data.extend(record);
+        }
+        data.resize(data.len() + item_size as usize, 0xff);
+        data
+    }
+
+    fn list(len: u32, item_size: u32) -> List {
+        let mut list = test_state().state.stake_system.stake_list;
+        list.count = len;
+        list.item_size = item_size;
+        list
+    }
+
+    /// Reads records with additional_record_space and rejects data not matching the list
+    fn check_list<R>(records: &[R])
+    where
+        R: ListRecord + BorshSerialize + Copy + PartialEq + Debug,
+    {
+        let len = records.len() as u32;
+        let item_size = R::RECORD_LEN as u32 + 4;
+        let data = list_data(records, item_size);
+        let view = ListView::<R>::new(&list(len, item_size), &data).unwrap();
+        assert_eq!(view.len(), len);
+        assert!(!view.is_empty());
+        assert_eq!(view.get(len - 1), Some(records[records.len() - 1]));
+        assert_eq!(view.get(len), None);
+        assert_eq!(view.iter().collect::<Vec<_>>(), records.to_vec());
+        for index in 0..len {
+            assert_eq!(
+                view.get(index).unwrap(),
+                list(len, item_size).get::<R>(&data, index, "list").unwrap()
+            );
+        }
+
+        let data = list_data(&records[..1], R::RECORD_LEN as u32);
+        assert!(ListView::<R>::new(&list(1, R::RECORD_LEN as u32), &data).is_ok());
+        assert_eq!(
+            ListView::<R>::new(&list(3, R::RECORD_LEN as u32), &data).unwrap_err(),
+            ProgramError::AccountDataTooSmall
+        );
+        assert_eq!(
+            ListView::<R>::new(&list(1, R::RECORD_LEN as u32 - 1), &data).unwrap_err(),
+            ProgramError::AccountDataTooSmall
+        );
+        let mut data = data;
+        data[0] ^= 1;
+        assert_eq!(
+            ListView::<R>::new(&list(1, R::RECORD_LEN as u32), &data).unwrap_err(),
+            ProgramError::InvalidAccountData
+        );
+    }
+
+    #[test]
+    fn test_stake_list() {
+        let stakes = [stake(400, 0), stake(401, 1), stake(399, 0)];
+        check_list(&stakes);
+
+        let data = list_data(&stakes, 57);
+        let stake_system = {
+            let mut stake_system = test_state().state.stake_system;
+            stake_system.stake_list = list(3, 57);
+            stake_system
+        };
+        let list = stake_system.stake_list(&data).unwrap();
+        assert!(list.get(1).unwrap().is_emergency_unstaking());
+        assert_eq!(list.stake_account(2), Some(stakes[2].stake_account));
+        assert_eq!(list.find(&stakes[2].stake_account), Some(2));
+        assert_eq!(list.find(&Pubkey::new_unique()), None);
+    }
+
+    #[test]
+    fn test_validator_list() {
+        let validators = [validator(10), validator(0), validator(30)];
+        check_list(&validators);
+
+        let data = list_data(&validators, 61);
+        let validator_system = {
+            let mut validator_system = test_state().state.validator_system;
+            validator_system.validator_list = list(3, 61);
+            validator_system
+        };
+        let list = validator_system.validator_list(&data).unwrap();
+        assert_eq!(list.score(0), Some(10));
+        assert_eq!(list.score(2), Some(30));
+        assert_eq!(list.score(3), None);
+    }
+}
@@ -0,0 +1,81 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use crate::common::test_state;
+    use marinade_sdk::{
+        error::{CommonError, Mismatch},
+        lp_pnl::{lp_pnl, LiquidityEvent, LpPnl},
+        state::marinade::Marinade,
+    };
+    use solana_program::pubkey::Pubkey;
+
+    const PRICE_ONE: u64 = Marinade::PRICE_DENOMINATOR;
+
+    #[test]
+    fn test_lp_pnl() {
+        let mut state = test_state();
+        let wallet = Pubkey::new_unique();
+        let history = [
+            LiquidityEvent::Add {
+                wallet,
+                lamports: 1_000,
+                lp_minted: 1_000,
+                msol_price: PRICE_ONE,
+            },
+            // other wallets are ignored
+            LiquidityEvent::Add {
+                wallet: Pubkey::new_unique(),
+                lamports: 5_000,
+                lp_minted: 5_000,
+                msol_price: PRICE_ONE,
+            },
+            LiquidityEvent::Remove {
+                wallet,
+                lp_burned: 400,
+                sol_out: 300,
+                msol_out: 100,
+                msol_price: PRICE_ONE,
+            },
+        ];
+        // the remaining 600 LP are the whole pool, which earned 60 lamports of fees
+        state.state.liq_pool.lp_supply = 600;
+
+        let pnl = lp_pnl(&wallet, &history, &state.state, 660, 0).unwrap();
+        assert_eq!(
+            pnl,
+            LpPnl {
+                lp_balance: 600,
+                cost_basis: 600,
+                realized: 0,
+                unrealized: 60,
+                current_value: 660,
+                vs_holding_sol: 60,
+                // mSOL is priced 1:1 in the test state
+                vs_holding_msol: 60,
+            }
+        );
+
+        assert_eq!(
+            lp_pnl(&Pubkey::new_unique(), &history, &state.state, 660, 0).unwrap(),
+            LpPnl::default()
+        );
+    }
+
+    #[test]
+    fn test_incomplete_history() {
+        let state = test_state();
+        let wallet = Pubkey::new_unique();
+        let history = [LiquidityEvent::Remove {
+            wallet,
+            lp_burned: 5,
+            sol_out: 5,
+            msol_out: 0,
+            msol_price: PRICE_ONE,
+        }];
+        assert_eq!(
+            lp_pnl(&wallet, &history, &state.state, 0, 0),
+            Err(CommonError::NumberTooHigh(Some(Mismatch::new(0, 5))))
+        );
+    }
+}
@@ -0,0 +1,143 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use crate::common::test_state;
+    use marinade_sdk::{
+        calc::Ratio,
+        error::{CommonError, Mismatch},
+        state::{
+            marinade::{EmergencyCoolingDown, StakeDelta, StakingCap},
+            stake_system::StakeSystemSummary,
+            validator_system::ValidatorSystemSummary,
+        },
+    };
+    use solana_program::program_error::ProgramError;
+
+    #[test]
+    fn test_staking_cap() {
+        let mut state = test_state();
+        state.state.available_reserve_balance = 1_000;
+
+        state.state.staking_sol_cap = 1_500;
+        assert_eq!(state.state.staking_cap(), StakingCap::Limited(1_500));
+        assert_eq!(state.state.staking_cap_headroom(), Some(500));
+        state.state.check_staking_cap(500).unwrap();
+        assert_eq!(
+            state.state.check_staking_cap(501),
+            Err(CommonError::CapReached(Some(Mismatch::new(1_500, 1_501))).into())
+        );
+
+        // the sentinel is a cap like any other for the program check
+        state.state.staking_sol_cap = StakingCap::UNLIMITED_SENTINEL;
+        assert_eq!(state.state.staking_cap(), StakingCap::Unlimited);
+        assert_eq!(state.state.staking_cap_headroom(), None);
+        state.state.check_staking_cap(u64::MAX - 1_000).unwrap();
+        assert_eq!(
+            state.state.check_staking_cap(u64::MAX - 999),
+            Err(ProgramError::InvalidArgument)
+        );
+        assert_eq!(
+            StakingCap::from_raw(StakingCap::Unlimited.to_raw()),
+            StakingCap::Unlimited
+        );
+    }
+
+    #[test]
+    fn test_emergency_cooling_down_status() {
+        let mut state = test_state();
+        assert_eq!(state.state.emergency_cooling_down_status(0), Ok(None));
+
+        state.state.validator_system.total_active_balance = 3_000;
+        state.state.emergency_cooling_down = 1_000;
+        state.state.circulating_ticket_balance = 1_500;
+        let status = state.state.emergency_cooling_down_status(0).unwrap();
+        assert_eq!(
+            status,
+            Some(EmergencyCoolingDown {
+                lamports: 1_000,
+                share_of_tvl: Ratio {
+                    part: 1_000,
+                    whole: 4_000,
+                },
+                // the tickets need 1_500, the emergency stake covers 1_000 of it
+                covers_unstake: 1_000,
+            })
+        );
+        assert_eq!(status.unwrap().share_of_tvl.basis_points(), 2_500);
+        assert_eq!(status.unwrap().share_of_tvl.to_string(), "25%");
+    }
+
+    #[test]
+    fn test_stake_delta_direction() {
+        let mut state = test_state();
+        assert_eq!(
+            state.state.stake_delta_direction(0),
+            Ok(StakeDelta::Neutral)
+        );
+        assert_eq!(
+            state.state.stake_delta_direction(1_000),
+            Ok(StakeDelta::Stake(1_000))
+        );
+
+        state.state.circulating_ticket_balance = 500;
+        assert_eq!(
+            state.state.stake_delta_direction(0),
+            Ok(StakeDelta::Unstake(500))
+        );
+        // the emergency unstake returning to the reserve covers part of the tickets
+        state.state.emergency_cooling_down = 200;
+        assert_eq!(
+            state.state.stake_delta_direction(0),
+            Ok(StakeDelta::Unstake(300))
+        );
+        // but is never staked again
+        state.state.emergency_cooling_down = 800;
+        assert_eq!(
+            state.state.stake_delta_direction(0),
+            Ok(StakeDelta::Neutral)
+        );
+        assert_eq!(
+            state.state.stake_delta_direction(1_000),
+            Ok(StakeDelta::Stake(500))
+        );
+    }
+
+    #[test]
+    fn test_system_summaries() {
+        let mut state = test_state();
+        state.state.validator_system.validator_list.count = 2;
+        state.state.validator_system.total_validator_score = 30;
+        state.state.validator_system.total_active_balance = 5_000;
+        state.state.validator_system.auto_add_validator_enabled = 1;
+        state.state.stake_system.stake_list.count = 3;
+        state.state.stake_system.delayed_unstake_cooling_down = 100;
+        state.state.stake_system.min_stake = 1_000;
+        state.state.emergency_cooling_down = 50;
+
+        assert_eq!(state.state.validators_len(), 2);
+        assert_eq!(state.state.stakes_len(), 3);
+        assert_eq!(
+            ValidatorSystemSummary::from(&state.state),
+            ValidatorSystemSummary {
+                validators_len: 2,
+                total_validator_score: 30,
+                total_active_balance: 5_000,
+                auto_add_validator_enabled: true,
+                manager_authority: state.state.validator_system.manager_authority,
+            }
+        );
+        assert_eq!(
+            StakeSystemSummary::from(&state.state),
+            StakeSystemSummary {
+                stakes_len: 3,
+                delayed_unstake_cooling_down: 100,
+                emergency_cooling_down: 50,
+                last_stake_delta_epoch: 0,
+                slots_for_stake_delta: 0,
+                min_stake: 1_000,
+                extra_stake_delta_runs: 0,
+            }
+        );
+    }
+}
@@ -0,0 +1,169 @@
+#![cfg(feature = "marinade-finance-compat")]
+//! The migration steps of compat::marinade_finance, one test each
+
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use crate::common::test_state;
+    use borsh::{BorshDeserialize, BorshSerialize};
+    use marinade_sdk::{
+        instructions::{
+            add_liquidity::AddLiquidityAccounts, claim::ClaimAccounts, deposit::DepositAccounts,
+            liquid_unstake::LiquidUnstakeAccounts, order_unstake::OrderUnstakeAccounts,
+            remove_liquidity::RemoveLiquidityAccounts,
+        },
+        located::KeyedAccount,
+        state::marinade::{Marinade, MarinadeHelpers},
+    };
+    use micro_anchor::{InstructionBuilder, ToAccountMetas};
+    use solana_program::{instruction::Instruction, pubkey::Pubkey};
+
+    fn deposit_accounts() -> marinade_finance::accounts::Deposit {
+        marinade_finance::accounts::Deposit {
+            state: Pubkey::new_unique(),
+            msol_mint: Pubkey::new_unique(),
+            liq_pool_sol_leg_pda: Pubkey::new_unique(),
+            liq_pool_msol_leg: Pubkey::new_unique(),
+            liq_pool_msol_leg_authority: Pubkey::new_unique(),
+            reserve_pda: Pubkey::new_unique(),
+            transfer_from: Pubkey::new_unique(),
+            mint_to: Pubkey::new_unique(),
+            msol_mint_authority: Pubkey::new_unique(),
+            system_program: Pubkey::new_unique(),
+            token_program: Pubkey::new_unique(),
+        }
+    }
+
+    fn liquid_unstake_accounts() -> marinade_finance::accounts::LiquidUnstake {
+        marinade_finance::accounts::LiquidUnstake {
+            state: Pubkey::new_unique(),
+            msol_mint: Pubkey::new_unique(),
+            liq_pool_sol_leg_pda: Pubkey::new_unique(),
+            liq_pool_msol_leg: Pubkey::new_unique(),
+            treasury_msol_account: Pubkey::new_unique(),
+            get_msol_from: Pubkey::new_unique(),
+            get_msol_from_authority: Pubkey::new_unique(),
+            transfer_sol_to: Pubkey::new_unique(),
+            system_program: Pubkey::new_unique(),
+            token_program: Pubkey::new_unique(),
+        }
+    }
+
+    fn order_unstake_accounts() -> marinade_finance::accounts::OrderUnstake {
+        marinade_finance::accounts::OrderUnstake {
+            state: Pubkey::new_unique(),
+            msol_mint: Pubkey::new_unique(),
+            burn_msol_from: Pubkey::new_unique(),
+            burn_msol_authority: Pubkey::new_unique(),
+            new_ticket_account: Pubkey::new_unique(),
+            clock: Pubkey::new_unique(),
+            rent: Pubkey::new_unique(),
+            token_program: Pubkey::new_unique(),
+        }
+    }
+
+    fn claim_accounts() -> marinade_finance::accounts::Claim {
+        marinade_finance::accounts::Claim {
+            state: Pubkey::new_unique(),
+            reserve_pda: Pubkey::new_unique(),
+            ticket_account: Pubkey::new_unique(),
+            transfer_sol_to: Pubkey::new_unique(),
+            clock: Pubkey::new_unique(),
+            system_program: Pubkey::new_unique(),
+        }
+    }
+
+    fn add_liquidity_accounts() -> marinade_finance::accounts::AddLiquidity {
+        marinade_finance::accounts::AddLiquidity {
+            state: Pubkey::new_unique(),
+            lp_mint: Pubkey::new_unique(),
+            lp_mint_authority: Pubkey::new_unique(),
+            liq_pool_msol_leg: Pubkey::new_unique(),
+            liq_pool_sol_leg_pda: Pubkey::new_unique(),
+            transfer_from: Pubkey::new_unique(),
+            mint_to: Pubkey::new_unique(),
+            system_program: Pubkey::new_unique(),
+            token_program: Pubkey::new_unique(),
+        }
+    }
+
+    fn remove_liquidity_accounts() -> marinade_finance::accounts::RemoveLiquidity {
+        marinade_finance::accounts::RemoveLiquidity {
+            state: Pubkey::new_unique(),
+            lp_mint: Pubkey::new_unique(),
+            burn_from: Pubkey::new_unique(),
+            burn_from_authority: Pubkey::new_unique(),
+            transfer_sol_to: Pubkey::new_unique(),
+            transfer_msol_to: Pubkey::new_unique(),
+            liq_pool_sol_leg_pda: Pubkey::new_unique(),
+            liq_pool_msol_leg: Pubkey::new_unique(),
+            liq_pool_msol_leg_authority: Pubkey::new_unique(),
+            system_program: Pubkey::new_unique(),
+            token_program: Pubkey::new_unique(),
+        }
+    }
+
+    /// The converted instruction against the one the program crate builds through anchor
+    fn assert_same_instruction<A, F, D>(accounts: F, data: D)
+    where
+        A: ToAccountMetas,
+        F: anchor_lang::ToAccountMetas + Into<A>,
+        D: anchor_lang::InstructionData + Into<A::Data>,
+    {
+        let expected_accounts = anchor_lang::ToAccountMetas::to_account_metas(&accounts, None);
+        let expected_data = anchor_lang::InstructionData::data(&data);
+        let instruction: Instruction = (&InstructionBuilder {
+            accounts: accounts.into(),
+            data: data.into(),
+        })
+            .into();
+        assert_eq!(instruction.program_id, marinade_finance::ID);
+        assert_eq!(instruction.accounts, expected_accounts);
+        assert_eq!(instruction.data, expected_data);
+    }
+
+    #[test]
+    fn test_step_1_state_converts() {
+        let test = test_state();
+        let state =
+            marinade_finance::State::try_from_slice(&test.state.try_to_vec().unwrap()).unwrap();
+        let marinade = Marinade::try_from(&state).unwrap();
+        assert_eq!(
+            marinade.try_to_vec().unwrap(),
+            test.state.try_to_vec().unwrap()
+        );
+        let keyed = KeyedAccount::new(test.key, marinade);
+        assert_eq!(
+            keyed.try_reserve_address().unwrap(),
+            test.try_reserve_address().unwrap()
+        );
+    }
+
+    #[test]
+    fn test_step_2_3_instructions_keep_accounts_and_data() {
+        use marinade_finance::instruction;
+
+        assert_same_instruction::<DepositAccounts, _, _>(
+            deposit_accounts(),
+            instruction::Deposit { lamports: 42 },
+        );
+        assert_same_instruction::<LiquidUnstakeAccounts, _, _>(
+            liquid_unstake_accounts(),
+            instruction::LiquidUnstake { msol_amount: 43 },
+        );
+        assert_same_instruction::<OrderUnstakeAccounts, _, _>(
+            order_unstake_accounts(),
+            instruction::OrderUnstake { msol_amount: 44 },
+        );
+        assert_same_instruction::<ClaimAccounts, _, _>(claim_accounts(), instruction::Claim {});
+        assert_same_instruction::<AddLiquidityAccounts, _, _>(
+            add_liquidity_accounts(),
+            instruction::AddLiquidity { lamports: 45 },
+        );
+        assert_same_instruction::<RemoveLiquidityAccounts, _, _>(
+            remove_liquidity_accounts(),
+            instruction::RemoveLiquidity { tokens: 46 },
+        );
+    }
+}
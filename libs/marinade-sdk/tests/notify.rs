@@ -0,0 +1,65 @@
+#![cfg(feature = "notify")]
+
+#[cfg(test)]
+mod tests {
+    use marinade_sdk::{
+        alerts::{AlertEvent, AlertRule},
+        notify::{AlertSender, DiscordNotifier, SlackNotifier, WebhookNotifier},
+    };
+    use serde_json::json;
+
+    fn event(message: &str) -> AlertEvent {
+        AlertEvent {
+            name: "reserve".to_string(),
+            rule: AlertRule::ReserveBelow(1_000),
+            observed: 500,
+            slot: 1_000,
+            message: message.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_webhook_payload() {
+        let notifier = WebhookNotifier::new("http://localhost".to_string());
+        assert_eq!(
+            notifier.payload(&[event("Reserve balance 500 below 1000")]),
+            json!({
+                "alerts": [{
+                    "name": "reserve",
+                    "rule": "reserve_below(1000)",
+                    "observed": "500",
+                    "slot": "1000",
+                    "message": "Reserve balance 500 below 1000",
+                }]
+            })
+        );
+    }
+
+    #[test]
+    fn test_chat_payloads() {
+        let events = [event("low"), event("still low")];
+        let slack = SlackNotifier::new("http://localhost".to_string());
+        assert_eq!(
+            slack.payload(&events),
+            json!({
+                "text": ":rotating_light: Marinade alerts\n[reserve] slot 1000: low\n[reserve] slot 1000: still low"
+            })
+        );
+
+        let discord = DiscordNotifier::new("http://localhost".to_string());
+        let long = [event(&"é".repeat(2_000))];
+        let content = discord.payload(&long)["content"]
+            .as_str()
+            .unwrap()
+            .to_string();
+        assert!(content.len() <= 2_000);
+        assert!(content.ends_with("..."));
+    }
+
+    #[test]
+    fn test_nothing_sent_for_no_events() {
+        // an unreachable url, send must return before any request
+        let notifier = WebhookNotifier::new("http://127.0.0.1:1".to_string());
+        notifier.send(&[]).unwrap();
+    }
+}
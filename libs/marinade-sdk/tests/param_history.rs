@@ -0,0 +1,152 @@
+#![cfg(feature = "history")]
+
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use crate::common::test_state;
+    use marinade_sdk::{
+        history::{ClassifiedTransaction, HistoryTransaction},
+        instructions::{
+            change_authority::ChangeAuthorityData, config_marinade::ConfigMarinadeData,
+        },
+        param_history::{param_history, ParamChange},
+        program::MarinadeProgram,
+        state::marinade::MarinadeHelpers,
+    };
+    use solana_program::{instruction::Instruction, message::Message, pubkey::Pubkey};
+    use solana_sdk::signature::Signature;
+
+    fn transaction(slot: u64, instruction: Instruction) -> ClassifiedTransaction {
+        let message = Message::new(&[instruction], Some(&Pubkey::new_unique()));
+        ClassifiedTransaction {
+            signature: Signature::new_unique(),
+            slot,
+            block_time: None,
+            failed: false,
+            instructions: vec![],
+            transaction: HistoryTransaction {
+                account_keys: message.account_keys,
+                instructions: message.instructions,
+                inner_instructions: vec![],
+                token_balances: vec![],
+            },
+        }
+    }
+
+    fn change(field: &'static str, old: Option<&str>, new: &str) -> ParamChange {
+        ParamChange {
+            field,
+            old: old.map(str::to_string),
+            new: new.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_param_history() {
+        let state = test_state();
+        let new_admin = Pubkey::new_unique();
+        let history = vec![
+            transaction(
+                10,
+                state
+                    .config_marinade_instruction(ConfigMarinadeData::default().with_min_deposit(5)),
+            ),
+            transaction(
+                20,
+                state.config_marinade_instruction(
+                    ConfigMarinadeData::default()
+                        .with_min_deposit(7)
+                        .with_min_withdraw(0),
+                ),
+            ),
+            transaction(
+                30,
+                state.change_authority_instruction(
+                    ChangeAuthorityData::default().with_admin(new_admin),
+                ),
+            ),
+        ];
+
+        // without snapshots only the values set by the history are known
+        let entries = param_history(&MarinadeProgram::default(), &history, &state.key, &[], ..);
+        assert_eq!(entries.len(), 3);
+        assert_eq!(entries[0].instruction, "config_marinade");
+        assert_eq!(entries[0].changes, vec![change("min_deposit", None, "5")]);
+        assert_eq!(
+            entries[1].changes,
+            vec![
+                change("min_deposit", Some("5"), "7"),
+                change("min_withdraw", None, "0"),
+            ]
+        );
+
+        let snapshots = [(5, state.state.clone())];
+        let entries = param_history(
+            &MarinadeProgram::default(),
+            &history,
+            &state.key,
+            &snapshots,
+            15..,
+        );
+        assert_eq!(entries.len(), 2);
+        assert_eq!(
+            entries[0].changes,
+            vec![
+                change("min_deposit", Some("5"), "7"),
+                change("min_withdraw", Some("0"), "0"),
+            ]
+        );
+        assert!(entries[0].changes[1].is_noop());
+        assert_eq!(entries[1].instruction, "change_authority");
+        assert_eq!(
+            entries[1].changes,
+            vec![change(
+                "admin",
+                Some(&state.state.admin_authority.to_string()),
+                &new_admin.to_string(),
+            )]
+        );
+
+        // instructions of other states are skipped
+        assert!(param_history(
+            &MarinadeProgram::default(),
+            &history,
+            &Pubkey::new_unique(),
+            &snapshots,
+            ..
+        )
+        .is_empty());
+    }
+
+    #[test]
+    fn test_param_history_of_cpi() {
+        let state = test_state();
+        let governance = Pubkey::new_unique();
+        let execute = Instruction::new_with_bytes(governance, &[1], vec![]);
+        let config =
+            state.config_marinade_instruction(ConfigMarinadeData::default().with_min_deposit(9));
+        // the governance program invokes config_marinade
+        let message = Message::new(&[execute, config], Some(&Pubkey::new_unique()));
+        let mut instructions = message.instructions;
+        let invoked = instructions.pop().unwrap();
+        let history = vec![ClassifiedTransaction {
+            signature: Signature::new_unique(),
+            slot: 10,
+            block_time: None,
+            failed: false,
+            instructions: vec![],
+            transaction: HistoryTransaction {
+                account_keys: message.account_keys,
+                instructions,
+                inner_instructions: vec![(0, vec![invoked])],
+                token_balances: vec![],
+            },
+        }];
+
+        let entries = param_history(&MarinadeProgram::default(), &history, &state.key, &[], ..);
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].instruction, "config_marinade");
+        assert_eq!(entries[0].changes, vec![change("min_deposit", None, "9")]);
+    }
+}
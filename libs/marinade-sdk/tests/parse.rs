@@ -0,0 +1,78 @@
+#![cfg(feature = "parse")]
+
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use crate::common::test_state;
+    use marinade_sdk::{
+        decode::MarinadeInstruction,
+        instructions::{deposit::DepositData, order_unstake::OrderUnstakeData},
+        parse::parse_transaction,
+        program::MarinadeProgram,
+        state::marinade::MarinadeHelpers,
+    };
+    use solana_program::{message::Message, pubkey::Pubkey, system_instruction};
+    use solana_sdk::transaction::{Transaction, VersionedTransaction};
+
+    #[test]
+    fn test_top_level_instructions() {
+        let state = test_state();
+        let user = Pubkey::new_unique();
+        let instructions = [
+            system_instruction::transfer(&user, &Pubkey::new_unique(), 1),
+            state
+                .deposit(DepositData::new(42), user, Pubkey::new_unique())
+                .unwrap(),
+            state.order_unstake(
+                OrderUnstakeData::new(7),
+                Pubkey::new_unique(),
+                user,
+                Pubkey::new_unique(),
+            ),
+        ];
+        let transaction = VersionedTransaction::from(Transaction::new_unsigned(Message::new(
+            &instructions,
+            Some(&user),
+        )));
+        let parsed = parse_transaction(&MarinadeProgram::default(), &transaction, None).unwrap();
+        assert_eq!(parsed.len(), 2);
+        assert_eq!(parsed[0].instruction_index, 1);
+        assert_eq!(parsed[0].inner_index, None);
+        assert!(matches!(
+            parsed[0].instruction,
+            Ok(MarinadeInstruction::Deposit { data, .. }) if data == DepositData::new(42)
+        ));
+        assert_eq!(parsed[1].instruction_index, 2);
+        assert_eq!(
+            parsed[1].instruction.as_ref().unwrap().name(),
+            "order_unstake"
+        );
+    }
+
+    #[test]
+    fn test_fork_instructions() {
+        let fork = MarinadeProgram::new(Pubkey::new_unique());
+        let state = fork.locate(test_state());
+        let user = Pubkey::new_unique();
+        let instruction = state.order_unstake(
+            OrderUnstakeData::new(7),
+            Pubkey::new_unique(),
+            user,
+            Pubkey::new_unique(),
+        );
+        let transaction = VersionedTransaction::from(Transaction::new_unsigned(Message::new(
+            &[instruction],
+            Some(&user),
+        )));
+        assert_eq!(
+            parse_transaction(&fork, &transaction, None).unwrap().len(),
+            1
+        );
+        assert!(
+            parse_transaction(&MarinadeProgram::default(), &transaction, None)
+                .unwrap()
+                .is_empty()
+        );
+    }
+}
@@ -0,0 +1,38 @@
+#[cfg(test)]
+mod tests {
+    use marinade_sdk::{
+        pda_cache::PdaCache,
+        state::{stake_system::StakeSystem, validator_system::ValidatorRecord},
+    };
+    use solana_program::pubkey::Pubkey;
+
+    #[test]
+    fn test_cached_pdas_match_derivation() {
+        let state = Pubkey::new_unique();
+        let vote = Pubkey::new_unique();
+        let mut cache = PdaCache::new(state);
+
+        let validator = cache.validator(&vote);
+        assert_eq!(
+            (validator.duplication_flag, validator.bump),
+            ValidatorRecord::find_duplication_flag(&state, &vote)
+        );
+        assert_eq!(cache.validator(&vote), validator);
+
+        let stake_accounts = cache.stake_accounts();
+        assert_eq!(
+            (
+                stake_accounts.deposit_authority,
+                stake_accounts.deposit_bump
+            ),
+            StakeSystem::find_stake_deposit_authority(&state)
+        );
+        assert_eq!(
+            (
+                stake_accounts.withdraw_authority,
+                stake_accounts.withdraw_bump
+            ),
+            StakeSystem::find_stake_withdraw_authority(&state)
+        );
+    }
+}
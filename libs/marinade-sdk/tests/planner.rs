@@ -0,0 +1,304 @@
+#[cfg(test)]
+mod tests {
+    use marinade_sdk::{
+        planner::{
+            clip_stake_delta_plan, next_stake_reserve, plan_updates, plan_wind_down,
+            simulate_rebalance, DelegatedStake, EpochPosition, RebalanceLimits, StakeDeltaAction,
+            UpdateAction, WindDownAction,
+        },
+        state::{
+            marinade::StakeDelta,
+            stake_system::{StakeDeltaLimits, StakeRecord},
+            validator_system::ValidatorRecord,
+        },
+    };
+    use solana_program::pubkey::Pubkey;
+
+    const SOL: u64 = 1_000_000_000;
+
+    fn validator(score: u32, active_balance: u64) -> ValidatorRecord {
+        ValidatorRecord {
+            validator_account: Pubkey::new_unique(),
+            active_balance,
+            score,
+            ..ValidatorRecord::default()
+        }
+    }
+
+    fn stake(stake_index: u32, validator: &ValidatorRecord, lamports: u64) -> DelegatedStake {
+        DelegatedStake {
+            stake_index,
+            record: StakeRecord {
+                stake_account: Pubkey::new_unique(),
+                last_update_delegated_lamports: lamports,
+                ..StakeRecord::default()
+            },
+            validator_vote: validator.validator_account,
+        }
+    }
+
+    #[test]
+    fn test_wind_down_respects_epoch_budget() {
+        let removed = validator(0, 25 * SOL);
+        let active = validator(100, 50 * SOL);
+        let validators = [active, removed];
+        let stakes = [
+            stake(0, &validators[0], 50 * SOL),
+            stake(1, &validators[1], 20 * SOL),
+            stake(2, &validators[1], 5 * SOL),
+        ];
+
+        let plans = plan_wind_down(&validators, &stakes, SOL, 10 * SOL);
+
+        assert_eq!(plans.len(), 3);
+        for plan in &plans {
+            assert!(plan.total_lamports() <= 10 * SOL);
+        }
+        let total: u64 = plans.iter().map(|plan| plan.total_lamports()).sum();
+        assert_eq!(total, 25 * SOL);
+        assert_eq!(
+            plans[0].actions[0],
+            WindDownAction::PartialUnstake {
+                stake_index: 1,
+                validator_index: 1,
+                lamports: 10 * SOL
+            }
+        );
+        // the active validator is never touched
+        assert!(plans
+            .iter()
+            .flat_map(|plan| plan.actions.iter())
+            .all(|action| !matches!(
+                action,
+                WindDownAction::EmergencyUnstake { stake_index: 0, .. }
+                    | WindDownAction::PartialUnstake { stake_index: 0, .. }
+            )));
+    }
+
+    #[test]
+    fn test_clip_stake_delta_plan() {
+        let mut processed = validator(100, 0);
+        processed.last_stake_delta_epoch = 500;
+        let validators = [validator(100, 0), processed, validator(100, 0)];
+        let limits = StakeDeltaLimits {
+            slots_for_stake_delta: 3000,
+            last_stake_delta_epoch: 500,
+            extra_stake_delta_runs: 0,
+            min_stake: SOL,
+        };
+        let position = EpochPosition {
+            epoch: 500,
+            slot_index: 430_000,
+            slots_in_epoch: 432_000,
+        };
+        let actions = [
+            StakeDeltaAction::Stake {
+                validator_index: 0,
+                lamports: 4 * SOL,
+            },
+            // same validator twice
+            StakeDeltaAction::Stake {
+                validator_index: 0,
+                lamports: 4 * SOL,
+            },
+            // already processed this epoch
+            StakeDeltaAction::Stake {
+                validator_index: 1,
+                lamports: 4 * SOL,
+            },
+            // against the delta direction
+            StakeDeltaAction::Deactivate {
+                stake_index: 0,
+                validator_index: 2,
+                lamports: SOL,
+            },
+            StakeDeltaAction::Stake {
+                validator_index: 2,
+                lamports: 4 * SOL,
+            },
+        ];
+
+        let clipped = clip_stake_delta_plan(
+            &actions,
+            &validators,
+            &limits,
+            StakeDelta::Stake(6 * SOL),
+            &position,
+        );
+        assert_eq!(
+            clipped,
+            vec![
+                StakeDeltaAction::Stake {
+                    validator_index: 0,
+                    lamports: 4 * SOL
+                },
+                StakeDeltaAction::Stake {
+                    validator_index: 2,
+                    lamports: 2 * SOL
+                },
+            ]
+        );
+
+        let early = EpochPosition {
+            slot_index: 1000,
+            ..position
+        };
+        assert!(clip_stake_delta_plan(
+            &actions,
+            &validators,
+            &limits,
+            StakeDelta::Stake(6 * SOL),
+            &early
+        )
+        .is_empty());
+    }
+
+    #[test]
+    fn test_clip_stake_delta_plan_extra_runs() {
+        let mut validators = [validator(100, 0), validator(100, 0)];
+        for validator in &mut validators {
+            validator.last_stake_delta_epoch = 500;
+        }
+        let limits = StakeDeltaLimits {
+            slots_for_stake_delta: 3000,
+            last_stake_delta_epoch: 500,
+            extra_stake_delta_runs: 1,
+            min_stake: SOL,
+        };
+        let position = EpochPosition {
+            epoch: 500,
+            slot_index: 430_000,
+            slots_in_epoch: 432_000,
+        };
+        let actions = [
+            StakeDeltaAction::Stake {
+                validator_index: 0,
+                lamports: 2 * SOL,
+            },
+            StakeDeltaAction::Stake {
+                validator_index: 1,
+                lamports: 2 * SOL,
+            },
+        ];
+
+        // one extra run admits one of the processed validators only
+        let clipped = clip_stake_delta_plan(
+            &actions,
+            &validators,
+            &limits,
+            StakeDelta::Stake(10 * SOL),
+            &position,
+        );
+        assert_eq!(clipped, vec![actions[0]]);
+    }
+
+    #[test]
+    fn test_next_stake_reserve() {
+        // targets with 6 SOL staked: 3, 3 and 0 SOL
+        let mut validators = [validator(100, 2 * SOL), validator(100, 0), validator(0, 0)];
+        let limits = StakeDeltaLimits {
+            slots_for_stake_delta: 3000,
+            last_stake_delta_epoch: 500,
+            extra_stake_delta_runs: 0,
+            min_stake: SOL,
+        };
+        let position = EpochPosition {
+            epoch: 500,
+            slot_index: 430_000,
+            slots_in_epoch: 432_000,
+        };
+        let stake = StakeDelta::Stake(4 * SOL);
+        assert_eq!(
+            next_stake_reserve(&validators, &limits, stake, &position),
+            Some(StakeDeltaAction::Stake {
+                validator_index: 1,
+                lamports: 4 * SOL,
+            })
+        );
+
+        // the furthest below target was already processed this epoch
+        validators[1].last_stake_delta_epoch = 500;
+        assert_eq!(
+            next_stake_reserve(&validators, &limits, stake, &position),
+            Some(StakeDeltaAction::Stake {
+                validator_index: 0,
+                lamports: 4 * SOL,
+            })
+        );
+
+        let closed = EpochPosition {
+            slot_index: 100_000,
+            ..position
+        };
+        assert_eq!(
+            next_stake_reserve(&validators, &limits, stake, &closed),
+            None
+        );
+        assert_eq!(
+            next_stake_reserve(&validators, &limits, StakeDelta::Unstake(SOL), &position),
+            None
+        );
+        assert_eq!(
+            next_stake_reserve(&validators, &limits, StakeDelta::Stake(SOL / 2), &position),
+            None
+        );
+    }
+
+    #[test]
+    fn test_simulate_rebalance() {
+        let validators = [validator(100, 80 * SOL), validator(0, 20 * SOL)];
+        let new_scores = [(validators[1].validator_account, 100)];
+        let limits = RebalanceLimits {
+            max_unstake_per_epoch: 10 * SOL,
+            min_stake: SOL,
+        };
+
+        let schedule = simulate_rebalance(&validators, &new_scores, 10, &limits);
+
+        // unstaked lamports are staked again one epoch later
+        assert_eq!(schedule.len(), 4);
+        assert_eq!(schedule[0].movements.len(), 1);
+        assert_eq!(schedule[0].cooling_down, 10 * SOL);
+        assert_eq!(schedule[2].remaining_excess, 0);
+        let last = &schedule[3];
+        assert_eq!(last.cooling_down, 0);
+        assert_eq!(last.movements[0].validator_index, 1);
+        assert_eq!(last.movements[0].balance_after, 50 * SOL);
+
+        let capped = simulate_rebalance(&validators, &new_scores, 2, &limits);
+        assert_eq!(capped.len(), 2);
+    }
+
+    #[test]
+    fn test_plan_updates_removes_deactivated_last() {
+        let validators = [validator(100, 10 * SOL), validator(100, 10 * SOL)];
+        let mut stakes = vec![
+            stake(0, &validators[0], SOL),
+            stake(1, &validators[1], SOL),
+            stake(2, &validators[0], SOL),
+            stake(3, &validators[1], SOL),
+            stake(4, &validator(100, SOL), SOL),
+        ];
+        for stake in &mut stakes {
+            stake.record.last_update_epoch = 9;
+        }
+        stakes[1].record.last_update_epoch = 10;
+
+        let plan = plan_updates(&validators, &stakes, 10, |stake| {
+            stake.stake_index == 0 || stake.stake_index == 2
+        });
+
+        // stake 4 is delegated to an unlisted validator
+        assert_eq!(
+            plan,
+            vec![
+                UpdateAction::Active {
+                    stake_index: 3,
+                    validator_index: 1
+                },
+                UpdateAction::Deactivated { stake_index: 2 },
+                UpdateAction::Deactivated { stake_index: 0 },
+            ]
+        );
+    }
+}
@@ -0,0 +1,76 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use crate::common::test_state;
+    use marinade_sdk::{
+        error::CommonError,
+        portfolio::{aggregate, Position, WalletHoldings},
+        state::delayed_unstake_ticket::DelayedUnstakeTicket,
+    };
+    use solana_program::pubkey::Pubkey;
+
+    fn ticket(lamports_amount: u64, created_epoch: u64) -> (Pubkey, DelayedUnstakeTicket) {
+        (
+            Pubkey::new_unique(),
+            DelayedUnstakeTicket {
+                state_address: Pubkey::new_unique(),
+                beneficiary: Pubkey::new_unique(),
+                lamports_amount,
+                created_epoch,
+            },
+        )
+    }
+
+    #[test]
+    fn test_aggregate() {
+        let state = test_state();
+        let holdings = [
+            WalletHoldings {
+                wallet: Pubkey::new_unique(),
+                msol_amount: 1_000,
+                tickets: vec![ticket(300, 20), ticket(200, 10)],
+            },
+            WalletHoldings {
+                wallet: Pubkey::new_unique(),
+                msol_amount: 500,
+                tickets: vec![ticket(100, u64::MAX)],
+            },
+        ];
+        let portfolio = aggregate(&holdings, &state.state).unwrap();
+        // price 1 without any stake
+        assert_eq!(
+            portfolio.wallets[0].1,
+            Position {
+                msol_amount: 1_000,
+                msol_value: 1_000,
+                ticket_count: 2,
+                ticket_lamports: 500,
+                total_value: 1_500,
+            }
+        );
+        assert_eq!(portfolio.total.total_value, 2_100);
+        assert_eq!(portfolio.total.ticket_count, 3);
+        let due_epochs: Vec<u64> = portfolio
+            .maturity_schedule
+            .iter()
+            .map(|maturity| maturity.due_epoch)
+            .collect();
+        // the due epoch of a ticket created at u64::MAX saturates instead of overflowing
+        assert_eq!(due_epochs, vec![11, 21, u64::MAX]);
+    }
+
+    #[test]
+    fn test_aggregate_overflow() {
+        let state = test_state();
+        let holdings = [WalletHoldings {
+            wallet: Pubkey::new_unique(),
+            msol_amount: 0,
+            tickets: vec![ticket(u64::MAX, 10), ticket(1, 10)],
+        }];
+        assert!(matches!(
+            aggregate(&holdings, &state.state),
+            Err(CommonError::CalculationFailure)
+        ));
+    }
+}
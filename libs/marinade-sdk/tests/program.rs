@@ -0,0 +1,78 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use crate::common::test_state;
+    use marinade_sdk::{
+        instructions::{deposit::DepositData, order_unstake::OrderUnstakeData},
+        located::Located,
+        program::MarinadeProgram,
+        state::marinade::{Marinade, MarinadeHelpers},
+        ticket::order_unstake_with_seed,
+        ID,
+    };
+    use solana_program::pubkey::Pubkey;
+
+    #[test]
+    fn test_default_program_matches_static_finders() {
+        let state = Pubkey::new_unique();
+        let program = MarinadeProgram::default();
+        assert_eq!(program.program_id, ID);
+        assert_eq!(
+            program.find_reserve_address(&state),
+            Marinade::find_reserve_address(&state)
+        );
+        assert_eq!(test_state().program_id(), ID);
+    }
+
+    #[test]
+    fn test_fork_builders() {
+        let program = MarinadeProgram::new(Pubkey::new_unique());
+        let mut test = test_state();
+        let key = test.key;
+        test.state.reserve_bump_seed = program.find_reserve_address(&key).1;
+        test.state.msol_mint_authority_bump_seed = program.find_msol_mint_authority(&key).1;
+        test.state.liq_pool.sol_leg_bump_seed = program.find_liq_pool_sol_leg_address(&key).1;
+        test.state.liq_pool.msol_leg_authority_bump_seed =
+            program.find_liq_pool_msol_leg_authority(&key).1;
+        let fork = program.locate(test);
+
+        assert_eq!(fork.program_id(), program.program_id);
+        assert_eq!(
+            fork.try_reserve_address().unwrap(),
+            program.find_reserve_address(&key).0
+        );
+        assert_ne!(
+            fork.try_reserve_address().unwrap(),
+            Marinade::find_reserve_address(&key).0
+        );
+
+        let deposit = fork
+            .deposit(DepositData::new(1), Pubkey::default(), Pubkey::default())
+            .unwrap();
+        assert_eq!(deposit.program_id, program.program_id);
+        assert!(deposit
+            .accounts
+            .iter()
+            .any(|meta| meta.pubkey == program.find_reserve_address(&key).0));
+
+        let base = Pubkey::new_unique();
+        let (ticket, instructions) = order_unstake_with_seed(
+            &fork,
+            OrderUnstakeData::new(1),
+            Pubkey::new_unique(),
+            base,
+            base,
+            base,
+            "ticket",
+            1,
+            None,
+        )
+        .unwrap();
+        assert_eq!(
+            ticket,
+            program.ticket_address_with_seed(&base, "ticket").unwrap()
+        );
+        assert_eq!(instructions[1].program_id, program.program_id);
+    }
+}
@@ -0,0 +1,307 @@
+//! Differential tests of the SDK math against the real program.
+//! They need the program binary built from the matching marinade program release at
+//! tests/fixtures/marinade_finance.so, are ignored by default and fail when it is missing:
+//! `cargo test --features test-utils --test program_test -- --ignored`.
+#![cfg(feature = "test-utils")]
+
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use crate::common::{
+        self, mint_account, state_account, system_account, token_account, TestState,
+    };
+    use marinade_sdk::{
+        instructions::{
+            deposit::DepositData, liquid_unstake::LiquidUnstakeData,
+            order_unstake::OrderUnstakeData,
+        },
+        located::Located,
+        quote::liquid_unstake_quote,
+        state::{
+            delayed_unstake_ticket::DelayedUnstakeTicket, fee::Fee, liq_pool::LiqPoolHelpers,
+            marinade::MarinadeHelpers,
+        },
+        ID,
+    };
+    use micro_anchor::AccountDeserialize;
+    use solana_program::{program_pack::Pack, pubkey::Pubkey};
+    use solana_program_test::{BanksClient, ProgramTest};
+    use solana_sdk::{
+        account::Account,
+        rent::Rent,
+        signature::{Keypair, Signer},
+        transaction::Transaction,
+    };
+
+    const PROGRAM_PATH: &str = "tests/fixtures/marinade_finance.so";
+    const SAMPLES: usize = 20;
+    const USER_LAMPORTS: u64 = 1_000_000_000_000_000;
+    const TICKET_LEN: usize = 8 + 32 + 32 + 8 + 8;
+
+    /// Pool parameters drawn for one sample
+    struct Pool {
+        total_staked: u64,
+        msol_supply: u64,
+        liquidity: u64,
+    }
+
+    /// Deterministic pseudo random sequence (no external rng needed)
+    fn samples() -> impl Iterator<Item = (Pool, u64)> {
+        let mut x: u64 = 0x9e37_79b9_7f4a_7c15;
+        let mut next = move || {
+            x ^= x << 13;
+            x ^= x >> 7;
+            x ^= x << 17;
+            x
+        };
+        (0..SAMPLES).map(move |_| {
+            let msol_supply = next() % 10_000_000_000_000_000 + 1_000_000_000;
+            // msol price between 1 and 2
+            let total_staked = msol_supply + next() % msol_supply;
+            let liquidity = next() % 100_000_000_000_000 + 1_000_000_000;
+            let amount = next() % 1_000_000_000_000 + 1_000_000;
+            (
+                Pool {
+                    total_staked,
+                    msol_supply,
+                    liquidity,
+                },
+                amount,
+            )
+        })
+    }
+
+    fn require_program() {
+        assert!(
+            Path::new(PROGRAM_PATH).exists(),
+            "{} not found, build it from the marinade program release",
+            PROGRAM_PATH
+        );
+    }
+
+    fn test_state(pool: &Pool) -> TestState {
+        let mut state = common::test_state();
+        state.state.rent_exempt_for_token_acc =
+            Rent::default().minimum_balance(spl_token::state::Account::LEN);
+        state.state.reward_fee = Fee::from_basis_points(200);
+        let liq_pool = &mut state.state.liq_pool;
+        liq_pool.lp_min_fee = Fee::from_basis_points(30);
+        liq_pool.lp_max_fee = Fee::from_basis_points(300);
+        liq_pool.lp_liquidity_target = 10_000_000_000_000;
+        liq_pool.treasury_cut = Fee::from_basis_points(2500);
+        liq_pool.liquidity_sol_cap = u64::MAX;
+        // everything sits in the reserve so no validator or stake accounts are needed
+        state.state.available_reserve_balance = pool.total_staked;
+        state.state.msol_supply = pool.msol_supply;
+        state.state.min_deposit = 1;
+        state.state.min_withdraw = 1;
+        state.state.staking_sol_cap = u64::MAX;
+        state
+    }
+
+    /// Program test with the state, its PDAs and mints; user holds msol_amount
+    fn program_test(
+        state: &TestState,
+        pool: &Pool,
+        user: &Pubkey,
+        msol_amount: u64,
+    ) -> ProgramTest {
+        let mut program_test = ProgramTest::new("marinade_finance", ID, None);
+        program_test.prefer_bpf(true);
+        let marinade = state.as_ref();
+        let rent_exempt = marinade.rent_exempt_for_token_acc;
+        program_test.add_account(state.key(), state_account(marinade));
+        program_test.add_account(
+            marinade.msol_mint,
+            mint_account(
+                state.try_msol_mint_authority().unwrap(),
+                marinade.msol_supply,
+            ),
+        );
+        program_test.add_account(
+            state.try_reserve_address().unwrap(),
+            system_account(marinade.available_reserve_balance + rent_exempt),
+        );
+        program_test.add_account(
+            state.try_liq_pool_sol_leg_address().unwrap(),
+            system_account(pool.liquidity + rent_exempt),
+        );
+        program_test.add_account(
+            marinade.liq_pool.msol_leg,
+            token_account(
+                marinade.msol_mint,
+                state.try_liq_pool_msol_leg_authority().unwrap(),
+                0,
+            ),
+        );
+        program_test.add_account(
+            marinade.treasury_msol_account,
+            token_account(marinade.msol_mint, Pubkey::new_unique(), 0),
+        );
+        program_test.add_account(*user, system_account(USER_LAMPORTS));
+        program_test.add_account(
+            user_msol_account(user),
+            token_account(marinade.msol_mint, *user, msol_amount),
+        );
+        program_test
+    }
+
+    fn user_msol_account(user: &Pubkey) -> Pubkey {
+        Pubkey::create_with_seed(user, "msol", &spl_token::ID).unwrap()
+    }
+
+    async fn token_balance(banks_client: &mut BanksClient, account: Pubkey) -> u64 {
+        let account = banks_client.get_account(account).await.unwrap().unwrap();
+        spl_token::state::Account::unpack(&account.data)
+            .unwrap()
+            .amount
+    }
+
+    async fn lamports(banks_client: &mut BanksClient, account: Pubkey) -> u64 {
+        banks_client.get_balance(account).await.unwrap()
+    }
+
+    #[tokio::test]
+    #[ignore = "needs tests/fixtures/marinade_finance.so"]
+    async fn test_deposit_matches_program() {
+        require_program();
+        for (pool, lamports) in samples() {
+            let state = test_state(&pool);
+            let user = Keypair::new();
+            let msol_account = user_msol_account(&user.pubkey());
+            let expected = state.as_ref().calc_msol_from_lamports(lamports).unwrap();
+
+            let (mut banks_client, payer, recent_blockhash) =
+                program_test(&state, &pool, &user.pubkey(), 0).start().await;
+            let instruction = state
+                .deposit(DepositData::new(lamports), user.pubkey(), msol_account)
+                .unwrap();
+            let transaction = Transaction::new_signed_with_payer(
+                &[instruction],
+                Some(&payer.pubkey()),
+                &[&payer, &user],
+                recent_blockhash,
+            );
+            banks_client.process_transaction(transaction).await.unwrap();
+
+            assert_eq!(
+                token_balance(&mut banks_client, msol_account).await,
+                expected,
+                "deposit of {} lamports",
+                lamports
+            );
+        }
+    }
+
+    #[tokio::test]
+    #[ignore = "needs tests/fixtures/marinade_finance.so"]
+    async fn test_liquid_unstake_matches_program() {
+        require_program();
+        for (pool, msol_amount) in samples() {
+            let state = test_state(&pool);
+            let quote = liquid_unstake_quote(state.as_ref(), pool.liquidity, msol_amount);
+            let user = Keypair::new();
+            let msol_account = user_msol_account(&user.pubkey());
+            let transfer_sol_to = Pubkey::new_unique();
+
+            let (mut banks_client, payer, recent_blockhash) =
+                program_test(&state, &pool, &user.pubkey(), msol_amount)
+                    .start()
+                    .await;
+            let instruction = state
+                .liquid_unstake(
+                    LiquidUnstakeData::new(msol_amount),
+                    msol_account,
+                    user.pubkey(),
+                    transfer_sol_to,
+                )
+                .unwrap();
+            let transaction = Transaction::new_signed_with_payer(
+                &[instruction],
+                Some(&payer.pubkey()),
+                &[&payer, &user],
+                recent_blockhash,
+            );
+            let result = banks_client.process_transaction(transaction).await;
+            let quote = match quote {
+                Ok(quote) => quote,
+                Err(err) => {
+                    // e.g. insufficient liquidity, the program must refuse it as well
+                    assert!(
+                        result.is_err(),
+                        "liquid unstake of {} mSOL quoted as {:?} but executed",
+                        msol_amount,
+                        err
+                    );
+                    continue;
+                }
+            };
+            result.unwrap();
+
+            assert_eq!(
+                lamports(&mut banks_client, transfer_sol_to).await,
+                quote.lamports,
+                "liquid unstake of {} mSOL",
+                msol_amount
+            );
+            let msol_leg = token_balance(&mut banks_client, state.as_ref().liq_pool.msol_leg).await;
+            let treasury =
+                token_balance(&mut banks_client, state.as_ref().treasury_msol_account).await;
+            assert_eq!(msol_leg + treasury, quote.msol_fee);
+        }
+    }
+
+    #[tokio::test]
+    #[ignore = "needs tests/fixtures/marinade_finance.so"]
+    async fn test_order_unstake_matches_program() {
+        require_program();
+        for (pool, msol_amount) in samples() {
+            let state = test_state(&pool);
+            let expected = state
+                .as_ref()
+                .calc_lamports_from_msol_amount(msol_amount)
+                .unwrap();
+            let user = Keypair::new();
+            let msol_account = user_msol_account(&user.pubkey());
+            let ticket = Pubkey::new_unique();
+
+            let mut program_test = program_test(&state, &pool, &user.pubkey(), msol_amount);
+            program_test.add_account(
+                ticket,
+                Account {
+                    lamports: Rent::default().minimum_balance(TICKET_LEN),
+                    data: vec![0; TICKET_LEN],
+                    owner: ID,
+                    executable: false,
+                    rent_epoch: 0,
+                },
+            );
+            let (mut banks_client, payer, recent_blockhash) = program_test.start().await;
+            let instruction = state.order_unstake(
+                OrderUnstakeData::new(msol_amount),
+                msol_account,
+                user.pubkey(),
+                ticket,
+            );
+            let transaction = Transaction::new_signed_with_payer(
+                &[instruction],
+                Some(&payer.pubkey()),
+                &[&payer, &user],
+                recent_blockhash,
+            );
+            banks_client.process_transaction(transaction).await.unwrap();
+
+            let account = banks_client.get_account(ticket).await.unwrap().unwrap();
+            let ticket =
+                DelayedUnstakeTicket::try_deserialize(&mut account.data.as_slice()).unwrap();
+            assert_eq!(
+                ticket.lamports_amount, expected,
+                "order unstake of {} mSOL",
+                msol_amount
+            );
+        }
+    }
+}
@@ -0,0 +1,84 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use crate::common::test_state;
+    use marinade_sdk::{
+        instructions::config_marinade::ConfigMarinadeData,
+        propose::{filter_already_applied, propose, AdminStateView, ProposalTarget},
+        state::marinade::MarinadeHelpers,
+        ID,
+    };
+    use solana_program::{message::Message, pubkey::Pubkey};
+
+    #[test]
+    fn test_filter_already_applied() {
+        let state = test_state();
+        let noop =
+            state.config_marinade_instruction(ConfigMarinadeData::default().with_min_deposit(0));
+        let change =
+            state.config_marinade_instruction(ConfigMarinadeData::default().with_min_deposit(5));
+        let view = AdminStateView {
+            state: &state.state,
+            validators: &[],
+        };
+
+        let remaining = filter_already_applied(&[noop, change.clone()], &view);
+        assert_eq!(remaining, vec![change]);
+    }
+
+    #[test]
+    fn test_propose_summary() {
+        let state = test_state();
+        let proposal = propose(
+            &state,
+            ConfigMarinadeData::default()
+                .with_min_deposit(5)
+                .with_min_withdraw(0),
+            ProposalTarget::Squads,
+        );
+        assert_eq!(proposal.summary(), "min_deposit: 0 -> 5");
+        assert!(!proposal.payload.is_empty());
+    }
+
+    #[test]
+    fn test_proposal_payloads() {
+        let state = test_state();
+        let config = ConfigMarinadeData::default().with_min_deposit(5);
+
+        let fee_payer = Pubkey::new_unique();
+        let transaction = propose(&state, config, ProposalTarget::Transaction { fee_payer });
+        assert_eq!(
+            transaction.payload,
+            Message::new(&[transaction.instruction.clone()], Some(&fee_payer)).serialize()
+        );
+
+        // program id | account count | accounts | data length | data
+        let governance = propose(&state, config, ProposalTarget::Governance);
+        let instruction = &governance.instruction;
+        let payload = &governance.payload;
+        assert_eq!(&payload[..32], ID.as_ref());
+        assert_eq!(
+            payload[32..36],
+            (instruction.accounts.len() as u32).to_le_bytes()
+        );
+        let accounts_end = 36 + 34 * instruction.accounts.len();
+        assert_eq!(&payload[36..68], instruction.accounts[0].pubkey.as_ref());
+        assert_eq!(
+            payload[accounts_end..accounts_end + 4],
+            (instruction.data.len() as u32).to_le_bytes()
+        );
+        assert_eq!(&payload[accounts_end + 4..], instruction.data.as_slice());
+
+        let squads = propose(&state, config, ProposalTarget::Squads);
+        assert_eq!(squads.payload, governance.payload);
+
+        let noop = propose(
+            &state,
+            ConfigMarinadeData::default(),
+            ProposalTarget::Governance,
+        );
+        assert!(noop.changes.is_empty());
+        assert_eq!(noop.summary(), "No change");
+    }
+}
@@ -0,0 +1,244 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use crate::common::test_state;
+    use borsh::BorshSerialize;
+    use marinade_sdk::{
+        calc::{RoundingPolicy, ON_CHAIN_ROUNDING},
+        error::{CommonError, Mismatch},
+        quote::{
+            liquid_unstake_quote, max_liquid_unstake, order_unstake_quote, quote_deposit,
+            quote_deposit_with_liq_pool, quote_liquid_unstake, quote_max_liquid_unstake,
+            quote_order_unstake, LiqPoolLegs,
+        },
+        state::{
+            fee::{Fee, FeeCents},
+            marinade::{Marinade, MarinadeExtension},
+        },
+    };
+    use solana_program::program_error::ProgramError;
+
+    #[test]
+    fn test_stale_quote() {
+        let mut state = test_state();
+        state.state.msol_supply = 1_000;
+        state.state.available_reserve_balance = 2_000;
+        state.state.staking_sol_cap = u64::MAX;
+        let quote = quote_deposit_with_liq_pool(&state, 100, &LiqPoolLegs::default(), 10).unwrap();
+        assert_eq!(quote.value.msol_amount(), 5);
+        // 10 * 1_000 / 2_000 exactly, rounded the program's way like every quote
+        assert_eq!(quote.rounding, ON_CHAIN_ROUNDING);
+        assert_eq!(quote.rounding, RoundingPolicy::ProtocolFavor);
+
+        assert_eq!(quote.fresh_value(150, 50), Ok(&quote.value));
+        assert!(!quote.is_stale(90, 50));
+        let stale = quote.fresh_value(151, 50).unwrap_err();
+        assert_eq!(stale.age, 51);
+
+        assert!(quote.matches_state(&state));
+        state.state.available_reserve_balance += 1;
+        assert!(!quote.matches_state(&state));
+    }
+
+    #[test]
+    fn test_deposit_buys_from_liq_pool_first() {
+        let mut state = test_state();
+        state.state.msol_supply = 1_000;
+        state.state.available_reserve_balance = 2_000;
+        state.state.staking_sol_cap = u64::MAX;
+
+        let legs = LiqPoolLegs {
+            msol_leg_balance: 20,
+            ..Default::default()
+        };
+        let quote = quote_deposit_with_liq_pool(&state, 100, &legs, 100).unwrap();
+        assert_eq!(quote.slot, 100);
+        assert_eq!(quote.value.msol_from_liq_pool, 20);
+        assert_eq!(quote.value.lamports_to_liq_pool, 40);
+        assert_eq!(quote.value.lamports_to_reserve, 60);
+        assert_eq!(quote.value.msol_minted, 30);
+        assert_eq!(quote.value.msol_amount(), 50);
+
+        // a leg covering the whole deposit takes every lamport
+        let swap_only = quote_deposit(&state.state, &msol_leg(1_000), 100).unwrap();
+        assert_eq!(swap_only.lamports_to_liq_pool, 100);
+        assert_eq!(swap_only.msol_minted, 0);
+        assert_eq!(swap_only.msol_amount(), 50);
+    }
+
+    fn msol_leg(msol_leg_balance: u64) -> LiqPoolLegs {
+        LiqPoolLegs {
+            msol_leg_balance,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_deposit_below_min_deposit() {
+        let mut state = test_state();
+        state.state.msol_supply = 1_000;
+        state.state.available_reserve_balance = 2_000;
+        state.state.staking_sol_cap = u64::MAX;
+        state.state.min_deposit = 100;
+
+        let error = quote_deposit(&state.state, &msol_leg(1_000), 99).unwrap_err();
+        assert_eq!(
+            error,
+            ProgramError::from(CommonError::NumberTooLow(Some(Mismatch::new(100, 99))))
+        );
+        assert!(quote_deposit(&state.state, &msol_leg(1_000), 100).is_ok());
+    }
+
+    #[test]
+    fn test_tiny_deposit_buys_nothing_from_liq_pool() {
+        let mut state = test_state();
+        state.state.msol_supply = 1_000;
+        state.state.available_reserve_balance = 2_000;
+        state.state.staking_sol_cap = u64::MAX;
+
+        // 1 lamport buys 0 mSOL, it is all sent to the reserve rather than to the liq pool
+        let quote = quote_deposit(&state.state, &msol_leg(1_000), 1).unwrap();
+        assert_eq!(quote.msol_from_liq_pool, 0);
+        assert_eq!(quote.lamports_to_liq_pool, 0);
+        assert_eq!(quote.lamports_to_reserve, 1);
+        assert_eq!(quote.msol_minted, 0);
+    }
+
+    #[test]
+    fn test_deposit_staking_cap_applies_to_minted_part() {
+        let mut state = test_state();
+        state.state.msol_supply = 1_000;
+        state.state.available_reserve_balance = 2_000;
+        state.state.staking_sol_cap = state.state.try_total_lamports_under_control().unwrap() + 50;
+
+        assert!(quote_deposit(&state.state, &msol_leg(20), 100).is_err());
+        assert!(quote_deposit(&state.state, &msol_leg(30), 100).is_ok());
+        assert!(quote_deposit(&state.state, &msol_leg(1_000), 100).is_ok());
+    }
+
+    #[test]
+    fn test_liquid_unstake_fee_curve() {
+        let mut state = test_state();
+        state.state.msol_supply = 100_000;
+        state.state.available_reserve_balance = 100_000;
+        let liq_pool = &mut state.state.liq_pool;
+        liq_pool.lp_min_fee = Fee::from_basis_points(30);
+        liq_pool.lp_max_fee = Fee::from_basis_points(300);
+        liq_pool.lp_liquidity_target = 10_000;
+        liq_pool.treasury_cut = Fee::from_basis_points(2500);
+
+        // liquidity left exactly at the target
+        let at_target = quote_liquid_unstake(&state, 7, 10_000, 20_000).unwrap();
+        assert_eq!(at_target.slot, 7);
+        assert_eq!(at_target.value.fee, Fee::from_basis_points(30));
+        assert_eq!(at_target.value.msol_fee, 30);
+        assert_eq!(at_target.value.lamports, 9_970);
+        assert_eq!(at_target.value.treasury_msol_cut, 7);
+
+        // one lamport below the target, the curve rounds in favor of the pool
+        let below = liquid_unstake_quote(&state.state, 20_000, 10_001).unwrap();
+        assert_eq!(below.fee, Fee::from_basis_points(31));
+
+        // draining the whole leg pays the max fee
+        let drain = liquid_unstake_quote(&state.state, 20_000, 20_000).unwrap();
+        assert_eq!(drain.fee, Fee::from_basis_points(300));
+        assert_eq!(drain.lamports, 19_400);
+
+        assert!(matches!(
+            liquid_unstake_quote(&state.state, 20_000, 30_000),
+            Err(CommonError::InsufficientLiquidity)
+        ));
+    }
+
+    #[test]
+    fn test_max_liquid_unstake_under_fee_budget() {
+        let mut state = test_state();
+        state.state.msol_supply = 100_000;
+        state.state.available_reserve_balance = 100_000;
+        let liq_pool = &mut state.state.liq_pool;
+        liq_pool.lp_min_fee = Fee::from_basis_points(30);
+        liq_pool.lp_max_fee = Fee::from_basis_points(300);
+        liq_pool.lp_liquidity_target = 10_000;
+
+        // the min fee holds while the liquidity left stays at the target
+        let max =
+            quote_max_liquid_unstake(&state, 7, 20_000, Fee::from_basis_points(30), 4).unwrap();
+        assert_eq!(max.slot, 7);
+        assert_eq!(max.value.msol_amount, 10_000);
+        let amounts: Vec<u64> = max
+            .value
+            .tranches
+            .iter()
+            .map(|tranche| tranche.msol_amount)
+            .collect();
+        assert_eq!(amounts, vec![2_500, 5_000, 7_500, 10_000]);
+        assert_eq!(
+            max.value.tranches[3].quote,
+            liquid_unstake_quote(&state.state, 20_000, 10_000).unwrap()
+        );
+        assert!(max
+            .value
+            .tranches
+            .windows(2)
+            .all(|pair| pair[0].quote.fee <= pair[1].quote.fee));
+
+        let none = max_liquid_unstake(&state.state, 20_000, Fee::from_basis_points(29), 4).unwrap();
+        assert_eq!(none.msol_amount, 0);
+        assert!(none.tranches.is_empty());
+
+        // at the max fee the SOL leg liquidity is the limit
+        let all = max_liquid_unstake(&state.state, 20_000, Fee::from_basis_points(300), 1).unwrap();
+        assert!(all.tranches[0].quote.lamports <= 20_000);
+        assert!(liquid_unstake_quote(&state.state, 20_000, all.msol_amount + 1).is_err());
+    }
+
+    #[test]
+    fn test_order_unstake_quote() {
+        let state = test_state();
+        let no_fee = quote_order_unstake(&state, 3, 10_000, FeeCents::default()).unwrap();
+        assert_eq!(no_fee.slot, 3);
+        assert_eq!(no_fee.value.lamports, 10_000);
+        assert_eq!(no_fee.value.msol_fee, 0);
+
+        // 0.5% taken on the mSOL before pricing the ticket
+        let fee =
+            order_unstake_quote(&state.state, FeeCents::from_bp_cents(5_000), 10_000).unwrap();
+        assert_eq!(fee.msol_fee, 50);
+        assert_eq!(fee.lamports, 9_950);
+    }
+
+    #[test]
+    fn test_extension_from_account_data() {
+        let extension = MarinadeExtension {
+            delayed_unstake_fee: FeeCents::from_bp_cents(5_000),
+            ..MarinadeExtension::default()
+        };
+        let mut data = vec![0; Marinade::serialized_len()];
+        assert_eq!(
+            MarinadeExtension::from_account_data(&data),
+            MarinadeExtension::default()
+        );
+        data.extend(extension.try_to_vec().unwrap());
+        assert_eq!(MarinadeExtension::from_account_data(&data), extension);
+    }
+
+    #[test]
+    fn test_under_control_overflow() {
+        let mut state = test_state();
+        state.state.emergency_cooling_down = u64::MAX;
+        state.state.stake_system.delayed_unstake_cooling_down = 1;
+        assert!(matches!(
+            state.state.try_total_lamports_under_control(),
+            Err(CommonError::CalculationFailure)
+        ));
+        assert!(matches!(
+            state.state.calc_msol_from_lamports(1),
+            Err(CommonError::CalculationFailure)
+        ));
+
+        state.state.msol_supply = u64::MAX;
+        assert!(state.state.try_on_msol_mint(1).is_err());
+        assert_eq!(state.state.msol_supply, u64::MAX);
+    }
+}
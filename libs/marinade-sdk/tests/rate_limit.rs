@@ -0,0 +1,87 @@
+#![cfg(feature = "rate-limit")]
+
+#[cfg(test)]
+mod tests {
+    use marinade_sdk::rate_limit::EndpointPool;
+    use std::time::Duration;
+
+    const PRIMARY: &str = "http://127.0.0.1:1";
+    const SECONDARY: &str = "http://127.0.0.1:2";
+
+    #[test]
+    fn test_spillover_to_the_secondary_endpoint() {
+        let mut pool = EndpointPool::new()
+            .with_endpoint(PRIMARY, 2, 0)
+            .with_endpoint(SECONDARY, 1, 0);
+        assert_eq!(pool.acquire(2), Some(PRIMARY));
+        assert_eq!(pool.acquire(1), Some(SECONDARY));
+        assert_eq!(pool.acquire(1), None);
+        assert_eq!(pool.acquire_blocking(1), None);
+        assert_eq!(pool.time_until_available(1), Duration::MAX);
+
+        let counters = pool.counters();
+        assert_eq!(counters[0].1.granted, 1);
+        assert_eq!(counters[0].1.consumed, 2);
+        assert_eq!(counters[1].1.spilled_over, 1);
+        assert_eq!(counters[1].1.throttled, 2);
+    }
+
+    #[test]
+    fn test_wait_for_the_refill() {
+        let mut pool = EndpointPool::new().with_endpoint(PRIMARY, 1, 1);
+        assert_eq!(pool.acquire(1), Some(PRIMARY));
+        let wait = pool.time_until_available(1);
+        assert!(wait > Duration::ZERO && wait <= Duration::from_secs(1));
+        assert_eq!(pool.time_until_available(2), Duration::MAX);
+    }
+
+    #[cfg(feature = "rpc")]
+    #[test]
+    fn test_client_reads_go_through_the_rate_limit() {
+        use marinade_sdk::client::{FetchError, ReadClient};
+        use solana_client::rpc_client::RpcClient;
+        use solana_program::pubkey::Pubkey;
+
+        // the primary has no budget, the state is read from the secondary (nothing listens on
+        // either port, the read itself fails)
+        let pool = EndpointPool::new()
+            .with_endpoint(PRIMARY, 0, 0)
+            .with_endpoint(SECONDARY, 2, 0);
+        let mut client = ReadClient::new(RpcClient::new(PRIMARY.to_string()), Pubkey::new_unique())
+            .with_rate_limit(pool);
+        assert!(matches!(client.refresh(), Err(FetchError::Client(_))));
+        let counters = client.rate_limit_counters();
+        assert_eq!(counters[0].0, PRIMARY);
+        assert_eq!(counters[0].1.throttled, 1);
+        assert_eq!(counters[1].0, SECONDARY);
+        assert_eq!(counters[1].1.spilled_over, 1);
+        assert_eq!(counters[1].1.consumed, 2);
+
+        // budget used up and never refilled
+        assert!(matches!(
+            client.refresh(),
+            Err(FetchError::RateLimited(wait)) if wait == Duration::MAX
+        ));
+        assert!(client.health().rpc_error.unwrap().contains("Rate limited"));
+    }
+
+    #[cfg(feature = "async")]
+    #[tokio::test]
+    async fn test_async_client_reports_the_wait() {
+        use marinade_sdk::client::{nonblocking::ReadClient, FetchError};
+        use solana_client::nonblocking::rpc_client::RpcClient;
+        use solana_program::pubkey::Pubkey;
+
+        let pool = EndpointPool::new().with_endpoint(PRIMARY, 2, 1);
+        let mut client = ReadClient::new(RpcClient::new(PRIMARY.to_string()), Pubkey::new_unique())
+            .with_rate_limit(pool);
+        assert!(matches!(client.refresh().await, Err(FetchError::Client(_))));
+        match client.refresh().await {
+            Err(FetchError::RateLimited(wait)) => {
+                assert!(wait > Duration::ZERO && wait <= Duration::from_secs(2))
+            }
+            other => panic!("expected RateLimited, got {:?}", other.map(|_| ())),
+        }
+        assert_eq!(client.rate_limit_counters()[0].1.granted, 1);
+    }
+}
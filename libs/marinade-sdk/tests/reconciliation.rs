@@ -0,0 +1,30 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use crate::common::test_state;
+    use marinade_sdk::{
+        reconciliation::{detect_reserve_donation, reserve_discrepancy},
+        state::{fee::Fee, marinade::Marinade},
+    };
+
+    #[test]
+    fn test_reserve_donation() {
+        let mut state = test_state().state;
+        state.validator_system.total_active_balance = 1_000_000;
+        state.msol_supply = 1_000_000;
+        state.rent_exempt_for_token_acc = 1_000;
+        state.reward_fee = Fee::from_basis_points(1_000);
+
+        assert_eq!(detect_reserve_donation(&state, 1_000).unwrap(), None);
+        assert_eq!(reserve_discrepancy(&state, 500), -500);
+
+        let donation = detect_reserve_donation(&state, 101_000).unwrap().unwrap();
+        assert_eq!(donation.donated_lamports, 100_000);
+        assert_eq!(donation.fee_lamports, 10_000);
+        assert_eq!(donation.price_before, Marinade::PRICE_DENOMINATOR);
+        // 1_100_000 lamports over 1_000_000 + 9_174 fee mSOL
+        assert_eq!(donation.price_after, 4_681_515_799);
+        assert_eq!(donation.price_bump_bps().unwrap(), 900);
+    }
+}
@@ -0,0 +1,65 @@
+#![cfg(feature = "replay")]
+
+#[cfg(test)]
+mod tests {
+    use marinade_sdk::replay::{ExecutionOutcome, ReplayReport};
+    use solana_sdk::{
+        instruction::InstructionError,
+        signature::Signature,
+        transaction::{TransactionError, VersionedTransaction},
+    };
+
+    fn outcome(
+        err: Option<TransactionError>,
+        logs: &[&str],
+        units_consumed: Option<u64>,
+    ) -> ExecutionOutcome {
+        ExecutionOutcome {
+            err,
+            logs: logs.iter().map(|log| log.to_string()).collect(),
+            units_consumed,
+        }
+    }
+
+    fn report(original: ExecutionOutcome, replay: ExecutionOutcome) -> ReplayReport {
+        ReplayReport {
+            signature: Signature::default(),
+            slot: 1,
+            transaction: VersionedTransaction::default(),
+            original,
+            replay,
+        }
+    }
+
+    #[test]
+    fn test_identical_replay() {
+        let logs = ["Program log: Instruction: Deposit", "Program consumed 100"];
+        let same = report(
+            outcome(None, &logs, Some(100)),
+            outcome(None, &logs, Some(100)),
+        );
+        assert!(same.same_result());
+        assert_eq!(same.first_log_divergence(), None);
+        assert_eq!(same.units_delta(), Some(0));
+    }
+
+    #[test]
+    fn test_diverging_replay() {
+        let failed = TransactionError::InstructionError(0, InstructionError::Custom(6007));
+        let diverged = report(
+            outcome(None, &["a", "b", "c"], Some(100)),
+            outcome(Some(failed), &["a", "x"], Some(80)),
+        );
+        assert!(!diverged.same_result());
+        assert_eq!(diverged.first_log_divergence(), Some(1));
+        assert_eq!(diverged.units_delta(), Some(-20));
+
+        // a log prefix diverges where the shorter log ends
+        let truncated = report(
+            outcome(None, &["a", "b"], None),
+            outcome(None, &["a"], Some(80)),
+        );
+        assert_eq!(truncated.first_log_divergence(), Some(1));
+        assert_eq!(truncated.units_delta(), None);
+    }
+}
@@ -0,0 +1,38 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use crate::common::test_state;
+    use marinade_sdk::{
+        located::Located,
+        required_accounts::{addresses, AccountRole, Operation},
+        state::marinade::MarinadeHelpers,
+    };
+    use solana_program::pubkey::Pubkey;
+
+    #[test]
+    fn test_claim_requires_ticket_and_reserve() {
+        let state = test_state();
+        let ticket_account = Pubkey::new_unique();
+        let accounts = Operation::Claim { ticket_account }
+            .required_accounts(&state)
+            .unwrap();
+        let roles: Vec<AccountRole> = accounts.iter().map(|account| account.role).collect();
+        assert_eq!(
+            roles,
+            vec![
+                AccountRole::State,
+                AccountRole::Ticket,
+                AccountRole::Reserve
+            ]
+        );
+        assert_eq!(
+            addresses(&accounts),
+            vec![
+                state.key(),
+                ticket_account,
+                state.try_reserve_address().unwrap()
+            ]
+        );
+    }
+}
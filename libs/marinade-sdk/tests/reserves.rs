@@ -0,0 +1,61 @@
+#![cfg(feature = "reserves")]
+
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use crate::common::test_state;
+    use marinade_sdk::{
+        portfolio::WalletHoldings, reserves::ReserveStatement,
+        state::delayed_unstake_ticket::DelayedUnstakeTicket,
+    };
+    use solana_program::pubkey::Pubkey;
+    use solana_sdk::{signature::Keypair, signer::Signer};
+
+    #[test]
+    fn test_signed_statement() {
+        // no mSOL minted yet, 1 mSOL = 1 SOL
+        let state = test_state();
+        let wallet = Pubkey::new_from_array([1; 32]);
+        let ticket = Pubkey::new_from_array([2; 32]);
+        let holdings = [WalletHoldings {
+            wallet,
+            msol_amount: 2_000,
+            tickets: vec![(
+                ticket,
+                DelayedUnstakeTicket {
+                    state_address: state.key,
+                    beneficiary: wallet,
+                    lamports_amount: 700,
+                    created_epoch: 500,
+                },
+            )],
+        }];
+        let statement = ReserveStatement::new(&state, 1_234, &holdings).unwrap();
+        assert_eq!(statement.total.total_value, 2_700);
+        assert_eq!(
+            statement.canonical_json(),
+            format!(
+                concat!(
+                    r#"{{"msol_price":"4294967296","price_denominator":"4294967296","slot":"1234","#,
+                    r#""state":"{state}","tickets":[{{"due_epoch":"501","lamports_amount":"700","#,
+                    r#""ticket":"{ticket}","wallet":"{wallet}"}}],"total":{{"msol_amount":"2000","#,
+                    r#""msol_value":"2000","ticket_count":"1","ticket_lamports":"700","#,
+                    r#""total_value":"2700"}},"version":1,"wallets":[{{"msol_amount":"2000","#,
+                    r#""msol_value":"2000","ticket_count":"1","ticket_lamports":"700","#,
+                    r#""total_value":"2700","wallet":"{wallet}"}}]}}"#
+                ),
+                state = state.key,
+                ticket = ticket,
+                wallet = wallet
+            )
+        );
+
+        let signer = Keypair::new();
+        let mut signed = statement.sign(&signer).unwrap();
+        assert_eq!(signed.signer, signer.pubkey());
+        assert!(signed.verify());
+        signed.json = signed.json.replace("2700", "2701");
+        assert!(!signed.verify());
+    }
+}
@@ -0,0 +1,78 @@
+#[cfg(test)]
+mod tests {
+    use marinade_sdk::calc::{
+        proportional, proportional_with_rounding, quote_add_liquidity, quote_remove_liquidity,
+        shares_from_value, value_from_shares, RoundingPolicy,
+    };
+
+    /// Deterministic pseudo random sequence (no external rng needed)
+    fn samples() -> impl Iterator<Item = (u64, u64, u64)> {
+        let mut x: u64 = 0x2545_f491_4f6c_dd1d;
+        let mut next = move || {
+            x ^= x << 13;
+            x ^= x >> 7;
+            x ^= x << 17;
+            x
+        };
+        (0..10_000).map(move |_| {
+            let denominator = next() % 1_000_000_000_000_000 + 1;
+            let numerator = next() % denominator + 1; // price <= 1 keeps the result in u64
+            let amount = next() % 1_000_000_000_000_000;
+            (amount, numerator, denominator)
+        })
+    }
+
+    #[test]
+    fn test_protocol_favor_never_overestimates() {
+        for (amount, numerator, denominator) in samples() {
+            let exact = amount as u128 * numerator as u128;
+            let down = proportional(amount, numerator, denominator).unwrap() as u128;
+            let up = proportional_with_rounding(
+                amount,
+                numerator,
+                denominator,
+                RoundingPolicy::UserFavor,
+            )
+            .unwrap() as u128;
+            assert!(down * denominator as u128 <= exact);
+            assert!(up * denominator as u128 >= exact);
+            assert!(up - down <= 1);
+        }
+    }
+
+    #[test]
+    fn test_share_conversions_round_down() {
+        // small <= big, so both conversions stay within u64
+        for (amount, small, big) in samples() {
+            let shares = shares_from_value(amount, big, small).unwrap();
+            assert!(shares as u128 * big as u128 <= amount as u128 * small as u128);
+            let value = value_from_shares(amount, small, big).unwrap();
+            assert!(value as u128 * big as u128 <= amount as u128 * small as u128);
+        }
+    }
+
+    #[test]
+    fn test_liquidity_round_trip_rounds_down() {
+        const SOL: u64 = 1_000_000_000;
+        // empty pool mints 1:1
+        assert_eq!(quote_add_liquidity(5 * SOL, 0, 0, 0).unwrap(), 5 * SOL);
+
+        let (sol_leg, msol_leg, lp_supply) = (70 * SOL, 25 * SOL, 90 * SOL);
+        // mSOL at 1.2 SOL
+        let msol_leg_value = proportional(msol_leg, 6, 5).unwrap();
+        let lamports = 3 * SOL + 7;
+        let tokens = quote_add_liquidity(lamports, sol_leg, msol_leg_value, lp_supply).unwrap();
+        assert_eq!(
+            tokens,
+            proportional(lamports, lp_supply, sol_leg + msol_leg_value).unwrap()
+        );
+
+        let out = quote_remove_liquidity(tokens, sol_leg + lamports, msol_leg, lp_supply + tokens)
+            .unwrap();
+        let out_value = out.sol_amount + proportional(out.msol_amount, 6, 5).unwrap();
+        assert!(out_value <= lamports);
+        assert!(lamports - out_value < 10);
+
+        assert!(quote_remove_liquidity(lp_supply + 1, sol_leg, msol_leg, lp_supply).is_err());
+    }
+}
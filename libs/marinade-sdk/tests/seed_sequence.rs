@@ -0,0 +1,95 @@
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashSet, convert::Infallible};
+
+    use marinade_sdk::{
+        seed_sequence::{SeedSequence, SeedSequenceError},
+        storage::{MemoryStorage, Storage},
+    };
+    use solana_program::{pubkey::Pubkey, stake};
+
+    fn unused(_: &Pubkey) -> Result<bool, Infallible> {
+        Ok(false)
+    }
+
+    #[test]
+    fn test_addresses_are_deterministic() {
+        let base = Pubkey::new_unique();
+        let state = Pubkey::new_unique();
+        let mut seeds = SeedSequence::new(base, state, MemoryStorage::new());
+        let first = seeds.next(400, unused).unwrap();
+        let second = seeds.next(400, unused).unwrap();
+        assert_ne!(first, second);
+        assert_eq!(
+            first.address,
+            Pubkey::create_with_seed(&base, &first.seed, &stake::program::ID).unwrap()
+        );
+        assert_eq!(
+            seeds.issued::<Infallible>(400).unwrap(),
+            vec![first, second]
+        );
+    }
+
+    #[test]
+    fn test_counter_restarts_with_epoch() {
+        let mut seeds = SeedSequence::new(
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            MemoryStorage::new(),
+        );
+        seeds.next(400, unused).unwrap();
+        seeds.next(400, unused).unwrap();
+        assert_eq!(seeds.counter::<Infallible>(400).unwrap(), 2);
+        assert_eq!(
+            seeds.next(401, unused).unwrap(),
+            seeds.address(401, 0).unwrap()
+        );
+        assert_eq!(seeds.counter::<Infallible>(400).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_counter_survives_restart() {
+        let base = Pubkey::new_unique();
+        let state = Pubkey::new_unique();
+        let mut seeds = SeedSequence::new(base, state, MemoryStorage::new());
+        let first = seeds.next(400, unused).unwrap();
+        let mut restarted = SeedSequence::new(base, state, seeds.storage);
+        assert_ne!(restarted.next(400, unused).unwrap(), first);
+    }
+
+    #[test]
+    fn test_skips_used_addresses() {
+        let mut seeds = SeedSequence::new(
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            MemoryStorage::new(),
+        );
+        let used: HashSet<Pubkey> = (0..3)
+            .map(|counter| seeds.address(400, counter).unwrap().address)
+            .collect();
+        let seeded = seeds
+            .next(400, |address| Ok::<_, Infallible>(used.contains(address)))
+            .unwrap();
+        assert_eq!(seeded, seeds.address(400, 3).unwrap());
+
+        seeds.max_collisions = 2;
+        let result = seeds.next(400, |_| Ok::<_, Infallible>(true));
+        assert!(matches!(result, Err(SeedSequenceError::Exhausted(3))));
+    }
+
+    #[test]
+    fn test_exhausted_counts_the_attempts() {
+        let base = Pubkey::new_unique();
+        let state = Pubkey::new_unique();
+        let mut storage = MemoryStorage::new();
+        // epoch 4, counter u64::MAX - 1, the seed still fits MAX_SEED_LEN
+        let position = [4u64.to_le_bytes(), (u64::MAX - 1).to_le_bytes()].concat();
+        storage
+            .store(&format!("seed-sequence-{}-{}", state, base), &position)
+            .unwrap();
+        let mut seeds = SeedSequence::new(base, state, storage);
+        seeds.max_collisions = u64::MAX;
+        let result = seeds.next(4, |_| Ok::<_, Infallible>(true));
+        assert!(matches!(result, Err(SeedSequenceError::Exhausted(2))));
+    }
+}
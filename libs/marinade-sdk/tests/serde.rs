@@ -0,0 +1,64 @@
+#![cfg(feature = "serde")]
+
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use borsh::BorshSerialize;
+    use marinade_sdk::{
+        dto::MarinadeDto,
+        state::{delayed_unstake_ticket::DelayedUnstakeTicket, marinade::Marinade},
+    };
+    use solana_program::pubkey::Pubkey;
+
+    use crate::common::test_state;
+
+    #[test]
+    fn test_state_pubkeys_are_base58_strings() {
+        let state = test_state().state;
+        let json = serde_json::to_value(&state).unwrap();
+        assert_eq!(json["msol_mint"], state.msol_mint.to_string());
+        assert_eq!(
+            json["validator_system"]["validator_list"]["account"],
+            state.validator_system.validator_list.account.to_string()
+        );
+        assert_eq!(json["liq_pool"]["lp_max_fee"]["basis_points"], 0);
+
+        let decoded: Marinade = serde_json::from_value(json).unwrap();
+        assert_eq!(decoded.try_to_vec().unwrap(), state.try_to_vec().unwrap());
+    }
+
+    #[test]
+    fn test_ticket_round_trip() {
+        let beneficiary = Pubkey::new_unique();
+        let json = format!(
+            r#"{{"state_address":"{}","beneficiary":"{}","lamports_amount":5,"created_epoch":7}}"#,
+            Pubkey::default(),
+            beneficiary
+        );
+        let ticket: DelayedUnstakeTicket = serde_json::from_str(&json).unwrap();
+        assert_eq!(ticket.beneficiary, beneficiary);
+        assert_eq!(serde_json::to_string(&ticket).unwrap(), json);
+    }
+
+    #[test]
+    fn test_invalid_pubkey_is_rejected() {
+        let json = r#"{"state_address":"not a key","beneficiary":"11111111111111111111111111111111","lamports_amount":5,"created_epoch":7}"#;
+        assert!(serde_json::from_str::<DelayedUnstakeTicket>(json).is_err());
+    }
+
+    #[test]
+    fn test_dto_u64_are_strings() {
+        let mut state = test_state().state;
+        state.msol_supply = u64::MAX;
+        let dto = MarinadeDto::from(&state);
+        let json = serde_json::to_value(&dto).unwrap();
+        assert_eq!(json["msol_supply"], u64::MAX.to_string());
+        assert_eq!(json["msol_mint"], state.msol_mint.to_string());
+        assert_eq!(json["reserve_bump_seed"], state.reserve_bump_seed);
+        assert_eq!(json["reward_fee"]["basis_points"], 0);
+
+        let decoded: MarinadeDto = serde_json::from_value(json).unwrap();
+        assert_eq!(decoded, dto);
+    }
+}
@@ -0,0 +1,36 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use crate::common::test_state;
+    use marinade_sdk::{
+        instructions::deposit_stake_account::DepositStakeAccountData,
+        state::marinade::MarinadeHelpers,
+    };
+    use solana_program::pubkey::Pubkey;
+
+    #[test]
+    #[allow(deprecated)]
+    fn test_deposit_stake_accounts_shim() {
+        let state = test_state();
+        let data = DepositStakeAccountData::new(3);
+        let keys = [
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+        ];
+
+        let new = state
+            .deposit_stake_account(data, keys[0], keys[1], keys[2], keys[3], keys[4])
+            .unwrap();
+        let old = state
+            .deposit_stake_accounts(data, keys[0], keys[1], keys[2], keys[3], keys[4])
+            .unwrap();
+
+        assert_eq!(new.program_id, old.program_id);
+        assert_eq!(new.accounts, old.accounts);
+        assert_eq!(new.data, old.data);
+    }
+}
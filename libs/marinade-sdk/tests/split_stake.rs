@@ -0,0 +1,130 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use crate::common::test_state;
+    use marinade_sdk::{
+        instructions::{merge_stakes::MergeStakesData, partial_unstake::PartialUnstakeData},
+        split_stake::{SplitStakeManifest, SplitStakeStatus},
+    };
+    use solana_program::pubkey::Pubkey;
+
+    #[test]
+    fn test_split_stake_lifecycle() {
+        let state = test_state();
+        let mut manifest = SplitStakeManifest::default();
+        let source = Pubkey::new_unique();
+        let split_a = Pubkey::new_unique();
+        let split_b = Pubkey::new_unique();
+        for split in [split_a, split_b] {
+            let instruction = manifest
+                .partial_unstake(
+                    &state,
+                    PartialUnstakeData::new(0, 0, 1_000_000_000),
+                    Pubkey::new_unique(),
+                    source,
+                    split,
+                    Pubkey::new_unique(),
+                    100,
+                )
+                .unwrap();
+            assert!(instruction
+                .accounts
+                .iter()
+                .any(|meta| meta.pubkey == split && meta.is_signer));
+        }
+        assert_eq!(manifest.outstanding().count(), 2);
+
+        manifest.reconcile(&[source, split_a, split_b]);
+        assert_eq!(
+            manifest.get(&split_a).unwrap().status,
+            SplitStakeStatus::Created
+        );
+
+        let destination = Pubkey::new_unique();
+        assert!(manifest
+            .merge(&state, MergeStakesData::new(0, 1, 0), destination, split_a)
+            .unwrap()
+            .is_some());
+        // merge in flight, still outstanding
+        assert!(manifest
+            .merge(&state, MergeStakesData::new(0, 1, 0), destination, split_a)
+            .unwrap()
+            .is_none());
+        assert_eq!(
+            manifest.get(&split_a).unwrap().status,
+            SplitStakeStatus::Merging { destination }
+        );
+        assert_eq!(manifest.outstanding().count(), 2);
+        // a merge that did not land can be built again
+        manifest.abort_merge(&split_a);
+        assert_eq!(
+            manifest.get(&split_a).unwrap().status,
+            SplitStakeStatus::Created
+        );
+        assert!(manifest
+            .merge(&state, MergeStakesData::new(0, 1, 0), destination, split_a)
+            .unwrap()
+            .is_some());
+        assert!(manifest.confirm_merge(&split_a));
+        assert!(!manifest.confirm_merge(&split_a));
+        assert_eq!(
+            manifest.get(&split_a).unwrap().status,
+            SplitStakeStatus::Merged { destination }
+        );
+
+        // reconcile sees the merge land: the source left the stake list
+        assert!(manifest
+            .merge(&state, MergeStakesData::new(0, 2, 0), destination, split_b)
+            .unwrap()
+            .is_some());
+        manifest.reconcile(&[source, split_b]);
+        assert_eq!(
+            manifest.get(&split_b).unwrap().status,
+            SplitStakeStatus::Merging { destination }
+        );
+        manifest.reconcile(&[source]);
+        assert_eq!(
+            manifest.get(&split_b).unwrap().status,
+            SplitStakeStatus::Merged { destination }
+        );
+        assert_eq!(manifest.outstanding().count(), 0);
+        assert!(manifest.stale(200, 10).is_empty());
+    }
+
+    #[test]
+    fn test_manifest_survives_save_and_load() {
+        let state = test_state();
+        let mut manifest = SplitStakeManifest::default();
+        let split = Pubkey::new_unique();
+        manifest
+            .partial_unstake(
+                &state,
+                PartialUnstakeData::new(0, 0, 1_000_000_000),
+                Pubkey::new_unique(),
+                Pubkey::new_unique(),
+                split,
+                Pubkey::new_unique(),
+                100,
+            )
+            .unwrap();
+        manifest.reconcile(&[split]);
+        manifest
+            .merge(
+                &state,
+                MergeStakesData::new(0, 1, 0),
+                Pubkey::new_unique(),
+                split,
+            )
+            .unwrap();
+
+        let dir = std::env::temp_dir().join(format!("split-stake-{}", Pubkey::new_unique()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("manifest");
+        assert_eq!(SplitStakeManifest::load(&path).unwrap(), Default::default());
+        manifest.save(&path).unwrap();
+        assert_eq!(SplitStakeManifest::load(&path).unwrap(), manifest);
+        assert!(!path.with_extension("tmp").exists());
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,48 @@
+#[cfg(test)]
+mod tests {
+    use marinade_sdk::{
+        planner::EpochPosition, state::ticket::TicketAccountData, sysvars::SysvarCache,
+    };
+    use solana_program::{clock::Clock, epoch_schedule::EpochSchedule, pubkey::Pubkey};
+
+    fn sysvars(slot: u64, epoch: u64) -> SysvarCache {
+        SysvarCache {
+            clock: Clock {
+                slot,
+                epoch,
+                epoch_start_timestamp: 1_700_000_000,
+                unix_timestamp: 1_700_000_000 + 3_600,
+                ..Clock::default()
+            },
+            epoch_schedule: EpochSchedule::without_warmup(),
+            ..SysvarCache::default()
+        }
+    }
+
+    #[test]
+    fn test_epoch_position() {
+        let sysvars = sysvars(500 * 432_000 + 430_000, 500);
+        assert_eq!(
+            sysvars.epoch_position(),
+            EpochPosition {
+                epoch: 500,
+                slot_index: 430_000,
+                slots_in_epoch: 432_000,
+            }
+        );
+        assert_eq!(sysvars.slots_until_epoch_end(), 2_000);
+    }
+
+    #[test]
+    fn test_ticket_claimable_at_cached_clock() {
+        let sysvars = sysvars(501 * 432_000, 501);
+        let ticket = |created_epoch| TicketAccountData {
+            state_address: Pubkey::new_unique(),
+            beneficiary: Pubkey::new_unique(),
+            lamports_amount: 1,
+            created_epoch,
+        };
+        assert!(sysvars.is_ticket_claimable(&ticket(500)));
+        assert!(!sysvars.is_ticket_claimable(&ticket(501)));
+    }
+}
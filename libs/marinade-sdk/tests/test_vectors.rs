@@ -0,0 +1,50 @@
+#![cfg(feature = "test-utils")]
+
+#[cfg(test)]
+mod tests {
+    use marinade_sdk::test_vectors::test_vectors;
+    use serde_json::Value;
+
+    const PUBLISHED: &str = include_str!("../test_vectors/instructions.json");
+
+    fn hex(bytes: &[u8]) -> String {
+        bytes.iter().map(|b| format!("{:02x}", b)).collect()
+    }
+
+    #[test]
+    fn test_published_vectors_match() {
+        let published: Vec<Value> = serde_json::from_str(PUBLISHED).unwrap();
+        let vectors = test_vectors();
+        assert_eq!(published.len(), vectors.len());
+
+        for (expected, vector) in published.iter().zip(vectors.iter()) {
+            let instruction = &vector.instruction;
+            assert_eq!(expected["name"], vector.name);
+            assert_eq!(
+                expected["program_id"],
+                instruction.program_id.to_string(),
+                "{}",
+                vector.name
+            );
+            assert_eq!(expected["data"], hex(&instruction.data), "{}", vector.name);
+
+            let accounts = expected["accounts"].as_array().unwrap();
+            assert_eq!(
+                accounts.len(),
+                instruction.accounts.len(),
+                "{}",
+                vector.name
+            );
+            for (expected, meta) in accounts.iter().zip(instruction.accounts.iter()) {
+                assert_eq!(
+                    expected["pubkey"],
+                    meta.pubkey.to_string(),
+                    "{}",
+                    vector.name
+                );
+                assert_eq!(expected["is_signer"], meta.is_signer, "{}", vector.name);
+                assert_eq!(expected["is_writable"], meta.is_writable, "{}", vector.name);
+            }
+        }
+    }
+}
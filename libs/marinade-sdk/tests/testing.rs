@@ -0,0 +1,83 @@
+#![cfg(feature = "test-utils")]
+
+#[cfg(test)]
+mod tests {
+    use marinade_sdk::{
+        client::{fetch_account_data_chunked, fetch_program_state, FetchError},
+        program::MarinadeProgram,
+        testing::{program_accounts, test_state, token_account, MockRpc},
+    };
+    use solana_program::pubkey::Pubkey;
+
+    #[test]
+    fn test_program_state_from_mock_rpc() {
+        let mut state = test_state();
+        state.state.msol_supply = 1_000;
+        let rpc = MockRpc::new(program_accounts(&state))
+            .with_slot(42)
+            .into_client();
+
+        let fetched = fetch_program_state(&rpc, &MarinadeProgram::default(), &state.key).unwrap();
+        assert_eq!(fetched.slot, 42);
+        assert_eq!(fetched.state.msol_supply, 1_000);
+        assert!(fetched.validators.is_empty());
+        assert_eq!(fetched.liq_pool_msol_leg_amount, 0);
+
+        assert!(
+            fetch_program_state(&rpc, &MarinadeProgram::default(), &Pubkey::new_unique()).is_err()
+        );
+    }
+
+    #[test]
+    fn test_mock_rpc_serves_updated_accounts() {
+        let state = test_state();
+        let mut mock = MockRpc::new(program_accounts(&state));
+        mock.set_account(
+            state.state.liq_pool.msol_leg,
+            token_account(state.state.msol_mint, Pubkey::new_unique(), 77),
+        );
+        let fetched =
+            fetch_program_state(&mock.into_client(), &MarinadeProgram::default(), &state.key)
+                .unwrap();
+        assert_eq!(fetched.liq_pool_msol_leg_amount, 77);
+    }
+
+    #[test]
+    fn test_truncated_lists_are_fetched_in_chunks_at_the_same_slot() {
+        let mut state = test_state();
+        state.state.stake_system.stake_list.item_size = 57;
+        state.state.stake_system.stake_list.count = 10;
+        let stake_list = state.state.stake_system.stake_list.account;
+        // the stake list is 578 bytes long, the token accounts 165
+        let rpc = MockRpc::new(program_accounts(&state))
+            .with_slot(42)
+            .with_multiple_accounts_data_limit(200)
+            .into_client();
+
+        let fetched = fetch_program_state(&rpc, &MarinadeProgram::default(), &state.key).unwrap();
+        assert_eq!(fetched.slot, 42);
+        assert_eq!(fetched.stakes.len(), 10);
+
+        let data = fetch_account_data_chunked(&rpc, &stake_list, 578, 100, Some(42), None).unwrap();
+        assert_eq!(data.len(), 578);
+        assert!(matches!(
+            fetch_account_data_chunked(&rpc, &stake_list, 578, 100, Some(41), None),
+            Err(FetchError::Integrity(_, "chunk slot"))
+        ));
+        assert!(fetch_account_data_chunked(&rpc, &stake_list, 578, 100, None, None).is_ok());
+    }
+
+    #[test]
+    fn test_mock_rpc_honours_min_context_slot() {
+        let state = test_state();
+        let stake_list = state.state.stake_system.stake_list.account;
+        let rpc = MockRpc::new(program_accounts(&state))
+            .with_slot(42)
+            .into_client();
+        assert!(matches!(
+            fetch_account_data_chunked(&rpc, &stake_list, 8, 8, Some(43), None),
+            Err(FetchError::Client(_))
+        ));
+        assert!(fetch_account_data_chunked(&rpc, &stake_list, 8, 8, Some(42), None).is_ok());
+    }
+}
@@ -0,0 +1,179 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use crate::common::test_state;
+    use marinade_sdk::{
+        error::{CommonError, Mismatch},
+        instructions::order_unstake::OrderUnstakeData,
+        state::{delayed_unstake_ticket::DelayedUnstakeTicket, fee::Fee},
+        ticket::{
+            check_ticket_purchase, claim_checked, order_unstake_with_seed, otc_ticket_sale,
+            plan_claims, value_ticket, ExistingTicketAccount, TicketError, MAX_DISCOUNTED_EPOCHS,
+        },
+        ID,
+    };
+    use marinade_sdk::{located::Located, state::marinade::MarinadeHelpers};
+    use micro_anchor::{legacy_transaction_size, Discriminator, PACKET_DATA_SIZE};
+    use solana_program::{clock::Clock, pubkey::Pubkey};
+
+    #[test]
+    fn test_ticket_created_only_once() {
+        let state = test_state();
+        let user = Pubkey::new_unique();
+        let order = |existing: Option<ExistingTicketAccount>| {
+            order_unstake_with_seed(
+                &state,
+                OrderUnstakeData::new(1),
+                Pubkey::new_unique(),
+                user,
+                user,
+                user,
+                "ticket-1",
+                1_000,
+                existing,
+            )
+        };
+
+        let (ticket, instructions) = order(None).unwrap();
+        assert_eq!(instructions.len(), 2);
+        assert_eq!(instructions[1].accounts[4].pubkey, ticket);
+
+        let mut data = vec![0; DelayedUnstakeTicket::ACCOUNT_LEN];
+        let (_, instructions) = order(Some(ExistingTicketAccount {
+            owner: &ID,
+            data: &data,
+        }))
+        .unwrap();
+        assert_eq!(instructions.len(), 1);
+
+        data[..8].copy_from_slice(&DelayedUnstakeTicket::DISCRIMINATOR);
+        assert!(matches!(
+            order(Some(ExistingTicketAccount {
+                owner: &ID,
+                data: &data,
+            })),
+            Err(TicketError::InUse(_))
+        ));
+    }
+
+    fn ticket(beneficiary: Pubkey) -> DelayedUnstakeTicket {
+        DelayedUnstakeTicket {
+            state_address: Pubkey::new_unique(),
+            beneficiary,
+            lamports_amount: 1_000_000,
+            created_epoch: 10,
+        }
+    }
+
+    #[test]
+    fn test_value_ticket() {
+        let state = test_state();
+        let ticket = ticket(Pubkey::new_unique());
+        let pending = value_ticket(&ticket, &state.state, 10, Fee::from_basis_points(100)).unwrap();
+        assert_eq!(pending.due_epoch, 11);
+        assert_eq!(pending.epochs_to_maturity, 1);
+        assert_eq!(pending.present_lamports, 990_000);
+        // price 1 without any stake
+        assert_eq!(pending.msol_equivalent, 990_000);
+
+        let due = value_ticket(&ticket, &state.state, 12, Fee::from_basis_points(100)).unwrap();
+        assert_eq!(due.epochs_to_maturity, 0);
+        assert_eq!(due.present_lamports, ticket.lamports_amount);
+
+        // a current epoch far before the ticket is not discounted epoch by epoch
+        let future = DelayedUnstakeTicket {
+            created_epoch: 100,
+            ..ticket
+        };
+        assert!(matches!(
+            value_ticket(&future, &state.state, 0, Fee::from_basis_points(100)),
+            Err(CommonError::NumberTooHigh(Some(Mismatch {
+                expected: MAX_DISCOUNTED_EPOCHS,
+                actual: 101,
+            })))
+        ));
+    }
+
+    #[test]
+    fn test_ticket_sale() {
+        let state = test_state();
+        let (seller, buyer) = (Pubkey::new_unique(), Pubkey::new_unique());
+        assert!(matches!(
+            check_ticket_purchase(&ticket(seller), &buyer),
+            Err(TicketError::BeneficiaryNotBuyer { .. })
+        ));
+        check_ticket_purchase(&ticket(buyer), &buyer).unwrap();
+
+        let seller_msol_account = Pubkey::new_unique();
+        let (ticket, instructions) = otc_ticket_sale(
+            &state,
+            1_000,
+            990,
+            seller,
+            seller_msol_account,
+            buyer,
+            "otc-1",
+            1_000,
+        )
+        .unwrap();
+        assert_eq!(instructions.len(), 4);
+        let order_unstake = &instructions[3];
+        assert_eq!(order_unstake.accounts[2].pubkey, seller_msol_account);
+        // burn authority, the beneficiary of the new ticket
+        assert_eq!(order_unstake.accounts[3].pubkey, buyer);
+        assert_eq!(order_unstake.accounts[4].pubkey, ticket);
+    }
+
+    #[test]
+    fn test_plan_claims() {
+        let state = test_state();
+        let beneficiary = Pubkey::new_unique();
+        let ticket = |state_address: Pubkey, beneficiary: Pubkey, created_epoch: u64| {
+            (
+                Pubkey::new_unique(),
+                DelayedUnstakeTicket {
+                    state_address,
+                    beneficiary,
+                    lamports_amount: 1_000,
+                    created_epoch,
+                },
+            )
+        };
+        let mut tickets: Vec<_> = (0..40)
+            .map(|_| ticket(state.key(), beneficiary, 5))
+            .collect();
+        tickets.push(ticket(state.key(), beneficiary, 10));
+        tickets.push(ticket(state.key(), Pubkey::new_unique(), 5));
+        tickets.push(ticket(Pubkey::new_unique(), beneficiary, 5));
+        let clock = Clock {
+            epoch: 10,
+            ..Clock::default()
+        };
+
+        let transactions = plan_claims(&state, &beneficiary, &tickets, &clock).unwrap();
+        assert!(transactions.len() > 1);
+        assert!(transactions
+            .iter()
+            .all(|instructions| legacy_transaction_size(instructions) <= PACKET_DATA_SIZE));
+        let claims: Vec<_> = transactions.into_iter().flatten().collect();
+        assert_eq!(claims.len(), 40);
+        assert_eq!(claims[0], state.claim(tickets[0].0, beneficiary).unwrap());
+    }
+
+    #[test]
+    fn test_claim_checked() {
+        let state = test_state();
+        let (beneficiary, other) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let address = Pubkey::new_unique();
+        let ticket = ticket(beneficiary);
+
+        let claim = claim_checked(&state, address, &ticket, beneficiary).unwrap();
+        assert_eq!(claim, state.claim(address, beneficiary).unwrap());
+
+        assert!(matches!(
+            claim_checked(&state, address, &ticket, other),
+            Err(TicketError::ThirdPartyRecipient { recipient, .. }) if recipient == other
+        ));
+    }
+}
@@ -0,0 +1,51 @@
+#[cfg(test)]
+mod tests {
+    use borsh::BorshSerialize;
+    use marinade_sdk::state::ticket::TicketAccountData;
+    use micro_anchor::{AccountDeserialize, Discriminator};
+    use solana_program::{clock::Clock, pubkey::Pubkey};
+
+    fn ticket(created_epoch: u64) -> TicketAccountData {
+        TicketAccountData {
+            state_address: Pubkey::new_unique(),
+            beneficiary: Pubkey::new_unique(),
+            lamports_amount: 1_000_000_000,
+            created_epoch,
+        }
+    }
+
+    fn clock(epoch: u64, seconds_into_epoch: i64) -> Clock {
+        Clock {
+            epoch,
+            epoch_start_timestamp: 1_700_000_000,
+            unix_timestamp: 1_700_000_000 + seconds_into_epoch,
+            ..Clock::default()
+        }
+    }
+
+    #[test]
+    fn test_deserialize() {
+        let ticket = ticket(400);
+        let mut data = TicketAccountData::DISCRIMINATOR.to_vec();
+        data.extend(ticket.try_to_vec().unwrap());
+        assert_eq!(data.len(), TicketAccountData::ACCOUNT_LEN);
+
+        let decoded = TicketAccountData::try_deserialize(&mut data.as_slice()).unwrap();
+        assert_eq!(decoded.beneficiary, ticket.beneficiary);
+        assert_eq!(decoded.lamports_amount(), 1_000_000_000);
+        assert_eq!(decoded.due_epoch(), 401);
+
+        data[0] ^= 1;
+        assert!(TicketAccountData::try_deserialize(&mut data.as_slice()).is_err());
+    }
+
+    #[test]
+    fn test_is_claimable() {
+        let ticket = ticket(400);
+        assert!(!ticket.is_claimable(&clock(400, 86_400)));
+        assert!(!ticket.is_claimable(&clock(401, 0)));
+        assert!(!ticket.is_claimable(&clock(401, TicketAccountData::EXTRA_WAIT_SECONDS - 1)));
+        assert!(ticket.is_claimable(&clock(401, TicketAccountData::EXTRA_WAIT_SECONDS)));
+        assert!(ticket.is_claimable(&clock(402, 0)));
+    }
+}
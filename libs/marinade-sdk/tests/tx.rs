@@ -0,0 +1,118 @@
+#![cfg(feature = "tx")]
+
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use crate::common::test_state;
+    use marinade_sdk::{
+        ata::ASSOCIATED_TOKEN_PROGRAM_ID,
+        instructions::order_unstake::OrderUnstakeData,
+        state::delayed_unstake_ticket::DelayedUnstakeTicket,
+        state::marinade::MarinadeHelpers,
+        tx::{
+            order_unstake_with_ticket, ticket_rent_exempt_lamports, MarinadeTransactionBuilder,
+            TxError,
+        },
+        ID,
+    };
+    use solana_program::{hash::Hash, rent::Rent, system_instruction, system_program};
+    use solana_sdk::signature::{Keypair, Signer};
+
+    #[test]
+    fn test_chained_flows_and_signers() {
+        let state = test_state();
+        let payer = Keypair::new();
+        let wallet = Keypair::new();
+        let mut builder = MarinadeTransactionBuilder::new(&state, payer.pubkey());
+        builder
+            .deposit(wallet.pubkey(), 1_000, true)
+            .unwrap()
+            .order_unstake(wallet.pubkey(), 500, 1_500_000)
+            .unwrap();
+
+        let instructions = builder.instructions();
+        assert_eq!(instructions.len(), 4);
+        assert_eq!(instructions[0].program_id, ASSOCIATED_TOKEN_PROGRAM_ID);
+        assert_eq!(instructions[0].accounts[0].pubkey, payer.pubkey());
+        assert_eq!(instructions[2].program_id, system_program::ID);
+        assert_eq!(instructions[3].program_id, ID);
+
+        let ticket = builder.keypairs()[0].pubkey();
+        assert_eq!(
+            builder.signers(),
+            vec![payer.pubkey(), wallet.pubkey(), ticket]
+        );
+        assert_eq!(
+            instructions[2].data,
+            system_instruction::create_account(
+                &payer.pubkey(),
+                &ticket,
+                1_500_000,
+                DelayedUnstakeTicket::ACCOUNT_LEN as u64,
+                &ID
+            )
+            .data
+        );
+
+        let legacy = builder
+            .legacy_transaction(&[&payer, &wallet], Hash::default())
+            .unwrap();
+        assert!(legacy.is_signed());
+        assert_eq!(legacy.signatures.len(), 3);
+
+        let versioned = builder
+            .versioned_transaction(&[&wallet, &payer], &[], Hash::default())
+            .unwrap();
+        assert_eq!(versioned.signatures.len(), 3);
+        assert!(versioned.verify_with_results().iter().all(|ok| *ok));
+
+        assert!(matches!(
+            builder.legacy_transaction(&[&payer], Hash::default()),
+            Err(TxError::MissingSigner(key)) if key == wallet.pubkey()
+        ));
+    }
+
+    #[test]
+    fn test_invalid_operation_is_not_added() {
+        let state = test_state();
+        let mut builder = MarinadeTransactionBuilder::new(&state, Keypair::new().pubkey());
+        assert!(builder.liquid_unstake(Keypair::new().pubkey(), 0).is_err());
+        assert!(builder.instructions().is_empty());
+    }
+
+    #[test]
+    fn test_order_unstake_with_ticket() {
+        let state = test_state();
+        let payer = Keypair::new().pubkey();
+        let owner = Keypair::new().pubkey();
+        let msol_account = Keypair::new().pubkey();
+        let rent = Rent::default();
+        let (ticket, instructions) = order_unstake_with_ticket(
+            &state,
+            OrderUnstakeData::new(500),
+            msol_account,
+            owner,
+            payer,
+            &rent,
+        );
+        assert_eq!(
+            instructions,
+            vec![
+                system_instruction::create_account(
+                    &payer,
+                    &ticket.pubkey(),
+                    ticket_rent_exempt_lamports(&rent),
+                    DelayedUnstakeTicket::ACCOUNT_LEN as u64,
+                    &ID
+                ),
+                state.order_unstake(
+                    OrderUnstakeData::new(500),
+                    msol_account,
+                    owner,
+                    ticket.pubkey()
+                ),
+            ]
+        );
+    }
+}
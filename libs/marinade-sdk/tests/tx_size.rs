@@ -0,0 +1,136 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use crate::common::test_state;
+    use marinade_sdk::{
+        instructions::{
+            add_liquidity::AddLiquidityData, change_authority::ChangeAuthorityData,
+            config_lp::ConfigLpData, deposit::DepositData,
+            deposit_stake_account::DepositStakeAccountData, liquid_unstake::LiquidUnstakeData,
+            order_unstake::OrderUnstakeData, remove_liquidity::RemoveLiquidityData,
+        },
+        state::marinade::MarinadeHelpers,
+    };
+    use micro_anchor::{legacy_transaction_size, PACKET_DATA_SIZE};
+    use solana_program::{
+        instruction::{AccountMeta, Instruction},
+        pubkey::Pubkey,
+        system_program,
+    };
+    use solana_sdk::message::Message;
+
+    /// SetComputeUnitLimit + SetComputeUnitPrice
+    fn compute_budget_instructions() -> Vec<Instruction> {
+        let program_id = Pubkey::from_str("ComputeBudget111111111111111111111111111111").unwrap();
+        vec![
+            Instruction::new_with_bytes(program_id, &[2, 0, 0, 0, 0], vec![]),
+            Instruction::new_with_bytes(program_id, &[3, 0, 0, 0, 0, 0, 0, 0, 0], vec![]),
+        ]
+    }
+
+    /// create_associated_token_account_idempotent
+    fn create_ata_instruction(payer: Pubkey, owner: Pubkey, mint: Pubkey) -> Instruction {
+        let program_id = Pubkey::from_str("ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL").unwrap();
+        Instruction::new_with_bytes(
+            program_id,
+            &[1],
+            vec![
+                AccountMeta::new(payer, true),
+                AccountMeta::new(Pubkey::new_unique(), false),
+                AccountMeta::new_readonly(owner, false),
+                AccountMeta::new_readonly(mint, false),
+                AccountMeta::new_readonly(system_program::ID, false),
+                AccountMeta::new_readonly(spl_token::ID, false),
+            ],
+        )
+    }
+
+    #[test]
+    fn test_instructions_fit_legacy_transaction() {
+        let state = test_state();
+        let user = Pubkey::new_unique();
+        let token = Pubkey::new_unique();
+        let instructions = vec![
+            (
+                "deposit",
+                state.deposit(DepositData::new(1), user, token).unwrap(),
+            ),
+            (
+                "deposit_stake_account",
+                state
+                    .deposit_stake_account(
+                        DepositStakeAccountData::new(0),
+                        Pubkey::new_unique(),
+                        user,
+                        token,
+                        Pubkey::new_unique(),
+                        user,
+                    )
+                    .unwrap(),
+            ),
+            (
+                "liquid_unstake",
+                state
+                    .liquid_unstake(LiquidUnstakeData::new(1), token, user, user)
+                    .unwrap(),
+            ),
+            (
+                "add_liquidity",
+                state
+                    .add_liquidity(AddLiquidityData::new(1), user, token)
+                    .unwrap(),
+            ),
+            (
+                "remove_liquidity",
+                state
+                    .remove_liquidity(
+                        RemoveLiquidityData::new(1),
+                        token,
+                        user,
+                        user,
+                        Pubkey::new_unique(),
+                    )
+                    .unwrap(),
+            ),
+            (
+                "order_unstake",
+                state.order_unstake(OrderUnstakeData::new(1), token, user, Pubkey::new_unique()),
+            ),
+            ("claim", state.claim(Pubkey::new_unique(), user).unwrap()),
+            (
+                "config_lp",
+                state.config_lp_instruction(ConfigLpData::default()),
+            ),
+            (
+                "change_authority",
+                state.change_authority_instruction(ChangeAuthorityData::default()),
+            ),
+        ];
+
+        for (name, instruction) in instructions {
+            let mut tx = compute_budget_instructions();
+            tx.push(create_ata_instruction(user, user, state.state.msol_mint));
+            tx.push(instruction);
+            let size = legacy_transaction_size(&tx);
+            // signatures (compact length + 64 bytes each) and the serialized message
+            let message = Message::new(&tx, Some(&user));
+            let signatures = message.header.num_required_signatures as usize;
+            assert_eq!(
+                size,
+                1 + 64 * signatures + message.serialize().len(),
+                "{} size",
+                name
+            );
+            assert!(
+                size <= PACKET_DATA_SIZE,
+                "{} does not fit: {} > {}",
+                name,
+                size,
+                PACKET_DATA_SIZE
+            );
+        }
+    }
+}
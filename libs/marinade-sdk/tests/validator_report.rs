@@ -0,0 +1,51 @@
+#[cfg(test)]
+mod tests {
+    use marinade_sdk::{
+        state::{fee::Fee, validator_system::ValidatorRecord},
+        validator_report::ValidatorReport,
+    };
+    use solana_program::pubkey::Pubkey;
+
+    fn validator(active_balance: u64) -> ValidatorRecord {
+        ValidatorRecord {
+            validator_account: Pubkey::new_unique(),
+            active_balance,
+            ..ValidatorRecord::default()
+        }
+    }
+
+    #[test]
+    fn test_concentration() {
+        let validators = vec![
+            validator(40),
+            validator(30),
+            validator(20),
+            validator(10),
+            validator(0),
+        ];
+        let network_stakes = vec![
+            (validators[1].validator_account, 1_000),
+            (Pubkey::new_unique(), 900),
+            (validators[0].validator_account, 100),
+            (Pubkey::new_unique(), 800),
+        ];
+        let report = ValidatorReport::new(validators, network_stakes);
+
+        let concentration = report.concentration().unwrap();
+        assert_eq!(concentration.staked_validators, 4);
+        assert_eq!(concentration.total_stake, 100);
+        assert_eq!(concentration.nakamoto_coefficient, 1);
+        assert_eq!(concentration.top_10_share, Fee::from_basis_points(10_000));
+        assert_eq!(
+            report.top_n_share(2).unwrap(),
+            Fee::from_basis_points(7_000)
+        );
+        // 1000 is more than a third of the 2800 network stake
+        assert_eq!(report.superminority().len(), 1);
+        assert_eq!(concentration.superminority_validators, 1);
+        assert_eq!(
+            concentration.superminority_exposure,
+            Fee::from_basis_points(3_000)
+        );
+    }
+}
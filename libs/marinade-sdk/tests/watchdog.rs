@@ -0,0 +1,123 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use marinade_sdk::{
+        planner::{DelegatedStake, EpochPosition, UpdateAction},
+        state::{stake_system::StakeRecord, validator_system::ValidatorRecord},
+        watchdog::{recovery_instructions, CrankAlert, Watchdog, WatchdogConfig},
+    };
+    use solana_program::pubkey::Pubkey;
+
+    use crate::common::test_state;
+
+    const EPOCH: u64 = 10;
+
+    fn validators() -> Vec<ValidatorRecord> {
+        (0..2)
+            .map(|_| ValidatorRecord {
+                validator_account: Pubkey::new_unique(),
+                score: 100,
+                ..ValidatorRecord::default()
+            })
+            .collect()
+    }
+
+    fn stakes(validators: &[ValidatorRecord], last_update_epoch: u64) -> Vec<DelegatedStake> {
+        (0..6)
+            .map(|stake_index| DelegatedStake {
+                stake_index,
+                record: StakeRecord {
+                    stake_account: Pubkey::new_unique(),
+                    last_update_epoch,
+                    ..StakeRecord::default()
+                },
+                validator_vote: validators[stake_index as usize % 2].validator_account,
+            })
+            .collect()
+    }
+
+    fn position(slot_index: u64) -> EpochPosition {
+        EpochPosition {
+            epoch: EPOCH,
+            slot_index,
+            slots_in_epoch: 432_000,
+        }
+    }
+
+    #[test]
+    fn test_updates_are_overdue_after_the_window() {
+        let mut state = test_state().state;
+        state.stake_system.last_stake_delta_epoch = EPOCH - 1;
+        let validators = validators();
+        let stakes = stakes(&validators, EPOCH - 1);
+        let watchdog = Watchdog::default();
+        let window = WatchdogConfig::default().update_window_slots;
+
+        let early = watchdog.check(&state, &validators, &stakes, &position(window - 1), |_| {
+            false
+        });
+        assert!(early.is_ok());
+
+        let late = watchdog.check(&state, &validators, &stakes, &position(window), |_| false);
+        assert_eq!(
+            late.alerts,
+            vec![CrankAlert::UpdatesOverdue {
+                epoch: EPOCH,
+                slot_index: window,
+                stakes: 6
+            }]
+        );
+        assert!(late.recovery.is_empty());
+
+        let updated = self::stakes(&validators, EPOCH);
+        assert!(watchdog
+            .check(&state, &validators, &updated, &position(window), |_| false)
+            .is_ok());
+    }
+
+    #[test]
+    fn test_missed_stake_delta_is_reported_from_the_epoch_start() {
+        let mut state = test_state().state;
+        state.stake_system.last_stake_delta_epoch = EPOCH - 2;
+        let validators = validators();
+        let stakes = stakes(&validators, EPOCH);
+
+        let report =
+            Watchdog::default().check(&state, &validators, &stakes, &position(0), |_| false);
+        assert_eq!(report.alerts, vec![CrankAlert::StakeDeltaMissed(EPOCH - 2)]);
+    }
+
+    #[test]
+    fn test_self_heal_plans_the_overdue_updates() {
+        let test = test_state();
+        let mut state = test.state.clone();
+        state.stake_system.last_stake_delta_epoch = EPOCH - 1;
+        let validators = validators();
+        let mut stakes = stakes(&validators, EPOCH - 1);
+        stakes[0].record.last_update_epoch = EPOCH;
+        let watchdog = Watchdog::new(WatchdogConfig {
+            update_window_slots: 0,
+            self_heal: true,
+        });
+
+        let report = watchdog.check(&state, &validators, &stakes, &position(100), |stake| {
+            stake.stake_index == 1
+        });
+        assert_eq!(report.recovery.len(), 5);
+        assert_eq!(
+            report.recovery.last(),
+            Some(&UpdateAction::Deactivated { stake_index: 1 })
+        );
+
+        let transactions = recovery_instructions(&test, &stakes, &report.recovery, 2).unwrap();
+        assert_eq!(
+            transactions.iter().map(Vec::len).collect::<Vec<_>>(),
+            vec![2, 2, 1]
+        );
+        assert_eq!(
+            transactions[0][0].accounts[2].pubkey,
+            stakes[2].record.stake_account
+        );
+    }
+}
@@ -0,0 +1,105 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashMap, convert::Infallible};
+
+    use crate::common::test_state;
+    use marinade_sdk::{
+        state::ticket::TicketAccountData,
+        storage::MemoryStorage,
+        withdrawal_queue::{WithdrawalQueue, WithdrawalStatus},
+    };
+    use solana_program::{clock::Clock, pubkey::Pubkey};
+
+    fn clock(epoch: u64) -> Clock {
+        Clock {
+            epoch,
+            epoch_start_timestamp: 1_700_000_000,
+            unix_timestamp: 1_700_000_000 + 3_600,
+            ..Clock::default()
+        }
+    }
+
+    #[test]
+    fn test_request_lifecycle() {
+        let state = test_state();
+        let custodian = Pubkey::new_unique();
+        let mut queue = WithdrawalQueue::new(
+            state.key,
+            custodian,
+            Pubkey::new_unique(),
+            MemoryStorage::new(),
+        );
+        let alice = Pubkey::new_unique();
+        let bob = Pubkey::new_unique();
+        let (first, instructions) = queue
+            .request::<_, Infallible>(&state, alice, 1_000, 1_500_000)
+            .unwrap();
+        assert_eq!(instructions.len(), 2);
+        let (second, _) = queue
+            .request::<_, Infallible>(&state, bob, 2_000, 1_500_000)
+            .unwrap();
+        assert_ne!(first.ticket, second.ticket);
+        assert_eq!(first.status, WithdrawalStatus::Ordered);
+
+        // only alice's ticket landed
+        let mut chain: HashMap<Pubkey, u64> = HashMap::new();
+        chain.insert(first.ticket, 500);
+        let lookup = |chain: &HashMap<Pubkey, u64>| {
+            let chain = chain.clone();
+            move |ticket: &Pubkey| -> Result<Option<TicketAccountData>, Infallible> {
+                Ok(chain.get(ticket).map(|created_epoch| TicketAccountData {
+                    state_address: state.key,
+                    beneficiary: custodian,
+                    lamports_amount: 1_100,
+                    created_epoch: *created_epoch,
+                }))
+            }
+        };
+        queue.sync(lookup(&chain)).unwrap();
+        let alice_requests = queue.user_requests::<Infallible>(&alice).unwrap();
+        assert_eq!(
+            alice_requests[0].status,
+            WithdrawalStatus::Open {
+                lamports_amount: 1_100,
+                due_epoch: 501
+            }
+        );
+        assert_eq!(
+            queue.user_requests::<Infallible>(&bob).unwrap()[0].status,
+            WithdrawalStatus::Ordered
+        );
+
+        assert!(queue
+            .drain::<_, Infallible>(&state, &clock(500))
+            .unwrap()
+            .is_empty());
+        let claims = queue.drain::<_, Infallible>(&state, &clock(501)).unwrap();
+        assert_eq!(claims.len(), 1);
+        assert_eq!(claims[0].0.id, first.id);
+        // not drained twice while the claim is in flight
+        assert!(queue
+            .drain::<_, Infallible>(&state, &clock(501))
+            .unwrap()
+            .is_empty());
+
+        // the claim failed: the ticket is still there
+        queue.sync(lookup(&chain)).unwrap();
+        assert_eq!(
+            queue
+                .drain::<_, Infallible>(&state, &clock(502))
+                .unwrap()
+                .len(),
+            1
+        );
+        chain.remove(&first.ticket);
+        queue.sync(lookup(&chain)).unwrap();
+        assert_eq!(
+            queue.user_requests::<Infallible>(&alice).unwrap()[0].status,
+            WithdrawalStatus::Claimed {
+                lamports_amount: 1_100
+            }
+        );
+    }
+}
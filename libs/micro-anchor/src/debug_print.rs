@@ -0,0 +1,63 @@
+use std::fmt::Debug;
+
+use solana_program::instruction::AccountMeta;
+
+use crate::{InstructionBuilder, ToAccountMetas};
+
+/// Field names of an accounts struct in account list order,
+/// nested accounts structs as `field.nested_field`
+pub trait AccountNames {
+    fn account_names() -> Vec<String>;
+}
+
+fn flags(meta: &AccountMeta) -> &'static str {
+    match (meta.is_writable, meta.is_signer) {
+        (true, true) => "ws",
+        (true, false) => "w",
+        (false, true) => "s",
+        (false, false) => "r",
+    }
+}
+
+impl<A> InstructionBuilder<A, A::Data>
+where
+    A: ToAccountMetas + AccountNames,
+    A::Data: Debug,
+{
+    fn named_metas(&self) -> impl Iterator<Item = (String, AccountMeta)> {
+        A::account_names()
+            .into_iter()
+            .zip(self.accounts.to_account_metas())
+    }
+
+    /// Program, decoded data and one line per account with its name and
+    /// flags (w - writable, s - signer, r - read only)
+    pub fn pretty_print(&self) -> String {
+        let mut output = format!("program: {}\ndata: {:?}\naccounts:", A::owner(), self.data);
+        for (index, (name, meta)) in self.named_metas().enumerate() {
+            output.push_str(&format!(
+                "\n  {:>2} {:<2} {} {}",
+                index,
+                flags(&meta),
+                meta.pubkey,
+                name
+            ));
+        }
+        output
+    }
+
+    /// Single line version of pretty_print for log aggregation
+    pub fn compact_print(&self) -> String {
+        let accounts = self
+            .named_metas()
+            .map(|(name, meta)| format!("{}={}:{}", name, meta.pubkey, flags(&meta)))
+            .collect::<Vec<_>>()
+            .join(" ");
+        format!(
+            "program={} data={:?} accounts=[{}]",
+            A::owner(),
+            self.data,
+            accounts
+        )
+    }
+}
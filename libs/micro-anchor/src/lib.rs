@@ -8,6 +8,11 @@ use solana_program::{
     pubkey::Pubkey,
 };
 
+#[cfg(feature = "debug-print")]
+mod debug_print;
+#[cfg(feature = "debug-print")]
+pub use debug_print::AccountNames;
+
 /// 8 byte unique identifier for a type.
 pub trait Discriminator {
     const DISCRIMINATOR: [u8; 8];
@@ -79,11 +84,95 @@ pub trait ToAccountInfos<'info>: ToAccountMetas {
     fn to_account_infos(&self) -> Vec<AccountInfo<'info>>;
 }
 
+/// Accounts read back from the account keys of an instruction, in to_account_metas order.
+pub trait FromAccountKeys: Sized {
+    /// None when the keys run out before every account is set; remaining keys are left unread.
+    fn from_account_keys(keys: &mut std::slice::Iter<'_, Pubkey>) -> Option<Self>;
+}
+
 pub struct InstructionBuilder<A, D> {
     pub accounts: A,
     pub data: D,
 }
 
+/// Maximal size of serialized transaction (solana_sdk::packet::PACKET_DATA_SIZE)
+pub const PACKET_DATA_SIZE: usize = 1280 - 40 - 8;
+
+fn compact_len(len: usize) -> usize {
+    if len < 0x80 {
+        1
+    } else if len < 0x4000 {
+        2
+    } else {
+        3
+    }
+}
+
+/// Size in bytes of a legacy transaction containing the instructions.
+/// When no instruction has a signer, a separate fee payer signature is counted.
+pub fn legacy_transaction_size(instructions: &[Instruction]) -> usize {
+    let mut keys: Vec<Pubkey> = Vec::new();
+    let mut signers: Vec<Pubkey> = Vec::new();
+    for instruction in instructions {
+        for meta in &instruction.accounts {
+            if !keys.contains(&meta.pubkey) {
+                keys.push(meta.pubkey);
+            }
+            if meta.is_signer && !signers.contains(&meta.pubkey) {
+                signers.push(meta.pubkey);
+            }
+        }
+        if !keys.contains(&instruction.program_id) {
+            keys.push(instruction.program_id);
+        }
+    }
+    let (signatures, key_count) = if signers.is_empty() {
+        (1, keys.len() + 1)
+    } else {
+        (signers.len(), keys.len())
+    };
+    let instructions_size: usize = instructions
+        .iter()
+        .map(|ix| {
+            1 + compact_len(ix.accounts.len())
+                + ix.accounts.len()
+                + compact_len(ix.data.len())
+                + ix.data.len()
+        })
+        .sum();
+    compact_len(signatures)
+        + signatures * 64
+        + 3 // message header
+        + compact_len(key_count)
+        + key_count * 32
+        + 32 // recent blockhash
+        + compact_len(instructions.len())
+        + instructions_size
+}
+
+/// Whether the instruction fits into a legacy transaction together with extra_bytes of other content
+pub fn fits_in_legacy_tx_with(instruction: &Instruction, extra_bytes: usize) -> bool {
+    legacy_transaction_size(std::slice::from_ref(instruction)) + extra_bytes <= PACKET_DATA_SIZE
+}
+
+impl<A> InstructionBuilder<A, A::Data>
+where
+    A: ToAccountMetas,
+{
+    pub fn fits_in_legacy_tx_with(&self, extra_bytes: usize) -> bool {
+        fits_in_legacy_tx_with(&self.into(), extra_bytes)
+    }
+
+    /// Instruction of a deployment at program_id, e.g. a fork, instead of A::owner()
+    pub fn instruction(&self, program_id: Pubkey) -> Instruction {
+        Instruction::new_with_bytes(
+            program_id,
+            &self.data.data(),
+            self.accounts.to_account_metas(),
+        )
+    }
+}
+
 impl<'info, A> InstructionBuilder<A, A::Data>
 where
     A: ToAccountInfos<'info>,
@@ -105,14 +194,8 @@ impl<A> From<&InstructionBuilder<A, A::Data>> for Instruction
 where
     A: ToAccountMetas,
 {
-    fn from(
-        InstructionBuilder {
-            accounts: account_infos,
-            data,
-            ..
-        }: &InstructionBuilder<A, A::Data>,
-    ) -> Self {
-        Instruction::new_with_bytes(A::owner(), &data.data(), account_infos.to_account_metas())
+    fn from(builder: &InstructionBuilder<A, A::Data>) -> Self {
+        builder.instruction(A::owner())
     }
 }
 
@@ -0,0 +1,128 @@
+//! Anchor program calling Marinade deposit and liquid_unstake through the SDK account infos
+//! and `InstructionBuilder::invoke`, so the on-chain part of the SDK is exercised inside BPF.
+//! Build with `cargo build-sbf` and test with `cargo test-sbf`; the tests need the Marinade
+//! program binary at libs/marinade-sdk/tests/fixtures/marinade_finance.so.
+
+use anchor_lang::prelude::*;
+use marinade_sdk::instructions::{
+    deposit::{DepositAccountInfos, DepositData},
+    liquid_unstake::{LiquidUnstakeAccountInfos, LiquidUnstakeData},
+};
+use micro_anchor::InstructionBuilder;
+
+declare_id!("a9bhUp8rwfjZTCoFsh7YEypqwbHqsNa6U3dYWRe2Kjq");
+
+#[program]
+pub mod marinade_cpi_example {
+    use super::*;
+
+    pub fn deposit(ctx: Context<Deposit>, lamports: u64) -> Result<()> {
+        let accounts = &ctx.accounts;
+        InstructionBuilder {
+            accounts: DepositAccountInfos {
+                marinade: accounts.marinade.to_account_info(),
+                msol_mint: accounts.msol_mint.to_account_info(),
+                liq_pool_sol_leg_pda: accounts.liq_pool_sol_leg_pda.to_account_info(),
+                liq_pool_msol_leg: accounts.liq_pool_msol_leg.to_account_info(),
+                liq_pool_msol_leg_authority: accounts.liq_pool_msol_leg_authority.to_account_info(),
+                reserve_pda: accounts.reserve_pda.to_account_info(),
+                transfer_from: accounts.transfer_from.to_account_info(),
+                mint_to: accounts.mint_to.to_account_info(),
+                msol_mint_authority: accounts.msol_mint_authority.to_account_info(),
+                system_program: accounts.system_program.to_account_info(),
+                token_program: accounts.token_program.to_account_info(),
+            },
+            data: DepositData::new(lamports),
+        }
+        .invoke()?;
+        Ok(())
+    }
+
+    pub fn liquid_unstake(ctx: Context<LiquidUnstake>, msol_amount: u64) -> Result<()> {
+        let accounts = &ctx.accounts;
+        InstructionBuilder {
+            accounts: LiquidUnstakeAccountInfos {
+                marinade: accounts.marinade.to_account_info(),
+                msol_mint: accounts.msol_mint.to_account_info(),
+                liq_pool_sol_leg_pda: accounts.liq_pool_sol_leg_pda.to_account_info(),
+                liq_pool_msol_leg: accounts.liq_pool_msol_leg.to_account_info(),
+                treasury_msol_account: accounts.treasury_msol_account.to_account_info(),
+                get_msol_from: accounts.get_msol_from.to_account_info(),
+                get_msol_from_authority: accounts.get_msol_from_authority.to_account_info(),
+                transfer_sol_to: accounts.transfer_sol_to.to_account_info(),
+                system_program: accounts.system_program.to_account_info(),
+                token_program: accounts.token_program.to_account_info(),
+            },
+            data: LiquidUnstakeData::new(msol_amount),
+        }
+        .invoke()?;
+        Ok(())
+    }
+}
+
+/// Marinade checks every account, they are passed through unchecked
+#[derive(Accounts)]
+pub struct Deposit<'info> {
+    /// CHECK: Marinade state
+    #[account(mut)]
+    pub marinade: UncheckedAccount<'info>,
+    /// CHECK: checked by Marinade
+    #[account(mut)]
+    pub msol_mint: UncheckedAccount<'info>,
+    /// CHECK: checked by Marinade
+    #[account(mut)]
+    pub liq_pool_sol_leg_pda: UncheckedAccount<'info>,
+    /// CHECK: checked by Marinade
+    #[account(mut)]
+    pub liq_pool_msol_leg: UncheckedAccount<'info>,
+    /// CHECK: checked by Marinade
+    pub liq_pool_msol_leg_authority: UncheckedAccount<'info>,
+    /// CHECK: checked by Marinade
+    #[account(mut)]
+    pub reserve_pda: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub transfer_from: Signer<'info>,
+    /// CHECK: checked by Marinade
+    #[account(mut)]
+    pub mint_to: UncheckedAccount<'info>,
+    /// CHECK: checked by Marinade
+    pub msol_mint_authority: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+    /// CHECK: checked by Marinade
+    pub token_program: UncheckedAccount<'info>,
+    /// CHECK: the invoked program
+    #[account(address = marinade_sdk::ID)]
+    pub marinade_program: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct LiquidUnstake<'info> {
+    /// CHECK: Marinade state
+    #[account(mut)]
+    pub marinade: UncheckedAccount<'info>,
+    /// CHECK: checked by Marinade
+    #[account(mut)]
+    pub msol_mint: UncheckedAccount<'info>,
+    /// CHECK: checked by Marinade
+    #[account(mut)]
+    pub liq_pool_sol_leg_pda: UncheckedAccount<'info>,
+    /// CHECK: checked by Marinade
+    #[account(mut)]
+    pub liq_pool_msol_leg: UncheckedAccount<'info>,
+    /// CHECK: checked by Marinade
+    #[account(mut)]
+    pub treasury_msol_account: UncheckedAccount<'info>,
+    /// CHECK: checked by Marinade
+    #[account(mut)]
+    pub get_msol_from: UncheckedAccount<'info>,
+    pub get_msol_from_authority: Signer<'info>,
+    /// CHECK: checked by Marinade
+    #[account(mut)]
+    pub transfer_sol_to: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+    /// CHECK: checked by Marinade
+    pub token_program: UncheckedAccount<'info>,
+    /// CHECK: the invoked program
+    #[account(address = marinade_sdk::ID)]
+    pub marinade_program: UncheckedAccount<'info>,
+}
@@ -0,0 +1,214 @@
+//! Deposit and liquid unstake through the example program against the real Marinade program.
+//! They need the Marinade binary of the SDK test fixtures, are ignored by default and fail
+//! when it is missing: `cargo test --test cpi -- --ignored`.
+
+use std::{fs, path::Path};
+
+use anchor_lang::{InstructionData, ToAccountMetas};
+use marinade_cpi_example::{accounts, instruction};
+use marinade_sdk::{
+    quote::liquid_unstake_quote,
+    state::{fee::Fee, liq_pool::LiqPoolHelpers, marinade::MarinadeHelpers},
+    testing::{mint_account, state_account, system_account, test_state, token_account, TestState},
+    ID as MARINADE_ID,
+};
+use solana_program_test::{processor, BanksClient, ProgramTest};
+use solana_sdk::{
+    account::Account,
+    bpf_loader,
+    instruction::Instruction,
+    program_pack::Pack,
+    pubkey::Pubkey,
+    rent::Rent,
+    signature::{Keypair, Signer},
+    system_program,
+    transaction::Transaction,
+};
+
+const MARINADE_PROGRAM: &str = "../../libs/marinade-sdk/tests/fixtures/marinade_finance.so";
+const POOL_LAMPORTS: u64 = 1_000_000_000_000;
+const USER_LAMPORTS: u64 = 1_000_000_000_000;
+
+fn state() -> TestState {
+    let mut state = test_state();
+    let liq_pool = &mut state.state.liq_pool;
+    liq_pool.lp_min_fee = Fee::from_basis_points(30);
+    liq_pool.lp_max_fee = Fee::from_basis_points(300);
+    liq_pool.lp_liquidity_target = 10_000_000_000_000;
+    liq_pool.treasury_cut = Fee::from_basis_points(2500);
+    liq_pool.liquidity_sol_cap = u64::MAX;
+    let marinade = &mut state.state;
+    marinade.rent_exempt_for_token_acc =
+        Rent::default().minimum_balance(spl_token::state::Account::LEN);
+    // everything sits in the reserve so no validator or stake accounts are needed
+    marinade.available_reserve_balance = POOL_LAMPORTS;
+    marinade.msol_supply = POOL_LAMPORTS;
+    marinade.min_deposit = 1;
+    marinade.min_withdraw = 1;
+    marinade.staking_sol_cap = u64::MAX;
+    state
+}
+
+/// Both programs, the state with its PDAs and a user holding msol_amount
+fn program_test(
+    state: &TestState,
+    user: &Pubkey,
+    user_msol: &Pubkey,
+    msol_amount: u64,
+) -> ProgramTest {
+    assert!(
+        Path::new(MARINADE_PROGRAM).exists(),
+        "{} not found, build it from the marinade program release",
+        MARINADE_PROGRAM
+    );
+    let mut program_test = ProgramTest::new(
+        "marinade_cpi_example",
+        marinade_cpi_example::ID,
+        processor!(marinade_cpi_example::entry),
+    );
+    program_test.add_account(
+        MARINADE_ID,
+        Account {
+            lamports: Rent::default().minimum_balance(0),
+            data: fs::read(MARINADE_PROGRAM).unwrap(),
+            owner: bpf_loader::ID,
+            executable: true,
+            rent_epoch: 0,
+        },
+    );
+    let marinade = &state.state;
+    let rent_exempt = marinade.rent_exempt_for_token_acc;
+    program_test.add_account(state.key, state_account(marinade));
+    program_test.add_account(
+        marinade.msol_mint,
+        mint_account(
+            state.try_msol_mint_authority().unwrap(),
+            marinade.msol_supply,
+        ),
+    );
+    program_test.add_account(
+        state.try_reserve_address().unwrap(),
+        system_account(marinade.available_reserve_balance + rent_exempt),
+    );
+    program_test.add_account(
+        state.try_liq_pool_sol_leg_address().unwrap(),
+        system_account(POOL_LAMPORTS + rent_exempt),
+    );
+    program_test.add_account(
+        marinade.liq_pool.msol_leg,
+        token_account(
+            marinade.msol_mint,
+            state.try_liq_pool_msol_leg_authority().unwrap(),
+            0,
+        ),
+    );
+    program_test.add_account(
+        marinade.treasury_msol_account,
+        token_account(marinade.msol_mint, Pubkey::new_unique(), 0),
+    );
+    program_test.add_account(*user, system_account(USER_LAMPORTS));
+    program_test.add_account(
+        *user_msol,
+        token_account(marinade.msol_mint, *user, msol_amount),
+    );
+    program_test
+}
+
+async fn send(
+    banks_client: &mut BanksClient,
+    instruction: Instruction,
+    payer: &Keypair,
+    user: &Keypair,
+) {
+    let recent_blockhash = banks_client.get_latest_blockhash().await.unwrap();
+    let transaction = Transaction::new_signed_with_payer(
+        &[instruction],
+        Some(&payer.pubkey()),
+        &[payer, user],
+        recent_blockhash,
+    );
+    banks_client.process_transaction(transaction).await.unwrap();
+}
+
+async fn token_balance(banks_client: &mut BanksClient, address: Pubkey) -> u64 {
+    let account = banks_client.get_account(address).await.unwrap().unwrap();
+    spl_token::state::Account::unpack(&account.data)
+        .unwrap()
+        .amount
+}
+
+#[tokio::test]
+#[ignore = "needs the Marinade binary of the SDK test fixtures"]
+async fn test_deposit_through_cpi() {
+    let state = state();
+    let user = Keypair::new();
+    let user_msol = Pubkey::new_unique();
+    let (mut banks_client, payer, _) = program_test(&state, &user.pubkey(), &user_msol, 0)
+        .start()
+        .await;
+    let lamports = 1_000_000_000;
+    let instruction = Instruction {
+        program_id: marinade_cpi_example::ID,
+        accounts: accounts::Deposit {
+            marinade: state.key,
+            msol_mint: state.state.msol_mint,
+            liq_pool_sol_leg_pda: state.try_liq_pool_sol_leg_address().unwrap(),
+            liq_pool_msol_leg: state.state.liq_pool.msol_leg,
+            liq_pool_msol_leg_authority: state.try_liq_pool_msol_leg_authority().unwrap(),
+            reserve_pda: state.try_reserve_address().unwrap(),
+            transfer_from: user.pubkey(),
+            mint_to: user_msol,
+            msol_mint_authority: state.try_msol_mint_authority().unwrap(),
+            system_program: system_program::ID,
+            token_program: spl_token::ID,
+            marinade_program: MARINADE_ID,
+        }
+        .to_account_metas(None),
+        data: instruction::Deposit { lamports }.data(),
+    };
+    send(&mut banks_client, instruction, &payer, &user).await;
+
+    assert_eq!(
+        token_balance(&mut banks_client, user_msol).await,
+        state.state.calc_msol_from_lamports(lamports).unwrap()
+    );
+}
+
+#[tokio::test]
+#[ignore = "needs the Marinade binary of the SDK test fixtures"]
+async fn test_liquid_unstake_through_cpi() {
+    let state = state();
+    let user = Keypair::new();
+    let user_msol = Pubkey::new_unique();
+    let msol_amount = 1_000_000_000;
+    let (mut banks_client, payer, _) =
+        program_test(&state, &user.pubkey(), &user_msol, msol_amount)
+            .start()
+            .await;
+    let transfer_sol_to = Pubkey::new_unique();
+    let instruction = Instruction {
+        program_id: marinade_cpi_example::ID,
+        accounts: accounts::LiquidUnstake {
+            marinade: state.key,
+            msol_mint: state.state.msol_mint,
+            liq_pool_sol_leg_pda: state.try_liq_pool_sol_leg_address().unwrap(),
+            liq_pool_msol_leg: state.state.liq_pool.msol_leg,
+            treasury_msol_account: state.state.treasury_msol_account,
+            get_msol_from: user_msol,
+            get_msol_from_authority: user.pubkey(),
+            transfer_sol_to,
+            system_program: system_program::ID,
+            token_program: spl_token::ID,
+            marinade_program: MARINADE_ID,
+        }
+        .to_account_metas(None),
+        data: instruction::LiquidUnstake { msol_amount }.data(),
+    };
+    send(&mut banks_client, instruction, &payer, &user).await;
+
+    let quote = liquid_unstake_quote(&state.state, POOL_LAMPORTS, msol_amount).unwrap();
+    assert_eq!(
+        banks_client.get_balance(transfer_sol_to).await.unwrap(),
+        quote.lamports
+    );
+}
@@ -0,0 +1,260 @@
+//! Workspace tasks, run with `cargo xtask <task>` (alias in .cargo/config.toml).
+//!
+//! feature-matrix builds and tests marinade-sdk in the feature combinations users depend on
+//! and reports the ones that break. With --each-feature every feature of the package (from
+//! cargo metadata) is also checked alone, which catches a module using a dependency its
+//! feature does not enable.
+
+use std::{
+    env,
+    path::{Path, PathBuf},
+    process::{exit, Command},
+};
+
+use serde_json::Value;
+
+const PACKAGE: &str = "marinade-sdk";
+
+const USAGE: &str =
+    "usage: cargo xtask feature-matrix [--build-only] [--each-feature] [--only <name>]";
+
+#[derive(Clone, Debug)]
+struct Combination {
+    name: String,
+    features: Vec<String>,
+    target: Option<&'static str>,
+    /// Run the tests too, the test files and doc-tests of disabled features are skipped
+    test: bool,
+}
+
+impl Combination {
+    fn new(name: &str, features: &[&str], target: Option<&'static str>, test: bool) -> Self {
+        Self {
+            name: name.to_string(),
+            features: features.iter().map(|feature| feature.to_string()).collect(),
+            target,
+            test,
+        }
+    }
+}
+
+fn combinations() -> Vec<Combination> {
+    vec![
+        Combination::new("onchain-only", &[], None, false),
+        Combination::new(
+            "client+rpc",
+            &[
+                "rpc",
+                "async",
+                "batch",
+                "tx",
+                "history",
+                "replay",
+                "parse",
+                "events",
+                "rate-limit",
+                "test-utils",
+            ],
+            None,
+            true,
+        ),
+        Combination::new("cli", &["cli"], None, false),
+        // the default modules the wasm bindings (marinade-sdk-wasm) are built on
+        Combination::new("wasm", &[], Some("wasm32-unknown-unknown"), false),
+        Combination::new("anchor", &["anchor"], None, true),
+        Combination::new("serde", &["serde"], None, true),
+    ]
+}
+
+/// Features of the package in `cargo metadata` output, the implicit features of optional
+/// dependencies (e.g. serde) included
+fn package_features(metadata: &Value) -> Vec<String> {
+    let package = metadata["packages"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .find(|package| package["name"] == PACKAGE)
+        .unwrap_or_else(|| panic!("{} is not in the workspace metadata", PACKAGE));
+    package["features"]
+        .as_object()
+        .into_iter()
+        .flatten()
+        .map(|(feature, _)| feature.clone())
+        .filter(|feature| feature != "default")
+        .collect()
+}
+
+fn each_feature(root: &Path) -> Vec<Combination> {
+    let output = Command::new(cargo_bin())
+        .current_dir(root)
+        .args(["metadata", "--no-deps", "--format-version", "1"])
+        .output()
+        .unwrap_or_else(|err| panic!("cannot run cargo metadata: {}", err));
+    if !output.status.success() {
+        panic!(
+            "cargo metadata failed: {}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+    }
+    let metadata: Value = serde_json::from_slice(&output.stdout)
+        .unwrap_or_else(|err| panic!("invalid cargo metadata output: {}", err));
+    package_features(&metadata)
+        .iter()
+        .map(|feature| Combination::new(&format!("only {}", feature), &[feature], None, false))
+        .collect()
+}
+
+fn cargo_bin() -> String {
+    env::var("CARGO").unwrap_or_else(|_| "cargo".to_string())
+}
+
+struct Outcome {
+    combination: String,
+    step: &'static str,
+    /// Tail of the cargo output, None when the step passed
+    failure: Option<String>,
+}
+
+fn cargo(root: &Path, step: &'static str, combination: &Combination) -> Outcome {
+    let mut command = Command::new(cargo_bin());
+    command
+        .current_dir(root)
+        .args([step, "--package", PACKAGE, "--no-default-features"]);
+    if !combination.features.is_empty() {
+        command.args(["--features", &combination.features.join(",")]);
+    }
+    match combination.target {
+        // only the library is built for wasm, the binaries and tests are std only
+        Some(target) => command.args(["--lib", "--target", target]),
+        // the test targets are built by the test step
+        None if step != "test" => command.args(["--lib", "--bins"]),
+        None => &mut command,
+    };
+    if step == "test" {
+        command.args(["--", "--quiet"]);
+    }
+    eprintln!("[{}] cargo {}", combination.name, step);
+    let failure = match command.output() {
+        Ok(output) if output.status.success() => None,
+        Ok(output) => {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            let stdout = String::from_utf8_lossy(&output.stdout);
+            let lines: Vec<&str> = stderr.lines().chain(stdout.lines()).collect();
+            Some(lines[lines.len().saturating_sub(30)..].join("\n"))
+        }
+        Err(err) => Some(format!("cannot run cargo: {}", err)),
+    };
+    Outcome {
+        combination: combination.name.clone(),
+        step,
+        failure,
+    }
+}
+
+fn feature_matrix(root: &Path, args: &[String]) -> bool {
+    let mut build_only = false;
+    let mut matrix = combinations();
+    let mut only = None;
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--build-only" => build_only = true,
+            "--each-feature" => matrix.extend(each_feature(root)),
+            "--only" => only = args.next().cloned(),
+            _ => {
+                eprintln!("{}", USAGE);
+                exit(2);
+            }
+        }
+    }
+    if let Some(only) = &only {
+        matrix.retain(|combination| combination.name == *only);
+        if matrix.is_empty() {
+            eprintln!("no combination named {}", only);
+            exit(2);
+        }
+    }
+
+    let mut outcomes = Vec::new();
+    for combination in &matrix {
+        // each-feature entries only need to compile
+        let step = if combination.name.starts_with("only ") {
+            "check"
+        } else {
+            "build"
+        };
+        let built = cargo(root, step, combination);
+        let passed = built.failure.is_none();
+        outcomes.push(built);
+        if passed && combination.test && !build_only {
+            outcomes.push(cargo(root, "test", combination));
+        }
+    }
+
+    println!();
+    println!("{:<28} {:<6} result", "combination", "step");
+    for outcome in &outcomes {
+        let result = if outcome.failure.is_some() {
+            "FAILED"
+        } else {
+            "ok"
+        };
+        println!("{:<28} {:<6} {}", outcome.combination, outcome.step, result);
+    }
+    let failed: Vec<&Outcome> = outcomes
+        .iter()
+        .filter(|outcome| outcome.failure.is_some())
+        .collect();
+    for outcome in &failed {
+        println!();
+        println!("--- {} ({}) ---", outcome.combination, outcome.step);
+        println!("{}", outcome.failure.as_deref().unwrap_or_default());
+    }
+    if !failed.is_empty() {
+        println!();
+        println!("{} of {} steps failed", failed.len(), outcomes.len());
+    }
+    failed.is_empty()
+}
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let root: PathBuf = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .parent()
+        .expect("xtask is in the workspace root")
+        .to_path_buf();
+    let ok = match args.first().map(String::as_str) {
+        Some("feature-matrix") => feature_matrix(&root, &args[1..]),
+        _ => {
+            eprintln!("{}", USAGE);
+            exit(2);
+        }
+    };
+    if !ok {
+        exit(1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::package_features;
+    use serde_json::json;
+
+    #[test]
+    fn test_package_features() {
+        let metadata = json!({
+            "packages": [
+                { "name": "micro-anchor", "features": { "debug-print": [] } },
+                {
+                    "name": "marinade-sdk",
+                    "features": {
+                        "rpc": ["solana-client", "solana-sdk"],
+                        "serde": ["dep:serde"],
+                        "wasm": ["wasm-bindgen"],
+                    },
+                },
+            ],
+        });
+        assert_eq!(package_features(&metadata), ["rpc", "serde", "wasm"]);
+    }
+}